@@ -0,0 +1,199 @@
+//! Agent stderr capture
+//!
+//! Agents write their own diagnostics (stack traces, auth errors, rate
+//! limit warnings) to stderr. `AcpClient::spawn` pipes it here instead of
+//! the app's stderr: each line is redacted, kept in a per-worker buffer,
+//! emitted as `worker-agent-log` and persisted with the session. The tail
+//! is appended to the error when the worker fails.
+
+use crate::acp::events::EventSink;
+use crate::acp::session_store::SessionStore;
+use crate::events::WorkerAgentLogEvent;
+use crate::secrets::redact::redactor_for_worker;
+use crate::time::now_ms;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+
+/// Lines kept per worker (the oldest are dropped)
+const MAX_LINES_PER_WORKER: usize = 500;
+
+/// Characters kept of a single line
+const MAX_LINE_CHARS: usize = 2_000;
+
+/// Lines of stderr added to a worker failure message
+pub const FAILURE_TAIL_LINES: usize = 20;
+
+/// Global registry of agent stderr (session_id -> lines in order)
+static AGENT_LOGS: Lazy<Mutex<HashMap<String, Vec<AgentLogLine>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// One line an agent wrote to stderr
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AgentLogLine {
+    pub worker_id: String,
+    pub line: String,
+    /// Unix timestamp (milliseconds)
+    pub timestamp: i64,
+}
+
+/// Read an agent's stderr until it closes, recording every line
+pub fn capture<R>(stderr: R, events: EventSink, session_id: String, worker_id: String)
+where
+    R: AsyncRead + Unpin + 'static,
+{
+    tokio::task::spawn_local(async move {
+        let mut lines = BufReader::new(stderr).lines();
+        loop {
+            match lines.next_line().await {
+                Ok(Some(line)) => record(&events, &session_id, &worker_id, &line),
+                Ok(None) => break,
+                Err(e) => {
+                    eprintln!("[ACP] Failed to read agent stderr: {}", e);
+                    break;
+                }
+            }
+        }
+    });
+}
+
+/// Add a stderr line to the worker's buffer and emit it as `worker-agent-log`
+pub fn record(events: &EventSink, session_id: &str, worker_id: &str, line: &str) {
+    let line = line.trim_end();
+    if line.is_empty() {
+        return;
+    }
+    let line: String = line.chars().take(MAX_LINE_CHARS).collect();
+    let line = redactor_for_worker(worker_id).redact(&line);
+    eprintln!("[Agent {}] {}", worker_id, line);
+
+    let entry = AgentLogLine {
+        worker_id: worker_id.to_string(),
+        line: line.clone(),
+        timestamp: now_ms(),
+    };
+    push(session_id, entry);
+
+    events.send(&WorkerAgentLogEvent {
+        session_id: session_id.to_string(),
+        worker_id: worker_id.to_string(),
+        line,
+    });
+}
+
+fn push(session_id: &str, entry: AgentLogLine) {
+    let mut logs = AGENT_LOGS.lock();
+    let log = logs.entry(session_id.to_string()).or_default();
+    let count = log.iter().filter(|l| l.worker_id == entry.worker_id).count();
+    if count >= MAX_LINES_PER_WORKER {
+        if let Some(oldest) = log.iter().position(|l| l.worker_id == entry.worker_id) {
+            log.remove(oldest);
+        }
+    }
+    log.push(entry);
+}
+
+/// The last `lines` stderr lines of a worker, oldest first
+pub fn tail(session_id: &str, worker_id: &str, lines: usize) -> Vec<String> {
+    let logs = AGENT_LOGS.lock();
+    let Some(log) = logs.get(session_id) else {
+        return Vec::new();
+    };
+    let mut tail: Vec<String> = log
+        .iter()
+        .rev()
+        .filter(|l| l.worker_id == worker_id)
+        .take(lines)
+        .map(|l| l.line.clone())
+        .collect();
+    tail.reverse();
+    tail
+}
+
+/// `error` followed by the tail of the worker's stderr, if it wrote any
+pub fn with_stderr_tail(session_id: &str, worker_id: &str, error: String) -> String {
+    let tail = tail(session_id, worker_id, FAILURE_TAIL_LINES);
+    if tail.is_empty() {
+        return error;
+    }
+    format!("{}\n\nAgent stderr:\n{}", error, tail.join("\n"))
+}
+
+/// Get a session's agent stderr, oldest first
+pub fn get_agent_log(session_id: &str) -> Option<Vec<AgentLogLine>> {
+    AGENT_LOGS.lock().get(session_id).cloned()
+}
+
+/// Drop a session's buffered stderr
+pub fn clear_agent_log(session_id: &str) {
+    AGENT_LOGS.lock().remove(session_id);
+}
+
+/// Get the agent stderr of a session (live, or the persisted copy)
+#[tauri::command]
+pub fn get_session_agent_log(session_id: String) -> Result<Vec<AgentLogLine>, String> {
+    if let Some(lines) = get_agent_log(&session_id) {
+        return Ok(lines);
+    }
+
+    let store = SessionStore::new()?;
+    if !store.session_exists(&session_id) {
+        return Ok(Vec::new());
+    }
+    Ok(store.load_session(&session_id)?.agent_log)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(worker_id: &str, line: &str) -> AgentLogLine {
+        AgentLogLine {
+            worker_id: worker_id.to_string(),
+            line: line.to_string(),
+            timestamp: 0,
+        }
+    }
+
+    #[test]
+    fn test_buffer_is_capped_per_worker() {
+        let session = "agent-log-test-cap";
+        push(session, line("other", "kept"));
+        for i in 0..MAX_LINES_PER_WORKER + 5 {
+            push(session, line("w1", &i.to_string()));
+        }
+
+        let log = get_agent_log(session).unwrap();
+        assert_eq!(log.len(), MAX_LINES_PER_WORKER + 1);
+        assert_eq!(log[0].line, "kept");
+        assert_eq!(log[1].line, "5");
+        assert_eq!(
+            tail(session, "w1", 2),
+            vec![
+                (MAX_LINES_PER_WORKER + 3).to_string(),
+                (MAX_LINES_PER_WORKER + 4).to_string()
+            ]
+        );
+        clear_agent_log(session);
+        assert!(get_agent_log(session).is_none());
+    }
+
+    #[test]
+    fn test_failure_message_includes_tail() {
+        let session = "agent-log-test-failure";
+        assert_eq!(
+            with_stderr_tail(session, "w1", "exited".to_string()),
+            "exited"
+        );
+
+        push(session, line("w1", "Error: invalid API key"));
+        push(session, line("w2", "unrelated"));
+        assert_eq!(
+            with_stderr_tail(session, "w1", "exited".to_string()),
+            "exited\n\nAgent stderr:\nError: invalid API key"
+        );
+        clear_agent_log(session);
+    }
+}
@@ -0,0 +1,392 @@
+//! Files dropped onto the composer
+//!
+//! `ingest_attachment` turns a dropped file into something a prompt can
+//! carry, by type:
+//!
+//! - images are scaled down to `MAX_IMAGE_EDGE` and returned as an
+//!   `ImageAttachment` for `send_acp_prompt_with_images`
+//! - PDFs and Word documents have their text extracted
+//! - CSV/TSV tables are cut to a preview of their first rows
+//! - other text files (code, mostly) are sent as they are
+//!
+//! Everything but images is staged (see `staged_context`), so it goes out
+//! with the session's next prompt.
+//!
+//! Pasted images take the same path through `prepare_clipboard_image`: the
+//! webview can't tell a TIFF from a PNG by its bytes, or scale either.
+
+use crate::acp::commands::ImageAttachment;
+use crate::acp::staged_context::{stage_context, ContextItem};
+use crate::error::CommandError;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use image::imageops::FilterType;
+use image::{DynamicImage, GenericImageView, ImageFormat};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+use std::fs;
+use std::io::{BufRead, BufReader, Cursor, Read};
+use std::path::Path;
+
+/// Larger files are refused
+const MAX_ATTACHMENT_BYTES: u64 = 50 * 1024 * 1024;
+
+/// Longest image edge sent to the agent; larger images are scaled down
+const MAX_IMAGE_EDGE: u32 = 1568;
+
+/// Largest encoded image sent to the agent
+const MAX_IMAGE_BYTES: usize = 5 * 1024 * 1024;
+
+/// Largest pasted image accepted (before scaling)
+const MAX_PASTED_IMAGE_BYTES: usize = 30 * 1024 * 1024;
+
+/// Most text kept from a document
+const MAX_DOCUMENT_CHARS: usize = 100 * 1024;
+
+/// Rows of a table kept in its preview (after the header)
+const MAX_TABLE_ROWS: usize = 50;
+
+/// Bytes sniffed for NULs to tell binary files apart
+const SNIFF_BYTES: usize = 8000;
+
+/// Text runs, paragraph ends, tabs and line breaks in a Word document
+static DOCX_TOKEN_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"<w:t(?:\s[^>]*)?>([^<]*)</w:t>|</w:p>|<w:tab/>|<w:br/>").unwrap()
+});
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AttachmentKind {
+    Image,
+    Document,
+    Table,
+    Code,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Attachment {
+    pub kind: AttachmentKind,
+    /// File name, for the composer's attachment chip
+    pub name: String,
+    pub path: String,
+    /// Images: send with `send_acp_prompt_with_images`
+    pub image: Option<ImageAttachment>,
+    /// Everything else: what was staged for the next prompt (pass it to
+    /// `unstage_context` to drop the attachment)
+    pub context: Option<ContextItem>,
+    /// Only part of the file was kept (a long document, a large table)
+    pub truncated: bool,
+}
+
+fn kind_of(path: &Path) -> Option<AttachmentKind> {
+    let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+    match ext.as_str() {
+        "png" | "jpg" | "jpeg" | "gif" | "webp" | "bmp" | "tif" | "tiff" => {
+            Some(AttachmentKind::Image)
+        }
+        "pdf" | "docx" => Some(AttachmentKind::Document),
+        "csv" | "tsv" => Some(AttachmentKind::Table),
+        _ => None,
+    }
+}
+
+/// MIME type agents accept for an image format, if they accept it as is
+fn image_mime(format: ImageFormat) -> Option<&'static str> {
+    match format {
+        ImageFormat::Png => Some("image/png"),
+        ImageFormat::Jpeg => Some("image/jpeg"),
+        ImageFormat::Gif => Some("image/gif"),
+        ImageFormat::WebP => Some("image/webp"),
+        _ => None,
+    }
+}
+
+fn encode(img: &DynamicImage, format: ImageFormat) -> Result<Vec<u8>, String> {
+    let mut out = Cursor::new(Vec::new());
+    let written = match format {
+        ImageFormat::Jpeg => DynamicImage::ImageRgb8(img.to_rgb8()).write_to(&mut out, format),
+        _ => img.write_to(&mut out, format),
+    };
+    written.map_err(|e| format!("Failed to encode image: {}", e))?;
+    Ok(out.into_inner())
+}
+
+/// An image no larger than `MAX_IMAGE_EDGE` on either side or
+/// `MAX_IMAGE_BYTES` encoded, in a format agents accept. The format is
+/// sniffed from the bytes. Images that already fit go out untouched;
+/// others are scaled and re-encoded as PNG (JPEGs, and PNGs still too
+/// large, as JPEG).
+pub fn prepare_image(bytes: &[u8]) -> Result<ImageAttachment, String> {
+    let format = image::guess_format(bytes).map_err(|_| "Not a supported image".to_string())?;
+    let img = image::load_from_memory_with_format(bytes, format)
+        .map_err(|e| format!("Failed to decode image: {}", e))?;
+    let (width, height) = img.dimensions();
+    let fits = width.max(height) <= MAX_IMAGE_EDGE;
+    if let (true, true, Some(mime)) = (fits, bytes.len() <= MAX_IMAGE_BYTES, image_mime(format)) {
+        return Ok(ImageAttachment {
+            data: BASE64.encode(bytes),
+            mime_type: mime.to_string(),
+        });
+    }
+
+    let img = if fits {
+        img
+    } else {
+        img.resize(MAX_IMAGE_EDGE, MAX_IMAGE_EDGE, FilterType::Lanczos3)
+    };
+    let mut format = match format {
+        ImageFormat::Jpeg => ImageFormat::Jpeg,
+        _ => ImageFormat::Png,
+    };
+    let mut encoded = encode(&img, format)?;
+    if encoded.len() > MAX_IMAGE_BYTES && format == ImageFormat::Png {
+        format = ImageFormat::Jpeg;
+        encoded = encode(&img, format)?;
+    }
+    if encoded.len() > MAX_IMAGE_BYTES {
+        return Err(format!(
+            "Image too large even scaled down ({} MB max)",
+            MAX_IMAGE_BYTES / (1024 * 1024)
+        ));
+    }
+    Ok(ImageAttachment {
+        data: BASE64.encode(encoded),
+        mime_type: image_mime(format).unwrap_or("image/png").to_string(),
+    })
+}
+
+fn load_image(path: &Path) -> Result<ImageAttachment, String> {
+    let bytes = fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    prepare_image(&bytes).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+fn unescape_xml(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Plain text of a Word document's `word/document.xml`, a line per
+/// paragraph
+fn docx_xml_text(xml: &str) -> String {
+    let mut text = String::new();
+    for caps in DOCX_TOKEN_RE.captures_iter(xml) {
+        match (caps.get(1), &caps[0]) {
+            (Some(run), _) => text.push_str(&unescape_xml(run.as_str())),
+            (None, "<w:tab/>") => text.push('\t'),
+            (None, _) => text.push('\n'),
+        }
+    }
+    text
+}
+
+fn docx_text(path: &Path) -> Result<String, String> {
+    let file = fs::File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let mut archive =
+        zip::ZipArchive::new(file).map_err(|e| format!("Not a Word document: {}", e))?;
+    let mut xml = String::new();
+    archive
+        .by_name("word/document.xml")
+        .map_err(|e| format!("Not a Word document: {}", e))?
+        .read_to_string(&mut xml)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    Ok(docx_xml_text(&xml))
+}
+
+/// Text of a PDF or Word document, cut at `MAX_DOCUMENT_CHARS`
+fn document_text(path: &Path) -> Result<(String, bool), String> {
+    let is_pdf = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("pdf"));
+    let text = if is_pdf {
+        pdf_extract::extract_text(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?
+    } else {
+        docx_text(path)?
+    };
+    let text = text.trim();
+    if text.is_empty() {
+        return Err(format!("No text found in {}", path.display()));
+    }
+    let truncated = text.chars().count() > MAX_DOCUMENT_CHARS;
+    Ok((text.chars().take(MAX_DOCUMENT_CHARS).collect(), truncated))
+}
+
+/// Header and the first `MAX_TABLE_ROWS` rows of a table, noting how many
+/// rows were left out
+fn table_preview(path: &Path) -> Result<(String, bool), String> {
+    let file = fs::File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let mut preview = Vec::new();
+    let mut rows = 0usize;
+    for line in BufReader::new(file).lines() {
+        let line = line.map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        if preview.len() <= MAX_TABLE_ROWS {
+            preview.push(line);
+        }
+        rows += 1;
+    }
+    let left_out = rows.saturating_sub(MAX_TABLE_ROWS + 1);
+    let mut text = preview.join("\n");
+    if left_out > 0 {
+        text.push_str(&format!("\n... ({} more rows)", left_out));
+    }
+    Ok((text, left_out > 0))
+}
+
+fn is_text_file(path: &Path) -> Result<bool, String> {
+    let mut head = Vec::with_capacity(SNIFF_BYTES);
+    fs::File::open(path)
+        .and_then(|file| file.take(SNIFF_BYTES as u64).read_to_end(&mut head))
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    // The sniffed prefix may end inside a character
+    let utf8 = match std::str::from_utf8(&head) {
+        Ok(_) => true,
+        Err(e) => e.error_len().is_none(),
+    };
+    Ok(utf8 && !head.contains(&0))
+}
+
+fn ingest(session_id: &str, path: &Path) -> Result<Attachment, CommandError> {
+    let meta = fs::metadata(path)
+        .map_err(|_| CommandError::not_found(format!("File not found: {}", path.display())))?;
+    if !meta.is_file() {
+        return Err(CommandError::invalid_input(format!(
+            "Not a file: {}",
+            path.display()
+        )));
+    }
+    if meta.len() > MAX_ATTACHMENT_BYTES {
+        return Err(CommandError::invalid_input(format!(
+            "{} is too large to attach ({} MB max)",
+            path.display(),
+            MAX_ATTACHMENT_BYTES / (1024 * 1024)
+        )));
+    }
+
+    let kind = match kind_of(path) {
+        Some(kind) => kind,
+        None if is_text_file(path)? => AttachmentKind::Code,
+        None => {
+            return Err(CommandError::invalid_input(format!(
+                "Can't attach binary file {}",
+                path.display()
+            )))
+        }
+    };
+    let mut attachment = Attachment {
+        kind,
+        name: path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        path: path.to_string_lossy().to_string(),
+        image: None,
+        context: None,
+        truncated: false,
+    };
+    let (text, truncated) = match kind {
+        AttachmentKind::Image => {
+            attachment.image = Some(load_image(path)?);
+            return Ok(attachment);
+        }
+        AttachmentKind::Document => document_text(path).map(|(text, cut)| (Some(text), cut))?,
+        AttachmentKind::Table => table_preview(path).map(|(text, cut)| (Some(text), cut))?,
+        AttachmentKind::Code => (None, false),
+    };
+    let item = ContextItem {
+        path: attachment.path.clone(),
+        start_line: None,
+        end_line: None,
+        text,
+    };
+    stage_context(session_id.to_string(), vec![item.clone()])?;
+    attachment.context = Some(item);
+    attachment.truncated = truncated;
+    Ok(attachment)
+}
+
+/// Turn a dropped file into an attachment: images come back ready to send
+/// with the prompt, anything else is staged for the session's next prompt
+#[tauri::command]
+pub async fn ingest_attachment(session_id: String, path: String) -> Result<Attachment, CommandError> {
+    tauri::async_runtime::spawn_blocking(move || ingest(&session_id, Path::new(&path)))
+        .await
+        .map_err(|e| format!("Attachment task failed: {}", e))?
+}
+
+/// Check and convert a pasted image (base64): TIFF, BMP and other formats
+/// agents don't take become PNG, and large images are scaled down
+#[tauri::command]
+pub async fn prepare_clipboard_image(data: String) -> Result<ImageAttachment, CommandError> {
+    let bytes = BASE64
+        .decode(data.trim())
+        .map_err(|e| CommandError::invalid_input(format!("Invalid image data: {}", e)))?;
+    if bytes.len() > MAX_PASTED_IMAGE_BYTES {
+        return Err(CommandError::invalid_input(format!(
+            "Pasted image too large ({} MB max)",
+            MAX_PASTED_IMAGE_BYTES / (1024 * 1024)
+        )));
+    }
+    tauri::async_runtime::spawn_blocking(move || prepare_image(&bytes))
+        .await
+        .map_err(|e| format!("Image task failed: {}", e))?
+        .map_err(CommandError::invalid_input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acp::staged_context::{get_staged_context, unstage_context};
+
+    #[test]
+    fn test_docx_xml_text() {
+        let xml = r#"<w:body><w:p><w:r><w:t>Q&amp;A</w:t></w:r><w:r><w:tab/><w:t xml:space="preserve"> notes</w:t></w:r></w:p><w:p><w:r><w:t>Second</w:t></w:r></w:p></w:body>"#;
+        assert_eq!(docx_xml_text(xml), "Q&A\t notes\nSecond\n");
+    }
+
+    #[test]
+    fn test_ingest() {
+        let dir = tempfile::tempdir().unwrap();
+        let session = "attachments-test";
+
+        let table = dir.path().join("data.csv");
+        let rows: Vec<String> = (0..=MAX_TABLE_ROWS + 9).map(|i| format!("{},x", i)).collect();
+        fs::write(&table, rows.join("\n")).unwrap();
+        let attachment = ingest(session, &table).unwrap();
+        assert_eq!(attachment.kind, AttachmentKind::Table);
+        assert!(attachment.truncated);
+        let text = attachment.context.unwrap().text.unwrap();
+        assert!(text.ends_with(&format!("{},x\n... (9 more rows)", MAX_TABLE_ROWS)));
+
+        let code = dir.path().join("main.rs");
+        fs::write(&code, "fn main() {}\n").unwrap();
+        let attachment = ingest(session, &code).unwrap();
+        assert_eq!(attachment.kind, AttachmentKind::Code);
+        assert_eq!(get_staged_context(session.to_string()).len(), 2);
+
+        let image = dir.path().join("wide.png");
+        DynamicImage::new_rgba8(MAX_IMAGE_EDGE * 2, 100)
+            .save(&image)
+            .unwrap();
+        let attachment = ingest(session, &image).unwrap();
+        let data = BASE64.decode(attachment.image.unwrap().data).unwrap();
+        let scaled = image::load_from_memory(&data).unwrap();
+        assert_eq!(scaled.dimensions(), (MAX_IMAGE_EDGE, 50));
+
+        // Formats agents don't take are converted
+        let mut bmp = Cursor::new(Vec::new());
+        DynamicImage::new_rgb8(4, 4)
+            .write_to(&mut bmp, ImageFormat::Bmp)
+            .unwrap();
+        assert_eq!(prepare_image(bmp.get_ref()).unwrap().mime_type, "image/png");
+        assert!(prepare_image(b"not an image").is_err());
+
+        let binary = dir.path().join("blob");
+        fs::write(&binary, b"\0\x01\x02").unwrap();
+        assert!(ingest(session, &binary).is_err());
+        unstage_context(session.to_string(), None);
+    }
+}
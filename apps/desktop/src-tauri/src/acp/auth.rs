@@ -0,0 +1,236 @@
+//! Agent authentication status and guided login
+//!
+//! Agents that advertise auth methods in their handshake don't say whether
+//! the user is already signed in, so `get_agent_auth_status` combines the
+//! handshake with the places each CLI keeps its credentials (API key env
+//! vars or keychain secrets, and known credential files). Agents that need
+//! an interactive login (`claude /login`, `gh auth login`, ...) get it run
+//! in a managed terminal by `start_agent_login`; the UI streams it like any
+//! other terminal, and once it exits the status is checked again and
+//! emitted as `agent-login-finished`.
+
+use crate::acp::capabilities::{cached_capabilities, AuthMethodInfo};
+use crate::acp::commands::probe_handshake;
+use crate::acp::registry::get_agent;
+use crate::events::{emit, AgentLoginFinishedEvent};
+use crate::pty::profile::TerminalProfile;
+use crate::pty::terminal::{TerminalOptions, TERMINAL_MANAGER};
+use crate::secrets::env_or_secret;
+use serde::Serialize;
+use std::path::Path;
+use std::time::Duration;
+use tauri::AppHandle;
+use ts_rs::TS;
+
+/// How often a login terminal is checked for exit
+const LOGIN_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Where an agent CLI keeps its credentials and how to sign in
+struct LoginSpec {
+    /// Interactive login command run in a terminal
+    command: &'static str,
+    /// Credential files, relative to the home directory
+    credential_files: &'static [&'static str],
+    /// API key variables that work instead of a login
+    api_key_vars: &'static [&'static str],
+}
+
+fn login_spec(agent_id: &str) -> Option<LoginSpec> {
+    match agent_id {
+        // On macOS the OAuth token lives in the keychain, so an API key or
+        // the handshake is all that can be checked there
+        "claude" => Some(LoginSpec {
+            command: "claude /login",
+            credential_files: &[".claude/.credentials.json"],
+            api_key_vars: &["ANTHROPIC_API_KEY"],
+        }),
+        "gemini" => Some(LoginSpec {
+            command: "gemini",
+            credential_files: &[".gemini/oauth_creds.json"],
+            api_key_vars: &["GEMINI_API_KEY", "GOOGLE_API_KEY"],
+        }),
+        "codex" => Some(LoginSpec {
+            command: "codex login",
+            credential_files: &[".codex/auth.json"],
+            api_key_vars: &["OPENAI_API_KEY"],
+        }),
+        "opencode" => Some(LoginSpec {
+            command: "opencode auth login",
+            credential_files: &[".local/share/opencode/auth.json"],
+            api_key_vars: &[],
+        }),
+        "copilot" => Some(LoginSpec {
+            command: "gh auth login",
+            credential_files: &[".config/gh/hosts.yml"],
+            api_key_vars: &["GH_TOKEN", "GITHUB_TOKEN"],
+        }),
+        _ => None,
+    }
+}
+
+/// First credential found for the spec, e.g. "ANTHROPIC_API_KEY" or
+/// "~/.codex/auth.json"
+fn find_credential(
+    spec: &LoginSpec,
+    home: Option<&Path>,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Option<String> {
+    if let Some(var) = spec
+        .api_key_vars
+        .iter()
+        .find(|var| lookup(var).is_some_and(|value| !value.trim().is_empty()))
+    {
+        return Some(var.to_string());
+    }
+    let home = home?;
+    spec.credential_files
+        .iter()
+        .find(|file| home.join(file).is_file())
+        .map(|file| format!("~/{}", file))
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct AgentAuthStatus {
+    pub agent_id: String,
+    /// The handshake succeeded and either no auth is advertised or
+    /// credentials were found
+    pub authenticated: bool,
+    /// Where the credentials were found
+    pub credential_source: Option<String>,
+    /// Auth methods from the handshake
+    pub auth_methods: Vec<AuthMethodInfo>,
+    /// Command `start_agent_login` runs, if the agent has one
+    pub login_command: Option<String>,
+    /// Why the handshake failed
+    pub error: Option<String>,
+}
+
+async fn auth_status(agent_id: &str, app_handle: AppHandle) -> Result<AgentAuthStatus, String> {
+    let agent = get_agent(agent_id)
+        .ok_or_else(|| format!("Agent '{}' not found or not available", agent_id))?;
+    let handshake = probe_handshake(agent, app_handle).await;
+    let auth_methods = cached_capabilities()
+        .remove(agent_id)
+        .map(|caps| caps.auth_methods)
+        .unwrap_or_default();
+    let spec = login_spec(agent_id);
+    let credential_source = spec
+        .as_ref()
+        .and_then(|spec| find_credential(spec, dirs::home_dir().as_deref(), env_or_secret));
+
+    Ok(AgentAuthStatus {
+        agent_id: agent_id.to_string(),
+        authenticated: handshake.is_ok()
+            && (auth_methods.is_empty() || credential_source.is_some()),
+        credential_source,
+        auth_methods,
+        login_command: spec.map(|spec| spec.command.to_string()),
+        error: handshake.err(),
+    })
+}
+
+/// Check whether an agent is signed in (runs its handshake)
+#[tauri::command]
+pub async fn get_agent_auth_status(
+    agent_id: String,
+    app_handle: AppHandle,
+) -> Result<AgentAuthStatus, String> {
+    auth_status(&agent_id, app_handle).await
+}
+
+/// Run an agent's interactive login in a new terminal and return its id.
+/// The terminal closes when the login command exits, after which the auth
+/// status is checked again and emitted as `agent-login-finished`.
+#[tauri::command]
+pub fn start_agent_login(
+    agent_id: String,
+    cols: Option<u16>,
+    rows: Option<u16>,
+    app_handle: AppHandle,
+) -> Result<String, String> {
+    let agent = get_agent(&agent_id)
+        .ok_or_else(|| format!("Agent '{}' not found or not available", agent_id))?;
+    let spec =
+        login_spec(&agent_id).ok_or_else(|| format!("{} has no interactive login", agent.name))?;
+
+    let options = TerminalOptions {
+        name: Some(format!("{} login", agent.name)),
+        overrides: TerminalProfile {
+            startup_commands: vec![format!("{}; exit", spec.command)],
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let terminal_id = TERMINAL_MANAGER.lock().create(
+        app_handle.clone(),
+        cols.unwrap_or(100),
+        rows.unwrap_or(30),
+        None,
+        options,
+    )?;
+    eprintln!(
+        "[ACP] Started {} login in terminal {}",
+        agent_id, terminal_id
+    );
+
+    let watched_id = terminal_id.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(LOGIN_POLL_INTERVAL).await;
+            // A terminal the user killed is gone from the manager
+            let exited = TERMINAL_MANAGER
+                .lock()
+                .list(None)
+                .into_iter()
+                .find(|info| info.id == watched_id)
+                .is_none_or(|info| info.exited);
+            if exited {
+                break;
+            }
+        }
+        let (status, error) = match auth_status(&agent_id, app_handle.clone()).await {
+            Ok(status) => (Some(status), None),
+            Err(e) => (None, Some(e)),
+        };
+        emit(
+            &app_handle,
+            &AgentLoginFinishedEvent {
+                agent_id,
+                terminal_id: watched_id,
+                status,
+                error,
+            },
+        );
+    });
+
+    Ok(terminal_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_credential() {
+        let home = tempfile::tempdir().unwrap();
+        let spec = login_spec("codex").unwrap();
+        let no_env = |_: &str| None;
+        assert_eq!(find_credential(&spec, Some(home.path()), no_env), None);
+
+        std::fs::create_dir_all(home.path().join(".codex")).unwrap();
+        std::fs::write(home.path().join(".codex/auth.json"), "{}").unwrap();
+        assert_eq!(
+            find_credential(&spec, Some(home.path()), no_env).as_deref(),
+            Some("~/.codex/auth.json")
+        );
+
+        let with_key = |name: &str| (name == "OPENAI_API_KEY").then(|| "sk-test".to_string());
+        assert_eq!(
+            find_credential(&spec, None, with_key).as_deref(),
+            Some("OPENAI_API_KEY")
+        );
+        let blank_key = |_: &str| Some("  ".to_string());
+        assert_eq!(find_credential(&spec, None, blank_key), None);
+    }
+}
@@ -0,0 +1,468 @@
+//! Session sharing bundles
+//!
+//! `export_session_bundle` packs a persisted session into one gzipped JSON
+//! file a teammate can open: the transcript, tool calls and metadata, the
+//! session's patch set, and the project's .env variables with secrets
+//! masked. The whole bundle then passes through the project's redactor.
+//!
+//! `import_session_bundle` copies a bundle into ~/.crafter-code/imported.
+//! Imported sessions are read-only: they never enter the session store, so
+//! they can be reviewed but not resumed, forked or prompted.
+
+use crate::acp::session_store::{PersistedSession, PersistedSessionSummary, SessionStore};
+use crate::agent::dotenv::{preview_project_env, EnvVarPreview};
+use crate::error::CommandError;
+use crate::jobs::{self, Job};
+use crate::orchestrator::patch::{
+    display_path, file_patch, session_patch, BaseSource, FilePatch, SessionPatch,
+};
+use crate::orchestrator::tool_calls::ToolCallRecord;
+use crate::secrets::redact::redactor_for;
+use crate::settings::load_settings;
+use crate::time::{normalize_ms, now_ms};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+/// Marks a file as a session bundle
+pub const BUNDLE_FORMAT: &str = "crafter-session-bundle";
+
+/// Bumped when the bundle layout changes incompatibly
+pub const BUNDLE_VERSION: u32 = 1;
+
+/// Everything needed to review an agent run elsewhere
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionBundle {
+    pub format: String,
+    pub version: u32,
+    /// Unix timestamp (milliseconds)
+    pub exported_at: i64,
+    /// crafter-code version that wrote the bundle
+    pub app_version: String,
+    /// Transcript, tool calls and metadata
+    pub session: PersistedSession,
+    /// Changes the session made to the project
+    pub patch: SessionPatch,
+    /// Project .env variables, secrets masked
+    #[serde(default)]
+    pub env: Vec<EnvVarPreview>,
+}
+
+/// An imported bundle as listed
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportedBundleSummary {
+    #[serde(flatten)]
+    pub session: PersistedSessionSummary,
+    pub exported_at: i64,
+    pub imported_at: i64,
+    pub files_changed: usize,
+    pub additions: usize,
+    pub deletions: usize,
+}
+
+/// A bundle as kept after import
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportedBundle {
+    #[serde(flatten)]
+    pub bundle: SessionBundle,
+    /// Unix timestamp (milliseconds)
+    pub imported_at: i64,
+}
+
+impl ImportedBundle {
+    /// Convert timestamps from bundles written in seconds to milliseconds
+    fn normalize_timestamps(&mut self) {
+        self.imported_at = normalize_ms(self.imported_at);
+        self.bundle.normalize_timestamps();
+    }
+}
+
+impl From<&ImportedBundle> for ImportedBundleSummary {
+    fn from(imported: &ImportedBundle) -> Self {
+        let bundle = &imported.bundle;
+        Self {
+            session: PersistedSessionSummary::from(&bundle.session),
+            exported_at: bundle.exported_at,
+            imported_at: imported.imported_at,
+            files_changed: bundle.patch.files.len(),
+            additions: bundle.patch.additions,
+            deletions: bundle.patch.deletions,
+        }
+    }
+}
+
+/// Patch set rebuilt from the diffs of persisted tool calls, for sessions
+/// whose live patch set is gone: each file goes from the first old text
+/// seen to the last new text
+fn patch_from_tool_calls(session_id: &str, cwd: &str, tool_calls: &[ToolCallRecord]) -> SessionPatch {
+    let mut changes: BTreeMap<&str, (Option<&str>, &str)> = BTreeMap::new();
+    for diff in tool_calls.iter().flat_map(|call| &call.diffs) {
+        changes
+            .entry(diff.path.as_str())
+            .and_modify(|(_, new)| *new = diff.new_text.as_str())
+            .or_insert((diff.old_text.as_deref(), diff.new_text.as_str()));
+    }
+
+    let files: Vec<FilePatch> = changes
+        .into_iter()
+        .filter_map(|(path, (old, new))| {
+            let source = if old.is_some() {
+                BaseSource::Checkpoint
+            } else {
+                BaseSource::None
+            };
+            file_patch(path, &display_path(path, Some(cwd)), old, source, Some(new))
+        })
+        .collect();
+
+    SessionPatch {
+        session_id: session_id.to_string(),
+        additions: files.iter().map(|f| f.additions).sum(),
+        deletions: files.iter().map(|f| f.deletions).sum(),
+        unified: files.iter().map(|f| f.unified.as_str()).collect(),
+        files,
+    }
+}
+
+impl SessionBundle {
+    fn normalize_timestamps(&mut self) {
+        self.exported_at = normalize_ms(self.exported_at);
+        self.session.normalize_timestamps();
+    }
+}
+
+/// Assemble the bundle for a persisted session
+fn build_bundle(mut session: PersistedSession) -> SessionBundle {
+    if !load_settings().export_thinking {
+        session.strip_thinking();
+    }
+    // So the reviewer sees times as they were where the session ran
+    session.metadata.timezone = Some(session.timezone());
+    let live = session_patch(&session.id);
+    let patch = if live.files.is_empty() {
+        patch_from_tool_calls(&session.id, &session.cwd, &session.tool_calls)
+    } else {
+        live
+    };
+    SessionBundle {
+        format: BUNDLE_FORMAT.to_string(),
+        version: BUNDLE_VERSION,
+        exported_at: now_ms(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        env: preview_project_env(session.cwd.clone()).unwrap_or_default(),
+        patch,
+        session,
+    }
+}
+
+/// Redact the bundle with its project's redactor and gzip it to `path`
+fn write_bundle(bundle: &SessionBundle, path: &Path) -> Result<usize, String> {
+    let value = serde_json::to_value(bundle)
+        .map_err(|e| format!("Failed to serialize bundle: {}", e))?;
+    let value = redactor_for(Some(&bundle.session.cwd)).redact_json(value);
+    let json = serde_json::to_vec(&value)
+        .map_err(|e| format!("Failed to serialize bundle: {}", e))?;
+
+    let file = fs::File::create(path)
+        .map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+    let mut encoder = GzEncoder::new(file, Compression::default());
+    encoder
+        .write_all(&json)
+        .and_then(|_| encoder.finish().map(|_| ()))
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(json.len())
+}
+
+/// Read and check a bundle file
+fn read_bundle(path: &Path) -> Result<SessionBundle, String> {
+    let file = fs::File::open(path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let mut json = Vec::new();
+    GzDecoder::new(file)
+        .read_to_end(&mut json)
+        .map_err(|e| format!("{} is not a session bundle: {}", path.display(), e))?;
+
+    let value: serde_json::Value = serde_json::from_slice(&json)
+        .map_err(|e| format!("{} is not a session bundle: {}", path.display(), e))?;
+    if value.get("format").and_then(|f| f.as_str()) != Some(BUNDLE_FORMAT) {
+        return Err(format!("{} is not a session bundle", path.display()));
+    }
+    let version = value.get("version").and_then(|v| v.as_u64()).unwrap_or(0);
+    if version > BUNDLE_VERSION as u64 {
+        return Err(format!(
+            "Bundle version {} is newer than this app supports ({}); update crafter-code",
+            version, BUNDLE_VERSION
+        ));
+    }
+    let mut bundle: SessionBundle =
+        serde_json::from_value(value).map_err(|e| format!("Failed to parse bundle: {}", e))?;
+    bundle.normalize_timestamps();
+    Ok(bundle)
+}
+
+/// Imported bundles on disk (~/.crafter-code/imported)
+pub struct BundleStore {
+    base_path: PathBuf,
+}
+
+impl BundleStore {
+    pub fn new() -> Result<Self, String> {
+        let base_path = dirs::home_dir()
+            .ok_or_else(|| "Could not determine home directory".to_string())?
+            .join(".crafter-code")
+            .join("imported");
+        Self::at(base_path)
+    }
+
+    /// A store in `base_path` instead of the home directory
+    pub fn at(base_path: PathBuf) -> Result<Self, String> {
+        fs::create_dir_all(&base_path)
+            .map_err(|e| format!("Failed to create import directory: {}", e))?;
+        Ok(Self { base_path })
+    }
+
+    fn bundle_path(&self, session_id: &str) -> PathBuf {
+        self.base_path.join(format!("{}.json", session_id))
+    }
+
+    pub fn save(&self, imported: &ImportedBundle) -> Result<(), String> {
+        let json = serde_json::to_string(imported)
+            .map_err(|e| format!("Failed to serialize imported bundle: {}", e))?;
+        fs::write(self.bundle_path(&imported.bundle.session.id), json)
+            .map_err(|e| format!("Failed to write imported bundle: {}", e))
+    }
+
+    pub fn load(&self, session_id: &str) -> Result<ImportedBundle, String> {
+        let json = fs::read_to_string(self.bundle_path(session_id))
+            .map_err(|e| format!("Imported session {} not found: {}", session_id, e))?;
+        parse_imported(&json).map_err(|e| format!("Failed to parse imported bundle: {}", e))
+    }
+
+    /// Imported bundles, most recently imported first
+    pub fn list(&self) -> Vec<ImportedBundleSummary> {
+        let Ok(entries) = fs::read_dir(&self.base_path) else {
+            return Vec::new();
+        };
+        let mut bundles: Vec<ImportedBundleSummary> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|path| fs::read_to_string(path).ok())
+            .filter_map(|json| parse_imported(&json).ok())
+            .map(|imported| ImportedBundleSummary::from(&imported))
+            .collect();
+        bundles.sort_by_key(|b| std::cmp::Reverse(b.imported_at));
+        bundles
+    }
+
+    pub fn delete(&self, session_id: &str) -> Result<(), String> {
+        let path = self.bundle_path(session_id);
+        if path.exists() {
+            fs::remove_file(&path)
+                .map_err(|e| format!("Failed to delete imported bundle: {}", e))?;
+        }
+        Ok(())
+    }
+}
+
+fn parse_imported(json: &str) -> Result<ImportedBundle, serde_json::Error> {
+    let mut imported: ImportedBundle = serde_json::from_str(json)?;
+    imported.normalize_timestamps();
+    Ok(imported)
+}
+
+/// Export a persisted session as a sharing bundle at `path`, as a
+/// background job; returns the job id. The file only appears once the
+/// export is complete.
+#[tauri::command]
+pub fn export_session_bundle(
+    session_id: String,
+    path: String,
+    app_handle: AppHandle,
+) -> Result<String, CommandError> {
+    let target = PathBuf::from(&path);
+    if target.file_name().is_none() {
+        return Err(CommandError::invalid_input(format!("Not a file path: {}", path)));
+    }
+    // Fail fast on unknown sessions instead of in the job
+    let session = SessionStore::new()?.load_session(&session_id)?;
+    let label = format!("Export bundle {}", session_id);
+    let work = move |job: &Job| export_bundle(job, session, &target);
+    Ok(jobs::spawn(&app_handle, "export_session_bundle", label, work))
+}
+
+fn export_bundle(job: &Job, session: PersistedSession, target: &Path) -> Result<(), String> {
+    job.phase("collecting", Some(0.0));
+    let bundle = build_bundle(session);
+    job.check_cancelled()?;
+
+    job.phase("writing", Some(50.0));
+    let partial = target.with_extension("partial");
+    let written = write_bundle(&bundle, &partial)
+        .and_then(|bytes| fs::rename(&partial, target).map(|_| bytes).map_err(|e| e.to_string()));
+    if written.is_err() {
+        let _ = fs::remove_file(&partial);
+    }
+    let bytes = written?;
+    job.log(format!(
+        "Bundled {} messages, {} tool calls and {} changed files ({} bytes uncompressed) into {}",
+        bundle.session.messages.len(),
+        bundle.session.tool_calls.len(),
+        bundle.patch.files.len(),
+        bytes,
+        target.display()
+    ));
+    Ok(())
+}
+
+/// Import a sharing bundle for read-only review
+#[tauri::command]
+pub fn import_session_bundle(path: String) -> Result<ImportedBundleSummary, CommandError> {
+    let bundle = read_bundle(Path::new(&path)).map_err(CommandError::invalid_input)?;
+    let imported = ImportedBundle {
+        bundle,
+        imported_at: now_ms(),
+    };
+    BundleStore::new()?.save(&imported)?;
+    Ok(ImportedBundleSummary::from(&imported))
+}
+
+/// Imported bundles, most recently imported first
+#[tauri::command]
+pub fn list_imported_bundles() -> Result<Vec<ImportedBundleSummary>, CommandError> {
+    Ok(BundleStore::new()?.list())
+}
+
+/// An imported bundle in full
+#[tauri::command]
+pub fn get_imported_bundle(session_id: String) -> Result<ImportedBundle, CommandError> {
+    BundleStore::new()?
+        .load(&session_id)
+        .map_err(CommandError::not_found)
+}
+
+#[tauri::command]
+pub fn delete_imported_bundle(session_id: String) -> Result<(), CommandError> {
+    Ok(BundleStore::new()?.delete(&session_id)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acp::session_store::{PersistedMessage, SessionMetadata};
+    use crate::orchestrator::tool_calls::ToolCallDiff;
+
+    fn tool_call(id: &str, path: &str, old: Option<&str>, new: &str) -> ToolCallRecord {
+        ToolCallRecord {
+            id: id.to_string(),
+            worker_id: "w1".to_string(),
+            kind: Some("edit".to_string()),
+            title: None,
+            status: "completed".to_string(),
+            transitions: Vec::new(),
+            raw_input: None,
+            diffs: vec![ToolCallDiff {
+                path: path.to_string(),
+                old_text: old.map(str::to_string),
+                new_text: new.to_string(),
+            }],
+            started_at: 0,
+            finished_at: None,
+            duration_ms: None,
+        }
+    }
+
+    fn session(cwd: &str, tool_calls: Vec<ToolCallRecord>) -> PersistedSession {
+        PersistedSession {
+            id: "bundle-test".to_string(),
+            acp_session_id: "acp-1".to_string(),
+            cwd: cwd.to_string(),
+            agent_id: "claude".to_string(),
+            created_at: 1,
+            updated_at: 2,
+            messages: vec![PersistedMessage {
+                role: "user".to_string(),
+                content: "Fix the bug".to_string(),
+                timestamp: 1,
+            }],
+            mode: "default".to_string(),
+            initial_prompt: "Fix the bug".to_string(),
+            tool_calls,
+            build_results: Vec::new(),
+            hook_runs: Vec::new(),
+            agent_log: Vec::new(),
+            criteria: None,
+            summary: None,
+            parent_session_id: None,
+            forked_at_message: None,
+            metadata: SessionMetadata::default(),
+        }
+    }
+
+    #[test]
+    fn test_patch_from_tool_calls() {
+        let calls = vec![
+            tool_call("1", "/repo/src/a.rs", Some("one\n"), "two\n"),
+            tool_call("2", "/repo/src/a.rs", Some("two\n"), "three\n"),
+            tool_call("3", "/repo/b.rs", None, "new\n"),
+        ];
+        let patch = patch_from_tool_calls("s", "/repo", &calls);
+
+        assert_eq!(patch.files.len(), 2);
+        assert_eq!(patch.files[0].display_path, "b.rs");
+        assert_eq!(patch.files[0].base_source, BaseSource::None);
+        assert_eq!(patch.files[1].display_path, "src/a.rs");
+        assert!(patch.files[1].unified.contains("-one"));
+        assert!(patch.files[1].unified.contains("+three"));
+        assert_eq!((patch.additions, patch.deletions), (2, 1));
+    }
+
+    #[test]
+    fn test_bundle_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let calls = vec![tool_call("1", "/repo/a.rs", Some("a\n"), "b\n")];
+        let bundle = build_bundle(session("/repo", calls));
+        let path = dir.path().join("run.crafter-bundle");
+        write_bundle(&bundle, &path).unwrap();
+
+        let read = read_bundle(&path).unwrap();
+        assert_eq!(read.format, BUNDLE_FORMAT);
+        assert_eq!(read.session.messages.len(), 1);
+        assert_eq!(read.patch.files.len(), 1);
+
+        let store = BundleStore::at(dir.path().join("imported")).unwrap();
+        store
+            .save(&ImportedBundle {
+                bundle: read,
+                imported_at: 5,
+            })
+            .unwrap();
+        let listed = store.list();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].session.id, "bundle-test");
+        assert_eq!(listed[0].files_changed, 1);
+        store.delete("bundle-test").unwrap();
+        assert!(store.list().is_empty());
+    }
+
+    #[test]
+    fn test_read_bundle_rejects_other_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("plain.json");
+        fs::write(&path, "{}").unwrap();
+        assert!(read_bundle(&path).is_err());
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder
+            .write_all(br#"{"format":"crafter-session-bundle","version":99}"#)
+            .unwrap();
+        fs::write(&path, encoder.finish().unwrap()).unwrap();
+        assert!(read_bundle(&path).unwrap_err().contains("newer"));
+    }
+}
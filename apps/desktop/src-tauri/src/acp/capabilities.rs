@@ -0,0 +1,161 @@
+//! Agent capability cache
+//!
+//! What each agent reported in its last initialize handshake (prompt
+//! content types, session loading, auth methods) plus the session modes
+//! from its last new/loaded session, kept in
+//! ~/.crafter-code/agent-capabilities.json. `list_available_agents` attaches
+//! these so the UI can disable unsupported features before a session starts;
+//! `check_agent_health` refreshes them.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use ts_rs::TS;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
+#[ts(export)]
+pub struct AuthMethodInfo {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SessionModeInfo {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct AgentCapabilityInfo {
+    pub load_session: bool,
+    pub image: bool,
+    pub audio: bool,
+    pub embedded_context: bool,
+    pub modes: Vec<SessionModeInfo>,
+    pub auth_methods: Vec<AuthMethodInfo>,
+    /// Unix timestamp of the handshake these came from
+    pub checked_at: i64,
+}
+
+struct CapabilityCache {
+    path: PathBuf,
+}
+
+impl CapabilityCache {
+    fn new() -> Result<Self, String> {
+        let dir = dirs::home_dir()
+            .ok_or_else(|| "Could not determine home directory".to_string())?
+            .join(".crafter-code");
+        fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create config directory: {}", e))?;
+        Ok(Self {
+            path: dir.join("agent-capabilities.json"),
+        })
+    }
+
+    #[allow(dead_code)]
+    fn with_path(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    fn load(&self) -> HashMap<String, AgentCapabilityInfo> {
+        fs::read_to_string(&self.path)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self, cache: &HashMap<String, AgentCapabilityInfo>) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(cache)
+            .map_err(|e| format!("Failed to serialize agent capabilities: {}", e))?;
+        fs::write(&self.path, json)
+            .map_err(|e| format!("Failed to write agent capabilities: {}", e))
+    }
+
+    fn update(
+        &self,
+        agent_id: &str,
+        f: impl FnOnce(&mut AgentCapabilityInfo),
+    ) -> Result<(), String> {
+        let mut cache = self.load();
+        f(cache.entry(agent_id.to_string()).or_default());
+        self.save(&cache)
+    }
+}
+
+fn log_error(result: Result<(), String>) {
+    if let Err(e) = result {
+        eprintln!("[ACP] Failed to cache agent capabilities: {}", e);
+    }
+}
+
+/// Capabilities cached for every agent that has completed a handshake
+pub fn cached_capabilities() -> HashMap<String, AgentCapabilityInfo> {
+    CapabilityCache::new()
+        .map(|cache| cache.load())
+        .unwrap_or_default()
+}
+
+/// Modes only come with a session, so a new handshake keeps the known ones
+fn apply_handshake(entry: &mut AgentCapabilityInfo, handshake: AgentCapabilityInfo) {
+    *entry = AgentCapabilityInfo {
+        modes: std::mem::take(&mut entry.modes),
+        ..handshake
+    };
+}
+
+/// Record an initialize handshake
+pub fn record_handshake(agent_id: &str, handshake: AgentCapabilityInfo) {
+    log_error(
+        CapabilityCache::new()
+            .and_then(|cache| cache.update(agent_id, |entry| apply_handshake(entry, handshake))),
+    );
+}
+
+/// Record the session modes an agent offered
+pub fn record_modes(agent_id: &str, modes: Vec<SessionModeInfo>) {
+    log_error(
+        CapabilityCache::new()
+            .and_then(|cache| cache.update(agent_id, |entry| entry.modes = modes)),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_handshake_keeps_modes() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = CapabilityCache::with_path(dir.path().join("caps.json"));
+        let plan = SessionModeInfo {
+            id: "plan".to_string(),
+            name: "Plan".to_string(),
+        };
+        cache
+            .update("gemini", |entry| entry.modes = vec![plan.clone()])
+            .unwrap();
+        cache
+            .update("gemini", |entry| {
+                apply_handshake(
+                    entry,
+                    AgentCapabilityInfo {
+                        image: true,
+                        checked_at: 42,
+                        ..Default::default()
+                    },
+                )
+            })
+            .unwrap();
+
+        let loaded = cache.load();
+        let gemini = &loaded["gemini"];
+        assert!(gemini.image);
+        assert!(!gemini.load_session);
+        assert_eq!(gemini.modes, vec![plan]);
+        assert_eq!(gemini.checked_at, 42);
+    }
+}
@@ -0,0 +1,158 @@
+//! Iterate until green
+//!
+//! A lightweight Ralph loop for interactive sessions: run a shell check
+//! (e.g. `cargo test`) in the project, and while it fails, send its output
+//! back to the agent as the next prompt, up to an iteration cap. Driven by
+//! `run_until_passing` in `acp::commands`; each check emits `check-loop`.
+
+use crate::acp::events::EventSink;
+use crate::events::CheckLoopEvent;
+use serde::Serialize;
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+use ts_rs::TS;
+
+/// Longest a single check may run
+const CHECK_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Characters of failing output sent back to the agent (the end, where the
+/// summary and errors usually are)
+const MAX_PROMPT_OUTPUT_CHARS: usize = 8000;
+
+/// One run of the check command
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct CheckRun {
+    pub passed: bool,
+    pub exit_code: Option<i32>,
+    /// Combined stdout and stderr
+    pub output: String,
+    #[ts(type = "number")]
+    pub duration_ms: u64,
+}
+
+/// Outcome of `run_until_passing`
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckLoopResult {
+    pub passed: bool,
+    /// Prompts sent to the agent
+    pub iterations: u32,
+    /// Stopped because the user cancelled the agent's turn
+    pub cancelled: bool,
+    pub last_run: CheckRun,
+}
+
+/// Run the check command in `cwd`
+pub async fn run_check(command: &str, cwd: &str) -> CheckRun {
+    let started = Instant::now();
+    let mut cmd = tokio::process::Command::new("/bin/sh");
+    cmd.args(["-c", command])
+        .current_dir(cwd)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    let (exit_code, output) = match cmd.spawn() {
+        Ok(child) => match tokio::time::timeout(CHECK_TIMEOUT, child.wait_with_output()).await {
+            Ok(Ok(output)) => {
+                let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+                text.push_str(&String::from_utf8_lossy(&output.stderr));
+                (output.status.code(), text)
+            }
+            Ok(Err(e)) => (None, format!("Failed to wait for check: {}", e)),
+            Err(_) => (
+                None,
+                format!("Check timed out after {}s", CHECK_TIMEOUT.as_secs()),
+            ),
+        },
+        Err(e) => (None, format!("Failed to run check: {}", e)),
+    };
+    CheckRun {
+        passed: exit_code == Some(0),
+        exit_code,
+        output,
+        duration_ms: started.elapsed().as_millis() as u64,
+    }
+}
+
+/// Prompt asking the agent to fix a failing check
+pub fn failure_prompt(
+    command: &str,
+    run: &CheckRun,
+    iteration: u32,
+    max_iterations: u32,
+) -> String {
+    let count = run.output.chars().count();
+    let output: String = run
+        .output
+        .chars()
+        .skip(count.saturating_sub(MAX_PROMPT_OUTPUT_CHARS))
+        .collect();
+    let status = match run.exit_code {
+        Some(code) => format!("exited with code {}", code),
+        None => "did not finish".to_string(),
+    };
+    format!(
+        "`{}` {} (attempt {} of {}). Fix the problems below, then stop; \
+         the check will be run again.\n\n```\n{}\n```",
+        command,
+        status,
+        iteration,
+        max_iterations,
+        output.trim_end()
+    )
+}
+
+/// Report a check run, with the prompt it led to (if any)
+pub fn emit_check(
+    events: &EventSink,
+    session_id: &str,
+    command: &str,
+    iterations: u32,
+    max_iterations: u32,
+    run: &CheckRun,
+    follow_up: Option<&str>,
+) {
+    events.send(&CheckLoopEvent {
+        session_id: session_id.to_string(),
+        command: command.to_string(),
+        iterations,
+        max_iterations,
+        run: run.clone(),
+        follow_up: follow_up.map(str::to_string),
+        timestamp: chrono::Utc::now().timestamp_millis(),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_run_check() {
+        let dir = tempfile::tempdir().unwrap();
+        let cwd = dir.path().to_str().unwrap();
+        let run = run_check("echo ok", cwd).await;
+        assert!(run.passed);
+        assert_eq!(run.output, "ok\n");
+
+        let run = run_check("echo 'test a ... FAILED' >&2; exit 101", cwd).await;
+        assert!(!run.passed);
+        assert_eq!(run.exit_code, Some(101));
+        assert_eq!(run.output, "test a ... FAILED\n");
+    }
+
+    #[test]
+    fn test_failure_prompt_keeps_tail() {
+        let run = CheckRun {
+            passed: false,
+            exit_code: Some(1),
+            output: format!("{}END\n", "x".repeat(MAX_PROMPT_OUTPUT_CHARS)),
+            duration_ms: 0,
+        };
+        let prompt = failure_prompt("cargo test", &run, 2, 5);
+        assert!(prompt.starts_with("`cargo test` exited with code 1 (attempt 2 of 5)."));
+        assert!(prompt.ends_with("END\n```"));
+        assert!(prompt.len() < MAX_PROMPT_OUTPUT_CHARS + 200);
+    }
+}
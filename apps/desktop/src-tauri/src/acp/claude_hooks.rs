@@ -0,0 +1,479 @@
+//! Claude Code hook bridge
+//!
+//! Runs the `PreToolUse`, `PostToolUse` and `Stop` hooks configured in
+//! Claude Code settings (`~/.claude/settings.json`, and the project's
+//! `.claude/settings.json` and `.claude/settings.local.json`) for every
+//! agent, so existing hook tooling keeps working whichever agent runs.
+//! ACP tool calls are translated into Claude Code tool names and the hook
+//! JSON is passed on stdin, as Claude Code does:
+//!
+//! - `PreToolUse` when the agent announces a tool call. A hook that exits
+//!   with 2 (or answers `"decision": "block"`) denies the call if the agent
+//!   asks permission for it; otherwise the call is already running and the
+//!   block is only reported.
+//! - `PostToolUse` after a tool call completes
+//! - `Stop` after a prompt finishes. A blocking hook's reason is sent back
+//!   to the agent as a follow-up turn, with `stop_hook_active` set.
+//!
+//! Runs are recorded alongside the project's own hooks (see `hooks`). The
+//! Claude agent runs these hooks itself and is skipped, and nothing runs in
+//! directories the user doesn't trust.
+
+use crate::acp::events::EventSink;
+use crate::acp::hooks::{record_run, tail, HookRun};
+use crate::acp::trust::{trust_level, TrustLevel};
+use crate::settings::load_settings;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+use tokio::io::AsyncWriteExt;
+
+/// Agents that run Claude Code hooks themselves
+const NATIVE_AGENTS: &[&str] = &["claude"];
+
+/// JSON-RPC error code for tool calls denied by a `PreToolUse` hook
+pub const HOOK_ERROR_CODE: i32 = -32004;
+
+/// Exit code with which a hook blocks
+const BLOCK_EXIT_CODE: i32 = 2;
+
+/// Most follow-up turns `Stop` hooks can ask for in a row
+pub const MAX_STOP_CONTINUATIONS: u32 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HookEvent {
+    PreToolUse,
+    PostToolUse,
+    Stop,
+}
+
+impl HookEvent {
+    pub fn name(self) -> &'static str {
+        match self {
+            HookEvent::PreToolUse => "PreToolUse",
+            HookEvent::PostToolUse => "PostToolUse",
+            HookEvent::Stop => "Stop",
+        }
+    }
+}
+
+/// The `hooks` section of a Claude Code settings file
+#[derive(Debug, Default, Deserialize)]
+struct ClaudeSettings {
+    #[serde(default)]
+    hooks: HashMap<String, Vec<HookMatcher>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct HookMatcher {
+    /// Tool name or regex; empty or "*" matches every tool
+    #[serde(default)]
+    matcher: String,
+    #[serde(default)]
+    hooks: Vec<HookCommand>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct HookCommand {
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default)]
+    pub command: String,
+    /// Seconds; `hook_timeout_secs` when missing
+    pub timeout: Option<u64>,
+}
+
+/// Settings files read for `project`, lowest precedence first
+fn settings_files(project: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = dirs::home_dir()
+        .map(|home| home.join(".claude").join("settings.json"))
+        .into_iter()
+        .collect();
+    let dir = project.join(".claude");
+    files.push(dir.join("settings.json"));
+    files.push(dir.join("settings.local.json"));
+    files
+}
+
+fn load_matchers(files: &[PathBuf], event: HookEvent) -> Vec<HookMatcher> {
+    let mut matchers = Vec::new();
+    for file in files {
+        let Ok(json) = fs::read_to_string(file) else {
+            continue;
+        };
+        match serde_json::from_str::<ClaudeSettings>(&json) {
+            Ok(mut settings) => {
+                matchers.extend(settings.hooks.remove(event.name()).unwrap_or_default())
+            }
+            Err(e) => eprintln!("[ClaudeHooks] Ignoring {:?}: {}", file, e),
+        }
+    }
+    matchers
+}
+
+fn matches(matcher: &str, tool_name: Option<&str>) -> bool {
+    let Some(tool_name) = tool_name else {
+        return true;
+    };
+    if matcher.is_empty() || matcher == "*" || matcher == tool_name {
+        return true;
+    }
+    regex::Regex::new(&format!("^(?:{})$", matcher))
+        .map(|re| re.is_match(tool_name))
+        .unwrap_or(false)
+}
+
+/// Commands to run for `event` (and `tool_name`, for tool events), in
+/// order and without repeats
+fn commands_in(files: &[PathBuf], event: HookEvent, tool_name: Option<&str>) -> Vec<HookCommand> {
+    let mut commands: Vec<HookCommand> = Vec::new();
+    for matcher in load_matchers(files, event) {
+        if !matches(&matcher.matcher, tool_name) {
+            continue;
+        }
+        for hook in matcher.hooks {
+            if hook.kind == "command" && !hook.command.is_empty() && !commands.contains(&hook) {
+                commands.push(hook);
+            }
+        }
+    }
+    commands
+}
+
+/// Commands configured for `event` in `project`
+pub fn find_commands(
+    project: &Path,
+    event: HookEvent,
+    tool_name: Option<&str>,
+) -> Vec<HookCommand> {
+    commands_in(&settings_files(project), event, tool_name)
+}
+
+/// Whether hooks should run for `agent_id` in `cwd`
+pub fn bridged(agent_id: Option<&str>, cwd: &str) -> bool {
+    if !load_settings().claude_hooks {
+        return false;
+    }
+    match agent_id {
+        Some(id) if NATIVE_AGENTS.contains(&id) => false,
+        Some(id) => trust_level(id, cwd) != TrustLevel::Untrusted,
+        None => true,
+    }
+}
+
+/// The Claude Code tool name for an ACP tool call
+pub fn tool_name(kind: Option<&str>, raw_input: Option<&Value>) -> String {
+    let writes_file = raw_input
+        .and_then(|input| input.get("content"))
+        .is_some_and(Value::is_string);
+    match kind.unwrap_or("other") {
+        "execute" => "Bash".to_string(),
+        "read" => "Read".to_string(),
+        "edit" if writes_file => "Write".to_string(),
+        "edit" => "Edit".to_string(),
+        "search" => "Grep".to_string(),
+        "fetch" => "WebFetch".to_string(),
+        other => other
+            .split('_')
+            .map(|word| {
+                let mut chars = word.chars();
+                chars
+                    .next()
+                    .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                    .unwrap_or_default()
+            })
+            .collect(),
+    }
+}
+
+/// The tool input as Claude Code hooks expect it (paths under `file_path`)
+pub fn tool_input(raw_input: Option<&Value>) -> Value {
+    let mut input = raw_input.cloned().unwrap_or_else(|| json!({}));
+    if let Some(object) = input.as_object_mut() {
+        if !object.contains_key("file_path") {
+            let path = ["path", "abs_path"]
+                .iter()
+                .find_map(|key| object.get(*key).cloned());
+            if let Some(path) = path {
+                object.insert("file_path".to_string(), path);
+            }
+        }
+    }
+    input
+}
+
+/// What the hooks for one event decided
+#[derive(Debug, Default)]
+pub struct HookOutcome {
+    /// Reason given by the first hook that blocked
+    pub blocked: Option<String>,
+}
+
+/// A block reason from a hook's result
+fn block_reason(exit_code: Option<i32>, stdout: &str, stderr: &str) -> Option<String> {
+    if exit_code == Some(BLOCK_EXIT_CODE) {
+        let reason = stderr.trim();
+        return Some(if reason.is_empty() {
+            "Blocked by hook".to_string()
+        } else {
+            reason.to_string()
+        });
+    }
+    if exit_code != Some(0) {
+        return None;
+    }
+    let output: Value = serde_json::from_str(stdout.trim()).ok()?;
+    let specific = output.get("hookSpecificOutput");
+    let denied = output.get("decision").and_then(Value::as_str) == Some("block")
+        || specific
+            .and_then(|o| o.get("permissionDecision"))
+            .and_then(Value::as_str)
+            == Some("deny");
+    if !denied {
+        return None;
+    }
+    let reason = output
+        .get("reason")
+        .or_else(|| specific.and_then(|o| o.get("permissionDecisionReason")))
+        .and_then(Value::as_str)
+        .unwrap_or("Blocked by hook");
+    Some(reason.to_string())
+}
+
+/// Run one command with the hook JSON on stdin. Returns the exit code,
+/// stdout, stderr and whether it timed out.
+async fn execute(
+    command: &str,
+    cwd: &Path,
+    input: &Value,
+    timeout: Duration,
+) -> (Option<i32>, String, String, bool) {
+    let mut cmd = tokio::process::Command::new("/bin/sh");
+    cmd.args(["-c", command])
+        .current_dir(cwd)
+        .env("CLAUDE_PROJECT_DIR", cwd)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    let mut child = match cmd.spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            let error = format!("Failed to run {}: {}", command, e);
+            return (None, String::new(), error, false);
+        }
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        // Hooks that don't read their input close stdin early
+        let _ = stdin.write_all(input.to_string().as_bytes()).await;
+    }
+    match tokio::time::timeout(timeout, child.wait_with_output()).await {
+        Ok(Ok(output)) => (
+            output.status.code(),
+            String::from_utf8_lossy(&output.stdout).into_owned(),
+            String::from_utf8_lossy(&output.stderr).into_owned(),
+            false,
+        ),
+        Ok(Err(e)) => (
+            None,
+            String::new(),
+            format!("Failed to wait for {}: {}", command, e),
+            false,
+        ),
+        Err(_) => (
+            None,
+            String::new(),
+            format!("Timed out after {}s", timeout.as_secs()),
+            true,
+        ),
+    }
+}
+
+/// Run the commands configured for `event`, recording each run in the
+/// session log. `fields` are added to the common hook input.
+pub async fn run_claude_hooks(
+    events: &EventSink,
+    session_id: &str,
+    worker_id: &str,
+    project: &str,
+    event: HookEvent,
+    tool_name: Option<&str>,
+    fields: Value,
+) -> HookOutcome {
+    let commands = find_commands(Path::new(project), event, tool_name);
+    let mut outcome = HookOutcome::default();
+    if commands.is_empty() {
+        return outcome;
+    }
+
+    let mut input = json!({
+        "session_id": session_id,
+        "transcript_path": null,
+        "cwd": project,
+        "hook_event_name": event.name(),
+    });
+    if let (Some(input), Value::Object(fields)) = (input.as_object_mut(), fields) {
+        input.extend(fields);
+    }
+    let hook = match tool_name {
+        Some(tool_name) => format!("{}({})", event.name(), tool_name),
+        None => event.name().to_string(),
+    };
+    let default_timeout = load_settings().hook_timeout_secs;
+
+    for command in commands {
+        eprintln!(
+            "[ClaudeHooks] Running {} for worker {}: {}",
+            hook, worker_id, command.command
+        );
+        let timeout = Duration::from_secs(command.timeout.unwrap_or(default_timeout).max(1));
+        let started = Instant::now();
+        let (exit_code, stdout, stderr, timed_out) =
+            execute(&command.command, Path::new(project), &input, timeout).await;
+        let run = HookRun {
+            hook: hook.clone(),
+            worker_id: worker_id.to_string(),
+            exit_code,
+            output: tail(&format!("{}{}", stdout, stderr)),
+            duration_ms: started.elapsed().as_millis() as u64,
+            timed_out,
+            timestamp: chrono::Utc::now().timestamp_millis(),
+        };
+        record_run(events, session_id, &run);
+
+        if let Some(reason) = block_reason(exit_code, &stdout, &stderr) {
+            eprintln!("[ClaudeHooks] {} blocked: {}", hook, reason);
+            outcome.blocked = Some(reason);
+            break;
+        }
+    }
+    outcome
+}
+
+/// Error for a tool call a `PreToolUse` hook denied
+pub fn hook_error(tool_name: &str, reason: &str) -> agent_client_protocol::Error {
+    agent_client_protocol::Error::new(
+        HOOK_ERROR_CODE,
+        format!("Blocked by PreToolUse hook: {}", reason),
+    )
+    .with_data(json!({
+        "reason": "pre_tool_use_hook",
+        "tool_name": tool_name,
+        "detail": reason
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_commands_in() {
+        let dir = tempfile::tempdir().unwrap();
+        let shared = dir.path().join("settings.json");
+        let local = dir.path().join("settings.local.json");
+        fs::write(
+            &shared,
+            r#"{"hooks": {
+                "PreToolUse": [
+                    {"matcher": "Bash", "hooks": [{"type": "command", "command": "./check-bash"}]},
+                    {"matcher": "Edit|Write", "hooks": [{"type": "command", "command": "./lint", "timeout": 5}]}
+                ],
+                "Stop": [{"hooks": [{"type": "command", "command": "./notify"}]}]
+            }}"#,
+        )
+        .unwrap();
+        fs::write(
+            &local,
+            r#"{"hooks": {"PreToolUse": [{"matcher": "*", "hooks": [
+                {"type": "command", "command": "./audit"},
+                {"type": "command", "command": "./check-bash"}
+            ]}]}}"#,
+        )
+        .unwrap();
+        let files = vec![shared, local, dir.path().join("missing.json")];
+
+        let commands = |event, tool| -> Vec<String> {
+            commands_in(&files, event, tool)
+                .into_iter()
+                .map(|c| c.command)
+                .collect()
+        };
+        assert_eq!(
+            commands(HookEvent::PreToolUse, Some("Bash")),
+            vec!["./check-bash", "./audit"]
+        );
+        assert_eq!(
+            commands(HookEvent::PreToolUse, Some("Write")),
+            vec!["./lint", "./audit"]
+        );
+        assert_eq!(commands(HookEvent::Stop, None), vec!["./notify"]);
+        assert!(commands(HookEvent::PostToolUse, Some("Bash")).is_empty());
+        let lint = commands_in(&files, HookEvent::PreToolUse, Some("Edit"));
+        assert_eq!(lint[0].timeout, Some(5));
+    }
+
+    #[test]
+    fn test_tool_translation() {
+        let write = json!({"path": "/p/src/main.rs", "content": "fn main() {}"});
+        assert_eq!(tool_name(Some("edit"), Some(&write)), "Write");
+        assert_eq!(
+            tool_name(Some("edit"), Some(&json!({"old_string": "a"}))),
+            "Edit"
+        );
+        assert_eq!(tool_name(Some("execute"), None), "Bash");
+        assert_eq!(tool_name(Some("switch_mode"), None), "SwitchMode");
+        assert_eq!(tool_name(None, None), "Other");
+
+        assert_eq!(tool_input(Some(&write))["file_path"], "/p/src/main.rs");
+        assert_eq!(tool_input(None), json!({}));
+    }
+
+    #[test]
+    fn test_block_reason() {
+        assert_eq!(
+            block_reason(Some(2), "", "rm is not allowed\n").as_deref(),
+            Some("rm is not allowed")
+        );
+        assert_eq!(block_reason(Some(1), "", "oops").as_deref(), None);
+        assert_eq!(block_reason(Some(0), "all good", "").as_deref(), None);
+        assert_eq!(
+            block_reason(
+                Some(0),
+                r#"{"decision": "block", "reason": "tests fail"}"#,
+                ""
+            )
+            .as_deref(),
+            Some("tests fail")
+        );
+        let deny = r#"{"hookSpecificOutput": {"permissionDecision": "deny",
+            "permissionDecisionReason": "protected file"}}"#;
+        assert_eq!(
+            block_reason(Some(0), deny, "").as_deref(),
+            Some("protected file")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_execute_passes_input_on_stdin() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = json!({"hook_event_name": "PreToolUse", "tool_name": "Bash"});
+        let (code, stdout, _, timed_out) = execute(
+            "cat; echo \" $CLAUDE_PROJECT_DIR\"",
+            dir.path(),
+            &input,
+            Duration::from_secs(5),
+        )
+        .await;
+        assert_eq!(code, Some(0));
+        assert!(stdout.starts_with(&input.to_string()));
+        assert!(stdout
+            .trim_end()
+            .ends_with(&dir.path().display().to_string()));
+        assert!(!timed_out);
+    }
+}
@@ -10,7 +10,7 @@ use agent_client_protocol::{
     LoadSessionRequest, NewSessionRequest, PermissionOptionId, PromptRequest,
     ReadTextFileRequest, ReadTextFileResponse, ReleaseTerminalRequest,
     ReleaseTerminalResponse, RequestPermissionOutcome, RequestPermissionRequest,
-    RequestPermissionResponse, SelectedPermissionOutcome, SessionModeId, SessionNotification,
+    RequestPermissionResponse, SelectedPermissionOutcome, SessionModeId, SessionModeState, SessionNotification,
     SessionUpdate, SetSessionModeRequest, StopReason, TerminalExitStatus, TerminalOutputRequest,
     TerminalOutputResponse, TextContent, WaitForTerminalExitRequest, WaitForTerminalExitResponse,
     WriteTextFileRequest, WriteTextFileResponse,
@@ -19,15 +19,75 @@ use futures::io::BufReader;
 use once_cell::sync::Lazy;
 use parking_lot::Mutex;
 use std::collections::HashMap;
+use std::path::Path;
 use std::process::Child;
 use std::sync::Arc;
-use tauri::{AppHandle, Emitter};
+use tauri::{AppHandle, Manager};
 use tokio::process::Command;
 use tokio::sync::{mpsc, oneshot};
 use tokio_util::compat::{TokioAsyncReadCompatExt, TokioAsyncWriteCompatExt};
 
-use crate::acp::swarm::{execute_swarm_command, is_swarm_command, parse_swarm_command};
+use crate::acp::agent_log;
+use crate::acp::capabilities::{
+    record_handshake, record_modes, AgentCapabilityInfo, AuthMethodInfo, SessionModeInfo,
+};
+use crate::acp::compaction::{COMPACT_PROMPT, COMPACT_SEED_PREFIX};
+use crate::acp::context::{ContextTracker, ContextUsage};
+use crate::acp::criteria::check_session;
+use crate::acp::delta_batcher::{emit_worker_event, DeltaBatcher};
+use crate::acp::dev_servers;
+use crate::acp::events::EventSink;
+use crate::acp::guards::{
+    await_review, check, emit_hits, guard_error, load_rules, strongest, tool_call_text,
+};
+use crate::acp::claude_hooks::{
+    bridged, hook_error, run_claude_hooks, tool_input, tool_name, HookEvent,
+    MAX_STOP_CONTINUATIONS,
+};
+use crate::acp::hooks::{find_hooks, post_tool_hooks, run_hooks, ON_COMPLETE, PRE_PROMPT};
+use crate::acp::language::prompt_language;
+use crate::acp::modes::{set_current_mode, set_session_modes, SessionModes};
+use crate::acp::network_isolation::{
+    is_isolated, is_network_command, isolated_shell, network_error,
+};
+use crate::acp::protocol_trace::{TracedReader, TracedWriter};
+use crate::acp::read_only::{is_edit_kind, is_read_only_command, is_read_only_mode, read_only_error};
+use crate::acp::rate_limits;
+use crate::acp::resource_limits;
+use crate::acp::retry::{self, TransientError};
+use crate::acp::stream_metrics::StreamMetrics;
+use crate::acp::swarm::{
+    execute_prd_command, execute_service_command, execute_swarm_command, is_swarm_command,
+    parse_swarm_command, swarm_help, SwarmCategory,
+};
+use crate::acp::trust::{trust_level, TrustLevel, UNTRUSTED_MODE};
+use crate::acp::turn::{TurnAccumulator, TurnOutput};
+use crate::acp::write_diffs::{FileWrite, WriteDiffs};
+use crate::agent::dotenv::project_env;
+use crate::agent::scratch;
+use crate::events::{
+    BuildResultEvent, ContextWarningEvent, PermissionOption, PlanEntry, PlanImportedEvent,
+    TokenUsage, ToolCallContent, WorkerCommandsEvent, WorkerEvent, WorkerEventType,
+    WorkerModeEvent, WorkerPermissionEvent, WorkerProgressEvent, WorkerRetryEvent,
+    WorkerStreamEvent, WorkerToolEvent, WorkerUserMessageEvent,
+};
 use crate::inbox::InboxManager;
+use crate::notifications::{notify, NotificationEvent};
+use crate::orchestrator::build_results::{parse_output, record_build_result};
+use crate::orchestrator::patch::{track_before_write, track_diff};
+use crate::orchestrator::tool_calls::{
+    diffs_from_content, get_tool_calls, record_tool_call, ToolCallDiff, ToolCallFilter,
+};
+use crate::prd::PrdManager;
+use crate::pty::{recording, service};
+use crate::secrets::env_or_secret;
+use crate::secrets::redact::{
+    redactor_for_worker, remove_worker_project, set_worker_project, RedactingStream,
+};
+use crate::settings::load_settings;
+use crate::telemetry;
+use crate::settings::store::{GuardAction, GuardTarget};
+use crate::tasks::plan;
 use crate::tasks::TaskManager;
 
 /// Global registry for permission response channels
@@ -45,17 +105,61 @@ pub fn send_permission_response(worker_id: &str, option_id: String) -> Result<()
     }
 }
 
-/// Our implementation of the ACP Client trait
+/// The serde name of an ACP enum value (e.g. "in_progress"), which is what
+/// the frontend's ACP SDK types use
+fn wire_name<T: serde::Serialize + std::fmt::Debug>(value: &T) -> String {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(name)) => name,
+        _ => format!("{:?}", value).to_lowercase(),
+    }
+}
+
+/// Flatten ACP tool-call content for the frontend
+fn tool_call_content(content: &[agent_client_protocol::ToolCallContent]) -> Vec<ToolCallContent> {
+    content
+        .iter()
+        .map(|c| match c {
+            agent_client_protocol::ToolCallContent::Content(content) => match &content.content {
+                ContentBlock::Text(text_content) => ToolCallContent::Text {
+                    text: text_content.text.clone(),
+                },
+                other => ToolCallContent::Content {
+                    text: format!("{:?}", other),
+                },
+            },
+            agent_client_protocol::ToolCallContent::Diff(diff) => ToolCallContent::Diff {
+                path: diff.path.to_string_lossy().into_owned(),
+                old_text: diff.old_text.clone(),
+                new_text: diff.new_text.clone(),
+            },
+            agent_client_protocol::ToolCallContent::Terminal(term) => ToolCallContent::Terminal {
+                terminal_id: term.terminal_id.to_string(),
+            },
+            // Handle any future variants
+            _ => ToolCallContent::Unknown {
+                text: format!("{:?}", c),
+            },
+        })
+        .collect()
+}
+
+/// Our implementation of the ACP Client trait. Clones share all state.
+#[derive(Clone)]
 pub struct CrafterClient {
-    app_handle: AppHandle,
+    events: EventSink,
     worker_id: String,
     session_id: String,
     /// Session working directory (default for terminals)
     session_cwd: Arc<Mutex<Option<String>>>,
     /// Terminal processes spawned via terminal/create
     terminals: Arc<Mutex<HashMap<String, Child>>>,
-    /// Accumulated text for the current response
-    accumulated_text: Arc<Mutex<String>>,
+    /// Output read so far from each terminal (for build result detection)
+    terminal_outputs: Arc<Mutex<HashMap<String, String>>>,
+    /// Terminal output not yet redacted: the tail of the last read that
+    /// could be the start of a secret
+    terminal_streams: Arc<Mutex<HashMap<String, RedactingStream>>>,
+    /// Accumulated response text, keyed by prompt id
+    turns: Arc<Mutex<TurnAccumulator>>,
     /// Task manager for swarm coordination
     task_manager: Option<Arc<TaskManager>>,
     /// Inbox manager for swarm coordination
@@ -64,21 +168,50 @@ pub struct CrafterClient {
     total_input_chars: Arc<Mutex<u64>>,
     /// Total output characters (for token estimation)
     total_output_chars: Arc<Mutex<u64>>,
+    /// Delta timing for throughput/progress reporting
+    stream_metrics: Arc<Mutex<StreamMetrics>>,
+    /// Coalesces high-frequency delta emissions
+    delta_batcher: Arc<DeltaBatcher>,
+    /// Current ACP session mode (for read-only enforcement)
+    session_mode: Arc<Mutex<Option<String>>>,
+    /// Registry agent id (for directory trust)
+    agent_id: Arc<Mutex<Option<String>>>,
+    /// PRD session this agent leads (for `swarm prd` commands)
+    prd_access: Arc<Mutex<Option<(Arc<PrdManager>, String)>>>,
+    /// PreToolUse hook outcome per tool call (the block reason), shared by
+    /// the call's announcement and its permission request
+    pre_tool_hooks: Arc<Mutex<HashMap<String, Arc<tokio::sync::OnceCell<Option<String>>>>>>,
+    /// File writes waiting to be diffed against their tool call
+    write_diffs: Arc<Mutex<WriteDiffs>>,
 }
 
 impl CrafterClient {
-    pub fn new(app_handle: AppHandle, worker_id: String, session_id: String) -> Self {
+    pub fn new(events: EventSink, worker_id: String, session_id: String) -> Self {
+        let delta_batcher = DeltaBatcher::new(
+            events.clone(),
+            worker_id.clone(),
+            load_settings().delta_flush_hz,
+        );
         Self {
-            app_handle,
+            events,
             worker_id,
             session_id,
             session_cwd: Arc::new(Mutex::new(None)),
             terminals: Arc::new(Mutex::new(HashMap::new())),
-            accumulated_text: Arc::new(Mutex::new(String::new())),
+            terminal_outputs: Arc::new(Mutex::new(HashMap::new())),
+            terminal_streams: Arc::new(Mutex::new(HashMap::new())),
+            turns: Arc::new(Mutex::new(TurnAccumulator::new())),
             task_manager: None,
             inbox_manager: None,
             total_input_chars: Arc::new(Mutex::new(0)),
             total_output_chars: Arc::new(Mutex::new(0)),
+            stream_metrics: Arc::new(Mutex::new(StreamMetrics::new())),
+            delta_batcher,
+            session_mode: Arc::new(Mutex::new(None)),
+            agent_id: Arc::new(Mutex::new(None)),
+            prd_access: Arc::new(Mutex::new(None)),
+            pre_tool_hooks: Arc::new(Mutex::new(HashMap::new())),
+            write_diffs: Arc::new(Mutex::new(WriteDiffs::default())),
         }
     }
 
@@ -103,42 +236,344 @@ impl CrafterClient {
         self
     }
 
-    fn emit_event(&self, event_type: &str, data: serde_json::Value) {
-        let event_name = format!("worker-stream-{}", self.worker_id);
-        let mut event = serde_json::json!({ "type": event_type });
-        if let serde_json::Value::Object(map) = data {
-            if let serde_json::Value::Object(ref mut event_map) = event {
-                for (k, v) in map {
-                    event_map.insert(k, v);
-                }
+    /// Run guard rules on a command or tool call the agent wants to run.
+    /// Errs when a rule blocks it or the user rejects a paused action.
+    async fn guard(&self, target: GuardTarget, text: &str) -> agent_client_protocol::Result<()> {
+        let cwd = self.get_session_cwd();
+        let rules = load_rules(cwd.as_deref());
+        if rules.is_empty() {
+            return Ok(());
+        }
+        let hits = check(&rules, target, text, cwd.as_deref()).await;
+        let allowed = match strongest(&hits) {
+            None => return Ok(()),
+            Some(GuardAction::Flag) => {
+                emit_hits(&self.events, &self.session_id, &self.worker_id, &hits, false);
+                true
             }
+            Some(GuardAction::Block) => {
+                emit_hits(&self.events, &self.session_id, &self.worker_id, &hits, false);
+                false
+            }
+            Some(GuardAction::Pause) => {
+                self.delta_batcher.flush();
+                emit_hits(&self.events, &self.session_id, &self.worker_id, &hits, true);
+                await_review(&self.events, &self.worker_id).await
+            }
+        };
+        if allowed {
+            Ok(())
+        } else {
+            Err(guard_error(&hits))
+        }
+    }
+
+    /// The current mode, if it is one of the configured read-only modes,
+    /// or "untrusted" if the user doesn't trust the session directory
+    fn read_only_mode(&self) -> Option<String> {
+        let mode = self.session_mode.lock().clone();
+        if is_read_only_mode(mode.as_deref(), &load_settings().read_only_modes) {
+            return Some(mode.unwrap_or_default());
+        }
+        let agent_id = self.agent_id.lock().clone()?;
+        let cwd = self.get_session_cwd()?;
+        (trust_level(&agent_id, &cwd) == TrustLevel::Untrusted)
+            .then(|| UNTRUSTED_MODE.to_string())
+    }
+
+    /// Refuse an action because the session is read-only
+    fn refuse_read_only(
+        &self,
+        mode: &str,
+        action: &str,
+        detail: &str,
+    ) -> agent_client_protocol::Error {
+        eprintln!(
+            "[ACP] Refused {} in read-only mode '{}': {}",
+            action, mode, detail
+        );
+        self.emit_event(WorkerEventType::ReadOnlyBlocked {
+            mode: mode.to_string(),
+            action: action.to_string(),
+            detail: detail.to_string(),
+        });
+        read_only_error(mode, action, detail)
+    }
+
+    /// Whether this session's terminals run without network access
+    fn network_isolated(&self) -> bool {
+        let mode = self.session_mode.lock().clone();
+        is_isolated(
+            &load_settings().network_isolation,
+            self.get_session_cwd().as_deref(),
+            mode.as_deref(),
+        )
+    }
+
+    /// A network-isolated shell for `command`. Without a sandbox, known
+    /// network commands are refused (every command with `require_sandbox`)
+    /// and the rest run in a plain shell.
+    fn network_isolated_shell(
+        &self,
+        command: &str,
+    ) -> agent_client_protocol::Result<std::process::Command> {
+        if let Some(cmd) = isolated_shell(command) {
+            return Ok(cmd);
+        }
+        let reason = if load_settings().network_isolation.require_sandbox {
+            "no network sandbox is available on this machine"
+        } else if is_network_command(command) {
+            "network commands are not allowed"
+        } else {
+            let mut cmd = std::process::Command::new("/bin/sh");
+            cmd.args(["-c", command]);
+            return Ok(cmd);
+        };
+        eprintln!("[ACP] Refused command in network-isolated session: {}", command);
+        self.emit_event(WorkerEventType::NetworkBlocked {
+            command: command.to_string(),
+            reason: reason.to_string(),
+        });
+        Err(network_error(command, reason))
+    }
+
+    fn emit_event(&self, kind: WorkerEventType) {
+        let prompt_id = self.turns.lock().current();
+        emit_worker_event(&self.events, &self.worker_id, prompt_id.as_deref(), kind);
+    }
+
+    /// Add files from tool-call diffs to the session patch set
+    fn track_tool_diffs(&self, diffs: &[ToolCallDiff], status: Option<&str>) {
+        // Once the call has finished the file on disk already holds the new content
+        let applied = matches!(status, Some("completed") | Some("failed"));
+        for diff in diffs {
+            track_diff(
+                &self.session_id,
+                self.get_session_cwd(),
+                &diff.path,
+                diff.old_text.as_deref(),
+                applied,
+            );
+        }
+    }
+
+    /// Diff an `fs/write_text_file` against its tool call, emitting it if
+    /// the agent won't report it
+    fn record_write(&self, path: &str, old_text: Option<String>, new_text: String) {
+        let write = self.write_diffs.lock().record(path, old_text, new_text);
+        if let Some(write) = write {
+            self.emit_file_diff(write);
+        }
+    }
+
+    /// Add a write the agent didn't report a diff for to the tool call log
+    /// and emit it as a `file_diff` event
+    fn emit_file_diff(&self, write: FileWrite) {
+        eprintln!("[ACP] Synthesized diff for {}", write.path);
+        if let Some(tool_call_id) = &write.tool_call_id {
+            record_tool_call(
+                &self.session_id,
+                &self.worker_id,
+                tool_call_id,
+                None,
+                None,
+                None,
+                None,
+                vec![write.diff()],
+            );
+        }
+        self.emit_event(WorkerEventType::FileDiff {
+            unified: write.unified(),
+            tool_call_id: write.tool_call_id,
+            path: write.path,
+            old_text: write.old_text,
+            new_text: write.new_text,
+        });
+    }
+
+    /// Detect test/build results in command output and emit a `build-result` event
+    fn analyze_output(&self, source_id: &str, output: &str) {
+        let Some(summary) = parse_output(output) else {
+            return;
+        };
+        let result = record_build_result(&self.session_id, &self.worker_id, source_id, summary);
+        eprintln!(
+            "[ACP] Build result from {}: {:?} success={}",
+            source_id, result.summary.tool, result.summary.success
+        );
+        self.events.send(&BuildResultEvent {
+            session_id: self.session_id.clone(),
+            result,
+        });
+    }
+
+    /// Whether Claude Code hooks run for this session, and where
+    fn claude_hooks_cwd(&self) -> Option<String> {
+        let cwd = self.get_session_cwd()?;
+        let agent_id = self.agent_id.lock().clone();
+        bridged(agent_id.as_deref(), &cwd).then_some(cwd)
+    }
+
+    /// Run the PreToolUse hooks for a tool call, once however often it's
+    /// asked. Resolves to the reason a hook blocked the call.
+    fn pre_tool_use(
+        &self,
+        tool_call_id: &str,
+        kind: Option<&str>,
+        raw_input: Option<&serde_json::Value>,
+    ) -> Option<impl std::future::Future<Output = Option<String>> + 'static> {
+        let cwd = self.claude_hooks_cwd()?;
+        let cell = self
+            .pre_tool_hooks
+            .lock()
+            .entry(tool_call_id.to_string())
+            .or_default()
+            .clone();
+        let events = self.events.clone();
+        let session_id = self.session_id.clone();
+        let worker_id = self.worker_id.clone();
+        let tool = tool_name(kind, raw_input);
+        let input = tool_input(raw_input);
+        Some(async move {
+            cell.get_or_init(|| async {
+                run_claude_hooks(
+                    &events,
+                    &session_id,
+                    &worker_id,
+                    &cwd,
+                    HookEvent::PreToolUse,
+                    Some(&tool),
+                    serde_json::json!({ "tool_name": tool, "tool_input": input }),
+                )
+                .await
+                .blocked
+            })
+            .await
+            .clone()
+        })
+    }
+
+    /// Run the project's post-tool hooks and Claude Code PostToolUse hooks
+    /// for a completed tool call in the background, so long hooks don't
+    /// hold up the notification stream
+    fn spawn_post_tool_hooks(&self, tool_call_id: &str, tool_response: serde_json::Value) {
+        let Some(cwd) = self.get_session_cwd() else {
+            return;
+        };
+        let record = get_tool_calls(&self.session_id, &ToolCallFilter::default())
+            .and_then(|calls| calls.into_iter().find(|call| call.id == tool_call_id));
+        let kind = record.as_ref().and_then(|r| r.kind.clone());
+        let raw_input = record.as_ref().and_then(|r| r.raw_input.clone());
+        let title = record.and_then(|r| r.title).unwrap_or_default();
+        let names = post_tool_hooks(kind.as_deref());
+        let hook_names: Vec<&str> = names.iter().map(String::as_str).collect();
+        let project_hooks = !find_hooks(Path::new(&cwd), &hook_names).is_empty();
+        let claude_hooks = self.claude_hooks_cwd().is_some();
+        if !project_hooks && !claude_hooks {
+            return;
+        }
+
+        let events = self.events.clone();
+        let session_id = self.session_id.clone();
+        let worker_id = self.worker_id.clone();
+        let tool_call_id = tool_call_id.to_string();
+        tokio::task::spawn_local(async move {
+            if project_hooks {
+                let names: Vec<&str> = names.iter().map(String::as_str).collect();
+                let kind = kind.as_deref().unwrap_or_default();
+                run_hooks(
+                    &events,
+                    &session_id,
+                    &worker_id,
+                    &cwd,
+                    &names,
+                    &[
+                        ("CRAFTER_TOOL_CALL_ID", tool_call_id.as_str()),
+                        ("CRAFTER_TOOL_KIND", kind),
+                        ("CRAFTER_TOOL_TITLE", title.as_str()),
+                    ],
+                )
+                .await;
+            }
+            if claude_hooks {
+                let tool = tool_name(kind.as_deref(), raw_input.as_ref());
+                run_claude_hooks(
+                    &events,
+                    &session_id,
+                    &worker_id,
+                    &cwd,
+                    HookEvent::PostToolUse,
+                    Some(&tool),
+                    serde_json::json!({
+                        "tool_name": tool,
+                        "tool_input": tool_input(raw_input.as_ref()),
+                        "tool_response": tool_response,
+                    }),
+                )
+                .await;
+            }
+        });
+    }
+
+    /// Analyze and drop the output collected for a finished terminal
+    fn finish_terminal_output(&self, terminal_id: &str) {
+        let output = self.terminal_outputs.lock().remove(terminal_id);
+        if let Some(output) = output {
+            self.analyze_output(terminal_id, &output);
+        }
+    }
+
+    /// `terminal/output` for a service terminal: its retained output, with
+    /// the exit status once it has stopped for good
+    fn service_output(
+        &self,
+        terminal_id: &str,
+    ) -> agent_client_protocol::Result<TerminalOutputResponse> {
+        let output = service::output(terminal_id)
+            .ok_or_else(|| agent_client_protocol::Error::new(-32000, "Terminal not found"))?;
+        let text = redactor_for_worker(&self.worker_id)
+            .redact(&output.output)
+            .into_owned();
+        let mut response = TerminalOutputResponse::new(text, output.truncated);
+        if matches!(
+            output.info.status,
+            service::ServiceStatus::Stopped | service::ServiceStatus::Failed
+        ) {
+            response = response.exit_status(
+                TerminalExitStatus::new().exit_code(output.info.last_exit_code.map(|c| c as u32)),
+            );
+        }
+        Ok(response)
+    }
+
+    /// Copy a plan to the session's task board, if plan import is on
+    fn import_plan(&self, entries: &[PlanEntry]) {
+        let Some(task_manager) = &self.task_manager else {
+            return;
+        };
+        if !plan::import_enabled(&self.session_id) {
+            return;
+        }
+        let imported = plan::import_plan(task_manager, &self.session_id, &self.worker_id, entries);
+        if imported.is_empty() {
+            return;
         }
-        let payload = serde_json::json!({
-            "worker_id": self.worker_id,
-            "event": event
+        self.events.send(&PlanImportedEvent {
+            session_id: self.session_id.clone(),
+            worker_id: self.worker_id.clone(),
+            created: imported.created,
+            updated: imported.updated,
         });
-        let _ = self.app_handle.emit(&event_name, payload);
     }
 
     /// Handle a swarm command by executing it against TaskManager/InboxManager
-    /// and creating a fake terminal that immediately returns the result
+    /// (or the led PRD session) and creating a fake terminal that immediately returns the result
     fn handle_swarm_terminal(
         &self,
         command: &str,
     ) -> agent_client_protocol::Result<CreateTerminalResponse> {
         eprintln!("[ACP] Intercepted swarm command: {}", command);
 
-        // Check if we have the coordination managers
-        let (task_manager, inbox_manager) = match (&self.task_manager, &self.inbox_manager) {
-            (Some(tm), Some(im)) => (tm.clone(), im.clone()),
-            _ => {
-                return Err(agent_client_protocol::Error::new(
-                    -32000,
-                    "Swarm coordination not enabled for this session".to_string(),
-                ));
-            }
-        };
-
         // Parse the swarm command
         let swarm_cmd = match parse_swarm_command(command) {
             Some(cmd) => cmd,
@@ -151,10 +586,54 @@ impl CrafterClient {
         };
 
         // Execute the swarm command
-        let result = execute_swarm_command(&swarm_cmd, &task_manager, &inbox_manager, &self.worker_id);
+        let result = if swarm_cmd.category == SwarmCategory::Help {
+            swarm_help(prompt_language(&self.session_id).as_ref())
+        } else if swarm_cmd.category == SwarmCategory::Prd {
+            let Some((manager, prd_session_id)) = self.prd_access.lock().clone() else {
+                return Err(agent_client_protocol::Error::new(
+                    -32000,
+                    "PRD commands are only available to a PRD leader".to_string(),
+                ));
+            };
+            execute_prd_command(&swarm_cmd, &manager, &prd_session_id)
+        } else if swarm_cmd.category == SwarmCategory::Service {
+            let Some(app_handle) = self.events.app_handle() else {
+                return Err(agent_client_protocol::Error::new(
+                    -32000,
+                    "Service commands need the desktop app".to_string(),
+                ));
+            };
+            execute_service_command(
+                &swarm_cmd,
+                app_handle,
+                &self.session_id,
+                self.get_session_cwd().as_deref(),
+            )
+        } else {
+            // Check if we have the coordination managers
+            let (task_manager, inbox_manager) = match (&self.task_manager, &self.inbox_manager) {
+                (Some(tm), Some(im)) => (tm.clone(), im.clone()),
+                _ => {
+                    return Err(agent_client_protocol::Error::new(
+                        -32000,
+                        "Swarm coordination not enabled for this session".to_string(),
+                    ));
+                }
+            };
+            // Team listings show worker statuses as the orchestrator has them
+            if let (SwarmCategory::Team, Some(app_handle)) =
+                (&swarm_cmd.category, self.events.app_handle())
+            {
+                let state = app_handle.state::<crate::AppState>();
+                if let Some(session) = state.orchestrator_manager.lock().get_session(&self.session_id) {
+                    inbox_manager.sync_status(&session.workers);
+                }
+            }
+            execute_swarm_command(&swarm_cmd, &task_manager, &inbox_manager, &self.worker_id)
+        };
 
         // Emit swarm activity event to frontend for UI updates
-        let _ = self.app_handle.emit(
+        let _ = self.events.emit(
             "swarm-activity",
             serde_json::json!({
                 "worker_id": self.worker_id,
@@ -219,6 +698,102 @@ impl Client for CrafterClient {
             );
         }
 
+        // Edits (and commands that aren't read-only) never reach the user in
+        // read-only modes
+        if let Some(mode) = self.read_only_mode() {
+            if let Some(kind) = args.tool_call.fields.kind.as_ref() {
+                if is_edit_kind(kind) {
+                    return Err(self.refuse_read_only(&mode, "edit permission", title));
+                }
+            }
+            let command = args
+                .tool_call
+                .fields
+                .raw_input
+                .as_ref()
+                .and_then(|input| input.get("command"))
+                .and_then(|command| command.as_str());
+            if let Some(command) = command.filter(|c| !is_read_only_command(c)) {
+                return Err(self.refuse_read_only(&mode, "command", command));
+            }
+        }
+
+        // Guard rules see the tool call, and the command it would run
+        let kind = args.tool_call.fields.kind.as_ref().map(wire_name);
+        let raw_input = args.tool_call.fields.raw_input.as_ref();
+        self.guard(
+            GuardTarget::ToolCall,
+            &tool_call_text(kind.as_deref().unwrap_or_default(), title, raw_input),
+        )
+        .await?;
+        if let Some(command) = raw_input
+            .and_then(|input| input.get("command"))
+            .and_then(|command| command.as_str())
+        {
+            self.guard(GuardTarget::Command, command).await?;
+        }
+
+        // Claude Code PreToolUse hooks can deny the call
+        if let Some(pre_tool_use) = self.pre_tool_use(
+            &args.tool_call.tool_call_id.to_string(),
+            kind.as_deref(),
+            raw_input,
+        ) {
+            if let Some(reason) = pre_tool_use.await {
+                let tool = tool_name(kind.as_deref(), raw_input);
+                return Err(hook_error(&tool, &reason));
+            }
+        }
+
+        // Make sure buffered text reaches the UI before the permission prompt
+        self.delta_batcher.flush();
+        if let Some(app_handle) = self.events.app_handle() {
+            notify(
+                app_handle,
+                NotificationEvent::PermissionRequired,
+                Some(&self.session_id),
+                "Permission required",
+                title,
+            );
+        }
+
+        // Headless runs have nobody to ask: answer from the CLI flag
+        if let Some(approve) = self.events.headless_permission() {
+            let option_id = args
+                .options
+                .iter()
+                .find(|opt| {
+                    if approve {
+                        matches!(
+                            opt.kind,
+                            agent_client_protocol::PermissionOptionKind::AllowOnce
+                                | agent_client_protocol::PermissionOptionKind::AllowAlways
+                        )
+                    } else {
+                        matches!(
+                            opt.kind,
+                            agent_client_protocol::PermissionOptionKind::RejectOnce
+                                | agent_client_protocol::PermissionOptionKind::RejectAlways
+                        )
+                    }
+                })
+                .map(|opt| opt.option_id.clone())
+                .unwrap_or_else(|| {
+                    PermissionOptionId::new(if approve { "allow_once" } else { "reject_once" })
+                });
+            eprintln!("[ACP] Headless permission answer: {}", option_id);
+            self.events.send(&WorkerPermissionEvent {
+                worker_id: self.worker_id.clone(),
+                title: title.to_string(),
+                tool_call_id: args.tool_call.tool_call_id.to_string(),
+                options: Vec::new(),
+                selected: Some(option_id.to_string()),
+            });
+            return Ok(RequestPermissionResponse::new(
+                RequestPermissionOutcome::Selected(SelectedPermissionOutcome::new(option_id)),
+            ));
+        }
+
         // Create a channel to wait for the user's response
         let (tx, rx) = oneshot::channel::<String>();
 
@@ -229,28 +804,22 @@ impl Client for CrafterClient {
         }
 
         // Emit permission request event to frontend
-        let event_name = format!("worker-permission-{}", self.worker_id);
-        let options: Vec<serde_json::Value> = args
+        let options = args
             .options
             .iter()
-            .map(|opt| {
-                serde_json::json!({
-                    "id": opt.option_id.to_string(),
-                    "name": opt.name,
-                    "kind": format!("{:?}", opt.kind).to_lowercase()
-                })
+            .map(|opt| PermissionOption {
+                id: opt.option_id.to_string(),
+                name: opt.name.clone(),
+                kind: wire_name(&opt.kind),
             })
             .collect();
-
-        let _ = self.app_handle.emit(
-            &event_name,
-            serde_json::json!({
-                "worker_id": self.worker_id,
-                "title": title,
-                "tool_call_id": args.tool_call.tool_call_id.to_string(),
-                "options": options
-            }),
-        );
+        self.events.send(&WorkerPermissionEvent {
+            worker_id: self.worker_id.clone(),
+            title: title.to_string(),
+            tool_call_id: args.tool_call.tool_call_id.to_string(),
+            options,
+            selected: None,
+        });
 
         eprintln!("[ACP] Waiting for user permission response...");
 
@@ -307,6 +876,11 @@ impl Client for CrafterClient {
     ) -> agent_client_protocol::Result<()> {
         eprintln!("[ACP] session_notification: {:?}", args.update);
 
+        // Preserve ordering: pending deltas go out before any other event
+        if !matches!(args.update, SessionUpdate::AgentMessageChunk(_)) {
+            self.delta_batcher.flush();
+        }
+
         match args.update {
             SessionUpdate::AgentMessageChunk(chunk) => {
                 // ContentBlock::Text is a tuple variant: Text(TextContent)
@@ -318,11 +892,30 @@ impl Client for CrafterClient {
                             let mut output_chars = self.total_output_chars.lock();
                             *output_chars += text.len() as u64;
                         }
-                        {
-                            let mut acc = self.accumulated_text.lock();
-                            acc.push_str(&text);
+                        let prompt_id = self.turns.lock().push(&text);
+                        if prompt_id.is_none() {
+                            eprintln!("[ACP] Chunk arrived outside an active prompt, not accumulating");
+                        }
+                        self.delta_batcher.push(&text, prompt_id);
+
+                        // Periodically report generation speed
+                        let now = std::time::Instant::now();
+                        let progress = {
+                            let mut metrics = self.stream_metrics.lock();
+                            if metrics.record(now, text.len() as u64) {
+                                Some(metrics.snapshot(now))
+                            } else {
+                                None
+                            }
+                        };
+                        if let Some(progress) = progress {
+                            self.events.send(&WorkerProgressEvent {
+                                worker_id: self.worker_id.clone(),
+                                session_id: self.session_id.clone(),
+                                prompt_id: self.turns.lock().current(),
+                                progress,
+                            });
                         }
-                        self.emit_event("delta", serde_json::json!({ "text": text }));
                     }
                 }
             }
@@ -330,197 +923,192 @@ impl Client for CrafterClient {
                 if let ContentBlock::Text(text_content) = chunk.content {
                     let text = text_content.text;
                     eprintln!("[ACP] ThoughtChunk: {}", text);
-                    self.emit_event("thinking", serde_json::json!({ "text": text }));
+                    self.turns.lock().push_thinking(&text);
+                    self.emit_event(WorkerEventType::Thinking { text });
                 }
             }
             SessionUpdate::ToolCall(tool_call) => {
-                let event_name = format!("worker-tool-{}", self.worker_id);
-
-                // Convert content from ToolCall
-                let content: Vec<serde_json::Value> = tool_call
-                    .content
-                    .iter()
-                    .map(|c| {
-                        match c {
-                            agent_client_protocol::ToolCallContent::Content(content) => {
-                                if let ContentBlock::Text(text_content) = &content.content {
-                                    serde_json::json!({
-                                        "type": "text",
-                                        "text": text_content.text
-                                    })
-                                } else {
-                                    serde_json::json!({
-                                        "type": "content",
-                                        "text": format!("{:?}", content.content)
-                                    })
-                                }
-                            }
-                            agent_client_protocol::ToolCallContent::Diff(diff) => {
-                                serde_json::json!({
-                                    "type": "diff",
-                                    "path": diff.path,
-                                    "old_text": diff.old_text,
-                                    "new_text": diff.new_text
-                                })
-                            }
-                            agent_client_protocol::ToolCallContent::Terminal(term) => {
-                                serde_json::json!({
-                                    "type": "terminal",
-                                    "terminal_id": term.terminal_id.to_string()
-                                })
-                            }
-                            _ => {
-                                serde_json::json!({
-                                    "type": "unknown",
-                                    "text": format!("{:?}", c)
-                                })
-                            }
-                        }
-                    })
-                    .collect();
+                let content = tool_call_content(&tool_call.content);
 
                 // Extract raw_input for plan mode and other metadata
                 let raw_input = tool_call.raw_input.as_ref().map(|v| v.clone());
 
-                let _ = self.app_handle.emit(
-                    &event_name,
-                    serde_json::json!({
-                        "worker_id": self.worker_id,
-                        "tool_call_id": tool_call.tool_call_id.to_string(),
-                        "title": tool_call.title,
-                        "kind": format!("{:?}", tool_call.kind).to_lowercase(),
-                        "status": format!("{:?}", tool_call.status).to_lowercase(),
-                        "content": content,
-                        "raw_input": raw_input
-                    }),
+                // The agent is already running this call, so rules can only flag it
+                let cwd = self.get_session_cwd();
+                let rules = load_rules(cwd.as_deref());
+                if !rules.is_empty() {
+                    let text = tool_call_text(
+                        &wire_name(&tool_call.kind),
+                        &tool_call.title,
+                        raw_input.as_ref(),
+                    );
+                    let hits = check(&rules, GuardTarget::ToolCall, &text, cwd.as_deref()).await;
+                    emit_hits(&self.events, &self.session_id, &self.worker_id, &hits, false);
+                }
+
+                let status = wire_name(&tool_call.status);
+                let kind = wire_name(&tool_call.kind);
+
+                // Blocks only take effect if the agent asks permission for the call
+                if let Some(pre_tool_use) = self.pre_tool_use(
+                    &tool_call.tool_call_id.to_string(),
+                    Some(kind.as_str()),
+                    raw_input.as_ref(),
+                ) {
+                    let title = tool_call.title.clone();
+                    tokio::task::spawn_local(async move {
+                        if let Some(reason) = pre_tool_use.await {
+                            eprintln!("[ACP] PreToolUse hook blocked '{}': {}", title, reason);
+                        }
+                    });
+                }
+
+                if !matches!(status.as_str(), "completed" | "failed") {
+                    self.write_diffs
+                        .lock()
+                        .call_started(&tool_call.tool_call_id.to_string(), &kind);
+                }
+
+                let diffs = diffs_from_content(&content);
+                self.track_tool_diffs(&diffs, Some(&status));
+                record_tool_call(
+                    &self.session_id,
+                    &self.worker_id,
+                    &tool_call.tool_call_id.to_string(),
+                    Some(status.clone()),
+                    Some(tool_call.title.clone()),
+                    Some(kind.clone()),
+                    raw_input.clone(),
+                    diffs,
                 );
+
+                self.events.send(&WorkerToolEvent {
+                    worker_id: self.worker_id.clone(),
+                    tool_call_id: tool_call.tool_call_id.to_string(),
+                    title: Some(tool_call.title),
+                    kind: Some(kind),
+                    status: Some(status),
+                    content,
+                    raw_input,
+                });
             }
             SessionUpdate::ToolCallUpdate(update) => {
-                let event_name = format!("worker-tool-{}", self.worker_id);
-                // ToolCallUpdate has nested fields - flatten content for frontend
-                let content: Vec<serde_json::Value> = update
+                let content = update
                     .fields
                     .content
-                    .as_ref()
-                    .map(|contents| {
-                        contents
-                            .iter()
-                            .map(|c| {
-                                // Extract text from nested Content structure
-                                match c {
-                                    agent_client_protocol::ToolCallContent::Content(content) => {
-                                        if let ContentBlock::Text(text_content) = &content.content {
-                                            serde_json::json!({
-                                                "type": "text",
-                                                "text": text_content.text
-                                            })
-                                        } else {
-                                            serde_json::json!({
-                                                "type": "content",
-                                                "text": format!("{:?}", content.content)
-                                            })
-                                        }
-                                    }
-                                    agent_client_protocol::ToolCallContent::Diff(diff) => {
-                                        serde_json::json!({
-                                            "type": "diff",
-                                            "path": diff.path,
-                                            "old_text": diff.old_text,
-                                            "new_text": diff.new_text
-                                        })
-                                    }
-                                    agent_client_protocol::ToolCallContent::Terminal(term) => {
-                                        serde_json::json!({
-                                            "type": "terminal",
-                                            "text": term.terminal_id.to_string()
-                                        })
-                                    }
-                                    _ => {
-                                        // Handle any future variants
-                                        serde_json::json!({
-                                            "type": "unknown",
-                                            "text": format!("{:?}", c)
-                                        })
-                                    }
-                                }
-                            })
-                            .collect()
-                    })
+                    .as_deref()
+                    .map(tool_call_content)
                     .unwrap_or_default();
 
-                // Build payload, only include content if not empty
-                let mut payload = serde_json::json!({
-                    "worker_id": self.worker_id,
-                    "tool_call_id": update.tool_call_id.to_string(),
-                    "status": format!("{:?}", update.fields.status).to_lowercase(),
-                    "title": update.fields.title,
-                    "kind": update.fields.kind.as_ref().map(|k| format!("{:?}", k).to_lowercase())
-                });
+                let status = update.fields.status.as_ref().map(wire_name);
+                let kind = update.fields.kind.as_ref().map(wire_name);
+                let diffs = diffs_from_content(&content);
+                self.track_tool_diffs(&diffs, status.as_deref());
+                record_tool_call(
+                    &self.session_id,
+                    &self.worker_id,
+                    &update.tool_call_id.to_string(),
+                    status.clone(),
+                    update.fields.title.clone(),
+                    kind.clone(),
+                    update.fields.raw_input.clone(),
+                    diffs,
+                );
 
-                // Only include content if not empty (to avoid overwriting existing content)
-                if !content.is_empty() {
-                    payload["content"] = serde_json::json!(content);
+                // Shell tool output (e.g. `cargo test` run via Bash)
+                if matches!(status.as_deref(), Some("completed") | Some("failed")) {
+                    let output: Vec<&str> = content
+                        .iter()
+                        .filter_map(|c| match c {
+                            ToolCallContent::Text { text } => Some(text.as_str()),
+                            _ => None,
+                        })
+                        .collect();
+                    if !output.is_empty() {
+                        self.analyze_output(&update.tool_call_id.to_string(), &output.join("\n"));
+                    }
                 }
 
-                // Include raw_input if available (for plan mode, etc.)
-                if let Some(raw_input) = &update.fields.raw_input {
-                    payload["raw_input"] = raw_input.clone();
+                if matches!(status.as_deref(), Some("completed") | Some("failed")) {
+                    let tool_call_id = update.tool_call_id.to_string();
+                    self.pre_tool_hooks.lock().remove(&tool_call_id);
+
+                    // Writes the call made without reporting a diff for them
+                    let reported = get_tool_calls(&self.session_id, &ToolCallFilter::default())
+                        .and_then(|calls| calls.into_iter().find(|call| call.id == tool_call_id))
+                        .map(|call| call.diffs)
+                        .unwrap_or_default();
+                    let writes = self.write_diffs.lock().call_finished(&tool_call_id, &reported);
+                    for write in writes {
+                        self.emit_file_diff(write);
+                    }
+                }
+                if status.as_deref() == Some("completed") {
+                    let tool_response = update.fields.raw_output.clone().unwrap_or_else(|| {
+                        let text: Vec<&str> = content
+                            .iter()
+                            .filter_map(|c| match c {
+                                ToolCallContent::Text { text } => Some(text.as_str()),
+                                _ => None,
+                            })
+                            .collect();
+                        serde_json::Value::String(text.join("\n"))
+                    });
+                    self.spawn_post_tool_hooks(&update.tool_call_id.to_string(), tool_response);
                 }
 
-                let _ = self.app_handle.emit(&event_name, payload);
+                // Empty content leaves what the frontend already shows
+                self.events.send(&WorkerToolEvent {
+                    worker_id: self.worker_id.clone(),
+                    tool_call_id: update.tool_call_id.to_string(),
+                    title: update.fields.title,
+                    kind,
+                    status,
+                    content,
+                    raw_input: update.fields.raw_input,
+                });
             }
             SessionUpdate::Plan(plan) => {
                 // Plan has entries: Vec<PlanEntry>, not title/content
                 // Serialize the entries for the UI
-                let entries: Vec<serde_json::Value> = plan
+                let entries: Vec<PlanEntry> = plan
                     .entries
                     .iter()
-                    .map(|e| {
-                        serde_json::json!({
-                            "content": e.content,
-                            "priority": format!("{:?}", e.priority).to_lowercase(),
-                            "status": format!("{:?}", e.status).to_lowercase()
-                        })
+                    .map(|e| PlanEntry {
+                        content: e.content.clone(),
+                        priority: wire_name(&e.priority),
+                        status: wire_name(&e.status),
                     })
                     .collect();
-                self.emit_event(
-                    "plan",
-                    serde_json::json!({
-                        "entries": entries
-                    }),
-                );
+                self.import_plan(&entries);
+                self.emit_event(WorkerEventType::Plan { entries });
             }
             SessionUpdate::AvailableCommandsUpdate(cmds) => {
-                let event_name = format!("worker-commands-{}", self.worker_id);
-                let _ = self.app_handle.emit(
-                    &event_name,
-                    serde_json::json!({
-                        "worker_id": self.worker_id,
-                        "commands": cmds.available_commands
-                    }),
-                );
+                let commands = cmds
+                    .available_commands
+                    .iter()
+                    .filter_map(|command| serde_json::to_value(command).ok())
+                    .collect();
+                self.events.send(&WorkerCommandsEvent {
+                    worker_id: self.worker_id.clone(),
+                    commands,
+                });
             }
             SessionUpdate::CurrentModeUpdate(mode) => {
-                let event_name = format!("worker-mode-{}", self.worker_id);
-                let _ = self.app_handle.emit(
-                    &event_name,
-                    serde_json::json!({
-                        "worker_id": self.worker_id,
-                        "mode_id": mode.current_mode_id.to_string()
-                    }),
-                );
+                *self.session_mode.lock() = Some(mode.current_mode_id.to_string());
+                set_current_mode(&self.session_id, &mode.current_mode_id.to_string());
+                self.events.send(&WorkerModeEvent {
+                    worker_id: self.worker_id.clone(),
+                    mode_id: mode.current_mode_id.to_string(),
+                });
             }
             SessionUpdate::UserMessageChunk(chunk) => {
                 // Echo user message chunks back to frontend (for multi-part messages)
                 if let ContentBlock::Text(text_content) = chunk.content {
-                    let event_name = format!("worker-user-message-{}", self.worker_id);
-                    let _ = self.app_handle.emit(
-                        &event_name,
-                        serde_json::json!({
-                            "worker_id": self.worker_id,
-                            "text": text_content.text
-                        }),
-                    );
+                    self.events.send(&WorkerUserMessageEvent {
+                        worker_id: self.worker_id.clone(),
+                        text: text_content.text,
+                    });
                 }
             }
             _ => {
@@ -541,9 +1129,14 @@ impl Client for CrafterClient {
             args.path, args.line, args.limit
         );
 
-        let content = std::fs::read_to_string(&args.path).map_err(|e| {
-            agent_client_protocol::Error::new(-32000, format!("Failed to read file: {}", e))
-        })?;
+        let path = args.path.clone();
+        let content = blocking_fs(
+            "read file",
+            FS_OP_TIMEOUT,
+            move || std::fs::read_to_string(path),
+            |_| {},
+        )
+        .await?;
 
         // Apply line/limit if specified
         let result = match (args.line, args.limit) {
@@ -576,9 +1169,42 @@ impl Client for CrafterClient {
             args.content.len()
         );
 
-        std::fs::write(&args.path, &args.content).map_err(|e| {
-            agent_client_protocol::Error::new(-32000, format!("Failed to write file: {}", e))
-        })?;
+        if let Some(mode) = self.read_only_mode() {
+            return Err(self.refuse_read_only(
+                &mode,
+                "fs/write_text_file",
+                &args.path.to_string_lossy(),
+            ));
+        }
+
+        let session_id = self.session_id.clone();
+        let cwd = self.get_session_cwd();
+        let path = args.path.clone();
+        let content = args.content.clone();
+        // A write that times out may still land; it's diffed all the same
+        let late = {
+            let client = self.clone();
+            let path = args.path.to_string_lossy().to_string();
+            let content = args.content.clone();
+            move |old_text| client.record_write(&path, old_text, content)
+        };
+        let old_text = blocking_fs(
+            "write file",
+            FS_OP_TIMEOUT,
+            move || {
+                // Capture the original content for the session patch before the first write
+                track_before_write(&session_id, cwd, &path.to_string_lossy());
+
+                // Snapshot for agents that don't report the change as a diff
+                let old_text = std::fs::read_to_string(&path).ok();
+                std::fs::write(&path, content)?;
+                Ok(old_text)
+            },
+            late,
+        )
+        .await?;
+
+        self.record_write(&args.path.to_string_lossy(), old_text, args.content);
 
         Ok(WriteTextFileResponse::new())
     }
@@ -604,9 +1230,22 @@ impl Client for CrafterClient {
             return self.handle_swarm_terminal(&full_command);
         }
 
+        if let Some(mode) = self.read_only_mode() {
+            if !is_read_only_command(&full_command) {
+                return Err(self.refuse_read_only(&mode, "command", &full_command));
+            }
+        }
+        self.guard(GuardTarget::Command, &full_command).await?;
+
         // Use shell to execute the command (handles commands like "ls -la" properly)
-        let mut cmd = std::process::Command::new("/bin/sh");
-        cmd.args(["-c", &full_command]);
+        let isolated = self.network_isolated();
+        let mut cmd = if isolated {
+            self.network_isolated_shell(&full_command)?
+        } else {
+            let mut cmd = std::process::Command::new("/bin/sh");
+            cmd.args(["-c", &full_command]);
+            cmd
+        };
 
         // Use request's cwd, or fall back to session's cwd
         let effective_cwd: Option<std::path::PathBuf> = args.cwd.clone().or_else(|| {
@@ -615,6 +1254,11 @@ impl Client for CrafterClient {
         if let Some(cwd) = &effective_cwd {
             eprintln!("[ACP] terminal using cwd: {}", cwd.display());
             cmd.current_dir(cwd);
+            // Project .env variables (opt-in), below the agent's own env
+            cmd.envs(project_env(&cwd.to_string_lossy()));
+        }
+        if let Some(dir) = scratch::path(&self.session_id) {
+            cmd.env(scratch::ENV_VAR, dir);
         }
         for env_var in &args.env {
             cmd.env(&env_var.name, &env_var.value);
@@ -627,13 +1271,26 @@ impl Client for CrafterClient {
         })?;
 
         let terminal_id = format!("term_{}", child.id());
+        resource_limits::watch_terminal(child.id(), &self.worker_id, &self.session_id, &terminal_id);
         {
             let mut terminals = self.terminals.lock();
             terminals.insert(terminal_id.clone(), child);
         }
 
+        if load_settings().record_agent_terminals {
+            if let Err(e) = recording::start(
+                &terminal_id,
+                Some(self.session_id.clone()),
+                80,
+                24,
+                Some(&full_command),
+            ) {
+                eprintln!("[ACP] Failed to record terminal {}: {}", terminal_id, e);
+            }
+        }
+
         // Emit terminal created event for frontend tracking
-        let _ = self.app_handle.emit(
+        let _ = self.events.emit(
             "terminal-created",
             serde_json::json!({
                 "terminal_id": terminal_id,
@@ -643,6 +1300,7 @@ impl Client for CrafterClient {
                 "args": args.args,
                 "cwd": args.cwd,
                 "running": true,
+                "network_isolated": isolated,
                 "timestamp": chrono::Utc::now().timestamp_millis()
             }),
         );
@@ -657,6 +1315,9 @@ impl Client for CrafterClient {
         eprintln!("[ACP] terminal/output: terminalId={}", args.terminal_id);
 
         let terminal_id_str = args.terminal_id.0.as_ref().to_string();
+        if service::is_service(&terminal_id_str) {
+            return self.service_output(&terminal_id_str);
+        }
         let mut terminals = self.terminals.lock();
         let child = terminals
             .get_mut(args.terminal_id.0.as_ref())
@@ -687,8 +1348,50 @@ impl Client for CrafterClient {
             Err(_) => false,
         };
 
+        // A secret can be split across reads: hold back what could be the
+        // start of one until the next read, or until the command exits
+        let output = {
+            let redactor = redactor_for_worker(&self.worker_id);
+            let mut streams = self.terminal_streams.lock();
+            if is_running {
+                streams
+                    .entry(terminal_id_str.clone())
+                    .or_default()
+                    .push(&redactor, &output)
+            } else {
+                let mut stream = streams.remove(&terminal_id_str).unwrap_or_default();
+                let mut text = stream.push(&redactor, &output);
+                text.push_str(&stream.flush(&redactor));
+                text
+            }
+        };
+        if !output.is_empty() {
+            recording::record_output(&terminal_id_str, &output);
+            let mut outputs = self.terminal_outputs.lock();
+            let collected = outputs.entry(terminal_id_str.clone()).or_default();
+            collected.push_str(&output);
+            // Rescan a little of the previous chunk: reads can split a URL
+            let mut from = collected.len().saturating_sub(output.len() + 256);
+            while !collected.is_char_boundary(from) {
+                from += 1;
+            }
+            let detected = dev_servers::record(
+                &self.session_id,
+                &terminal_id_str,
+                Some(&self.worker_id),
+                &collected[from..],
+            );
+            drop(outputs);
+            for event in &detected {
+                self.events.send(event);
+            }
+        }
+        if !is_running {
+            self.finish_terminal_output(&terminal_id_str);
+        }
+
         // Emit terminal output event for frontend tracking
-        let _ = self.app_handle.emit(
+        let _ = self.events.emit(
             "terminal-output",
             serde_json::json!({
                 "terminal_id": terminal_id_str,
@@ -728,9 +1431,11 @@ impl Client for CrafterClient {
         })?;
 
         let exit_code = status.code().map(|c| c as u32);
+        recording::stop(&terminal_id_str);
+        self.finish_terminal_output(&terminal_id_str);
 
         // Emit terminal exited event for frontend tracking
-        let _ = self.app_handle.emit(
+        let _ = self.events.emit(
             "terminal-exited",
             serde_json::json!({
                 "terminal_id": terminal_id_str,
@@ -759,7 +1464,7 @@ impl Client for CrafterClient {
         }
 
         // Emit terminal killed event for frontend tracking
-        let _ = self.app_handle.emit(
+        let _ = self.events.emit(
             "terminal-killed",
             serde_json::json!({
                 "terminal_id": terminal_id_str,
@@ -780,10 +1485,15 @@ impl Client for CrafterClient {
 
         let terminal_id_str = args.terminal_id.0.as_ref().to_string();
         let mut terminals = self.terminals.lock();
-        terminals.remove(args.terminal_id.0.as_ref());
+        if let Some(child) = terminals.remove(args.terminal_id.0.as_ref()) {
+            resource_limits::unwatch(child.id());
+        }
+        recording::stop(&terminal_id_str);
+        self.terminal_outputs.lock().remove(&terminal_id_str);
+        self.terminal_streams.lock().remove(&terminal_id_str);
 
         // Emit terminal released event for frontend tracking
-        let _ = self.app_handle.emit(
+        let _ = self.events.emit(
             "terminal-released",
             serde_json::json!({
                 "terminal_id": terminal_id_str,
@@ -800,6 +1510,52 @@ impl Client for CrafterClient {
 // High-level ACP Client wrapper
 // ============================================================================
 
+/// Longest an agent's file read or write may take before it's reported as
+/// failed (the IO itself can't be interrupted, but the worker moves on)
+const FS_OP_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Run file IO for the agent on the blocking pool, so a slow disk or hung
+/// network mount doesn't stall the worker's LocalSet thread. Past `timeout`
+/// the agent is told the operation failed, but the IO itself keeps going:
+/// if it still succeeds, `late` gets its result.
+async fn blocking_fs<T, F, L>(
+    op: &str,
+    timeout: std::time::Duration,
+    f: F,
+    late: L,
+) -> agent_client_protocol::Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> std::io::Result<T> + Send + 'static,
+    L: FnOnce(T) + 'static,
+{
+    let mut task = tokio::task::spawn_blocking(f);
+    match tokio::time::timeout(timeout, &mut task).await {
+        Ok(Ok(Ok(value))) => Ok(value),
+        Ok(Ok(Err(e))) => Err(agent_client_protocol::Error::new(
+            -32000,
+            format!("Failed to {}: {}", op, e),
+        )),
+        Ok(Err(e)) => Err(agent_client_protocol::Error::new(
+            -32000,
+            format!("Failed to {}: {}", op, e),
+        )),
+        Err(_) => {
+            let op = op.to_string();
+            tokio::task::spawn_local(async move {
+                if let Ok(Ok(value)) = task.await {
+                    eprintln!("[ACP] {} finished after timing out", op);
+                    late(value);
+                }
+            });
+            Err(agent_client_protocol::Error::new(
+                -32000,
+                format!("Failed to {}: timed out after {}s", op, timeout.as_secs()),
+            ))
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
 pub enum AcpError {
@@ -828,17 +1584,28 @@ impl std::fmt::Display for AcpError {
 
 impl std::error::Error for AcpError {}
 
+impl AcpError {
+    /// The kind of transient failure this is, for errors worth retrying.
+    /// Only prompt failures qualify; anything else means the agent itself
+    /// is broken.
+    pub fn transient(&self) -> Option<TransientError> {
+        match self {
+            AcpError::PromptFailed(e) => retry::classify(e),
+            _ => None,
+        }
+    }
+}
+
 /// ACP client wrapper that manages the connection lifecycle
 pub struct AcpClient {
     connection: ClientSideConnection,
     acp_session_id: Option<agent_client_protocol::SessionId>,
     process: tokio::process::Child,
-    accumulated_text: Arc<Mutex<String>>,
+    turns: Arc<Mutex<TurnAccumulator>>,
     /// Shared session cwd (for terminal commands to use)
     session_cwd: Arc<Mutex<Option<String>>>,
-    app_handle: AppHandle,
+    events: EventSink,
     worker_id: String,
-    #[allow(dead_code)]
     session_id: String,
     /// Authentication methods supported by the agent (from InitializeResponse)
     auth_methods: Vec<AuthMethod>,
@@ -850,6 +1617,26 @@ pub struct AcpClient {
     total_input_chars: Arc<Mutex<u64>>,
     /// Total output characters (for token estimation)
     total_output_chars: Arc<Mutex<u64>>,
+    /// Delta timing shared with the CrafterClient
+    stream_metrics: Arc<Mutex<StreamMetrics>>,
+    /// Delta batcher shared with the CrafterClient (flushed before completion)
+    delta_batcher: Arc<DeltaBatcher>,
+    /// Output of the most recently completed turn
+    last_output: Mutex<TurnOutput>,
+    /// Registry agent id, for the usage ledger
+    agent_id: String,
+    /// Model requested at spawn, for the usage ledger
+    model: String,
+    /// Estimated context size of the current ACP session
+    context: Mutex<ContextTracker>,
+    /// Current mode, shared with the CrafterClient for read-only enforcement
+    session_mode: Arc<Mutex<Option<String>>>,
+    /// Modes the current ACP session offers
+    available_modes: Mutex<Vec<SessionModeInfo>>,
+    /// Agent id as seen by the CrafterClient
+    handler_agent_id: Arc<Mutex<Option<String>>>,
+    /// PRD session access shared with the CrafterClient
+    prd_access: Arc<Mutex<Option<(Arc<PrdManager>, String)>>>,
 }
 
 impl AcpClient {
@@ -861,32 +1648,42 @@ impl AcpClient {
         env_vars: &[String],
         model: Option<String>,
         model_env_var: Option<String>,
-        app_handle: AppHandle,
+        events: impl Into<EventSink>,
         worker_id: String,
         session_id: String,
         task_manager: Option<Arc<TaskManager>>,
         inbox_manager: Option<Arc<InboxManager>>,
     ) -> Result<Self, AcpError> {
+        let events = events.into();
+        set_worker_project(&worker_id, cwd);
         let mut cmd = Command::new(command);
         cmd.args(args)
             .current_dir(cwd)
             .stdin(std::process::Stdio::piped())
             .stdout(std::process::Stdio::piped())
-            .stderr(std::process::Stdio::inherit())
+            .stderr(std::process::Stdio::piped())
             // Inherit ALL environment variables from parent process
             .envs(std::env::vars());
 
         // Set model environment variable if provided
         if let (Some(model_id), Some(env_var)) = (&model, &model_env_var) {
             cmd.env(env_var, model_id);
-            println!("[ACP] Setting {} = {}", env_var, model_id);
+            eprintln!("[ACP] Setting {} = {}", env_var, model_id);
+        }
+
+        // Project .env variables (opt-in), below the app's own environment
+        cmd.envs(project_env(cwd));
+
+        // Where the agent keeps temporary files instead of the project
+        if let Some(dir) = scratch::provision(&session_id) {
+            cmd.env(scratch::ENV_VAR, dir);
         }
 
         // Set any additional env vars from registry
         for env_var in env_vars {
             // These are just the required env var names, not values
-            // Values come from user's environment
-            if let Ok(value) = std::env::var(env_var) {
+            // Values come from user's environment, or the keychain
+            if let Some(value) = env_or_secret(env_var) {
                 cmd.env(env_var, value);
             }
         }
@@ -894,6 +1691,9 @@ impl AcpClient {
         let mut process = cmd
             .spawn()
             .map_err(|e| AcpError::SpawnFailed(format!("{}: {}", command, e)))?;
+        if let Some(pid) = process.id() {
+            resource_limits::watch_agent(pid, &worker_id, &session_id);
+        }
 
         let stdin = process
             .stdin
@@ -905,12 +1705,18 @@ impl AcpClient {
             .take()
             .ok_or_else(|| AcpError::SpawnFailed("Failed to capture stdout".to_string()))?;
 
-        // Use tokio-util compat layer to convert tokio AsyncRead/Write to futures traits
-        let stdin_compat = stdin.compat_write();
-        let stdout_compat = stdout.compat();
+        // Agent diagnostics go to the worker's log instead of our stderr
+        if let Some(stderr) = process.stderr.take() {
+            agent_log::capture(stderr, events.clone(), session_id.clone(), worker_id.clone());
+        }
+
+        // Use tokio-util compat layer to convert tokio AsyncRead/Write to futures traits,
+        // tapped so `set_protocol_trace` can record the traffic
+        let stdin_compat = TracedWriter::new(stdin.compat_write(), &session_id, &worker_id);
+        let stdout_compat = TracedReader::new(stdout.compat(), &session_id, &worker_id);
 
         // Create our client implementation with coordination support
-        let mut client = CrafterClient::new(app_handle.clone(), worker_id.clone(), session_id.clone());
+        let mut client = CrafterClient::new(events.clone(), worker_id.clone(), session_id.clone());
 
         // Enable swarm coordination if managers are provided
         if let (Some(tm), Some(im)) = (task_manager, inbox_manager) {
@@ -918,10 +1724,15 @@ impl AcpClient {
         }
 
         // Extract Arcs before moving client into connection
-        let accumulated_text = client.accumulated_text.clone();
+        let turns = client.turns.clone();
         let session_cwd = client.session_cwd.clone();
         let total_input_chars = client.total_input_chars.clone();
         let total_output_chars = client.total_output_chars.clone();
+        let stream_metrics = client.stream_metrics.clone();
+        let delta_batcher = client.delta_batcher.clone();
+        let session_mode = client.session_mode.clone();
+        let handler_agent_id = client.agent_id.clone();
+        let prd_access = client.prd_access.clone();
 
         // Create the connection using the official crate with futures-compatible streams
         let (connection, io_task) = ClientSideConnection::new(
@@ -944,9 +1755,9 @@ impl AcpClient {
             connection,
             acp_session_id: None,
             process,
-            accumulated_text,
+            turns,
             session_cwd,
-            app_handle,
+            events,
             worker_id,
             session_id,
             auth_methods: Vec::new(),
@@ -954,6 +1765,16 @@ impl AcpClient {
             agent_capabilities: None,
             total_input_chars,
             total_output_chars,
+            stream_metrics,
+            delta_batcher,
+            last_output: Mutex::new(TurnOutput::default()),
+            agent_id: "unknown".to_string(),
+            context: Mutex::new(ContextTracker::new(model.as_deref().unwrap_or("default"))),
+            model: model.unwrap_or_else(|| "default".to_string()),
+            session_mode,
+            available_modes: Mutex::new(Vec::new()),
+            handler_agent_id,
+            prd_access,
         })
     }
 
@@ -980,6 +1801,30 @@ impl AcpClient {
         self.auth_methods = response.auth_methods.clone();
         self.agent_capabilities = Some(response.agent_capabilities.clone());
 
+        let prompt_caps = &response.agent_capabilities.prompt_capabilities;
+        if self.agent_id != "unknown" {
+            record_handshake(
+                &self.agent_id,
+                AgentCapabilityInfo {
+                    load_session: response.agent_capabilities.load_session,
+                    image: prompt_caps.image,
+                    audio: prompt_caps.audio,
+                    embedded_context: prompt_caps.embedded_context,
+                    modes: Vec::new(),
+                    auth_methods: response
+                        .auth_methods
+                        .iter()
+                        .map(|method| AuthMethodInfo {
+                            id: method.id.to_string(),
+                            name: method.name.clone(),
+                            description: method.description.clone(),
+                        })
+                        .collect(),
+                    checked_at: chrono::Utc::now().timestamp(),
+                },
+            );
+        }
+
         // If no auth methods required, mark as authenticated
         if self.auth_methods.is_empty() {
             self.is_authenticated = true;
@@ -1071,10 +1916,12 @@ impl AcpClient {
             agent_client_protocol::SessionId::new(session_id),
             cwd.clone(),
         );
-        self.connection
+        let load_response = self
+            .connection
             .load_session(request)
             .await
             .map_err(|e: agent_client_protocol::Error| AcpError::SessionFailed(e.to_string()))?;
+        self.track_modes(load_response.modes);
 
         let acp_session_id = agent_client_protocol::SessionId::new(session_id_for_return.clone());
         eprintln!("[ACP] Session loaded: {} with cwd: {}", acp_session_id, cwd);
@@ -1082,6 +1929,47 @@ impl AcpClient {
         Ok(session_id_for_return)
     }
 
+    /// Remember the session's current mode and the modes on offer, and
+    /// cache them for the agent
+    fn track_modes(&self, modes: Option<SessionModeState>) {
+        let Some(modes) = modes else {
+            *self.session_mode.lock() = None;
+            self.available_modes.lock().clear();
+            set_session_modes(&self.session_id, SessionModes::default());
+            return;
+        };
+        let current = modes.current_mode_id.to_string();
+        let available: Vec<SessionModeInfo> = modes
+            .available_modes
+            .iter()
+            .map(|mode| SessionModeInfo {
+                id: mode.id.to_string(),
+                name: mode.name.clone(),
+            })
+            .collect();
+        *self.session_mode.lock() = Some(current.clone());
+        *self.available_modes.lock() = available.clone();
+        set_session_modes(
+            &self.session_id,
+            SessionModes {
+                current: Some(current),
+                available: available.clone(),
+            },
+        );
+        if self.agent_id == "unknown" {
+            return;
+        }
+        record_modes(&self.agent_id, available);
+    }
+
+    /// The current mode and the modes the session offers
+    pub fn session_modes(&self) -> SessionModes {
+        SessionModes {
+            current: self.session_mode.lock().clone(),
+            available: self.available_modes.lock().clone(),
+        }
+    }
+
     /// Create a new session
     pub async fn create_acp_session(&mut self, cwd: &str) -> Result<String, AcpError> {
         eprintln!("[AcpClient::create_acp_session] Creating session with cwd: {}", cwd);
@@ -1098,6 +1986,8 @@ impl AcpClient {
         let acp_session_id = session_response.session_id;
         eprintln!("[ACP] ACP Session created: {} with cwd: {}", acp_session_id, cwd);
         self.acp_session_id = Some(acp_session_id.clone());
+        self.context.lock().reset();
+        self.track_modes(session_response.modes);
         Ok(acp_session_id.to_string())
     }
 
@@ -1125,8 +2015,20 @@ impl AcpClient {
             .clone()
             .ok_or_else(|| AcpError::PromptFailed("No active ACP session".to_string()))?;
 
+        // Don't spend a prompt on an agent that's still rate limited
+        if let Some(window) = rate_limits::remaining(&self.agent_id, &self.model) {
+            eprintln!(
+                "[ACP] {} is rate limited, waiting {:?} before prompting",
+                self.agent_id, window
+            );
+            tokio::select! {
+                _ = tokio::time::sleep(window) => {}
+                _ = cancel_rx.recv() => return Err(AcpError::Cancelled),
+            }
+        }
+
         // Track input characters for token estimation
-        let input_chars: u64 = content.iter().map(|block| {
+        let turn_input_chars: u64 = content.iter().map(|block| {
             match block {
                 ContentBlock::Text(text) => text.text.len() as u64,
                 _ => 0, // Images/audio don't count as text chars
@@ -1134,8 +2036,16 @@ impl AcpClient {
         }).sum();
         {
             let mut total = self.total_input_chars.lock();
-            *total += input_chars;
+            *total += turn_input_chars;
         }
+        let output_chars_before = *self.total_output_chars.lock();
+
+        self.stream_metrics.lock().reset(std::time::Instant::now());
+        let started = std::time::SystemTime::now();
+        let timer = std::time::Instant::now();
+
+        // Open a new turn; chunks are accumulated under this id until completion
+        let prompt_id = self.turns.lock().begin();
 
         let prompt_request = PromptRequest::new(acp_session_id.clone(), content);
 
@@ -1150,9 +2060,32 @@ impl AcpClient {
                 Err(AcpError::Cancelled)
             }
         };
+        let outcome = match &result {
+            Ok(_) => "ok",
+            Err(AcpError::Cancelled) => "cancelled",
+            Err(_) => "error",
+        };
+        telemetry::prompt_finished(&self.agent_id, &self.model, outcome, started, timer.elapsed());
+
+        // Flush any buffered deltas so they arrive before the complete event
+        self.delta_batcher.flush();
 
         // Emit completion event with estimated token usage
-        let final_text = self.accumulated_text.lock().clone();
+        let mut turn_output = self.turns.lock().finish(&prompt_id);
+        let redactor = redactor_for_worker(&self.worker_id);
+        turn_output.text = redactor.redact(&turn_output.text).into_owned();
+        turn_output.thinking = redactor.redact(&turn_output.thinking).into_owned();
+        *self.last_output.lock() = turn_output.clone();
+
+        // Output rules run on the whole response, after the fact
+        let cwd = self.session_cwd.lock().clone();
+        let rules = load_rules(cwd.as_deref());
+        if !rules.is_empty() {
+            let hits = check(&rules, GuardTarget::Output, &turn_output.text, cwd.as_deref()).await;
+            emit_hits(&self.events, &self.session_id, &self.worker_id, &hits, false);
+        }
+
+        let thinking = Some(turn_output.thinking).filter(|t| !t.is_empty());
 
         // Estimate tokens: ~4 chars = 1 token for Claude models
         let input_chars = *self.total_input_chars.lock();
@@ -1160,44 +2093,292 @@ impl AcpClient {
         let estimated_input_tokens = (input_chars / 4).max(1);
         let estimated_output_tokens = (output_chars / 4).max(1);
 
-        // Estimate cost (using Sonnet 4 pricing as default: $3/1M input, $15/1M output)
-        let estimated_cost = (estimated_input_tokens as f64 * 3.0 / 1_000_000.0)
-            + (estimated_output_tokens as f64 * 15.0 / 1_000_000.0);
+        let pricing = crate::claude::pricing::pricing_registry();
+        let estimated_cost = pricing.cost(
+            Some(&self.agent_id),
+            &self.model,
+            estimated_input_tokens,
+            estimated_output_tokens,
+        );
 
-        let event_name = format!("worker-stream-{}", self.worker_id);
-        let _ = self.app_handle.emit(
-            &event_name,
-            serde_json::json!({
-                "worker_id": self.worker_id,
-                "event": {
-                    "type": "complete",
-                    "output": final_text,
-                    "usage": {
-                        "input_tokens": estimated_input_tokens,
-                        "output_tokens": estimated_output_tokens,
-                        "estimated": true
+        // The ledger gets this turn's share rather than the running totals
+        let turn_input_tokens = turn_input_chars / 4;
+        let turn_output_tokens = output_chars.saturating_sub(output_chars_before) / 4;
+        crate::stats::record_usage(crate::stats::UsageRecord {
+            timestamp: chrono::Utc::now().timestamp(),
+            session_id: self.session_id.clone(),
+            agent_id: self.agent_id.clone(),
+            model: self.model.clone(),
+            input_tokens: turn_input_tokens,
+            output_tokens: turn_output_tokens,
+            cost_usd: pricing.cost(
+                Some(&self.agent_id),
+                &self.model,
+                turn_input_tokens,
+                turn_output_tokens,
+            ),
+            estimated: true,
+        });
+
+        let context_warning = self.context.lock().add(turn_input_tokens + turn_output_tokens);
+        let context = self.context.lock().usage();
+
+        let metrics = self.stream_metrics.lock().snapshot(std::time::Instant::now());
+
+        // Already redacted above
+        self.events.send(&WorkerStreamEvent {
+            worker_id: self.worker_id.clone(),
+            event: WorkerEvent {
+                prompt_id: Some(prompt_id),
+                kind: WorkerEventType::Complete {
+                    output: turn_output.text.clone(),
+                    thinking,
+                    usage: TokenUsage {
+                        input_tokens: estimated_input_tokens,
+                        output_tokens: estimated_output_tokens,
+                        estimated: true,
                     },
-                    "cost_usd": estimated_cost
-                }
-            }),
-        );
+                    cost_usd: Some(estimated_cost),
+                    metrics: Some(metrics),
+                    context: Some(context),
+                },
+            },
+        });
 
-        // Clear accumulated text for next prompt (but keep cumulative token counts)
-        {
-            let mut acc = self.accumulated_text.lock();
-            acc.clear();
+        if let Some(usage) = context_warning {
+            self.events.send(&ContextWarningEvent {
+                session_id: self.session_id.clone(),
+                worker_id: self.worker_id.clone(),
+                usage,
+            });
+        }
+
+        if result.is_ok() {
+            rate_limits::record_success(&self.events, &self.agent_id, &self.model);
+        }
+
+        if let (true, Some(app_handle)) = (result.is_ok(), self.events.app_handle()) {
+            notify(
+                app_handle,
+                NotificationEvent::PromptCompleted,
+                Some(&self.session_id),
+                "Agent finished",
+                &turn_output.text,
+            );
         }
 
         result.map(|r| r.stop_reason)
     }
 
+    /// Send a prompt, sending it again after a backoff while it fails with a
+    /// transient error (rate limit, overload, network)
+    pub async fn prompt_with_retry(
+        &self,
+        content: Vec<ContentBlock>,
+        cancel_rx: &mut mpsc::Receiver<()>,
+    ) -> Result<StopReason, AcpError> {
+        let settings = load_settings().prompt_retry;
+        let mut attempt = 0;
+        loop {
+            let result = self.prompt_with_content(content.clone(), cancel_rx).await;
+            let retryable = match &result {
+                Err(e) if attempt < settings.max_attempts => {
+                    e.transient().map(|kind| (kind, e.to_string()))
+                }
+                _ => None,
+            };
+            let Some((kind, error)) = retryable else {
+                return result;
+            };
+            attempt += 1;
+            let mut delay = retry::backoff(
+                kind,
+                attempt,
+                std::time::Duration::from_secs(settings.base_delay_secs),
+                std::time::Duration::from_secs(settings.max_delay_secs),
+            );
+            if kind == TransientError::RateLimit {
+                rate_limits::record_hit(&self.events, &self.agent_id, &self.model, &error);
+                if let Some(window) = rate_limits::remaining(&self.agent_id, &self.model) {
+                    delay = delay.max(window);
+                }
+            }
+            eprintln!(
+                "[ACP] Transient prompt failure ({:?}), retry {}/{} in {:?}: {}",
+                kind, attempt, settings.max_attempts, delay, error
+            );
+            self.events.send(&WorkerRetryEvent {
+                session_id: self.session_id.clone(),
+                worker_id: self.worker_id.clone(),
+                kind,
+                attempt,
+                max_attempts: settings.max_attempts,
+                delay_secs: delay.as_secs(),
+                error: redactor_for_worker(&self.worker_id).redact(&error).into_owned(),
+            });
+
+            tokio::select! {
+                _ = tokio::time::sleep(delay) => {}
+                _ = cancel_rx.recv() => return Err(AcpError::Cancelled),
+            }
+        }
+    }
+
+    /// Send a user prompt. The project's `pre-prompt` and `on-complete` hooks
+    /// run around each turn, and the session's acceptance criteria are
+    /// checked after it; failures go back to the agent as follow-up turns
+    /// when the session asks for that.
+    pub async fn run_user_prompt(
+        &self,
+        content: Vec<ContentBlock>,
+        cancel_rx: &mut mpsc::Receiver<()>,
+    ) -> Result<StopReason, AcpError> {
+        let cwd = self.session_cwd.lock().clone();
+        let mut content = content;
+        let mut follow_ups = 0;
+        let mut stop_continuations = 0;
+        loop {
+            if let Some(cwd) = &cwd {
+                run_hooks(
+                    &self.events,
+                    &self.session_id,
+                    &self.worker_id,
+                    cwd,
+                    &[PRE_PROMPT],
+                    &[],
+                )
+                .await;
+            }
+
+            let result = self.prompt_with_retry(content, cancel_rx).await;
+            let Ok(stop_reason) = &result else {
+                return result;
+            };
+
+            if let Some(cwd) = &cwd {
+                let stop_reason = format!("{:?}", stop_reason).to_lowercase();
+                run_hooks(
+                    &self.events,
+                    &self.session_id,
+                    &self.worker_id,
+                    cwd,
+                    &[ON_COMPLETE],
+                    &[("CRAFTER_STOP_REASON", stop_reason.as_str())],
+                )
+                .await;
+            }
+
+            // A Stop hook can send the agent back to work
+            if let Some(cwd) = cwd.as_deref().filter(|cwd| bridged(Some(&self.agent_id), cwd)) {
+                let outcome = run_claude_hooks(
+                    &self.events,
+                    &self.session_id,
+                    &self.worker_id,
+                    cwd,
+                    HookEvent::Stop,
+                    None,
+                    serde_json::json!({ "stop_hook_active": stop_continuations > 0 }),
+                )
+                .await;
+                if let Some(reason) = outcome.blocked {
+                    if stop_continuations < MAX_STOP_CONTINUATIONS {
+                        stop_continuations += 1;
+                        content = vec![ContentBlock::Text(TextContent::new(reason))];
+                        continue;
+                    }
+                    eprintln!("[ACP] Stop hook blocked again, not continuing: {}", reason);
+                }
+            }
+            stop_continuations = 0;
+
+            let follow_up = check_session(
+                &self.events,
+                &self.session_id,
+                &self.worker_id,
+                cwd.as_deref(),
+                follow_ups,
+            )
+            .await;
+            let Some(follow_up) = follow_up else {
+                return result;
+            };
+            follow_ups += 1;
+            content = vec![ContentBlock::Text(TextContent::new(follow_up))];
+        }
+    }
+
+    /// Registry agent id reported in usage stats
+    pub fn set_agent_id(&mut self, agent_id: &str) {
+        self.agent_id = agent_id.to_string();
+        *self.handler_agent_id.lock() = Some(agent_id.to_string());
+    }
+
+    /// Let the agent read and re-prioritize a PRD session through
+    /// `swarm prd` commands
+    pub fn set_prd_access(&mut self, manager: Arc<PrdManager>, session_id: &str) {
+        *self.prd_access.lock() = Some((manager, session_id.to_string()));
+    }
+
+    /// ACP session id, once a session has been created or loaded
+    pub fn acp_session_id(&self) -> Option<String> {
+        self.acp_session_id.as_ref().map(|id| id.to_string())
+    }
+
+    /// Estimated context size of the current ACP session
+    pub fn context_usage(&self) -> ContextUsage {
+        self.context.lock().usage()
+    }
+
+    /// Have the agent summarize the conversation, then continue in a fresh
+    /// ACP session seeded with that summary. Returns the summary.
+    pub async fn compact(
+        &mut self,
+        cwd: &str,
+        cancel_rx: &mut mpsc::Receiver<()>,
+    ) -> Result<String, AcpError> {
+        self.prompt(COMPACT_PROMPT, cancel_rx).await?;
+        let summary = self.last_output().text.trim().to_string();
+        if summary.is_empty() {
+            return Err(AcpError::PromptFailed("Agent returned an empty summary".to_string()));
+        }
+
+        let seed = format!("{}{}", COMPACT_SEED_PREFIX, summary);
+        self.reseed(cwd, Some(&seed), cancel_rx).await?;
+        Ok(summary)
+    }
+
+    /// Continue in a fresh ACP session, first sending `seed` (e.g. replayed
+    /// history) if given
+    pub async fn reseed(
+        &mut self,
+        cwd: &str,
+        seed: Option<&str>,
+        cancel_rx: &mut mpsc::Receiver<()>,
+    ) -> Result<(), AcpError> {
+        self.create_acp_session(cwd).await?;
+        if let Some(seed) = seed {
+            self.prompt(seed, cancel_rx).await?;
+        }
+        Ok(())
+    }
+
+    /// Text and thinking of the most recently completed turn
+    pub fn last_output(&self) -> TurnOutput {
+        self.last_output.lock().clone()
+    }
+
     /// Set the session mode (e.g., "plan", "normal", "code")
-    /// Uses the official ACP session/set_mode method
+    /// Uses the official ACP session/set_mode method; modes the session
+    /// didn't list are refused
     pub async fn set_mode(&self, mode_id: &str) -> Result<(), AcpError> {
         let acp_session_id = self
             .acp_session_id
             .clone()
             .ok_or_else(|| AcpError::PromptFailed("No active ACP session".to_string()))?;
+        let modes = self.session_modes();
+        if !modes.allows(mode_id) {
+            return Err(AcpError::ProtocolError(modes.unknown_mode(mode_id)));
+        }
 
         let request = SetSessionModeRequest::new(acp_session_id, SessionModeId::new(mode_id));
 
@@ -1209,16 +2390,14 @@ impl AcpClient {
             })?;
 
         eprintln!("[ACP] Session mode set to: {}", mode_id);
+        *self.session_mode.lock() = Some(mode_id.to_string());
+        set_current_mode(&self.session_id, mode_id);
 
         // Emit mode change event to frontend
-        let event_name = format!("worker-mode-{}", self.worker_id);
-        let _ = self.app_handle.emit(
-            &event_name,
-            serde_json::json!({
-                "worker_id": self.worker_id,
-                "mode_id": mode_id
-            }),
-        );
+        self.events.send(&WorkerModeEvent {
+            worker_id: self.worker_id.clone(),
+            mode_id: mode_id.to_string(),
+        });
 
         Ok(())
     }
@@ -1246,6 +2425,8 @@ impl Drop for AcpClient {
     fn drop(&mut self) {
         // Try to kill the process on drop (blocking)
         let _ = self.process.start_kill();
+        remove_worker_project(&self.worker_id);
+        resource_limits::unwatch_worker(&self.worker_id);
     }
 }
 
@@ -1286,3 +2467,82 @@ pub async fn run_acp_agent(
 
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acp::guards::match_patterns;
+    use crate::settings::store::GuardRule;
+    use agent_client_protocol::ToolKind;
+    use std::time::Duration;
+
+    #[test]
+    fn test_guard_sees_wire_kind() {
+        let rules = vec![GuardRule {
+            name: "no-mode-switch".to_string(),
+            target: GuardTarget::ToolCall,
+            pattern: Some(r"^switch_mode\b".to_string()),
+            script: None,
+            action: GuardAction::Block,
+            enabled: true,
+        }];
+        let kind = wire_name(&ToolKind::SwitchMode);
+        assert_eq!(kind, "switch_mode");
+        let text = tool_call_text(&kind, "Switch to code mode", None);
+        let hits = match_patterns(&rules, GuardTarget::ToolCall, &text);
+        assert_eq!(hits.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_blocking_fs_hands_late_result_over() {
+        let local = tokio::task::LocalSet::new();
+        local
+            .run_until(async {
+                let (tx, rx) = tokio::sync::oneshot::channel();
+                let result = blocking_fs(
+                    "write file",
+                    Duration::from_millis(10),
+                    || {
+                        std::thread::sleep(Duration::from_millis(200));
+                        Ok(7)
+                    },
+                    move |value| {
+                        let _ = tx.send(value);
+                    },
+                )
+                .await;
+                let err = result.unwrap_err();
+                assert!(err.message.contains("timed out"), "{}", err.message);
+                assert_eq!(rx.await.unwrap(), 7);
+            })
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_blocking_fs_in_time() {
+        let local = tokio::task::LocalSet::new();
+        local
+            .run_until(async {
+                let value = blocking_fs(
+                    "read file",
+                    Duration::from_secs(5),
+                    || Ok(1),
+                    |_: i32| panic!("not late"),
+                )
+                .await
+                .unwrap();
+                assert_eq!(value, 1);
+
+                let err = blocking_fs(
+                    "read file",
+                    Duration::from_secs(5),
+                    || Err::<i32, _>(std::io::Error::other("gone")),
+                    |_| {},
+                )
+                .await
+                .unwrap_err();
+                assert!(err.message.contains("gone"));
+            })
+            .await;
+    }
+}
@@ -1,18 +1,69 @@
 //! Tauri commands for ACP-based agent orchestration
 
 use agent_client_protocol::{ContentBlock, ImageContent, TextContent};
+use crate::acp::agent_log::{self, get_agent_log};
+use crate::acp::capabilities::{cached_capabilities, AgentCapabilityInfo};
+use crate::acp::check_loop::{emit_check, failure_prompt, run_check, CheckLoopResult};
 use crate::acp::client::{send_permission_response, AcpClient, AcpError};
+use crate::acp::compaction::{compact, reseed, resume_summary};
+use crate::acp::coordination_delta::CoordinationTracker;
 use crate::acp::coordination_prompt::build_coordination_prompt;
+use crate::acp::criteria::{clear_session_criteria, get_live_criteria};
+use crate::acp::dev_servers;
+use crate::acp::language::{
+    clear_live_language, live_language, prompt_language, prompt_strings, set_live_language,
+};
+use crate::acp::modes::{clear_session_modes, live_session_modes};
+use crate::acp::preprocess::{clear_session_pipeline, preprocess_prompt};
+use crate::acp::skills_commands::find_slash_action;
+use crate::acp::slash_actions;
+use crate::acp::staged_context;
+use crate::stats::budget::require_budget;
+use crate::acp::drafts::DraftStore;
+use crate::acp::events::EventSink;
+use crate::acp::fork::replay_prompt;
+use crate::acp::hibernation::{next_command, persist_on_stop, take_hibernated, IdleContext};
+use crate::acp::hooks::{clear_hook_runs, get_hook_runs};
+use crate::acp::inbox_push::InboxPush;
+use crate::acp::worker_health;
 use crate::acp::registry::{get_agent, list_all_agents, AgentConfig};
-use crate::acp::session_store::{PersistedMessage, PersistedSession, PersistedSessionSummary, SessionStore};
+use crate::acp::resource_limits;
+use crate::acp::session_store::{
+    PersistedMessage, PersistedSession, PersistedSessionSummary, SessionFilter, SessionMetadata,
+    SessionMetadataUpdate, SessionStore,
+};
+use crate::acp::trust::require_decision;
+use crate::acp::title::{generate_title, take_pending_title};
+use crate::agent::scratch;
+use crate::agent::snapshot::snapshot_before_session;
 use crate::claude::pricing::Model;
+use crate::error::{CommandError, ErrorCode};
+use crate::events::{
+    emit, SessionDequeuedEvent, SessionHistoryTruncatedEvent, SessionQueuedEvent,
+    WorkerAuthenticatedEvent, WorkerModeChangeEvent, WorkerStatusChange,
+};
+use crate::inbox::message::WorkerInfo;
 use crate::inbox::InboxManager;
+use crate::jobs::{self, Job};
+use crate::integrations::{dispatch, LifecycleEvent, WebhookEvent};
+use crate::notifications::{notify, NotificationEvent};
+use crate::orchestrator::admission::{Admission, StartFn};
+use crate::orchestrator::build_results::{clear_build_results, get_build_results};
+use crate::orchestrator::patch::clear_patch_set;
 use crate::orchestrator::session::{OrchestratorSession, SessionStatus};
+use crate::orchestrator::tool_calls::{clear_tool_calls, get_tool_calls, ToolCallFilter};
 use crate::orchestrator::worker::{WorkerSession, WorkerStatus};
+use crate::preview;
+use crate::pty::service;
+use crate::secrets::redact::redactor_for;
+use crate::settings::load_settings;
 use crate::tasks::TaskManager;
+use crate::time::{clamp_skew, normalize_ms, now_ms, LocalZone};
 use crate::AppState;
 use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::future::Future;
 use std::sync::Arc;
 use std::thread;
 use tauri::{AppHandle, Emitter, State};
@@ -25,7 +76,7 @@ pub struct AcpSessionResponse {
 }
 
 /// Image attachment for prompts
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ImageAttachment {
     /// Base64-encoded image data
     pub data: String,
@@ -61,9 +112,20 @@ pub enum WorkerCommand {
         /// Channel to signal completion
         done_tx: oneshot::Sender<Result<(), String>>,
     },
+    /// Summarize the conversation and continue in a fresh ACP session
+    Compact {
+        /// Channel to return the summary
+        done_tx: oneshot::Sender<Result<String, String>>,
+    },
+    /// Continue in a fresh ACP session primed with the given history
+    Reseed {
+        seed: Option<String>,
+        /// Channel to return the new ACP session id
+        done_tx: oneshot::Sender<Result<String, String>>,
+    },
     /// Cancel the current operation
     Cancel,
-    /// Stop the worker thread entirely
+    /// Save the session and stop the worker thread entirely
     Stop,
 }
 
@@ -72,10 +134,108 @@ pub struct WorkerHandle {
     pub command_tx: mpsc::Sender<WorkerCommand>,
 }
 
-/// List all known CLI agents (available field indicates if installed)
+/// List all known CLI agents (available field indicates if installed), with
+/// the capabilities each reported the last time it ran
 #[tauri::command]
 pub fn list_available_agents() -> Vec<AgentConfig> {
+    let mut capabilities = cached_capabilities();
     list_all_agents()
+        .into_iter()
+        .map(|mut agent| {
+            agent.capabilities = capabilities.remove(&agent.id);
+            agent
+        })
+        .collect()
+}
+
+/// Longest a health check waits for an agent's initialize handshake
+const HEALTH_CHECK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentHealth {
+    pub agent_id: String,
+    pub ok: bool,
+    pub error: Option<String>,
+    /// Capabilities cached after the check (kept from earlier handshakes
+    /// if this one failed)
+    pub capabilities: Option<AgentCapabilityInfo>,
+}
+
+/// Spawn an agent in the home directory, run the initialize handshake and
+/// shut it down again. The handshake refreshes its cached capabilities.
+pub(crate) async fn probe_handshake(
+    agent: AgentConfig,
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    let cwd = dirs::home_dir()
+        .ok_or_else(|| "Could not determine home directory".to_string())?
+        .to_string_lossy()
+        .to_string();
+
+    let (done_tx, done_rx) = oneshot::channel();
+    thread::spawn(move || {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("Failed to create tokio runtime");
+        let local_set = tokio::task::LocalSet::new();
+        let result = local_set.block_on(&rt, async move {
+            let args_refs: Vec<&str> = agent.args.iter().map(|s| s.as_str()).collect();
+            let mut client = AcpClient::spawn(
+                &agent.command,
+                &args_refs,
+                &cwd,
+                &agent.env_vars,
+                None,
+                agent.model_env_var.clone(),
+                app_handle,
+                format!("health-{}", Uuid::new_v4()),
+                String::new(),
+                None,
+                None,
+            )
+            .await
+            .map_err(|e| format!("Failed to spawn {}: {}", agent.name, e))?;
+            client.set_agent_id(&agent.id);
+            let result = match tokio::time::timeout(HEALTH_CHECK_TIMEOUT, client.initialize()).await {
+                Ok(Ok(_)) => Ok(()),
+                Ok(Err(e)) => Err(format!("ACP initialization failed for {}: {}", agent.name, e)),
+                Err(_) => Err(format!(
+                    "{} did not answer within {}s",
+                    agent.name,
+                    HEALTH_CHECK_TIMEOUT.as_secs()
+                )),
+            };
+            let _ = client.kill().await;
+            result
+        });
+        let _ = done_tx.send(result);
+    });
+
+    done_rx
+        .await
+        .map_err(|_| "Health check thread exited unexpectedly".to_string())?
+}
+
+/// Run an agent's initialize handshake to check that it starts, refreshing
+/// its cached capabilities
+#[tauri::command]
+pub async fn check_agent_health(
+    agent_id: String,
+    app_handle: AppHandle,
+) -> Result<AgentHealth, CommandError> {
+    let agent = get_agent(&agent_id)
+        .ok_or_else(|| CommandError::agent_unavailable(&agent_id))?;
+    let result = probe_handshake(agent, app_handle).await;
+    if let Err(e) = &result {
+        eprintln!("[ACP] Health check failed for {}: {}", agent_id, e);
+    }
+    Ok(AgentHealth {
+        capabilities: cached_capabilities().remove(&agent_id),
+        agent_id,
+        ok: result.is_ok(),
+        error: result.err(),
+    })
 }
 
 /// Create a new ACP-based orchestrator session
@@ -85,9 +245,29 @@ pub async fn create_acp_session(
     agent_id: String,
     model_id: Option<String>,
     cwd: String,
+    language: Option<String>,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<AcpSessionResponse, CommandError> {
+    start_acp_session(
+        prompt, agent_id, model_id, cwd, language, None, app_handle, state,
+    )
+    .await
+}
+
+/// Create a new ACP-based orchestrator session, recorded as a run of
+/// `job_id` before its worker can report a result
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn start_acp_session(
+    prompt: String,
+    agent_id: String,
+    model_id: Option<String>,
+    cwd: String,
+    language: Option<String>,
+    job_id: Option<String>,
     app_handle: AppHandle,
     state: State<'_, AppState>,
-) -> Result<AcpSessionResponse, String> {
+) -> Result<AcpSessionResponse, CommandError> {
     eprintln!("[ACP Command] create_acp_session called with:");
     eprintln!("  prompt: {}", prompt);
     eprintln!("  agent_id: {}", agent_id);
@@ -96,7 +276,10 @@ pub async fn create_acp_session(
 
     // Get the agent config
     let agent = get_agent(&agent_id)
-        .ok_or_else(|| format!("Agent '{}' not found or not available", agent_id))?;
+        .ok_or_else(|| CommandError::agent_unavailable(&agent_id))?;
+    require_decision(&agent.id, &cwd)?;
+
+    require_budget()?;
 
     // Resolve the model to use - either user selection or agent's default
     let selected_model = model_id
@@ -105,6 +288,8 @@ pub async fn create_acp_session(
 
     eprintln!("[ACP] Using model: {}", selected_model);
 
+    require_agent_slot(&state)?;
+
     // Create the orchestrator session
     let session = {
         let mut mgr = state.orchestrator_manager.lock();
@@ -112,6 +297,9 @@ pub async fn create_acp_session(
     };
 
     let session_id = session.id.clone();
+    snapshot_before_session(&cwd, &session_id).await;
+    set_live_language(&session_id, language);
+    service::autostart(&app_handle, &session_id, &cwd);
 
     // Emit session created event
     let _ = app_handle.emit(
@@ -165,33 +353,37 @@ pub async fn create_acp_session(
     let initial_prompt = prompt.clone();
     let selected_model_clone = selected_model.clone();
 
-    // Spawn a PERSISTENT worker thread that handles all prompts for this session
-    thread::spawn(move || {
-        let rt = tokio::runtime::Builder::new_current_thread()
-            .enable_all()
-            .build()
-            .expect("Failed to create tokio runtime");
-
-        let local_set = tokio::task::LocalSet::new();
-
-        local_set.block_on(&rt, async move {
-            run_persistent_worker(
-                agent,
-                cwd,
-                session_id_clone,
-                worker_id_clone,
-                selected_model_clone,
-                app_handle_clone,
-                manager,
-                command_rx,
-                initial_prompt,
-                task_manager,
-                inbox_manager,
-            )
-            .await;
-        });
+    // Spawn a PERSISTENT worker thread that handles all prompts for this
+    // session, now or once an agent slot frees up
+    let start: StartFn = Box::new(move || {
+        spawn_worker_thread(
+            worker_id_clone.clone(),
+            manager.clone(),
+            app_handle_clone.clone(),
+            move || async move {
+                run_persistent_worker(
+                    agent,
+                    cwd,
+                    session_id_clone,
+                    worker_id_clone,
+                    selected_model_clone,
+                    app_handle_clone,
+                    manager,
+                    command_rx,
+                    initial_prompt,
+                    task_manager,
+                    inbox_manager,
+                )
+                .await;
+            },
+        );
     });
 
+    if let Some(job_id) = job_id {
+        crate::scheduler::register_job_session(&session_id, &job_id);
+    }
+    admit_worker(&state, &app_handle, &session_id, &worker_id, start);
+
     // Return the session
     let session = {
         let mgr = state.orchestrator_manager.lock();
@@ -200,7 +392,7 @@ pub async fn create_acp_session(
 
     match session {
         Some(s) => Ok(AcpSessionResponse { session: s }),
-        None => Err("Session not found after creation".to_string()),
+        None => Err(CommandError::session_not_found(&session_id)),
     }
 }
 
@@ -212,9 +404,10 @@ pub async fn create_acp_fleet_session(
     agent_id: String,
     cwd: String,
     worker_count: usize,
+    language: Option<String>,
     app_handle: AppHandle,
     state: State<'_, AppState>,
-) -> Result<AcpSessionResponse, String> {
+) -> Result<AcpSessionResponse, CommandError> {
     eprintln!("[ACP Fleet] Creating fleet session with {} workers", worker_count);
     eprintln!("  prompt: {}", prompt);
     eprintln!("  agent_id: {}", agent_id);
@@ -224,7 +417,10 @@ pub async fn create_acp_fleet_session(
 
     // Get the agent config
     let agent = get_agent(&agent_id)
-        .ok_or_else(|| format!("Agent '{}' not found or not available", agent_id))?;
+        .ok_or_else(|| CommandError::agent_unavailable(&agent_id))?;
+    require_decision(&agent.id, &cwd)?;
+    require_budget()?;
+    require_agent_slot(&state)?;
 
     // Create the orchestrator session
     let session = {
@@ -233,6 +429,9 @@ pub async fn create_acp_fleet_session(
     };
 
     let session_id = session.id.clone();
+    snapshot_before_session(&cwd, &session_id).await;
+    set_live_language(&session_id, language);
+    service::autostart(&app_handle, &session_id, &cwd);
 
     // Emit session created event
     let _ = app_handle.emit(
@@ -254,6 +453,9 @@ pub async fn create_acp_fleet_session(
         .get_inbox_manager(&session_id)
         .map_err(|e| format!("Failed to get inbox manager: {}", e))?;
 
+    let language = prompt_language(&session_id);
+    let strings = prompt_strings(language.as_ref());
+
     // Spawn N workers
     for i in 0..worker_count {
         let is_leader = i == 0;
@@ -261,14 +463,11 @@ pub async fn create_acp_fleet_session(
 
         // Create worker with role-specific task description
         let worker_task = if is_leader {
-            format!(
-                "You are the LEADER. Break down this task into subtasks using `swarm task create`, then coordinate the team.\n\nTask: {}",
-                prompt
-            )
+            format!("{} {}", strings.fleet_leader_task, prompt)
         } else {
             format!(
-                "You are WORKER #{}. Wait for the leader to create tasks, then claim and complete them using swarm commands.\n\nContext: {}",
-                i,
+                "{} {}",
+                strings.fleet_worker_task.replace("{n}", &i.to_string()),
                 prompt
             )
         };
@@ -313,32 +512,32 @@ pub async fn create_acp_fleet_session(
         let inbox_manager_clone = inbox_manager.clone();
         let selected_model = agent.default_model.clone();
 
-        // Spawn worker thread
-        thread::spawn(move || {
-            let rt = tokio::runtime::Builder::new_current_thread()
-                .enable_all()
-                .build()
-                .expect("Failed to create tokio runtime");
-
-            let local_set = tokio::task::LocalSet::new();
-
-            local_set.block_on(&rt, async move {
-                run_persistent_worker(
-                    agent_clone,
-                    cwd_clone,
-                    session_id_clone,
-                    worker_id_clone,
-                    selected_model,
-                    app_handle_clone,
-                    manager,
-                    command_rx,
-                    worker_task,
-                    task_manager_clone,
-                    inbox_manager_clone,
-                )
-                .await;
-            });
+        // Spawn the worker thread, now or once an agent slot frees up
+        let thread_worker_id = worker_id.clone();
+        let start: StartFn = Box::new(move || {
+            spawn_worker_thread(
+                thread_worker_id,
+                manager.clone(),
+                app_handle_clone.clone(),
+                move || async move {
+                    run_persistent_worker(
+                        agent_clone,
+                        cwd_clone,
+                        session_id_clone,
+                        worker_id_clone,
+                        selected_model,
+                        app_handle_clone,
+                        manager,
+                        command_rx,
+                        worker_task,
+                        task_manager_clone,
+                        inbox_manager_clone,
+                    )
+                    .await;
+                },
+            );
         });
+        admit_worker(&state, &app_handle, &session_id, &worker_id, start);
 
         // Small delay between spawning workers to avoid race conditions
         if i < worker_count - 1 {
@@ -354,10 +553,218 @@ pub async fn create_acp_fleet_session(
 
     match session {
         Some(s) => Ok(AcpSessionResponse { session: s }),
-        None => Err("Session not found after creation".to_string()),
+        None => Err(CommandError::session_not_found(&session_id)),
+    }
+}
+
+/// Refuse to start an agent when every slot is taken and sessions don't
+/// queue
+fn require_agent_slot(state: &AppState) -> Result<(), CommandError> {
+    let settings = load_settings();
+    let agent_limit = settings.max_running_agents as usize;
+    if settings.queue_sessions_when_full
+        || state.orchestrator_manager.lock().has_free_agent_slot(agent_limit)
+    {
+        return Ok(());
+    }
+    Err(CommandError::new(
+        ErrorCode::LimitReached,
+        format!(
+            "Too many agents running (limit {}). Close a session or raise the limit in settings.",
+            agent_limit
+        ),
+    )
+    .with_details(serde_json::json!({ "limit": agent_limit })))
+}
+
+/// Start a worker now, or queue it until an agent slot frees up
+fn admit_worker(
+    state: &AppState,
+    app_handle: &AppHandle,
+    session_id: &str,
+    worker_id: &str,
+    start: StartFn,
+) {
+    let agent_limit = load_settings().max_running_agents as usize;
+    let admission = {
+        let mut mgr = state.orchestrator_manager.lock();
+        mgr.admit_session(session_id, worker_id, agent_limit, start)
+    };
+    match admission {
+        Admission::Start(start) => start(),
+        Admission::Queued { position } => {
+            eprintln!("[ACP] Agent limit reached, queued session={} at {}", session_id, position);
+            emit(
+                app_handle,
+                &SessionQueuedEvent {
+                    session_id: session_id.to_string(),
+                    worker_id: worker_id.to_string(),
+                    position,
+                },
+            );
+        }
+    }
+}
+
+/// Run a worker on its own thread with a current-thread runtime and
+/// LocalSet. It holds an agent slot until it exits; freeing the slot starts
+/// queued sessions that now fit.
+fn spawn_worker_thread<F, Fut>(
+    worker_id: String,
+    manager: Arc<Mutex<crate::orchestrator::OrchestratorManager>>,
+    app_handle: AppHandle,
+    run: F,
+) where
+    F: FnOnce() -> Fut + Send + 'static,
+    Fut: Future<Output = ()> + 'static,
+{
+    manager.lock().occupy_agent_slot(&worker_id);
+    worker_health::thread_started(&worker_id);
+
+    thread::spawn(move || {
+        // Frees the slot even if the worker panics
+        let _exit = WorkerThreadExit {
+            worker_id,
+            manager,
+            app_handle,
+        };
+
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("Failed to create tokio runtime");
+
+        let local_set = tokio::task::LocalSet::new();
+
+        local_set.block_on(&rt, run());
+    });
+}
+
+/// Runs when a worker thread ends: records the exit and hands the agent
+/// slot to the next queued session
+struct WorkerThreadExit {
+    worker_id: String,
+    manager: Arc<Mutex<crate::orchestrator::OrchestratorManager>>,
+    app_handle: AppHandle,
+}
+
+impl Drop for WorkerThreadExit {
+    fn drop(&mut self) {
+        let panicked = thread::panicking();
+        if panicked {
+            eprintln!("[ACP] Worker thread panicked for worker={}", self.worker_id);
+        }
+        worker_health::thread_exited(&self.worker_id, panicked);
+
+        let limit = load_settings().max_running_agents as usize;
+        let ready = self.manager.lock().release_agent_slot(&self.worker_id, limit);
+        for (session_id, start) in ready {
+            eprintln!("[ACP] Agent slot freed, starting queued session={}", session_id);
+            emit(&self.app_handle, &SessionDequeuedEvent { session_id });
+            start();
+        }
     }
 }
 
+/// Look up a session's worker, waking it through load_session if it
+/// hibernated after sitting idle
+fn worker_command_tx(
+    session_id: &str,
+    app_handle: &AppHandle,
+    state: &AppState,
+) -> Result<mpsc::Sender<WorkerCommand>, CommandError> {
+    let mut handles = state.worker_handles.lock();
+    match handles.get(session_id) {
+        // The worker thread died; drop its handle so the session reconnects
+        Some(handle) if handle.command_tx.is_closed() => {
+            eprintln!("[ACP] Dropping dead worker handle for session={}", session_id);
+            handles.remove(session_id);
+        }
+        Some(handle) => return Ok(handle.command_tx.clone()),
+        None => {}
+    }
+    // Waking starts the agent again, which a spent budget refuses
+    require_budget()?;
+    let hibernated = take_hibernated(session_id)
+        .ok_or_else(|| {
+            CommandError::worker_unavailable(format!(
+                "No active worker for session '{}'",
+                session_id
+            ))
+            .with_details(serde_json::json!({ "session_id": session_id }))
+        })?;
+
+    eprintln!("[ACP] Waking hibernated worker for session={}", session_id);
+
+    let agent = get_agent(&hibernated.agent_id)
+        .ok_or_else(|| CommandError::agent_unavailable(&hibernated.agent_id))?;
+    let task_manager = state
+        .get_task_manager(session_id)
+        .map_err(|e| format!("Failed to get task manager: {}", e))?;
+    let inbox_manager = state
+        .get_inbox_manager(session_id)
+        .map_err(|e| format!("Failed to get inbox manager: {}", e))?;
+
+    // Commands queue in the channel until the session has loaded
+    let (command_tx, command_rx) = mpsc::channel::<WorkerCommand>(32);
+    handles.insert(session_id.to_string(), WorkerHandle { command_tx: command_tx.clone() });
+    drop(handles);
+
+    let manager = state.orchestrator_manager.clone();
+    let mode = live_session_modes(session_id).and_then(|modes| modes.current);
+    let worker_id = hibernated.worker_id.clone();
+    let thread_session_id = session_id.to_string();
+    let thread_app_handle = app_handle.clone();
+
+    let start: StartFn = Box::new(move || {
+        spawn_worker_thread(
+            hibernated.worker_id.clone(),
+            manager.clone(),
+            thread_app_handle.clone(),
+            move || async move {
+                run_resume_worker(
+                    agent,
+                    hibernated.cwd,
+                    thread_session_id,
+                    hibernated.worker_id,
+                    hibernated.acp_session_id,
+                    Some(hibernated.model),
+                    // Agents that can't load_session never hibernate
+                    None,
+                    mode,
+                    thread_app_handle,
+                    manager,
+                    command_rx,
+                    task_manager,
+                    inbox_manager,
+                )
+                .await;
+            },
+        );
+    });
+    admit_worker(state, app_handle, session_id, &worker_id, start);
+
+    Ok(command_tx)
+}
+
+/// Send a command to a session's worker. A worker that hibernates between
+/// lookup and send closes its channel, so retry once against the woken one.
+async fn send_worker_command(
+    command_tx: mpsc::Sender<WorkerCommand>,
+    cmd: WorkerCommand,
+    session_id: &str,
+    app_handle: &AppHandle,
+    state: &AppState,
+) -> Result<(), CommandError> {
+    if let Err(mpsc::error::SendError(cmd)) = command_tx.send(cmd).await {
+        worker_command_tx(session_id, app_handle, state)?
+            .send(cmd)
+            .await
+            .map_err(|_| CommandError::worker_unavailable("Worker thread has stopped"))?;
+    }
+    Ok(())
+}
+
 /// Send a follow-up prompt to an existing ACP session
 #[tauri::command]
 pub async fn send_acp_prompt(
@@ -365,33 +772,48 @@ pub async fn send_acp_prompt(
     prompt: String,
     app_handle: AppHandle,
     state: State<'_, AppState>,
-) -> Result<(), String> {
+) -> Result<(), CommandError> {
     eprintln!(
         "[ACP] send_acp_prompt called: session={}, prompt={}",
         session_id, prompt
     );
 
+    // Backend slash commands run here instead of reaching the agent
+    if let Some((action, args)) = find_slash_action(&session_id, &prompt) {
+        return slash_actions::run(&session_id, action, &args, &app_handle, &state)
+            .await
+            .map(|_| ());
+    }
+
+    send_prompt(session_id, prompt, app_handle, &state).await
+}
+
+/// Send a prompt to a session's first worker without looking for slash
+/// commands, and watch for it failing in the background
+pub(crate) async fn send_prompt(
+    session_id: String,
+    prompt: String,
+    app_handle: AppHandle,
+    state: &AppState,
+) -> Result<(), CommandError> {
+    require_budget()?;
+    slash_actions::remember_prompt(&session_id, &prompt);
+
     // Get worker ID from session
     let worker_id = {
         let mgr = state.orchestrator_manager.lock();
         let session = mgr
             .get_session(&session_id)
-            .ok_or_else(|| format!("Session '{}' not found", session_id))?;
+            .ok_or_else(|| CommandError::session_not_found(&session_id))?;
         session
             .workers
             .first()
             .map(|w| w.id.clone())
-            .ok_or_else(|| "No worker in session".to_string())?
+            .ok_or_else(|| CommandError::not_found("No worker in session"))?
     };
 
-    // Get the worker handle
-    let command_tx = {
-        let handles = state.worker_handles.lock();
-        handles
-            .get(&session_id)
-            .map(|h| h.command_tx.clone())
-            .ok_or_else(|| format!("No active worker for session '{}'", session_id))?
-    };
+    // Get the worker handle (waking it if it hibernated)
+    let command_tx = worker_command_tx(&session_id, &app_handle, state)?;
 
     // Update session status to running
     {
@@ -400,26 +822,23 @@ pub async fn send_acp_prompt(
         mgr.update_worker_status(&session_id, &worker_id, WorkerStatus::Running);
     }
 
-    let _ = app_handle.emit(
-        "worker-status-change",
-        serde_json::json!({
-            "session_id": session_id,
-            "worker_id": worker_id,
-            "status": "running"
-        }),
-    );
+    emit(&app_handle, &WorkerStatusChange::new(&session_id, &worker_id, WorkerStatus::Running));
 
     // Create completion channel
     let (done_tx, done_rx) = oneshot::channel();
 
     // Send prompt command to the persistent worker
-    command_tx
-        .send(WorkerCommand::Prompt {
+    send_worker_command(
+        command_tx,
+        WorkerCommand::Prompt {
             message: prompt,
             done_tx,
-        })
-        .await
-        .map_err(|_| "Worker thread has stopped".to_string())?;
+        },
+        &session_id,
+        &app_handle,
+        state,
+    )
+    .await?;
 
     // Wait for completion in a background task (don't block the command)
     let session_id_clone = session_id.clone();
@@ -466,33 +885,28 @@ pub async fn send_acp_prompt_with_images(
     images: Vec<ImageAttachment>,
     app_handle: AppHandle,
     state: State<'_, AppState>,
-) -> Result<(), String> {
+) -> Result<(), CommandError> {
     eprintln!(
         "[ACP] send_acp_prompt_with_images called: session={}, prompt={}, images={}",
         session_id, prompt, images.len()
     );
+    require_budget()?;
 
     // Get worker ID from session
     let worker_id = {
         let mgr = state.orchestrator_manager.lock();
         let session = mgr
             .get_session(&session_id)
-            .ok_or_else(|| format!("Session '{}' not found", session_id))?;
+            .ok_or_else(|| CommandError::session_not_found(&session_id))?;
         session
             .workers
             .first()
             .map(|w| w.id.clone())
-            .ok_or_else(|| "No worker in session".to_string())?
+            .ok_or_else(|| CommandError::not_found("No worker in session"))?
     };
 
-    // Get the worker handle
-    let command_tx = {
-        let handles = state.worker_handles.lock();
-        handles
-            .get(&session_id)
-            .map(|h| h.command_tx.clone())
-            .ok_or_else(|| format!("No active worker for session '{}'", session_id))?
-    };
+    // Get the worker handle (waking it if it hibernated)
+    let command_tx = worker_command_tx(&session_id, &app_handle, &state)?;
 
     // Update session status to running
     {
@@ -501,27 +915,24 @@ pub async fn send_acp_prompt_with_images(
         mgr.update_worker_status(&session_id, &worker_id, WorkerStatus::Running);
     }
 
-    let _ = app_handle.emit(
-        "worker-status-change",
-        serde_json::json!({
-            "session_id": session_id,
-            "worker_id": worker_id,
-            "status": "running"
-        }),
-    );
+    emit(&app_handle, &WorkerStatusChange::new(&session_id, &worker_id, WorkerStatus::Running));
 
     // Create completion channel
     let (done_tx, done_rx) = oneshot::channel();
 
     // Send prompt with images command to the persistent worker
-    command_tx
-        .send(WorkerCommand::PromptWithImages {
+    send_worker_command(
+        command_tx,
+        WorkerCommand::PromptWithImages {
             message: prompt,
             images,
             done_tx,
-        })
-        .await
-        .map_err(|_| "Worker thread has stopped".to_string())?;
+        },
+        &session_id,
+        &app_handle,
+        &state,
+    )
+    .await?;
 
     // Wait for completion in a background task (don't block the command)
     let session_id_clone = session_id.clone();
@@ -574,9 +985,6 @@ async fn run_persistent_worker(
     task_manager: Arc<TaskManager>,
     inbox_manager: Arc<InboxManager>,
 ) {
-    // Register this worker in the inbox manager
-    inbox_manager.register_worker(&worker_id);
-
     // Determine if this is the leader (first worker in session)
     let is_leader = {
         let mgr = manager.lock();
@@ -585,17 +993,44 @@ async fn run_persistent_worker(
             .unwrap_or(true)
     };
 
+    // Register this worker in the inbox manager
+    inbox_manager.register(WorkerInfo {
+        role: if is_leader { "leader" } else { "worker" }.to_string(),
+        agent_id: Some(agent.id.clone()),
+        model: Some(selected_model.clone()).filter(|m| !m.is_empty()),
+        ..WorkerInfo::new(&worker_id)
+    });
+
+    // Continue the project's backlog when tasks are kept on its board
+    crate::tasks::board::auto_mount(&task_manager, &cwd);
+
     // Get current tasks for the coordination prompt
     let current_tasks = task_manager.list();
 
+    // Tell the worker how to build and test the repository
+    let project = crate::agent::project::project_info(std::path::Path::new(&cwd));
+    // ...and pass on the project instructions it wouldn't read itself
+    let instructions = crate::agent::context_files::context_instructions(
+        std::path::Path::new(&cwd),
+        &agent.id,
+        &crate::settings::load_settings().context_files,
+    );
+
     // Build coordination context to prepend to the initial prompt
     let coordination_context = build_coordination_prompt(
         &worker_id,
         &session_id,
         is_leader,
         &current_tasks,
+        Some(&project),
+        instructions.as_deref(),
+        prompt_language(&session_id).as_ref(),
     );
 
+    // Follow-up prompts report task and roster changes since this one
+    let mut coordination =
+        CoordinationTracker::new(&session_id, &worker_id, task_manager.clone(), inbox_manager.clone());
+
     // Combine coordination context with initial prompt
     let full_initial_prompt = format!(
         "{}\n## Your Task\n\n{}",
@@ -609,15 +1044,13 @@ async fn run_persistent_worker(
         mgr.update_worker_status(&session_id, &worker_id, WorkerStatus::Running);
     }
 
-    let _ = app_handle.emit(
-        "worker-status-change",
-        serde_json::json!({
-            "session_id": session_id,
-            "worker_id": worker_id,
-            "status": "running",
-            "agent": agent.id,
-            "is_leader": is_leader
-        }),
+    emit(
+        &app_handle,
+        &WorkerStatusChange {
+            agent: Some(agent.id.clone()),
+            is_leader: Some(is_leader),
+            ..WorkerStatusChange::new(&session_id, &worker_id, WorkerStatus::Running)
+        },
     );
 
     // Write model to .claude/settings.json as a workaround for claude-code-acp not respecting env vars
@@ -688,6 +1121,7 @@ async fn run_persistent_worker(
             return;
         }
     };
+    client.set_agent_id(&agent.id);
 
     // Initialize ACP connection
     match client.initialize().await {
@@ -750,7 +1184,12 @@ async fn run_persistent_worker(
             mgr.register_worker_cancel(worker_id.clone(), cancel_tx);
         }
 
-        let result = client.prompt(&full_initial_prompt, &mut cancel_rx).await;
+        let result = client
+            .run_user_prompt(
+                vec![ContentBlock::Text(TextContent::new(&full_initial_prompt))],
+                &mut cancel_rx,
+            )
+            .await;
 
         match result {
             Ok(stop_reason) => {
@@ -760,14 +1199,12 @@ async fn run_persistent_worker(
                     mgr.remove_worker_cancel(&worker_id);
                 }
 
-                let _ = app_handle.emit(
-                    "worker-status-change",
-                    serde_json::json!({
-                        "session_id": session_id,
-                        "worker_id": worker_id,
-                        "status": "completed",
-                        "stop_reason": format!("{:?}", stop_reason)
-                    }),
+                emit(
+                    &app_handle,
+                    &WorkerStatusChange {
+                        stop_reason: Some(format!("{:?}", stop_reason)),
+                        ..WorkerStatusChange::new(&session_id, &worker_id, WorkerStatus::Completed)
+                    },
                 );
             }
             Err(AcpError::Cancelled) => {
@@ -776,13 +1213,9 @@ async fn run_persistent_worker(
                 mgr.update_worker_status(&session_id, &worker_id, WorkerStatus::Idle);
                 mgr.remove_worker_cancel(&worker_id);
 
-                let _ = app_handle.emit(
-                    "worker-status-change",
-                    serde_json::json!({
-                        "session_id": session_id,
-                        "worker_id": worker_id,
-                        "status": "idle"
-                    }),
+                emit(
+                    &app_handle,
+                    &WorkerStatusChange::new(&session_id, &worker_id, WorkerStatus::Idle),
                 );
             }
             Err(e) => {
@@ -792,13 +1225,43 @@ async fn run_persistent_worker(
         }
     }
 
+    if let Err(e) = persist_turn(&client, &session_id, &cwd, &agent.id, &initial_prompt) {
+        eprintln!("[ACP] Failed to persist session {}: {}", session_id, e);
+    }
+    if crate::scheduler::is_job_session(&session_id) {
+        let error = is_cancelled.then_some("Cancelled");
+        crate::scheduler::record_session_result(&app_handle, &session_id, error);
+    }
+
+    // Forks were persisted before their worker had an ACP session, and keep
+    // their parent's title
+    let is_fork = crate::acp::fork::finish_fork(&session_id, client.acp_session_id());
+    if !is_fork {
+        generate_title(
+            app_handle.clone(),
+            session_id.clone(),
+            initial_prompt.clone(),
+            client.last_output().text,
+        );
+    }
+
     // Don't exit on cancel - continue to command loop to accept new prompts
     let _ = is_cancelled; // Suppress unused warning
 
     // Main loop: wait for follow-up commands
     eprintln!("[ACP] Worker entering command loop for session={}", session_id);
 
-    while let Some(cmd) = command_rx.recv().await {
+    let idle = IdleContext {
+        session_id: &session_id,
+        worker_id: &worker_id,
+        agent_id: &agent.id,
+        model: &selected_model,
+        cwd: &cwd,
+        app_handle: &app_handle,
+        manager: &manager,
+    };
+    let mut inbox_push = InboxPush::new(inbox_manager.clone(), &worker_id);
+    while let Some(cmd) = next_command(&mut command_rx, &client, &idle, &mut inbox_push).await {
         match cmd {
             WorkerCommand::Prompt { message, done_tx } => {
                 eprintln!("[ACP] Worker received prompt: {}", message);
@@ -816,7 +1279,10 @@ async fn run_persistent_worker(
                     mgr.register_worker_cancel(worker_id.clone(), cancel_tx);
                 }
 
-                let result = client.prompt(&message, &mut cancel_rx).await;
+                let message = preprocess_prompt(&session_id, &cwd, &message);
+                let mut content = vec![ContentBlock::Text(TextContent::new(coordination.prepare(&message)))];
+                staged_context::attach(&session_id, &cwd, client.supports_embedded_context(), &mut content);
+                let result = client.run_user_prompt(content, &mut cancel_rx).await;
 
                 match result {
                     Ok(stop_reason) => {
@@ -826,16 +1292,21 @@ async fn run_persistent_worker(
                             mgr.remove_worker_cancel(&worker_id);
                         }
 
-                        let _ = app_handle.emit(
-                            "worker-status-change",
-                            serde_json::json!({
-                                "session_id": session_id,
-                                "worker_id": worker_id,
-                                "status": "completed",
-                                "stop_reason": format!("{:?}", stop_reason)
-                            }),
+                        emit(
+                            &app_handle,
+                            &WorkerStatusChange {
+                                stop_reason: Some(format!("{:?}", stop_reason)),
+                                ..WorkerStatusChange::new(
+                                    &session_id,
+                                    &worker_id,
+                                    WorkerStatus::Completed,
+                                )
+                            },
                         );
 
+                        if let Err(e) = persist_turn(&client, &session_id, &cwd, &agent.id, &message) {
+                            eprintln!("[ACP] Failed to persist session {}: {}", session_id, e);
+                        }
                         let _ = done_tx.send(Ok(()));
                     }
                     Err(AcpError::Cancelled) => {
@@ -845,13 +1316,9 @@ async fn run_persistent_worker(
                             mgr.remove_worker_cancel(&worker_id);
                         }
 
-                        let _ = app_handle.emit(
-                            "worker-status-change",
-                            serde_json::json!({
-                                "session_id": session_id,
-                                "worker_id": worker_id,
-                                "status": "idle"
-                            }),
+                        emit(
+                            &app_handle,
+                            &WorkerStatusChange::new(&session_id, &worker_id, WorkerStatus::Idle),
                         );
 
                         let _ = done_tx.send(Ok(()));
@@ -887,9 +1354,11 @@ async fn run_persistent_worker(
                     mgr.register_worker_cancel(worker_id.clone(), cancel_tx);
                 }
 
+                let message = preprocess_prompt(&session_id, &cwd, &message);
+
                 // Build content blocks: text first, then images
                 let mut content: Vec<ContentBlock> = vec![
-                    ContentBlock::Text(TextContent::new(&message))
+                    ContentBlock::Text(TextContent::new(coordination.prepare(&message)))
                 ];
 
                 // Add image content blocks
@@ -899,8 +1368,9 @@ async fn run_persistent_worker(
                         img.mime_type.clone(),
                     )));
                 }
+                staged_context::attach(&session_id, &cwd, client.supports_embedded_context(), &mut content);
 
-                let result = client.prompt_with_content(content, &mut cancel_rx).await;
+                let result = client.run_user_prompt(content, &mut cancel_rx).await;
 
                 match result {
                     Ok(stop_reason) => {
@@ -910,16 +1380,21 @@ async fn run_persistent_worker(
                             mgr.remove_worker_cancel(&worker_id);
                         }
 
-                        let _ = app_handle.emit(
-                            "worker-status-change",
-                            serde_json::json!({
-                                "session_id": session_id,
-                                "worker_id": worker_id,
-                                "status": "completed",
-                                "stop_reason": format!("{:?}", stop_reason)
-                            }),
+                        emit(
+                            &app_handle,
+                            &WorkerStatusChange {
+                                stop_reason: Some(format!("{:?}", stop_reason)),
+                                ..WorkerStatusChange::new(
+                                    &session_id,
+                                    &worker_id,
+                                    WorkerStatus::Completed,
+                                )
+                            },
                         );
 
+                        if let Err(e) = persist_turn(&client, &session_id, &cwd, &agent.id, &message) {
+                            eprintln!("[ACP] Failed to persist session {}: {}", session_id, e);
+                        }
                         let _ = done_tx.send(Ok(()));
                     }
                     Err(AcpError::Cancelled) => {
@@ -929,13 +1404,9 @@ async fn run_persistent_worker(
                             mgr.remove_worker_cancel(&worker_id);
                         }
 
-                        let _ = app_handle.emit(
-                            "worker-status-change",
-                            serde_json::json!({
-                                "session_id": session_id,
-                                "worker_id": worker_id,
-                                "status": "idle"
-                            }),
+                        emit(
+                            &app_handle,
+                            &WorkerStatusChange::new(&session_id, &worker_id, WorkerStatus::Idle),
                         );
 
                         let _ = done_tx.send(Ok(()));
@@ -960,13 +1431,13 @@ async fn run_persistent_worker(
 
                 match result {
                     Ok(()) => {
-                        let _ = app_handle.emit(
-                            "worker-mode-change",
-                            serde_json::json!({
-                                "session_id": session_id,
-                                "worker_id": worker_id,
-                                "mode_id": mode_id
-                            }),
+                        emit(
+                            &app_handle,
+                            &WorkerModeChangeEvent {
+                                session_id: session_id.clone(),
+                                worker_id: worker_id.clone(),
+                                mode_id,
+                            },
                         );
                         let _ = done_tx.send(Ok(()));
                     }
@@ -985,13 +1456,13 @@ async fn run_persistent_worker(
 
                 match result {
                     Ok(()) => {
-                        let _ = app_handle.emit(
-                            "worker-authenticated",
-                            serde_json::json!({
-                                "session_id": session_id,
-                                "worker_id": worker_id,
-                                "method_id": method_id
-                            }),
+                        emit(
+                            &app_handle,
+                            &WorkerAuthenticatedEvent {
+                                session_id: session_id.clone(),
+                                worker_id: worker_id.clone(),
+                                method_id,
+                            },
                         );
                         let _ = done_tx.send(Ok(()));
                     }
@@ -1003,6 +1474,13 @@ async fn run_persistent_worker(
                     }
                 }
             }
+            WorkerCommand::Compact { done_tx } => {
+                eprintln!("[ACP] Worker received compact for session={}", session_id);
+                let _ = done_tx.send(compact(&mut client, &idle).await);
+            }
+            WorkerCommand::Reseed { seed, done_tx } => {
+                let _ = done_tx.send(reseed(&mut client, &idle, seed).await);
+            }
             WorkerCommand::Cancel => {
                 eprintln!("[ACP] Worker received cancel command");
                 // Cancellation is handled via the cancel_rx in prompt()
@@ -1010,6 +1488,7 @@ async fn run_persistent_worker(
             }
             WorkerCommand::Stop => {
                 eprintln!("[ACP] Worker received stop command");
+                persist_on_stop(&client, &idle);
                 break;
             }
         }
@@ -1021,6 +1500,46 @@ async fn run_persistent_worker(
     let _ = client.kill().await;
 }
 
+/// Append a finished turn (the prompt, any thinking, and the response) to
+/// the saved session, creating it on the first turn. The frontend never
+/// saves sessions itself, so this is what keeps their history.
+fn persist_turn(
+    client: &AcpClient,
+    session_id: &str,
+    cwd: &str,
+    agent_id: &str,
+    prompt: &str,
+) -> Result<(), String> {
+    let now = now_ms();
+    let output = client.last_output();
+    let message = |role: &str, content: String| PersistedMessage {
+        role: role.to_string(),
+        content,
+        timestamp: now,
+    };
+
+    let existing = SessionStore::new()?.load_session(session_id).ok();
+    let (mut messages, mode, initial_prompt) = match existing {
+        Some(s) => (s.messages, s.mode, s.initial_prompt),
+        None => (Vec::new(), "default".to_string(), prompt.to_string()),
+    };
+    messages.push(message("user", prompt.to_string()));
+    if !output.thinking.is_empty() {
+        messages.push(message("thinking", output.thinking));
+    }
+    messages.push(message("assistant", output.text));
+
+    save_session_to_persistence(
+        session_id.to_string(),
+        client.acp_session_id().unwrap_or_default(),
+        cwd.to_string(),
+        agent_id.to_string(),
+        initial_prompt,
+        messages,
+        mode,
+    )
+}
+
 /// Handle worker failure
 fn handle_worker_failure(
     session_id: &str,
@@ -1029,35 +1548,60 @@ fn handle_worker_failure(
     app_handle: &AppHandle,
     manager: &Arc<Mutex<crate::orchestrator::OrchestratorManager>>,
 ) {
-    eprintln!("[ACP] Worker failed: {}", error);
+    // A resource limit killed the agent and already failed the worker
+    if resource_limits::was_enforced(worker_id) {
+        eprintln!("[ACP] Worker stopped by resource limit: {}", error);
+        return;
+    }
+    let error = agent_log::with_stderr_tail(session_id, worker_id, error);
+    fail_worker(session_id, worker_id, error, app_handle, manager);
+}
 
-    let mut mgr = manager.lock();
-    if let Some(session) = mgr.get_session_mut(session_id) {
-        if let Some(worker) = session.get_worker_mut(worker_id) {
-            worker.mark_failed(error.clone());
-        }
+/// Mark a worker failed and report it (notification, webhook, status event),
+/// once per failure
+pub(crate) fn fail_worker(
+    session_id: &str,
+    worker_id: &str,
+    error: String,
+    app_handle: &AppHandle,
+    manager: &Arc<Mutex<crate::orchestrator::OrchestratorManager>>,
+) {
+    if !manager.lock().fail_worker(session_id, worker_id, error.clone()) {
+        return;
     }
-    mgr.remove_worker_cancel(worker_id);
+    eprintln!("[ACP] Worker failed: {}", error);
+    notify(
+        app_handle,
+        NotificationEvent::WorkerFailed,
+        Some(session_id),
+        "Worker failed",
+        &error,
+    );
+    dispatch(
+        WebhookEvent::new(LifecycleEvent::WorkerFailed, session_id, "Worker failed", &error)
+            .with_data(serde_json::json!({ "worker_id": worker_id })),
+    );
 
-    let _ = app_handle.emit(
-        "worker-status-change",
-        serde_json::json!({
-            "session_id": session_id,
-            "worker_id": worker_id,
-            "status": "failed",
-            "error": error
-        }),
+    crate::scheduler::record_session_result(app_handle, session_id, Some(&error));
+    crate::acp::fork::finish_fork(session_id, None);
+
+    emit(
+        app_handle,
+        &WorkerStatusChange {
+            error: Some(error),
+            ..WorkerStatusChange::new(session_id, worker_id, WorkerStatus::Failed)
+        },
     );
 }
 
 /// Respond to a permission request from the frontend
 #[tauri::command]
-pub fn respond_to_permission(worker_id: String, option_id: String) -> Result<(), String> {
+pub fn respond_to_permission(worker_id: String, option_id: String) -> Result<(), CommandError> {
     eprintln!(
         "[ACP] respond_to_permission: worker={}, option={}",
         worker_id, option_id
     );
-    send_permission_response(&worker_id, option_id)
+    send_permission_response(&worker_id, option_id).map_err(CommandError::not_found)
 }
 
 /// Set the session mode for an ACP session (e.g., "plan", "normal")
@@ -1066,81 +1610,336 @@ pub fn respond_to_permission(worker_id: String, option_id: String) -> Result<(),
 pub async fn set_acp_session_mode(
     session_id: String,
     mode_id: String,
+    app_handle: AppHandle,
     state: State<'_, AppState>,
-) -> Result<(), String> {
+) -> Result<(), CommandError> {
     eprintln!(
         "[ACP] set_acp_session_mode called: session={}, mode={}",
         session_id, mode_id
     );
 
-    // Get the worker handle
-    let command_tx = {
-        let handles = state.worker_handles.lock();
-        handles
-            .get(&session_id)
-            .map(|h| h.command_tx.clone())
-            .ok_or_else(|| format!("No active worker for session '{}'", session_id))?
-    };
+    // Refuse modes the agent didn't offer before waking anything up
+    if let Some(modes) = live_session_modes(&session_id) {
+        if !modes.allows(&mode_id) {
+            return Err(CommandError::invalid_input(modes.unknown_mode(&mode_id)));
+        }
+    }
+
+    // Get the worker handle (waking it if it hibernated)
+    let command_tx = worker_command_tx(&session_id, &app_handle, &state)?;
 
     // Create completion channel
     let (done_tx, done_rx) = oneshot::channel();
 
     // Send set mode command to the persistent worker
-    command_tx
-        .send(WorkerCommand::SetMode {
-            mode_id: mode_id.clone(),
+    send_worker_command(
+        command_tx,
+        WorkerCommand::SetMode {
+            mode_id,
             done_tx,
-        })
-        .await
-        .map_err(|_| "Worker thread has stopped".to_string())?;
+        },
+        &session_id,
+        &app_handle,
+        &state,
+    )
+    .await?;
 
     // Wait for completion
     match done_rx.await {
         Ok(Ok(())) => Ok(()),
-        Ok(Err(e)) => Err(e),
-        Err(_) => Err("Worker thread stopped while setting mode".to_string()),
+        Ok(Err(e)) => Err(CommandError::agent_failed(e)),
+        Err(_) => Err(CommandError::worker_unavailable(
+            "Worker thread stopped while setting mode",
+        )),
     }
 }
 
+/// Summarize a session's history and continue it in a fresh ACP session
+/// seeded with the summary. Returns the summary.
+#[tauri::command]
+pub async fn compact_session(
+    session_id: String,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<String, CommandError> {
+    eprintln!("[ACP] compact_session called: session={}", session_id);
+    compact_worker(&session_id, &app_handle, &state).await
+}
+
+/// Have a session's worker compact its history; returns the summary
+pub(crate) async fn compact_worker(
+    session_id: &str,
+    app_handle: &AppHandle,
+    state: &AppState,
+) -> Result<String, CommandError> {
+    // Get the worker handle (waking it if it hibernated)
+    let command_tx = worker_command_tx(session_id, app_handle, state)?;
+
+    let (done_tx, done_rx) = oneshot::channel();
+    send_worker_command(
+        command_tx,
+        WorkerCommand::Compact { done_tx },
+        session_id,
+        app_handle,
+        state,
+    )
+    .await?;
+
+    match done_rx.await {
+        Ok(result) => result.map_err(CommandError::agent_failed),
+        Err(_) => Err(CommandError::worker_unavailable(
+            "Worker thread stopped while compacting",
+        )),
+    }
+}
+
+/// Run a shell check in the session's project and, while it fails, send
+/// its output to the agent and check again, up to `max_iterations` prompts
+#[tauri::command]
+pub async fn run_until_passing(
+    session_id: String,
+    command: String,
+    max_iterations: u32,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<CheckLoopResult, CommandError> {
+    eprintln!(
+        "[ACP] run_until_passing called: session={}, command={}",
+        session_id, command
+    );
+
+    let cwd = SessionStore::new()?
+        .load_session(&session_id)
+        .map_err(|_| {
+            CommandError::not_found(format!(
+                "Session '{}' has no saved project directory",
+                session_id
+            ))
+        })?
+        .cwd;
+    let worker_id = {
+        let mgr = state.orchestrator_manager.lock();
+        let session = mgr
+            .get_session(&session_id)
+            .ok_or_else(|| CommandError::session_not_found(&session_id))?;
+        session
+            .workers
+            .first()
+            .map(|w| w.id.clone())
+            .ok_or_else(|| CommandError::not_found("No worker in session"))?
+    };
+    let events = EventSink::App(app_handle.clone());
+
+    let mut iterations = 0;
+    loop {
+        let run = run_check(&command, &cwd).await;
+        if run.passed || iterations >= max_iterations {
+            emit_check(
+                &events,
+                &session_id,
+                &command,
+                iterations,
+                max_iterations,
+                &run,
+                None,
+            );
+            return Ok(CheckLoopResult {
+                passed: run.passed,
+                iterations,
+                cancelled: false,
+                last_run: run,
+            });
+        }
+
+        iterations += 1;
+        let prompt = failure_prompt(&command, &run, iterations, max_iterations);
+        emit_check(
+            &events,
+            &session_id,
+            &command,
+            iterations,
+            max_iterations,
+            &run,
+            Some(&prompt),
+        );
+
+        let command_tx = worker_command_tx(&session_id, &app_handle, &state)?;
+        {
+            let mut mgr = state.orchestrator_manager.lock();
+            mgr.update_session_status(&session_id, SessionStatus::Running);
+            mgr.update_worker_status(&session_id, &worker_id, WorkerStatus::Running);
+        }
+        emit(&app_handle, &WorkerStatusChange::new(&session_id, &worker_id, WorkerStatus::Running));
+
+        let (done_tx, done_rx) = oneshot::channel();
+        send_worker_command(
+            command_tx,
+            WorkerCommand::Prompt {
+                message: prompt,
+                done_tx,
+            },
+            &session_id,
+            &app_handle,
+            &state,
+        )
+        .await?;
+
+        let error = match done_rx.await {
+            Ok(Ok(())) => None,
+            Ok(Err(e)) => Some(e),
+            Err(_) => Some("Worker thread stopped unexpectedly".to_string()),
+        };
+        if let Some(error) = error {
+            handle_worker_failure(
+                &session_id,
+                &worker_id,
+                error.clone(),
+                &app_handle,
+                &state.orchestrator_manager,
+            );
+            return Err(CommandError::agent_failed(error));
+        }
+
+        // A cancelled turn leaves the worker idle rather than completed
+        let cancelled = state
+            .orchestrator_manager
+            .lock()
+            .get_session(&session_id)
+            .and_then(|session| session.get_worker(&worker_id))
+            .is_some_and(|worker| worker.status == WorkerStatus::Idle);
+        if cancelled {
+            return Ok(CheckLoopResult {
+                passed: false,
+                iterations,
+                cancelled: true,
+                last_run: run,
+            });
+        }
+    }
+}
+
+/// Rewrite a user message: drop it and everything after it from the
+/// persisted history, restart the agent from the remaining history, and
+/// send the new text in its place
+#[tauri::command]
+pub async fn edit_and_resend(
+    session_id: String,
+    message_index: usize,
+    new_text: String,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<(), CommandError> {
+    eprintln!(
+        "[ACP] edit_and_resend called: session={}, message={}",
+        session_id, message_index
+    );
+
+    let store = SessionStore::new()?;
+    let mut persisted = store.load_session(&session_id)?;
+    match persisted.messages.get(message_index) {
+        Some(message) if message.role == "user" => {}
+        Some(_) => {
+            return Err(CommandError::invalid_input(format!(
+                "Message {} is not a user message",
+                message_index
+            )))
+        }
+        None => {
+            return Err(CommandError::invalid_input(format!(
+                "Message index {} is out of range",
+                message_index
+            )))
+        }
+    }
+    persisted.messages.truncate(message_index);
+
+    // Agents can't rewind a loaded session, so the kept history is replayed
+    // into a fresh one. A compaction summary may describe the dropped
+    // messages, so it isn't reused.
+    persisted.summary = None;
+    let seed = persisted
+        .messages
+        .iter()
+        .any(|m| !m.is_thinking())
+        .then(|| replay_prompt(&persisted.messages, None));
+
+    // Get the worker handle (waking it if it hibernated)
+    let command_tx = worker_command_tx(&session_id, &app_handle, &state)?;
+    let (done_tx, done_rx) = oneshot::channel();
+    send_worker_command(
+        command_tx,
+        WorkerCommand::Reseed { seed, done_tx },
+        &session_id,
+        &app_handle,
+        &state,
+    )
+    .await?;
+    persisted.acp_session_id = match done_rx.await {
+        Ok(result) => result.map_err(CommandError::agent_failed)?,
+        Err(_) => {
+            return Err(CommandError::worker_unavailable(
+                "Worker thread stopped while resetting the session",
+            ))
+        }
+    };
+
+    if message_index == 0 {
+        persisted.initial_prompt = new_text.clone();
+    }
+    persisted.updated_at = now_ms();
+    store.save_session(&persisted)?;
+
+    emit(
+        &app_handle,
+        &SessionHistoryTruncatedEvent {
+            session_id: session_id.clone(),
+            message_index,
+        },
+    );
+
+    send_acp_prompt(session_id, new_text, app_handle, state).await
+}
+
 /// Authenticate an ACP session with the specified method
 /// Uses the official ACP authenticate protocol method
 #[tauri::command]
 pub async fn authenticate_acp_session(
     session_id: String,
     method_id: String,
+    app_handle: AppHandle,
     state: State<'_, AppState>,
-) -> Result<(), String> {
+) -> Result<(), CommandError> {
     eprintln!(
         "[ACP] authenticate_acp_session called: session={}, method={}",
         session_id, method_id
     );
 
-    // Get the worker handle
-    let command_tx = {
-        let handles = state.worker_handles.lock();
-        handles
-            .get(&session_id)
-            .map(|h| h.command_tx.clone())
-            .ok_or_else(|| format!("No active worker for session '{}'", session_id))?
-    };
+    // Get the worker handle (waking it if it hibernated)
+    let command_tx = worker_command_tx(&session_id, &app_handle, &state)?;
 
     // Create completion channel
     let (done_tx, done_rx) = oneshot::channel();
 
     // Send authenticate command to the persistent worker
-    command_tx
-        .send(WorkerCommand::Authenticate {
-            method_id: method_id.clone(),
+    send_worker_command(
+        command_tx,
+        WorkerCommand::Authenticate {
+            method_id,
             done_tx,
-        })
-        .await
-        .map_err(|_| "Worker thread has stopped".to_string())?;
+        },
+        &session_id,
+        &app_handle,
+        &state,
+    )
+    .await?;
 
     // Wait for completion
     match done_rx.await {
         Ok(Ok(())) => Ok(()),
-        Ok(Err(e)) => Err(e),
-        Err(_) => Err("Worker thread stopped while authenticating".to_string()),
+        Ok(Err(e)) => Err(CommandError::new(ErrorCode::AuthRequired, e)),
+        Err(_) => Err(CommandError::worker_unavailable(
+            "Worker thread stopped while authenticating",
+        )),
     }
 }
 
@@ -1148,35 +1947,74 @@ pub async fn authenticate_acp_session(
 // Session Persistence Commands
 // ============================================================================
 
-/// List all persisted sessions
+/// List persisted sessions, by default everything that isn't archived
+#[tauri::command]
+pub fn list_persisted_sessions(
+    filter: Option<SessionFilter>,
+) -> Result<Vec<PersistedSessionSummary>, CommandError> {
+    let store = SessionStore::new()?;
+    Ok(store.list_sessions(&filter.unwrap_or_default()))
+}
+
+/// Set a persisted session's title, tags, pinned or archived state
 #[tauri::command]
-pub fn list_persisted_sessions() -> Result<Vec<PersistedSessionSummary>, String> {
+pub fn update_session_metadata(
+    session_id: String,
+    update: SessionMetadataUpdate,
+) -> Result<PersistedSessionSummary, CommandError> {
     let store = SessionStore::new()?;
-    Ok(store.list_sessions())
+    let mut session = store.load_session(&session_id)?;
+    session.metadata.apply(update);
+    store.save_session(&session)?;
+    Ok(PersistedSessionSummary::from(&session))
 }
 
 /// Get a specific persisted session
 #[tauri::command]
-pub fn get_persisted_session(session_id: String) -> Result<PersistedSession, String> {
+pub fn get_persisted_session(session_id: String) -> Result<PersistedSession, CommandError> {
     let store = SessionStore::new()?;
-    store.load_session(&session_id)
+    Ok(store.load_session(&session_id)?)
 }
 
 /// Delete a persisted session
 #[tauri::command]
-pub fn delete_persisted_session(session_id: String) -> Result<(), String> {
+pub fn delete_persisted_session(session_id: String) -> Result<(), CommandError> {
     let store = SessionStore::new()?;
-    store.delete_session(&session_id)
+    store.delete_session(&session_id)?;
+    clear_session_state(&session_id);
+    Ok(DraftStore::new()?.delete(&session_id)?)
+}
+
+/// Drop everything kept in memory for a session, and stop the processes
+/// and remove the scratch directory it owns (on delete or archive)
+pub(crate) fn clear_session_state(session_id: &str) {
+    clear_tool_calls(session_id);
+    clear_patch_set(session_id);
+    clear_build_results(session_id);
+    clear_session_criteria(session_id);
+    clear_hook_runs(session_id);
+    agent_log::clear_agent_log(session_id);
+    clear_session_modes(session_id);
+    clear_live_language(session_id);
+    clear_session_pipeline(session_id);
+    crate::tasks::plan::clear_session(session_id);
+    slash_actions::clear_session(session_id);
+    staged_context::clear_session(session_id);
+    service::stop_session(session_id);
+    dev_servers::clear_session(session_id);
+    preview::stop_session(session_id);
+    scratch::remove(session_id);
 }
 
 /// Resume a persisted ACP session
-/// Creates a new worker, loads the session from the agent, and returns the session
+/// Creates a new worker, loads the session from the agent, and returns the session.
+/// Agents without load_session start a fresh session from a condensed transcript.
 #[tauri::command]
 pub async fn resume_acp_session(
     persisted_session_id: String,
     app_handle: AppHandle,
     state: State<'_, AppState>,
-) -> Result<AcpSessionResponse, String> {
+) -> Result<AcpSessionResponse, CommandError> {
     eprintln!("[ACP Command] resume_acp_session called with id: {}", persisted_session_id);
 
     // Load the persisted session
@@ -1185,10 +2023,9 @@ pub async fn resume_acp_session(
 
     // Get the agent config
     let agent = get_agent(&persisted.agent_id)
-        .ok_or_else(|| format!("Agent '{}' not found or not available", persisted.agent_id))?;
-
-    // Check if agent supports load_session
-    // (We'll verify this during initialization, but provide early feedback if possible)
+        .ok_or_else(|| CommandError::agent_unavailable(&persisted.agent_id))?;
+    require_decision(&agent.id, &persisted.cwd)?;
+    require_budget()?;
 
     // Create a new orchestrator session
     let session = {
@@ -1197,6 +2034,9 @@ pub async fn resume_acp_session(
     };
 
     let session_id = session.id.clone();
+    snapshot_before_session(&persisted.cwd, &session_id).await;
+    set_live_language(&session_id, persisted.metadata.language.clone());
+    service::autostart(&app_handle, &session_id, &persisted.cwd);
 
     // Emit session created event
     let _ = app_handle.emit(
@@ -1250,23 +2090,25 @@ pub async fn resume_acp_session(
     let app_handle_clone = app_handle.clone();
     let acp_session_id = persisted.acp_session_id.clone();
     let cwd = persisted.cwd.clone();
+    // Used if the agent turns out not to support load_session
+    let summary = resume_summary(&persisted);
+    let mode = persisted.mode.clone();
 
     // Spawn a worker thread that loads the existing session
-    thread::spawn(move || {
-        let rt = tokio::runtime::Builder::new_current_thread()
-            .enable_all()
-            .build()
-            .expect("Failed to create tokio runtime");
-
-        let local_set = tokio::task::LocalSet::new();
-
-        local_set.block_on(&rt, async move {
+    spawn_worker_thread(
+        worker_id.clone(),
+        manager.clone(),
+        app_handle.clone(),
+        move || async move {
             run_resume_worker(
                 agent,
                 cwd,
                 session_id_clone,
                 worker_id_clone,
                 acp_session_id,
+                None,
+                Some(summary),
+                Some(mode),
                 app_handle_clone,
                 manager,
                 command_rx,
@@ -1274,8 +2116,8 @@ pub async fn resume_acp_session(
                 inbox_manager,
             )
             .await;
-        });
-    });
+        },
+    );
 
     // Return the session
     let session = {
@@ -1285,17 +2127,44 @@ pub async fn resume_acp_session(
 
     match session {
         Some(s) => Ok(AcpSessionResponse { session: s }),
-        None => Err("Session not found after creation".to_string()),
+        None => Err(CommandError::session_not_found(&session_id)),
     }
 }
 
-/// Worker that resumes an existing session via load_session
+/// Inbox registration for a worker coming back to its session; the
+/// session's first worker is its leader
+fn rejoining_worker(
+    manager: &Mutex<crate::orchestrator::OrchestratorManager>,
+    session_id: &str,
+    worker_id: &str,
+    agent_id: &str,
+    model: &str,
+) -> WorkerInfo {
+    let is_leader = manager
+        .lock()
+        .get_session(session_id)
+        .and_then(|s| s.workers.first())
+        .map_or(true, |w| w.id == worker_id);
+    WorkerInfo {
+        role: if is_leader { "leader" } else { "worker" }.to_string(),
+        agent_id: Some(agent_id.to_string()),
+        model: Some(model.to_string()).filter(|m| !m.is_empty()),
+        ..WorkerInfo::new(worker_id)
+    }
+}
+
+/// Worker that resumes an existing session via load_session. Agents that
+/// can't load sessions get a fresh one primed with `summary` instead (when
+/// given). The session is put back in `mode` if the agent offers it.
 async fn run_resume_worker(
     agent: AgentConfig,
     cwd: String,
     session_id: String,
     worker_id: String,
     acp_session_id: String,
+    model: Option<String>,
+    summary: Option<String>,
+    mode: Option<String>,
     app_handle: AppHandle,
     manager: Arc<Mutex<crate::orchestrator::OrchestratorManager>>,
     mut command_rx: mpsc::Receiver<WorkerCommand>,
@@ -1303,7 +2172,21 @@ async fn run_resume_worker(
     inbox_manager: Arc<InboxManager>,
 ) {
     // Register this worker in the inbox manager
-    inbox_manager.register_worker(&worker_id);
+    let resumed_model = model.clone().unwrap_or_else(|| agent.default_model.clone());
+    inbox_manager.register(rejoining_worker(
+        &manager,
+        &session_id,
+        &worker_id,
+        &agent.id,
+        &resumed_model,
+    ));
+
+    // Continue the project's backlog when tasks are kept on its board
+    crate::tasks::board::auto_mount(&task_manager, &cwd);
+
+    // Follow-up prompts report task and roster changes since the worker started
+    let mut coordination =
+        CoordinationTracker::new(&session_id, &worker_id, task_manager.clone(), inbox_manager.clone());
 
     // Update worker status to running
     {
@@ -1311,32 +2194,33 @@ async fn run_resume_worker(
         mgr.update_worker_status(&session_id, &worker_id, WorkerStatus::Running);
     }
 
-    let _ = app_handle.emit(
-        "worker-status-change",
-        serde_json::json!({
-            "session_id": session_id,
-            "worker_id": worker_id,
-            "status": "running",
-            "agent": agent.id,
-            "resuming": true
-        }),
+    emit(
+        &app_handle,
+        &WorkerStatusChange {
+            agent: Some(agent.id.clone()),
+            resuming: true,
+            ..WorkerStatusChange::new(&session_id, &worker_id, WorkerStatus::Running)
+        },
     );
 
+    // Woken workers keep their model; resumed sessions use the default
+    let model = model.unwrap_or_else(|| agent.default_model.clone());
+
     // Build args from agent config, including model CLI flag if available
     let mut args: Vec<String> = agent.args.clone();
     if let Some(ref cli_flag) = agent.model_cli_flag {
         args.push(cli_flag.clone());
-        args.push(agent.default_model.clone());
+        args.push(model.clone());
     }
     let args_refs: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
 
-    // Spawn the ACP agent with coordination support (using default model for resumed sessions)
+    // Spawn the ACP agent with coordination support
     let client_result = AcpClient::spawn(
         &agent.command,
         &args_refs,
         &cwd,
         &agent.env_vars,
-        Some(agent.default_model.clone()),
+        Some(model.clone()),
         agent.model_env_var.clone(),
         app_handle.clone(),
         worker_id.clone(),
@@ -1358,20 +2242,32 @@ async fn run_resume_worker(
             return;
         }
     };
+    client.set_agent_id(&agent.id);
+
+    // Replayed instead of loading the session when the agent can't load it
+    let mut seed = None;
 
     // Initialize ACP connection
     match client.initialize().await {
         Ok(_init_response) => {
             // Check if agent supports load_session
             if !client.supports_load_session() {
-                handle_worker_failure(
-                    &session_id,
-                    &worker_id,
-                    format!("Agent {} does not support session resumption", agent.name),
-                    &app_handle,
-                    &manager,
-                );
-                return;
+                if let Some(summary) = summary {
+                    eprintln!(
+                        "[ACP] {} can't load sessions, resuming session={} from a summary",
+                        agent.name, session_id
+                    );
+                    seed = Some(summary);
+                } else {
+                    handle_worker_failure(
+                        &session_id,
+                        &worker_id,
+                        format!("Agent {} does not support session resumption", agent.name),
+                        &app_handle,
+                        &manager,
+                    );
+                    return;
+                }
             }
 
             // Check if authentication is required
@@ -1406,8 +2302,25 @@ async fn run_resume_worker(
         }
     }
 
-    // Load the existing session instead of creating a new one
-    if let Err(e) = client.load_acp_session(acp_session_id.clone(), cwd.clone()).await {
+    let resumed_from_summary = seed.is_some();
+    if let Some(seed) = seed {
+        // Start over from the condensed transcript (cancellable, it's a turn)
+        let (cancel_tx, mut cancel_rx) = mpsc::channel::<()>(1);
+        manager.lock().register_worker_cancel(worker_id.clone(), cancel_tx);
+        let result = client.reseed(&cwd, Some(&seed), &mut cancel_rx).await;
+        manager.lock().remove_worker_cancel(&worker_id);
+        if let Err(e) = result {
+            handle_worker_failure(
+                &session_id,
+                &worker_id,
+                format!("Failed to resume session {} from its summary: {}", acp_session_id, e),
+                &app_handle,
+                &manager,
+            );
+            return;
+        }
+    } else if let Err(e) = client.load_acp_session(acp_session_id.clone(), cwd.clone()).await {
+        // Load the existing session instead of creating a new one
         handle_worker_failure(
             &session_id,
             &worker_id,
@@ -1418,26 +2331,45 @@ async fn run_resume_worker(
         return;
     }
 
+    // Restore the mode the session was left in
+    let modes = client.session_modes();
+    if let Some(mode) = mode.filter(|mode| {
+        modes.current.as_ref() != Some(mode) && modes.available.iter().any(|m| &m.id == mode)
+    }) {
+        if let Err(e) = client.set_mode(&mode).await {
+            eprintln!("[ACP] Failed to restore mode {} for session={}: {}", mode, session_id, e);
+        }
+    }
+
     // Update status to completed (session loaded successfully)
     {
         let mut mgr = manager.lock();
         mgr.update_worker_status(&session_id, &worker_id, WorkerStatus::Completed);
     }
 
-    let _ = app_handle.emit(
-        "worker-status-change",
-        serde_json::json!({
-            "session_id": session_id,
-            "worker_id": worker_id,
-            "status": "completed",
-            "resumed": true
-        }),
+    emit(
+        &app_handle,
+        &WorkerStatusChange {
+            resumed: true,
+            resumed_from_summary,
+            ..WorkerStatusChange::new(&session_id, &worker_id, WorkerStatus::Completed)
+        },
     );
 
     // Main loop: wait for follow-up commands (same as normal worker)
     eprintln!("[ACP] Resume worker entering command loop for session={}", session_id);
 
-    while let Some(cmd) = command_rx.recv().await {
+    let idle = IdleContext {
+        session_id: &session_id,
+        worker_id: &worker_id,
+        agent_id: &agent.id,
+        model: &model,
+        cwd: &cwd,
+        app_handle: &app_handle,
+        manager: &manager,
+    };
+    let mut inbox_push = InboxPush::new(inbox_manager.clone(), &worker_id);
+    while let Some(cmd) = next_command(&mut command_rx, &client, &idle, &mut inbox_push).await {
         match cmd {
             WorkerCommand::Prompt { message, done_tx } => {
                 eprintln!("[ACP] Resume worker received prompt: {}", message);
@@ -1455,7 +2387,10 @@ async fn run_resume_worker(
                     mgr.register_worker_cancel(worker_id.clone(), cancel_tx);
                 }
 
-                let result = client.prompt(&message, &mut cancel_rx).await;
+                let message = preprocess_prompt(&session_id, &cwd, &message);
+                let mut content = vec![ContentBlock::Text(TextContent::new(coordination.prepare(&message)))];
+                staged_context::attach(&session_id, &cwd, client.supports_embedded_context(), &mut content);
+                let result = client.run_user_prompt(content, &mut cancel_rx).await;
 
                 match result {
                     Ok(stop_reason) => {
@@ -1465,16 +2400,21 @@ async fn run_resume_worker(
                             mgr.remove_worker_cancel(&worker_id);
                         }
 
-                        let _ = app_handle.emit(
-                            "worker-status-change",
-                            serde_json::json!({
-                                "session_id": session_id,
-                                "worker_id": worker_id,
-                                "status": "completed",
-                                "stop_reason": format!("{:?}", stop_reason)
-                            }),
+                        emit(
+                            &app_handle,
+                            &WorkerStatusChange {
+                                stop_reason: Some(format!("{:?}", stop_reason)),
+                                ..WorkerStatusChange::new(
+                                    &session_id,
+                                    &worker_id,
+                                    WorkerStatus::Completed,
+                                )
+                            },
                         );
 
+                        if let Err(e) = persist_turn(&client, &session_id, &cwd, &agent.id, &message) {
+                            eprintln!("[ACP] Failed to persist session {}: {}", session_id, e);
+                        }
                         let _ = done_tx.send(Ok(()));
                     }
                     Err(AcpError::Cancelled) => {
@@ -1484,13 +2424,9 @@ async fn run_resume_worker(
                             mgr.remove_worker_cancel(&worker_id);
                         }
 
-                        let _ = app_handle.emit(
-                            "worker-status-change",
-                            serde_json::json!({
-                                "session_id": session_id,
-                                "worker_id": worker_id,
-                                "status": "idle"
-                            }),
+                        emit(
+                            &app_handle,
+                            &WorkerStatusChange::new(&session_id, &worker_id, WorkerStatus::Idle),
                         );
 
                         let _ = done_tx.send(Ok(()));
@@ -1512,13 +2448,13 @@ async fn run_resume_worker(
                 let result = client.set_mode(&mode_id).await;
                 match result {
                     Ok(()) => {
-                        let _ = app_handle.emit(
-                            "worker-mode-change",
-                            serde_json::json!({
-                                "session_id": session_id,
-                                "worker_id": worker_id,
-                                "mode_id": mode_id
-                            }),
+                        emit(
+                            &app_handle,
+                            &WorkerModeChangeEvent {
+                                session_id: session_id.clone(),
+                                worker_id: worker_id.clone(),
+                                mode_id,
+                            },
                         );
                         let _ = done_tx.send(Ok(()));
                     }
@@ -1531,13 +2467,13 @@ async fn run_resume_worker(
                 let result = client.authenticate(&method_id).await;
                 match result {
                     Ok(()) => {
-                        let _ = app_handle.emit(
-                            "worker-authenticated",
-                            serde_json::json!({
-                                "session_id": session_id,
-                                "worker_id": worker_id,
-                                "method_id": method_id
-                            }),
+                        emit(
+                            &app_handle,
+                            &WorkerAuthenticatedEvent {
+                                session_id: session_id.clone(),
+                                worker_id: worker_id.clone(),
+                                method_id,
+                            },
                         );
                         let _ = done_tx.send(Ok(()));
                     }
@@ -1560,8 +2496,9 @@ async fn run_resume_worker(
                     mgr.register_worker_cancel(worker_id.clone(), cancel_tx);
                 }
 
+                let message = preprocess_prompt(&session_id, &cwd, &message);
                 let mut content: Vec<ContentBlock> = vec![
-                    ContentBlock::Text(TextContent::new(&message))
+                    ContentBlock::Text(TextContent::new(coordination.prepare(&message)))
                 ];
                 for img in &images {
                     content.push(ContentBlock::Image(ImageContent::new(
@@ -1569,8 +2506,9 @@ async fn run_resume_worker(
                         img.mime_type.clone(),
                     )));
                 }
+                staged_context::attach(&session_id, &cwd, client.supports_embedded_context(), &mut content);
 
-                let result = client.prompt_with_content(content, &mut cancel_rx).await;
+                let result = client.run_user_prompt(content, &mut cancel_rx).await;
 
                 match result {
                     Ok(stop_reason) => {
@@ -1579,15 +2517,20 @@ async fn run_resume_worker(
                             mgr.update_worker_status(&session_id, &worker_id, WorkerStatus::Completed);
                             mgr.remove_worker_cancel(&worker_id);
                         }
-                        let _ = app_handle.emit(
-                            "worker-status-change",
-                            serde_json::json!({
-                                "session_id": session_id,
-                                "worker_id": worker_id,
-                                "status": "completed",
-                                "stop_reason": format!("{:?}", stop_reason)
-                            }),
+                        emit(
+                            &app_handle,
+                            &WorkerStatusChange {
+                                stop_reason: Some(format!("{:?}", stop_reason)),
+                                ..WorkerStatusChange::new(
+                                    &session_id,
+                                    &worker_id,
+                                    WorkerStatus::Completed,
+                                )
+                            },
                         );
+                        if let Err(e) = persist_turn(&client, &session_id, &cwd, &agent.id, &message) {
+                            eprintln!("[ACP] Failed to persist session {}: {}", session_id, e);
+                        }
                         let _ = done_tx.send(Ok(()));
                     }
                     Err(AcpError::Cancelled) => {
@@ -1596,13 +2539,9 @@ async fn run_resume_worker(
                             mgr.update_worker_status(&session_id, &worker_id, WorkerStatus::Idle);
                             mgr.remove_worker_cancel(&worker_id);
                         }
-                        let _ = app_handle.emit(
-                            "worker-status-change",
-                            serde_json::json!({
-                                "session_id": session_id,
-                                "worker_id": worker_id,
-                                "status": "idle"
-                            }),
+                        emit(
+                            &app_handle,
+                            &WorkerStatusChange::new(&session_id, &worker_id, WorkerStatus::Idle),
                         );
                         let _ = done_tx.send(Ok(()));
                         // Don't break - keep worker alive to accept new prompts
@@ -1619,7 +2558,17 @@ async fn run_resume_worker(
                     }
                 }
             }
-            WorkerCommand::Cancel | WorkerCommand::Stop => {
+            WorkerCommand::Compact { done_tx } => {
+                let _ = done_tx.send(compact(&mut client, &idle).await);
+            }
+            WorkerCommand::Reseed { seed, done_tx } => {
+                let _ = done_tx.send(reseed(&mut client, &idle, seed).await);
+            }
+            WorkerCommand::Cancel => {
+                break;
+            }
+            WorkerCommand::Stop => {
+                persist_on_stop(&client, &idle);
                 break;
             }
         }
@@ -1638,7 +2587,7 @@ pub async fn reconnect_worker(
     cwd: String,
     app_handle: AppHandle,
     state: State<'_, AppState>,
-) -> Result<(), String> {
+) -> Result<(), CommandError> {
     eprintln!("[ACP] reconnect_worker called: session={}, agent={}", session_id, agent_id);
 
     // Check if worker handle already exists (shouldn't happen but be safe)
@@ -1651,7 +2600,9 @@ pub async fn reconnect_worker(
 
     // Get the agent config
     let agent = get_agent(&agent_id)
-        .ok_or_else(|| format!("Agent '{}' not found or not available", agent_id))?;
+        .ok_or_else(|| CommandError::agent_unavailable(&agent_id))?;
+    require_decision(&agent.id, &cwd)?;
+    require_budget()?;
 
     // Get or create the session and worker
     let worker_id = {
@@ -1669,7 +2620,7 @@ pub async fn reconnect_worker(
         }
 
         let session = mgr.get_session_mut(&session_id)
-            .ok_or_else(|| format!("Session '{}' not found", session_id))?;
+            .ok_or_else(|| CommandError::session_not_found(&session_id))?;
 
         // Use existing worker if available, otherwise create one
         if let Some(worker) = session.workers.first() {
@@ -1712,15 +2663,11 @@ pub async fn reconnect_worker(
     let cwd_clone = cwd.clone();
 
     // Spawn a worker thread that just initializes the connection (no initial prompt)
-    thread::spawn(move || {
-        let rt = tokio::runtime::Builder::new_current_thread()
-            .enable_all()
-            .build()
-            .expect("Failed to create tokio runtime");
-
-        let local_set = tokio::task::LocalSet::new();
-
-        local_set.block_on(&rt, async move {
+    spawn_worker_thread(
+        worker_id.clone(),
+        manager.clone(),
+        app_handle.clone(),
+        move || async move {
             run_reconnect_worker(
                 agent,
                 cwd_clone,
@@ -1733,8 +2680,8 @@ pub async fn reconnect_worker(
                 inbox_manager,
             )
             .await;
-        });
-    });
+        },
+    );
 
     // Wait a bit for the worker to initialize
     tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
@@ -1755,7 +2702,20 @@ async fn run_reconnect_worker(
     inbox_manager: Arc<InboxManager>,
 ) {
     // Register this worker in the inbox manager
-    inbox_manager.register_worker(&worker_id);
+    inbox_manager.register(rejoining_worker(
+        &manager,
+        &session_id,
+        &worker_id,
+        &agent.id,
+        &agent.default_model,
+    ));
+
+    // Continue the project's backlog when tasks are kept on its board
+    crate::tasks::board::auto_mount(&task_manager, &cwd);
+
+    // Follow-up prompts report task and roster changes since the worker started
+    let mut coordination =
+        CoordinationTracker::new(&session_id, &worker_id, task_manager.clone(), inbox_manager.clone());
 
     // Update worker status to running
     {
@@ -1763,15 +2723,13 @@ async fn run_reconnect_worker(
         mgr.update_worker_status(&session_id, &worker_id, WorkerStatus::Running);
     }
 
-    let _ = app_handle.emit(
-        "worker-status-change",
-        serde_json::json!({
-            "session_id": session_id,
-            "worker_id": worker_id,
-            "status": "running",
-            "agent": agent.id,
-            "reconnecting": true
-        }),
+    emit(
+        &app_handle,
+        &WorkerStatusChange {
+            agent: Some(agent.id.clone()),
+            reconnecting: true,
+            ..WorkerStatusChange::new(&session_id, &worker_id, WorkerStatus::Running)
+        },
     );
 
     // Build args from agent config, including model CLI flag if available
@@ -1810,6 +2768,7 @@ async fn run_reconnect_worker(
             return;
         }
     };
+    client.set_agent_id(&agent.id);
 
     // Initialize ACP connection
     match client.initialize().await {
@@ -1864,20 +2823,28 @@ async fn run_reconnect_worker(
         mgr.update_worker_status(&session_id, &worker_id, WorkerStatus::Completed);
     }
 
-    let _ = app_handle.emit(
-        "worker-status-change",
-        serde_json::json!({
-            "session_id": session_id,
-            "worker_id": worker_id,
-            "status": "completed",
-            "reconnected": true
-        }),
+    emit(
+        &app_handle,
+        &WorkerStatusChange {
+            reconnected: true,
+            ..WorkerStatusChange::new(&session_id, &worker_id, WorkerStatus::Completed)
+        },
     );
 
     // Main loop: wait for commands (same as normal worker)
     eprintln!("[ACP] Reconnect worker entering command loop for session={}", session_id);
 
-    while let Some(cmd) = command_rx.recv().await {
+    let idle = IdleContext {
+        session_id: &session_id,
+        worker_id: &worker_id,
+        agent_id: &agent.id,
+        model: &agent.default_model,
+        cwd: &cwd,
+        app_handle: &app_handle,
+        manager: &manager,
+    };
+    let mut inbox_push = InboxPush::new(inbox_manager.clone(), &worker_id);
+    while let Some(cmd) = next_command(&mut command_rx, &client, &idle, &mut inbox_push).await {
         match cmd {
             WorkerCommand::Prompt { message, done_tx } => {
                 eprintln!("[ACP] Reconnect worker received prompt: {}", message);
@@ -1895,7 +2862,10 @@ async fn run_reconnect_worker(
                     mgr.register_worker_cancel(worker_id.clone(), cancel_tx);
                 }
 
-                let result = client.prompt(&message, &mut cancel_rx).await;
+                let message = preprocess_prompt(&session_id, &cwd, &message);
+                let mut content = vec![ContentBlock::Text(TextContent::new(coordination.prepare(&message)))];
+                staged_context::attach(&session_id, &cwd, client.supports_embedded_context(), &mut content);
+                let result = client.run_user_prompt(content, &mut cancel_rx).await;
 
                 match result {
                     Ok(stop_reason) => {
@@ -1905,16 +2875,21 @@ async fn run_reconnect_worker(
                             mgr.remove_worker_cancel(&worker_id);
                         }
 
-                        let _ = app_handle.emit(
-                            "worker-status-change",
-                            serde_json::json!({
-                                "session_id": session_id,
-                                "worker_id": worker_id,
-                                "status": "completed",
-                                "stop_reason": format!("{:?}", stop_reason)
-                            }),
+                        emit(
+                            &app_handle,
+                            &WorkerStatusChange {
+                                stop_reason: Some(format!("{:?}", stop_reason)),
+                                ..WorkerStatusChange::new(
+                                    &session_id,
+                                    &worker_id,
+                                    WorkerStatus::Completed,
+                                )
+                            },
                         );
 
+                        if let Err(e) = persist_turn(&client, &session_id, &cwd, &agent.id, &message) {
+                            eprintln!("[ACP] Failed to persist session {}: {}", session_id, e);
+                        }
                         let _ = done_tx.send(Ok(()));
                     }
                     Err(AcpError::Cancelled) => {
@@ -1924,13 +2899,9 @@ async fn run_reconnect_worker(
                             mgr.remove_worker_cancel(&worker_id);
                         }
 
-                        let _ = app_handle.emit(
-                            "worker-status-change",
-                            serde_json::json!({
-                                "session_id": session_id,
-                                "worker_id": worker_id,
-                                "status": "idle"
-                            }),
+                        emit(
+                            &app_handle,
+                            &WorkerStatusChange::new(&session_id, &worker_id, WorkerStatus::Idle),
                         );
 
                         let _ = done_tx.send(Ok(()));
@@ -1952,13 +2923,13 @@ async fn run_reconnect_worker(
                 let result = client.set_mode(&mode_id).await;
                 match result {
                     Ok(()) => {
-                        let _ = app_handle.emit(
-                            "worker-mode-change",
-                            serde_json::json!({
-                                "session_id": session_id,
-                                "worker_id": worker_id,
-                                "mode_id": mode_id
-                            }),
+                        emit(
+                            &app_handle,
+                            &WorkerModeChangeEvent {
+                                session_id: session_id.clone(),
+                                worker_id: worker_id.clone(),
+                                mode_id,
+                            },
                         );
                         let _ = done_tx.send(Ok(()));
                     }
@@ -1971,13 +2942,13 @@ async fn run_reconnect_worker(
                 let result = client.authenticate(&method_id).await;
                 match result {
                     Ok(()) => {
-                        let _ = app_handle.emit(
-                            "worker-authenticated",
-                            serde_json::json!({
-                                "session_id": session_id,
-                                "worker_id": worker_id,
-                                "method_id": method_id
-                            }),
+                        emit(
+                            &app_handle,
+                            &WorkerAuthenticatedEvent {
+                                session_id: session_id.clone(),
+                                worker_id: worker_id.clone(),
+                                method_id,
+                            },
                         );
                         let _ = done_tx.send(Ok(()));
                     }
@@ -2000,8 +2971,9 @@ async fn run_reconnect_worker(
                     mgr.register_worker_cancel(worker_id.clone(), cancel_tx);
                 }
 
+                let message = preprocess_prompt(&session_id, &cwd, &message);
                 let mut content: Vec<ContentBlock> = vec![
-                    ContentBlock::Text(TextContent::new(&message))
+                    ContentBlock::Text(TextContent::new(coordination.prepare(&message)))
                 ];
                 for img in &images {
                     content.push(ContentBlock::Image(ImageContent::new(
@@ -2009,8 +2981,9 @@ async fn run_reconnect_worker(
                         img.mime_type.clone(),
                     )));
                 }
+                staged_context::attach(&session_id, &cwd, client.supports_embedded_context(), &mut content);
 
-                let result = client.prompt_with_content(content, &mut cancel_rx).await;
+                let result = client.run_user_prompt(content, &mut cancel_rx).await;
 
                 match result {
                     Ok(stop_reason) => {
@@ -2019,15 +2992,20 @@ async fn run_reconnect_worker(
                             mgr.update_worker_status(&session_id, &worker_id, WorkerStatus::Completed);
                             mgr.remove_worker_cancel(&worker_id);
                         }
-                        let _ = app_handle.emit(
-                            "worker-status-change",
-                            serde_json::json!({
-                                "session_id": session_id,
-                                "worker_id": worker_id,
-                                "status": "completed",
-                                "stop_reason": format!("{:?}", stop_reason)
-                            }),
+                        emit(
+                            &app_handle,
+                            &WorkerStatusChange {
+                                stop_reason: Some(format!("{:?}", stop_reason)),
+                                ..WorkerStatusChange::new(
+                                    &session_id,
+                                    &worker_id,
+                                    WorkerStatus::Completed,
+                                )
+                            },
                         );
+                        if let Err(e) = persist_turn(&client, &session_id, &cwd, &agent.id, &message) {
+                            eprintln!("[ACP] Failed to persist session {}: {}", session_id, e);
+                        }
                         let _ = done_tx.send(Ok(()));
                     }
                     Err(AcpError::Cancelled) => {
@@ -2036,13 +3014,9 @@ async fn run_reconnect_worker(
                             mgr.update_worker_status(&session_id, &worker_id, WorkerStatus::Idle);
                             mgr.remove_worker_cancel(&worker_id);
                         }
-                        let _ = app_handle.emit(
-                            "worker-status-change",
-                            serde_json::json!({
-                                "session_id": session_id,
-                                "worker_id": worker_id,
-                                "status": "idle"
-                            }),
+                        emit(
+                            &app_handle,
+                            &WorkerStatusChange::new(&session_id, &worker_id, WorkerStatus::Idle),
                         );
                         let _ = done_tx.send(Ok(()));
                         // Don't break - keep worker alive to accept new prompts
@@ -2059,7 +3033,17 @@ async fn run_reconnect_worker(
                     }
                 }
             }
-            WorkerCommand::Cancel | WorkerCommand::Stop => {
+            WorkerCommand::Compact { done_tx } => {
+                let _ = done_tx.send(compact(&mut client, &idle).await);
+            }
+            WorkerCommand::Reseed { seed, done_tx } => {
+                let _ = done_tx.send(reseed(&mut client, &idle, seed).await);
+            }
+            WorkerCommand::Cancel => {
+                break;
+            }
+            WorkerCommand::Stop => {
+                persist_on_stop(&client, &idle);
                 break;
             }
         }
@@ -2082,28 +3066,174 @@ pub fn save_session_to_persistence(
 ) -> Result<(), String> {
     let store = SessionStore::new()?;
 
-    let now = chrono::Utc::now().timestamp();
+    let now = now_ms();
 
     // Check if session already exists to preserve created_at
-    let created_at = if store.session_exists(&session_id) {
-        store.load_session(&session_id)
-            .map(|s| s.created_at)
-            .unwrap_or(now)
+    let existing = if store.session_exists(&session_id) {
+        store.load_session(&session_id).ok()
     } else {
-        now
+        None
+    };
+    let created_at = existing.as_ref().map(|s| s.created_at).unwrap_or(now);
+    // Message times come from the webview's clock, in seconds from older
+    // frontends
+    let messages: Vec<PersistedMessage> = messages
+        .into_iter()
+        .map(|mut message| {
+            message.timestamp = clamp_skew(normalize_ms(message.timestamp));
+            message
+        })
+        .collect();
+    // The agent may have switched modes itself since the frontend last looked
+    let mode = live_session_modes(&session_id)
+        .and_then(|modes| modes.current)
+        .unwrap_or(mode);
+
+    // Prefer the live tool-call log; keep what was persisted if it's gone (e.g. after restart)
+    let tool_calls = get_tool_calls(&session_id, &ToolCallFilter::default())
+        .filter(|calls| !calls.is_empty())
+        .or_else(|| existing.as_ref().map(|s| s.tool_calls.clone()))
+        .unwrap_or_default();
+    let build_results = get_build_results(&session_id)
+        .filter(|results| !results.is_empty())
+        .or_else(|| existing.as_ref().map(|s| s.build_results.clone()))
+        .unwrap_or_default();
+    let hook_runs = get_hook_runs(&session_id)
+        .filter(|runs| !runs.is_empty())
+        .or_else(|| existing.as_ref().map(|s| s.hook_runs.clone()))
+        .unwrap_or_default();
+    let agent_log = get_agent_log(&session_id)
+        .filter(|lines| !lines.is_empty())
+        .or_else(|| existing.as_ref().map(|s| s.agent_log.clone()))
+        .unwrap_or_default();
+    let criteria = get_live_criteria(&session_id)
+        .or_else(|| existing.as_ref().and_then(|s| s.criteria.clone()));
+    let (summary, parent_session_id, forked_at_message, metadata) = match existing {
+        Some(s) => (s.summary, s.parent_session_id, s.forked_at_message, s.metadata),
+        None => {
+            let metadata = SessionMetadata {
+                title: take_pending_title(&session_id),
+                language: live_language(&session_id),
+                timezone: Some(LocalZone::current()),
+                ..Default::default()
+            };
+            (None, None, None, metadata)
+        }
     };
 
-    let session = PersistedSession {
+    let mut session = PersistedSession {
         id: session_id,
         acp_session_id,
         cwd,
         agent_id,
         created_at,
-        updated_at: now,
+        // Never before created_at, even if the clock was set back
+        updated_at: now.max(created_at),
         messages,
         mode,
         initial_prompt,
+        tool_calls,
+        build_results,
+        hook_runs,
+        agent_log,
+        criteria,
+        summary,
+        parent_session_id,
+        forked_at_message,
+        metadata,
     };
 
+    // Thought blocks are only kept if the user opted in
+    if !load_settings().persist_thinking {
+        session.strip_thinking();
+    }
+
+    let redactor = redactor_for(Some(&session.cwd));
+    for message in &mut session.messages {
+        if let Cow::Owned(redacted) = redactor.redact(&message.content) {
+            message.content = redacted;
+        }
+    }
+
     store.save_session(&session)
 }
+
+/// Export a persisted session as pretty-printed JSON
+#[tauri::command]
+pub fn export_persisted_session(session_id: String) -> Result<String, CommandError> {
+    let store = SessionStore::new()?;
+    let mut session = store.load_session(&session_id)?;
+    prepare_export(&mut session);
+
+    serde_json::to_string_pretty(&session)
+        .map_err(|e| format!("Failed to serialize session: {}", e).into())
+}
+
+/// Drop thinking unless exports keep it, and record the zone the session's
+/// times are local to, so they read the same wherever the export is opened
+fn prepare_export(session: &mut PersistedSession) {
+    if !load_settings().export_thinking {
+        session.strip_thinking();
+    }
+    session.metadata.timezone = Some(session.timezone());
+}
+
+/// Bytes written between progress updates of a session export
+const EXPORT_CHUNK_BYTES: usize = 1024 * 1024;
+
+/// Export a persisted session to `path` as a background job and return the
+/// job id. The file only appears once the export is complete.
+#[tauri::command]
+pub fn export_session_to_file(
+    session_id: String,
+    path: String,
+    app_handle: AppHandle,
+) -> Result<String, CommandError> {
+    let target = std::path::PathBuf::from(&path);
+    if target.file_name().is_none() {
+        return Err(CommandError::invalid_input(format!("Not a file path: {}", path)));
+    }
+    let label = format!("Export session {}", session_id);
+    let work = move |job: &Job| export_session(job, &session_id, &target);
+    Ok(jobs::spawn(&app_handle, "export_session", label, work))
+}
+
+fn export_session(job: &Job, session_id: &str, target: &std::path::Path) -> Result<(), String> {
+    job.phase("loading", Some(0.0));
+    let mut session = SessionStore::new()?.load_session(session_id)?;
+    prepare_export(&mut session);
+    job.check_cancelled()?;
+
+    job.phase("serializing", Some(20.0));
+    let json = serde_json::to_string_pretty(&session)
+        .map_err(|e| format!("Failed to serialize session: {}", e))?;
+    job.check_cancelled()?;
+
+    job.phase("writing", Some(40.0));
+    let partial = target.with_extension("partial");
+    let written = write_export(job, &partial, json.as_bytes())
+        .and_then(|_| std::fs::rename(&partial, target).map_err(|e| e.to_string()));
+    if written.is_err() {
+        let _ = std::fs::remove_file(&partial);
+    }
+    written?;
+    job.log(format!("Wrote {} bytes to {}", json.len(), target.display()));
+    Ok(())
+}
+
+/// Write `bytes` in chunks, moving the job from 40% to 100%
+fn write_export(job: &Job, path: &std::path::Path, bytes: &[u8]) -> Result<(), String> {
+    use std::io::Write;
+
+    let mut file = std::fs::File::create(path)
+        .map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+    let mut done = 0;
+    for chunk in bytes.chunks(EXPORT_CHUNK_BYTES) {
+        job.check_cancelled()?;
+        file.write_all(chunk)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        done += chunk.len();
+        job.phase("writing", Some(40.0 + 60.0 * done as f32 / bytes.len() as f32));
+    }
+    Ok(())
+}
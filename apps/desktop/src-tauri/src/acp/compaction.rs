@@ -0,0 +1,307 @@
+//! Session compaction and reseeding
+//!
+//! When a session's context fills up, the worker asks the agent for a
+//! summary of the conversation, saves it with the persisted session, and
+//! switches to a fresh ACP session that starts from the summary. Reseeding
+//! is the general form: a fresh ACP session primed with any given history
+//! (used when earlier messages are edited, and to resume sessions of agents
+//! that can't `load_session`, from a condensed transcript).
+
+use crate::acp::client::AcpClient;
+use crate::acp::commands::save_session_to_persistence;
+use crate::acp::hibernation::IdleContext;
+use crate::acp::session_store::{PersistedSession, SessionStore};
+use crate::events::{emit, SessionCompactedEvent, WorkerStatusChange};
+use crate::orchestrator::worker::WorkerStatus;
+use tokio::sync::mpsc;
+
+pub const COMPACT_PROMPT: &str = "Summarize our conversation so far so that it can be \
+continued in a new session without the full history. Include the goal, decisions made, \
+files changed, the current state of the work, and any open questions or next steps. \
+Reply with the summary only.";
+
+pub const COMPACT_SEED_PREFIX: &str = "This session continues an earlier conversation that \
+was compacted. Here is its summary; use it as context for what follows and reply with a \
+short acknowledgement.\n\n";
+
+/// Longest message kept whole in a resume summary; longer ones keep their
+/// start and end
+const MAX_SUMMARY_MESSAGE_CHARS: usize = 2_000;
+
+/// Most transcript characters in a resume summary; older messages beyond
+/// this are dropped
+const MAX_SUMMARY_CHARS: usize = 60_000;
+
+/// Most changed files listed in a resume summary
+const MAX_SUMMARY_FILES: usize = 50;
+
+/// Prompt that primes a fresh ACP session with a condensed transcript of a
+/// persisted session, for agents that can't load it: the original task, the
+/// compaction summary, the files changed, and the recent messages with long
+/// ones shortened
+pub fn resume_summary(session: &PersistedSession) -> String {
+    let mut prompt = String::from(
+        "This session resumes an earlier conversation that can't be reloaded. Below is a \
+         condensed transcript of it; treat it as what has happened so far and reply with a \
+         short acknowledgement.\n\n",
+    );
+    prompt.push_str(&format!(
+        "Original task:\n{}\n\n",
+        shorten(session.initial_prompt.trim())
+    ));
+    if let Some(summary) = &session.summary {
+        prompt.push_str(&format!("Summary of earlier context:\n{}\n\n", summary.trim()));
+    }
+
+    let mut files: Vec<&str> = Vec::new();
+    for diff in session.tool_calls.iter().flat_map(|c| &c.diffs) {
+        if !files.contains(&diff.path.as_str()) {
+            files.push(&diff.path);
+        }
+    }
+    if !files.is_empty() {
+        prompt.push_str("Files changed:\n");
+        for file in files.iter().take(MAX_SUMMARY_FILES) {
+            prompt.push_str(&format!("- {}\n", file));
+        }
+        if files.len() > MAX_SUMMARY_FILES {
+            prompt.push_str(&format!("- ...and {} more\n", files.len() - MAX_SUMMARY_FILES));
+        }
+        prompt.push('\n');
+    }
+
+    let mut turns: Vec<String> = Vec::new();
+    let mut chars = 0;
+    for message in session.messages.iter().rev().filter(|m| !m.is_thinking()) {
+        let speaker = if message.role == "user" {
+            "User"
+        } else {
+            "Assistant"
+        };
+        let turn = format!("{}: {}", speaker, shorten(message.content.trim()));
+        chars += turn.len();
+        if chars > MAX_SUMMARY_CHARS && !turns.is_empty() {
+            break;
+        }
+        turns.push(turn);
+    }
+    turns.reverse();
+
+    let omitted = session.messages.iter().filter(|m| !m.is_thinking()).count() - turns.len();
+    prompt.push_str("Conversation:\n\n");
+    if omitted > 0 {
+        prompt.push_str(&format!("({} earlier messages omitted)\n\n", omitted));
+    }
+    prompt.push_str(&turns.join("\n\n"));
+    prompt
+}
+
+/// Keep the start and end of a long message
+fn shorten(text: &str) -> String {
+    let count = text.chars().count();
+    if count <= MAX_SUMMARY_MESSAGE_CHARS {
+        return text.to_string();
+    }
+    let half = MAX_SUMMARY_MESSAGE_CHARS / 2;
+    let head: String = text.chars().take(half).collect();
+    let tail: String = text.chars().skip(count - half).collect();
+    format!("{}\n[... {} characters omitted ...]\n{}", head, count - 2 * half, tail)
+}
+
+/// Compact the worker's session. Runs inside the worker's command loop.
+pub async fn compact(client: &mut AcpClient, ctx: &IdleContext<'_>) -> Result<String, String> {
+    let mut cancel_rx = begin(ctx);
+    let result = client
+        .compact(ctx.cwd, &mut cancel_rx)
+        .await
+        .map_err(|e| format!("Failed to compact session: {}", e));
+    end(ctx, result.is_ok());
+
+    let summary = result?;
+    let acp_session_id = client.acp_session_id().unwrap_or_default();
+    if let Err(e) = persist(ctx, &acp_session_id, &summary) {
+        eprintln!(
+            "[ACP] Failed to persist compacted session {}: {}",
+            ctx.session_id, e
+        );
+    }
+
+    emit(
+        ctx.app_handle,
+        &SessionCompactedEvent {
+            session_id: ctx.session_id.to_string(),
+            worker_id: ctx.worker_id.to_string(),
+            acp_session_id,
+            summary: summary.clone(),
+            context: client.context_usage(),
+        },
+    );
+    Ok(summary)
+}
+
+/// Switch the worker to a fresh ACP session, primed with `seed` if given.
+/// Runs inside the worker's command loop; returns the new ACP session id.
+pub async fn reseed(
+    client: &mut AcpClient,
+    ctx: &IdleContext<'_>,
+    seed: Option<String>,
+) -> Result<String, String> {
+    let mut cancel_rx = begin(ctx);
+    let result = client
+        .reseed(ctx.cwd, seed.as_deref(), &mut cancel_rx)
+        .await
+        .map_err(|e| format!("Failed to reset session: {}", e));
+    end(ctx, result.is_ok());
+
+    result?;
+    Ok(client.acp_session_id().unwrap_or_default())
+}
+
+/// Mark the worker running and make the work cancellable
+fn begin(ctx: &IdleContext<'_>) -> mpsc::Receiver<()> {
+    set_status(ctx, WorkerStatus::Running);
+    let (cancel_tx, cancel_rx) = mpsc::channel::<()>(1);
+    ctx.manager
+        .lock()
+        .register_worker_cancel(ctx.worker_id.to_string(), cancel_tx);
+    cancel_rx
+}
+
+fn end(ctx: &IdleContext<'_>, ok: bool) {
+    ctx.manager.lock().remove_worker_cancel(ctx.worker_id);
+    if ok {
+        set_status(ctx, WorkerStatus::Completed);
+    } else {
+        set_status(ctx, WorkerStatus::Idle);
+    }
+}
+
+fn set_status(ctx: &IdleContext<'_>, status: WorkerStatus) {
+    ctx.manager
+        .lock()
+        .update_worker_status(ctx.session_id, ctx.worker_id, status.clone());
+    emit(
+        ctx.app_handle,
+        &WorkerStatusChange::new(ctx.session_id, ctx.worker_id, status),
+    );
+}
+
+/// Point the persisted session at the new ACP session and store the summary,
+/// keeping the messages the frontend already saved
+fn persist(ctx: &IdleContext<'_>, acp_session_id: &str, summary: &str) -> Result<(), String> {
+    let store = SessionStore::new()?;
+    let existing = store.load_session(ctx.session_id).ok();
+    let (messages, mode, initial_prompt) = match existing {
+        Some(s) => (s.messages, s.mode, s.initial_prompt),
+        None => {
+            let prompt = ctx
+                .manager
+                .lock()
+                .get_session(ctx.session_id)
+                .map(|s| s.prompt.clone())
+                .unwrap_or_default();
+            (Vec::new(), "default".to_string(), prompt)
+        }
+    };
+    save_session_to_persistence(
+        ctx.session_id.to_string(),
+        acp_session_id.to_string(),
+        ctx.cwd.to_string(),
+        ctx.agent_id.to_string(),
+        initial_prompt,
+        messages,
+        mode,
+    )?;
+
+    let mut session = store.load_session(ctx.session_id)?;
+    session.summary = Some(summary.to_string());
+    store.save_session(&session)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acp::session_store::{PersistedMessage, SessionMetadata};
+    use crate::orchestrator::tool_calls::{ToolCallDiff, ToolCallRecord};
+
+    fn message(role: &str, content: &str) -> PersistedMessage {
+        PersistedMessage {
+            role: role.to_string(),
+            content: content.to_string(),
+            timestamp: 0,
+        }
+    }
+
+    fn session(messages: Vec<PersistedMessage>) -> PersistedSession {
+        PersistedSession {
+            id: "s1".to_string(),
+            acp_session_id: "acp-1".to_string(),
+            cwd: "/tmp".to_string(),
+            agent_id: "gemini".to_string(),
+            created_at: 0,
+            updated_at: 0,
+            messages,
+            mode: "default".to_string(),
+            initial_prompt: "Add a login form".to_string(),
+            tool_calls: Vec::new(),
+            build_results: Vec::new(),
+            hook_runs: Vec::new(),
+            agent_log: Vec::new(),
+            criteria: None,
+            summary: None,
+            parent_session_id: None,
+            forked_at_message: None,
+            metadata: SessionMetadata::default(),
+        }
+    }
+
+    #[test]
+    fn test_resume_summary() {
+        let mut session = session(vec![
+            message("user", "Add a login form"),
+            message("thinking", "hmm"),
+            message("assistant", &"x".repeat(MAX_SUMMARY_MESSAGE_CHARS + 10)),
+        ]);
+        session.summary = Some("Building an auth flow".to_string());
+        session.tool_calls.push(ToolCallRecord {
+            id: "t1".to_string(),
+            worker_id: "w1".to_string(),
+            kind: Some("edit".to_string()),
+            title: None,
+            status: "completed".to_string(),
+            transitions: Vec::new(),
+            raw_input: None,
+            diffs: vec![ToolCallDiff {
+                path: "src/login.tsx".to_string(),
+                old_text: None,
+                new_text: "export {}".to_string(),
+            }],
+            started_at: 0,
+            finished_at: None,
+            duration_ms: None,
+        });
+
+        let prompt = resume_summary(&session);
+        assert!(prompt.contains("Original task:\nAdd a login form"));
+        assert!(prompt.contains("Summary of earlier context:\nBuilding an auth flow"));
+        assert!(prompt.contains("Files changed:\n- src/login.tsx\n"));
+        assert!(prompt.contains("User: Add a login form\n\nAssistant: x"));
+        assert!(prompt.contains("[... 10 characters omitted ...]"));
+        assert!(!prompt.contains("hmm"));
+        assert!(!prompt.contains("earlier messages omitted"));
+    }
+
+    #[test]
+    fn test_resume_summary_keeps_recent_messages() {
+        let long = "y".repeat(MAX_SUMMARY_MESSAGE_CHARS);
+        let count = MAX_SUMMARY_CHARS / MAX_SUMMARY_MESSAGE_CHARS + 5;
+        let mut messages: Vec<PersistedMessage> =
+            (0..count).map(|_| message("assistant", &long)).collect();
+        messages.push(message("user", "latest"));
+
+        let prompt = resume_summary(&session(messages));
+        assert!(prompt.ends_with("User: latest"));
+        assert!(prompt.contains("earlier messages omitted"));
+        assert!(prompt.len() < MAX_SUMMARY_CHARS + 1_000);
+    }
+}
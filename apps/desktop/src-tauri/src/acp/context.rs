@@ -0,0 +1,141 @@
+//! Context window estimates
+//!
+//! Agents don't report how full their context is, so we approximate it from
+//! the characters sent and received in the current ACP session (~4 chars per
+//! token, like the usage estimates) and warn as it nears the model's limit.
+
+use serde::Serialize;
+use ts_rs::TS;
+
+/// Context sizes by model id prefix (longest match wins)
+const CONTEXT_LIMITS: &[(&str, u64)] = &[
+    ("claude", 200_000),
+    ("gemini", 1_048_576),
+    ("gpt-5", 400_000),
+    ("codex", 200_000),
+    ("o3", 200_000),
+    ("o4", 200_000),
+];
+
+/// Used for models we don't know (e.g. an agent's "default")
+const DEFAULT_CONTEXT_LIMIT: u64 = 128_000;
+
+/// Fraction of the window at which to warn
+const WARNING_RATIO: f64 = 0.8;
+const CRITICAL_RATIO: f64 = 0.95;
+
+pub fn context_limit(model: &str) -> u64 {
+    let model = model.to_lowercase();
+    CONTEXT_LIMITS
+        .iter()
+        .filter(|(prefix, _)| model.starts_with(prefix))
+        .max_by_key(|(prefix, _)| prefix.len())
+        .map(|(_, limit)| *limit)
+        .unwrap_or(DEFAULT_CONTEXT_LIMIT)
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq, PartialOrd, Ord, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum ContextLevel {
+    Ok,
+    /// Past 80% - time to think about compacting
+    Warning,
+    /// Past 95% - the agent may start dropping history
+    Critical,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, TS)]
+#[ts(export)]
+pub struct ContextUsage {
+    #[ts(type = "number")]
+    pub tokens: u64,
+    #[ts(type = "number")]
+    pub limit: u64,
+    pub level: ContextLevel,
+}
+
+/// Running estimate for one ACP session
+#[derive(Debug)]
+pub struct ContextTracker {
+    tokens: u64,
+    limit: u64,
+    /// Highest level already reported, so each warning fires once
+    reported: ContextLevel,
+}
+
+impl ContextTracker {
+    pub fn new(model: &str) -> Self {
+        Self {
+            tokens: 0,
+            limit: context_limit(model),
+            reported: ContextLevel::Ok,
+        }
+    }
+
+    fn level(&self) -> ContextLevel {
+        let ratio = self.tokens as f64 / self.limit as f64;
+        if ratio >= CRITICAL_RATIO {
+            ContextLevel::Critical
+        } else if ratio >= WARNING_RATIO {
+            ContextLevel::Warning
+        } else {
+            ContextLevel::Ok
+        }
+    }
+
+    /// Count a turn's tokens. Returns the usage when it first crosses into
+    /// a higher level.
+    pub fn add(&mut self, tokens: u64) -> Option<ContextUsage> {
+        self.tokens += tokens;
+        let level = self.level();
+        if level > self.reported {
+            self.reported = level;
+            return Some(self.usage());
+        }
+        None
+    }
+
+    /// Start over for a new ACP session
+    pub fn reset(&mut self) {
+        self.tokens = 0;
+        self.reported = ContextLevel::Ok;
+    }
+
+    pub fn usage(&self) -> ContextUsage {
+        ContextUsage {
+            tokens: self.tokens,
+            limit: self.limit,
+            level: self.level(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_context_limit() {
+        assert_eq!(context_limit("claude-sonnet-4-5-20250929"), 200_000);
+        assert_eq!(context_limit("gemini-2.5-pro"), 1_048_576);
+        assert_eq!(context_limit("gpt-5.2-codex"), 400_000);
+        assert_eq!(context_limit("default"), DEFAULT_CONTEXT_LIMIT);
+    }
+
+    #[test]
+    fn test_warns_once_per_level() {
+        let mut tracker = ContextTracker::new("claude-opus-4-5");
+        assert_eq!(tracker.add(100_000), None);
+        let warning = tracker.add(65_000).unwrap();
+        assert_eq!(warning.level, ContextLevel::Warning);
+        assert_eq!(warning.tokens, 165_000);
+        assert_eq!(tracker.add(5_000), None);
+        assert_eq!(tracker.add(25_000).unwrap().level, ContextLevel::Critical);
+        assert_eq!(tracker.add(50_000), None);
+
+        tracker.reset();
+        assert_eq!(tracker.usage().level, ContextLevel::Ok);
+        assert_eq!(tracker.add(170_000).unwrap().level, ContextLevel::Warning);
+    }
+}
@@ -0,0 +1,264 @@
+//! Coordination updates on follow-up prompts
+//!
+//! The coordination prompt is only prepended to a worker's first prompt, so
+//! workers never hear about tasks created or workers joining later. Each
+//! worker keeps a snapshot of the session's tasks and roster as of its last
+//! turn; when either changed, the next prompt starts with a short
+//! "Coordination Update" listing what changed and how many messages arrived.
+//! Updates are on by default and can be turned off per session.
+
+use crate::inbox::InboxManager;
+use crate::tasks::task::{Task, TaskStatus};
+use crate::tasks::TaskManager;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+/// Per-session switch (session_id -> enabled). Sessions not listed get updates.
+static COORDINATION_UPDATES: Lazy<Mutex<HashMap<String, bool>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Whether workers in the session get coordination updates
+pub fn updates_enabled(session_id: &str) -> bool {
+    COORDINATION_UPDATES
+        .lock()
+        .get(session_id)
+        .copied()
+        .unwrap_or(true)
+}
+
+/// What a worker last saw of its session
+#[derive(Debug, Clone, Default)]
+struct Snapshot {
+    /// task id -> task (ordered by id for stable output)
+    tasks: BTreeMap<String, Task>,
+    workers: Vec<String>,
+    messages: usize,
+}
+
+impl Snapshot {
+    fn capture(tasks: Vec<Task>, workers: Vec<String>, messages: usize) -> Self {
+        Self {
+            tasks: tasks.into_iter().map(|t| (t.id.clone(), t)).collect(),
+            workers,
+            messages,
+        }
+    }
+}
+
+fn status_name(status: &TaskStatus) -> &'static str {
+    match status {
+        TaskStatus::Pending => "pending",
+        TaskStatus::InProgress => "in_progress",
+        TaskStatus::Completed => "completed",
+        TaskStatus::Deleted => "deleted",
+    }
+}
+
+/// Describe what changed between two snapshots for `worker_id`. None when
+/// neither the tasks nor the roster changed. Changes to tasks the worker
+/// owns are left out, since it made them itself.
+fn describe_delta(worker_id: &str, before: &Snapshot, now: &Snapshot) -> Option<String> {
+    let mut new_tasks = Vec::new();
+    let mut updated = Vec::new();
+    for (id, task) in &now.tasks {
+        match before.tasks.get(id) {
+            None => new_tasks.push(format!("#{} {}", id, task.subject)),
+            Some(_) if task.owner.as_deref() == Some(worker_id) => {}
+            Some(old) => {
+                let mut changes = Vec::new();
+                if old.status != task.status {
+                    changes.push(status_name(&task.status).to_string());
+                }
+                if old.owner != task.owner {
+                    changes.push(match &task.owner {
+                        Some(owner) => format!("owned by {}", owner),
+                        None => "unassigned".to_string(),
+                    });
+                }
+                if old.blocked_by != task.blocked_by {
+                    changes.push(if task.blocked_by.is_empty() {
+                        "unblocked".to_string()
+                    } else {
+                        format!("blocked by {}", task.blocked_by.join(", "))
+                    });
+                }
+                if !changes.is_empty() {
+                    updated.push(format!("#{} {} → {}", id, task.subject, changes.join(", ")));
+                }
+            }
+        }
+    }
+    let removed: Vec<String> = before
+        .tasks
+        .iter()
+        .filter(|(id, _)| !now.tasks.contains_key(*id))
+        .map(|(id, task)| format!("#{} {}", id, task.subject))
+        .collect();
+    let joined: Vec<&str> = now
+        .workers
+        .iter()
+        .filter(|w| !before.workers.contains(w) && *w != worker_id)
+        .map(String::as_str)
+        .collect();
+    let left: Vec<&str> = before
+        .workers
+        .iter()
+        .filter(|w| !now.workers.contains(w))
+        .map(String::as_str)
+        .collect();
+
+    if new_tasks.is_empty()
+        && updated.is_empty()
+        && removed.is_empty()
+        && joined.is_empty()
+        && left.is_empty()
+    {
+        return None;
+    }
+
+    let mut out = String::from("## Coordination Update\n\nSince your last turn:\n");
+    for (label, items) in [
+        ("New tasks", new_tasks),
+        ("Updated tasks", updated),
+        ("Removed tasks", removed),
+    ] {
+        if !items.is_empty() {
+            out.push_str(&format!("- **{}:** {}\n", label, items.join("; ")));
+        }
+    }
+    if !joined.is_empty() {
+        out.push_str(&format!("- **Joined:** {}\n", joined.join(", ")));
+    }
+    if !left.is_empty() {
+        out.push_str(&format!("- **Left:** {}\n", left.join(", ")));
+    }
+    let new_messages = now.messages.saturating_sub(before.messages);
+    if new_messages > 0 {
+        out.push_str(&format!(
+            "- **{} new message{}** in your inbox (`swarm inbox read --unread`)\n",
+            new_messages,
+            if new_messages == 1 { "" } else { "s" }
+        ));
+    }
+    out.push_str("\nRun `swarm task list` for details.\n\n---\n\n");
+    Some(out)
+}
+
+/// Tracks one worker's view of its session between turns
+pub struct CoordinationTracker {
+    session_id: String,
+    worker_id: String,
+    task_manager: Arc<TaskManager>,
+    inbox_manager: Arc<InboxManager>,
+    seen: Snapshot,
+}
+
+impl CoordinationTracker {
+    /// Start tracking from the session's current state
+    pub fn new(
+        session_id: &str,
+        worker_id: &str,
+        task_manager: Arc<TaskManager>,
+        inbox_manager: Arc<InboxManager>,
+    ) -> Self {
+        let mut tracker = Self {
+            session_id: session_id.to_string(),
+            worker_id: worker_id.to_string(),
+            task_manager,
+            inbox_manager,
+            seen: Snapshot::default(),
+        };
+        tracker.seen = tracker.capture();
+        tracker
+    }
+
+    fn capture(&self) -> Snapshot {
+        Snapshot::capture(
+            self.task_manager.list(),
+            self.inbox_manager.get_workers(),
+            self.inbox_manager.count(&self.worker_id, false),
+        )
+    }
+
+    /// The next prompt to send: `message`, preceded by a coordination update
+    /// if tasks or the roster changed since the last turn
+    pub fn prepare(&mut self, message: &str) -> String {
+        let now = self.capture();
+        let delta = updates_enabled(&self.session_id)
+            .then(|| describe_delta(&self.worker_id, &self.seen, &now))
+            .flatten();
+        self.seen = now;
+        match delta {
+            Some(delta) => format!("{}{}", delta, message),
+            None => message.to_string(),
+        }
+    }
+}
+
+/// Turn coordination updates on follow-up prompts on or off for a session
+#[tauri::command]
+pub fn set_coordination_updates(session_id: String, enabled: bool) {
+    COORDINATION_UPDATES.lock().insert(session_id, enabled);
+}
+
+/// Whether a session's workers get coordination updates
+#[tauri::command]
+pub fn get_coordination_updates(session_id: String) -> bool {
+    updates_enabled(&session_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tracker_reports_changes_once() {
+        let tasks = Arc::new(TaskManager::new("delta-test".to_string()));
+        let inbox = Arc::new(InboxManager::new("delta-test".to_string()));
+        inbox.register_worker("worker-1");
+        let setup = tasks.create("Setup".to_string(), "".to_string(), None);
+
+        let mut tracker =
+            CoordinationTracker::new("delta-test", "worker-1", tasks.clone(), inbox.clone());
+        assert_eq!(tracker.prepare("go on"), "go on");
+
+        tasks.create("Write docs".to_string(), "".to_string(), None);
+        tasks.update(
+            &setup.id,
+            crate::tasks::task::TaskUpdate {
+                status: Some(TaskStatus::Completed),
+                owner: Some("worker-2".to_string()),
+                ..Default::default()
+            },
+        );
+        inbox.register_worker("worker-2");
+        inbox.send(
+            "worker-2",
+            "worker-1",
+            crate::inbox::message::MessageType::Text {
+                content: "hi".to_string(),
+            },
+        );
+
+        let prompt = tracker.prepare("go on");
+        assert!(prompt.starts_with("## Coordination Update"));
+        assert!(prompt.contains("- **New tasks:** #2 Write docs\n"));
+        assert!(prompt.contains("#1 Setup → completed, owned by worker-2"));
+        assert!(prompt.contains("- **Joined:** worker-2\n"));
+        assert!(prompt.contains("**1 new message** in your inbox"));
+        assert!(prompt.ends_with("---\n\ngo on"));
+
+        // Nothing new since, and the worker's own claims aren't reported
+        assert_eq!(tracker.prepare("next"), "next");
+        tasks.claim_available("worker-1");
+        assert_eq!(tracker.prepare("next"), "next");
+
+        set_coordination_updates("delta-test".to_string(), false);
+        tasks.create("Deploy".to_string(), "".to_string(), None);
+        assert_eq!(tracker.prepare("next"), "next");
+        set_coordination_updates("delta-test".to_string(), true);
+        assert_eq!(tracker.prepare("next"), "next");
+    }
+}
@@ -2,106 +2,217 @@
 //!
 //! Generates the system context that gets prepended to agent prompts
 //! to enable swarm coordination via Task and Inbox primitives.
-
+//!
+//! The prompt is a minijinja template. The built-in one
+//! (`prompts/coordination.md`) can be overridden per user at
+//! `~/.crafter/prompts/coordination.md` and per project at
+//! `<project>/.crafter/prompts/coordination.md` (the project wins).
+//! Templates get `worker_id`, `session_id`, `role` ("leader" or "worker"),
+//! `is_leader`, `role_description`, `tasks` (the task objects),
+//! `task_list` (tasks formatted as a checklist), `project` (see
+//! `ProjectInfo`), `project_context` (how to build and test the repo),
+//! `project_instructions` (context files the agent doesn't load itself, see
+//! `agent::context_files`), `response_language` (the language to answer
+//! in, see `acp::language`) and `scratch_dir` (the session's directory
+//! for temporary files, see `agent::scratch`).
+
+use crate::acp::language::{prompt_language, prompt_strings, PromptLanguage};
+use crate::agent::context_files::context_instructions;
+use crate::agent::project::{project_info, ProjectInfo};
+use crate::agent::scratch;
+use crate::settings::load_settings;
 use crate::tasks::task::{Task, TaskStatus};
+use crate::AppState;
+use minijinja::Environment;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::State;
+
+/// Built-in coordination template
+pub const DEFAULT_COORDINATION_TEMPLATE: &str = include_str!("prompts/coordination.md");
+
+const TEMPLATE_FILE: &str = "coordination.md";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CoordinationPromptPreview {
+    pub prompt: String,
+    /// The template used
+    pub template: String,
+    /// Path of the override used, "builtin" or "draft"
+    pub source: String,
+}
 
-/// Build the coordination prompt to inject into agent context
-pub fn build_coordination_prompt(
+/// Override locations, most specific first
+fn template_paths(project_dir: Option<&Path>) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    if let Some(dir) = project_dir {
+        paths.push(dir.join(".crafter").join("prompts").join(TEMPLATE_FILE));
+    }
+    if let Some(home) = dirs::home_dir() {
+        paths.push(home.join(".crafter").join("prompts").join(TEMPLATE_FILE));
+    }
+    paths
+}
+
+/// The coordination template and where it came from
+fn load_template(project_dir: Option<&Path>) -> (String, String) {
+    for path in template_paths(project_dir) {
+        if let Ok(template) = fs::read_to_string(&path) {
+            return (template, path.to_string_lossy().to_string());
+        }
+    }
+    (
+        DEFAULT_COORDINATION_TEMPLATE.to_string(),
+        "builtin".to_string(),
+    )
+}
+
+/// Render a coordination template
+#[allow(clippy::too_many_arguments)]
+fn render(
+    template: &str,
     worker_id: &str,
     session_id: &str,
     is_leader: bool,
-    initial_tasks: &[Task],
-) -> String {
+    tasks: &[Task],
+    project: Option<&ProjectInfo>,
+    project_instructions: Option<&str>,
+    language: Option<&PromptLanguage>,
+) -> Result<String, String> {
+    let strings = prompt_strings(language);
     let role_description = if is_leader {
-        "You are the **leader** of this session. Coordinate work, create tasks for the team, and manage other workers."
+        strings.leader_role
     } else {
-        "You are a **worker** in this session. Claim tasks, complete work, and communicate with your team."
+        strings.worker_role
     };
 
-    let task_list = format_tasks(initial_tasks);
-
-    format!(
-        r#"## Swarm Coordination
-
-You are worker `{worker_id}` in session `{session_id}`.
-{role_description}
-
-### Available Commands (via Bash tool)
-
-You can coordinate with other workers using these commands:
-
-**Task Management:**
-```bash
-swarm task list                              # See all tasks
-swarm task get <id>                          # Get task details
-swarm task claim                             # Claim next available task
-swarm task update <id> completed             # Mark task done
-swarm task update <id> in_progress           # Mark task in progress
-swarm task create "Subject" "Description"   # Create new task
-swarm task delete <id>                       # Delete a task
-```
-
-**Communication:**
-```bash
-swarm inbox read                             # Check messages from other workers
-swarm inbox read --unread                    # Check only unread messages
-swarm inbox write <worker-id> "message"      # Send to specific worker
-swarm inbox broadcast "message"              # Send to all workers
-swarm inbox workers                          # List all workers
-swarm inbox count                            # Count unread messages
-swarm inbox mark-read                        # Mark all messages as read
-```
-
-### Coordination Workflow
-
-1. **Check inbox first**: `swarm inbox read --unread`
-2. **Review available tasks**: `swarm task list`
-3. **Claim work**: `swarm task claim`
-4. **Do the actual work** (write code, edit files, etc.)
-5. **Mark complete**: `swarm task update <id> completed`
-6. **Notify team**: `swarm inbox broadcast "Completed: <subject>"`
-7. **Repeat** or wait for new work
-
-### Task Status Flow
-
-```
-pending → in_progress → completed
-                     ↘ deleted
-```
-
-- **pending**: Not started, available to claim
-- **in_progress**: Someone is working on it
-- **completed**: Done
-- **deleted**: Removed
-
-### Task Dependencies
-
-Tasks can have dependencies (blocked_by). A task is only claimable when:
-- Status is `pending`
-- No owner assigned
-- All `blocked_by` tasks are completed
-
-### Current Session State
-
-**Workers in session:** Check with `swarm inbox workers`
-
-**Current Tasks:**
-{task_list}
-
----
+    let mut env = Environment::new();
+    env.set_trim_blocks(true);
+    env.set_lstrip_blocks(true);
+    env.set_keep_trailing_newline(true);
+    let template = env
+        .template_from_str(template)
+        .map_err(|e| format!("Invalid coordination template: {}", e))?;
+    template
+        .render(minijinja::context! {
+            worker_id,
+            session_id,
+            is_leader,
+            role => if is_leader { "leader" } else { "worker" },
+            role_description,
+            tasks,
+            task_list => format_tasks(tasks, strings.no_tasks).trim_end(),
+            project,
+            project_context => project
+                .and_then(ProjectInfo::prompt_context)
+                .map(|context| context.trim_end().to_string()),
+            project_instructions,
+            response_language => language.map(PromptLanguage::name),
+            scratch_dir => scratch::path(session_id)
+                .map(|dir| dir.to_string_lossy().to_string()),
+        })
+        .map_err(|e| format!("Failed to render coordination template: {}", e))
+}
 
-"#,
-        worker_id = worker_id,
-        session_id = session_id,
-        role_description = role_description,
-        task_list = task_list,
+/// Build the coordination prompt to inject into agent context, from the
+/// template overriding the built-in one for `project` (if any). A broken
+/// override falls back to the built-in template.
+pub fn build_coordination_prompt(
+    worker_id: &str,
+    session_id: &str,
+    is_leader: bool,
+    initial_tasks: &[Task],
+    project: Option<&ProjectInfo>,
+    project_instructions: Option<&str>,
+    language: Option<&PromptLanguage>,
+) -> String {
+    let (template, source) = load_template(project.map(|p| Path::new(&p.path)));
+    render(
+        &template,
+        worker_id,
+        session_id,
+        is_leader,
+        initial_tasks,
+        project,
+        project_instructions,
+        language,
     )
+    .unwrap_or_else(|e| {
+        eprintln!(
+            "[Coordination] {} ({}), using the built-in template",
+            e, source
+        );
+        render(
+            DEFAULT_COORDINATION_TEMPLATE,
+            worker_id,
+            session_id,
+            is_leader,
+            initial_tasks,
+            project,
+            project_instructions,
+            language,
+        )
+        .expect("built-in coordination template renders")
+    })
+}
+
+/// Render the coordination prompt a worker in `cwd` would get. `template`
+/// previews unsaved edits instead of the template on disk; `agent_id`
+/// decides which project context files are injected; `language` previews a
+/// response language other than the session's (or the global) one.
+#[tauri::command]
+pub fn preview_coordination_prompt(
+    cwd: String,
+    session_id: Option<String>,
+    worker_id: Option<String>,
+    is_leader: Option<bool>,
+    template: Option<String>,
+    agent_id: Option<String>,
+    language: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<CoordinationPromptPreview, String> {
+    let tasks = match &session_id {
+        Some(session_id) => state.get_task_manager(session_id)?.list(),
+        None => Vec::new(),
+    };
+    let project = project_info(Path::new(&cwd));
+    let instructions = agent_id.as_deref().and_then(|agent_id| {
+        context_instructions(Path::new(&cwd), agent_id, &load_settings().context_files)
+    });
+    let language = match language {
+        Some(respond_in) => Some(PromptLanguage {
+            respond_in,
+            localize: load_settings().language.localize_prompts,
+        })
+        .filter(|language| !language.respond_in.trim().is_empty()),
+        None => prompt_language(session_id.as_deref().unwrap_or_default()),
+    };
+    let (template, source) = match template {
+        Some(template) => (template, "draft".to_string()),
+        None => load_template(Some(Path::new(&cwd))),
+    };
+    let prompt = render(
+        &template,
+        worker_id.as_deref().unwrap_or("worker-1"),
+        session_id.as_deref().unwrap_or("preview"),
+        is_leader.unwrap_or(true),
+        &tasks,
+        Some(&project),
+        instructions.as_deref(),
+        language.as_ref(),
+    )?;
+    Ok(CoordinationPromptPreview {
+        prompt,
+        template,
+        source,
+    })
 }
 
 /// Format tasks for display in the prompt
-fn format_tasks(tasks: &[Task]) -> String {
+fn format_tasks(tasks: &[Task], no_tasks: &str) -> String {
     if tasks.is_empty() {
-        return "No tasks created yet. Create some with `swarm task create`.".to_string();
+        return no_tasks.to_string();
     }
 
     let mut output = String::new();
@@ -181,7 +292,8 @@ mod tests {
             make_task("2", "Implement feature", TaskStatus::Pending),
         ];
 
-        let prompt = build_coordination_prompt("worker-1", "session-123", true, &tasks);
+        let prompt =
+            build_coordination_prompt("worker-1", "session-123", true, &tasks, None, None, None);
 
         assert!(prompt.contains("worker `worker-1`"));
         assert!(prompt.contains("session `session-123`"));
@@ -192,18 +304,100 @@ mod tests {
 
     #[test]
     fn test_build_coordination_prompt_worker() {
-        let prompt = build_coordination_prompt("worker-2", "session-456", false, &[]);
+        let prompt =
+            build_coordination_prompt("worker-2", "session-456", false, &[], None, None, None);
 
         assert!(prompt.contains("**worker**"));
         assert!(prompt.contains("No tasks created yet"));
     }
 
+    #[test]
+    fn test_build_coordination_prompt_with_project() {
+        let project = ProjectInfo {
+            name: "app".to_string(),
+            path: "/tmp/app".to_string(),
+            git_branch: None,
+            git_status: None,
+            env_files: vec![],
+            context_files: vec![],
+            language: Some("rust".to_string()),
+            toolchains: vec![crate::agent::project::Toolchain {
+                language: "rust".to_string(),
+                tool: "cargo".to_string(),
+                manifest: "Cargo.toml".to_string(),
+            }],
+            frameworks: vec![],
+            scripts: vec![],
+            build_command: Some("cargo build".to_string()),
+            test_command: Some("cargo test".to_string()),
+            workspaces: vec![],
+            git: None,
+        };
+        let instructions = "### Project Instructions\n\n#### AGENTS.md\n\nUse pnpm.";
+        let prompt = build_coordination_prompt(
+            "worker-1",
+            "session-1",
+            true,
+            &[],
+            Some(&project),
+            Some(instructions),
+            None,
+        );
+
+        assert!(prompt.contains("### Project: app"));
+        assert!(prompt.contains("- **Test:** `cargo test`"));
+        assert!(prompt.contains("#### AGENTS.md\n\nUse pnpm."));
+    }
+
+    #[test]
+    fn test_build_coordination_prompt_language() {
+        let mut language = PromptLanguage {
+            respond_in: "es".to_string(),
+            localize: false,
+        };
+        let prompt =
+            build_coordination_prompt("worker-1", "s", true, &[], None, None, Some(&language));
+        assert!(prompt.contains("Respond to the user in Spanish"));
+        assert!(prompt.contains("You are the **leader**"));
+        assert!(prompt.contains("No tasks created yet"));
+
+        language.localize = true;
+        let prompt =
+            build_coordination_prompt("worker-1", "s", true, &[], None, None, Some(&language));
+        assert!(prompt.contains("Eres el **líder**"));
+        assert!(prompt.contains("Todavía no hay tareas"));
+    }
+
+    #[test]
+    fn test_project_template_override() {
+        let dir = tempfile::tempdir().unwrap();
+        let prompts = dir.path().join(".crafter").join("prompts");
+        fs::create_dir_all(&prompts).unwrap();
+        fs::write(
+            prompts.join(TEMPLATE_FILE),
+            "{{ worker_id }} is the {{ role }}\n{% for task in tasks %}- {{ task.subject }}\n{% endfor %}",
+        )
+        .unwrap();
+        let mut project = project_info(dir.path());
+        project.path = dir.path().to_string_lossy().to_string();
+        let tasks = vec![make_task("1", "Write docs", TaskStatus::Pending)];
+
+        let prompt = build_coordination_prompt("worker-3", "s", false, &tasks, Some(&project), None, None);
+        assert_eq!(prompt, "worker-3 is the worker\n- Write docs\n");
+
+        // A broken override falls back to the built-in template
+        fs::write(prompts.join(TEMPLATE_FILE), "{% if %}").unwrap();
+        let prompt = build_coordination_prompt("worker-3", "s", false, &tasks, Some(&project), None, None);
+        assert!(prompt.contains("## Swarm Coordination"));
+        assert!(prompt.contains("[ ] #1 Write docs"));
+    }
+
     #[test]
     fn test_format_tasks_with_dependencies() {
         let mut task = make_task("3", "Deploy", TaskStatus::Pending);
         task.blocked_by = vec!["1".to_string(), "2".to_string()];
 
-        let output = format_tasks(&[task]);
+        let output = format_tasks(&[task], "");
         assert!(output.contains("blocked by: 1, 2"));
     }
 }
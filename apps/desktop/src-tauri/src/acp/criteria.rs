@@ -0,0 +1,257 @@
+//! Session acceptance criteria ("definition of done")
+//!
+//! Interactive sessions can carry the same acceptance criteria as PRD
+//! stories. After each completed prompt they're checked with the PRD
+//! verifier and the results are emitted as `session-criteria`. With
+//! `auto_follow_up` on, failures are sent back to the agent as the next
+//! prompt, up to `max_follow_ups` times per user prompt.
+
+use crate::acp::events::EventSink;
+use crate::acp::session_store::SessionStore;
+use crate::events::SessionCriteriaEvent;
+use crate::prd::types::{AcceptanceCriterion, CriterionStatus, CriterionType};
+use crate::prd::verifier::verify_criteria;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use ts_rs::TS;
+
+/// Characters of each failure's output included in a follow-up prompt
+const MAX_ERROR_CHARS: usize = 1500;
+
+/// Criteria attached to live sessions (session_id -> criteria)
+static SESSION_CRITERIA: Lazy<Mutex<HashMap<String, SessionCriteria>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct CriterionResult {
+    pub criterion: AcceptanceCriterion,
+    pub status: CriterionStatus,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionCriteria {
+    pub criteria: Vec<AcceptanceCriterion>,
+    /// Send failures back to the agent automatically
+    #[serde(default)]
+    pub auto_follow_up: bool,
+    /// Most automatic follow-ups per user prompt
+    #[serde(default = "default_max_follow_ups")]
+    pub max_follow_ups: u32,
+    /// Results of the last check
+    #[serde(default)]
+    pub last_results: Vec<CriterionResult>,
+}
+
+fn default_max_follow_ups() -> u32 {
+    3
+}
+
+/// The session's criteria, loading them from the session store the first
+/// time a restored session is seen
+pub fn criteria_for(session_id: &str) -> Option<SessionCriteria> {
+    if let Some(criteria) = SESSION_CRITERIA.lock().get(session_id) {
+        return Some(criteria.clone());
+    }
+    let store = SessionStore::new().ok()?;
+    if !store.session_exists(session_id) {
+        return None;
+    }
+    let criteria = store.load_session(session_id).ok()?.criteria?;
+    SESSION_CRITERIA
+        .lock()
+        .insert(session_id.to_string(), criteria.clone());
+    Some(criteria)
+}
+
+/// Criteria attached while the session was live
+pub fn get_live_criteria(session_id: &str) -> Option<SessionCriteria> {
+    SESSION_CRITERIA.lock().get(session_id).cloned()
+}
+
+/// Drop a session's live criteria (on session cleanup)
+pub fn clear_session_criteria(session_id: &str) {
+    SESSION_CRITERIA.lock().remove(session_id);
+}
+
+/// Short label for a criterion in prompts
+fn describe(criterion: &AcceptanceCriterion) -> String {
+    if let Some(description) = &criterion.description {
+        return description.clone();
+    }
+    match criterion.criterion_type {
+        CriterionType::Test => format!("`{}` passes", criterion.command.as_deref().unwrap_or("")),
+        CriterionType::FileExists => {
+            format!("{} exists", criterion.path.as_deref().unwrap_or(""))
+        }
+        CriterionType::Pattern => format!(
+            "{} matches `{}`",
+            criterion.file.as_deref().unwrap_or(""),
+            criterion.pattern.as_deref().unwrap_or("")
+        ),
+        CriterionType::Custom => {
+            format!("`{}` succeeds", criterion.script.as_deref().unwrap_or(""))
+        }
+    }
+}
+
+/// Prompt listing the failing criteria, or None if all pass
+pub fn follow_up_prompt(results: &[CriterionResult]) -> Option<String> {
+    let failures: Vec<&CriterionResult> = results.iter().filter(|r| !r.status.passed).collect();
+    if failures.is_empty() {
+        return None;
+    }
+    let mut prompt =
+        String::from("Some acceptance criteria for this session are still failing:\n\n");
+    for (i, failure) in failures.iter().enumerate() {
+        prompt.push_str(&format!("{}. {}\n", i + 1, describe(&failure.criterion)));
+        if let Some(error) = &failure.status.error {
+            let error: String = error.trim().chars().take(MAX_ERROR_CHARS).collect();
+            prompt.push_str(&format!("```\n{}\n```\n", error));
+        }
+    }
+    prompt.push_str("\nPlease fix these and make sure every criterion passes.");
+    Some(prompt)
+}
+
+/// Check the session's criteria after a completed prompt. Returns the
+/// follow-up prompt to send, if failures should go back to the agent.
+pub async fn check_session(
+    events: &EventSink,
+    session_id: &str,
+    worker_id: &str,
+    cwd: Option<&str>,
+    follow_ups_sent: u32,
+) -> Option<String> {
+    let criteria = criteria_for(session_id).filter(|c| !c.criteria.is_empty())?;
+    let statuses = verify_criteria(&criteria.criteria, cwd.map(Path::new)).await;
+    let results: Vec<CriterionResult> = criteria
+        .criteria
+        .iter()
+        .cloned()
+        .zip(statuses)
+        .map(|(criterion, status)| CriterionResult { criterion, status })
+        .collect();
+    let passed = results.iter().all(|r| r.status.passed);
+
+    let follow_up = if criteria.auto_follow_up && follow_ups_sent < criteria.max_follow_ups {
+        follow_up_prompt(&results)
+    } else {
+        None
+    };
+
+    if let Some(live) = SESSION_CRITERIA.lock().get_mut(session_id) {
+        live.last_results = results.clone();
+    }
+    eprintln!(
+        "[Criteria] Session {}: {}/{} passing",
+        session_id,
+        results.iter().filter(|r| r.status.passed).count(),
+        results.len()
+    );
+    events.send(&SessionCriteriaEvent {
+        session_id: session_id.to_string(),
+        worker_id: worker_id.to_string(),
+        passed,
+        results,
+        follow_up: follow_up.clone(),
+        follow_ups_sent,
+        timestamp: chrono::Utc::now().timestamp_millis(),
+    });
+    follow_up
+}
+
+/// Attach acceptance criteria to a session (an empty list removes them)
+#[tauri::command]
+pub fn set_session_criteria(
+    session_id: String,
+    criteria: Vec<AcceptanceCriterion>,
+    auto_follow_up: Option<bool>,
+    max_follow_ups: Option<u32>,
+) -> Result<Option<SessionCriteria>, String> {
+    let value = if criteria.is_empty() {
+        SESSION_CRITERIA.lock().remove(&session_id);
+        None
+    } else {
+        let value = SessionCriteria {
+            criteria,
+            auto_follow_up: auto_follow_up.unwrap_or(false),
+            max_follow_ups: max_follow_ups.unwrap_or_else(default_max_follow_ups),
+            last_results: Vec::new(),
+        };
+        SESSION_CRITERIA
+            .lock()
+            .insert(session_id.clone(), value.clone());
+        Some(value)
+    };
+
+    // Keep the persisted copy in sync for sessions that are already saved
+    let store = SessionStore::new()?;
+    if store.session_exists(&session_id) {
+        let mut session = store.load_session(&session_id)?;
+        session.criteria = value.clone();
+        store.save_session(&session)?;
+    }
+    Ok(value)
+}
+
+/// Get a session's acceptance criteria and the results of the last check
+#[tauri::command]
+pub fn get_session_criteria(session_id: String) -> Result<Option<SessionCriteria>, String> {
+    Ok(criteria_for(&session_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn criterion(criterion_type: CriterionType, path: Option<&str>) -> AcceptanceCriterion {
+        AcceptanceCriterion {
+            criterion_type,
+            command: None,
+            path: path.map(str::to_string),
+            file: None,
+            pattern: None,
+            script: None,
+            description: None,
+        }
+    }
+
+    #[test]
+    fn test_follow_up_prompt() {
+        let passing = CriterionResult {
+            criterion: criterion(CriterionType::FileExists, Some("README.md")),
+            status: CriterionStatus::passed(),
+        };
+        assert_eq!(follow_up_prompt(std::slice::from_ref(&passing)), None);
+
+        let mut test = criterion(CriterionType::Test, None);
+        test.command = Some("cargo test".to_string());
+        let failing = CriterionResult {
+            criterion: test,
+            status: CriterionStatus::failed("test a::b ... FAILED\n".to_string()),
+        };
+        let prompt = follow_up_prompt(&[passing, failing]).unwrap();
+        assert!(prompt.contains("1. `cargo test` passes\n```\ntest a::b ... FAILED\n```"));
+        assert!(!prompt.contains("README.md"));
+    }
+
+    #[tokio::test]
+    async fn test_verify_criteria_in_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("done.txt"), "ok").unwrap();
+        let statuses = verify_criteria(
+            &[
+                criterion(CriterionType::FileExists, Some("done.txt")),
+                criterion(CriterionType::FileExists, Some("missing.txt")),
+            ],
+            Some(dir.path()),
+        )
+        .await;
+        assert!(statuses[0].passed);
+        assert!(!statuses[1].passed);
+    }
+}
@@ -0,0 +1,185 @@
+//! Delta event batching
+//!
+//! Fast models can produce hundreds of `AgentMessageChunk`s per second.
+//! Emitting each one individually floods the IPC bridge, so deltas are
+//! coalesced here and flushed at a fixed rate (`delta_flush_hz`). Any other
+//! worker event must call `flush()` first so the frontend still sees events
+//! in order.
+//!
+//! Redaction needs whole secrets, so each emit (batched or not) keeps back
+//! the tail that could be the start of one still arriving (see
+//! `Redactor::streamable_len`); `flush()` sends it.
+
+use crate::acp::events::EventSink;
+use crate::events::{WorkerEvent, WorkerEventType, WorkerStreamEvent};
+use crate::secrets::redact::{redactor_for_worker, Redactor};
+use parking_lot::Mutex;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Emit a `worker-stream-{worker_id}` event. `prompt_id` identifies the
+/// turn the event belongs to. Secrets in the event's text are redacted.
+pub fn emit_worker_event(
+    events: &EventSink,
+    worker_id: &str,
+    prompt_id: Option<&str>,
+    kind: WorkerEventType,
+) {
+    events.send(&WorkerStreamEvent {
+        worker_id: worker_id.to_string(),
+        event: WorkerEvent {
+            prompt_id: prompt_id.map(String::from),
+            kind: kind.redact(&redactor_for_worker(worker_id)),
+        },
+    });
+}
+
+/// Flush interval for a given rate in Hz (`None` when batching is disabled)
+pub fn flush_interval(hz: u32) -> Option<Duration> {
+    if hz == 0 {
+        None
+    } else {
+        Some(Duration::from_millis((1000 / hz.min(1000)) as u64))
+    }
+}
+
+struct BatchState {
+    pending: String,
+    /// Turn the pending text belongs to
+    prompt_id: Option<String>,
+    flush_scheduled: bool,
+}
+
+impl BatchState {
+    /// Take the pending text to emit: all of it, or with a redactor only
+    /// what can't hide the start of a secret
+    fn take(&mut self, redactor: Option<&Redactor>) -> (String, Option<String>) {
+        self.flush_scheduled = false;
+        let len = redactor.map_or(self.pending.len(), |r| r.streamable_len(&self.pending));
+        let rest = self.pending.split_off(len);
+        let text = std::mem::replace(&mut self.pending, rest);
+        let prompt_id = if self.pending.is_empty() {
+            self.prompt_id.take()
+        } else {
+            self.prompt_id.clone()
+        };
+        (text, prompt_id)
+    }
+}
+
+/// Coalesces delta text and emits it at most once per interval
+pub struct DeltaBatcher {
+    events: EventSink,
+    worker_id: String,
+    interval: Option<Duration>,
+    state: Mutex<BatchState>,
+}
+
+impl DeltaBatcher {
+    pub fn new(events: EventSink, worker_id: String, hz: u32) -> Arc<Self> {
+        Arc::new(Self {
+            events,
+            worker_id,
+            interval: flush_interval(hz),
+            state: Mutex::new(BatchState {
+                pending: String::new(),
+                prompt_id: None,
+                flush_scheduled: false,
+            }),
+        })
+    }
+
+    /// Queue delta text. Must be called from within a tokio `LocalSet`
+    /// when batching is on.
+    pub fn push(self: &Arc<Self>, text: &str, prompt_id: Option<String>) {
+        // Never merge text from different turns into one delta
+        let turn_changed = {
+            let state = self.state.lock();
+            !state.pending.is_empty() && state.prompt_id != prompt_id
+        };
+        if turn_changed {
+            self.flush();
+        }
+
+        let schedule = {
+            let mut state = self.state.lock();
+            state.pending.push_str(text);
+            state.prompt_id = prompt_id;
+            let schedule = self.interval.is_some() && !state.flush_scheduled;
+            state.flush_scheduled |= schedule;
+            schedule
+        };
+
+        match self.interval {
+            None => self.emit(false),
+            Some(interval) if schedule => {
+                let batcher = self.clone();
+                tokio::task::spawn_local(async move {
+                    tokio::time::sleep(interval).await;
+                    batcher.emit(false);
+                });
+            }
+            Some(_) => {}
+        }
+    }
+
+    /// Emit all pending delta text immediately
+    pub fn flush(&self) {
+        self.emit(true);
+    }
+
+    /// Emit pending text, all of it or only what can't hide part of a secret
+    fn emit(&self, all: bool) {
+        let redactor = (!all).then(|| redactor_for_worker(&self.worker_id));
+        let (text, prompt_id) = self.state.lock().take(redactor.as_deref());
+        if !text.is_empty() {
+            emit_worker_event(
+                &self.events,
+                &self.worker_id,
+                prompt_id.as_deref(),
+                WorkerEventType::Delta { text },
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flush_interval() {
+        assert_eq!(flush_interval(0), None);
+        assert_eq!(flush_interval(30), Some(Duration::from_millis(33)));
+        assert_eq!(flush_interval(60), Some(Duration::from_millis(16)));
+        assert_eq!(flush_interval(5000), Some(Duration::from_millis(1)));
+    }
+
+    #[test]
+    fn test_secret_split_across_pushes() {
+        let redactor = Redactor::new(Vec::new(), &[], Vec::new());
+        let mut state = BatchState {
+            pending: String::new(),
+            prompt_id: None,
+            flush_scheduled: false,
+        };
+        let token = format!("sk-ant-api03-{}", "a".repeat(40));
+        let (head, tail) = token.split_at(10);
+        let filler = "x".repeat(300);
+
+        // Each emitted delta is redacted on its own
+        let mut streamed = Vec::new();
+        for chunk in [format!("{} {}", filler, head), format!("{} done", tail)] {
+            state.pending.push_str(&chunk);
+            streamed.push(state.take(Some(&redactor)).0);
+        }
+        streamed.push(state.take(None).0);
+        let redacted: Vec<String> = streamed
+            .iter()
+            .map(|text| redactor.redact(text).into_owned())
+            .collect();
+
+        assert!(redacted.iter().all(|text| !text.contains("sk-ant")));
+        assert_eq!(redacted.concat(), format!("{} [REDACTED] done", filler));
+    }
+}
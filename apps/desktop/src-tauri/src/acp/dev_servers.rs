@@ -0,0 +1,205 @@
+//! Dev servers started by agents
+//!
+//! Output of agent terminals and service terminals is scanned for local
+//! server addresses: URLs like `http://localhost:5173/`, bare
+//! `127.0.0.1:8000`, and "listening on port 3000" lines. Each new port
+//! found for a session is reported as a "dev-server-detected" event and
+//! listed by `list_detected_servers`, so the UI can offer a preview of
+//! whatever the agent just started.
+
+use crate::events::DevServerDetectedEvent;
+use crate::time::now_ms;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use regex::Regex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// How long `list_detected_servers` waits for a server to accept a
+/// connection
+const PROBE_TIMEOUT: Duration = Duration::from_millis(300);
+
+static ANSI_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\x1b\[[0-9;?]*[ -/]*[@-~]|\x1b\][^\x07]*\x07").unwrap());
+
+/// `http://localhost:3000/path` and friends; without a scheme a port is
+/// required
+static ADDRESS_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?i)\b(?:(https?)://)?(localhost|127\.0\.0\.1|0\.0\.0\.0|\[::1?\])(?::(\d{2,5}))?(/[^\s'`<>()\[\]]*)?",
+    )
+    .unwrap()
+});
+
+/// "Listening on port 3000", "server running at port 8080"
+static PORT_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\b(?:listening|running|serving|started|available)\b.{0,40}?\bport\s+(\d{2,5})\b")
+        .unwrap()
+});
+
+/// Servers found per session (session_id -> servers, in order found)
+static DETECTED: Lazy<Mutex<HashMap<String, Vec<DetectedServer>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct DetectedServer {
+    pub url: String,
+    pub port: u16,
+    /// Agent or service terminal whose output it appeared in
+    pub terminal_id: String,
+    pub worker_id: Option<String>,
+    pub detected_at: i64,
+    /// Whether the port accepted a connection when listed
+    pub alive: bool,
+}
+
+/// Local server URLs in some terminal output, with their ports
+pub fn detect_urls(output: &str) -> Vec<(String, u16)> {
+    let text = ANSI_RE.replace_all(output, "");
+    let mut found: Vec<(String, u16)> = Vec::new();
+    let mut add = |url: String, port: u16| {
+        if port > 0 && !found.iter().any(|(_, p)| *p == port) {
+            found.push((url, port));
+        }
+    };
+
+    for caps in ADDRESS_RE.captures_iter(&text) {
+        let scheme = caps.get(1).map(|m| m.as_str().to_lowercase());
+        let port = caps.get(3).and_then(|m| m.as_str().parse::<u16>().ok());
+        let port = match (&scheme, port) {
+            (_, Some(port)) => port,
+            (Some(scheme), None) if scheme == "https" => 443,
+            (Some(_), None) => 80,
+            // "localhost" in prose, not an address
+            (None, None) => continue,
+        };
+        let path = caps
+            .get(4)
+            .map(|m| m.as_str().trim_end_matches(['.', ',', ';', ':', '!', '?']))
+            .unwrap_or("/");
+        let url = format!(
+            "{}://localhost:{}{}",
+            scheme.as_deref().unwrap_or("http"),
+            port,
+            if path.is_empty() { "/" } else { path }
+        );
+        add(url, port);
+    }
+    for caps in PORT_RE.captures_iter(&text) {
+        if let Ok(port) = caps[1].parse::<u16>() {
+            add(format!("http://localhost:{}/", port), port);
+        }
+    }
+    found
+}
+
+/// Scan terminal output and remember the servers not seen before in the
+/// session, returning their events for the caller to emit
+pub fn record(
+    session_id: &str,
+    terminal_id: &str,
+    worker_id: Option<&str>,
+    output: &str,
+) -> Vec<DevServerDetectedEvent> {
+    let found = detect_urls(output);
+    if found.is_empty() {
+        return Vec::new();
+    }
+    let mut detected = DETECTED.lock();
+    let servers = detected.entry(session_id.to_string()).or_default();
+    let mut events = Vec::new();
+    for (url, port) in found {
+        if servers.iter().any(|server| server.port == port) {
+            continue;
+        }
+        servers.push(DetectedServer {
+            url: url.clone(),
+            port,
+            terminal_id: terminal_id.to_string(),
+            worker_id: worker_id.map(str::to_string),
+            detected_at: now_ms(),
+            alive: true,
+        });
+        events.push(DevServerDetectedEvent {
+            session_id: session_id.to_string(),
+            terminal_id: terminal_id.to_string(),
+            worker_id: worker_id.map(str::to_string),
+            url,
+            port,
+        });
+    }
+    events
+}
+
+/// Forget a session's servers
+pub fn clear_session(session_id: &str) {
+    DETECTED.lock().remove(session_id);
+}
+
+async fn is_listening(port: u16) -> bool {
+    matches!(
+        tokio::time::timeout(
+            PROBE_TIMEOUT,
+            tokio::net::TcpStream::connect(("127.0.0.1", port))
+        )
+        .await,
+        Ok(Ok(_))
+    )
+}
+
+/// Servers found in a session's terminal output, checking which still
+/// accept connections
+#[tauri::command]
+pub async fn list_detected_servers(session_id: String) -> Vec<DetectedServer> {
+    let servers = DETECTED
+        .lock()
+        .get(&session_id)
+        .cloned()
+        .unwrap_or_default();
+    let mut listed = Vec::with_capacity(servers.len());
+    for mut server in servers {
+        server.alive = is_listening(server.port).await;
+        listed.push(server);
+    }
+    listed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_urls() {
+        let vite = "  \x1b[32m➜\x1b[39m  \x1b[1mLocal\x1b[22m:   \x1b[36mhttp://localhost:\x1b[1m5173\x1b[22m/\x1b[39m\n";
+        assert_eq!(
+            detect_urls(vite),
+            vec![("http://localhost:5173/".to_string(), 5173)]
+        );
+        assert_eq!(
+            detect_urls("Uvicorn running on http://127.0.0.1:8000 (Press CTRL+C to quit)"),
+            vec![("http://localhost:8000/".to_string(), 8000)]
+        );
+        assert_eq!(
+            detect_urls("Server listening on port 3000."),
+            vec![("http://localhost:3000/".to_string(), 3000)]
+        );
+        assert_eq!(
+            detect_urls("bound to 0.0.0.0:4000, docs at http://localhost:4000/docs."),
+            vec![("http://localhost:4000/".to_string(), 4000)]
+        );
+        assert!(detect_urls("connect to localhost first").is_empty());
+        assert!(detect_urls("see https://example.com:8080/").is_empty());
+    }
+
+    #[test]
+    fn test_record_reports_new_ports_once() {
+        let events = record("s-dev", "term_1", Some("w1"), "ready on http://localhost:3000");
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].port, 3000);
+        assert!(record("s-dev", "term_2", None, "http://localhost:3000/api").is_empty());
+        clear_session("s-dev");
+        assert_eq!(record("s-dev", "term_2", None, "localhost:3000").len(), 1);
+        clear_session("s-dev");
+    }
+}
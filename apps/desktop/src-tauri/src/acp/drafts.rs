@@ -0,0 +1,162 @@
+//! Prompt drafts
+//!
+//! Half-written prompts (text plus pasted images) are kept per session under
+//! `~/.crafter-code/sessions/drafts/` so they survive app restarts and window
+//! reloads. Saving an empty draft removes it. Drafts are encrypted when
+//! `encrypt_sessions` is on.
+
+use crate::acp::commands::ImageAttachment;
+use crate::secrets::at_rest::{self, Encryption};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+/// Largest draft text, in characters
+const MAX_DRAFT_TEXT_CHARS: usize = 100_000;
+
+/// Largest draft on disk, attachments included
+const MAX_DRAFT_BYTES: usize = 5 * 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PromptDraft {
+    pub text: String,
+    #[serde(default)]
+    pub attachments: Vec<ImageAttachment>,
+    pub updated_at: i64,
+}
+
+impl PromptDraft {
+    fn is_empty(&self) -> bool {
+        self.text.trim().is_empty() && self.attachments.is_empty()
+    }
+}
+
+pub struct DraftStore {
+    base_path: PathBuf,
+    encryption: Encryption,
+}
+
+impl DraftStore {
+    pub fn new() -> Result<Self, String> {
+        let base_path = dirs::home_dir()
+            .ok_or_else(|| "Could not determine home directory".to_string())?
+            .join(".crafter-code")
+            .join("sessions")
+            .join("drafts");
+
+        fs::create_dir_all(&base_path)
+            .map_err(|e| format!("Failed to create drafts directory: {}", e))?;
+
+        Ok(Self::with_path(base_path, Encryption::Configured))
+    }
+
+    pub fn with_path(base_path: PathBuf, encryption: Encryption) -> Self {
+        Self {
+            base_path,
+            encryption,
+        }
+    }
+
+    fn draft_path(&self, session_id: &str) -> PathBuf {
+        self.base_path.join(format!("{}.json", session_id))
+    }
+
+    /// Save a draft, or remove it when it's empty
+    pub fn save(&self, session_id: &str, draft: &PromptDraft) -> Result<(), String> {
+        if draft.is_empty() {
+            return self.delete(session_id);
+        }
+        if draft.text.chars().count() > MAX_DRAFT_TEXT_CHARS {
+            return Err(format!(
+                "Draft is too long (max {} characters)",
+                MAX_DRAFT_TEXT_CHARS
+            ));
+        }
+        let json = serde_json::to_string(draft)
+            .map_err(|e| format!("Failed to serialize draft: {}", e))?;
+        if json.len() > MAX_DRAFT_BYTES {
+            return Err(format!(
+                "Draft is too large (max {} MB with attachments)",
+                MAX_DRAFT_BYTES / (1024 * 1024)
+            ));
+        }
+        at_rest::write(&self.draft_path(session_id), json.as_bytes(), self.encryption)
+            .map_err(|e| format!("Failed to write draft file: {}", e))
+    }
+
+    pub fn load(&self, session_id: &str) -> Option<PromptDraft> {
+        let json = at_rest::read_to_string(&self.draft_path(session_id), self.encryption).ok()?;
+        serde_json::from_str(&json).ok()
+    }
+
+    pub fn delete(&self, session_id: &str) -> Result<(), String> {
+        let path = self.draft_path(session_id);
+        if path.exists() {
+            fs::remove_file(&path).map_err(|e| format!("Failed to delete draft file: {}", e))?;
+        }
+        Ok(())
+    }
+}
+
+/// Save the unsent prompt for a session. An empty draft clears it.
+#[tauri::command]
+pub fn save_prompt_draft(
+    session_id: String,
+    text: String,
+    attachments: Option<Vec<ImageAttachment>>,
+) -> Result<(), String> {
+    let draft = PromptDraft {
+        text,
+        attachments: attachments.unwrap_or_default(),
+        updated_at: chrono::Utc::now().timestamp(),
+    };
+    DraftStore::new()?.save(&session_id, &draft)
+}
+
+/// Get the unsent prompt saved for a session, if any
+#[tauri::command]
+pub fn get_prompt_draft(session_id: String) -> Result<Option<PromptDraft>, String> {
+    Ok(DraftStore::new()?.load(&session_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn draft(text: &str) -> PromptDraft {
+        PromptDraft {
+            text: text.to_string(),
+            attachments: Vec::new(),
+            updated_at: 1,
+        }
+    }
+
+    #[test]
+    fn test_save_load_and_clear() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = DraftStore::with_path(dir.path().to_path_buf(), Encryption::Off);
+
+        store.save("s1", &draft("half a thought")).unwrap();
+        assert_eq!(store.load("s1"), Some(draft("half a thought")));
+
+        store.save("s1", &draft("  ")).unwrap();
+        assert_eq!(store.load("s1"), None);
+    }
+
+    #[test]
+    fn test_size_cap() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = DraftStore::with_path(dir.path().to_path_buf(), Encryption::Off);
+
+        let long = "x".repeat(MAX_DRAFT_TEXT_CHARS + 1);
+        assert!(store.save("s1", &draft(&long)).is_err());
+
+        let mut big = draft("see image");
+        big.attachments.push(ImageAttachment {
+            data: "A".repeat(MAX_DRAFT_BYTES),
+            mime_type: "image/png".to_string(),
+        });
+        assert!(store.save("s1", &big).is_err());
+        assert_eq!(store.load("s1"), None);
+    }
+}
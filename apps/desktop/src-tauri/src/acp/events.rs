@@ -0,0 +1,194 @@
+//! Event sinks for ACP workers
+//!
+//! Workers normally emit events to the Tauri frontend. The headless CLI has
+//! no webview, so the same events are written to stdout as JSON lines
+//! (`{"event": "...", "payload": {...}}`) instead.
+//!
+//! While the API server runs, events emitted to the app are also published
+//! on an in-process bus with sequence numbers so WebSocket clients can
+//! stream them and catch up after reconnecting.
+
+use crate::events::{AppEvent, Versioned};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::io::Write;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::broadcast;
+
+/// Buffered events per bus subscriber before it starts lagging
+const BUS_CAPACITY: usize = 1024;
+
+/// Recent events kept for clients that reconnect
+const REPLAY_CAPACITY: usize = 2048;
+
+/// An event as seen by bus subscribers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BusEvent {
+    /// Increases by one per event; 0 for control events
+    pub seq: u64,
+    pub event: String,
+    pub payload: serde_json::Value,
+}
+
+struct EventBus {
+    enabled: bool,
+    next_seq: u64,
+    replay: VecDeque<BusEvent>,
+    sender: broadcast::Sender<BusEvent>,
+}
+
+static EVENT_BUS: Lazy<Mutex<EventBus>> = Lazy::new(|| {
+    Mutex::new(EventBus {
+        enabled: false,
+        next_seq: 1,
+        replay: VecDeque::new(),
+        sender: broadcast::channel(BUS_CAPACITY).0,
+    })
+});
+
+/// Start or stop publishing app events (enabled while the API server runs)
+pub fn set_bus_enabled(enabled: bool) {
+    let mut bus = EVENT_BUS.lock();
+    bus.enabled = enabled;
+    if !enabled {
+        bus.replay.clear();
+    }
+}
+
+fn publish(event: &str, payload: serde_json::Value) {
+    let mut bus = EVENT_BUS.lock();
+    if !bus.enabled {
+        return;
+    }
+    let event = BusEvent {
+        seq: bus.next_seq,
+        event: event.to_string(),
+        payload,
+    };
+    bus.next_seq += 1;
+    if bus.replay.len() >= REPLAY_CAPACITY {
+        bus.replay.pop_front();
+    }
+    bus.replay.push_back(event.clone());
+    let _ = bus.sender.send(event);
+}
+
+/// Events after `since`, or None if some of them are no longer buffered
+fn replay_since(replay: &VecDeque<BusEvent>, next_seq: u64, since: u64) -> Option<Vec<BusEvent>> {
+    // A sequence number from the future means the host restarted
+    if since >= next_seq {
+        return None;
+    }
+    let oldest = replay.front().map_or(next_seq, |e| e.seq);
+    if since + 1 < oldest {
+        return None;
+    }
+    Some(replay.iter().filter(|e| e.seq > since).cloned().collect())
+}
+
+/// Subscribe to live events, plus the buffered events after `since`.
+/// The replay is None when the client missed too much and must resync.
+pub fn subscribe_since(
+    since: Option<u64>,
+) -> (broadcast::Receiver<BusEvent>, Option<Vec<BusEvent>>) {
+    let bus = EVENT_BUS.lock();
+    let replay = match since {
+        Some(since) => replay_since(&bus.replay, bus.next_seq, since),
+        None => Some(Vec::new()),
+    };
+    (bus.sender.subscribe(), replay)
+}
+
+/// Where worker events go
+#[derive(Clone)]
+pub enum EventSink {
+    /// Emit Tauri events to the frontend
+    App(AppHandle),
+    /// Print JSON lines to stdout (headless mode)
+    Stdout {
+        /// Answer permission requests with "allow" instead of "reject"
+        auto_approve: bool,
+    },
+}
+
+impl EventSink {
+    pub fn emit<S: Serialize + Clone>(&self, event: &str, payload: S) {
+        match self {
+            Self::App(app_handle) => {
+                if EVENT_BUS.lock().enabled {
+                    if let Ok(value) = serde_json::to_value(&payload) {
+                        publish(event, value);
+                    }
+                }
+                let _ = app_handle.emit(event, payload);
+            }
+            Self::Stdout { .. } => {
+                let line = serde_json::json!({ "event": event, "payload": payload });
+                let mut stdout = std::io::stdout().lock();
+                let _ = writeln!(stdout, "{}", line);
+                let _ = stdout.flush();
+            }
+        }
+    }
+
+    /// Emit a catalog event under its own name, with the schema version
+    pub fn send<E: AppEvent>(&self, event: &E) {
+        self.emit(&event.name(), Versioned::new(event));
+    }
+
+    /// The Tauri app handle, when running inside the desktop app
+    pub fn app_handle(&self) -> Option<&AppHandle> {
+        match self {
+            Self::App(app_handle) => Some(app_handle),
+            Self::Stdout { .. } => None,
+        }
+    }
+
+    /// Permission decision for headless runs (None = ask the frontend)
+    pub fn headless_permission(&self) -> Option<bool> {
+        match self {
+            Self::App(_) => None,
+            Self::Stdout { auto_approve } => Some(*auto_approve),
+        }
+    }
+}
+
+impl From<AppHandle> for EventSink {
+    fn from(app_handle: AppHandle) -> Self {
+        Self::App(app_handle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn buffered(seqs: std::ops::RangeInclusive<u64>) -> VecDeque<BusEvent> {
+        seqs.map(|seq| BusEvent {
+            seq,
+            event: "worker-stream-w1".to_string(),
+            payload: serde_json::Value::Null,
+        })
+        .collect()
+    }
+
+    #[test]
+    fn test_replay_since() {
+        let replay = buffered(5..=9);
+
+        let seqs = |events: Vec<BusEvent>| events.iter().map(|e| e.seq).collect::<Vec<_>>();
+        assert_eq!(seqs(replay_since(&replay, 10, 6).unwrap()), vec![7, 8, 9]);
+        assert_eq!(
+            seqs(replay_since(&replay, 10, 4).unwrap()),
+            vec![5, 6, 7, 8, 9]
+        );
+        assert!(replay_since(&replay, 10, 9).unwrap().is_empty());
+
+        // Evicted events and restarted hosts require a resync
+        assert!(replay_since(&replay, 10, 2).is_none());
+        assert!(replay_since(&replay, 10, 42).is_none());
+        assert!(replay_since(&VecDeque::new(), 1, 0).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,197 @@
+//! Session forking
+//!
+//! `fork_session` branches a persisted session at a message: the fork gets a
+//! copy of the history before that message and a fresh worker whose first
+//! prompt replays it, so the original thread is left untouched. Parent and
+//! child are linked through `parent_session_id` in the session store.
+
+use crate::acp::commands::{create_acp_session, AcpSessionResponse};
+use crate::acp::session_store::{
+    PersistedMessage, PersistedSession, SessionMetadata, SessionStore,
+};
+use crate::error::CommandError;
+use crate::events::{emit, SessionForkedEvent};
+use crate::time::now_ms;
+use crate::AppState;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use std::collections::HashSet;
+use tauri::{AppHandle, State};
+
+/// Most transcript characters replayed into a fork (~50k tokens); older
+/// messages beyond this are dropped from the replay
+const MAX_REPLAY_CHARS: usize = 200_000;
+
+/// Forks whose worker hasn't reported its ACP session id yet
+static PENDING_FORKS: Lazy<Mutex<HashSet<String>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+/// Prompt that primes a fresh ACP session with earlier history: the
+/// summary (if the session was compacted) and as much of the recent
+/// transcript as fits
+pub fn replay_prompt(messages: &[PersistedMessage], summary: Option<&str>) -> String {
+    let mut turns: Vec<String> = Vec::new();
+    let mut chars = 0;
+    for message in messages.iter().rev().filter(|m| !m.is_thinking()) {
+        let speaker = if message.role == "user" {
+            "User"
+        } else {
+            "Assistant"
+        };
+        let turn = format!("{}: {}", speaker, message.content);
+        chars += turn.len();
+        if chars > MAX_REPLAY_CHARS && !turns.is_empty() {
+            break;
+        }
+        turns.push(turn);
+    }
+    turns.reverse();
+
+    let mut prompt = String::from(
+        "This session continues an earlier conversation. Treat the history below as what \
+         has happened so far and reply with a short acknowledgement.\n\n",
+    );
+    if let Some(summary) = summary {
+        prompt.push_str("Summary of earlier context:\n");
+        prompt.push_str(summary);
+        prompt.push_str("\n\n");
+    }
+    prompt.push_str("Conversation:\n\n");
+    prompt.push_str(&turns.join("\n\n"));
+    prompt
+}
+
+/// Record the fork's ACP session id once its worker has replayed the
+/// history. Returns false (and does nothing) for sessions that aren't
+/// pending forks.
+pub fn finish_fork(session_id: &str, acp_session_id: Option<String>) -> bool {
+    if !PENDING_FORKS.lock().remove(session_id) {
+        return false;
+    }
+    let result = SessionStore::new().and_then(|store| {
+        let mut session = store.load_session(session_id)?;
+        session.acp_session_id = acp_session_id.unwrap_or_default();
+        session.updated_at = now_ms();
+        store.save_session(&session)
+    });
+    if let Err(e) = result {
+        eprintln!("[ACP] Failed to record fork {}: {}", session_id, e);
+    }
+    true
+}
+
+/// Branch a persisted session, keeping the messages before
+/// `at_message_index`, in a new session with its own worker
+#[tauri::command]
+pub async fn fork_session(
+    session_id: String,
+    at_message_index: usize,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<AcpSessionResponse, CommandError> {
+    eprintln!(
+        "[ACP] fork_session called: session={}, at={}",
+        session_id, at_message_index
+    );
+
+    let store = SessionStore::new()?;
+    let parent = store.load_session(&session_id)?;
+    if at_message_index == 0 || at_message_index > parent.messages.len() {
+        return Err(CommandError::invalid_input(format!(
+            "Message index {} is out of range (1..={})",
+            at_message_index,
+            parent.messages.len()
+        )));
+    }
+    let messages = parent.messages[..at_message_index].to_vec();
+    let prompt = replay_prompt(&messages, parent.summary.as_deref());
+
+    let response = create_acp_session(
+        prompt,
+        parent.agent_id.clone(),
+        None,
+        parent.cwd.clone(),
+        parent.metadata.language.clone(),
+        app_handle.clone(),
+        state,
+    )
+    .await?;
+    let fork_id = response.session.id.clone();
+
+    let now = now_ms();
+    store.save_session(&PersistedSession {
+        id: fork_id.clone(),
+        acp_session_id: String::new(),
+        cwd: parent.cwd,
+        agent_id: parent.agent_id,
+        created_at: now,
+        updated_at: now,
+        messages,
+        mode: parent.mode,
+        initial_prompt: parent.initial_prompt,
+        tool_calls: Vec::new(),
+        build_results: Vec::new(),
+        hook_runs: Vec::new(),
+        agent_log: Vec::new(),
+        criteria: None,
+        summary: parent.summary,
+        parent_session_id: Some(session_id.clone()),
+        forked_at_message: Some(at_message_index),
+        metadata: SessionMetadata {
+            title: parent
+                .metadata
+                .title
+                .map(|title| format!("{} (fork)", title)),
+            tags: parent.metadata.tags,
+            language: parent.metadata.language,
+            timezone: parent.metadata.timezone,
+            ..Default::default()
+        },
+    })?;
+    PENDING_FORKS.lock().insert(fork_id.clone());
+
+    emit(
+        &app_handle,
+        &SessionForkedEvent {
+            session_id: fork_id,
+            parent_session_id: session_id,
+            at_message_index,
+        },
+    );
+
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(role: &str, content: &str) -> PersistedMessage {
+        PersistedMessage {
+            role: role.to_string(),
+            content: content.to_string(),
+            timestamp: 0,
+        }
+    }
+
+    #[test]
+    fn test_replay_prompt() {
+        let messages = vec![
+            message("user", "Add a login form"),
+            message("thinking", "hmm"),
+            message("assistant", "Done, see login.tsx"),
+        ];
+        let prompt = replay_prompt(&messages, Some("Building an auth flow"));
+        assert!(prompt.contains("Summary of earlier context:\nBuilding an auth flow"));
+        assert!(prompt.ends_with("User: Add a login form\n\nAssistant: Done, see login.tsx"));
+        assert!(!prompt.contains("hmm"));
+    }
+
+    #[test]
+    fn test_replay_prompt_keeps_recent_turns() {
+        let long = "x".repeat(MAX_REPLAY_CHARS);
+        let messages = vec![message("user", &long), message("user", "latest")];
+        let prompt = replay_prompt(&messages, None);
+        assert!(prompt.ends_with("User: latest"));
+        assert!(!prompt.contains(&long));
+    }
+}
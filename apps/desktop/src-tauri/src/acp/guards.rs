@@ -0,0 +1,323 @@
+//! Guard rules
+//!
+//! User-configured checks on what agents do: regex patterns or small
+//! scripts run against completed responses, tool calls and shell commands.
+//! A triggered rule emits `guard-triggered`; `block` rules also refuse the
+//! tool call or command, and `pause` rules hold it until the user answers
+//! through `respond_to_guard_review`. Tool calls the agent runs on its own
+//! (without asking permission) can only be flagged after the fact.
+//!
+//! Rules come from settings (`guard_rules`) plus the project's
+//! `.crafter-code/guards.json`.
+
+use crate::acp::events::EventSink;
+use crate::events::GuardTriggeredEvent;
+use crate::settings::load_settings;
+use crate::settings::store::{GuardAction, GuardRule, GuardTarget};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use regex::Regex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::oneshot;
+
+/// JSON-RPC error code for actions refused by a guard rule
+pub const GUARD_ERROR_CODE: i32 = -32002;
+
+/// Longest a guard script may run before it's treated as not triggered
+const SCRIPT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Characters of the matched text included in events
+const MAX_EXCERPT_CHARS: usize = 200;
+
+/// Pending guard reviews, keyed by worker id
+static GUARD_REVIEWS: Lazy<Mutex<HashMap<String, oneshot::Sender<bool>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// A rule that fired
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct GuardHit {
+    pub rule: String,
+    pub target: GuardTarget,
+    pub action: GuardAction,
+    /// The matched text (or the start of the input for scripts)
+    pub matched: String,
+    /// First line of a script's output
+    pub message: Option<String>,
+}
+
+/// Enabled rules for a project
+pub fn load_rules(project: Option<&str>) -> Vec<GuardRule> {
+    let mut rules = load_settings().guard_rules;
+    if let Some(dir) = project {
+        let path = Path::new(dir).join(".crafter-code").join("guards.json");
+        if let Ok(json) = std::fs::read_to_string(&path) {
+            match serde_json::from_str::<Vec<GuardRule>>(&json) {
+                Ok(project_rules) => rules.extend(project_rules),
+                Err(e) => eprintln!("[Guards] Ignoring {:?}: {}", path, e),
+            }
+        }
+    }
+    rules.retain(|rule| rule.enabled);
+    rules
+}
+
+fn excerpt(text: &str) -> String {
+    text.chars().take(MAX_EXCERPT_CHARS).collect()
+}
+
+/// Regex rules for `target` that match `text`
+pub fn match_patterns(rules: &[GuardRule], target: GuardTarget, text: &str) -> Vec<GuardHit> {
+    rules
+        .iter()
+        .filter(|rule| rule.target == target)
+        .filter_map(|rule| {
+            let pattern = rule.pattern.as_deref()?;
+            let re = match Regex::new(pattern) {
+                Ok(re) => re,
+                Err(e) => {
+                    eprintln!("[Guards] Invalid pattern in {}: {}", rule.name, e);
+                    return None;
+                }
+            };
+            re.find(text).map(|m| GuardHit {
+                rule: rule.name.clone(),
+                target,
+                action: rule.action,
+                matched: excerpt(m.as_str()),
+                message: None,
+            })
+        })
+        .collect()
+}
+
+/// Run a guard script with `text` on stdin; Some(message) if it triggered
+async fn run_script(
+    script: &str,
+    text: &str,
+    target: GuardTarget,
+    cwd: Option<&str>,
+) -> Option<String> {
+    let mut cmd = tokio::process::Command::new("/bin/sh");
+    cmd.args(["-c", script])
+        .env(
+            "CRAFTER_GUARD_TARGET",
+            format!("{:?}", target).to_lowercase(),
+        )
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true);
+    if let Some(cwd) = cwd {
+        cmd.current_dir(cwd);
+    }
+    let mut child = match cmd.spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            eprintln!("[Guards] Failed to run script {:?}: {}", script, e);
+            return None;
+        }
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = stdin.write_all(text.as_bytes()).await;
+    }
+    match tokio::time::timeout(SCRIPT_TIMEOUT, child.wait_with_output()).await {
+        Ok(Ok(output)) if !output.status.success() => {
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            Some(stdout.lines().next().unwrap_or_default().trim().to_string())
+        }
+        Ok(_) => None,
+        Err(_) => {
+            eprintln!("[Guards] Script timed out: {:?}", script);
+            None
+        }
+    }
+}
+
+/// Every rule for `target` that fires on `text`
+pub async fn check(
+    rules: &[GuardRule],
+    target: GuardTarget,
+    text: &str,
+    cwd: Option<&str>,
+) -> Vec<GuardHit> {
+    let mut hits = match_patterns(rules, target, text);
+    for rule in rules.iter().filter(|rule| rule.target == target) {
+        let Some(script) = rule.script.as_deref() else {
+            continue;
+        };
+        if let Some(message) = run_script(script, text, target, cwd).await {
+            hits.push(GuardHit {
+                rule: rule.name.clone(),
+                target,
+                action: rule.action,
+                matched: excerpt(text),
+                message: Some(message).filter(|m| !m.is_empty()),
+            });
+        }
+    }
+    hits
+}
+
+/// Text tool-call rules are checked against
+pub fn tool_call_text(kind: &str, title: &str, raw_input: Option<&serde_json::Value>) -> String {
+    match raw_input {
+        Some(input) => format!("{} {} {}", kind, title, input),
+        None => format!("{} {}", kind, title),
+    }
+}
+
+/// Error returned to the agent for a refused action
+pub fn guard_error(hits: &[GuardHit]) -> agent_client_protocol::Error {
+    let rules: Vec<&str> = hits.iter().map(|hit| hit.rule.as_str()).collect();
+    agent_client_protocol::Error::new(
+        GUARD_ERROR_CODE,
+        format!("Blocked by guard rule: {}", rules.join(", ")),
+    )
+    .with_data(serde_json::json!({
+        "reason": "guard_rule",
+        "rules": rules
+    }))
+}
+
+/// The strongest action among the hits
+pub fn strongest(hits: &[GuardHit]) -> Option<GuardAction> {
+    hits.iter()
+        .map(|hit| hit.action)
+        .max_by_key(|action| match action {
+            GuardAction::Flag => 0,
+            GuardAction::Pause => 1,
+            GuardAction::Block => 2,
+        })
+}
+
+pub fn emit_hits(
+    events: &EventSink,
+    session_id: &str,
+    worker_id: &str,
+    hits: &[GuardHit],
+    awaiting_review: bool,
+) {
+    for hit in hits {
+        eprintln!(
+            "[Guards] Rule '{}' triggered ({:?}) for worker {}",
+            hit.rule, hit.action, worker_id
+        );
+        events.send(&GuardTriggeredEvent {
+            session_id: session_id.to_string(),
+            worker_id: worker_id.to_string(),
+            rule: hit.rule.clone(),
+            target: hit.target,
+            action: hit.action,
+            matched: hit.matched.clone(),
+            message: hit.message.clone(),
+            awaiting_review,
+            timestamp: chrono::Utc::now().timestamp_millis(),
+        });
+    }
+}
+
+/// Wait for the user to approve or reject a paused action. Headless runs
+/// answer from their permission flag.
+pub async fn await_review(events: &EventSink, worker_id: &str) -> bool {
+    if let Some(approve) = events.headless_permission() {
+        return approve;
+    }
+    let (tx, rx) = oneshot::channel();
+    GUARD_REVIEWS.lock().insert(worker_id.to_string(), tx);
+    rx.await.unwrap_or(false)
+}
+
+/// Approve or reject the action a guard rule paused
+#[tauri::command]
+pub fn respond_to_guard_review(worker_id: String, approve: bool) -> Result<(), String> {
+    let sender = GUARD_REVIEWS
+        .lock()
+        .remove(&worker_id)
+        .ok_or_else(|| format!("No paused action for worker {}", worker_id))?;
+    sender
+        .send(approve)
+        .map_err(|_| "Review is no longer pending".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(name: &str, target: GuardTarget, pattern: &str, action: GuardAction) -> GuardRule {
+        GuardRule {
+            name: name.to_string(),
+            target,
+            pattern: Some(pattern.to_string()),
+            script: None,
+            action,
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn test_match_patterns() {
+        let rules = vec![
+            rule(
+                "no-push",
+                GuardTarget::Command,
+                r"\bgit\s+push\b",
+                GuardAction::Block,
+            ),
+            rule(
+                "todo",
+                GuardTarget::Output,
+                r"TODO|FIXME",
+                GuardAction::Flag,
+            ),
+        ];
+        let hits = match_patterns(&rules, GuardTarget::Command, "git add . && git push origin");
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].rule, "no-push");
+        assert_eq!(hits[0].matched, "git push");
+        assert!(match_patterns(&rules, GuardTarget::Command, "git status").is_empty());
+        assert!(match_patterns(&rules, GuardTarget::Output, "git push").is_empty());
+    }
+
+    #[test]
+    fn test_strongest() {
+        let hit = |action| GuardHit {
+            rule: String::new(),
+            target: GuardTarget::Command,
+            action,
+            matched: String::new(),
+            message: None,
+        };
+        assert_eq!(strongest(&[]), None);
+        assert_eq!(
+            strongest(&[hit(GuardAction::Flag), hit(GuardAction::Pause)]),
+            Some(GuardAction::Pause)
+        );
+        assert_eq!(
+            strongest(&[hit(GuardAction::Block), hit(GuardAction::Pause)]),
+            Some(GuardAction::Block)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_script_rule() {
+        let rules = vec![GuardRule {
+            name: "no-secrets".to_string(),
+            target: GuardTarget::Output,
+            pattern: None,
+            script: Some("if grep -q password; then echo found it; exit 1; fi".to_string()),
+            action: GuardAction::Flag,
+            enabled: true,
+        }];
+        let hits = check(&rules, GuardTarget::Output, "the password is x", None).await;
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].message.as_deref(), Some("found it"));
+        assert!(check(&rules, GuardTarget::Output, "all clear", None)
+            .await
+            .is_empty());
+    }
+}
@@ -0,0 +1,235 @@
+//! Idle worker hibernation
+//!
+//! A persistent worker that receives no commands for
+//! `worker_idle_timeout_minutes` persists its session, kills the agent
+//! subprocess and is recorded here as hibernated. The next command sent to
+//! the session wakes it through `load_session` (see `worker_command_tx`).
+
+use crate::acp::client::AcpClient;
+use crate::acp::commands::{save_session_to_persistence, WorkerCommand, WorkerHandle};
+use crate::acp::inbox_push::InboxPush;
+use crate::acp::session_store::SessionStore;
+use crate::acp::worker_health;
+use crate::events::{emit, InboxPushEvent, WorkerStatusChange};
+use crate::orchestrator::worker::WorkerStatus;
+use crate::orchestrator::OrchestratorManager;
+use crate::settings::load_settings;
+use crate::AppState;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tokio::sync::{mpsc, oneshot};
+use tokio::time::Instant;
+
+/// What is needed to bring a hibernated worker back
+#[derive(Debug, Clone)]
+pub struct HibernatedWorker {
+    pub worker_id: String,
+    pub agent_id: String,
+    pub model: String,
+    pub cwd: String,
+    pub acp_session_id: String,
+}
+
+/// Hibernated workers by session_id
+static HIBERNATED: Lazy<Mutex<HashMap<String, HibernatedWorker>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Remove a session's hibernation record so it can be woken
+pub fn take_hibernated(session_id: &str) -> Option<HibernatedWorker> {
+    HIBERNATED.lock().remove(session_id)
+}
+
+/// Whether the session's worker is hibernated
+pub fn is_hibernated(session_id: &str) -> bool {
+    HIBERNATED.lock().contains_key(session_id)
+}
+
+/// Idle time before a worker hibernates; 0 minutes disables it
+fn idle_timeout(minutes: u32) -> Option<Duration> {
+    (minutes > 0).then(|| Duration::from_secs(u64::from(minutes) * 60))
+}
+
+/// The worker a command loop is serving, used when it goes idle
+pub struct IdleContext<'a> {
+    pub session_id: &'a str,
+    pub worker_id: &'a str,
+    pub agent_id: &'a str,
+    pub model: &'a str,
+    pub cwd: &'a str,
+    pub app_handle: &'a AppHandle,
+    pub manager: &'a Arc<Mutex<OrchestratorManager>>,
+}
+
+/// Wait for the next worker command. Returns `None` when the channel closes
+/// or the worker hibernated after sitting idle; either way the caller's
+/// command loop should exit and kill the agent. New inbox messages come
+/// back as a synthetic prompt when inbox push is on (see `inbox_push`).
+pub async fn next_command(
+    command_rx: &mut mpsc::Receiver<WorkerCommand>,
+    client: &AcpClient,
+    ctx: &IdleContext<'_>,
+    inbox_push: &mut InboxPush,
+) -> Option<WorkerCommand> {
+    worker_health::heartbeat(ctx.worker_id, false);
+    let cmd = wait_for_command(command_rx, client, ctx, inbox_push).await;
+    worker_health::heartbeat(ctx.worker_id, cmd.is_some());
+    cmd
+}
+
+async fn wait_for_command(
+    command_rx: &mut mpsc::Receiver<WorkerCommand>,
+    client: &AcpClient,
+    ctx: &IdleContext<'_>,
+    inbox_push: &mut InboxPush,
+) -> Option<WorkerCommand> {
+    let mut idle_since = Instant::now();
+    loop {
+        if let Some((message, count)) = inbox_push.take_prompt() {
+            eprintln!(
+                "[ACP] Pushing {} inbox message(s) to worker={}",
+                count, ctx.worker_id
+            );
+            emit(
+                ctx.app_handle,
+                &InboxPushEvent {
+                    session_id: ctx.session_id.to_string(),
+                    worker_id: ctx.worker_id.to_string(),
+                    count,
+                    prompt: message.clone(),
+                },
+            );
+            let (done_tx, _) = oneshot::channel();
+            return Some(WorkerCommand::Prompt { message, done_tx });
+        }
+
+        // Agents that can't load_session would lose their context
+        let timeout = idle_timeout(load_settings().worker_idle_timeout_minutes)
+            .filter(|_| client.supports_load_session());
+        let hibernate_at = async {
+            match timeout {
+                Some(timeout) => tokio::time::sleep_until(idle_since + timeout).await,
+                None => std::future::pending().await,
+            }
+        };
+
+        tokio::select! {
+            cmd = command_rx.recv() => return cmd,
+            _ = inbox_push.wait() => {}
+            _ = hibernate_at => {
+                if let Some(cmd) = hibernate(command_rx, client, ctx) {
+                    return Some(cmd);
+                }
+                if command_rx.is_closed() {
+                    return None;
+                }
+                idle_since = Instant::now();
+            }
+        }
+    }
+}
+
+/// Detach the worker from its session. Returns a command that raced the
+/// timeout, in which case the worker keeps running on a fresh channel.
+fn hibernate(
+    command_rx: &mut mpsc::Receiver<WorkerCommand>,
+    client: &AcpClient,
+    ctx: &IdleContext<'_>,
+) -> Option<WorkerCommand> {
+    let acp_session_id = client.acp_session_id()?;
+    let state = ctx.app_handle.state::<AppState>();
+
+    {
+        let mut handles = state.worker_handles.lock();
+        command_rx.close();
+        if let Ok(cmd) = command_rx.try_recv() {
+            let (command_tx, new_rx) = mpsc::channel::<WorkerCommand>(32);
+            while let Ok(queued) = command_rx.try_recv() {
+                let _ = command_tx.try_send(queued);
+            }
+            *command_rx = new_rx;
+            handles.insert(ctx.session_id.to_string(), WorkerHandle { command_tx });
+            return Some(cmd);
+        }
+        handles.remove(ctx.session_id);
+        HIBERNATED.lock().insert(
+            ctx.session_id.to_string(),
+            HibernatedWorker {
+                worker_id: ctx.worker_id.to_string(),
+                agent_id: ctx.agent_id.to_string(),
+                model: ctx.model.to_string(),
+                cwd: ctx.cwd.to_string(),
+                acp_session_id: acp_session_id.clone(),
+            },
+        );
+
+        // Still under the handles lock so a wake can't interleave
+        ctx.manager.lock().update_worker_status(
+            ctx.session_id,
+            ctx.worker_id,
+            WorkerStatus::Hibernated,
+        );
+        emit(
+            ctx.app_handle,
+            &WorkerStatusChange::new(ctx.session_id, ctx.worker_id, WorkerStatus::Hibernated),
+        );
+    }
+
+    eprintln!("[ACP] Worker idle, hibernated session={}", ctx.session_id);
+
+    save(ctx, acp_session_id);
+    None
+}
+
+/// Save the session before the worker stops, so it can be reconnected
+/// after the app restarts
+pub fn persist_on_stop(client: &AcpClient, ctx: &IdleContext<'_>) {
+    if let Some(acp_session_id) = client.acp_session_id() {
+        save(ctx, acp_session_id);
+    }
+}
+
+fn save(ctx: &IdleContext<'_>, acp_session_id: String) {
+    let initial_prompt = ctx
+        .manager
+        .lock()
+        .get_session(ctx.session_id)
+        .map(|s| s.prompt.clone())
+        .unwrap_or_default();
+    if let Err(e) = persist(ctx, acp_session_id, initial_prompt) {
+        eprintln!("[ACP] Failed to persist session {}: {}", ctx.session_id, e);
+    }
+}
+
+/// Save the session so it survives a restart, keeping any
+/// messages the frontend already persisted
+fn persist(ctx: &IdleContext<'_>, acp_session_id: String, initial_prompt: String) -> Result<(), String> {
+    let existing = SessionStore::new()?.load_session(ctx.session_id).ok();
+    let (messages, mode, initial_prompt) = match existing {
+        Some(s) => (s.messages, s.mode, s.initial_prompt),
+        None => (Vec::new(), "default".to_string(), initial_prompt),
+    };
+    save_session_to_persistence(
+        ctx.session_id.to_string(),
+        acp_session_id,
+        ctx.cwd.to_string(),
+        ctx.agent_id.to_string(),
+        initial_prompt,
+        messages,
+        mode,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_idle_timeout() {
+        assert_eq!(idle_timeout(0), None);
+        assert_eq!(idle_timeout(30), Some(Duration::from_secs(1800)));
+    }
+}
@@ -0,0 +1,290 @@
+//! Project lifecycle hooks
+//!
+//! Shell scripts in `{project}/.crafter/hooks/` run around agent work:
+//!
+//! - `pre-prompt` before each prompt is sent
+//! - `post-tool-<kind>` (e.g. `post-tool-edit`) and `post-tool` after a tool
+//!   call completes
+//! - `on-complete` after a prompt finishes successfully
+//!
+//! Scripts may carry a `.sh` suffix and are run with `/bin/sh` in the
+//! project directory, killed after `hook_timeout_secs`. Every run is recorded
+//! in the session log (persisted with the session) and emitted as
+//! `hook-output`. A failing hook is reported but doesn't stop the agent.
+//!
+//! Hooks configured the Claude Code way run too (see `claude_hooks`).
+
+use crate::acp::events::EventSink;
+use crate::acp::session_store::SessionStore;
+use crate::events::HookOutputEvent;
+use crate::settings::load_settings;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+use ts_rs::TS;
+
+pub const PRE_PROMPT: &str = "pre-prompt";
+pub const POST_TOOL: &str = "post-tool";
+pub const ON_COMPLETE: &str = "on-complete";
+
+/// Characters of hook output kept (the end, where errors usually are)
+const MAX_OUTPUT_CHARS: usize = 20_000;
+
+/// Global registry of hook runs (session_id -> runs in order)
+static HOOK_RUNS: Lazy<Mutex<HashMap<String, Vec<HookRun>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// One execution of a hook script
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
+#[ts(export)]
+pub struct HookRun {
+    /// Script name, e.g. "post-tool-edit"
+    pub hook: String,
+    pub worker_id: String,
+    /// None if the script was killed or couldn't start
+    pub exit_code: Option<i32>,
+    /// Combined stdout and stderr
+    pub output: String,
+    #[ts(type = "number")]
+    pub duration_ms: u64,
+    pub timed_out: bool,
+    #[ts(type = "number")]
+    pub timestamp: i64,
+}
+
+impl HookRun {
+    pub fn succeeded(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+}
+
+/// Scripts for `names` present in the project's hooks directory, in order
+pub fn find_hooks(project: &Path, names: &[&str]) -> Vec<(String, PathBuf)> {
+    let dir = project.join(".crafter").join("hooks");
+    names
+        .iter()
+        .filter_map(|name| {
+            [name.to_string(), format!("{}.sh", name)]
+                .into_iter()
+                .map(|file| dir.join(file))
+                .find(|path| path.is_file())
+                .map(|path| (name.to_string(), path))
+        })
+        .collect()
+}
+
+/// Hook names tried after a tool call of `kind` completes
+pub fn post_tool_hooks(kind: Option<&str>) -> Vec<String> {
+    let mut names: Vec<String> = kind
+        .filter(|k| !k.is_empty())
+        .map(|k| format!("{}-{}", POST_TOOL, k))
+        .into_iter()
+        .collect();
+    names.push(POST_TOOL.to_string());
+    names
+}
+
+/// The end of a hook's output, within `MAX_OUTPUT_CHARS`
+pub fn tail(text: &str) -> String {
+    let count = text.chars().count();
+    if count <= MAX_OUTPUT_CHARS {
+        return text.to_string();
+    }
+    let kept: String = text.chars().skip(count - MAX_OUTPUT_CHARS).collect();
+    format!(
+        "[… {} characters truncated]\n{}",
+        count - MAX_OUTPUT_CHARS,
+        kept
+    )
+}
+
+/// Run one script and capture the result
+async fn execute(
+    hook: &str,
+    script: &Path,
+    cwd: &Path,
+    env: &[(&str, &str)],
+    timeout: Duration,
+) -> (Option<i32>, String, bool) {
+    let mut cmd = tokio::process::Command::new("/bin/sh");
+    cmd.arg(script)
+        .current_dir(cwd)
+        .env("CRAFTER_HOOK", hook)
+        .envs(env.iter().copied())
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    let child = match cmd.spawn() {
+        Ok(child) => child,
+        Err(e) => return (None, format!("Failed to run {:?}: {}", script, e), false),
+    };
+    match tokio::time::timeout(timeout, child.wait_with_output()).await {
+        Ok(Ok(output)) => {
+            let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+            text.push_str(&String::from_utf8_lossy(&output.stderr));
+            (output.status.code(), tail(&text), false)
+        }
+        Ok(Err(e)) => (
+            None,
+            format!("Failed to wait for {:?}: {}", script, e),
+            false,
+        ),
+        Err(_) => (
+            None,
+            format!("Timed out after {}s", timeout.as_secs()),
+            true,
+        ),
+    }
+}
+
+/// Run the hooks among `names` that exist in `project`, recording each run
+/// in the session log. Returns the runs.
+pub async fn run_hooks(
+    events: &EventSink,
+    session_id: &str,
+    worker_id: &str,
+    project: &str,
+    names: &[&str],
+    env: &[(&str, &str)],
+) -> Vec<HookRun> {
+    let hooks = find_hooks(Path::new(project), names);
+    if hooks.is_empty() {
+        return Vec::new();
+    }
+    let timeout = Duration::from_secs(load_settings().hook_timeout_secs.max(1));
+    let mut env = env.to_vec();
+    env.push(("CRAFTER_SESSION_ID", session_id));
+
+    let mut runs = Vec::new();
+    for (hook, script) in hooks {
+        eprintln!("[Hooks] Running {} for worker {}", hook, worker_id);
+        let started = Instant::now();
+        let (exit_code, output, timed_out) =
+            execute(&hook, &script, Path::new(project), &env, timeout).await;
+        let run = HookRun {
+            hook,
+            worker_id: worker_id.to_string(),
+            exit_code,
+            output,
+            duration_ms: started.elapsed().as_millis() as u64,
+            timed_out,
+            timestamp: chrono::Utc::now().timestamp_millis(),
+        };
+        if !run.succeeded() {
+            eprintln!(
+                "[Hooks] {} failed (exit {:?}, timed out: {})",
+                run.hook, run.exit_code, run.timed_out
+            );
+        }
+        record_run(events, session_id, &run);
+        runs.push(run);
+    }
+    runs
+}
+
+/// Add a run to the session log and emit it as `hook-output`
+pub fn record_run(events: &EventSink, session_id: &str, run: &HookRun) {
+    HOOK_RUNS
+        .lock()
+        .entry(session_id.to_string())
+        .or_default()
+        .push(run.clone());
+    events.send(&HookOutputEvent {
+        session_id: session_id.to_string(),
+        run: run.clone(),
+    });
+}
+
+/// Get a session's hook runs, oldest first
+pub fn get_hook_runs(session_id: &str) -> Option<Vec<HookRun>> {
+    HOOK_RUNS.lock().get(session_id).cloned()
+}
+
+/// Drop a session's hook runs (on session cleanup)
+pub fn clear_hook_runs(session_id: &str) {
+    HOOK_RUNS.lock().remove(session_id);
+}
+
+/// Get the hook runs of a session (live, or the persisted copy)
+#[tauri::command]
+pub fn get_session_hook_runs(session_id: String) -> Result<Vec<HookRun>, String> {
+    if let Some(runs) = get_hook_runs(&session_id) {
+        return Ok(runs);
+    }
+
+    let store = SessionStore::new()?;
+    if !store.session_exists(&session_id) {
+        return Ok(Vec::new());
+    }
+    Ok(store.load_session(&session_id)?.hook_runs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_find_hooks() {
+        let dir = tempfile::tempdir().unwrap();
+        let hooks = dir.path().join(".crafter").join("hooks");
+        fs::create_dir_all(&hooks).unwrap();
+        fs::write(hooks.join("post-tool-edit.sh"), "cargo fmt\n").unwrap();
+        fs::write(hooks.join("post-tool"), "true\n").unwrap();
+
+        let names = post_tool_hooks(Some("edit"));
+        let names: Vec<&str> = names.iter().map(String::as_str).collect();
+        let found = find_hooks(dir.path(), &names);
+        assert_eq!(
+            found,
+            vec![
+                (
+                    "post-tool-edit".to_string(),
+                    hooks.join("post-tool-edit.sh")
+                ),
+                ("post-tool".to_string(), hooks.join("post-tool")),
+            ]
+        );
+        assert!(find_hooks(dir.path(), &[PRE_PROMPT]).is_empty());
+        assert_eq!(post_tool_hooks(None), vec!["post-tool"]);
+    }
+
+    #[tokio::test]
+    async fn test_execute_captures_output_and_times_out() {
+        let dir = tempfile::tempdir().unwrap();
+        let script = dir.path().join("hook.sh");
+        fs::write(
+            &script,
+            "echo \"$CRAFTER_HOOK $KIND\"\necho oops >&2\nexit 3\n",
+        )
+        .unwrap();
+        let (code, output, timed_out) = execute(
+            "post-tool",
+            &script,
+            dir.path(),
+            &[("KIND", "edit")],
+            Duration::from_secs(5),
+        )
+        .await;
+        assert_eq!(code, Some(3));
+        assert_eq!(output, "post-tool edit\noops\n");
+        assert!(!timed_out);
+
+        fs::write(&script, "sleep 5\n").unwrap();
+        let (code, _, timed_out) = execute(
+            "on-complete",
+            &script,
+            dir.path(),
+            &[],
+            Duration::from_millis(100),
+        )
+        .await;
+        assert_eq!(code, None);
+        assert!(timed_out);
+    }
+}
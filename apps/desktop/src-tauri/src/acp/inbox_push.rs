@@ -0,0 +1,231 @@
+//! Inbox push
+//!
+//! Workers only see inbox messages when they run `swarm inbox read`, which
+//! an idle agent never does. With `inbox_push.enabled`, a worker waiting in
+//! its command loop is sent a synthetic prompt listing its unread messages
+//! (which are then marked read) as soon as one arrives. Pushes are rate
+//! limited per worker (`min_interval_secs`, `max_per_hour`) so two agents
+//! answering each other can't loop. Hibernated workers aren't woken.
+
+use crate::inbox::message::{Message, MessageType};
+use crate::inbox::InboxManager;
+use crate::settings::load_settings;
+use crate::settings::store::InboxPushSettings;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::time::Instant;
+
+/// Characters of each message included in a push
+const MAX_MESSAGE_CHARS: usize = 2000;
+
+const HOUR: Duration = Duration::from_secs(3600);
+
+/// Recent pushes to one worker
+#[derive(Debug, Default)]
+struct RateLimit {
+    pushes: VecDeque<Instant>,
+}
+
+impl RateLimit {
+    /// When the next push is allowed (None if allowed at `now`)
+    fn blocked_until(&mut self, settings: &InboxPushSettings, now: Instant) -> Option<Instant> {
+        while self
+            .pushes
+            .front()
+            .is_some_and(|t| now.duration_since(*t) >= HOUR)
+        {
+            self.pushes.pop_front();
+        }
+        let mut until = None;
+        if let Some(last) = self.pushes.back() {
+            let next = *last + Duration::from_secs(settings.min_interval_secs);
+            if next > now {
+                until = Some(next);
+            }
+        }
+        if self.pushes.len() >= settings.max_per_hour as usize {
+            let next = self.pushes.front().map_or(now, |first| *first + HOUR);
+            until = Some(until.map_or(next, |u: Instant| u.max(next)));
+        }
+        until
+    }
+
+    fn record(&mut self, now: Instant) {
+        self.pushes.push_back(now);
+    }
+}
+
+fn truncate(text: &str) -> String {
+    match text.char_indices().nth(MAX_MESSAGE_CHARS) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+fn describe(message: &MessageType) -> String {
+    match message {
+        MessageType::Text { content } => truncate(content),
+        MessageType::ShutdownRequest { reason, .. } => format!("requests shutdown: {}", reason),
+        MessageType::ShutdownApproved { .. } => "approved your shutdown".to_string(),
+        MessageType::ShutdownRejected { reason, .. } => {
+            format!("rejected your shutdown: {}", reason)
+        }
+        MessageType::IdleNotification { completed_task_id } => match completed_task_id {
+            Some(id) => format!("is idle after completing task #{}", id),
+            None => "is idle".to_string(),
+        },
+        MessageType::TaskCompleted {
+            task_id,
+            task_subject,
+        } => format!("completed task #{} {}", task_id, task_subject),
+        MessageType::PlanApprovalRequest { plan_content, .. } => {
+            format!("asks you to approve a plan:\n{}", truncate(plan_content))
+        }
+        MessageType::PlanApproved { .. } => "approved your plan".to_string(),
+        MessageType::PlanRejected { feedback, .. } => {
+            format!("rejected your plan: {}", truncate(feedback))
+        }
+        MessageType::Custom { action, data } => {
+            format!("{}: {}", action, truncate(&data.to_string()))
+        }
+    }
+}
+
+/// The synthetic prompt delivering `messages`
+pub fn push_prompt(messages: &[Message]) -> String {
+    let mut out = format!(
+        "## New Messages\n\nYou have {} new message{} from your team:\n",
+        messages.len(),
+        if messages.len() == 1 { "" } else { "s" }
+    );
+    for message in messages {
+        out.push_str(&format!(
+            "- **{}**: {}\n",
+            message.from,
+            describe(&message.message)
+        ));
+    }
+    out.push_str(
+        "\nAct on them if they need it (reply with `swarm inbox write`), otherwise carry on or wait for more work.\n",
+    );
+    out
+}
+
+/// Pushes inbox messages to one idle worker
+pub struct InboxPush {
+    inbox: Arc<InboxManager>,
+    worker_id: String,
+    changes: watch::Receiver<u64>,
+    rate_limit: RateLimit,
+}
+
+impl InboxPush {
+    pub fn new(inbox: Arc<InboxManager>, worker_id: &str) -> Self {
+        let changes = inbox.subscribe();
+        Self {
+            inbox,
+            worker_id: worker_id.to_string(),
+            changes,
+            rate_limit: RateLimit::default(),
+        }
+    }
+
+    /// The prompt delivering the worker's unread messages, marking them
+    /// read. None if pushes are off, rate limited or there's nothing new.
+    pub fn take_prompt(&mut self) -> Option<(String, usize)> {
+        self.changes.borrow_and_update();
+        let settings = load_settings().inbox_push;
+        let now = Instant::now();
+        if !settings.enabled || self.rate_limit.blocked_until(&settings, now).is_some() {
+            return None;
+        }
+        let unread = self.inbox.read_unread(&self.worker_id);
+        if unread.is_empty() {
+            return None;
+        }
+        let ids: Vec<String> = unread.iter().map(|m| m.id.clone()).collect();
+        self.inbox.mark_read(&self.worker_id, &ids);
+        self.rate_limit.record(now);
+        Some((push_prompt(&unread), unread.len()))
+    }
+
+    /// Wait until a push may be due: a message arrived, or the rate limit
+    /// holding back unread messages expired. Never returns while pushes
+    /// are off.
+    pub async fn wait(&mut self) {
+        let settings = load_settings().inbox_push;
+        if !settings.enabled {
+            return std::future::pending().await;
+        }
+        let blocked = self.rate_limit.blocked_until(&settings, Instant::now());
+        match blocked {
+            Some(until) if self.inbox.count(&self.worker_id, true) > 0 => {
+                tokio::time::sleep_until(until).await
+            }
+            _ => {
+                if self.changes.changed().await.is_err() {
+                    std::future::pending::<()>().await;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_limit() {
+        let settings = InboxPushSettings {
+            enabled: true,
+            min_interval_secs: 30,
+            max_per_hour: 2,
+        };
+        let start = Instant::now();
+        let mut limit = RateLimit::default();
+        assert_eq!(limit.blocked_until(&settings, start), None);
+        limit.record(start);
+
+        let later = start + Duration::from_secs(10);
+        assert_eq!(
+            limit.blocked_until(&settings, later),
+            Some(start + Duration::from_secs(30))
+        );
+        let later = start + Duration::from_secs(40);
+        assert_eq!(limit.blocked_until(&settings, later), None);
+        limit.record(later);
+
+        // Hourly cap: blocked until the first push is an hour old
+        let later = start + Duration::from_secs(600);
+        assert_eq!(limit.blocked_until(&settings, later), Some(start + HOUR));
+        assert_eq!(limit.blocked_until(&settings, start + HOUR), None);
+    }
+
+    #[test]
+    fn test_push_prompt() {
+        let inbox = InboxManager::new("push-test".to_string());
+        inbox.register_worker("worker-1");
+        inbox.send(
+            "worker-2",
+            "worker-1",
+            MessageType::Text {
+                content: "Can you review #3?".to_string(),
+            },
+        );
+        inbox.send(
+            "worker-3",
+            "worker-1",
+            MessageType::TaskCompleted {
+                task_id: "2".to_string(),
+                task_subject: "Add tests".to_string(),
+            },
+        );
+        let prompt = push_prompt(&inbox.read_unread("worker-1"));
+        assert!(prompt.contains("You have 2 new messages"));
+        assert!(prompt.contains("- **worker-2**: Can you review #3?\n"));
+        assert!(prompt.contains("- **worker-3**: completed task #2 Add tests\n"));
+    }
+}
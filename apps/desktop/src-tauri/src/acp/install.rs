@@ -0,0 +1,125 @@
+//! Agent installation
+//!
+//! `install_agent` runs an agent's install command (npm, bun, go or brew) in
+//! the user's login shell, so the package managers on their PATH are found,
+//! as a background job. The installer's output becomes the job's log lines.
+//! Once it exits, the agent's command is looked up again and the job fails
+//! if it still can't be found.
+
+use crate::acp::registry::{check_command_exists, get_agent_config};
+use crate::error::CommandError;
+use crate::jobs::{self, Job};
+use crate::pty::profile::{default_shell, login_args};
+use std::io::{BufRead, BufReader, Read};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+use tauri::AppHandle;
+
+/// How often the installer is checked for exit and cancellation
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// How long output still arriving after the installer exited is waited for
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(1);
+
+fn install_command(agent_id: &str) -> Option<&'static str> {
+    match agent_id {
+        "claude" => Some("npm install -g @anthropic-ai/claude-code-acp"),
+        "gemini" => Some("bun install -g @google/gemini-cli"),
+        "codex" => Some("bun install -g @zed-industries/codex-acp"),
+        "opencode" => Some("go install github.com/anomaly/opencode@latest"),
+        "copilot" => Some("brew install --cask copilot-cli"),
+        _ => None,
+    }
+}
+
+/// Send each line `reader` produces to `lines` until it closes
+fn forward_lines(reader: impl Read + Send + 'static, lines: mpsc::Sender<String>) {
+    thread::spawn(move || {
+        for line in BufReader::new(reader).lines().map_while(Result::ok) {
+            if lines.send(line).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+/// Run `command` in a login shell, logging its output to the job. The
+/// installer is killed if the job is cancelled.
+fn run_installer(job: &Job, command: &str) -> Result<(), String> {
+    let shell = default_shell();
+    let mut child = Command::new(&shell)
+        .args(login_args(&shell))
+        .arg("-c")
+        .arg(command)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to start the installer: {}", e))?;
+
+    let (tx, rx) = mpsc::channel();
+    if let Some(stdout) = child.stdout.take() {
+        forward_lines(stdout, tx.clone());
+    }
+    if let Some(stderr) = child.stderr.take() {
+        forward_lines(stderr, tx);
+    }
+
+    loop {
+        match rx.recv_timeout(POLL_INTERVAL) {
+            Ok(line) => job.log(line),
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+            // Both streams closed; keep waiting for the exit
+            Err(mpsc::RecvTimeoutError::Disconnected) => thread::sleep(POLL_INTERVAL),
+        }
+        if let Err(e) = job.check_cancelled() {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(e);
+        }
+        if let Some(status) = child.try_wait().map_err(|e| e.to_string())? {
+            while let Ok(line) = rx.recv_timeout(DRAIN_TIMEOUT) {
+                job.log(line);
+            }
+            return if status.success() {
+                Ok(())
+            } else {
+                Err(format!("The installer exited with {}", status))
+            };
+        }
+    }
+}
+
+/// Install an agent in the background and return the job id. Progress and
+/// the installer's output are emitted as `job-progress` events.
+#[tauri::command]
+pub fn install_agent(agent_id: String, app_handle: AppHandle) -> Result<String, CommandError> {
+    let agent = get_agent_config(&agent_id)
+        .ok_or_else(|| CommandError::not_found(format!("Unknown agent: {}", agent_id)))?;
+    let command = install_command(&agent_id).ok_or_else(|| {
+        CommandError::invalid_input(format!("{} can't be installed from the app", agent.name))
+    })?;
+
+    let label = format!("Install {}", agent.name);
+    let binary = agent.command;
+    let work = move |job: &Job| install(job, command, &binary);
+    Ok(jobs::spawn(&app_handle, "install_agent", label, work))
+}
+
+fn install(job: &Job, command: &str, binary: &str) -> Result<(), String> {
+    job.phase("installing", None);
+    job.log(format!("$ {}", command));
+    run_installer(job, command)?;
+
+    job.phase("verifying", Some(90.0));
+    if !check_command_exists(binary) {
+        return Err(format!(
+            "The installer finished but `{}` still can't be found",
+            binary
+        ));
+    }
+    job.log(format!("Found {}", binary));
+    Ok(())
+}
@@ -0,0 +1,373 @@
+//! Agent response language
+//!
+//! Users pick the language agents answer in for every session
+//! (`language.respond_in` in settings) or for one session, which wins and
+//! is persisted with the session's metadata. The coordination prompt tells
+//! agents to use it. With `language.localize_prompts` on, the client's own
+//! text in the coordination prompt and `swarm help` is translated as well,
+//! for the languages in `LOCALES` (anything else stays English).
+
+use crate::acp::session_store::SessionStore;
+use crate::error::CommandError;
+use crate::settings::load_settings;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Languages chosen for live sessions (session_id -> language)
+static SESSION_LANGUAGES: Lazy<Mutex<HashMap<String, String>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Language codes and their English names
+const LANGUAGE_NAMES: &[(&str, &str)] = &[
+    ("en", "English"),
+    ("es", "Spanish"),
+    ("pt", "Portuguese"),
+    ("fr", "French"),
+    ("de", "German"),
+    ("it", "Italian"),
+    ("nl", "Dutch"),
+    ("pl", "Polish"),
+    ("ru", "Russian"),
+    ("uk", "Ukrainian"),
+    ("tr", "Turkish"),
+    ("ja", "Japanese"),
+    ("ko", "Korean"),
+    ("zh", "Chinese"),
+    ("hi", "Hindi"),
+    ("ar", "Arabic"),
+];
+
+/// Client text shown to agents, per locale
+pub struct PromptStrings {
+    pub leader_role: &'static str,
+    pub worker_role: &'static str,
+    pub no_tasks: &'static str,
+    /// Task prefixes for fleet workers; `{n}` is the worker's number
+    pub fleet_leader_task: &'static str,
+    pub fleet_worker_task: &'static str,
+    pub help_title: &'static str,
+    /// One per `swarm::HELP_COMMANDS` entry
+    pub help_commands: [&'static str; 18],
+}
+
+const EN: PromptStrings = PromptStrings {
+    leader_role: "You are the **leader** of this session. Coordinate work, create tasks for the team, and manage other workers.",
+    worker_role: "You are a **worker** in this session. Claim tasks, complete work, and communicate with your team.",
+    no_tasks: "No tasks created yet. Create some with `swarm task create`.",
+    fleet_leader_task: "You are the LEADER. Break down this task into subtasks using `swarm task create`, then coordinate the team.\n\nTask:",
+    fleet_worker_task: "You are WORKER #{n}. Wait for the leader to create tasks, then claim and complete them using swarm commands.\n\nContext:",
+    help_title: "Swarm commands:",
+    help_commands: [
+        "See all tasks",
+        "Get task details",
+        "Claim the next available task",
+        "Create a task",
+        "Set a task's status (pending, in_progress, completed, deleted)",
+        "Delete a task",
+        "Read your messages (--unread for new ones only)",
+        "Send a message to one worker",
+        "Send a message to every worker",
+        "List the workers in this session",
+        "Count unread messages",
+        "Mark all messages as read",
+        "List the workers with their role, model and specialties",
+        "Find the best worker for a skill",
+        "Declare your specialties",
+        "PRD leaders: session progress",
+        "Keep a dev server or other long-running command up for the session",
+        "Show this help",
+    ],
+};
+
+const ES: PromptStrings = PromptStrings {
+    leader_role: "Eres el **líder** de esta sesión. Coordina el trabajo, crea tareas para el equipo y gestiona a los demás workers.",
+    worker_role: "Eres un **worker** en esta sesión. Reclama tareas, completa el trabajo y comunícate con tu equipo.",
+    no_tasks: "Todavía no hay tareas. Crea algunas con `swarm task create`.",
+    fleet_leader_task: "Eres el LÍDER. Divide esta tarea en subtareas con `swarm task create` y luego coordina al equipo.\n\nTarea:",
+    fleet_worker_task: "Eres el WORKER #{n}. Espera a que el líder cree tareas y luego reclámalas y complétalas con los comandos de swarm.\n\nContexto:",
+    help_title: "Comandos de swarm:",
+    help_commands: [
+        "Ver todas las tareas",
+        "Ver los detalles de una tarea",
+        "Reclamar la siguiente tarea disponible",
+        "Crear una tarea",
+        "Cambiar el estado de una tarea (pending, in_progress, completed, deleted)",
+        "Eliminar una tarea",
+        "Leer tus mensajes (--unread solo para los nuevos)",
+        "Enviar un mensaje a un worker",
+        "Enviar un mensaje a todos los workers",
+        "Listar los workers de esta sesión",
+        "Contar los mensajes sin leer",
+        "Marcar todos los mensajes como leídos",
+        "Listar los workers con su rol, modelo y especialidades",
+        "Encontrar el mejor worker para una habilidad",
+        "Declarar tus especialidades",
+        "Líderes de PRD: progreso de la sesión",
+        "Mantener un servidor de desarrollo u otro comando largo activo durante la sesión",
+        "Mostrar esta ayuda",
+    ],
+};
+
+const PT: PromptStrings = PromptStrings {
+    leader_role: "Você é o **líder** desta sessão. Coordene o trabalho, crie tarefas para a equipe e gerencie os outros workers.",
+    worker_role: "Você é um **worker** nesta sessão. Assuma tarefas, conclua o trabalho e comunique-se com sua equipe.",
+    no_tasks: "Nenhuma tarefa criada ainda. Crie algumas com `swarm task create`.",
+    fleet_leader_task: "Você é o LÍDER. Divida esta tarefa em subtarefas com `swarm task create` e depois coordene a equipe.\n\nTarefa:",
+    fleet_worker_task: "Você é o WORKER #{n}. Espere o líder criar tarefas e depois assuma-as e conclua-as com os comandos do swarm.\n\nContexto:",
+    help_title: "Comandos do swarm:",
+    help_commands: [
+        "Ver todas as tarefas",
+        "Ver os detalhes de uma tarefa",
+        "Assumir a próxima tarefa disponível",
+        "Criar uma tarefa",
+        "Mudar o status de uma tarefa (pending, in_progress, completed, deleted)",
+        "Excluir uma tarefa",
+        "Ler suas mensagens (--unread apenas para as novas)",
+        "Enviar uma mensagem para um worker",
+        "Enviar uma mensagem para todos os workers",
+        "Listar os workers desta sessão",
+        "Contar as mensagens não lidas",
+        "Marcar todas as mensagens como lidas",
+        "Listar os workers com papel, modelo e especialidades",
+        "Encontrar o melhor worker para uma habilidade",
+        "Declarar suas especialidades",
+        "Líderes de PRD: progresso da sessão",
+        "Manter um servidor de desenvolvimento ou outro comando longo ativo durante a sessão",
+        "Mostrar esta ajuda",
+    ],
+};
+
+const FR: PromptStrings = PromptStrings {
+    leader_role: "Vous êtes le **leader** de cette session. Coordonnez le travail, créez des tâches pour l'équipe et gérez les autres workers.",
+    worker_role: "Vous êtes un **worker** dans cette session. Prenez des tâches, terminez le travail et communiquez avec votre équipe.",
+    no_tasks: "Aucune tâche pour l'instant. Créez-en avec `swarm task create`.",
+    fleet_leader_task: "Vous êtes le LEADER. Découpez cette tâche en sous-tâches avec `swarm task create`, puis coordonnez l'équipe.\n\nTâche :",
+    fleet_worker_task: "Vous êtes le WORKER n°{n}. Attendez que le leader crée des tâches, puis prenez-les et terminez-les avec les commandes swarm.\n\nContexte :",
+    help_title: "Commandes swarm :",
+    help_commands: [
+        "Voir toutes les tâches",
+        "Voir le détail d'une tâche",
+        "Prendre la prochaine tâche disponible",
+        "Créer une tâche",
+        "Changer le statut d'une tâche (pending, in_progress, completed, deleted)",
+        "Supprimer une tâche",
+        "Lire vos messages (--unread pour les nouveaux seulement)",
+        "Envoyer un message à un worker",
+        "Envoyer un message à tous les workers",
+        "Lister les workers de cette session",
+        "Compter les messages non lus",
+        "Marquer tous les messages comme lus",
+        "Lister les workers avec leur rôle, modèle et spécialités",
+        "Trouver le meilleur worker pour une compétence",
+        "Déclarer vos spécialités",
+        "Leaders de PRD : avancement de la session",
+        "Garder un serveur de dev ou une autre commande longue actif pendant la session",
+        "Afficher cette aide",
+    ],
+};
+
+const DE: PromptStrings = PromptStrings {
+    leader_role: "Du bist der **Leader** dieser Sitzung. Koordiniere die Arbeit, lege Aufgaben für das Team an und steuere die anderen Worker.",
+    worker_role: "Du bist ein **Worker** in dieser Sitzung. Übernimm Aufgaben, erledige die Arbeit und stimme dich mit deinem Team ab.",
+    no_tasks: "Noch keine Aufgaben. Lege welche mit `swarm task create` an.",
+    fleet_leader_task: "Du bist der LEADER. Zerlege diese Aufgabe mit `swarm task create` in Teilaufgaben und koordiniere dann das Team.\n\nAufgabe:",
+    fleet_worker_task: "Du bist WORKER #{n}. Warte, bis der Leader Aufgaben anlegt, und übernimm und erledige sie dann mit den swarm-Befehlen.\n\nKontext:",
+    help_title: "Swarm-Befehle:",
+    help_commands: [
+        "Alle Aufgaben anzeigen",
+        "Details einer Aufgabe anzeigen",
+        "Die nächste verfügbare Aufgabe übernehmen",
+        "Eine Aufgabe anlegen",
+        "Den Status einer Aufgabe setzen (pending, in_progress, completed, deleted)",
+        "Eine Aufgabe löschen",
+        "Nachrichten lesen (--unread nur neue)",
+        "Einem Worker eine Nachricht senden",
+        "Allen Workern eine Nachricht senden",
+        "Die Worker dieser Sitzung auflisten",
+        "Ungelesene Nachrichten zählen",
+        "Alle Nachrichten als gelesen markieren",
+        "Die Worker mit Rolle, Modell und Spezialgebieten auflisten",
+        "Den besten Worker für eine Fähigkeit finden",
+        "Deine Spezialgebiete angeben",
+        "PRD-Leader: Fortschritt der Sitzung",
+        "Einen Dev-Server oder anderen langlaufenden Befehl für die Sitzung am Laufen halten",
+        "Diese Hilfe anzeigen",
+    ],
+};
+
+/// Translations of the client's prompt text
+const LOCALES: &[(&str, &PromptStrings)] = &[("es", &ES), ("pt", &PT), ("fr", &FR), ("de", &DE)];
+
+/// The language a session's agents answer in, and whether the client's own
+/// prompt text follows it
+#[derive(Debug, Clone, PartialEq)]
+pub struct PromptLanguage {
+    /// As the user entered it: a code ("es", "pt-BR") or a name
+    pub respond_in: String,
+    pub localize: bool,
+}
+
+impl PromptLanguage {
+    /// English name of the language for the agent ("Portuguese (pt-BR)");
+    /// names nobody registered are passed through
+    pub fn name(&self) -> String {
+        let language = self.respond_in.trim();
+        match language_code(language) {
+            Some((_, name)) if language.contains(['-', '_']) => format!("{} ({})", name, language),
+            Some((_, name)) => name.to_string(),
+            None => language.to_string(),
+        }
+    }
+
+    /// Client text to use: translated when localizing to a known locale,
+    /// English otherwise
+    pub fn strings(&self) -> &'static PromptStrings {
+        if !self.localize {
+            return &EN;
+        }
+        language_code(&self.respond_in)
+            .and_then(|(code, _)| LOCALES.iter().find(|(locale, _)| *locale == code))
+            .map(|(_, strings)| *strings)
+            .unwrap_or(&EN)
+    }
+}
+
+/// Client text for an optional language
+pub fn prompt_strings(language: Option<&PromptLanguage>) -> &'static PromptStrings {
+    language.map(PromptLanguage::strings).unwrap_or(&EN)
+}
+
+/// Code and English name of a language given as a code, a code with a
+/// region, or an English name
+fn language_code(language: &str) -> Option<(&'static str, &'static str)> {
+    let primary = language
+        .trim()
+        .split(['-', '_'])
+        .next()
+        .unwrap_or_default();
+    LANGUAGE_NAMES
+        .iter()
+        .find(|(code, name)| {
+            primary.eq_ignore_ascii_case(code) || language.trim().eq_ignore_ascii_case(name)
+        })
+        .copied()
+}
+
+/// Choose (or with `None`, clear) the language of a live session
+pub fn set_live_language(session_id: &str, language: Option<String>) {
+    let mut languages = SESSION_LANGUAGES.lock();
+    match language.map(|l| l.trim().to_string()).filter(|l| !l.is_empty()) {
+        Some(language) => languages.insert(session_id.to_string(), language),
+        None => languages.remove(session_id),
+    };
+}
+
+/// The language chosen for a live session, if any
+pub fn live_language(session_id: &str) -> Option<String> {
+    SESSION_LANGUAGES.lock().get(session_id).cloned()
+}
+
+/// Drop a session's language (when it's deleted)
+pub fn clear_live_language(session_id: &str) {
+    SESSION_LANGUAGES.lock().remove(session_id);
+}
+
+/// The session's language, falling back to the global one. `None` leaves
+/// the agent to its default.
+pub fn prompt_language(session_id: &str) -> Option<PromptLanguage> {
+    let settings = load_settings().language;
+    let respond_in = live_language(session_id)
+        .or_else(|| Some(settings.respond_in.trim().to_string()))
+        .filter(|l| !l.is_empty())?;
+    Some(PromptLanguage {
+        respond_in,
+        localize: settings.localize_prompts,
+    })
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionLanguage {
+    /// Chosen for this session
+    pub session: Option<String>,
+    /// What the agents are told to use (`session` or the global setting)
+    pub effective: Option<String>,
+}
+
+/// Set the language a session's agents answer in (`None` or "" falls back
+/// to the global setting). Applies to workers started from now on and is
+/// saved with the session if it was persisted.
+#[tauri::command]
+pub fn set_session_language(
+    session_id: String,
+    language: Option<String>,
+) -> Result<SessionLanguage, CommandError> {
+    set_live_language(&session_id, language);
+
+    let store = SessionStore::new()?;
+    if store.session_exists(&session_id) {
+        let mut session = store.load_session(&session_id)?;
+        session.metadata.language = live_language(&session_id);
+        store.save_session(&session)?;
+    }
+    Ok(get_session_language(session_id))
+}
+
+/// The language chosen for a session and the one in effect
+#[tauri::command]
+pub fn get_session_language(session_id: String) -> SessionLanguage {
+    let session = live_language(&session_id).or_else(|| {
+        SessionStore::new()
+            .and_then(|store| store.load_session(&session_id))
+            .ok()
+            .and_then(|session| session.metadata.language)
+    });
+    let global = load_settings().language.respond_in.trim().to_string();
+    SessionLanguage {
+        effective: session.clone().or((!global.is_empty()).then_some(global)),
+        session,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn language(respond_in: &str, localize: bool) -> PromptLanguage {
+        PromptLanguage {
+            respond_in: respond_in.to_string(),
+            localize,
+        }
+    }
+
+    #[test]
+    fn test_name() {
+        assert_eq!(language("es", false).name(), "Spanish");
+        assert_eq!(language("pt-BR", false).name(), "Portuguese (pt-BR)");
+        assert_eq!(language("german", false).name(), "German");
+        assert_eq!(language("Klingon", false).name(), "Klingon");
+    }
+
+    #[test]
+    fn test_strings() {
+        assert_eq!(language("es", false).strings().no_tasks, EN.no_tasks);
+        assert_eq!(language("es-MX", true).strings().no_tasks, ES.no_tasks);
+        assert_eq!(language("French", true).strings().help_title, FR.help_title);
+        assert_eq!(language("ja", true).strings().no_tasks, EN.no_tasks);
+        assert_eq!(prompt_strings(None).leader_role, EN.leader_role);
+    }
+
+    #[test]
+    fn test_live_language() {
+        set_live_language("language-test", Some(" es ".to_string()));
+        assert_eq!(live_language("language-test").as_deref(), Some("es"));
+
+        set_live_language("language-test", Some(String::new()));
+        assert!(live_language("language-test").is_none());
+
+        set_live_language("language-test", Some("fr".to_string()));
+        clear_live_language("language-test");
+        assert!(live_language("language-test").is_none());
+    }
+}
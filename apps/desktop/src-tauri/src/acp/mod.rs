@@ -1,10 +1,48 @@
+pub mod agent_log;
+pub mod attachments;
+pub mod auth;
+pub mod bundle;
+pub mod capabilities;
+pub mod check_loop;
+pub mod claude_hooks;
 pub mod client;
 pub mod commands;
+pub mod compaction;
+pub mod context;
+pub mod coordination_delta;
 pub mod coordination_prompt;
+pub mod criteria;
+pub mod delta_batcher;
+pub mod dev_servers;
+pub mod drafts;
+pub mod events;
+pub mod fork;
+pub mod guards;
+pub mod hibernation;
+pub mod hooks;
+pub mod install;
+pub mod modes;
+pub mod inbox_push;
+pub mod language;
+pub mod network_isolation;
+pub mod preprocess;
+pub mod protocol_trace;
+pub mod rate_limits;
+pub mod read_only;
 pub mod registry;
+pub mod resource_limits;
+pub mod retry;
 pub mod session_store;
 pub mod skill_loader;
 pub mod skills;
 pub mod skills_commands;
+pub mod slash_actions;
 pub mod slash_commands;
+pub mod staged_context;
+pub mod stream_metrics;
 pub mod swarm;
+pub mod title;
+pub mod trust;
+pub mod turn;
+pub mod worker_health;
+pub mod write_diffs;
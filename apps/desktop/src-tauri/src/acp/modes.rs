@@ -0,0 +1,129 @@
+//! Session mode catalog
+//!
+//! Agents list the modes a session offers ("default", "plan", ...) when it
+//! is created or loaded, and report mode changes as `CurrentModeUpdate`s.
+//! The client records both here per session, so `get_session_modes` can
+//! answer without a round trip to the worker and `set_acp_session_mode`
+//! can reject modes the agent doesn't have. The current mode is what gets
+//! persisted with the session, and resuming restores it.
+
+use crate::acp::capabilities::{cached_capabilities, SessionModeInfo};
+use crate::acp::session_store::SessionStore;
+use crate::error::CommandError;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Modes of live sessions (session_id -> modes)
+static SESSION_MODES: Lazy<Mutex<HashMap<String, SessionModes>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct SessionModes {
+    pub current: Option<String>,
+    /// Empty when the agent doesn't support modes
+    pub available: Vec<SessionModeInfo>,
+}
+
+impl SessionModes {
+    /// Whether `mode_id` can be requested. Agents that didn't list their
+    /// modes get the benefit of the doubt.
+    pub fn allows(&self, mode_id: &str) -> bool {
+        self.available.is_empty() || self.available.iter().any(|m| m.id == mode_id)
+    }
+
+    /// Error for a mode the agent doesn't offer
+    pub fn unknown_mode(&self, mode_id: &str) -> String {
+        let ids: Vec<&str> = self.available.iter().map(|m| m.id.as_str()).collect();
+        format!("Unknown mode '{}' (available: {})", mode_id, ids.join(", "))
+    }
+}
+
+/// Record the modes a new or loaded session offers
+pub fn set_session_modes(session_id: &str, modes: SessionModes) {
+    SESSION_MODES.lock().insert(session_id.to_string(), modes);
+}
+
+/// Record a mode change
+pub fn set_current_mode(session_id: &str, mode_id: &str) {
+    SESSION_MODES
+        .lock()
+        .entry(session_id.to_string())
+        .or_default()
+        .current = Some(mode_id.to_string());
+}
+
+/// Modes of a live session
+pub fn live_session_modes(session_id: &str) -> Option<SessionModes> {
+    SESSION_MODES.lock().get(session_id).cloned()
+}
+
+/// Drop a session's modes (when it's deleted)
+pub fn clear_session_modes(session_id: &str) {
+    SESSION_MODES.lock().remove(session_id);
+}
+
+/// The modes a session offers and the one it's in. Sessions that aren't
+/// live fall back to their persisted mode and the modes their agent listed
+/// last time.
+#[tauri::command]
+pub fn get_session_modes(session_id: String) -> Result<SessionModes, CommandError> {
+    if let Some(modes) = live_session_modes(&session_id) {
+        return Ok(modes);
+    }
+    let session = SessionStore::new()?.load_session(&session_id)?;
+    let available = cached_capabilities()
+        .remove(&session.agent_id)
+        .map(|caps| caps.modes)
+        .unwrap_or_default();
+    Ok(SessionModes {
+        current: Some(session.mode),
+        available,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mode(id: &str) -> SessionModeInfo {
+        SessionModeInfo {
+            id: id.to_string(),
+            name: id.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_allows() {
+        let modes = SessionModes {
+            current: Some("default".to_string()),
+            available: vec![mode("default"), mode("plan")],
+        };
+        assert!(modes.allows("plan"));
+        assert!(!modes.allows("yolo"));
+        assert_eq!(
+            modes.unknown_mode("yolo"),
+            "Unknown mode 'yolo' (available: default, plan)"
+        );
+        assert!(SessionModes::default().allows("anything"));
+    }
+
+    #[test]
+    fn test_set_current_mode() {
+        set_session_modes(
+            "modes-test",
+            SessionModes {
+                current: Some("default".to_string()),
+                available: vec![mode("default"), mode("plan")],
+            },
+        );
+        set_current_mode("modes-test", "plan");
+        let modes = live_session_modes("modes-test").unwrap();
+        assert_eq!(modes.current.as_deref(), Some("plan"));
+        assert_eq!(modes.available.len(), 2);
+
+        clear_session_modes("modes-test");
+        assert!(live_session_modes("modes-test").is_none());
+    }
+}
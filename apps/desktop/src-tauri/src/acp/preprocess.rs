@@ -0,0 +1,285 @@
+//! Prompt preprocessing
+//!
+//! Follow-up prompts pass through a chain of preprocessors in the worker
+//! before they reach the agent:
+//!
+//! - `slash_commands` expands `/command args` from the session's registry
+//! - `templates` fills `{{name}}` placeholders from `prompt_templates`
+//! - `mentions` appends the contents of `@path` files in the project
+//! - `skills` puts the session's active skill prompts in front
+//! - `redaction` masks secrets
+//!
+//! The order comes from the `prompt_preprocessors` setting; a session can
+//! set its own with `set_prompt_preprocessors`. Each preprocessor is a plain
+//! function of the prompt and a `PromptContext`, so they can be tested (and
+//! chained) without a session.
+
+use crate::acp::skills_commands::{get_active_skill_prompts, process_slash_command};
+use crate::secrets::redact::redactor_for;
+use crate::settings::load_settings;
+pub use crate::settings::store::PromptPreprocessor;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use regex::Regex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Largest file inlined for an `@path` mention
+const MAX_MENTION_BYTES: u64 = 100 * 1024;
+
+/// `@path` at the start of the prompt or after whitespace
+static MENTION_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?:^|\s)@([\w][\w./\-]*[\w/])").unwrap());
+
+/// `{{name}}` placeholders
+static TEMPLATE_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\{\{\s*([\w.\-]+)\s*\}\}").unwrap());
+
+/// Pipelines chosen per session (session_id -> preprocessors in order)
+static SESSION_PIPELINES: Lazy<Mutex<HashMap<String, Vec<PromptPreprocessor>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// What the preprocessors know about the prompt being sent
+pub struct PromptContext<'a> {
+    pub session_id: &'a str,
+    pub cwd: Option<&'a str>,
+    /// `{{name}}` values
+    pub templates: &'a HashMap<String, String>,
+}
+
+/// Run one preprocessor over a prompt
+pub fn apply(step: PromptPreprocessor, ctx: &PromptContext, prompt: String) -> String {
+    match step {
+        PromptPreprocessor::SlashCommands => expand_slash_command(ctx, prompt),
+        PromptPreprocessor::Templates => fill_templates(ctx, prompt),
+        PromptPreprocessor::Mentions => resolve_mentions(ctx, prompt),
+        PromptPreprocessor::Skills => inject_skills(ctx, prompt),
+        PromptPreprocessor::Redaction => redactor_for(ctx.cwd).redact(&prompt).into_owned(),
+    }
+}
+
+/// Run a pipeline over a prompt, in order
+pub fn run_pipeline(steps: &[PromptPreprocessor], ctx: &PromptContext, prompt: &str) -> String {
+    steps
+        .iter()
+        .fold(prompt.to_string(), |prompt, step| apply(*step, ctx, prompt))
+}
+
+fn expand_slash_command(ctx: &PromptContext, prompt: String) -> String {
+    process_slash_command(ctx.session_id.to_string(), prompt.clone()).unwrap_or(prompt)
+}
+
+fn fill_templates(ctx: &PromptContext, prompt: String) -> String {
+    if ctx.templates.is_empty() {
+        return prompt;
+    }
+    TEMPLATE_RE
+        .replace_all(&prompt, |caps: &regex::Captures| {
+            ctx.templates
+                .get(&caps[1])
+                .cloned()
+                .unwrap_or_else(|| caps[0].to_string())
+        })
+        .into_owned()
+}
+
+fn resolve_mentions(ctx: &PromptContext, prompt: String) -> String {
+    let Some(cwd) = ctx.cwd else {
+        return prompt;
+    };
+    let root = Path::new(cwd);
+    let mut attached: Vec<String> = Vec::new();
+    let mut blocks = String::new();
+    for caps in MENTION_RE.captures_iter(&prompt) {
+        let mention = &caps[1];
+        if attached.iter().any(|m| m == mention) {
+            continue;
+        }
+        let path = root.join(mention);
+        // Mentions stay inside the project
+        let inside = path
+            .canonicalize()
+            .ok()
+            .zip(root.canonicalize().ok())
+            .is_some_and(|(path, root)| path.starts_with(root));
+        let small = path
+            .metadata()
+            .is_ok_and(|m| m.is_file() && m.len() <= MAX_MENTION_BYTES);
+        if !inside || !small {
+            continue;
+        }
+        let Ok(contents) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        blocks.push_str(&format!("\n\n<file path=\"{}\">\n{}\n</file>", mention, contents.trim_end()));
+        attached.push(mention.to_string());
+    }
+    format!("{}{}", prompt, blocks)
+}
+
+fn inject_skills(ctx: &PromptContext, prompt: String) -> String {
+    let skills = get_active_skill_prompts(ctx.session_id.to_string());
+    if skills.trim().is_empty() {
+        return prompt;
+    }
+    format!("{}\n\n---\n\n{}", skills, prompt)
+}
+
+/// The pipeline a session's prompts go through
+pub fn session_pipeline(session_id: &str) -> Vec<PromptPreprocessor> {
+    SESSION_PIPELINES
+        .lock()
+        .get(session_id)
+        .cloned()
+        .unwrap_or_else(|| load_settings().prompt_preprocessors)
+}
+
+/// Run a session's pipeline over a prompt about to be sent to its worker
+pub fn preprocess_prompt(session_id: &str, cwd: &str, prompt: &str) -> String {
+    let steps = session_pipeline(session_id);
+    if steps.is_empty() {
+        return prompt.to_string();
+    }
+    let templates = load_settings().prompt_templates;
+    let ctx = PromptContext {
+        session_id,
+        cwd: Some(cwd),
+        templates: &templates,
+    };
+    run_pipeline(&steps, &ctx, prompt)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionPipeline {
+    pub steps: Vec<PromptPreprocessor>,
+    /// Whether the session overrides the `prompt_preprocessors` setting
+    pub custom: bool,
+}
+
+/// Choose (and order) a session's preprocessors; `None` goes back to the
+/// `prompt_preprocessors` setting
+#[tauri::command]
+pub fn set_prompt_preprocessors(
+    session_id: String,
+    steps: Option<Vec<PromptPreprocessor>>,
+) -> SessionPipeline {
+    let mut pipelines = SESSION_PIPELINES.lock();
+    match steps {
+        Some(mut steps) => {
+            let mut seen = Vec::new();
+            steps.retain(|step| {
+                let first = !seen.contains(step);
+                seen.push(*step);
+                first
+            });
+            pipelines.insert(session_id.clone(), steps);
+        }
+        None => {
+            pipelines.remove(&session_id);
+        }
+    }
+    drop(pipelines);
+    get_prompt_preprocessors(session_id)
+}
+
+/// The preprocessors a session's prompts go through, in order
+#[tauri::command]
+pub fn get_prompt_preprocessors(session_id: String) -> SessionPipeline {
+    SessionPipeline {
+        custom: SESSION_PIPELINES.lock().contains_key(&session_id),
+        steps: session_pipeline(&session_id),
+    }
+}
+
+/// What a prompt would look like after the session's preprocessors
+#[tauri::command]
+pub fn preview_prompt(session_id: String, cwd: String, prompt: String) -> String {
+    preprocess_prompt(&session_id, &cwd, &prompt)
+}
+
+/// Drop a session's pipeline choice
+pub fn clear_session_pipeline(session_id: &str) {
+    SESSION_PIPELINES.lock().remove(session_id);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn context<'a>(cwd: Option<&'a str>, templates: &'a HashMap<String, String>) -> PromptContext<'a> {
+        PromptContext {
+            session_id: "preprocess-test",
+            cwd,
+            templates,
+        }
+    }
+
+    #[test]
+    fn test_templates() {
+        let templates = HashMap::from([("team".to_string(), "platform".to_string())]);
+        let ctx = context(None, &templates);
+        assert_eq!(
+            apply(
+                PromptPreprocessor::Templates,
+                &ctx,
+                "Ping {{ team }} about {{unknown}}".to_string()
+            ),
+            "Ping platform about {{unknown}}"
+        );
+    }
+
+    #[test]
+    fn test_mentions() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join("src/lib.rs"), "pub fn a() {}\n").unwrap();
+        let cwd = dir.path().to_string_lossy().to_string();
+        let templates = HashMap::new();
+        let ctx = context(Some(&cwd), &templates);
+
+        let out = apply(
+            PromptPreprocessor::Mentions,
+            &ctx,
+            "Fix @src/lib.rs and @src/lib.rs, mail me@example.com, skip @missing.rs".to_string(),
+        );
+        assert_eq!(
+            out,
+            "Fix @src/lib.rs and @src/lib.rs, mail me@example.com, skip @missing.rs\
+             \n\n<file path=\"src/lib.rs\">\npub fn a() {}\n</file>"
+        );
+
+        // Nothing outside the project
+        let out = apply(PromptPreprocessor::Mentions, &ctx, "@../etc/passwd".to_string());
+        assert_eq!(out, "@../etc/passwd");
+    }
+
+    #[test]
+    fn test_pipeline_order() {
+        let templates = HashMap::from([("cmd".to_string(), "/nope".to_string())]);
+        let ctx = context(None, &templates);
+        // Templates run after slash commands, so their output isn't expanded
+        let steps = [PromptPreprocessor::SlashCommands, PromptPreprocessor::Templates];
+        assert_eq!(run_pipeline(&steps, &ctx, "{{cmd}}"), "/nope");
+        assert_eq!(run_pipeline(&[], &ctx, "as is"), "as is");
+    }
+
+    #[test]
+    fn test_session_pipeline_dedups_and_resets() {
+        let session = "preprocess-test-session".to_string();
+        let pipeline = set_prompt_preprocessors(
+            session.clone(),
+            Some(vec![
+                PromptPreprocessor::Redaction,
+                PromptPreprocessor::Mentions,
+                PromptPreprocessor::Redaction,
+            ]),
+        );
+        assert!(pipeline.custom);
+        assert_eq!(
+            pipeline.steps,
+            vec![PromptPreprocessor::Redaction, PromptPreprocessor::Mentions]
+        );
+        assert!(!set_prompt_preprocessors(session, None).custom);
+    }
+}
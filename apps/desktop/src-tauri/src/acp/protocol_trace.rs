@@ -0,0 +1,380 @@
+//! ACP protocol tracing
+//!
+//! Every agent's stdin and stdout are wrapped in taps that, while tracing
+//! is on for the session (`set_protocol_trace`), copy each JSON-RPC frame
+//! to `~/.crafter-code/sessions/{session_id}/traces/{worker_id}.jsonl` as
+//! `{"ts", "dir", "frame"}` lines, `dir` being "send" (client to agent) or
+//! "recv". Frames pass through the worker's redactor first and are cut at
+//! `MAX_FRAME_BYTES`. A file past `protocol_trace_max_mb` is rotated to
+//! `{worker_id}.1.jsonl`, so a worker keeps at most two files.
+
+use crate::error::CommandError;
+use crate::secrets::redact::redactor_for_worker;
+use crate::settings::load_settings;
+use futures::io::{AsyncRead, AsyncWrite};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Frames longer than this are cut (file contents can make them huge)
+const MAX_FRAME_BYTES: usize = 256 * 1024;
+
+/// Sessions being traced (session_id -> open trace files)
+static TRACES: Lazy<Mutex<HashMap<String, SessionTrace>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Which way a frame went
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Direction {
+    /// Client to agent
+    Send,
+    /// Agent to client
+    Recv,
+}
+
+impl Direction {
+    fn as_str(self) -> &'static str {
+        match self {
+            Direction::Send => "send",
+            Direction::Recv => "recv",
+        }
+    }
+}
+
+struct TraceFile {
+    file: File,
+    bytes: u64,
+}
+
+/// Trace files of one session
+struct SessionTrace {
+    dir: PathBuf,
+    max_bytes: u64,
+    files: HashMap<String, TraceFile>,
+}
+
+impl SessionTrace {
+    fn new(dir: PathBuf, max_bytes: u64) -> Self {
+        Self {
+            dir,
+            max_bytes,
+            files: HashMap::new(),
+        }
+    }
+
+    fn path(&self, worker_id: &str) -> PathBuf {
+        self.dir.join(format!("{}.jsonl", worker_id))
+    }
+
+    fn open(&self, worker_id: &str) -> Result<TraceFile, String> {
+        fs::create_dir_all(&self.dir)
+            .map_err(|e| format!("Failed to create trace directory: {}", e))?;
+        let path = self.path(worker_id);
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+        let bytes = file.metadata().map(|m| m.len()).unwrap_or(0);
+        Ok(TraceFile { file, bytes })
+    }
+
+    /// Append one line to a worker's file, rotating it when full
+    fn write(&mut self, worker_id: &str, line: &str) -> Result<(), String> {
+        let full = self
+            .files
+            .get(worker_id)
+            .is_some_and(|f| self.max_bytes > 0 && f.bytes + line.len() as u64 > self.max_bytes);
+        if full {
+            self.files.remove(worker_id);
+            let path = self.path(worker_id);
+            fs::rename(&path, path.with_extension("1.jsonl"))
+                .map_err(|e| format!("Failed to rotate {}: {}", path.display(), e))?;
+        }
+        if !self.files.contains_key(worker_id) {
+            let file = self.open(worker_id)?;
+            self.files.insert(worker_id.to_string(), file);
+        }
+        let trace = self.files.get_mut(worker_id).expect("trace file just opened");
+        writeln!(trace.file, "{}", line).map_err(|e| format!("Failed to write trace: {}", e))?;
+        trace.bytes += line.len() as u64 + 1;
+        Ok(())
+    }
+}
+
+/// Directory holding a session's traces
+pub fn trace_dir(session_id: &str) -> Result<PathBuf, String> {
+    Ok(dirs::home_dir()
+        .ok_or_else(|| "Could not determine home directory".to_string())?
+        .join(".crafter-code")
+        .join("sessions")
+        .join(session_id)
+        .join("traces"))
+}
+
+pub fn is_tracing(session_id: &str) -> bool {
+    TRACES.lock().contains_key(session_id)
+}
+
+/// One trace line for a raw frame, redacted and cut to size
+fn trace_line(worker_id: &str, direction: Direction, frame: &[u8]) -> String {
+    let text = String::from_utf8_lossy(frame);
+    let text = text.trim_end();
+    let text = redactor_for_worker(worker_id).redact(text).into_owned();
+    let frame = if text.len() > MAX_FRAME_BYTES {
+        let mut end = MAX_FRAME_BYTES;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        serde_json::json!({ "truncated": text.len(), "text": &text[..end] })
+    } else {
+        serde_json::from_str(&text).unwrap_or(serde_json::Value::String(text))
+    };
+    serde_json::json!({
+        "ts": chrono::Utc::now().timestamp_millis(),
+        "dir": direction.as_str(),
+        "frame": frame,
+    })
+    .to_string()
+}
+
+/// Record a frame if the session is being traced
+fn record(session_id: &str, worker_id: &str, direction: Direction, frame: &[u8]) {
+    if frame.iter().all(u8::is_ascii_whitespace) {
+        return;
+    }
+    let line = trace_line(worker_id, direction, frame);
+    let mut traces = TRACES.lock();
+    let Some(trace) = traces.get_mut(session_id) else {
+        return;
+    };
+    if let Err(e) = trace.write(worker_id, &line) {
+        eprintln!("[Trace] {}, stopping the trace of session={}", e, session_id);
+        traces.remove(session_id);
+    }
+}
+
+/// Splits a byte stream into newline-delimited frames for one direction of
+/// a worker's connection
+struct FrameTap {
+    session_id: String,
+    worker_id: String,
+    direction: Direction,
+    pending: Vec<u8>,
+}
+
+impl FrameTap {
+    fn new(session_id: &str, worker_id: &str, direction: Direction) -> Self {
+        Self {
+            session_id: session_id.to_string(),
+            worker_id: worker_id.to_string(),
+            direction,
+            pending: Vec::new(),
+        }
+    }
+
+    fn observe(&mut self, bytes: &[u8]) {
+        if !is_tracing(&self.session_id) {
+            // Tracing starts at the next frame boundary
+            self.pending.clear();
+            return;
+        }
+        self.pending.extend_from_slice(bytes);
+        while let Some(end) = self.pending.iter().position(|b| *b == b'\n') {
+            let frame: Vec<u8> = self.pending.drain(..=end).collect();
+            record(&self.session_id, &self.worker_id, self.direction, &frame);
+        }
+    }
+}
+
+/// The agent's stdout, traced
+pub struct TracedReader<R> {
+    inner: R,
+    tap: FrameTap,
+}
+
+impl<R> TracedReader<R> {
+    pub fn new(inner: R, session_id: &str, worker_id: &str) -> Self {
+        Self {
+            inner,
+            tap: FrameTap::new(session_id, worker_id, Direction::Recv),
+        }
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for TracedReader<R> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let poll = Pin::new(&mut self.inner).poll_read(cx, buf);
+        if let Poll::Ready(Ok(n)) = &poll {
+            self.tap.observe(&buf[..*n]);
+        }
+        poll
+    }
+}
+
+/// The agent's stdin, traced
+pub struct TracedWriter<W> {
+    inner: W,
+    tap: FrameTap,
+}
+
+impl<W> TracedWriter<W> {
+    pub fn new(inner: W, session_id: &str, worker_id: &str) -> Self {
+        Self {
+            inner,
+            tap: FrameTap::new(session_id, worker_id, Direction::Send),
+        }
+    }
+}
+
+impl<W: AsyncWrite + Unpin> AsyncWrite for TracedWriter<W> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let poll = Pin::new(&mut self.inner).poll_write(cx, buf);
+        if let Poll::Ready(Ok(n)) = &poll {
+            self.tap.observe(&buf[..*n]);
+        }
+        poll
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_close(cx)
+    }
+}
+
+/// A worker's trace file
+#[derive(Debug, Clone, Serialize)]
+pub struct TraceFileInfo {
+    pub path: String,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProtocolTrace {
+    pub session_id: String,
+    pub enabled: bool,
+    pub dir: String,
+    /// Trace files written so far, including rotated ones
+    pub files: Vec<TraceFileInfo>,
+}
+
+fn trace_files(dir: &Path) -> Vec<TraceFileInfo> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<TraceFileInfo> = entries
+        .flatten()
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "jsonl"))
+        .map(|entry| TraceFileInfo {
+            path: entry.path().to_string_lossy().to_string(),
+            bytes: entry.metadata().map(|m| m.len()).unwrap_or(0),
+        })
+        .collect();
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    files
+}
+
+/// Whether a session is being traced and the trace files it has
+#[tauri::command]
+pub fn get_protocol_trace(session_id: String) -> Result<ProtocolTrace, CommandError> {
+    let dir = trace_dir(&session_id)?;
+    Ok(ProtocolTrace {
+        enabled: is_tracing(&session_id),
+        files: trace_files(&dir),
+        dir: dir.to_string_lossy().to_string(),
+        session_id,
+    })
+}
+
+/// Start or stop tracing the JSON-RPC traffic of a session's agents.
+/// Frames are appended to the existing files, so a trace can be paused and
+/// resumed.
+#[tauri::command]
+pub fn set_protocol_trace(
+    session_id: String,
+    enabled: bool,
+) -> Result<ProtocolTrace, CommandError> {
+    if enabled {
+        let dir = trace_dir(&session_id)?;
+        let max_bytes = load_settings().protocol_trace_max_mb * 1024 * 1024;
+        TRACES
+            .lock()
+            .entry(session_id.clone())
+            .or_insert_with(|| SessionTrace::new(dir, max_bytes));
+        eprintln!("[Trace] Tracing session={}", session_id);
+    } else if TRACES.lock().remove(&session_id).is_some() {
+        eprintln!("[Trace] Stopped tracing session={}", session_id);
+    }
+    get_protocol_trace(session_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frames_split_across_writes() {
+        let dir = tempfile::tempdir().unwrap();
+        TRACES.lock().insert(
+            "trace-test".to_string(),
+            SessionTrace::new(dir.path().to_path_buf(), 0),
+        );
+        let mut tap = FrameTap::new("trace-test", "w1", Direction::Send);
+        tap.observe(br#"{"jsonrpc":"2.0","id":1,"#);
+        tap.observe(b"\"method\":\"initialize\"}\n{\"jsonrpc\":\"2.0\",");
+        TRACES.lock().remove("trace-test");
+        // Stopped: the rest of the second frame is dropped
+        tap.observe(b"\"id\":2}\n");
+
+        let content = fs::read_to_string(dir.path().join("w1.jsonl")).unwrap();
+        let lines: Vec<serde_json::Value> = content
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0]["dir"], "send");
+        assert_eq!(lines[0]["frame"]["method"], "initialize");
+        assert!(tap.pending.is_empty());
+    }
+
+    #[test]
+    fn test_rotation() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut trace = SessionTrace::new(dir.path().to_path_buf(), 30);
+        trace.write("w1", "0123456789").unwrap();
+        trace.write("w1", "0123456789").unwrap();
+        trace.write("w1", "abcdefghij").unwrap();
+
+        let current = fs::read_to_string(dir.path().join("w1.jsonl")).unwrap();
+        let rotated = fs::read_to_string(dir.path().join("w1.1.jsonl")).unwrap();
+        assert_eq!(current, "abcdefghij\n");
+        assert_eq!(rotated, "0123456789\n0123456789\n");
+        assert_eq!(trace_files(dir.path()).len(), 2);
+    }
+
+    #[test]
+    fn test_long_frames_are_cut() {
+        let frame = format!("\"{}\"\n", "x".repeat(MAX_FRAME_BYTES + 10));
+        let line: serde_json::Value =
+            serde_json::from_str(&trace_line("w1", Direction::Recv, frame.as_bytes())).unwrap();
+        assert_eq!(line["frame"]["truncated"], MAX_FRAME_BYTES + 12);
+        assert_eq!(line["frame"]["text"].as_str().unwrap().len(), MAX_FRAME_BYTES);
+    }
+}
@@ -0,0 +1,178 @@
+//! Rate limit windows per agent and model
+//!
+//! When a prompt is rate limited the window is recorded for its agent and
+//! model, with the reset time the provider gave (or a default), and
+//! announced as `rate-limit-status`. Prompts to that agent and model wait
+//! for the window to close instead of failing again straight away; the
+//! first prompt that goes through clears it.
+
+use crate::acp::events::EventSink;
+use crate::events::RateLimitStatusEvent;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use regex::Regex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Window assumed when the error doesn't say when the limit resets
+const DEFAULT_WINDOW_SECS: i64 = 60;
+
+/// Longest window honoured, so a misparsed reset can't stall an agent
+const MAX_WINDOW_SECS: i64 = 60 * 60;
+
+/// "retry after 30", "retry-after: 30s", "try again in 2 minutes", "resets in 45 seconds"
+static RETRY_IN_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?i)(?:retry[- ]after|try again in|resets? in)[:\s]*(\d+(?:\.\d+)?)\s*(ms|milliseconds?|s|secs?|seconds?|m|mins?|minutes?|h|hours?)?\b",
+    )
+    .unwrap()
+});
+
+/// Open windows, keyed by `{agent_id}/{model}`
+static WINDOWS: Lazy<Mutex<HashMap<String, RateLimitWindow>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// A rate limit an agent and model are under
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct RateLimitWindow {
+    pub agent_id: String,
+    pub model: String,
+    /// Rate-limited responses since the window opened
+    pub hits: u32,
+    pub limited_at: i64,
+    /// Unix timestamp the window closes
+    pub reset_at: i64,
+    pub message: String,
+}
+
+fn key(agent_id: &str, model: &str) -> String {
+    format!("{}/{}", agent_id, model)
+}
+
+/// Seconds until the limit resets, if the message says
+pub fn parse_retry_after(message: &str) -> Option<i64> {
+    let captures = RETRY_IN_RE.captures(message)?;
+    let value: f64 = captures[1].parse().ok()?;
+    let unit = captures.get(2).map(|m| m.as_str().to_lowercase()).unwrap_or_default();
+    let secs = if unit.starts_with("ms") || unit.starts_with("milli") {
+        value / 1000.0
+    } else if unit.starts_with('m') {
+        value * 60.0
+    } else if unit.starts_with('h') {
+        value * 3600.0
+    } else {
+        value
+    };
+    Some(secs.ceil() as i64)
+}
+
+/// Record a rate-limited response and announce the window
+pub fn record_hit(events: &EventSink, agent_id: &str, model: &str, message: &str) -> RateLimitWindow {
+    let now = chrono::Utc::now().timestamp();
+    let retry_in = parse_retry_after(message)
+        .unwrap_or(DEFAULT_WINDOW_SECS)
+        .clamp(1, MAX_WINDOW_SECS);
+
+    let window = {
+        let mut windows = WINDOWS.lock();
+        let window = windows
+            .entry(key(agent_id, model))
+            .or_insert_with(|| RateLimitWindow {
+                agent_id: agent_id.to_string(),
+                model: model.to_string(),
+                hits: 0,
+                limited_at: now,
+                reset_at: now,
+                message: String::new(),
+            });
+        window.hits += 1;
+        window.reset_at = window.reset_at.max(now + retry_in);
+        window.message = message.to_string();
+        window.clone()
+    };
+
+    eprintln!(
+        "[ACP] {} rate limited, resets in {}s",
+        key(agent_id, model),
+        window.reset_at - now
+    );
+    events.send(&RateLimitStatusEvent {
+        agent_id: agent_id.to_string(),
+        model: model.to_string(),
+        limited: true,
+        reset_at: Some(window.reset_at),
+        hits: window.hits,
+        message: Some(window.message.clone()),
+    });
+    window
+}
+
+/// Close the window after a prompt got through
+pub fn record_success(events: &EventSink, agent_id: &str, model: &str) {
+    if WINDOWS.lock().remove(&key(agent_id, model)).is_none() {
+        return;
+    }
+    events.send(&RateLimitStatusEvent {
+        agent_id: agent_id.to_string(),
+        model: model.to_string(),
+        limited: false,
+        reset_at: None,
+        hits: 0,
+        message: None,
+    });
+}
+
+/// How long until the agent and model may be prompted again
+pub fn remaining(agent_id: &str, model: &str) -> Option<Duration> {
+    let reset_at = WINDOWS.lock().get(&key(agent_id, model))?.reset_at;
+    let secs = reset_at - chrono::Utc::now().timestamp();
+    (secs > 0).then(|| Duration::from_secs(secs as u64))
+}
+
+/// Agents and models currently rate limited
+#[tauri::command]
+pub fn get_rate_limits() -> Vec<RateLimitWindow> {
+    let now = chrono::Utc::now().timestamp();
+    let mut windows: Vec<RateLimitWindow> = WINDOWS
+        .lock()
+        .values()
+        .filter(|w| w.reset_at > now)
+        .cloned()
+        .collect();
+    windows.sort_by_key(|w| w.reset_at);
+    windows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_retry_after() {
+        assert_eq!(parse_retry_after("429: retry after 30"), Some(30));
+        assert_eq!(parse_retry_after("Retry-After: 12s"), Some(12));
+        assert_eq!(parse_retry_after("Please try again in 2 minutes."), Some(120));
+        assert_eq!(parse_retry_after("try again in 1.5s"), Some(2));
+        assert_eq!(parse_retry_after("limit resets in 500ms"), Some(1));
+        assert_eq!(parse_retry_after("rate limit exceeded"), None);
+    }
+
+    #[test]
+    fn test_window_lifecycle() {
+        let events = EventSink::Stdout { auto_approve: false };
+        let window = record_hit(&events, "rl-test", "model", "try again in 40 seconds");
+        assert_eq!(window.hits, 1);
+        let left = remaining("rl-test", "model").unwrap();
+        assert!(left <= Duration::from_secs(40) && left >= Duration::from_secs(38));
+
+        // A shorter reset doesn't shrink the window
+        let window = record_hit(&events, "rl-test", "model", "retry after 5");
+        assert_eq!(window.hits, 2);
+        assert!(remaining("rl-test", "model").unwrap() > Duration::from_secs(30));
+        assert!(remaining("rl-test", "other-model").is_none());
+
+        record_success(&events, "rl-test", "model");
+        assert!(remaining("rl-test", "model").is_none());
+    }
+}
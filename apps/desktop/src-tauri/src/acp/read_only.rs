@@ -0,0 +1,186 @@
+//! Client-side read-only enforcement
+//!
+//! Plan-style modes only ask the agent not to touch the repo. While a session
+//! is in one of the modes listed in `read_only_modes`, the client refuses
+//! file writes and edit-kind permissions itself, and runs only commands made
+//! up of an allowlist of read-only tools (`ls`, `grep`, `git diff`, ...);
+//! anything else, builds and tests included, is refused with a "read-only
+//! session" error the agent can report.
+
+use agent_client_protocol::ToolKind;
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+/// JSON-RPC error code for actions refused in a read-only session
+pub const READ_ONLY_ERROR_CODE: i32 = -32001;
+
+/// Programs that only read: a read-only session may run these and nothing
+/// else. Tools that can run other programs or write files through an
+/// option (`xargs`, `awk`, `sed`, `fd -x`, `tree -o`, ...) are left out.
+const READ_ONLY_COMMANDS: &[&str] = &[
+    "ls", "cat", "head", "tail", "grep", "rg", "wc", "pwd", "echo", "which", "stat", "du", "df",
+    "diff", "cmp", "cut", "tr", "nl", "basename", "dirname", "realpath", "find", "git", "true",
+    "false",
+];
+
+/// Git subcommands that don't touch the worktree, index or refs
+const READ_ONLY_GIT_SUBCOMMANDS: &[&str] = &[
+    "status",
+    "diff",
+    "log",
+    "show",
+    "blame",
+    "ls-files",
+    "ls-tree",
+    "rev-parse",
+    "cat-file",
+    "shortlog",
+    "describe",
+];
+
+/// Options (and their prefixes) that make an allowed program write a file or
+/// run a command
+const WRITING_OPTIONS: &[(&str, &[&str])] = &[
+    (
+        "find",
+        &[
+            "-delete", "-exec", "-execdir", "-ok", "-okdir", "-fprint", "-fprint0", "-fprintf",
+            "-fls",
+        ],
+    ),
+    ("rg", &["--pre"]),
+    (
+        "git",
+        &["--output", "--ext-diff", "--textconv", "--filters"],
+    ),
+];
+
+/// Redirections that don't write a file: `2>&1`, `>/dev/null`, ...
+static HARMLESS_REDIRECT: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\d*>>?\s*(&\d+|/dev/null)").unwrap());
+
+/// Whether `mode_id` is one of the configured read-only modes
+pub fn is_read_only_mode(mode_id: Option<&str>, read_only_modes: &[String]) -> bool {
+    mode_id.is_some_and(|mode| read_only_modes.iter().any(|m| m.eq_ignore_ascii_case(mode)))
+}
+
+/// Whether a shell command line only reads. Every command in it has to be
+/// on the allowlist; command substitution, subshells and redirection into
+/// files are refused, since what they run or write can't be checked here.
+pub fn is_read_only_command(command: &str) -> bool {
+    if command.contains("$(") || command.contains('`') || command.contains(['(', ')', '{', '}']) {
+        return false;
+    }
+    let command = HARMLESS_REDIRECT.replace_all(command, " ");
+    if command.contains('>') {
+        return false;
+    }
+    command
+        .split(['\n', ';', '|', '&'])
+        .map(str::trim)
+        .filter(|segment| !segment.is_empty())
+        .all(is_read_only_segment)
+}
+
+fn is_read_only_segment(segment: &str) -> bool {
+    let mut words = segment.split_whitespace();
+    let Some(program) = words.next() else {
+        return true;
+    };
+    let args: Vec<&str> = words.collect();
+    if !READ_ONLY_COMMANDS.contains(&program) {
+        return false;
+    }
+    let writes = WRITING_OPTIONS
+        .iter()
+        .find(|(tool, _)| *tool == program)
+        .is_some_and(|(_, options)| {
+            args.iter()
+                .any(|arg| options.iter().any(|option| arg.starts_with(option)))
+        });
+    if writes {
+        return false;
+    }
+    if program == "git" {
+        // Options before the subcommand (`-c`, `-C`, `--exec-path`, ...)
+        // can point git at programs of the agent's choosing
+        return args
+            .first()
+            .is_some_and(|sub| READ_ONLY_GIT_SUBCOMMANDS.contains(sub));
+    }
+    true
+}
+
+/// Tool kinds whose permission requests are refused outright
+pub fn is_edit_kind(kind: &ToolKind) -> bool {
+    matches!(kind, ToolKind::Edit | ToolKind::Delete | ToolKind::Move)
+}
+
+/// Structured error for an action refused in a read-only session
+pub fn read_only_error(mode_id: &str, action: &str, detail: &str) -> agent_client_protocol::Error {
+    agent_client_protocol::Error::new(
+        READ_ONLY_ERROR_CODE,
+        format!(
+            "read-only session: {} is not allowed in '{}' mode ({})",
+            action, mode_id, detail
+        ),
+    )
+    .with_data(serde_json::json!({
+        "reason": "read_only_session",
+        "mode": mode_id,
+        "action": action,
+        "detail": detail
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_only_commands() {
+        for command in [
+            "ls -la",
+            "git status && git diff HEAD~1",
+            "grep -rn foo src 2>&1 | head -20",
+            "rg -n 'fn main' src > /dev/null",
+            "find . -name '*.rs' | wc -l",
+            "cat Cargo.toml; git log --oneline -5",
+        ] {
+            assert!(is_read_only_command(command), "{}", command);
+        }
+        for command in [
+            "rm -rf target",
+            "cargo test",
+            "npm run lint",
+            "echo hi > notes.md",
+            "cat a >> b",
+            "python -c 'open(\"a\", \"w\")'",
+            "bash -c 'rm -rf src'",
+            "ls $(touch x)",
+            "ls `touch x`",
+            "env sed -i 's/a/b/' src/lib.rs",
+            "FOO=1 ls",
+            "/bin/rm a",
+            "find . -name '*.tmp' -delete",
+            "sort -o out.txt in.txt",
+            "find . -exec rm {} +",
+            "rg --pre=./script foo",
+            "git commit -am wip",
+            "git -c core.pager=sh log",
+            "git diff --output=patch.diff",
+            "ls | xargs rm",
+        ] {
+            assert!(!is_read_only_command(command), "{}", command);
+        }
+    }
+
+    #[test]
+    fn test_is_read_only_mode() {
+        let modes = vec!["plan".to_string()];
+        assert!(is_read_only_mode(Some("Plan"), &modes));
+        assert!(!is_read_only_mode(Some("default"), &modes));
+        assert!(!is_read_only_mode(None, &modes));
+        assert!(!is_read_only_mode(Some("plan"), &[]));
+    }
+}
@@ -7,6 +7,7 @@
 //! - OpenCode (open source coding agent)
 //! - GitHub Copilot (via copilot-language-server)
 
+use crate::acp::capabilities::AgentCapabilityInfo;
 use serde::{Deserialize, Serialize};
 use std::process::Command;
 
@@ -38,6 +39,9 @@ pub struct AgentConfig {
     pub model_env_var: Option<String>,
     /// CLI flag to pass model (e.g., "--model" for Claude)
     pub model_cli_flag: Option<String>,
+    /// What the agent reported in its last handshake (None until it has run)
+    #[serde(default)]
+    pub capabilities: Option<AgentCapabilityInfo>,
 }
 
 impl AgentConfig {
@@ -74,6 +78,7 @@ impl AgentConfig {
             default_model: default_model.to_string(),
             model_env_var: model_env_var.map(String::from),
             model_cli_flag: model_cli_flag.map(String::from),
+            capabilities: None,
         }
     }
 
@@ -99,12 +104,13 @@ impl AgentConfig {
             default_model: String::new(),
             model_env_var: None,
             model_cli_flag: None,
+            capabilities: None,
         }
     }
 }
 
 /// Check if a command exists in PATH or common install locations
-fn check_command_exists(command: &str) -> bool {
+pub fn check_command_exists(command: &str) -> bool {
     // First check PATH
     let in_path = Command::new("which")
         .arg(command)
@@ -0,0 +1,444 @@
+//! Resource limits for agent processes
+//!
+//! Agent processes and the terminals they create are registered here when
+//! they're spawned. With `resource_limits.enabled`, a background monitor
+//! samples each one's resident memory and CPU usage through sysinfo, child
+//! processes included (a terminal is `sh -c`, and agents run their own
+//! tools). Going over a soft limit emits a `worker-resource-limit` warning
+//! once per crossing. Going over a hard limit kills every process of the
+//! worker and fails it with the reason, so a runaway build or fork loop
+//! can't take the machine down.
+
+use crate::acp::commands::fail_worker;
+use crate::events::{emit, WorkerResourceLimitEvent};
+use crate::settings::load_settings;
+use crate::settings::store::ResourceLimitSettings;
+use crate::AppState;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System};
+use tauri::{AppHandle, Manager};
+use ts_rs::TS;
+
+/// How often watched processes are sampled
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum LimitResource {
+    Memory,
+    Cpu,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum LimitLevel {
+    /// Warn and carry on
+    Soft,
+    /// Kill the worker's processes and fail it
+    Hard,
+}
+
+/// Memory and CPU of a process and its children
+#[derive(Debug, Clone, Copy, Default)]
+struct Usage {
+    memory_mb: f64,
+    /// Percent of one core
+    cpu_percent: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Breach {
+    resource: LimitResource,
+    level: LimitLevel,
+    usage: f64,
+    limit: f64,
+}
+
+impl Breach {
+    fn message(&self, terminal_id: Option<&str>) -> String {
+        let subject = match terminal_id {
+            Some(id) => format!("Terminal {}", id),
+            None => "Agent process".to_string(),
+        };
+        let (what, unit) = match self.resource {
+            LimitResource::Memory => ("memory", " MB"),
+            LimitResource::Cpu => ("CPU", "%"),
+        };
+        match self.level {
+            LimitLevel::Soft => format!(
+                "{} is using {:.0}{} {}, over the soft limit of {:.0}{}",
+                subject, self.usage, unit, what, self.limit, unit
+            ),
+            LimitLevel::Hard => format!(
+                "{} was killed for using {:.0}{} {}, over the hard limit of {:.0}{}",
+                subject, self.usage, unit, what, self.limit, unit
+            ),
+        }
+    }
+}
+
+/// Which limits a process is over, and since when for CPU
+#[derive(Debug, Default)]
+struct LimitState {
+    memory_warned: bool,
+    cpu_warned: bool,
+    cpu_soft_since: Option<Instant>,
+    cpu_hard_since: Option<Instant>,
+}
+
+impl LimitState {
+    /// What a new sample breaches: a hard limit on its own, otherwise soft
+    /// limits not yet warned about since the process went over them
+    fn check(&mut self, usage: Usage, limits: &ResourceLimitSettings, now: Instant) -> Vec<Breach> {
+        let window = Duration::from_secs(limits.cpu_window_secs);
+        let over = |value: f64, limit: f64| limit > 0.0 && value > limit;
+        let sustained = |since: &mut Option<Instant>, is_over: bool| {
+            if !is_over {
+                *since = None;
+                return false;
+            }
+            now.duration_since(*since.get_or_insert(now)) >= window
+        };
+        let breach = |resource, level, usage, limit| Breach {
+            resource,
+            level,
+            usage,
+            limit,
+        };
+
+        let memory_hard = limits.memory_hard_mb as f64;
+        if over(usage.memory_mb, memory_hard) {
+            return vec![breach(LimitResource::Memory, LimitLevel::Hard, usage.memory_mb, memory_hard)];
+        }
+        let cpu_hard = limits.cpu_hard_percent as f64;
+        if sustained(&mut self.cpu_hard_since, over(usage.cpu_percent, cpu_hard)) {
+            return vec![breach(LimitResource::Cpu, LimitLevel::Hard, usage.cpu_percent, cpu_hard)];
+        }
+
+        let mut breaches = Vec::new();
+        let memory_soft = limits.memory_soft_mb as f64;
+        if !over(usage.memory_mb, memory_soft) {
+            self.memory_warned = false;
+        } else if !self.memory_warned {
+            self.memory_warned = true;
+            breaches.push(breach(LimitResource::Memory, LimitLevel::Soft, usage.memory_mb, memory_soft));
+        }
+        let cpu_soft = limits.cpu_soft_percent as f64;
+        if sustained(&mut self.cpu_soft_since, over(usage.cpu_percent, cpu_soft)) {
+            if !self.cpu_warned {
+                self.cpu_warned = true;
+                breaches.push(breach(LimitResource::Cpu, LimitLevel::Soft, usage.cpu_percent, cpu_soft));
+            }
+        } else if self.cpu_soft_since.is_none() {
+            self.cpu_warned = false;
+        }
+        breaches
+    }
+}
+
+struct Watched {
+    worker_id: String,
+    session_id: String,
+    /// None for the agent process itself
+    terminal_id: Option<String>,
+    state: LimitState,
+}
+
+/// Watched processes by pid
+static WATCHED: Lazy<Mutex<HashMap<u32, Watched>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Workers failed by a hard limit, whose own failure reports are dropped
+static ENFORCED: Lazy<Mutex<HashSet<String>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+fn watch(pid: u32, worker_id: &str, session_id: &str, terminal_id: Option<String>) {
+    WATCHED.lock().insert(
+        pid,
+        Watched {
+            worker_id: worker_id.to_string(),
+            session_id: session_id.to_string(),
+            terminal_id,
+            state: LimitState::default(),
+        },
+    );
+}
+
+/// Watch a worker's agent process
+pub fn watch_agent(pid: u32, worker_id: &str, session_id: &str) {
+    watch(pid, worker_id, session_id, None);
+}
+
+/// Watch a terminal created by a worker's agent
+pub fn watch_terminal(pid: u32, worker_id: &str, session_id: &str, terminal_id: &str) {
+    watch(pid, worker_id, session_id, Some(terminal_id.to_string()));
+}
+
+pub fn unwatch(pid: u32) {
+    WATCHED.lock().remove(&pid);
+}
+
+/// Pid of the shell running an agent-created terminal
+pub fn terminal_pid(terminal_id: &str) -> Option<u32> {
+    WATCHED
+        .lock()
+        .iter()
+        .find(|(_, w)| w.terminal_id.as_deref() == Some(terminal_id))
+        .map(|(pid, _)| *pid)
+}
+
+/// Forget a worker's processes once its agent is gone
+pub fn unwatch_worker(worker_id: &str) {
+    WATCHED.lock().retain(|_, w| w.worker_id != worker_id);
+    ENFORCED.lock().remove(worker_id);
+}
+
+/// Whether a hard limit already killed and failed this worker
+pub fn was_enforced(worker_id: &str) -> bool {
+    ENFORCED.lock().contains(worker_id)
+}
+
+/// Child pids of every process
+pub(crate) fn children_by_parent(system: &System) -> HashMap<Pid, Vec<Pid>> {
+    let mut children: HashMap<Pid, Vec<Pid>> = HashMap::new();
+    for (pid, process) in system.processes() {
+        // Threads are listed as processes on Linux; they share its memory
+        if process.thread_kind().is_some() {
+            continue;
+        }
+        if let Some(parent) = process.parent() {
+            children.entry(parent).or_default().push(*pid);
+        }
+    }
+    children
+}
+
+/// A process and all its descendants
+pub(crate) fn process_tree(root: Pid, children: &HashMap<Pid, Vec<Pid>>) -> Vec<Pid> {
+    let mut tree = vec![root];
+    let mut next = 0;
+    while next < tree.len() {
+        for child in children.get(&tree[next]).into_iter().flatten() {
+            if !tree.contains(child) {
+                tree.push(*child);
+            }
+        }
+        next += 1;
+    }
+    tree
+}
+
+fn tree_usage(system: &System, tree: &[Pid]) -> Usage {
+    tree.iter()
+        .filter_map(|pid| system.process(*pid))
+        .fold(Usage::default(), |usage, process| Usage {
+            memory_mb: usage.memory_mb + process.memory() as f64 / (1024.0 * 1024.0),
+            cpu_percent: usage.cpu_percent + process.cpu_usage() as f64,
+        })
+}
+
+/// Sample every watched process, warning about soft limits and enforcing
+/// hard ones
+fn sample(system: &mut System, limits: &ResourceLimitSettings, app: &AppHandle) {
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::All,
+        true,
+        ProcessRefreshKind::nothing().with_memory().with_cpu(),
+    );
+    let children = children_by_parent(system);
+    let now = Instant::now();
+
+    let events: Vec<WorkerResourceLimitEvent> = {
+        let mut watched = WATCHED.lock();
+        // Processes that exited on their own
+        watched.retain(|pid, _| system.process(Pid::from_u32(*pid)).is_some());
+        watched
+            .iter_mut()
+            .flat_map(|(pid, entry)| {
+                let usage = tree_usage(system, &process_tree(Pid::from_u32(*pid), &children));
+                entry
+                    .state
+                    .check(usage, limits, now)
+                    .into_iter()
+                    .map(|breach| WorkerResourceLimitEvent {
+                        session_id: entry.session_id.clone(),
+                        worker_id: entry.worker_id.clone(),
+                        terminal_id: entry.terminal_id.clone(),
+                        resource: breach.resource,
+                        level: breach.level,
+                        usage: breach.usage,
+                        limit: breach.limit,
+                        message: breach.message(entry.terminal_id.as_deref()),
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    };
+
+    for event in events {
+        eprintln!("[ACP] Worker {}: {}", event.worker_id, event.message);
+        emit(app, &event);
+        if event.level == LimitLevel::Hard {
+            enforce(system, &children, &event, app);
+        }
+    }
+}
+
+/// Kill every process of the worker and fail it with the event's reason
+fn enforce(
+    system: &System,
+    children: &HashMap<Pid, Vec<Pid>>,
+    event: &WorkerResourceLimitEvent,
+    app: &AppHandle,
+) {
+    // Marked first so the failure the dying agent causes is dropped
+    if !ENFORCED.lock().insert(event.worker_id.clone()) {
+        return;
+    }
+    let roots: Vec<u32> = WATCHED
+        .lock()
+        .iter()
+        .filter(|(_, w)| w.worker_id == event.worker_id)
+        .map(|(pid, _)| *pid)
+        .collect();
+    for root in roots {
+        for pid in process_tree(Pid::from_u32(root), children) {
+            if let Some(process) = system.process(pid) {
+                process.kill();
+            }
+        }
+    }
+
+    let state = app.state::<AppState>();
+    fail_worker(
+        &event.session_id,
+        &event.worker_id,
+        event.message.clone(),
+        app,
+        &state.orchestrator_manager,
+    );
+}
+
+/// Start the resource monitor. It idles while limits are disabled.
+pub fn start(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut system = System::new();
+        let mut interval = tokio::time::interval(SAMPLE_INTERVAL);
+        loop {
+            interval.tick().await;
+            let limits = load_settings().resource_limits;
+            if !limits.enabled || WATCHED.lock().is_empty() {
+                continue;
+            }
+            sample(&mut system, &limits, &app);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits() -> ResourceLimitSettings {
+        ResourceLimitSettings {
+            enabled: true,
+            memory_soft_mb: 1000,
+            memory_hard_mb: 2000,
+            cpu_soft_percent: 100,
+            cpu_hard_percent: 400,
+            cpu_window_secs: 10,
+        }
+    }
+
+    fn usage(memory_mb: f64, cpu_percent: f64) -> Usage {
+        Usage {
+            memory_mb,
+            cpu_percent,
+        }
+    }
+
+    #[test]
+    fn test_memory_limits() {
+        let mut state = LimitState::default();
+        let now = Instant::now();
+        assert!(state.check(usage(500.0, 0.0), &limits(), now).is_empty());
+
+        // Soft warnings are sent once per crossing
+        let breaches = state.check(usage(1500.0, 0.0), &limits(), now);
+        assert_eq!(breaches.len(), 1);
+        assert_eq!(breaches[0].level, LimitLevel::Soft);
+        assert!(state.check(usage(1600.0, 0.0), &limits(), now).is_empty());
+        assert!(state.check(usage(500.0, 0.0), &limits(), now).is_empty());
+        assert_eq!(state.check(usage(1500.0, 0.0), &limits(), now).len(), 1);
+
+        let breaches = state.check(usage(2500.0, 0.0), &limits(), now);
+        assert_eq!(
+            breaches,
+            vec![Breach {
+                resource: LimitResource::Memory,
+                level: LimitLevel::Hard,
+                usage: 2500.0,
+                limit: 2000.0,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_cpu_must_be_sustained() {
+        let mut state = LimitState::default();
+        let start = Instant::now();
+        assert!(state.check(usage(0.0, 500.0), &limits(), start).is_empty());
+        let breaches = state.check(usage(0.0, 500.0), &limits(), start + Duration::from_secs(10));
+        assert_eq!(breaches.len(), 1);
+        assert_eq!(breaches[0].resource, LimitResource::Cpu);
+        assert_eq!(breaches[0].level, LimitLevel::Hard);
+
+        // Dropping under the limit restarts the window
+        let mut state = LimitState::default();
+        state.check(usage(0.0, 500.0), &limits(), start);
+        state.check(usage(0.0, 50.0), &limits(), start + Duration::from_secs(5));
+        let breaches = state.check(usage(0.0, 200.0), &limits(), start + Duration::from_secs(12));
+        assert!(breaches.is_empty());
+    }
+
+    #[test]
+    fn test_zero_limits_are_off() {
+        let limits = ResourceLimitSettings {
+            memory_soft_mb: 0,
+            memory_hard_mb: 0,
+            ..limits()
+        };
+        let mut state = LimitState::default();
+        assert!(state.check(usage(100_000.0, 0.0), &limits, Instant::now()).is_empty());
+    }
+
+    #[test]
+    fn test_process_tree() {
+        let pid = Pid::from_u32;
+        let children = HashMap::from([
+            (pid(1), vec![pid(2), pid(3)]),
+            (pid(3), vec![pid(4)]),
+            (pid(9), vec![pid(10)]),
+        ]);
+        assert_eq!(process_tree(pid(1), &children), vec![pid(1), pid(2), pid(3), pid(4)]);
+        assert_eq!(process_tree(pid(4), &children), vec![pid(4)]);
+    }
+
+    #[test]
+    fn test_message() {
+        let breach = Breach {
+            resource: LimitResource::Memory,
+            level: LimitLevel::Hard,
+            usage: 2500.0,
+            limit: 2000.0,
+        };
+        assert_eq!(
+            breach.message(Some("term_42")),
+            "Terminal term_42 was killed for using 2500 MB memory, over the hard limit of 2000 MB"
+        );
+    }
+}
@@ -0,0 +1,112 @@
+//! Retrying transient prompt failures
+//!
+//! A prompt that fails because the provider is rate limiting, overloaded or
+//! unreachable is sent again after a backoff instead of failing the worker.
+//! Errors are classified from the agent's message text, since agents report
+//! provider errors as plain JSON-RPC errors. Anything else is permanent.
+
+use serde::Serialize;
+use std::time::Duration;
+use ts_rs::TS;
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum TransientError {
+    RateLimit,
+    Overloaded,
+    Network,
+}
+
+const RATE_LIMIT_MARKERS: &[&str] = &[
+    "rate limit",
+    "rate_limit",
+    "ratelimit",
+    "too many requests",
+    "429",
+    "quota exceeded",
+];
+
+const OVERLOADED_MARKERS: &[&str] = &[
+    "overloaded",
+    "529",
+    "503",
+    "service unavailable",
+    "server is busy",
+    "capacity",
+];
+
+const NETWORK_MARKERS: &[&str] = &[
+    "econnreset",
+    "econnrefused",
+    "etimedout",
+    "enotfound",
+    "eai_again",
+    "connection reset",
+    "connection refused",
+    "connection closed",
+    "socket hang up",
+    "fetch failed",
+    "network error",
+    "timed out",
+    "timeout",
+];
+
+/// What kind of transient failure an error message describes, if any
+pub fn classify(message: &str) -> Option<TransientError> {
+    let message = message.to_lowercase();
+    let matches = |markers: &[&str]| markers.iter().any(|m| message.contains(m));
+    if matches(RATE_LIMIT_MARKERS) {
+        Some(TransientError::RateLimit)
+    } else if matches(OVERLOADED_MARKERS) {
+        Some(TransientError::Overloaded)
+    } else if matches(NETWORK_MARKERS) {
+        Some(TransientError::Network)
+    } else {
+        None
+    }
+}
+
+/// Delay before retry number `attempt` (1-based): `base` doubled each time,
+/// capped at `max`. Rate limits start from twice the base.
+pub fn backoff(kind: TransientError, attempt: u32, base: Duration, max: Duration) -> Duration {
+    let base = match kind {
+        TransientError::RateLimit => base * 2,
+        TransientError::Overloaded | TransientError::Network => base,
+    };
+    let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+    base.saturating_mul(factor).min(max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(
+            classify("Internal error: 429 Too Many Requests"),
+            Some(TransientError::RateLimit)
+        );
+        assert_eq!(
+            classify("API Error: Overloaded"),
+            Some(TransientError::Overloaded)
+        );
+        assert_eq!(
+            classify("request to api.anthropic.com failed: ECONNRESET"),
+            Some(TransientError::Network)
+        );
+        assert_eq!(classify("Invalid API key"), None);
+        assert_eq!(classify("No active ACP session"), None);
+    }
+
+    #[test]
+    fn test_backoff() {
+        let base = Duration::from_secs(2);
+        let max = Duration::from_secs(30);
+        assert_eq!(backoff(TransientError::Network, 1, base, max), Duration::from_secs(2));
+        assert_eq!(backoff(TransientError::Network, 3, base, max), Duration::from_secs(8));
+        assert_eq!(backoff(TransientError::RateLimit, 1, base, max), Duration::from_secs(4));
+        assert_eq!(backoff(TransientError::Overloaded, 10, base, max), max);
+    }
+}
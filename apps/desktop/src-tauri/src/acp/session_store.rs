@@ -1,19 +1,38 @@
 //! Session persistence for ACP sessions
 //!
-//! Stores session data in ~/.crafter-code/sessions/{session_id}.json
+//! Stores session data in ~/.crafter-code/sessions/{session_id}.json.
+//! Timestamps are milliseconds; files from before that (in seconds) are
+//! converted as they're read. Files are encrypted when `encrypt_sessions`
+//! is on (see `secrets::at_rest`); ones that can't be decrypted or parsed
+//! are still listed, marked with the error.
 
+use crate::acp::agent_log::AgentLogLine;
+use crate::acp::criteria::SessionCriteria;
+use crate::acp::hooks::HookRun;
+use crate::orchestrator::build_results::BuildResult;
+use crate::orchestrator::tool_calls::ToolCallRecord;
+use crate::secrets::at_rest::{self, Encryption};
+use crate::time::{normalize_ms, LocalZone};
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// A message in a persisted session
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PersistedMessage {
-    pub role: String, // "user" or "assistant"
+    pub role: String, // "user", "assistant" or "thinking"
     pub content: String,
+    /// Unix timestamp (milliseconds)
     pub timestamp: i64,
 }
 
+impl PersistedMessage {
+    /// Whether this message is an agent thought/reasoning block
+    pub fn is_thinking(&self) -> bool {
+        self.role == "thinking"
+    }
+}
+
 /// A persisted session that can be saved/loaded from disk
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PersistedSession {
@@ -25,9 +44,9 @@ pub struct PersistedSession {
     pub cwd: String,
     /// Agent ID (e.g., "claude", "gemini")
     pub agent_id: String,
-    /// Unix timestamp when session was created
+    /// Unix timestamp (milliseconds) when session was created
     pub created_at: i64,
-    /// Unix timestamp when session was last updated
+    /// Unix timestamp (milliseconds) when session was last updated
     pub updated_at: i64,
     /// Conversation history
     pub messages: Vec<PersistedMessage>,
@@ -35,6 +54,134 @@ pub struct PersistedSession {
     pub mode: String,
     /// Original prompt that started the session
     pub initial_prompt: String,
+    /// Tool calls run during the session
+    #[serde(default)]
+    pub tool_calls: Vec<ToolCallRecord>,
+    /// Test/build results detected in command output
+    #[serde(default)]
+    pub build_results: Vec<BuildResult>,
+    /// Project hook scripts run during the session
+    #[serde(default)]
+    pub hook_runs: Vec<HookRun>,
+    /// Lines the agents wrote to stderr
+    #[serde(default)]
+    pub agent_log: Vec<AgentLogLine>,
+    /// Acceptance criteria checked after each prompt
+    #[serde(default)]
+    pub criteria: Option<SessionCriteria>,
+    /// Summary the session was last compacted to
+    #[serde(default)]
+    pub summary: Option<String>,
+    /// Session this one was forked from
+    #[serde(default)]
+    pub parent_session_id: Option<String>,
+    /// Number of the parent's messages copied into the fork
+    #[serde(default)]
+    pub forked_at_message: Option<usize>,
+    /// User-assigned title, tags, pin and archive state
+    #[serde(flatten)]
+    pub metadata: SessionMetadata,
+}
+
+impl PersistedSession {
+    /// Remove thought blocks from the conversation history
+    pub fn strip_thinking(&mut self) {
+        self.messages.retain(|m| !m.is_thinking());
+    }
+
+    /// Convert timestamps saved in seconds (older files) to milliseconds
+    pub fn normalize_timestamps(&mut self) {
+        self.created_at = normalize_ms(self.created_at);
+        self.updated_at = normalize_ms(self.updated_at);
+        for message in &mut self.messages {
+            message.timestamp = normalize_ms(message.timestamp);
+        }
+        for line in &mut self.agent_log {
+            line.timestamp = normalize_ms(line.timestamp);
+        }
+    }
+
+    /// The zone the session's local times are shown in: the one it was
+    /// started in, else this machine's
+    pub fn timezone(&self) -> LocalZone {
+        self.metadata
+            .timezone
+            .clone()
+            .unwrap_or_else(LocalZone::current)
+    }
+}
+
+/// User-managed labels for a persisted session
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct SessionMetadata {
+    /// Shown instead of the initial prompt when set
+    pub title: Option<String>,
+    pub tags: Vec<String>,
+    /// Listed before unpinned sessions
+    pub pinned: bool,
+    /// Hidden from the list unless asked for
+    pub archived: bool,
+    /// Language the session's agents answer in, over the global setting
+    pub language: Option<String>,
+    /// Time zone the session was started in
+    pub timezone: Option<LocalZone>,
+}
+
+/// Partial metadata change; absent fields are left as they are
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct SessionMetadataUpdate {
+    /// An empty title clears it
+    pub title: Option<String>,
+    pub tags: Option<Vec<String>>,
+    pub pinned: Option<bool>,
+    pub archived: Option<bool>,
+}
+
+impl SessionMetadata {
+    pub fn apply(&mut self, update: SessionMetadataUpdate) {
+        if let Some(title) = update.title {
+            let title = title.trim();
+            self.title = (!title.is_empty()).then(|| title.to_string());
+        }
+        if let Some(tags) = update.tags {
+            self.tags.clear();
+            for tag in tags {
+                let tag = tag.trim().to_lowercase();
+                if !tag.is_empty() && !self.tags.contains(&tag) {
+                    self.tags.push(tag);
+                }
+            }
+        }
+        if let Some(pinned) = update.pinned {
+            self.pinned = pinned;
+        }
+        if let Some(archived) = update.archived {
+            self.archived = archived;
+        }
+    }
+}
+
+/// Which sessions `list_sessions` returns
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct SessionFilter {
+    /// Sessions must carry all of these tags
+    pub tags: Vec<String>,
+    pub pinned_only: bool,
+    pub include_archived: bool,
+}
+
+impl SessionFilter {
+    pub fn matches(&self, metadata: &SessionMetadata) -> bool {
+        (self.include_archived || !metadata.archived)
+            && (!self.pinned_only || metadata.pinned)
+            && self
+                .tags
+                .iter()
+                .all(|tag| metadata.tags.contains(&tag.trim().to_lowercase()))
+    }
 }
 
 /// Summary of a persisted session for listing
@@ -48,6 +195,41 @@ pub struct PersistedSessionSummary {
     pub updated_at: i64,
     pub message_count: usize,
     pub initial_prompt: String,
+    pub parent_session_id: Option<String>,
+    #[serde(flatten)]
+    pub metadata: SessionMetadata,
+    /// Why the file can't be loaded; only `id` and `updated_at` are known then
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// The file is encrypted and couldn't be decrypted (e.g. the keychain
+    /// entry is gone)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub locked: bool,
+}
+
+impl PersistedSessionSummary {
+    /// A session file that can't be loaded
+    fn unreadable(path: &Path, error: String, locked: bool) -> Self {
+        let updated_at = fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| modified.duration_since(std::time::UNIX_EPOCH).ok())
+            .map_or(0, |age| age.as_millis() as i64);
+        Self {
+            id: path.file_stem().unwrap_or_default().to_string_lossy().to_string(),
+            acp_session_id: String::new(),
+            cwd: String::new(),
+            agent_id: String::new(),
+            created_at: updated_at,
+            updated_at,
+            message_count: 0,
+            initial_prompt: String::new(),
+            parent_session_id: None,
+            metadata: SessionMetadata::default(),
+            error: Some(error),
+            locked,
+        }
+    }
 }
 
 impl From<&PersistedSession> for PersistedSessionSummary {
@@ -61,6 +243,10 @@ impl From<&PersistedSession> for PersistedSessionSummary {
             updated_at: session.updated_at,
             message_count: session.messages.len(),
             initial_prompt: session.initial_prompt.clone(),
+            parent_session_id: session.parent_session_id.clone(),
+            metadata: session.metadata.clone(),
+            error: None,
+            locked: false,
         }
     }
 }
@@ -68,6 +254,7 @@ impl From<&PersistedSession> for PersistedSessionSummary {
 /// Manages session persistence to disk
 pub struct SessionStore {
     base_path: PathBuf,
+    encryption: Encryption,
 }
 
 impl SessionStore {
@@ -77,16 +264,28 @@ impl SessionStore {
             .ok_or_else(|| "Could not determine home directory".to_string())?
             .join(".crafter-code")
             .join("sessions");
+        Self::at(base_path, Encryption::Configured)
+    }
 
+    /// A store in `base_path` instead of the home directory
+    pub fn at(base_path: PathBuf, encryption: Encryption) -> Result<Self, String> {
         // Ensure the directory exists
         fs::create_dir_all(&base_path)
             .map_err(|e| format!("Failed to create sessions directory: {}", e))?;
 
-        Ok(Self { base_path })
+        Ok(Self {
+            base_path,
+            encryption,
+        })
+    }
+
+    /// Directory holding the session files
+    pub fn base_path(&self) -> &Path {
+        &self.base_path
     }
 
     /// Get the file path for a session
-    fn session_path(&self, session_id: &str) -> PathBuf {
+    pub fn session_path(&self, session_id: &str) -> PathBuf {
         self.base_path.join(format!("{}.json", session_id))
     }
 
@@ -95,7 +294,7 @@ impl SessionStore {
         let path = self.session_path(&session.id);
         let json = serde_json::to_string_pretty(session)
             .map_err(|e| format!("Failed to serialize session: {}", e))?;
-        fs::write(&path, json)
+        at_rest::write(&path, json.as_bytes(), self.encryption)
             .map_err(|e| format!("Failed to write session file: {}", e))?;
         eprintln!("[SessionStore] Saved session {} to {:?}", session.id, path);
         Ok(())
@@ -104,35 +303,53 @@ impl SessionStore {
     /// Load a session from disk
     pub fn load_session(&self, session_id: &str) -> Result<PersistedSession, String> {
         let path = self.session_path(session_id);
-        let json = fs::read_to_string(&path)
+        let json = at_rest::read_to_string(&path, self.encryption)
             .map_err(|e| format!("Failed to read session file: {}", e))?;
-        let session: PersistedSession = serde_json::from_str(&json)
-            .map_err(|e| format!("Failed to parse session file: {}", e))?;
-        Ok(session)
+        parse(&json).map_err(|e| format!("Failed to parse session file: {}", e))
     }
 
-    /// List all persisted sessions (returns summaries, sorted by updated_at desc)
-    pub fn list_sessions(&self) -> Vec<PersistedSessionSummary> {
+    /// List persisted sessions matching `filter` (pinned first, then by
+    /// updated_at desc)
+    pub fn list_sessions(&self, filter: &SessionFilter) -> Vec<PersistedSessionSummary> {
         let mut sessions = Vec::new();
 
         if let Ok(entries) = fs::read_dir(&self.base_path) {
             for entry in entries.flatten() {
                 let path = entry.path();
-                if path.extension().map_or(false, |ext| ext == "json") {
-                    if let Ok(json) = fs::read_to_string(&path) {
-                        if let Ok(session) = serde_json::from_str::<PersistedSession>(&json) {
-                            sessions.push(PersistedSessionSummary::from(&session));
+                if path.extension().is_some_and(|ext| ext == "json") {
+                    let summary = match self.summarize(&path) {
+                        Ok(session) => PersistedSessionSummary::from(&session),
+                        Err((error, locked)) => {
+                            PersistedSessionSummary::unreadable(&path, error, locked)
                         }
+                    };
+                    if filter.matches(&summary.metadata) {
+                        sessions.push(summary);
                     }
                 }
             }
         }
 
-        // Sort by updated_at descending (most recent first)
-        sessions.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+        // Pinned first, then most recently updated
+        sessions.sort_by(|a, b| {
+            b.metadata
+                .pinned
+                .cmp(&a.metadata.pinned)
+                .then(b.updated_at.cmp(&a.updated_at))
+        });
         sessions
     }
 
+    /// Load a listed file; fails with the error and whether the file is
+    /// locked (encrypted, and couldn't be decrypted)
+    fn summarize(&self, path: &Path) -> Result<PersistedSession, (String, bool)> {
+        let contents = fs::read(path).map_err(|e| (e.to_string(), false))?;
+        let locked = at_rest::is_sealed(&contents);
+        let plain = at_rest::open(contents, self.encryption).map_err(|e| (e, locked))?;
+        let json = String::from_utf8(plain).map_err(|e| (e.to_string(), false))?;
+        parse(&json).map_err(|e| (format!("Failed to parse session file: {}", e), false))
+    }
+
     /// Delete a session from disk
     pub fn delete_session(&self, session_id: &str) -> Result<(), String> {
         let path = self.session_path(session_id);
@@ -150,6 +367,12 @@ impl SessionStore {
     }
 }
 
+fn parse(json: &str) -> Result<PersistedSession, serde_json::Error> {
+    let mut session: PersistedSession = serde_json::from_str(json)?;
+    session.normalize_timestamps();
+    Ok(session)
+}
+
 impl Default for SessionStore {
     fn default() -> Self {
         Self::new().expect("Failed to create session store")
@@ -159,10 +382,14 @@ impl Default for SessionStore {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chacha20poly1305::aead::{KeyInit, OsRng};
+    use chacha20poly1305::XChaCha20Poly1305;
 
     #[test]
     fn test_session_store() {
-        let store = SessionStore::new().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let key = XChaCha20Poly1305::generate_key(&mut OsRng);
+        let store = SessionStore::at(dir.path().to_path_buf(), Encryption::With(key)).unwrap();
 
         let session = PersistedSession {
             id: "test_session_123".to_string(),
@@ -180,6 +407,15 @@ mod tests {
             ],
             mode: "normal".to_string(),
             initial_prompt: "Hello".to_string(),
+            tool_calls: vec![],
+            build_results: vec![],
+            hook_runs: vec![],
+            agent_log: vec![],
+            criteria: None,
+            summary: None,
+            parent_session_id: None,
+            forked_at_message: None,
+            metadata: SessionMetadata::default(),
         };
 
         // Save
@@ -189,13 +425,112 @@ mod tests {
         let loaded = store.load_session("test_session_123").unwrap();
         assert_eq!(loaded.id, session.id);
         assert_eq!(loaded.messages.len(), 1);
+        // Saved in seconds, read back in milliseconds
+        assert_eq!(loaded.created_at, 1_706_000_000_000);
+        assert_eq!(loaded.messages[0].timestamp, 1_706_000_100_000);
+
+        let on_disk = fs::read(store.session_path("test_session_123")).unwrap();
+        assert!(at_rest::is_sealed(&on_disk));
 
-        // List
-        let sessions = store.list_sessions();
-        assert!(sessions.iter().any(|s| s.id == "test_session_123"));
+        // List; files that can't be read show up with their error
+        fs::write(dir.path().join("broken.json"), "{").unwrap();
+        let other = XChaCha20Poly1305::generate_key(&mut OsRng);
+        SessionStore::at(dir.path().to_path_buf(), Encryption::With(other))
+            .unwrap()
+            .save_session(&PersistedSession {
+                id: "other_key".to_string(),
+                ..session.clone()
+            })
+            .unwrap();
+        let sessions = store.list_sessions(&SessionFilter::default());
+        assert_eq!(sessions.len(), 3);
+        let summary = |id: &str| sessions.iter().find(|s| s.id == id).unwrap();
+        assert_eq!(summary("test_session_123").error, None);
+        assert!(summary("broken").error.is_some() && !summary("broken").locked);
+        assert!(summary("other_key").error.is_some() && summary("other_key").locked);
 
         // Delete
         store.delete_session("test_session_123").unwrap();
         assert!(!store.session_exists("test_session_123"));
     }
+
+    #[test]
+    fn test_strip_thinking() {
+        let message = |role: &str| PersistedMessage {
+            role: role.to_string(),
+            content: "text".to_string(),
+            timestamp: 1706000100,
+        };
+        let mut session = PersistedSession {
+            id: "s".to_string(),
+            acp_session_id: "a".to_string(),
+            cwd: "/tmp".to_string(),
+            agent_id: "claude".to_string(),
+            created_at: 0,
+            updated_at: 0,
+            messages: vec![message("user"), message("thinking"), message("assistant")],
+            mode: "normal".to_string(),
+            initial_prompt: "text".to_string(),
+            tool_calls: vec![],
+            build_results: vec![],
+            hook_runs: vec![],
+            agent_log: vec![],
+            criteria: None,
+            summary: None,
+            parent_session_id: None,
+            forked_at_message: None,
+            metadata: SessionMetadata::default(),
+        };
+
+        session.strip_thinking();
+        assert_eq!(session.messages.len(), 2);
+        assert!(session.messages.iter().all(|m| !m.is_thinking()));
+    }
+
+    #[test]
+    fn test_metadata_update_and_filter() {
+        let mut metadata = SessionMetadata::default();
+        metadata.apply(SessionMetadataUpdate {
+            title: Some("  Auth refactor ".to_string()),
+            tags: Some(vec!["Backend".to_string(), "backend".to_string(), " ".to_string()]),
+            pinned: Some(true),
+            ..Default::default()
+        });
+        assert_eq!(metadata.title.as_deref(), Some("Auth refactor"));
+        assert_eq!(metadata.tags, vec!["backend"]);
+        assert!(metadata.pinned);
+
+        // Untouched fields stay, empty title clears
+        metadata.apply(SessionMetadataUpdate {
+            title: Some(String::new()),
+            archived: Some(true),
+            ..Default::default()
+        });
+        assert_eq!(metadata.title, None);
+        assert_eq!(metadata.tags, vec!["backend"]);
+
+        let filter = |tags: &[&str], include_archived: bool| SessionFilter {
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            pinned_only: false,
+            include_archived,
+        };
+        assert!(!filter(&[], false).matches(&metadata));
+        assert!(filter(&["Backend"], true).matches(&metadata));
+        assert!(!filter(&["backend", "ui"], true).matches(&metadata));
+    }
+
+    #[test]
+    fn test_metadata_round_trip_and_old_files() {
+        let json = r#"{"id":"s","acp_session_id":"a","cwd":"/","agent_id":"claude",
+            "created_at":0,"updated_at":0,"messages":[],"mode":"default","initial_prompt":"hi"}"#;
+        let session: PersistedSession = serde_json::from_str(json).unwrap();
+        assert_eq!(session.metadata, SessionMetadata::default());
+
+        let mut session = session;
+        session.metadata.tags = vec!["ui".to_string()];
+        session.metadata.pinned = true;
+        let value = serde_json::to_value(PersistedSessionSummary::from(&session)).unwrap();
+        assert_eq!(value["pinned"], true);
+        assert_eq!(value["tags"][0], "ui");
+    }
 }
@@ -3,7 +3,7 @@
 use crate::acp::registry::get_agent_config;
 use crate::acp::skill_loader::get_skill_directories;
 use crate::acp::skills::{Skill, SkillManager};
-use crate::acp::slash_commands::{CommandCategory, CommandRegistry, SlashCommand};
+use crate::acp::slash_commands::{BackendAction, CommandCategory, CommandRegistry, SlashCommand};
 use parking_lot::Mutex;
 use serde::Serialize;
 use std::collections::HashMap;
@@ -187,6 +187,8 @@ pub struct CommandInfo {
     pub description: String,
     pub input_hint: Option<String>,
     pub category: String,
+    /// Runs in the client instead of prompting the agent
+    pub backend: bool,
 }
 
 impl From<&SlashCommand> for CommandInfo {
@@ -196,6 +198,7 @@ impl From<&SlashCommand> for CommandInfo {
             description: cmd.description.clone(),
             input_hint: cmd.input_hint.clone(),
             category: format!("{:?}", cmd.category).to_lowercase(),
+            backend: cmd.action.is_some(),
         }
     }
 }
@@ -234,6 +237,13 @@ pub fn process_slash_command(session_id: String, input: String) -> Option<String
     reg.process_command(&input)
 }
 
+/// The backend action an input invokes in a session, if any
+pub fn find_slash_action(session_id: &str, input: &str) -> Option<(BackendAction, String)> {
+    let registry = get_command_registry(session_id);
+    let reg = registry.lock();
+    reg.find_action(input)
+}
+
 /// Check if input is a slash command
 #[tauri::command]
 pub fn is_slash_command(input: String) -> bool {
@@ -301,6 +311,7 @@ pub fn cleanup_session_features(session_id: String) {
         let mut registries = COMMAND_REGISTRIES.lock();
         registries.remove(&session_id);
     }
+    crate::pty::commands::close_session_terminals(session_id);
 }
 
 // ==================== WORKSPACE SKILLS (NO SESSION REQUIRED) ====================
@@ -0,0 +1,297 @@
+//! Backend slash commands
+//!
+//! `/cost`, `/compact`, `/retry` and `/export` don't expand into a prompt:
+//! `send_acp_prompt` recognizes them and runs them here instead of sending
+//! anything to the agent. Each one answers with a `slash-command-result`
+//! event (and as the return value of `run_slash_command`).
+
+use crate::acp::commands::{compact_worker, send_prompt};
+use crate::acp::session_store::{PersistedSession, SessionStore};
+use crate::acp::skills_commands::find_slash_action;
+use crate::acp::slash_commands::BackendAction;
+use crate::error::CommandError;
+use crate::events::{emit, SlashCommandResultEvent};
+use crate::orchestrator::session::OrchestratorSession;
+use crate::settings::load_settings;
+use crate::time::now_ms;
+use crate::AppState;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, State};
+
+/// Last prompt sent to each session (session_id -> prompt), for `/retry`
+static LAST_PROMPTS: Lazy<Mutex<HashMap<String, String>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Remember a prompt so `/retry` can send it again
+pub fn remember_prompt(session_id: &str, prompt: &str) {
+    LAST_PROMPTS
+        .lock()
+        .insert(session_id.to_string(), prompt.to_string());
+}
+
+/// Forget a session's last prompt
+pub fn clear_session(session_id: &str) {
+    LAST_PROMPTS.lock().remove(session_id);
+}
+
+fn command_name(action: BackendAction) -> &'static str {
+    match action {
+        BackendAction::Cost => "cost",
+        BackendAction::Compact => "compact",
+        BackendAction::Retry => "retry",
+        BackendAction::Export => "export",
+    }
+}
+
+/// Run a backend command and announce its result
+pub async fn run(
+    session_id: &str,
+    action: BackendAction,
+    args: &str,
+    app_handle: &AppHandle,
+    state: &AppState,
+) -> Result<String, CommandError> {
+    eprintln!(
+        "[ACP] Running /{} for session {}",
+        command_name(action),
+        session_id
+    );
+    let result = match action {
+        BackendAction::Cost => cost(session_id, state),
+        BackendAction::Compact => compact_worker(session_id, app_handle, state).await,
+        BackendAction::Retry => retry(session_id, app_handle, state).await,
+        BackendAction::Export => export(session_id, args),
+    };
+
+    let (success, output) = match &result {
+        Ok(output) => (true, output.clone()),
+        Err(e) => (false, e.message.clone()),
+    };
+    emit(
+        app_handle,
+        &SlashCommandResultEvent {
+            session_id: session_id.to_string(),
+            command: command_name(action).to_string(),
+            success,
+            output,
+        },
+    );
+    result
+}
+
+fn cost(session_id: &str, state: &AppState) -> Result<String, CommandError> {
+    let session = state
+        .orchestrator_manager
+        .lock()
+        .get_session(session_id)
+        .cloned()
+        .ok_or_else(|| CommandError::session_not_found(session_id))?;
+    Ok(cost_summary(&session))
+}
+
+fn cost_summary(session: &OrchestratorSession) -> String {
+    let mut lines = vec![
+        format!("Session cost: ${:.4}", session.total_cost),
+        format!(
+            "Tokens: {} in / {} out",
+            session.total_input_tokens, session.total_output_tokens
+        ),
+    ];
+    if session.workers.len() > 1 {
+        for worker in &session.workers {
+            lines.push(format!(
+                "  {}: ${:.4} ({} in / {} out)",
+                worker.id, worker.cost_usd, worker.input_tokens, worker.output_tokens
+            ));
+        }
+    }
+    lines.join("\n")
+}
+
+/// The prompt `/retry` sends: the last one sent this run, else the last
+/// user message on record, else the prompt the session started with
+fn last_prompt(session_id: &str, state: &AppState) -> Option<String> {
+    if let Some(prompt) = LAST_PROMPTS.lock().get(session_id) {
+        return Some(prompt.clone());
+    }
+    let persisted = SessionStore::new()
+        .and_then(|store| store.load_session(session_id))
+        .ok()
+        .and_then(|session| {
+            session
+                .messages
+                .iter()
+                .rev()
+                .find(|m| m.role == "user")
+                .map(|m| m.content.clone())
+        });
+    persisted.or_else(|| {
+        state
+            .orchestrator_manager
+            .lock()
+            .get_session(session_id)
+            .map(|session| session.prompt.clone())
+            .filter(|prompt| !prompt.is_empty())
+    })
+}
+
+async fn retry(
+    session_id: &str,
+    app_handle: &AppHandle,
+    state: &AppState,
+) -> Result<String, CommandError> {
+    let prompt = last_prompt(session_id, state)
+        .ok_or_else(|| CommandError::not_found("No prompt to retry"))?;
+    send_prompt(session_id.to_string(), prompt.clone(), app_handle.clone(), state).await?;
+    Ok(format!("Sent again: {}", prompt))
+}
+
+/// Render a session's conversation as Markdown
+pub fn transcript_markdown(session: &PersistedSession) -> String {
+    let title = session
+        .metadata
+        .title
+        .clone()
+        .or_else(|| session.initial_prompt.lines().next().map(str::to_string))
+        .filter(|title| !title.trim().is_empty())
+        .unwrap_or_else(|| session.id.clone());
+
+    let zone = session.timezone();
+    let mut out = format!(
+        "# {}\n\n- Session: {}\n- Agent: {}\n- Project: {}\n- Started: {}\n- Exported: {}\n",
+        title.trim(),
+        session.id,
+        session.agent_id,
+        session.cwd,
+        zone.format(session.created_at),
+        zone.format(now_ms())
+    );
+    if let Some(name) = &zone.name {
+        out.push_str(&format!("- Time zone: {}\n", name));
+    }
+    for message in &session.messages {
+        let heading = match message.role.as_str() {
+            "user" => "User",
+            "assistant" => "Assistant",
+            "thinking" => "Thinking",
+            other => other,
+        };
+        out.push_str(&format!("\n## {}\n\n{}\n", heading, message.content.trim_end()));
+    }
+    out
+}
+
+/// Where `/export` writes: `path` (relative to the project), else
+/// `~/.crafter-code/exports/<session_id>.md`
+fn export_path(session: &PersistedSession, path: &str) -> Result<PathBuf, CommandError> {
+    if path.is_empty() {
+        let dir = dirs::home_dir()
+            .ok_or("Could not determine home directory")?
+            .join(".crafter-code")
+            .join("exports");
+        return Ok(dir.join(format!("{}.md", session.id)));
+    }
+    let path = Path::new(path);
+    if path.file_name().is_none() {
+        return Err(CommandError::invalid_input(format!(
+            "Not a file path: {}",
+            path.display()
+        )));
+    }
+    Ok(Path::new(&session.cwd).join(path))
+}
+
+fn export(session_id: &str, path: &str) -> Result<String, CommandError> {
+    let mut session = SessionStore::new()?.load_session(session_id)?;
+    if !load_settings().export_thinking {
+        session.strip_thinking();
+    }
+    let target = export_path(&session, path)?;
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    fs::write(&target, transcript_markdown(&session))
+        .map_err(|e| format!("Failed to write {}: {}", target.display(), e))?;
+    Ok(format!(
+        "Exported {} messages to {}",
+        session.messages.len(),
+        target.display()
+    ))
+}
+
+/// Run `input` if it is a backend slash command, returning its output;
+/// `None` for anything else (which should be sent as a prompt)
+#[tauri::command]
+pub async fn run_slash_command(
+    session_id: String,
+    input: String,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<Option<String>, CommandError> {
+    let Some((action, args)) = find_slash_action(&session_id, &input) else {
+        return Ok(None);
+    };
+    run(&session_id, action, &args, &app_handle, &state)
+        .await
+        .map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acp::session_store::{PersistedMessage, SessionMetadata};
+    use crate::time::LocalZone;
+
+    fn message(role: &str, content: &str) -> PersistedMessage {
+        PersistedMessage {
+            role: role.to_string(),
+            content: content.to_string(),
+            timestamp: 0,
+        }
+    }
+
+    #[test]
+    fn test_transcript_markdown() {
+        let session = PersistedSession {
+            id: "s1".to_string(),
+            acp_session_id: "acp-1".to_string(),
+            cwd: "/work/app".to_string(),
+            agent_id: "claude".to_string(),
+            created_at: 0,
+            updated_at: 0,
+            messages: vec![
+                message("user", "Fix the build"),
+                message("assistant", "Done.\n\n"),
+            ],
+            mode: "default".to_string(),
+            initial_prompt: "Fix the build\nIt fails on CI".to_string(),
+            tool_calls: Vec::new(),
+            build_results: Vec::new(),
+            hook_runs: Vec::new(),
+            agent_log: Vec::new(),
+            criteria: None,
+            summary: None,
+            parent_session_id: None,
+            forked_at_message: None,
+            metadata: SessionMetadata {
+                timezone: Some(LocalZone::default()),
+                ..Default::default()
+            },
+        };
+        let markdown = transcript_markdown(&session);
+        assert!(markdown.starts_with("# Fix the build\n\n- Session: s1\n"));
+        assert!(markdown.contains("- Started: 1970-01-01T00:00:00+00:00\n"));
+        assert!(markdown.ends_with("\n## User\n\nFix the build\n\n## Assistant\n\nDone.\n"));
+
+        assert_eq!(
+            export_path(&session, "notes/run.md").unwrap(),
+            PathBuf::from("/work/app/notes/run.md")
+        );
+        assert!(export_path(&session, "").unwrap().ends_with("exports/s1.md"));
+    }
+}
@@ -4,6 +4,10 @@
 //! These are processed by the client before being sent to the agent.
 //!
 //! Similar to Claude Code's commands like `/commit`, `/test`, `/plan`.
+//!
+//! Most commands expand into a prompt. Commands with a `BackendAction`
+//! (`/cost`, `/compact`, `/retry`, `/export`) never reach the agent: they
+//! run in the client instead (see `slash_actions`).
 
 use serde::{Deserialize, Serialize};
 
@@ -22,6 +26,23 @@ pub struct SlashCommand {
     /// The prompt template to inject when command is invoked
     /// Use `{input}` placeholder for user input
     pub prompt_template: String,
+    /// What the client does instead of prompting, for backend commands
+    #[serde(default)]
+    pub action: Option<BackendAction>,
+}
+
+/// Something a slash command does in the client rather than in the agent
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum BackendAction {
+    /// Report the session's spend
+    Cost,
+    /// Run the compaction flow
+    Compact,
+    /// Send the last prompt again
+    Retry,
+    /// Write the transcript to a file
+    Export,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -47,6 +68,20 @@ impl SlashCommand {
             input_hint: None,
             category,
             prompt_template: prompt_template.into(),
+            action: None,
+        }
+    }
+
+    /// A command that runs `action` in the client instead of prompting
+    pub fn backend(
+        name: impl Into<String>,
+        description: impl Into<String>,
+        category: CommandCategory,
+        action: BackendAction,
+    ) -> Self {
+        Self {
+            action: Some(action),
+            ..Self::new(name, description, category, "")
         }
     }
 
@@ -210,12 +245,31 @@ Report findings with severity levels."#,
             CommandCategory::Utility,
             "Acknowledge that the context should be cleared. The client will handle the actual clearing.",
         ),
-        SlashCommand::new(
+        SlashCommand::backend(
             "compact",
-            "Summarize and compact the conversation",
+            "Summarize the conversation and continue in a fresh context",
+            CommandCategory::Utility,
+            BackendAction::Compact,
+        ),
+        SlashCommand::backend(
+            "cost",
+            "Show what this session has spent",
+            CommandCategory::Utility,
+            BackendAction::Cost,
+        ),
+        SlashCommand::backend(
+            "retry",
+            "Send the last prompt again",
             CommandCategory::Utility,
-            "Summarize the key points of our conversation so far in a concise format. This will help maintain context while reducing token usage.",
+            BackendAction::Retry,
         ),
+        SlashCommand::backend(
+            "export",
+            "Export the transcript as Markdown",
+            CommandCategory::Utility,
+            BackendAction::Export,
+        )
+        .with_input("file path (optional)"),
     ]
 }
 
@@ -265,13 +319,24 @@ impl CommandRegistry {
         self.commands.iter().find(|c| c.name == name)
     }
 
-    /// Process a slash command input, returning the expanded prompt
+    /// Process a slash command input, returning the expanded prompt.
+    /// Backend commands don't expand.
     pub fn process_command(&self, input: &str) -> Option<String> {
         let (name, args) = parse_slash_command(input)?;
         let command = self.find_command(&name)?;
+        if command.action.is_some() {
+            return None;
+        }
         Some(command.expand(&args))
     }
 
+    /// The backend action (and its arguments) an input invokes, if any
+    pub fn find_action(&self, input: &str) -> Option<(BackendAction, String)> {
+        let (name, args) = parse_slash_command(input)?;
+        let action = self.find_command(&name)?.action?;
+        Some((action, args.trim().to_string()))
+    }
+
     /// Add a custom command
     #[allow(dead_code)]
     pub fn add_command(&mut self, command: SlashCommand) {
@@ -331,4 +396,24 @@ mod tests {
         let swarm_cmds = registry.get_by_category(CommandCategory::Swarm);
         assert!(!swarm_cmds.is_empty());
     }
+
+    #[test]
+    fn test_backend_commands() {
+        let registry = CommandRegistry::new();
+
+        assert_eq!(registry.process_command("/cost"), None);
+        assert_eq!(
+            registry.find_action("/export  notes/run.md "),
+            Some((BackendAction::Export, "notes/run.md".to_string()))
+        );
+        assert_eq!(
+            registry.find_action("/retry"),
+            Some((BackendAction::Retry, String::new()))
+        );
+
+        // Prompt commands still expand and have no action
+        assert!(registry.process_command("/tasks").is_some());
+        assert_eq!(registry.find_action("/tasks"), None);
+        assert_eq!(registry.find_action("compact"), None);
+    }
 }
@@ -0,0 +1,257 @@
+//! Context staged for a session's next prompt
+//!
+//! The user picks files (from the explorer) or line ranges (from search
+//! hits) and stages them with `stage_context`. The worker takes the staged
+//! items when it sends the next prompt: agents that accept embedded context
+//! get each one as a resource block, others get it inlined after the prompt
+//! the way `@path` mentions are. Either way the basket is empty afterwards.
+
+use agent_client_protocol::{
+    ContentBlock, EmbeddedResource, EmbeddedResourceResource, TextContent, TextResourceContents,
+};
+use crate::error::CommandError;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Largest file (or part of one) attached per item
+const MAX_ITEM_BYTES: usize = 100 * 1024;
+
+/// Items waiting for each session's next prompt (session_id -> items)
+static STAGED: Lazy<Mutex<HashMap<String, Vec<ContextItem>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// A file, or some of its lines, to send with the next prompt
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ContextItem {
+    /// Absolute, or relative to the session's project
+    pub path: String,
+    /// First line to send (1-based); the whole file when neither bound is set
+    #[serde(default)]
+    pub start_line: Option<u64>,
+    /// Last line to send (inclusive)
+    #[serde(default)]
+    pub end_line: Option<u64>,
+    /// Sent instead of the file's contents (text extracted from a PDF, a
+    /// table preview)
+    #[serde(default)]
+    pub text: Option<String>,
+}
+
+impl ContextItem {
+    fn validate(&self) -> Result<(), CommandError> {
+        if self.path.trim().is_empty() {
+            return Err(CommandError::invalid_input("Context item without a path"));
+        }
+        match (self.start_line, self.end_line) {
+            (Some(0), _) | (_, Some(0)) => Err(CommandError::invalid_input(
+                "Line numbers start at 1",
+            )),
+            (Some(start), Some(end)) if start > end => Err(CommandError::invalid_input(format!(
+                "Line range {}-{} is backwards",
+                start, end
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// `#L3-L9` for a line range, empty for a whole file
+    fn fragment(&self) -> String {
+        match (self.start_line, self.end_line) {
+            (None, None) => String::new(),
+            (start, Some(end)) => format!("#L{}-L{}", start.unwrap_or(1), end),
+            (Some(start), None) => format!("#L{}-", start),
+        }
+    }
+
+    /// The item's text, or None if the file can't be read as text
+    fn read(&self, root: &Path) -> Option<(PathBuf, String)> {
+        let path = root.join(&self.path);
+        let contents = match &self.text {
+            Some(text) => text.clone(),
+            None => std::fs::read_to_string(&path).ok()?,
+        };
+        let mut text = match (self.start_line, self.end_line) {
+            (None, None) => contents,
+            (start, end) => {
+                let start = start.unwrap_or(1) as usize;
+                let count = end.map_or(usize::MAX, |end| end as usize + 1 - start);
+                contents
+                    .lines()
+                    .skip(start - 1)
+                    .take(count)
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            }
+        };
+        if text.len() > MAX_ITEM_BYTES {
+            let mut cut = MAX_ITEM_BYTES;
+            while !text.is_char_boundary(cut) {
+                cut -= 1;
+            }
+            text.truncate(cut);
+        }
+        Some((path, text))
+    }
+}
+
+/// Take a session's staged items (leaving its basket empty)
+pub fn take(session_id: &str) -> Vec<ContextItem> {
+    STAGED.lock().remove(session_id).unwrap_or_default()
+}
+
+/// Forget a session's staged items
+pub fn clear_session(session_id: &str) {
+    STAGED.lock().remove(session_id);
+}
+
+/// Add the session's staged items to a prompt and empty its basket:
+/// resource blocks if the agent takes embedded context, otherwise
+/// `<file>` blocks appended to the prompt text. Items that can't be read
+/// are skipped.
+pub fn attach(session_id: &str, cwd: &str, embedded: bool, content: &mut Vec<ContentBlock>) {
+    let items = take(session_id);
+    if items.is_empty() {
+        return;
+    }
+    let root = Path::new(cwd);
+    let mut inlined = String::new();
+    for item in &items {
+        let Some((path, text)) = item.read(root) else {
+            eprintln!("[ACP] Skipping staged context {}: not readable", item.path);
+            continue;
+        };
+        if embedded {
+            let uri = format!("file://{}{}", path.display(), item.fragment());
+            content.push(ContentBlock::Resource(EmbeddedResource::new(
+                EmbeddedResourceResource::TextResourceContents(TextResourceContents::new(
+                    text, uri,
+                )),
+            )));
+        } else {
+            inlined.push_str(&format!(
+                "\n\n<file path=\"{}{}\">\n{}\n</file>",
+                item.path,
+                item.fragment(),
+                text.trim_end()
+            ));
+        }
+    }
+    if inlined.is_empty() {
+        return;
+    }
+    match content.first_mut() {
+        Some(ContentBlock::Text(prompt)) => prompt.text.push_str(&inlined),
+        _ => content.insert(0, ContentBlock::Text(TextContent::new(inlined.trim_start()))),
+    }
+}
+
+/// Stage files or line ranges for the session's next prompt, returning
+/// everything staged so far. Items already staged aren't added twice.
+#[tauri::command]
+pub fn stage_context(
+    session_id: String,
+    items: Vec<ContextItem>,
+) -> Result<Vec<ContextItem>, CommandError> {
+    for item in &items {
+        item.validate()?;
+    }
+    let mut staged = STAGED.lock();
+    let basket = staged.entry(session_id).or_default();
+    for item in items {
+        if !basket.contains(&item) {
+            basket.push(item);
+        }
+    }
+    Ok(basket.clone())
+}
+
+/// Items waiting to go out with the session's next prompt
+#[tauri::command]
+pub fn get_staged_context(session_id: String) -> Vec<ContextItem> {
+    STAGED
+        .lock()
+        .get(&session_id)
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// Unstage some items (all of them if `items` is omitted), returning what's
+/// left
+#[tauri::command]
+pub fn unstage_context(session_id: String, items: Option<Vec<ContextItem>>) -> Vec<ContextItem> {
+    let mut staged = STAGED.lock();
+    let Some(items) = items else {
+        staged.remove(&session_id);
+        return Vec::new();
+    };
+    let Some(basket) = staged.get_mut(&session_id) else {
+        return Vec::new();
+    };
+    basket.retain(|item| !items.contains(item));
+    let left = basket.clone();
+    if left.is_empty() {
+        staged.remove(&session_id);
+    }
+    left
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(path: &str, start_line: Option<u64>, end_line: Option<u64>) -> ContextItem {
+        ContextItem {
+            path: path.to_string(),
+            start_line,
+            end_line,
+            text: None,
+        }
+    }
+
+    #[test]
+    fn test_stage_and_attach() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("lib.rs"), "one\ntwo\nthree\nfour\n").unwrap();
+        let cwd = dir.path().to_string_lossy().to_string();
+        let session = "staged-context-test";
+
+        assert!(stage_context(session.to_string(), vec![item("lib.rs", Some(3), Some(2))]).is_err());
+        stage_context(session.to_string(), vec![item("lib.rs", Some(2), Some(3))]).unwrap();
+        let staged = stage_context(
+            session.to_string(),
+            vec![item("lib.rs", Some(2), Some(3)), item("missing.rs", None, None)],
+        )
+        .unwrap();
+        assert_eq!(staged.len(), 2);
+
+        let mut content = vec![ContentBlock::Text(TextContent::new("Explain"))];
+        attach(session, &cwd, false, &mut content);
+        assert_eq!(content.len(), 1);
+        let ContentBlock::Text(prompt) = &content[0] else {
+            panic!("expected a text block");
+        };
+        assert_eq!(
+            prompt.text,
+            "Explain\n\n<file path=\"lib.rs#L2-L3\">\ntwo\nthree\n</file>"
+        );
+        // Sent once, then gone
+        assert!(get_staged_context(session.to_string()).is_empty());
+
+        stage_context(session.to_string(), vec![item("lib.rs", None, None)]).unwrap();
+        let mut content = vec![ContentBlock::Text(TextContent::new("Explain"))];
+        attach(session, &cwd, true, &mut content);
+        assert_eq!(content.len(), 2);
+        assert!(matches!(content[1], ContentBlock::Resource(_)));
+
+        stage_context(session.to_string(), vec![item("a", None, None), item("b", None, None)])
+            .unwrap();
+        assert_eq!(
+            unstage_context(session.to_string(), Some(vec![item("a", None, None)])),
+            vec![item("b", None, None)]
+        );
+        assert!(unstage_context(session.to_string(), None).is_empty());
+    }
+}
@@ -0,0 +1,168 @@
+//! Streaming throughput metrics
+//!
+//! Tracks timing of agent message deltas so the UI can show generation
+//! speed (chars/sec), elapsed time and chunk counts, and spot slowdowns.
+
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+use ts_rs::TS;
+
+/// Window used for the rolling throughput calculation
+const ROLLING_WINDOW: Duration = Duration::from_secs(3);
+
+/// Minimum interval between `worker-progress` emissions
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Snapshot of streaming progress sent to the frontend
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct StreamProgress {
+    #[ts(type = "number")]
+    pub elapsed_ms: u64,
+    #[ts(type = "number")]
+    pub chunk_count: u64,
+    #[ts(type = "number")]
+    pub total_chars: u64,
+    /// Throughput over the last few seconds
+    pub chars_per_sec: f64,
+    /// Throughput since the first delta
+    pub avg_chars_per_sec: f64,
+    /// Time since the most recent delta (useful to detect stalls)
+    #[ts(type = "number")]
+    pub idle_ms: u64,
+}
+
+/// Per-prompt delta timing state
+#[derive(Debug, Default)]
+pub struct StreamMetrics {
+    started_at: Option<Instant>,
+    last_chunk_at: Option<Instant>,
+    last_emit_at: Option<Instant>,
+    window: VecDeque<(Instant, u64)>,
+    chunk_count: u64,
+    total_chars: u64,
+}
+
+impl StreamMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start tracking a new prompt
+    pub fn reset(&mut self, now: Instant) {
+        *self = Self {
+            started_at: Some(now),
+            ..Self::default()
+        };
+    }
+
+    /// Record a delta. Returns true when a progress event should be emitted.
+    pub fn record(&mut self, now: Instant, chars: u64) -> bool {
+        if self.started_at.is_none() {
+            self.started_at = Some(now);
+        }
+        self.chunk_count += 1;
+        self.total_chars += chars;
+        self.last_chunk_at = Some(now);
+        self.window.push_back((now, chars));
+        while let Some((ts, _)) = self.window.front() {
+            if now.duration_since(*ts) > ROLLING_WINDOW {
+                self.window.pop_front();
+            } else {
+                break;
+            }
+        }
+
+        let due = self
+            .last_emit_at
+            .map(|last| now.duration_since(last) >= PROGRESS_INTERVAL)
+            .unwrap_or(true);
+        if due {
+            self.last_emit_at = Some(now);
+        }
+        due
+    }
+
+    pub fn snapshot(&self, now: Instant) -> StreamProgress {
+        let elapsed = self
+            .started_at
+            .map(|s| now.duration_since(s))
+            .unwrap_or_default();
+
+        let window_chars: u64 = self.window.iter().map(|(_, c)| c).sum();
+        let window_span = self
+            .window
+            .front()
+            .map(|(ts, _)| now.duration_since(*ts).min(ROLLING_WINDOW))
+            .unwrap_or_default();
+
+        StreamProgress {
+            elapsed_ms: elapsed.as_millis() as u64,
+            chunk_count: self.chunk_count,
+            total_chars: self.total_chars,
+            chars_per_sec: rate(window_chars, window_span),
+            avg_chars_per_sec: rate(self.total_chars, elapsed),
+            idle_ms: self
+                .last_chunk_at
+                .map(|t| now.duration_since(t).as_millis() as u64)
+                .unwrap_or(0),
+        }
+    }
+}
+
+fn rate(chars: u64, span: Duration) -> f64 {
+    let secs = span.as_secs_f64();
+    if secs <= 0.0 {
+        0.0
+    } else {
+        chars as f64 / secs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_throttles_progress() {
+        let start = Instant::now();
+        let mut metrics = StreamMetrics::new();
+        metrics.reset(start);
+
+        assert!(metrics.record(start, 10));
+        assert!(!metrics.record(start + Duration::from_millis(100), 10));
+        assert!(metrics.record(start + Duration::from_millis(600), 10));
+        assert_eq!(metrics.snapshot(start + Duration::from_millis(600)).chunk_count, 3);
+    }
+
+    #[test]
+    fn test_snapshot_rates() {
+        let start = Instant::now();
+        let mut metrics = StreamMetrics::new();
+        metrics.reset(start);
+        metrics.record(start + Duration::from_secs(1), 100);
+        metrics.record(start + Duration::from_secs(2), 100);
+
+        let snap = metrics.snapshot(start + Duration::from_secs(2));
+        assert_eq!(snap.total_chars, 200);
+        assert_eq!(snap.elapsed_ms, 2000);
+        assert!((snap.avg_chars_per_sec - 100.0).abs() < f64::EPSILON);
+        assert!((snap.chars_per_sec - 200.0).abs() < f64::EPSILON);
+        assert_eq!(snap.idle_ms, 0);
+    }
+
+    #[test]
+    fn test_rolling_window_drops_old_chunks() {
+        let start = Instant::now();
+        let mut metrics = StreamMetrics::new();
+        metrics.reset(start);
+        metrics.record(start, 1000);
+        metrics.record(start + Duration::from_secs(10), 30);
+
+        let snap = metrics.snapshot(start + Duration::from_secs(11));
+        assert_eq!(snap.total_chars, 1030);
+        assert!((snap.chars_per_sec - 30.0).abs() < f64::EPSILON);
+        assert_eq!(snap.idle_ms, 1000);
+    }
+}
@@ -3,20 +3,53 @@
 //! Agents communicate with the coordination system via "swarm" commands
 //! that get intercepted before being executed as real bash commands.
 
-use crate::inbox::message::MessageType;
+use crate::acp::language::{prompt_strings, PromptLanguage};
+use crate::inbox::message::{MessageType, WorkerInfo};
 use crate::inbox::InboxManager;
+use crate::prd::types::StoryStatus;
+use crate::prd::PrdManager;
+use crate::pty::service;
 use crate::tasks::task::{TaskStatus, TaskUpdate};
 use crate::tasks::TaskManager;
 use std::sync::Arc;
+use tauri::AppHandle;
+
+/// Output lines `swarm service logs` shows by default
+const SERVICE_LOG_LINES: usize = 50;
 
 /// Categories of swarm commands
 #[derive(Debug, Clone, PartialEq)]
 pub enum SwarmCategory {
     Task,
     Inbox,
-    Team, // Future: team management
+    Team, // Who is in the session and what they're good at
+    Prd,  // PRD leader: session progress and priorities
+    Service, // Long-running commands kept up for the session
+    Help,
 }
 
+/// Commands listed by `swarm help`, described by `PromptStrings::help_commands`
+pub const HELP_COMMANDS: [&str; 18] = [
+    "swarm task list",
+    "swarm task get <id>",
+    "swarm task claim",
+    "swarm task create \"Subject\" \"Description\"",
+    "swarm task update <id> <status>",
+    "swarm task delete <id>",
+    "swarm inbox read [--unread]",
+    "swarm inbox write <worker-id> \"message\"",
+    "swarm inbox broadcast \"message\"",
+    "swarm inbox workers",
+    "swarm inbox count",
+    "swarm inbox mark-read",
+    "swarm team list",
+    "swarm team find <skill>",
+    "swarm team declare <skill>...",
+    "swarm prd status|story <id>|prioritize <id>...",
+    "swarm service start <name> [\"command\"]|stop|restart|logs <name>|list",
+    "swarm help",
+];
+
 /// Parsed swarm command
 #[derive(Debug, Clone)]
 pub struct SwarmCommand {
@@ -70,6 +103,10 @@ impl SwarmResult {
 /// - `swarm task create "Subject" "Description"`
 /// - `swarm inbox read`
 /// - `swarm inbox write worker-2 "Hello"`
+/// - `swarm team find rust`
+/// - `swarm prd story s3`
+/// - `swarm service start web "pnpm dev"`
+/// - `swarm help`
 pub fn parse_swarm_command(command: &str) -> Option<SwarmCommand> {
     let trimmed = command.trim();
 
@@ -89,6 +126,15 @@ pub fn parse_swarm_command(command: &str) -> Option<SwarmCommand> {
         "task" => SwarmCategory::Task,
         "inbox" => SwarmCategory::Inbox,
         "team" => SwarmCategory::Team,
+        "prd" => SwarmCategory::Prd,
+        "service" => SwarmCategory::Service,
+        "help" => {
+            return Some(SwarmCommand {
+                category: SwarmCategory::Help,
+                action: "help".to_string(),
+                args: tokens.into_iter().skip(1).collect(),
+            })
+        }
         _ => return None,
     };
 
@@ -153,7 +199,251 @@ pub fn execute_swarm_command(
     match cmd.category {
         SwarmCategory::Task => execute_task_command(cmd, task_manager, worker_id),
         SwarmCategory::Inbox => execute_inbox_command(cmd, inbox_manager, worker_id),
-        SwarmCategory::Team => SwarmResult::error("Team commands not yet implemented".to_string()),
+        SwarmCategory::Team => execute_team_command(cmd, inbox_manager, worker_id),
+        SwarmCategory::Prd => SwarmResult::error("Not leading a PRD session".to_string()),
+        SwarmCategory::Service => SwarmResult::error("Services need the desktop app".to_string()),
+        SwarmCategory::Help => swarm_help(None),
+    }
+}
+
+/// List the swarm commands, described in `language` when prompts are
+/// localized
+pub fn swarm_help(language: Option<&PromptLanguage>) -> SwarmResult {
+    let strings = prompt_strings(language);
+    let width = HELP_COMMANDS.iter().map(|c| c.len()).max().unwrap_or(0);
+    let mut output = format!("{}\n", strings.help_title);
+    for (command, description) in HELP_COMMANDS.iter().zip(strings.help_commands) {
+        output.push_str(&format!("  {:<width$}  {}\n", command, description, width = width));
+    }
+    SwarmResult::success(output, None)
+}
+
+/// Execute PRD commands for the leader of a PRD session
+pub fn execute_prd_command(
+    cmd: &SwarmCommand,
+    prd_manager: &Arc<PrdManager>,
+    session_id: &str,
+) -> SwarmResult {
+    let Some(session) = prd_manager.get_session(session_id) else {
+        return SwarmResult::error(format!("PRD session '{}' not found", session_id));
+    };
+
+    match cmd.action.as_str() {
+        "status" => {
+            let stories: Vec<_> = session
+                .prd
+                .stories
+                .iter()
+                .filter_map(|story| {
+                    let progress = session.story_progress.get(&story.id)?;
+                    Some(serde_json::json!({
+                        "id": story.id,
+                        "title": story.title,
+                        "status": progress.status,
+                        "iteration": progress.iteration,
+                        "maxIterations": progress.max_iterations,
+                        "workerId": progress.worker_id,
+                        "dependencies": story.dependencies,
+                        "criteriaPassed": progress.criteria_status.iter().filter(|c| c.passed).count(),
+                        "criteriaTotal": progress.criteria_status.len(),
+                        "error": progress.error,
+                        "skipReason": progress.skip_reason,
+                    }))
+                })
+                .collect();
+            let done = session
+                .story_progress
+                .values()
+                .filter(|p| p.status == StoryStatus::Completed)
+                .count();
+
+            SwarmResult::success(
+                format!(
+                    "{}: {:?}, {}/{} stories completed",
+                    session.prd.title,
+                    session.status,
+                    done,
+                    stories.len()
+                ),
+                Some(serde_json::json!({
+                    "status": session.status,
+                    "totalCost": session.total_cost,
+                    "stories": stories,
+                })),
+            )
+        }
+
+        "story" => {
+            let id = cmd.args.first().map(|s| s.as_str()).unwrap_or("");
+            if id.is_empty() {
+                return SwarmResult::error("Usage: swarm prd story <id>".to_string());
+            }
+            let (Some(story), Some(progress)) = (
+                session.prd.stories.iter().find(|s| s.id == id),
+                session.story_progress.get(id),
+            ) else {
+                return SwarmResult::error(format!("Story '{}' not found", id));
+            };
+
+            let criteria: Vec<_> = story
+                .acceptance_criteria
+                .iter()
+                .zip(&progress.criteria_status)
+                .map(|(criterion, status)| {
+                    serde_json::json!({
+                        "criterion": criterion,
+                        "passed": status.passed,
+                        "error": status.error,
+                        "lastChecked": status.last_checked,
+                    })
+                })
+                .collect();
+
+            SwarmResult::success(
+                format!(
+                    "Story {}: {:?}, iteration {}/{}",
+                    story.id, progress.status, progress.iteration, progress.max_iterations
+                ),
+                Some(serde_json::json!({
+                    "story": story,
+                    "status": progress.status,
+                    "iteration": progress.iteration,
+                    "workerId": progress.worker_id,
+                    "error": progress.error,
+                    "skipReason": progress.skip_reason,
+                    "criteria": criteria,
+                    "guardrails": progress.guardrails,
+                })),
+            )
+        }
+
+        "prioritize" => {
+            if cmd.args.is_empty() {
+                return SwarmResult::error(
+                    "Usage: swarm prd prioritize <id> [<id>...]".to_string(),
+                );
+            }
+
+            match prd_manager.prioritize_stories(session_id, &cmd.args) {
+                Ok(order) => SwarmResult::success(
+                    format!("Story order: {}", order.join(", ")),
+                    Some(serde_json::json!({ "order": order })),
+                ),
+                Err(e) => SwarmResult::error(e),
+            }
+        }
+
+        _ => SwarmResult::error(format!(
+            "Unknown prd action '{}'. Available: status, story, prioritize",
+            cmd.action
+        )),
+    }
+}
+
+/// Execute service commands: start, stop and check on a session's
+/// long-running commands
+pub fn execute_service_command(
+    cmd: &SwarmCommand,
+    app: &AppHandle,
+    session_id: &str,
+    project_dir: Option<&str>,
+) -> SwarmResult {
+    let named = |usage: &str| match cmd.args.first() {
+        Some(name) => service::find(session_id, name)
+            .ok_or_else(|| format!("No service '{}' in this session", name)),
+        None => Err(format!("Usage: swarm service {} <name>", usage)),
+    };
+
+    match cmd.action.as_str() {
+        "start" => {
+            let Some(name) = cmd.args.first() else {
+                return SwarmResult::error(
+                    "Usage: swarm service start <name> [\"command\"]".to_string(),
+                );
+            };
+            let Some(project_dir) = project_dir else {
+                return SwarmResult::error("Session has no project directory".to_string());
+            };
+            let command = (cmd.args.len() > 1).then(|| cmd.args[1..].join(" "));
+            match service::resolve_spec(project_dir, name, command)
+                .and_then(|spec| service::start(app, session_id, name, spec, project_dir))
+            {
+                Ok(info) => SwarmResult::success(
+                    format!(
+                        "Service {} ({:?}): {}. Check it with `swarm service logs {}`",
+                        info.name, info.status, info.command, info.name
+                    ),
+                    Some(serde_json::json!(info)),
+                ),
+                Err(e) => SwarmResult::error(e),
+            }
+        }
+
+        "stop" => match named("stop").and_then(|info| service::stop(&info.id)) {
+            Ok(info) => SwarmResult::success(
+                format!("Stopping service {}", info.name),
+                Some(serde_json::json!(info)),
+            ),
+            Err(e) => SwarmResult::error(e),
+        },
+
+        "restart" => match named("restart").and_then(|info| service::restart(app, &info.id)) {
+            Ok(info) => SwarmResult::success(
+                format!("Restarting service {}", info.name),
+                Some(serde_json::json!(info)),
+            ),
+            Err(e) => SwarmResult::error(e),
+        },
+
+        "logs" => {
+            let output = match named("logs") {
+                Ok(info) => service::output(&info.id),
+                Err(e) => return SwarmResult::error(e),
+            };
+            let Some(output) = output else {
+                return SwarmResult::error("Service has stopped".to_string());
+            };
+            let lines = cmd
+                .args
+                .get(1)
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(SERVICE_LOG_LINES);
+            let all: Vec<&str> = output.output.lines().collect();
+            let tail = all[all.len().saturating_sub(lines)..].join("\n");
+            SwarmResult::success(
+                format!(
+                    "Service {} ({:?}, {} restarts)\n{}",
+                    output.info.name, output.info.status, output.info.restarts, tail
+                ),
+                Some(serde_json::json!(output.info)),
+            )
+        }
+
+        "list" | "status" => {
+            let services = service::list(Some(session_id));
+            let lines: Vec<String> = services
+                .iter()
+                .map(|info| {
+                    let mut line = format!("- {}: {:?}, {}", info.name, info.status, info.command);
+                    if info.restarts > 0 {
+                        line.push_str(&format!(" ({} restarts)", info.restarts));
+                    }
+                    if let Some(code) = info.last_exit_code {
+                        line.push_str(&format!(", last exit code {}", code));
+                    }
+                    line
+                })
+                .collect();
+            SwarmResult::success(
+                format!("{} services\n{}", services.len(), lines.join("\n")),
+                Some(serde_json::json!(services)),
+            )
+        }
+
+        _ => SwarmResult::error(format!(
+            "Unknown service action '{}'. Available: start, stop, restart, logs, list",
+            cmd.action
+        )),
     }
 }
 
@@ -368,6 +658,91 @@ fn execute_inbox_command(
     }
 }
 
+/// Execute team-related swarm commands
+fn execute_team_command(
+    cmd: &SwarmCommand,
+    inbox_manager: &Arc<InboxManager>,
+    worker_id: &str,
+) -> SwarmResult {
+    let describe = |workers: &[WorkerInfo]| {
+        workers
+            .iter()
+            .map(|w| {
+                let status = format!("{:?}", w.status).to_lowercase();
+                let mut line = format!("- {} ({}, {}", w.worker_id, w.role, status);
+                if let Some(agent) = &w.agent_id {
+                    line.push_str(&format!(", {}", agent));
+                }
+                if let Some(model) = &w.model {
+                    line.push_str(&format!(" {}", model));
+                }
+                line.push(')');
+                if !w.specialties.is_empty() {
+                    line.push_str(&format!(": {}", w.specialties.join(", ")));
+                }
+                line
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    match cmd.action.as_str() {
+        "list" => {
+            let workers = inbox_manager.get_worker_infos();
+            SwarmResult::success(
+                format!("{} workers\n{}", workers.len(), describe(&workers)),
+                Some(serde_json::json!(workers)),
+            )
+        }
+
+        "find" => {
+            if cmd.args.is_empty() {
+                return SwarmResult::error("Usage: swarm team find <skill>".to_string());
+            }
+            let skill = cmd.args.join(" ");
+            let workers = inbox_manager.find_workers(&skill, Some(worker_id));
+            let output = match workers.first() {
+                Some(best) => format!(
+                    "Best worker for '{}': {}\n{}",
+                    skill,
+                    best.worker_id,
+                    describe(&workers)
+                ),
+                None => format!("No worker declared '{}'", skill),
+            };
+            SwarmResult::success(output, Some(serde_json::json!(workers)))
+        }
+
+        "declare" => {
+            if cmd.args.is_empty() {
+                return SwarmResult::error(
+                    "Usage: swarm team declare <skill> [<skill>...]".to_string(),
+                );
+            }
+            // "rust, react" and rust react both work
+            let specialties: Vec<String> = cmd
+                .args
+                .iter()
+                .flat_map(|arg| arg.split(','))
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect();
+            if !inbox_manager.declare_specialties(worker_id, specialties.clone()) {
+                return SwarmResult::error(format!("Worker '{}' is not registered", worker_id));
+            }
+            SwarmResult::success(
+                format!("Declared: {}", specialties.join(", ")),
+                Some(serde_json::json!(specialties)),
+            )
+        }
+
+        _ => SwarmResult::error(format!(
+            "Unknown team action '{}'. Available: list, find, declare",
+            cmd.action
+        )),
+    }
+}
+
 /// Check if a command string is a swarm command
 pub fn is_swarm_command(command: &str) -> bool {
     command.trim().starts_with("swarm ")
@@ -397,11 +772,67 @@ mod tests {
         assert_eq!(cmd.action, "write");
         assert_eq!(cmd.args, vec!["worker-2", "Hello there"]);
 
+        // PRD story
+        let cmd = parse_swarm_command("swarm prd story s3").unwrap();
+        assert_eq!(cmd.category, SwarmCategory::Prd);
+        assert_eq!(cmd.action, "story");
+        assert_eq!(cmd.args, vec!["s3"]);
+
+        // Service start with a quoted command
+        let cmd = parse_swarm_command("swarm service start web \"pnpm dev\"").unwrap();
+        assert_eq!(cmd.category, SwarmCategory::Service);
+        assert_eq!(cmd.action, "start");
+        assert_eq!(cmd.args, vec!["web", "pnpm dev"]);
+
+        // Help
+        let cmd = parse_swarm_command("swarm help").unwrap();
+        assert_eq!(cmd.category, SwarmCategory::Help);
+
         // Not a swarm command
         assert!(parse_swarm_command("ls -la").is_none());
         assert!(parse_swarm_command("echo swarm").is_none());
     }
 
+    #[test]
+    fn test_swarm_help() {
+        let output = swarm_help(None).output;
+        assert!(output.starts_with("Swarm commands:"));
+        assert!(output.contains("swarm task claim"));
+        assert!(output.contains("Claim the next available task"));
+
+        let language = PromptLanguage {
+            respond_in: "pt-BR".to_string(),
+            localize: true,
+        };
+        let output = swarm_help(Some(&language)).output;
+        assert!(output.starts_with("Comandos do swarm:"));
+        assert!(output.contains("swarm task claim"));
+    }
+
+    #[test]
+    fn test_team_commands() {
+        let tasks = Arc::new(TaskManager::new("swarm-team-test".to_string()));
+        let inbox = Arc::new(InboxManager::new("swarm-team-test".to_string()));
+        inbox.register_worker("worker-1");
+        inbox.register_worker("worker-2");
+        let run = |command: &str, worker_id: &str| {
+            execute_swarm_command(&parse_swarm_command(command).unwrap(), &tasks, &inbox, worker_id)
+        };
+
+        let result = run("swarm team declare \"rust, sql\" testing", "worker-2");
+        assert!(result.success);
+        assert_eq!(result.output, "Declared: rust, sql, testing");
+
+        let result = run("swarm team find rust", "worker-1");
+        assert!(result.output.starts_with("Best worker for 'rust': worker-2\n"));
+        assert!(result.output.contains("- worker-2 (worker, pending): rust, sql, testing"));
+        // Not yourself
+        assert_eq!(run("swarm team find rust", "worker-2").output, "No worker declared 'rust'");
+
+        assert!(run("swarm team list", "worker-1").output.starts_with("2 workers\n"));
+        assert!(!run("swarm team find", "worker-1").success);
+    }
+
     #[test]
     fn test_parse_shell_tokens() {
         let tokens = parse_shell_tokens("task list");
@@ -0,0 +1,176 @@
+//! Automatic session titles
+//!
+//! After a session's first prompt completes, a short title is generated with
+//! a cheap model call (when an Anthropic API key is available) or a heuristic
+//! over the prompt, stored as the session's title unless the user already set
+//! one, and announced with `session-title-updated`.
+
+use crate::acp::session_store::SessionStore;
+use crate::claude::pricing::Model;
+use crate::claude::{ClaudeClient, Message};
+use crate::events::{emit, SessionTitleUpdatedEvent};
+use crate::stats::{record_usage, UsageRecord};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use tauri::AppHandle;
+
+const MAX_TITLE_WORDS: usize = 8;
+
+/// How much of the conversation the model sees
+const MAX_CONTEXT_CHARS: usize = 2000;
+
+const TITLE_SYSTEM_PROMPT: &str = "You name coding sessions. Reply with a 5-8 word title \
+describing the task in the conversation. No quotes, no trailing punctuation.";
+
+/// Titles for sessions the frontend hasn't persisted yet, applied when it does
+static PENDING_TITLES: Lazy<Mutex<HashMap<String, String>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Take the title generated for a session that wasn't persisted at the time
+pub fn take_pending_title(session_id: &str) -> Option<String> {
+    PENDING_TITLES.lock().remove(session_id)
+}
+
+/// First line of the prompt, cut to a handful of words
+fn heuristic_title(prompt: &str) -> String {
+    let line = prompt
+        .lines()
+        .map(|l| l.trim_start_matches(['#', '>', '-', '*', ' ']).trim())
+        .find(|l| !l.is_empty())
+        .unwrap_or_default();
+    let words: Vec<&str> = line.split_whitespace().take(MAX_TITLE_WORDS).collect();
+    let title = words
+        .join(" ")
+        .trim_end_matches(|c: char| c.is_ascii_punctuation())
+        .to_string();
+
+    let mut chars = title.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => "Untitled session".to_string(),
+    }
+}
+
+/// Tidy a model reply into a title, rejecting anything that isn't one
+fn clean_model_title(reply: &str) -> Option<String> {
+    let line = reply.lines().map(str::trim).find(|l| !l.is_empty())?;
+    let title = line
+        .trim_matches(|c: char| c == '"' || c == '\'' || c == '*')
+        .trim_end_matches('.')
+        .trim();
+    let words = title.split_whitespace().count();
+    (1..=MAX_TITLE_WORDS + 4)
+        .contains(&words)
+        .then(|| title.to_string())
+}
+
+fn truncate(text: &str, max_chars: usize) -> &str {
+    match text.char_indices().nth(max_chars) {
+        Some((i, _)) => &text[..i],
+        None => text,
+    }
+}
+
+async fn model_title(session_id: &str, prompt: &str, response: &str) -> Option<String> {
+    let client = ClaudeClient::from_env().ok()?;
+    let conversation = format!(
+        "User: {}\n\nAssistant: {}",
+        truncate(prompt, MAX_CONTEXT_CHARS),
+        truncate(response, MAX_CONTEXT_CHARS)
+    );
+    let model = Model::Haiku;
+    match client
+        .send_message(
+            &model,
+            vec![Message::user(&conversation)],
+            Some(TITLE_SYSTEM_PROMPT.to_string()),
+            30,
+        )
+        .await
+    {
+        Ok((reply, usage, cost)) => {
+            record_usage(UsageRecord {
+                timestamp: chrono::Utc::now().timestamp(),
+                session_id: session_id.to_string(),
+                agent_id: "crafter-code".to_string(),
+                model: model.model_id().to_string(),
+                input_tokens: usage.input_tokens,
+                output_tokens: usage.output_tokens,
+                cost_usd: cost,
+                estimated: false,
+            });
+            clean_model_title(&reply)
+        }
+        Err(e) => {
+            eprintln!("[Title] Model call failed, using heuristic: {}", e);
+            None
+        }
+    }
+}
+
+/// Store the title unless the user named the session. Returns whether it
+/// was stored.
+fn store_title(session_id: &str, title: &str) -> Result<bool, String> {
+    let store = SessionStore::new()?;
+    if !store.session_exists(session_id) {
+        PENDING_TITLES
+            .lock()
+            .insert(session_id.to_string(), title.to_string());
+        return Ok(true);
+    }
+    let mut session = store.load_session(session_id)?;
+    if session.metadata.title.is_some() {
+        return Ok(false);
+    }
+    session.metadata.title = Some(title.to_string());
+    store.save_session(&session)?;
+    Ok(true)
+}
+
+/// Title a session in the background from its first prompt and response
+pub fn generate_title(app_handle: AppHandle, session_id: String, prompt: String, response: String) {
+    tauri::async_runtime::spawn(async move {
+        let title = match model_title(&session_id, &prompt, &response).await {
+            Some(title) => title,
+            None => heuristic_title(&prompt),
+        };
+        match store_title(&session_id, &title) {
+            Ok(true) => {
+                emit(&app_handle, &SessionTitleUpdatedEvent { session_id, title });
+            }
+            Ok(false) => {}
+            Err(e) => eprintln!("[Title] Failed to store title for {}: {}", session_id, e),
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heuristic_title() {
+        assert_eq!(
+            heuristic_title(
+                "\n## fix the flaky login test in auth.spec.ts please, it times out\nmore"
+            ),
+            "Fix the flaky login test in auth.spec.ts please"
+        );
+        assert_eq!(
+            heuristic_title("Why does this crash?"),
+            "Why does this crash"
+        );
+        assert_eq!(heuristic_title("   "), "Untitled session");
+    }
+
+    #[test]
+    fn test_clean_model_title() {
+        assert_eq!(
+            clean_model_title("\"Refactor session store persistence.\"\n").as_deref(),
+            Some("Refactor session store persistence")
+        );
+        assert_eq!(clean_model_title(""), None);
+        assert_eq!(clean_model_title(&"word ".repeat(40)), None);
+    }
+}
@@ -0,0 +1,237 @@
+//! Working-directory trust
+//!
+//! Like workspace trust in editors: the first time an agent is launched in a
+//! directory the user has to decide whether to trust it. Decisions are kept
+//! per agent in ~/.crafter-code/trusted-directories.json (encrypted when
+//! `encrypt_sessions` is on) and cover subdirectories (the closest decided
+//! ancestor wins). Sessions in an untrusted directory run with client-side
+//! read-only enforcement; starting one in an undecided directory fails with
+//! a `TRUST_REQUIRED_PREFIX` error so the UI can ask.
+
+use crate::secrets::at_rest::{self, Encryption};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Prefix of the error returned when a directory needs a trust decision
+pub const TRUST_REQUIRED_PREFIX: &str = "trust-required:";
+
+/// Mode name reported when an untrusted directory refuses an action
+pub const UNTRUSTED_MODE: &str = "untrusted";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TrustLevel {
+    Trusted,
+    Untrusted,
+    /// No decision yet
+    Unknown,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TrustDecision {
+    pub path: String,
+    pub trusted: bool,
+    pub decided_at: i64,
+}
+
+/// agent_id -> decisions
+type TrustConfig = HashMap<String, Vec<TrustDecision>>;
+
+struct TrustStore {
+    path: PathBuf,
+    encryption: Encryption,
+}
+
+impl TrustStore {
+    fn new() -> Result<Self, String> {
+        let dir = dirs::home_dir()
+            .ok_or_else(|| "Could not determine home directory".to_string())?
+            .join(".crafter-code");
+        fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create config directory: {}", e))?;
+        Ok(Self::with_path(
+            dir.join("trusted-directories.json"),
+            Encryption::Configured,
+        ))
+    }
+
+    fn with_path(path: PathBuf, encryption: Encryption) -> Self {
+        Self { path, encryption }
+    }
+
+    fn load(&self) -> TrustConfig {
+        at_rest::read_to_string(&self.path, self.encryption)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self, config: &TrustConfig) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(config)
+            .map_err(|e| format!("Failed to serialize trust decisions: {}", e))?;
+        at_rest::write(&self.path, json.as_bytes(), self.encryption)
+            .map_err(|e| format!("Failed to write trust decisions: {}", e))
+    }
+
+    fn decide(&self, agent_id: &str, cwd: &str, trusted: bool) -> Result<TrustDecision, String> {
+        let mut config = self.load();
+        let decision = TrustDecision {
+            path: normalize(cwd),
+            trusted,
+            decided_at: chrono::Utc::now().timestamp(),
+        };
+        let decisions = config.entry(agent_id.to_string()).or_default();
+        decisions.retain(|d| d.path != decision.path);
+        decisions.push(decision.clone());
+        self.save(&config)?;
+        Ok(decision)
+    }
+
+    fn revoke(&self, agent_id: &str, cwd: &str) -> Result<bool, String> {
+        let mut config = self.load();
+        let path = normalize(cwd);
+        let Some(decisions) = config.get_mut(agent_id) else {
+            return Ok(false);
+        };
+        let before = decisions.len();
+        decisions.retain(|d| d.path != path);
+        let removed = decisions.len() != before;
+        if removed {
+            self.save(&config)?;
+        }
+        Ok(removed)
+    }
+}
+
+/// Canonical form of a directory, so symlinks and trailing slashes match
+fn normalize(cwd: &str) -> String {
+    fs::canonicalize(cwd)
+        .unwrap_or_else(|_| PathBuf::from(cwd))
+        .to_string_lossy()
+        .trim_end_matches('/')
+        .to_string()
+}
+
+/// The decision of the closest decided ancestor of `cwd` (or `cwd` itself)
+fn level_in(decisions: &[TrustDecision], cwd: &str) -> TrustLevel {
+    let cwd = normalize(cwd);
+    decisions
+        .iter()
+        .filter(|d| Path::new(&cwd).starts_with(&d.path))
+        .max_by_key(|d| d.path.len())
+        .map_or(TrustLevel::Unknown, |d| {
+            if d.trusted {
+                TrustLevel::Trusted
+            } else {
+                TrustLevel::Untrusted
+            }
+        })
+}
+
+/// How far `agent_id` is trusted in `cwd`
+pub fn trust_level(agent_id: &str, cwd: &str) -> TrustLevel {
+    let Ok(store) = TrustStore::new() else {
+        return TrustLevel::Unknown;
+    };
+    level_in(store.load().get(agent_id).map_or(&[], Vec::as_slice), cwd)
+}
+
+/// Fail with a `TRUST_REQUIRED_PREFIX` error if the user hasn't decided
+/// whether to trust `cwd` for this agent yet
+pub fn require_decision(agent_id: &str, cwd: &str) -> Result<TrustLevel, String> {
+    let level = trust_level(agent_id, cwd);
+    match level {
+        TrustLevel::Unknown => {
+            return Err(format!(
+                "{} {} has not been trusted for agent '{}' yet",
+                TRUST_REQUIRED_PREFIX, cwd, agent_id
+            ))
+        }
+        TrustLevel::Untrusted => eprintln!(
+            "[Trust] {} is untrusted for {}, session will be read-only",
+            cwd, agent_id
+        ),
+        TrustLevel::Trusted => {}
+    }
+    Ok(level)
+}
+
+/// Get how far an agent is trusted in a directory
+#[tauri::command]
+pub fn get_directory_trust(agent_id: String, cwd: String) -> TrustLevel {
+    trust_level(&agent_id, &cwd)
+}
+
+/// Record the user's trust decision for a directory (and its subdirectories)
+#[tauri::command]
+pub fn set_directory_trust(
+    agent_id: String,
+    cwd: String,
+    trusted: bool,
+) -> Result<TrustDecision, String> {
+    eprintln!(
+        "[Trust] {} {} for agent {}",
+        if trusted { "Trusted" } else { "Distrusted" },
+        cwd,
+        agent_id
+    );
+    TrustStore::new()?.decide(&agent_id, &cwd, trusted)
+}
+
+/// List trust decisions, per agent
+#[tauri::command]
+pub fn list_directory_trust() -> Result<HashMap<String, Vec<TrustDecision>>, String> {
+    Ok(TrustStore::new()?.load())
+}
+
+/// Forget a trust decision, so the next launch asks again
+#[tauri::command]
+pub fn revoke_directory_trust(agent_id: String, cwd: String) -> Result<bool, String> {
+    TrustStore::new()?.revoke(&agent_id, &cwd)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_closest_decision_wins() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join("project");
+        let vendor = project.join("vendor");
+        fs::create_dir_all(&vendor).unwrap();
+        let project = project.to_str().unwrap();
+        let vendor = vendor.to_str().unwrap();
+
+        let store = TrustStore::with_path(dir.path().join("trust.json"), Encryption::Off);
+        store.decide("claude", project, true).unwrap();
+        store.decide("claude", vendor, false).unwrap();
+        let config = store.load();
+        let claude = &config["claude"];
+
+        assert_eq!(level_in(claude, project), TrustLevel::Trusted);
+        assert_eq!(
+            level_in(claude, &format!("{}/", project)),
+            TrustLevel::Trusted
+        );
+        assert_eq!(level_in(claude, vendor), TrustLevel::Untrusted);
+        assert_eq!(
+            level_in(claude, dir.path().to_str().unwrap()),
+            TrustLevel::Unknown
+        );
+        // A sibling sharing the prefix isn't covered
+        assert_eq!(
+            level_in(claude, &format!("{}-fork", project)),
+            TrustLevel::Unknown
+        );
+        assert!(!config.contains_key("gemini"));
+
+        assert!(store.revoke("claude", vendor).unwrap());
+        assert_eq!(
+            level_in(&store.load()["claude"], vendor),
+            TrustLevel::Trusted
+        );
+    }
+}
@@ -0,0 +1,114 @@
+//! Per-prompt response accumulation
+//!
+//! Each call to `AcpClient::prompt_with_content` opens a turn with its own
+//! id. Message chunks are accumulated into the active turn only, so late
+//! chunks arriving after a cancel cannot leak into the next turn's output.
+
+use std::collections::HashMap;
+
+/// Text collected for a single turn
+#[derive(Debug, Clone, Default)]
+pub struct TurnOutput {
+    /// Agent message text
+    pub text: String,
+    /// Agent thought/reasoning text
+    pub thinking: String,
+}
+
+#[derive(Debug, Default)]
+pub struct TurnAccumulator {
+    current: Option<String>,
+    buffers: HashMap<String, TurnOutput>,
+}
+
+impl TurnAccumulator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start a new turn and make it the active one
+    pub fn begin(&mut self) -> String {
+        let prompt_id = uuid::Uuid::new_v4().to_string();
+        self.buffers.insert(prompt_id.clone(), TurnOutput::default());
+        self.current = Some(prompt_id.clone());
+        prompt_id
+    }
+
+    /// Id of the turn currently receiving chunks
+    pub fn current(&self) -> Option<String> {
+        self.current.clone()
+    }
+
+    /// Append text to the active turn. Returns the turn id it was added to,
+    /// or None if no turn is active (e.g. a late chunk after completion).
+    pub fn push(&mut self, text: &str) -> Option<String> {
+        let prompt_id = self.current.clone()?;
+        self.buffers
+            .entry(prompt_id.clone())
+            .or_default()
+            .text
+            .push_str(text);
+        Some(prompt_id)
+    }
+
+    /// Append thought text to the active turn
+    pub fn push_thinking(&mut self, text: &str) -> Option<String> {
+        let prompt_id = self.current.clone()?;
+        self.buffers
+            .entry(prompt_id.clone())
+            .or_default()
+            .thinking
+            .push_str(text);
+        Some(prompt_id)
+    }
+
+    /// Close a turn and return its accumulated output
+    pub fn finish(&mut self, prompt_id: &str) -> TurnOutput {
+        if self.current.as_deref() == Some(prompt_id) {
+            self.current = None;
+        }
+        self.buffers.remove(prompt_id).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_turns_are_isolated() {
+        let mut acc = TurnAccumulator::new();
+        let first = acc.begin();
+        acc.push("hello ");
+        acc.push("world");
+        assert_eq!(acc.finish(&first).text, "hello world");
+
+        // Late chunk after the turn ended is not accumulated
+        assert_eq!(acc.push("late"), None);
+
+        let second = acc.begin();
+        assert_ne!(first, second);
+        assert_eq!(acc.push("next").as_deref(), Some(second.as_str()));
+        assert_eq!(acc.finish(&second).text, "next");
+    }
+
+    #[test]
+    fn test_thinking_is_kept_separate() {
+        let mut acc = TurnAccumulator::new();
+        let id = acc.begin();
+        acc.push_thinking("let me think");
+        acc.push("answer");
+        let output = acc.finish(&id);
+        assert_eq!(output.text, "answer");
+        assert_eq!(output.thinking, "let me think");
+    }
+
+    #[test]
+    fn test_finish_stale_turn_keeps_current() {
+        let mut acc = TurnAccumulator::new();
+        let first = acc.begin();
+        let second = acc.begin();
+        acc.finish(&first);
+        assert_eq!(acc.current(), Some(second));
+    }
+}
@@ -0,0 +1,271 @@
+//! Worker liveness
+//!
+//! A worker thread that dies leaves its `WorkerHandle` behind with a closed
+//! channel, and every send to it used to fail with "Worker thread has
+//! stopped". Worker threads report in here: `spawn_worker_thread` records
+//! when each starts and exits (and whether it panicked), and the command
+//! loop stamps a heartbeat whenever it starts or finishes a command. A
+//! background sweep removes dead handles so the session can be reconnected,
+//! and fails workers that still claim to be running. `get_worker_health`
+//! lets the UI check a session before sending to it.
+
+use crate::acp::hibernation::is_hibernated;
+use crate::events::{emit, WorkerStatusChange};
+use crate::orchestrator::tool_calls::slow_call_count;
+use crate::orchestrator::worker::WorkerStatus;
+use crate::AppState;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+/// How often dead worker handles are looked for
+const SWEEP_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Records of exited threads are dropped after this many seconds
+const EXITED_RETENTION_SECS: i64 = 3600;
+
+#[derive(Debug, Clone, Default)]
+struct ThreadRecord {
+    started_at: i64,
+    /// Running a command rather than waiting for one
+    busy: bool,
+    last_heartbeat: i64,
+    exited_at: Option<i64>,
+    panicked: bool,
+}
+
+/// Worker threads by worker_id
+static THREADS: Lazy<Mutex<HashMap<String, ThreadRecord>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn now() -> i64 {
+    chrono::Utc::now().timestamp()
+}
+
+pub fn thread_started(worker_id: &str) {
+    let now = now();
+    THREADS.lock().insert(
+        worker_id.to_string(),
+        ThreadRecord {
+            started_at: now,
+            last_heartbeat: now,
+            ..Default::default()
+        },
+    );
+}
+
+pub fn thread_exited(worker_id: &str, panicked: bool) {
+    if let Some(record) = THREADS.lock().get_mut(worker_id) {
+        record.exited_at = Some(now());
+        record.busy = false;
+        record.panicked = panicked;
+    }
+}
+
+/// The worker's command loop picked up a command (`busy`) or went back to
+/// waiting for one
+pub fn heartbeat(worker_id: &str, busy: bool) {
+    if let Some(record) = THREADS.lock().get_mut(worker_id) {
+        record.busy = busy;
+        record.last_heartbeat = now();
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Liveness {
+    /// The worker thread is accepting commands
+    Alive,
+    /// Stopped after sitting idle; the next command wakes it
+    Hibernated,
+    /// The worker thread is gone; reconnect before sending
+    Dead,
+    /// The session never had a worker (or it was stopped)
+    Missing,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkerHealth {
+    pub session_id: String,
+    pub worker_id: Option<String>,
+    pub liveness: Liveness,
+    /// Running a prompt or other command
+    pub busy: bool,
+    /// Tool calls running past the slow-tool threshold; a busy worker with
+    /// none of these and no output is stalled rather than waiting on a tool
+    pub slow_tools: usize,
+    /// Commands waiting in the worker's channel
+    pub queued_commands: usize,
+    /// Unix timestamps (seconds)
+    pub started_at: Option<i64>,
+    pub last_heartbeat: Option<i64>,
+    pub exited_at: Option<i64>,
+    pub panicked: bool,
+}
+
+/// The session key of a worker handle (fleet workers use
+/// "session_id:worker_id")
+fn handle_session(key: &str) -> (&str, Option<&str>) {
+    match key.split_once(':') {
+        Some((session_id, worker_id)) => (session_id, Some(worker_id)),
+        None => (key, None),
+    }
+}
+
+/// Remove handles whose worker thread is gone, failing workers that still
+/// claim to be running. Returns the removed handle keys.
+pub fn sweep(state: &AppState, app_handle: &AppHandle) -> Vec<String> {
+    let dead: Vec<String> = {
+        let mut handles = state.worker_handles.lock();
+        let dead: Vec<String> = handles
+            .iter()
+            .filter(|(_, handle)| handle.command_tx.is_closed())
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in &dead {
+            handles.remove(key);
+        }
+        dead
+    };
+
+    for key in &dead {
+        let (session_id, worker_id) = handle_session(key);
+        eprintln!("[ACP] Removed dead worker handle for session={}", key);
+        let mut failed = Vec::new();
+        {
+            let mut mgr = state.orchestrator_manager.lock();
+            let Some(session) = mgr.get_session_mut(session_id) else {
+                continue;
+            };
+            for worker in session
+                .workers
+                .iter_mut()
+                .filter(|w| worker_id.is_none_or(|id| w.id == id))
+                .filter(|w| {
+                    matches!(
+                        w.status,
+                        WorkerStatus::Pending | WorkerStatus::Running | WorkerStatus::Idle
+                    )
+                })
+            {
+                worker.mark_failed("Worker thread stopped unexpectedly".to_string());
+                failed.push(worker.id.clone());
+            }
+            // Recompute the session status from its workers
+            for worker_id in &failed {
+                mgr.update_worker_status(session_id, worker_id, WorkerStatus::Failed);
+                mgr.remove_worker_cancel(worker_id);
+            }
+        }
+        for worker_id in failed {
+            emit(
+                app_handle,
+                &WorkerStatusChange {
+                    error: Some("Worker thread stopped unexpectedly".to_string()),
+                    ..WorkerStatusChange::new(session_id, &worker_id, WorkerStatus::Failed)
+                },
+            );
+        }
+    }
+
+    let cutoff = now() - EXITED_RETENTION_SECS;
+    THREADS
+        .lock()
+        .retain(|_, record| record.exited_at.is_none_or(|at| at > cutoff));
+    dead
+}
+
+/// Start the background sweep for dead worker handles
+pub fn start(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(SWEEP_INTERVAL);
+        loop {
+            interval.tick().await;
+            sweep(&app.state::<AppState>(), &app);
+        }
+    });
+}
+
+/// Whether a session's worker can take commands right now. A dead handle
+/// found here is swept immediately.
+#[tauri::command]
+pub fn get_worker_health(
+    session_id: String,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> WorkerHealth {
+    let (mut liveness, queued_commands) = match state.worker_handles.lock().get(&session_id) {
+        Some(handle) if handle.command_tx.is_closed() => (Liveness::Dead, 0),
+        Some(handle) => (
+            Liveness::Alive,
+            handle.command_tx.max_capacity() - handle.command_tx.capacity(),
+        ),
+        None => (Liveness::Missing, 0),
+    };
+    if liveness == Liveness::Dead {
+        sweep(&state, &app_handle);
+    } else if liveness == Liveness::Missing && is_hibernated(&session_id) {
+        liveness = Liveness::Hibernated;
+    }
+
+    let worker_id = state
+        .orchestrator_manager
+        .lock()
+        .get_session(&session_id)
+        .and_then(|s| s.workers.first().map(|w| w.id.clone()));
+    let record = worker_id
+        .as_ref()
+        .and_then(|id| THREADS.lock().get(id).cloned());
+    if liveness == Liveness::Missing && record.as_ref().is_some_and(|r| r.exited_at.is_some()) {
+        liveness = Liveness::Dead;
+    }
+
+    let slow_tools = worker_id
+        .as_deref()
+        .map(|id| slow_call_count(&session_id, id))
+        .unwrap_or(0);
+
+    WorkerHealth {
+        session_id,
+        worker_id,
+        liveness,
+        busy: liveness == Liveness::Alive && record.as_ref().is_some_and(|r| r.busy),
+        slow_tools,
+        queued_commands,
+        started_at: record.as_ref().map(|r| r.started_at),
+        last_heartbeat: record.as_ref().map(|r| r.last_heartbeat),
+        exited_at: record.as_ref().and_then(|r| r.exited_at),
+        panicked: record.is_some_and(|r| r.panicked),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thread_records() {
+        thread_started("health-worker");
+        heartbeat("health-worker", true);
+        assert!(THREADS.lock()["health-worker"].busy);
+
+        thread_exited("health-worker", true);
+        let record = THREADS.lock()["health-worker"].clone();
+        assert!(!record.busy);
+        assert!(record.panicked);
+        assert!(record.exited_at.is_some());
+
+        // Heartbeats for unknown workers are ignored
+        heartbeat("health-unknown", true);
+        assert!(!THREADS.lock().contains_key("health-unknown"));
+    }
+
+    #[test]
+    fn test_handle_session() {
+        assert_eq!(handle_session("s1"), ("s1", None));
+        assert_eq!(handle_session("s1:w2"), ("s1", Some("w2")));
+    }
+}
@@ -0,0 +1,190 @@
+//! Diffs for file writes
+//!
+//! Some agents edit files through `fs/write_text_file` without putting a
+//! `Diff` block in the tool call, which leaves the diff view empty. The
+//! client snapshots each file before such a write and diffs it against the
+//! new content. Writes made while an edit tool call is running are held
+//! until the call finishes and dropped if the call reported a diff for the
+//! file itself; the others are added to the tool call log and emitted as
+//! `file_diff` worker events, so every agent's edits show up the same way.
+
+use crate::orchestrator::tool_calls::ToolCallDiff;
+use similar::TextDiff;
+
+/// Lines of context around each hunk
+const CONTEXT_LINES: usize = 3;
+
+/// Tool call kinds that change files
+const EDIT_KINDS: &[&str] = &["edit", "delete", "move"];
+
+/// A write, with the file's content before it
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileWrite {
+    /// Edit tool call running when the write happened
+    pub tool_call_id: Option<String>,
+    pub path: String,
+    /// None if the file didn't exist
+    pub old_text: Option<String>,
+    pub new_text: String,
+}
+
+impl FileWrite {
+    pub fn diff(&self) -> ToolCallDiff {
+        ToolCallDiff {
+            path: self.path.clone(),
+            old_text: self.old_text.clone(),
+            new_text: self.new_text.clone(),
+        }
+    }
+
+    pub fn unified(&self) -> String {
+        unified_diff(&self.path, self.old_text.as_deref(), &self.new_text)
+    }
+}
+
+/// A unified diff of one file
+pub fn unified_diff(path: &str, old_text: Option<&str>, new_text: &str) -> String {
+    let old_header = if old_text.is_some() {
+        path
+    } else {
+        "/dev/null"
+    };
+    TextDiff::from_lines(old_text.unwrap_or(""), new_text)
+        .unified_diff()
+        .context_radius(CONTEXT_LINES)
+        .header(old_header, path)
+        .to_string()
+}
+
+/// Writes of one worker, and the edit tool calls they may belong to
+#[derive(Debug, Default)]
+pub struct WriteDiffs {
+    /// Edit tool calls in progress, oldest first
+    open_calls: Vec<String>,
+    /// Writes waiting for their tool call to finish
+    pending: Vec<FileWrite>,
+}
+
+impl WriteDiffs {
+    /// Note a tool call starting; only edits can own writes
+    pub fn call_started(&mut self, tool_call_id: &str, kind: &str) {
+        if EDIT_KINDS.contains(&kind) && !self.open_calls.iter().any(|id| id == tool_call_id) {
+            self.open_calls.push(tool_call_id.to_string());
+        }
+    }
+
+    /// Record a write. Returns it if no edit call is running; otherwise it
+    /// waits for the latest one to finish. Writes that change nothing are
+    /// dropped.
+    pub fn record(
+        &mut self,
+        path: &str,
+        old_text: Option<String>,
+        new_text: String,
+    ) -> Option<FileWrite> {
+        if old_text.as_deref() == Some(new_text.as_str()) {
+            return None;
+        }
+        let Some(tool_call_id) = self.open_calls.last().cloned() else {
+            return Some(FileWrite {
+                tool_call_id: None,
+                path: path.to_string(),
+                old_text,
+                new_text,
+            });
+        };
+        // Repeated writes in one call make one diff from the first snapshot
+        if let Some(write) = self
+            .pending
+            .iter_mut()
+            .find(|w| w.path == path && w.tool_call_id.as_deref() == Some(&tool_call_id))
+        {
+            write.new_text = new_text;
+            return None;
+        }
+        self.pending.push(FileWrite {
+            tool_call_id: Some(tool_call_id),
+            path: path.to_string(),
+            old_text,
+            new_text,
+        });
+        None
+    }
+
+    /// A tool call finished. Returns its writes to files it didn't report
+    /// a diff for.
+    pub fn call_finished(
+        &mut self,
+        tool_call_id: &str,
+        reported: &[ToolCallDiff],
+    ) -> Vec<FileWrite> {
+        self.open_calls.retain(|id| id != tool_call_id);
+        let (writes, pending): (Vec<FileWrite>, Vec<FileWrite>) = std::mem::take(&mut self.pending)
+            .into_iter()
+            .partition(|w| w.tool_call_id.as_deref() == Some(tool_call_id));
+        self.pending = pending;
+        writes
+            .into_iter()
+            .filter(|w| w.old_text.as_deref() != Some(w.new_text.as_str()))
+            .filter(|w| !reported.iter().any(|diff| diff.path == w.path))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unified_diff() {
+        let diff = unified_diff("/p/a.txt", Some("one\ntwo\n"), "one\nthree\n");
+        assert_eq!(
+            diff,
+            "--- /p/a.txt\n+++ /p/a.txt\n@@ -1,2 +1,2 @@\n one\n-two\n+three\n"
+        );
+        let diff = unified_diff("/p/new.txt", None, "hello\n");
+        assert!(diff.starts_with("--- /dev/null\n+++ /p/new.txt\n"));
+    }
+
+    #[test]
+    fn test_writes_outside_edit_calls_are_returned() {
+        let mut diffs = WriteDiffs::default();
+        diffs.call_started("t1", "read");
+        let write = diffs
+            .record("/p/a.txt", Some("a".into()), "b".into())
+            .unwrap();
+        assert_eq!(write.tool_call_id, None);
+        assert!(diffs
+            .record("/p/a.txt", Some("b".into()), "b".into())
+            .is_none());
+    }
+
+    #[test]
+    fn test_writes_wait_for_their_call() {
+        let mut diffs = WriteDiffs::default();
+        diffs.call_started("t1", "edit");
+        assert!(diffs
+            .record("/p/a.txt", Some("a".into()), "b".into())
+            .is_none());
+        assert!(diffs
+            .record("/p/a.txt", Some("b".into()), "c".into())
+            .is_none());
+        assert!(diffs.record("/p/b.txt", None, "new".into()).is_none());
+
+        let reported = vec![ToolCallDiff {
+            path: "/p/b.txt".to_string(),
+            old_text: None,
+            new_text: "new".to_string(),
+        }];
+        let writes = diffs.call_finished("t1", &reported);
+        assert_eq!(writes.len(), 1);
+        assert_eq!(writes[0].old_text.as_deref(), Some("a"));
+        assert_eq!(writes[0].new_text, "c");
+
+        // Nothing left open, so the next write is returned right away
+        assert!(diffs.call_finished("t1", &[]).is_empty());
+        assert!(diffs
+            .record("/p/a.txt", Some("c".into()), "d".into())
+            .is_some());
+    }
+}
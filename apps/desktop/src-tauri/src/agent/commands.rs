@@ -1,3 +1,6 @@
+use crate::agent::context_files::{find_context_files, ProjectContextFile};
+use crate::agent::project::{project_info, ProjectInfo};
+use crate::agent::tree::{build_tree, expand_tree, GitStatusEntry, RepoStatus, TreeNode};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::Path;
@@ -13,14 +16,6 @@ pub struct FileEntry {
     pub modified: Option<i64>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ProjectInfo {
-    pub name: String,
-    pub path: String,
-    pub git_branch: Option<String>,
-    pub git_status: Option<String>,
-}
-
 #[tauri::command]
 pub fn read_directory(path: String) -> Result<Vec<FileEntry>, String> {
     let dir_path = Path::new(&path);
@@ -81,21 +76,46 @@ pub fn read_directory(path: String) -> Result<Vec<FileEntry>, String> {
     Ok(entries)
 }
 
+/// Read a directory several levels deep, skipping git-ignored entries and
+/// `ignore_globs`. Directories past `depth` carry an expand token.
 #[tauri::command]
-pub fn read_file_content(path: String) -> Result<String, String> {
-    let file_path = Path::new(&path);
-
-    if !file_path.exists() {
-        return Err(format!("File does not exist: {}", path));
+pub fn read_directory_tree(
+    path: String,
+    depth: Option<u32>,
+    ignore_globs: Option<Vec<String>>,
+) -> Result<TreeNode, String> {
+    let dir_path = Path::new(&path);
+    if !dir_path.exists() {
+        return Err(format!("Path does not exist: {}", path));
     }
+    build_tree(
+        dir_path,
+        dir_path,
+        depth.unwrap_or(2),
+        &ignore_globs.unwrap_or_default(),
+    )
+}
 
-    if !file_path.is_file() {
-        return Err(format!("Path is not a file: {}", path));
-    }
+/// Load a directory left unexpanded by `read_directory_tree`
+#[tauri::command]
+pub fn expand_directory_tree(token: String, depth: Option<u32>) -> Result<TreeNode, String> {
+    expand_tree(&token, depth.unwrap_or(1))
+}
 
-    fs::read_to_string(file_path).map_err(|e| e.to_string())
+/// Changed and untracked files in the repository containing `path`
+/// (empty outside git)
+#[tauri::command]
+pub fn get_git_status(path: String) -> Result<Vec<GitStatusEntry>, String> {
+    let dir_path = Path::new(&path);
+    if !dir_path.exists() {
+        return Err(format!("Path does not exist: {}", path));
+    }
+    Ok(RepoStatus::load(dir_path, false)
+        .map(|repo| repo.entries())
+        .unwrap_or_default())
 }
 
+/// Name, toolchains, scripts and git metadata of a project
 #[tauri::command]
 pub fn get_project_info(path: String) -> Result<ProjectInfo, String> {
     let dir_path = Path::new(&path);
@@ -104,58 +124,15 @@ pub fn get_project_info(path: String) -> Result<ProjectInfo, String> {
         return Err(format!("Path does not exist: {}", path));
     }
 
-    let name = dir_path
-        .file_name()
-        .map(|n| n.to_string_lossy().to_string())
-        .unwrap_or_else(|| "Unknown".to_string());
-
-    // Try to get git info
-    let git_dir = dir_path.join(".git");
-    let (git_branch, git_status) = if git_dir.exists() {
-        let branch = get_git_branch(&path);
-        let status = get_git_status(&path);
-        (branch, status)
-    } else {
-        (None, None)
-    };
-
-    Ok(ProjectInfo {
-        name,
-        path: path.clone(),
-        git_branch,
-        git_status,
-    })
+    Ok(project_info(dir_path))
 }
 
-fn get_git_branch(path: &str) -> Option<String> {
-    let output = std::process::Command::new("git")
-        .current_dir(path)
-        .args(["branch", "--show-current"])
-        .output()
-        .ok()?;
-
-    if output.status.success() {
-        Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
-    } else {
-        None
-    }
-}
-
-fn get_git_status(path: &str) -> Option<String> {
-    let output = std::process::Command::new("git")
-        .current_dir(path)
-        .args(["status", "--porcelain"])
-        .output()
-        .ok()?;
-
-    if output.status.success() {
-        let status = String::from_utf8_lossy(&output.stdout).trim().to_string();
-        if status.is_empty() {
-            Some("clean".to_string())
-        } else {
-            Some("modified".to_string())
-        }
-    } else {
-        None
+/// AGENTS.md, CLAUDE.md, GEMINI.md and .cursorrules in a project root
+#[tauri::command]
+pub fn list_project_context_files(path: String) -> Result<Vec<ProjectContextFile>, String> {
+    let dir_path = Path::new(&path);
+    if !dir_path.exists() {
+        return Err(format!("Path does not exist: {}", path));
     }
+    Ok(find_context_files(dir_path))
 }
@@ -0,0 +1,144 @@
+//! Project context files
+//!
+//! AGENTS.md, CLAUDE.md, GEMINI.md and .cursorrules hold a project's
+//! instructions for coding agents, but each agent only reads its own. They
+//! are listed in `ProjectInfo::context_files`; when `context_files.inject`
+//! is on, the ones an agent doesn't load itself are added to its
+//! coordination prompt.
+
+use crate::settings::store::ContextFileSettings;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// Context files looked for in the project root, and the agents (registry
+/// ids) that load each one natively
+const CONTEXT_FILES: &[(&str, &[&str])] = &[
+    ("AGENTS.md", &["codex", "opencode"]),
+    ("CLAUDE.md", &["claude", "opencode"]),
+    ("GEMINI.md", &["gemini"]),
+    (".cursorrules", &[]),
+];
+
+/// A context file found in a project
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ProjectContextFile {
+    /// File name, relative to the project root
+    pub name: String,
+    pub size: u64,
+    /// Agents that read the file themselves
+    pub native_agents: Vec<String>,
+}
+
+impl ProjectContextFile {
+    /// Whether `agent_id` reads this file without our help
+    pub fn is_native_to(&self, agent_id: &str) -> bool {
+        self.native_agents.iter().any(|a| a == agent_id)
+    }
+}
+
+/// Context files in the project root
+pub fn find_context_files(dir: &Path) -> Vec<ProjectContextFile> {
+    CONTEXT_FILES
+        .iter()
+        .filter_map(|(name, agents)| {
+            let meta = fs::metadata(dir.join(name)).ok().filter(|m| m.is_file())?;
+            Some(ProjectContextFile {
+                name: name.to_string(),
+                size: meta.len(),
+                native_agents: agents.iter().map(|a| a.to_string()).collect(),
+            })
+        })
+        .collect()
+}
+
+/// Instructions from the context files `agent_id` doesn't load itself, for
+/// its prompt. None when injection is off or there is nothing to add.
+pub fn context_instructions(
+    dir: &Path,
+    agent_id: &str,
+    settings: &ContextFileSettings,
+) -> Option<String> {
+    if !settings.inject {
+        return None;
+    }
+
+    let mut out = String::new();
+    for file in find_context_files(dir) {
+        if file.is_native_to(agent_id) {
+            continue;
+        }
+        let Ok(content) = fs::read_to_string(dir.join(&file.name)) else {
+            continue;
+        };
+        let content = content.trim();
+        if content.is_empty() {
+            continue;
+        }
+        out.push_str(&format!("#### {}\n\n", file.name));
+        if settings.max_bytes > 0 && content.len() > settings.max_bytes {
+            let mut end = settings.max_bytes;
+            while !content.is_char_boundary(end) {
+                end -= 1;
+            }
+            out.push_str(&content[..end]);
+            out.push_str(&format!("\n\n(truncated, read {} for the rest)", file.name));
+        } else {
+            out.push_str(content);
+        }
+        out.push_str("\n\n");
+    }
+
+    if out.is_empty() {
+        return None;
+    }
+    Some(format!(
+        "### Project Instructions\n\nThe project gives these instructions to coding agents:\n\n{}",
+        out.trim_end()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_find_context_files() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("CLAUDE.md"), "Use pnpm.").unwrap();
+        fs::write(dir.path().join(".cursorrules"), "No semicolons.").unwrap();
+        fs::create_dir(dir.path().join("AGENTS.md")).unwrap();
+
+        let files = find_context_files(dir.path());
+        let names: Vec<&str> = files.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, vec!["CLAUDE.md", ".cursorrules"]);
+        assert_eq!(files[0].size, 9);
+        assert!(files[0].is_native_to("claude"));
+        assert!(!files[1].is_native_to("claude"));
+    }
+
+    #[test]
+    fn test_context_instructions() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("CLAUDE.md"), "Use pnpm.\n").unwrap();
+        fs::write(dir.path().join("AGENTS.md"), "Run the linter before committing.").unwrap();
+        let mut settings = ContextFileSettings::default();
+
+        settings.inject = false;
+        assert!(context_instructions(dir.path(), "gemini", &settings).is_none());
+
+        settings.inject = true;
+        let instructions = context_instructions(dir.path(), "claude", &settings).unwrap();
+        assert!(instructions.contains("#### AGENTS.md\n\nRun the linter"));
+        assert!(!instructions.contains("CLAUDE.md"));
+
+        // Nothing to add when the agent reads everything itself
+        fs::remove_file(dir.path().join("AGENTS.md")).unwrap();
+        assert!(context_instructions(dir.path(), "claude", &settings).is_none());
+
+        settings.max_bytes = 3;
+        let instructions = context_instructions(dir.path(), "gemini", &settings).unwrap();
+        assert!(instructions.contains("#### CLAUDE.md\n\nUse\n\n(truncated"));
+    }
+}
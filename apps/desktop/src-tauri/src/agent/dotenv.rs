@@ -0,0 +1,224 @@
+//! Project .env files
+//!
+//! The files listed in `dotenv.files` (`.env`, `.env.local` by default) are
+//! read from the project root. When `dotenv.inject` is on, their variables
+//! are added to agent processes and agent-created terminals, without
+//! overriding anything already set in the app's environment. Values of
+//! variables matching the `dotenv.mask` patterns are masked in previews and
+//! redacted from worker output (see `secrets::redact`).
+
+use crate::settings::load_settings;
+use crate::settings::store::DotenvSettings;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+const MASK: &str = "••••••";
+
+/// A variable a project .env file would contribute
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EnvVarPreview {
+    pub name: String,
+    /// The value, or a mask for secrets
+    pub value: String,
+    /// File the value comes from (the last file defining it)
+    pub file: String,
+    pub masked: bool,
+    /// Already set in the app's environment, so it won't be injected
+    pub overridden: bool,
+}
+
+/// Parse .env content: `KEY=value`, optional `export`, quotes and comments
+pub fn parse_env(content: &str) -> Vec<(String, String)> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                return None;
+            }
+            let line = line.strip_prefix("export ").unwrap_or(line);
+            let (key, value) = line.split_once('=')?;
+            let key = key.trim();
+            if key.is_empty() || key.contains(char::is_whitespace) {
+                return None;
+            }
+            Some((key.to_string(), parse_value(value.trim())))
+        })
+        .collect()
+}
+
+fn parse_value(value: &str) -> String {
+    for quote in ['"', '\''] {
+        if let Some(rest) = value.strip_prefix(quote) {
+            if let Some(end) = rest.find(quote) {
+                let inner = &rest[..end];
+                return if quote == '"' {
+                    inner.replace("\\n", "\n")
+                } else {
+                    inner.to_string()
+                };
+            }
+        }
+    }
+    // Unquoted values end at an inline comment
+    match value.find(" #") {
+        Some(i) => value[..i].trim_end().to_string(),
+        None => value.to_string(),
+    }
+}
+
+/// Case-insensitive match with `*` wildcards
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.to_ascii_uppercase();
+    let name = name.to_ascii_uppercase();
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == name;
+    }
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if !name.starts_with(first) || !name.ends_with(last) || name.len() < first.len() + last.len() {
+        return false;
+    }
+    let mut rest = &name[first.len()..name.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+/// Whether a variable's value should be masked
+pub fn is_masked(name: &str, settings: &DotenvSettings) -> bool {
+    !settings.unmask.iter().any(|p| wildcard_match(p, name))
+        && settings.mask.iter().any(|p| wildcard_match(p, name))
+}
+
+/// The configured .env files present in `dir`
+pub fn find_env_files(dir: &Path, settings: &DotenvSettings) -> Vec<String> {
+    settings
+        .files
+        .iter()
+        .filter(|file| dir.join(file).is_file())
+        .cloned()
+        .collect()
+}
+
+/// Variables from the project's .env files with the file each came from,
+/// later files overriding earlier ones
+fn read_project_env(dir: &Path, settings: &DotenvSettings) -> Vec<(String, String, String)> {
+    let mut vars: Vec<(String, String, String)> = Vec::new();
+    for file in find_env_files(dir, settings) {
+        let Ok(content) = fs::read_to_string(dir.join(&file)) else {
+            continue;
+        };
+        for (key, value) in parse_env(&content) {
+            vars.retain(|(k, _, _)| *k != key);
+            vars.push((key, value, file.clone()));
+        }
+    }
+    vars
+}
+
+/// Environment to inject for a project; empty unless injection is enabled
+pub fn project_env(dir: &str) -> Vec<(String, String)> {
+    let settings = load_settings().dotenv;
+    if !settings.inject {
+        return Vec::new();
+    }
+    read_project_env(Path::new(dir), &settings)
+        .into_iter()
+        .filter(|(key, _, _)| std::env::var_os(key).is_none())
+        .map(|(key, value, _)| (key, value))
+        .collect()
+}
+
+/// Values of the project's masked .env variables
+pub fn project_secret_values(dir: &Path, settings: &DotenvSettings) -> Vec<String> {
+    read_project_env(dir, settings)
+        .into_iter()
+        .filter(|(key, _, _)| is_masked(key, settings))
+        .map(|(_, value, _)| value)
+        .collect()
+}
+
+/// Variables the project's .env files would contribute, with secrets masked
+#[tauri::command]
+pub fn preview_project_env(path: String) -> Result<Vec<EnvVarPreview>, String> {
+    let dir = Path::new(&path);
+    if !dir.is_dir() {
+        return Err(format!("Path is not a directory: {}", path));
+    }
+    let settings = load_settings().dotenv;
+    Ok(read_project_env(dir, &settings)
+        .into_iter()
+        .map(|(name, value, file)| {
+            let masked = is_masked(&name, &settings);
+            EnvVarPreview {
+                overridden: std::env::var_os(&name).is_some(),
+                value: if masked { MASK.to_string() } else { value },
+                name,
+                file,
+                masked,
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_env() {
+        let vars = parse_env(
+            "# comment\nexport API_KEY=\"sk-abc\"\nNAME='two words'\nPORT=3000 # dev\n\nbad line\n",
+        );
+        assert_eq!(
+            vars,
+            vec![
+                ("API_KEY".to_string(), "sk-abc".to_string()),
+                ("NAME".to_string(), "two words".to_string()),
+                ("PORT".to_string(), "3000".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_masking_rules() {
+        let settings = DotenvSettings {
+            unmask: vec!["NEXT_PUBLIC_*".to_string()],
+            ..Default::default()
+        };
+        assert!(is_masked("OPENAI_API_KEY", &settings));
+        assert!(is_masked("github_token", &settings));
+        assert!(is_masked("DATABASE_URL", &settings));
+        assert!(!is_masked("NEXT_PUBLIC_STRIPE_KEY", &settings));
+        assert!(!is_masked("PORT", &settings));
+        assert!(!wildcard_match("A*C*A", "ABA"));
+        assert!(wildcard_match("A*B*A", "AXBYA"));
+    }
+
+    #[test]
+    fn test_read_project_env_overrides() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join(".env"), "A=1\nB=2\n").unwrap();
+        fs::write(dir.path().join(".env.local"), "B=3\n").unwrap();
+        let settings = DotenvSettings::default();
+
+        assert_eq!(
+            find_env_files(dir.path(), &settings),
+            vec![".env", ".env.local"]
+        );
+        let vars = read_project_env(dir.path(), &settings);
+        assert_eq!(
+            vars,
+            vec![
+                ("A".to_string(), "1".to_string(), ".env".to_string()),
+                ("B".to_string(), "3".to_string(), ".env.local".to_string()),
+            ]
+        );
+    }
+}
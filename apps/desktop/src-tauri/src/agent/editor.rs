@@ -0,0 +1,408 @@
+//! Opening files in the user's editor
+//!
+//! `open_in_editor` opens a file at a line and column (a diff hunk, a
+//! failing criterion) in VS Code, Zed, a JetBrains IDE or `$EDITOR`. The
+//! editor is the one asked for, else the project's preference, else
+//! `editor.preferred`, else the first one found on this machine. Terminal
+//! editors from `$EDITOR` run in a new integrated terminal.
+
+use crate::acp::registry::check_command_exists;
+use crate::palette::recent;
+use crate::pty::profile::TerminalProfile;
+use crate::pty::terminal::{TerminalOptions, TERMINAL_MANAGER};
+use crate::settings::load_settings;
+use crate::settings::store::{EditorSettings, SettingsStore};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use tauri::AppHandle;
+
+/// JetBrains IDE launchers, most general first
+const JETBRAINS_LAUNCHERS: &[&str] = &[
+    "idea",
+    "webstorm",
+    "pycharm",
+    "goland",
+    "rustrover",
+    "clion",
+    "phpstorm",
+    "rubymine",
+];
+
+/// Where the CLIs live when they aren't on the PATH (macOS app bundles)
+const VSCODE_PATHS: &[&str] =
+    &["/Applications/Visual Studio Code.app/Contents/Resources/app/bin/code"];
+const ZED_PATHS: &[&str] = &["/Applications/Zed.app/Contents/MacOS/cli"];
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Editor {
+    Vscode,
+    Zed,
+    Jetbrains,
+    /// `$VISUAL` or `$EDITOR`
+    Env,
+}
+
+impl Editor {
+    const ALL: [Editor; 4] = [Editor::Vscode, Editor::Zed, Editor::Jetbrains, Editor::Env];
+
+    pub fn id(self) -> &'static str {
+        match self {
+            Editor::Vscode => "vscode",
+            Editor::Zed => "zed",
+            Editor::Jetbrains => "jetbrains",
+            Editor::Env => "env",
+        }
+    }
+
+    fn from_id(id: &str) -> Option<Editor> {
+        Self::ALL.into_iter().find(|editor| editor.id() == id)
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Editor::Vscode => "VS Code",
+            Editor::Zed => "Zed",
+            Editor::Jetbrains => "JetBrains",
+            Editor::Env => "$EDITOR",
+        }
+    }
+
+    /// The program that opens files, if it's installed
+    fn program(self) -> Option<String> {
+        match self {
+            Editor::Vscode => find_program("code", VSCODE_PATHS),
+            Editor::Zed => find_program("zed", ZED_PATHS),
+            Editor::Jetbrains => JETBRAINS_LAUNCHERS
+                .iter()
+                .find(|launcher| check_command_exists(launcher))
+                .map(|launcher| launcher.to_string()),
+            Editor::Env => env_editor(),
+        }
+    }
+}
+
+fn find_program(command: &str, fallbacks: &[&str]) -> Option<String> {
+    if check_command_exists(command) {
+        return Some(command.to_string());
+    }
+    fallbacks
+        .iter()
+        .find(|path| Path::new(path).is_file())
+        .map(|path| path.to_string())
+}
+
+fn env_editor() -> Option<String> {
+    ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .map(|value| value.trim().to_string())
+        .find(|value| !value.is_empty())
+}
+
+/// An editor and whether it's installed
+#[derive(Debug, Clone, Serialize)]
+pub struct EditorInfo {
+    pub editor: Editor,
+    pub name: String,
+    /// The program that would be run (None when not installed)
+    pub program: Option<String>,
+}
+
+/// How a file was opened
+#[derive(Debug, Clone, Serialize)]
+pub struct OpenedEditor {
+    pub editor: Editor,
+    /// The command line that was run
+    pub command: String,
+    /// Integrated terminal running a terminal editor
+    pub terminal_id: Option<String>,
+}
+
+/// Single-quote `arg` for the shell
+fn quote(arg: &str) -> String {
+    format!("'{}'", arg.replace('\'', r"'\''"))
+}
+
+/// `path:line:column`, as far as known
+fn location(path: &str, line: Option<u32>, column: Option<u32>) -> String {
+    match (line, column) {
+        (Some(line), Some(column)) => format!("{}:{}:{}", path, line, column),
+        (Some(line), None) => format!("{}:{}", path, line),
+        _ => path.to_string(),
+    }
+}
+
+/// Arguments that open `path` at the position in a GUI editor
+fn gui_args(editor: Editor, path: &str, line: Option<u32>, column: Option<u32>) -> Vec<String> {
+    match (editor, line) {
+        (Editor::Vscode, Some(_)) => vec!["--goto".to_string(), location(path, line, column)],
+        (Editor::Zed, _) => vec![location(path, line, column)],
+        (Editor::Jetbrains, Some(line)) => {
+            let mut args = vec!["--line".to_string(), line.to_string()];
+            if let Some(column) = column {
+                args.extend(["--column".to_string(), column.to_string()]);
+            }
+            args.push(path.to_string());
+            args
+        }
+        _ => vec![path.to_string()],
+    }
+}
+
+/// The GUI editor behind an `$EDITOR` value like "code --wait"
+fn gui_editor_in(command: &str) -> Option<Editor> {
+    let program = command.split_whitespace().next()?;
+    let program = Path::new(program).file_name()?.to_str()?;
+    match program {
+        "code" => Some(Editor::Vscode),
+        "zed" => Some(Editor::Zed),
+        _ if JETBRAINS_LAUNCHERS.contains(&program) => Some(Editor::Jetbrains),
+        _ => None,
+    }
+}
+
+/// Shell command opening `path` at the position in a terminal editor
+fn terminal_command(command: &str, path: &str, line: Option<u32>, column: Option<u32>) -> String {
+    let program = command
+        .split_whitespace()
+        .next()
+        .and_then(|p| Path::new(p).file_name())
+        .and_then(|p| p.to_str())
+        .unwrap_or_default();
+    let Some(line) = line else {
+        return format!("{} {}", command, quote(path));
+    };
+    match program {
+        "hx" | "helix" | "micro" => {
+            format!("{} {}", command, quote(&location(path, Some(line), column)))
+        }
+        "nano" => format!(
+            "{} +{},{} {}",
+            command,
+            line,
+            column.unwrap_or(1),
+            quote(path)
+        ),
+        // vi, vim, nvim, emacs, kak and most others take +line
+        _ => format!("{} +{} {}", command, line, quote(path)),
+    }
+}
+
+/// The project preference that applies to `path` (the longest matching
+/// project root wins)
+fn project_editor(settings: &EditorSettings, path: &Path) -> Option<Editor> {
+    settings
+        .projects
+        .iter()
+        .filter(|(root, _)| path.starts_with(root))
+        .max_by_key(|(root, _)| root.len())
+        .and_then(|(_, id)| Editor::from_id(id))
+}
+
+/// The editor to use for `path` when none was asked for
+fn choose_editor(settings: &EditorSettings, path: &Path) -> Result<Editor, String> {
+    if let Some(editor) = project_editor(settings, path) {
+        return Ok(editor);
+    }
+    if let Some(editor) = Editor::from_id(&settings.preferred) {
+        return Ok(editor);
+    }
+    Editor::ALL
+        .into_iter()
+        .find(|editor| editor.program().is_some())
+        .ok_or_else(|| {
+            "No editor found. Install VS Code, Zed or a JetBrains IDE, or set $EDITOR".to_string()
+        })
+}
+
+/// Editors this app can open files in, and which are installed
+#[tauri::command]
+pub fn detect_editors() -> Vec<EditorInfo> {
+    Editor::ALL
+        .into_iter()
+        .map(|editor| EditorInfo {
+            editor,
+            name: editor.name().to_string(),
+            program: editor.program(),
+        })
+        .collect()
+}
+
+/// Open a file at a line and column (both 1-based) in an editor
+#[tauri::command]
+pub fn open_in_editor(
+    path: String,
+    line: Option<u32>,
+    column: Option<u32>,
+    editor: Option<Editor>,
+    app_handle: AppHandle,
+) -> Result<OpenedEditor, String> {
+    let file = Path::new(&path);
+    if !file.exists() {
+        return Err(format!("Path does not exist: {}", path));
+    }
+    if file.is_file() {
+        recent::record(&path);
+    }
+    let mut editor = match editor {
+        Some(editor) => editor,
+        None => choose_editor(&load_settings().editor, file)?,
+    };
+    let mut program = editor
+        .program()
+        .ok_or_else(|| format!("{} is not installed", editor.name()))?;
+
+    if editor == Editor::Env {
+        match gui_editor_in(&program) {
+            Some(gui) => {
+                editor = gui;
+                program = program
+                    .split_whitespace()
+                    .next()
+                    .unwrap_or_default()
+                    .to_string();
+            }
+            None => {
+                let command = terminal_command(&program, &path, line, column);
+                let cwd = file.parent().map(|dir| dir.to_string_lossy().into_owned());
+                let options = TerminalOptions {
+                    name: file
+                        .file_name()
+                        .map(|name| name.to_string_lossy().into_owned()),
+                    overrides: TerminalProfile {
+                        startup_commands: vec![format!("{}; exit", command)],
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                };
+                let terminal_id = TERMINAL_MANAGER
+                    .lock()
+                    .create(app_handle, 120, 40, cwd, options)?;
+                eprintln!("[Editor] Opened {} in terminal {}", path, terminal_id);
+                return Ok(OpenedEditor {
+                    editor,
+                    command,
+                    terminal_id: Some(terminal_id),
+                });
+            }
+        }
+    }
+
+    let args = gui_args(editor, &path, line, column);
+    let mut child = Command::new(&program)
+        .args(&args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to start {}: {}", program, e))?;
+    // The CLIs hand off to the running editor and exit
+    std::thread::spawn(move || {
+        let _ = child.wait();
+    });
+
+    let command = std::iter::once(program.as_str())
+        .chain(args.iter().map(String::as_str))
+        .map(quote)
+        .collect::<Vec<_>>()
+        .join(" ");
+    eprintln!("[Editor] {}", command);
+    Ok(OpenedEditor {
+        editor,
+        command,
+        terminal_id: None,
+    })
+}
+
+/// Set (or with None, clear) the editor used for files in a project
+#[tauri::command]
+pub fn set_project_editor(project: String, editor: Option<Editor>) -> Result<(), String> {
+    let store = SettingsStore::new()?;
+    let mut settings = store.load()?;
+    match editor {
+        Some(editor) => {
+            settings
+                .editor
+                .projects
+                .insert(project, editor.id().to_string());
+        }
+        None => {
+            settings.editor.projects.remove(&project);
+        }
+    }
+    store.save(&settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gui_args() {
+        assert_eq!(
+            gui_args(Editor::Vscode, "/p/a.rs", Some(12), Some(4)),
+            vec!["--goto", "/p/a.rs:12:4"]
+        );
+        assert_eq!(
+            gui_args(Editor::Vscode, "/p/a.rs", None, None),
+            vec!["/p/a.rs"]
+        );
+        assert_eq!(
+            gui_args(Editor::Zed, "/p/a.rs", Some(12), None),
+            vec!["/p/a.rs:12"]
+        );
+        assert_eq!(
+            gui_args(Editor::Jetbrains, "/p/a.rs", Some(12), Some(4)),
+            vec!["--line", "12", "--column", "4", "/p/a.rs"]
+        );
+    }
+
+    #[test]
+    fn test_env_editor_commands() {
+        assert_eq!(gui_editor_in("code --wait"), Some(Editor::Vscode));
+        assert_eq!(
+            gui_editor_in("/usr/local/bin/idea"),
+            Some(Editor::Jetbrains)
+        );
+        assert_eq!(gui_editor_in("nvim"), None);
+
+        assert_eq!(
+            terminal_command("nvim", "/p/a b.rs", Some(3), Some(7)),
+            "nvim +3 '/p/a b.rs'"
+        );
+        assert_eq!(
+            terminal_command("nano", "/p/a.rs", Some(3), None),
+            "nano +3,1 '/p/a.rs'"
+        );
+        assert_eq!(
+            terminal_command("hx", "/p/a.rs", Some(3), Some(7)),
+            "hx '/p/a.rs:3:7'"
+        );
+        assert_eq!(
+            terminal_command("vim", "/p/it's.rs", None, None),
+            r"vim '/p/it'\''s.rs'"
+        );
+    }
+
+    #[test]
+    fn test_project_preference() {
+        let mut settings = EditorSettings {
+            preferred: "zed".to_string(),
+            ..Default::default()
+        };
+        settings
+            .projects
+            .insert("/work/app".to_string(), "vscode".to_string());
+        settings
+            .projects
+            .insert("/work/app/crates/core".to_string(), "jetbrains".to_string());
+
+        let editor = |path: &str| choose_editor(&settings, Path::new(path)).unwrap();
+        assert_eq!(editor("/work/app/src/main.rs"), Editor::Vscode);
+        assert_eq!(editor("/work/app/crates/core/lib.rs"), Editor::Jetbrains);
+        // Path components, not string prefixes
+        assert_eq!(editor("/work/application/main.rs"), Editor::Zed);
+    }
+}
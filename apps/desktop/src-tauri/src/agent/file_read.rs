@@ -0,0 +1,501 @@
+//! Ranged file reads for the file viewer
+//!
+//! Agents can leave behind logs and dumps far too large to load whole.
+//! `read_file_content` reads a byte or line range (by default the first
+//! `MAX_READ_BYTES`) and reports the file's total size, its encoding and
+//! whether the content was cut off. `stream_file` sends a file in chunks
+//! over a channel and can keep following it as it grows, like `tail -f`.
+
+use crate::palette::recent;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use tauri::ipc::Channel;
+use uuid::Uuid;
+
+/// Most bytes returned by one read
+pub const MAX_READ_BYTES: u64 = 4 * 1024 * 1024;
+
+/// Bytes per streamed chunk
+const STREAM_CHUNK_BYTES: usize = 64 * 1024;
+
+/// How often a followed file is checked for new content
+const FOLLOW_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Bytes sniffed for NULs to tell binary files apart
+const SNIFF_BYTES: usize = 8000;
+
+/// Cancel flags of running streams (stream id -> cancelled)
+static STREAMS: Lazy<Mutex<HashMap<String, Arc<AtomicBool>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Part of a file to read. Line ranges take precedence over byte ranges.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ReadRange {
+    /// Byte offset to start at
+    pub offset: Option<u64>,
+    /// Most bytes to read (capped at `MAX_READ_BYTES`)
+    pub length: Option<u64>,
+    /// First line to read (1-based)
+    pub start_line: Option<u64>,
+    /// Most lines to read
+    pub line_count: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct FileContent {
+    /// Empty for binary files
+    pub content: String,
+    /// "utf-8", "utf-16le", "utf-16be", "utf-8-lossy" (invalid bytes
+    /// replaced) or "binary"
+    pub encoding: String,
+    pub total_size: u64,
+    /// Byte range the content came from
+    pub offset: u64,
+    pub end_offset: u64,
+    /// More of the file exists past `end_offset`
+    pub truncated: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FileChunk {
+    pub offset: u64,
+    pub data: String,
+    /// Reached the current end of the file (a followed stream keeps going)
+    pub eof: bool,
+}
+
+fn sniff_encoding(head: &[u8]) -> &'static str {
+    if head.starts_with(&[0xFF, 0xFE]) {
+        "utf-16le"
+    } else if head.starts_with(&[0xFE, 0xFF]) {
+        "utf-16be"
+    } else if head.iter().take(SNIFF_BYTES).any(|b| *b == 0) {
+        "binary"
+    } else {
+        "utf-8"
+    }
+}
+
+/// Trim a byte range read from the middle of a UTF-8 file to whole
+/// characters. Returns the bytes skipped at the start and the kept bytes.
+fn utf8_window(bytes: &[u8], at_start: bool, at_end: bool) -> (usize, &[u8]) {
+    let is_continuation = |b: u8| b & 0xC0 == 0x80;
+    let skip = if at_start {
+        0
+    } else {
+        bytes
+            .iter()
+            .take(3)
+            .take_while(|b| is_continuation(**b))
+            .count()
+    };
+    let mut end = bytes.len();
+    if !at_end {
+        // Drop a multi-byte character cut off at the end
+        if let Some(lead) = (end.saturating_sub(4)..end)
+            .rev()
+            .find(|&i| i >= skip && !is_continuation(bytes[i]))
+        {
+            let width = match bytes[lead] {
+                b if b >= 0xF0 => 4,
+                b if b >= 0xE0 => 3,
+                b if b >= 0xC0 => 2,
+                _ => 1,
+            };
+            if lead + width > end {
+                end = lead;
+            }
+        }
+    }
+    (skip, &bytes[skip..end.max(skip)])
+}
+
+fn decode_utf16(bytes: &[u8], little_endian: bool) -> String {
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|pair| {
+            if little_endian {
+                u16::from_le_bytes([pair[0], pair[1]])
+            } else {
+                u16::from_be_bytes([pair[0], pair[1]])
+            }
+        })
+        .collect();
+    String::from_utf16_lossy(&units)
+}
+
+/// Decode bytes read from `offset`, trimming partial characters at the edges
+fn decode(
+    bytes: &[u8],
+    encoding: &str,
+    offset: u64,
+    total_size: u64,
+) -> (String, &'static str, u64, u64) {
+    let end_offset = offset + bytes.len() as u64;
+    match encoding {
+        "binary" => (String::new(), "binary", offset, end_offset),
+        "utf-16le" | "utf-16be" => {
+            // Keep whole code units, skipping the BOM
+            let bom = if offset == 0 { 2.min(bytes.len()) } else { 0 };
+            let skip = bom + ((offset as usize + bom) % 2);
+            let body = &bytes[skip.min(bytes.len())..];
+            let body = &body[..body.len() - body.len() % 2];
+            let little = encoding == "utf-16le";
+            (
+                decode_utf16(body, little),
+                if little { "utf-16le" } else { "utf-16be" },
+                offset + skip as u64,
+                offset + (skip + body.len()) as u64,
+            )
+        }
+        _ => {
+            let (skip, kept) = utf8_window(bytes, offset == 0, end_offset >= total_size);
+            let start = offset + skip as u64;
+            let end = start + kept.len() as u64;
+            match std::str::from_utf8(kept) {
+                Ok(text) => (text.to_string(), "utf-8", start, end),
+                Err(_) => (
+                    String::from_utf8_lossy(kept).into_owned(),
+                    "utf-8-lossy",
+                    start,
+                    end,
+                ),
+            }
+        }
+    }
+}
+
+/// Read bytes `[offset, offset + length)`, capped at `MAX_READ_BYTES`
+fn read_bytes(file: &mut File, offset: u64, length: u64) -> Result<Vec<u8>, String> {
+    file.seek(SeekFrom::Start(offset))
+        .map_err(|e| e.to_string())?;
+    let mut bytes = Vec::new();
+    file.take(length.min(MAX_READ_BYTES))
+        .read_to_end(&mut bytes)
+        .map_err(|e| e.to_string())?;
+    Ok(bytes)
+}
+
+/// Byte range of `line_count` lines starting at 1-based `start_line`
+fn line_span(
+    file: &mut File,
+    start_line: u64,
+    line_count: Option<u64>,
+) -> Result<(u64, u64), String> {
+    file.seek(SeekFrom::Start(0)).map_err(|e| e.to_string())?;
+    let mut reader = BufReader::new(file);
+    let mut line = Vec::new();
+    let mut position = 0u64;
+    let mut start = None;
+    let mut lines_read = 0u64;
+    loop {
+        if start.is_none() && lines_read + 1 >= start_line {
+            start = Some(position);
+            lines_read = 0;
+        }
+        if let Some(start) = start {
+            let done = line_count.is_some_and(|count| lines_read >= count);
+            if done || position - start >= MAX_READ_BYTES {
+                break;
+            }
+        }
+        line.clear();
+        let read = reader
+            .read_until(b'\n', &mut line)
+            .map_err(|e| e.to_string())?;
+        if read == 0 {
+            break;
+        }
+        position += read as u64;
+        lines_read += 1;
+    }
+    let start = start.unwrap_or(position);
+    Ok((start, position.min(start + MAX_READ_BYTES)))
+}
+
+/// Read part of a file (see `ReadRange`)
+pub fn read_range(path: &Path, range: &ReadRange) -> Result<FileContent, String> {
+    let mut file = File::open(path).map_err(|e| e.to_string())?;
+    let total_size = file.metadata().map_err(|e| e.to_string())?.len();
+
+    let mut head = [0u8; SNIFF_BYTES];
+    let head_len = file.read(&mut head).map_err(|e| e.to_string())?;
+    let encoding = sniff_encoding(&head[..head_len]);
+
+    let (offset, length) = match range.start_line {
+        Some(start_line) if !encoding.starts_with("utf-16") => {
+            let (start, end) = line_span(&mut file, start_line.max(1), range.line_count)?;
+            (start, end - start)
+        }
+        _ => {
+            let offset = range.offset.unwrap_or(0).min(total_size);
+            (offset, range.length.unwrap_or(MAX_READ_BYTES))
+        }
+    };
+    let bytes = read_bytes(&mut file, offset, length)?;
+    let (content, encoding, offset, end_offset) = decode(&bytes, encoding, offset, total_size);
+    Ok(FileContent {
+        content,
+        encoding: encoding.to_string(),
+        total_size,
+        offset,
+        end_offset,
+        truncated: end_offset < total_size,
+    })
+}
+
+/// Send `path` from `offset` in chunks until the end, then (if `follow`)
+/// keep sending what's appended until cancelled
+fn pump(
+    path: &Path,
+    mut offset: u64,
+    follow: bool,
+    cancelled: &AtomicBool,
+    channel: &Channel<FileChunk>,
+) -> Result<(), String> {
+    let mut file = File::open(path).map_err(|e| e.to_string())?;
+    let mut buffer = vec![0u8; STREAM_CHUNK_BYTES];
+    // Bytes of a character split across chunks
+    let mut carry: Vec<u8> = Vec::new();
+    while !cancelled.load(Ordering::SeqCst) {
+        let size = file.metadata().map_err(|e| e.to_string())?.len();
+        if size < offset {
+            // Truncated (log rotation): start over
+            offset = 0;
+            carry.clear();
+        }
+        file.seek(SeekFrom::Start(offset + carry.len() as u64))
+            .map_err(|e| e.to_string())?;
+        let read = file.read(&mut buffer).map_err(|e| e.to_string())?;
+        if read == 0 {
+            if !follow {
+                // Bytes of an invalid character left at the end
+                if !carry.is_empty() {
+                    channel
+                        .send(FileChunk {
+                            offset,
+                            data: String::from_utf8_lossy(&carry).into_owned(),
+                            eof: true,
+                        })
+                        .map_err(|e| e.to_string())?;
+                }
+                break;
+            }
+            thread::sleep(FOLLOW_POLL_INTERVAL);
+            continue;
+        }
+        carry.extend_from_slice(&buffer[..read]);
+        let at_eof = offset + carry.len() as u64 >= size;
+        let (_, kept) = utf8_window(&carry, true, false);
+        let kept_len = kept.len();
+        let data = String::from_utf8_lossy(kept).into_owned();
+        channel
+            .send(FileChunk {
+                offset,
+                data,
+                eof: at_eof && kept_len == carry.len(),
+            })
+            .map_err(|e| e.to_string())?;
+        offset += kept_len as u64;
+        carry.drain(..kept_len);
+    }
+    Ok(())
+}
+
+/// Read part of a file (the first 4 MB by default), with its size,
+/// encoding and whether more remains
+#[tauri::command]
+pub fn read_file_content(path: String, range: Option<ReadRange>) -> Result<FileContent, String> {
+    let file_path = Path::new(&path);
+
+    if !file_path.exists() {
+        return Err(format!("File does not exist: {}", path));
+    }
+
+    if !file_path.is_file() {
+        return Err(format!("Path is not a file: {}", path));
+    }
+
+    recent::record(&path);
+    read_range(file_path, &range.unwrap_or_default())
+}
+
+/// Stream a file over `on_chunk`, starting at `offset`. With `follow`, keep
+/// streaming appended content until `stop_file_stream`. Returns the stream id.
+#[tauri::command]
+pub fn stream_file(
+    path: String,
+    offset: Option<u64>,
+    follow: Option<bool>,
+    on_chunk: Channel<FileChunk>,
+) -> Result<String, String> {
+    if !Path::new(&path).is_file() {
+        return Err(format!("Path is not a file: {}", path));
+    }
+    let stream_id = Uuid::new_v4().to_string();
+    let cancelled = Arc::new(AtomicBool::new(false));
+    STREAMS.lock().insert(stream_id.clone(), cancelled.clone());
+
+    let id = stream_id.clone();
+    thread::spawn(move || {
+        let result = pump(
+            Path::new(&path),
+            offset.unwrap_or(0),
+            follow.unwrap_or(false),
+            &cancelled,
+            &on_chunk,
+        );
+        if let Err(e) = result {
+            eprintln!("[Files] Stream of {} ended: {}", path, e);
+        }
+        STREAMS.lock().remove(&id);
+    });
+    Ok(stream_id)
+}
+
+/// Stop a file stream. Returns false if it already ended.
+#[tauri::command]
+pub fn stop_file_stream(stream_id: String) -> bool {
+    match STREAMS.lock().remove(&stream_id) {
+        Some(cancelled) => {
+            cancelled.store(true, Ordering::SeqCst);
+            true
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(dir: &tempfile::TempDir, name: &str, bytes: &[u8]) -> std::path::PathBuf {
+        let path = dir.path().join(name);
+        std::fs::write(&path, bytes).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_byte_range_keeps_whole_characters() {
+        let dir = tempfile::tempdir().unwrap();
+        // "é" is bytes 3..5 and "€" bytes 5..8: a range starting or ending
+        // inside a character drops it
+        let path = write(&dir, "a.txt", "abcé€xyz".as_bytes());
+        let read = read_range(
+            &path,
+            &ReadRange {
+                offset: Some(4),
+                length: Some(4),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(read.content, "€");
+        assert_eq!((read.offset, read.end_offset), (5, 8));
+
+        let read = read_range(
+            &path,
+            &ReadRange {
+                offset: Some(0),
+                length: Some(7),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(read.content, "abcé");
+        assert_eq!(read.end_offset, 5);
+
+        let read = read_range(
+            &path,
+            &ReadRange {
+                offset: Some(3),
+                length: Some(5),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(read.content, "é€");
+        assert_eq!(read.encoding, "utf-8");
+        assert_eq!(read.total_size, 11);
+        assert!(read.truncated);
+
+        let read = read_range(&path, &ReadRange::default()).unwrap();
+        assert_eq!(read.content, "abcé€xyz");
+        assert!(!read.truncated);
+    }
+
+    #[test]
+    fn test_line_range() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write(&dir, "log.txt", b"one\ntwo\nthree\nfour");
+        let read = read_range(
+            &path,
+            &ReadRange {
+                start_line: Some(2),
+                line_count: Some(2),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(read.content, "two\nthree\n");
+        assert_eq!((read.offset, read.end_offset), (4, 14));
+        assert!(read.truncated);
+
+        let read = read_range(
+            &path,
+            &ReadRange {
+                start_line: Some(4),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(read.content, "four");
+        assert!(!read.truncated);
+
+        let read = read_range(
+            &path,
+            &ReadRange {
+                start_line: Some(9),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(read.content, "");
+    }
+
+    #[test]
+    fn test_encodings() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut utf16 = vec![0xFF, 0xFE];
+        utf16.extend("hi".encode_utf16().flat_map(|u| u.to_le_bytes()));
+        let read = read_range(&write(&dir, "u16.txt", &utf16), &ReadRange::default()).unwrap();
+        assert_eq!(
+            (read.content.as_str(), read.encoding.as_str()),
+            ("hi", "utf-16le")
+        );
+
+        let read = read_range(&write(&dir, "a.bin", &[1, 0, 2, 3]), &ReadRange::default()).unwrap();
+        assert_eq!(
+            (read.content.as_str(), read.encoding.as_str()),
+            ("", "binary")
+        );
+
+        let read = read_range(
+            &write(&dir, "latin1.txt", b"caf\xe9!"),
+            &ReadRange::default(),
+        )
+        .unwrap();
+        assert_eq!(read.encoding, "utf-8-lossy");
+        assert_eq!(read.content, "caf\u{FFFD}!");
+    }
+}
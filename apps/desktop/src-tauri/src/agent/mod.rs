@@ -1,3 +1,13 @@
 pub mod commands;
+pub mod context_files;
+pub mod dotenv;
+pub mod editor;
+pub mod file_read;
 pub mod manager;
+pub mod project;
+pub mod scratch;
+pub mod search;
+pub mod snapshot;
 pub mod stop_hook;
+pub mod tree;
+pub mod watcher;
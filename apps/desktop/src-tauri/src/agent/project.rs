@@ -0,0 +1,690 @@
+//! Project detection
+//!
+//! Looks at the manifests in a project root (Cargo.toml, package.json,
+//! pyproject.toml, go.mod, Makefile) to work out its languages, frameworks,
+//! scripts, build and test commands and workspace members, and asks git for
+//! the branch, remotes and dirty state. The UI shows this in the project
+//! header; `ProjectInfo::prompt_context` tells agents how to build and test
+//! the repository.
+
+use crate::agent::context_files::{find_context_files, ProjectContextFile};
+use crate::agent::dotenv::find_env_files;
+use crate::settings::load_settings;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+/// Most scripts listed in the agent prompt
+const MAX_PROMPT_SCRIPTS: usize = 12;
+
+/// Dependencies that identify a framework (dependency name, framework)
+const JS_FRAMEWORKS: &[(&str, &str)] = &[
+    ("next", "next"),
+    ("nuxt", "nuxt"),
+    ("@remix-run/react", "remix"),
+    ("@sveltejs/kit", "sveltekit"),
+    ("svelte", "svelte"),
+    ("astro", "astro"),
+    ("vue", "vue"),
+    ("react", "react"),
+    ("@tauri-apps/api", "tauri"),
+    ("electron", "electron"),
+    ("express", "express"),
+    ("hono", "hono"),
+    ("vite", "vite"),
+];
+const RUST_FRAMEWORKS: &[(&str, &str)] = &[
+    ("tauri", "tauri"),
+    ("axum", "axum"),
+    ("actix-web", "actix-web"),
+    ("rocket", "rocket"),
+    ("leptos", "leptos"),
+    ("bevy", "bevy"),
+];
+const PYTHON_FRAMEWORKS: &[(&str, &str)] = &[
+    ("django", "django"),
+    ("fastapi", "fastapi"),
+    ("flask", "flask"),
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectInfo {
+    pub name: String,
+    pub path: String,
+    pub git_branch: Option<String>,
+    pub git_status: Option<String>,
+    /// Project .env files found in the root (see `dotenv.files`)
+    pub env_files: Vec<String>,
+    /// AGENTS.md, CLAUDE.md and friends found in the root
+    pub context_files: Vec<ProjectContextFile>,
+    /// Main language (of the first toolchain found)
+    pub language: Option<String>,
+    pub toolchains: Vec<Toolchain>,
+    pub frameworks: Vec<String>,
+    pub scripts: Vec<ProjectScript>,
+    pub build_command: Option<String>,
+    pub test_command: Option<String>,
+    /// Workspace member globs of a monorepo
+    pub workspaces: Vec<String>,
+    pub git: Option<GitMetadata>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Toolchain {
+    pub language: String,
+    /// "cargo", "npm", "pnpm", "yarn", "bun", "uv", "poetry", "pip" or "go"
+    pub tool: String,
+    /// Manifest it was detected from
+    pub manifest: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ProjectScript {
+    pub name: String,
+    /// Shell command that runs it
+    pub command: String,
+    /// File defining it
+    pub source: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GitRemote {
+    pub name: String,
+    pub url: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct GitMetadata {
+    /// None on a detached HEAD
+    pub branch: Option<String>,
+    /// Commit hash of HEAD (None before the first commit)
+    pub head: Option<String>,
+    pub upstream: Option<String>,
+    pub ahead: u32,
+    pub behind: u32,
+    pub dirty: bool,
+    pub changed_files: usize,
+    pub remotes: Vec<GitRemote>,
+}
+
+/// What the manifests say, before git and .env files are added
+#[derive(Debug, Default, PartialEq)]
+struct Detection {
+    toolchains: Vec<Toolchain>,
+    frameworks: Vec<String>,
+    scripts: Vec<ProjectScript>,
+    build_command: Option<String>,
+    test_command: Option<String>,
+    workspaces: Vec<String>,
+}
+
+impl Detection {
+    fn toolchain(&mut self, language: &str, tool: &str, manifest: &str) {
+        self.toolchains.push(Toolchain {
+            language: language.to_string(),
+            tool: tool.to_string(),
+            manifest: manifest.to_string(),
+        });
+    }
+
+    fn frameworks(&mut self, known: &[(&str, &str)], has_dependency: impl Fn(&str) -> bool) {
+        for (dependency, framework) in known {
+            if has_dependency(dependency) && !self.frameworks.iter().any(|f| f == framework) {
+                self.frameworks.push(framework.to_string());
+            }
+        }
+    }
+
+    fn script(&mut self, name: &str, command: String, source: &str) {
+        self.scripts.push(ProjectScript {
+            name: name.to_string(),
+            command,
+            source: source.to_string(),
+        });
+    }
+
+    /// Fill in build/test commands a toolchain implies, unless a script
+    /// already provided them
+    fn defaults(&mut self, build: Option<&str>, test: Option<&str>) {
+        if self.build_command.is_none() {
+            self.build_command = build.map(str::to_string);
+        }
+        if self.test_command.is_none() {
+            self.test_command = test.map(str::to_string);
+        }
+    }
+}
+
+fn read_toml(path: &Path) -> Option<toml::Value> {
+    toml::from_str(&fs::read_to_string(path).ok()?).ok()
+}
+
+fn string_list(value: Option<&toml::Value>) -> Vec<String> {
+    value
+        .and_then(|v| v.as_array())
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|i| i.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Package manager of a JS project: `packageManager`, then the lockfile
+fn js_package_manager(root: &Path, package: &serde_json::Value) -> &'static str {
+    let declared = package["packageManager"].as_str().unwrap_or_default();
+    for tool in ["pnpm", "yarn", "bun", "npm"] {
+        if declared.starts_with(&format!("{}@", tool)) {
+            return tool;
+        }
+    }
+    if root.join("pnpm-lock.yaml").exists() {
+        "pnpm"
+    } else if root.join("yarn.lock").exists() {
+        "yarn"
+    } else if root.join("bun.lock").exists() || root.join("bun.lockb").exists() {
+        "bun"
+    } else {
+        "npm"
+    }
+}
+
+fn detect_package_json(root: &Path, detection: &mut Detection) {
+    let Some(package) = fs::read_to_string(root.join("package.json"))
+        .ok()
+        .and_then(|json| serde_json::from_str::<serde_json::Value>(&json).ok())
+    else {
+        return;
+    };
+    let has_dependency = |name: &str| {
+        ["dependencies", "devDependencies", "peerDependencies"]
+            .iter()
+            .any(|field| package[field].get(name).is_some())
+    };
+    let language = if root.join("tsconfig.json").exists() || has_dependency("typescript") {
+        "typescript"
+    } else {
+        "javascript"
+    };
+    let manager = js_package_manager(root, &package);
+    detection.toolchain(language, manager, "package.json");
+    detection.frameworks(JS_FRAMEWORKS, has_dependency);
+
+    if let Some(scripts) = package["scripts"].as_object() {
+        for name in scripts.keys() {
+            detection.script(name, format!("{} run {}", manager, name), "package.json");
+        }
+        if scripts.contains_key("build") {
+            detection.build_command = Some(format!("{} run build", manager));
+        }
+        if scripts.contains_key("test") {
+            detection.test_command = Some(format!("{} run test", manager));
+        }
+    }
+
+    // `workspaces` is a list, or an object with `packages` (yarn)
+    let workspaces = match &package["workspaces"] {
+        serde_json::Value::Array(items) => items.clone(),
+        other => other["packages"].as_array().cloned().unwrap_or_default(),
+    };
+    detection.workspaces.extend(
+        workspaces
+            .iter()
+            .filter_map(|w| w.as_str().map(str::to_string)),
+    );
+    if let Some(pnpm) = fs::read_to_string(root.join("pnpm-workspace.yaml"))
+        .ok()
+        .and_then(|yaml| serde_yaml::from_str::<serde_yaml::Value>(&yaml).ok())
+    {
+        if let Some(packages) = pnpm["packages"].as_sequence() {
+            detection.workspaces.extend(
+                packages
+                    .iter()
+                    .filter_map(|p| p.as_str().map(str::to_string)),
+            );
+        }
+    }
+}
+
+fn detect_cargo(root: &Path, detection: &mut Detection) {
+    let Some(manifest) = read_toml(&root.join("Cargo.toml")) else {
+        return;
+    };
+    detection.toolchain("rust", "cargo", "Cargo.toml");
+    let has_dependency = |name: &str| {
+        ["dependencies", "dev-dependencies", "build-dependencies"]
+            .iter()
+            .any(|table| manifest.get(table).and_then(|t| t.get(name)).is_some())
+            || manifest
+                .get("workspace")
+                .and_then(|w| w.get("dependencies"))
+                .and_then(|t| t.get(name))
+                .is_some()
+    };
+    detection.frameworks(RUST_FRAMEWORKS, has_dependency);
+
+    match manifest.get("workspace") {
+        Some(workspace) => {
+            detection
+                .workspaces
+                .extend(string_list(workspace.get("members")));
+            detection.defaults(
+                Some("cargo build --workspace"),
+                Some("cargo test --workspace"),
+            );
+        }
+        None => detection.defaults(Some("cargo build"), Some("cargo test")),
+    }
+}
+
+fn detect_pyproject(root: &Path, detection: &mut Detection) {
+    let Some(manifest) = read_toml(&root.join("pyproject.toml")) else {
+        return;
+    };
+    let tool_table = |name: &str| manifest.get("tool").and_then(|t| t.get(name));
+    let (tool, runner) = if root.join("uv.lock").exists() || tool_table("uv").is_some() {
+        ("uv", "uv run ")
+    } else if tool_table("poetry").is_some() {
+        ("poetry", "poetry run ")
+    } else {
+        ("pip", "")
+    };
+    detection.toolchain("python", tool, "pyproject.toml");
+
+    let mut dependencies = string_list(manifest.get("project").and_then(|p| p.get("dependencies")));
+    if let Some(poetry) = tool_table("poetry")
+        .and_then(|p| p.get("dependencies"))
+        .and_then(|d| d.as_table())
+    {
+        dependencies.extend(poetry.keys().cloned());
+    }
+    // PEP 508 specs like "fastapi[all]>=0.110"
+    let dependency_names: Vec<String> = dependencies
+        .iter()
+        .map(|d| {
+            d.split(|c: char| !(c.is_alphanumeric() || c == '-' || c == '_' || c == '.'))
+                .next()
+                .unwrap_or_default()
+                .to_lowercase()
+        })
+        .collect();
+    detection.frameworks(PYTHON_FRAMEWORKS, |name| {
+        dependency_names.iter().any(|d| d == name)
+    });
+
+    for table in [
+        manifest.get("project").and_then(|p| p.get("scripts")),
+        tool_table("poetry").and_then(|p| p.get("scripts")),
+    ]
+    .into_iter()
+    .flatten()
+    .filter_map(|t| t.as_table())
+    {
+        for name in table.keys() {
+            detection.script(name, format!("{}{}", runner, name), "pyproject.toml");
+        }
+    }
+
+    if let Some(members) = tool_table("uv")
+        .and_then(|uv| uv.get("workspace"))
+        .and_then(|w| w.get("members"))
+    {
+        detection.workspaces.extend(string_list(Some(members)));
+    }
+    let uses_pytest = tool_table("pytest").is_some()
+        || dependencies.iter().any(|d| d.starts_with("pytest"))
+        || root.join("tests").is_dir();
+    if uses_pytest {
+        detection.defaults(None, Some(&format!("{}pytest", runner)));
+    }
+}
+
+fn detect_go(root: &Path, detection: &mut Detection) {
+    if !root.join("go.mod").is_file() {
+        return;
+    }
+    detection.toolchain("go", "go", "go.mod");
+    detection.defaults(Some("go build ./..."), Some("go test ./..."));
+    if let Ok(work) = fs::read_to_string(root.join("go.work")) {
+        // `use ./a` or a `use ( ... )` block
+        let mut in_block = false;
+        for line in work.lines().map(str::trim) {
+            if line.starts_with("use (") {
+                in_block = true;
+            } else if in_block && line == ")" {
+                in_block = false;
+            } else if let Some(dir) = line.strip_prefix("use ").filter(|_| !in_block) {
+                detection.workspaces.push(dir.trim().to_string());
+            } else if in_block && !line.is_empty() && !line.starts_with("//") {
+                detection.workspaces.push(line.to_string());
+            }
+        }
+    }
+}
+
+/// Make targets, skipping special (`.PHONY`) and pattern targets and
+/// variable assignments
+fn makefile_targets(content: &str) -> Vec<String> {
+    let mut targets: Vec<String> = Vec::new();
+    for line in content.lines() {
+        if line.starts_with(['\t', ' ', '.', '#']) {
+            continue;
+        }
+        let Some((names, rest)) = line.split_once(':') else {
+            continue;
+        };
+        if rest.starts_with('=') || names.contains('=') {
+            continue;
+        }
+        for name in names.split_whitespace() {
+            if !name.contains(['%', '$']) && !targets.iter().any(|t| t == name) {
+                targets.push(name.to_string());
+            }
+        }
+    }
+    targets
+}
+
+fn detect_makefile(root: &Path, detection: &mut Detection) {
+    let Ok(content) = fs::read_to_string(root.join("Makefile")) else {
+        return;
+    };
+    for target in makefile_targets(&content) {
+        if target == "build" && detection.build_command.is_none() {
+            detection.build_command = Some("make build".to_string());
+        }
+        if target == "test" && detection.test_command.is_none() {
+            detection.test_command = Some("make test".to_string());
+        }
+        detection.script(&target, format!("make {}", target), "Makefile");
+    }
+}
+
+/// Detect toolchains, frameworks, scripts and workspaces from the manifests
+/// in `root`. Explicit scripts (package.json, Makefile) win over toolchain
+/// defaults for the build and test commands.
+fn detect(root: &Path) -> Detection {
+    let mut detection = Detection::default();
+    detect_package_json(root, &mut detection);
+    detect_makefile(root, &mut detection);
+    detect_cargo(root, &mut detection);
+    detect_pyproject(root, &mut detection);
+    detect_go(root, &mut detection);
+    detection
+}
+
+/// Parse `git status --porcelain=v2 --branch`
+fn parse_status_v2(output: &str) -> GitMetadata {
+    let mut git = GitMetadata::default();
+    for line in output.lines() {
+        if let Some(header) = line.strip_prefix("# ") {
+            let (key, value) = header.split_once(' ').unwrap_or((header, ""));
+            match key {
+                "branch.oid" if value != "(initial)" => git.head = Some(value.to_string()),
+                "branch.head" if value != "(detached)" => git.branch = Some(value.to_string()),
+                "branch.upstream" => git.upstream = Some(value.to_string()),
+                "branch.ab" => {
+                    for count in value.split_whitespace() {
+                        if let Some(ahead) = count.strip_prefix('+') {
+                            git.ahead = ahead.parse().unwrap_or(0);
+                        } else if let Some(behind) = count.strip_prefix('-') {
+                            git.behind = behind.parse().unwrap_or(0);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        } else if !line.is_empty() && !line.starts_with('!') {
+            git.changed_files += 1;
+        }
+    }
+    git.dirty = git.changed_files > 0;
+    git
+}
+
+/// Parse `git remote -v`, one entry per remote (the fetch URL)
+fn parse_remotes(output: &str) -> Vec<GitRemote> {
+    let mut remotes: Vec<GitRemote> = Vec::new();
+    for line in output.lines() {
+        let mut parts = line.split_whitespace();
+        let (Some(name), Some(url)) = (parts.next(), parts.next()) else {
+            continue;
+        };
+        if !remotes.iter().any(|r| r.name == name) {
+            remotes.push(GitRemote {
+                name: name.to_string(),
+                url: url.to_string(),
+            });
+        }
+    }
+    remotes
+}
+
+fn git_output(path: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .current_dir(path)
+        .args(args)
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Branch, remotes and dirty state (None outside a git repository)
+pub fn git_metadata(path: &Path) -> Option<GitMetadata> {
+    let status = git_output(path, &["status", "--porcelain=v2", "--branch"])?;
+    let mut git = parse_status_v2(&status);
+    git.remotes = git_output(path, &["remote", "-v"])
+        .map(|out| parse_remotes(&out))
+        .unwrap_or_default();
+    Some(git)
+}
+
+/// Everything known about the project at `path`
+pub fn project_info(path: &Path) -> ProjectInfo {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "Unknown".to_string());
+    let detection = detect(path);
+    let git = git_metadata(path);
+
+    ProjectInfo {
+        name,
+        path: path.to_string_lossy().to_string(),
+        git_branch: git.as_ref().and_then(|g| g.branch.clone()),
+        git_status: git
+            .as_ref()
+            .map(|g| if g.dirty { "modified" } else { "clean" }.to_string()),
+        env_files: find_env_files(path, &load_settings().dotenv),
+        context_files: find_context_files(path),
+        language: detection.toolchains.first().map(|t| t.language.clone()),
+        toolchains: detection.toolchains,
+        frameworks: detection.frameworks,
+        scripts: detection.scripts,
+        build_command: detection.build_command,
+        test_command: detection.test_command,
+        workspaces: detection.workspaces,
+        git,
+    }
+}
+
+impl ProjectInfo {
+    /// "How to build and test this repo" section for agent prompts. None if
+    /// nothing useful was detected.
+    pub fn prompt_context(&self) -> Option<String> {
+        if self.toolchains.is_empty() && self.scripts.is_empty() && self.git.is_none() {
+            return None;
+        }
+        let mut out = format!("### Project: {}\n\n", self.name);
+        if !self.toolchains.is_empty() {
+            let toolchains: Vec<String> = self
+                .toolchains
+                .iter()
+                .map(|t| format!("{} ({})", t.language, t.tool))
+                .collect();
+            out.push_str(&format!("- **Languages:** {}\n", toolchains.join(", ")));
+        }
+        if !self.frameworks.is_empty() {
+            out.push_str(&format!(
+                "- **Frameworks:** {}\n",
+                self.frameworks.join(", ")
+            ));
+        }
+        if let Some(build) = &self.build_command {
+            out.push_str(&format!("- **Build:** `{}`\n", build));
+        }
+        if let Some(test) = &self.test_command {
+            out.push_str(&format!("- **Test:** `{}`\n", test));
+        }
+        if !self.scripts.is_empty() {
+            let mut scripts: Vec<String> = self
+                .scripts
+                .iter()
+                .take(MAX_PROMPT_SCRIPTS)
+                .map(|s| format!("`{}`", s.command))
+                .collect();
+            if self.scripts.len() > MAX_PROMPT_SCRIPTS {
+                scripts.push(format!(
+                    "and {} more",
+                    self.scripts.len() - MAX_PROMPT_SCRIPTS
+                ));
+            }
+            out.push_str(&format!("- **Scripts:** {}\n", scripts.join(", ")));
+        }
+        if !self.workspaces.is_empty() {
+            out.push_str(&format!(
+                "- **Workspaces:** {}\n",
+                self.workspaces.join(", ")
+            ));
+        }
+        if let Some(git) = &self.git {
+            out.push_str(&format!(
+                "- **Git:** branch `{}`, {}\n",
+                git.branch.as_deref().unwrap_or("(detached)"),
+                if git.dirty {
+                    format!("{} uncommitted changes", git.changed_files)
+                } else {
+                    "clean".to_string()
+                }
+            ));
+        }
+        Some(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_js_monorepo() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::write(
+            root.join("package.json"),
+            r#"{
+                "workspaces": { "packages": ["apps/*"] },
+                "scripts": { "dev": "next dev", "test": "vitest" },
+                "dependencies": { "next": "15", "react": "19" },
+                "devDependencies": { "typescript": "5" }
+            }"#,
+        )
+        .unwrap();
+        fs::write(root.join("pnpm-lock.yaml"), "").unwrap();
+        fs::write(
+            root.join("pnpm-workspace.yaml"),
+            "packages:\n  - packages/*\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("Makefile"),
+            ".PHONY: build test\nCC := gcc\nbuild: deps\n\tpnpm build\ntest:\n\tpnpm test\n%.o: %.c\n",
+        )
+        .unwrap();
+
+        let detection = detect(root);
+        assert_eq!(
+            detection.toolchains,
+            vec![Toolchain {
+                language: "typescript".to_string(),
+                tool: "pnpm".to_string(),
+                manifest: "package.json".to_string(),
+            }]
+        );
+        assert_eq!(detection.frameworks, vec!["next", "react"]);
+        assert_eq!(detection.workspaces, vec!["apps/*", "packages/*"]);
+        assert_eq!(detection.test_command.as_deref(), Some("pnpm run test"));
+        assert_eq!(detection.build_command.as_deref(), Some("make build"));
+        let commands: Vec<&str> = detection
+            .scripts
+            .iter()
+            .map(|s| s.command.as_str())
+            .collect();
+        assert_eq!(
+            commands,
+            vec!["pnpm run dev", "pnpm run test", "make build", "make test"]
+        );
+    }
+
+    #[test]
+    fn test_detect_cargo_and_python() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::write(
+            root.join("Cargo.toml"),
+            "[workspace]\nmembers = [\"crates/*\"]\n\n[workspace.dependencies]\naxum = \"0.8\"\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("pyproject.toml"),
+            "[project]\nname = \"api\"\ndependencies = [\"FastAPI[all]>=0.110\", \"pytest\"]\n\n[project.scripts]\nserve = \"api:main\"\n\n[tool.uv]\n",
+        )
+        .unwrap();
+
+        let detection = detect(root);
+        let languages: Vec<&str> = detection
+            .toolchains
+            .iter()
+            .map(|t| t.tool.as_str())
+            .collect();
+        assert_eq!(languages, vec!["cargo", "uv"]);
+        assert_eq!(detection.frameworks, vec!["axum", "fastapi"]);
+        assert_eq!(
+            detection.build_command.as_deref(),
+            Some("cargo build --workspace")
+        );
+        assert_eq!(
+            detection.test_command.as_deref(),
+            Some("cargo test --workspace")
+        );
+        assert_eq!(detection.workspaces, vec!["crates/*"]);
+        assert_eq!(detection.scripts[0].command, "uv run serve");
+    }
+
+    #[test]
+    fn test_parse_git_output() {
+        let git = parse_status_v2(
+            "# branch.oid abc123\n# branch.head main\n# branch.upstream origin/main\n# branch.ab +2 -1\n1 .M N... 100644 100644 100644 a b src/lib.rs\n? new.txt\n! ignored.log\n",
+        );
+        assert_eq!(git.branch.as_deref(), Some("main"));
+        assert_eq!(git.head.as_deref(), Some("abc123"));
+        assert_eq!(git.upstream.as_deref(), Some("origin/main"));
+        assert_eq!((git.ahead, git.behind), (2, 1));
+        assert_eq!(git.changed_files, 2);
+        assert!(git.dirty);
+
+        let fresh = parse_status_v2("# branch.oid (initial)\n# branch.head (detached)\n");
+        assert_eq!((fresh.head, fresh.branch, fresh.dirty), (None, None, false));
+
+        let remotes = parse_remotes(
+            "origin\tgit@github.com:a/b.git (fetch)\norigin\tgit@github.com:a/b.git (push)\nup\thttps://x/y (fetch)\n",
+        );
+        assert_eq!(remotes.len(), 2);
+        assert_eq!(remotes[1].url, "https://x/y");
+    }
+}
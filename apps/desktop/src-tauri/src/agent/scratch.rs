@@ -0,0 +1,232 @@
+//! Per-session scratch directories
+//!
+//! Every session gets `~/.crafter-code/scratch/<session_id>/` for temporary
+//! artifacts (test output, fixtures, throwaway scripts), so agents stop
+//! leaving them in the repository. The coordination prompt advertises it,
+//! and agent processes and the terminals they open get it as
+//! `CRAFTER_SCRATCH_DIR`. It's deleted with its session; a background sweep
+//! keeps each directory under `scratch.max_mb` by removing its oldest files,
+//! and removes the directories of sessions that are long gone.
+
+use crate::settings::load_settings;
+use crate::storage::files_under;
+use crate::AppState;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Manager};
+
+/// Environment variable agents and their terminals find the directory in
+pub const ENV_VAR: &str = "CRAFTER_SCRATCH_DIR";
+
+/// How often the background sweep enforces quotas
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Directories of sessions that aren't live are removed once nothing in
+/// them has changed for this long (saved sessions may still be resumed)
+const ORPHAN_AFTER: Duration = Duration::from_secs(7 * 24 * 3600);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScratchDir {
+    pub path: String,
+    pub bytes: u64,
+    /// 0 = no limit
+    pub max_bytes: u64,
+}
+
+/// ~/.crafter-code/scratch
+fn root() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".crafter-code").join("scratch"))
+}
+
+/// Session ids become directory names; anything that could leave the root
+/// is refused
+fn dir_for(root: &Path, session_id: &str) -> Option<PathBuf> {
+    let safe = !session_id.is_empty()
+        && session_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    safe.then(|| root.join(session_id))
+}
+
+/// A session's scratch directory, or `None` when scratch directories are
+/// turned off. It may not exist yet.
+pub fn path(session_id: &str) -> Option<PathBuf> {
+    if !load_settings().scratch.enabled {
+        return None;
+    }
+    dir_for(&root()?, session_id)
+}
+
+/// Create a session's scratch directory, returning it
+pub fn provision(session_id: &str) -> Option<PathBuf> {
+    let dir = path(session_id)?;
+    match fs::create_dir_all(&dir) {
+        Ok(()) => Some(dir),
+        Err(e) => {
+            eprintln!("[Scratch] Failed to create {}: {}", dir.display(), e);
+            None
+        }
+    }
+}
+
+/// Delete a session's scratch directory and everything in it
+pub fn remove(session_id: &str) {
+    let Some(dir) = root().and_then(|root| dir_for(&root, session_id)) else {
+        return;
+    };
+    if dir.exists() {
+        if let Err(e) = fs::remove_dir_all(&dir) {
+            eprintln!("[Scratch] Failed to remove {}: {}", dir.display(), e);
+        }
+    }
+}
+
+/// Total size of the files under `dir`
+pub fn dir_size(dir: &Path) -> u64 {
+    files_under(dir).iter().map(|file| file.bytes).sum()
+}
+
+/// Remove the oldest files under `dir` until it fits in `max_bytes`;
+/// returns the bytes freed
+pub fn enforce_quota(dir: &Path, max_bytes: u64) -> u64 {
+    let mut files = files_under(dir);
+    let mut total: u64 = files.iter().map(|file| file.bytes).sum();
+    if total <= max_bytes {
+        return 0;
+    }
+    files.sort_by_key(|file| file.modified);
+    let mut freed = 0;
+    for file in files {
+        if total <= max_bytes {
+            break;
+        }
+        if fs::remove_file(&file.path).is_ok() {
+            total -= file.bytes;
+            freed += file.bytes;
+        }
+    }
+    freed
+}
+
+/// When anything under `dir` last changed
+fn last_modified(dir: &Path) -> SystemTime {
+    let own = fs::metadata(dir)
+        .and_then(|metadata| metadata.modified())
+        .unwrap_or(SystemTime::UNIX_EPOCH);
+    files_under(dir)
+        .into_iter()
+        .map(|file| file.modified)
+        .fold(own, SystemTime::max)
+}
+
+fn sweep(state: &AppState, root: &Path, max_bytes: u64) {
+    let Ok(entries) = fs::read_dir(root) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let dir = entry.path();
+        if !dir.is_dir() {
+            continue;
+        }
+        let session_id = entry.file_name().to_string_lossy().to_string();
+        let live = state
+            .orchestrator_manager
+            .lock()
+            .get_session(&session_id)
+            .is_some();
+        let idle = SystemTime::now()
+            .duration_since(last_modified(&dir))
+            .unwrap_or_default();
+        if !live && idle > ORPHAN_AFTER {
+            remove(&session_id);
+            continue;
+        }
+        if max_bytes > 0 {
+            let freed = enforce_quota(&dir, max_bytes);
+            if freed > 0 {
+                eprintln!(
+                    "[Scratch] Freed {} bytes in {} to stay under its quota",
+                    freed,
+                    dir.display()
+                );
+            }
+        }
+    }
+}
+
+/// Start the background sweep
+pub fn start(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(SWEEP_INTERVAL);
+        loop {
+            interval.tick().await;
+            let settings = load_settings().scratch;
+            let Some(root) = root() else {
+                continue;
+            };
+            if !settings.enabled || !root.exists() {
+                continue;
+            }
+            let state = app.state::<AppState>();
+            sweep(&state, &root, settings.max_mb * 1024 * 1024);
+        }
+    });
+}
+
+/// A session's scratch directory and how full it is
+#[tauri::command]
+pub fn get_scratch_dir(session_id: String) -> Result<ScratchDir, String> {
+    let dir = path(&session_id).ok_or("Scratch directories are turned off")?;
+    Ok(ScratchDir {
+        path: dir.to_string_lossy().to_string(),
+        bytes: dir_size(&dir),
+        max_bytes: load_settings().scratch.max_mb * 1024 * 1024,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dir_for_refuses_paths() {
+        let root = Path::new("/scratch");
+        assert_eq!(
+            dir_for(root, "3f2a-b_1"),
+            Some(PathBuf::from("/scratch/3f2a-b_1"))
+        );
+        assert_eq!(dir_for(root, ".."), None);
+        assert_eq!(dir_for(root, "a/b"), None);
+        assert_eq!(dir_for(root, ""), None);
+    }
+
+    #[test]
+    fn test_enforce_quota_removes_oldest_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let nested = dir.path().join("out");
+        fs::create_dir_all(&nested).unwrap();
+        let old = dir.path().join("old.log");
+        let middle = nested.join("middle.bin");
+        let new = dir.path().join("new.txt");
+        for (i, path) in [&old, &middle, &new].into_iter().enumerate() {
+            fs::write(path, vec![0u8; 100]).unwrap();
+            let modified = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000 + i as u64);
+            fs::File::options()
+                .write(true)
+                .open(path)
+                .unwrap()
+                .set_modified(modified)
+                .unwrap();
+        }
+
+        assert_eq!(dir_size(dir.path()), 300);
+        assert_eq!(enforce_quota(dir.path(), 300), 0);
+        assert_eq!(enforce_quota(dir.path(), 150), 200);
+        assert!(!old.exists());
+        assert!(!middle.exists());
+        assert!(new.exists());
+        assert_eq!(dir_size(dir.path()), 100);
+    }
+}
@@ -0,0 +1,265 @@
+//! Project-wide text search
+//!
+//! `search_in_project` searches a project's files the way ripgrep does,
+//! with the same crates: files git would ignore (per .gitignore, .ignore
+//! and the global excludes, in or out of a git repository), hidden files
+//! and binary files are skipped. Queries are literal text unless `regex`
+//! is set, and match case-insensitively unless they contain an uppercase
+//! letter. Every matching line comes back with the character ranges of
+//! its matches, ready to highlight.
+
+use grep::matcher::Matcher;
+use grep::regex::{RegexMatcher, RegexMatcherBuilder};
+use grep::searcher::sinks::Lossy;
+use grep::searcher::{BinaryDetection, SearcherBuilder};
+use ignore::overrides::OverrideBuilder;
+use ignore::WalkBuilder;
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::path::Path;
+
+/// Matches returned when no limit is given
+const DEFAULT_MAX_RESULTS: usize = 500;
+
+/// Most matches a single search may return
+const MAX_RESULTS: usize = 10_000;
+
+/// Larger files are skipped (generated bundles, data dumps)
+const MAX_FILE_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Longer lines are cut off (minified code would otherwise flood the UI)
+const MAX_LINE_CHARS: usize = 1000;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct SearchOptions {
+    /// Treat the query as a regular expression rather than literal text
+    pub regex: bool,
+    /// Only search files matching these globs, relative to the project
+    /// (prefix with `!` to exclude instead)
+    pub globs: Vec<String>,
+    /// Stop after this many matching lines (`DEFAULT_MAX_RESULTS` if unset)
+    pub max_results: Option<usize>,
+}
+
+/// Character range [start, end) of a match within its line
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+pub struct MatchRange {
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct SearchMatch {
+    /// Absolute path
+    pub path: String,
+    /// Path relative to the project
+    pub relative_path: String,
+    /// 1-based
+    pub line_number: u64,
+    /// The matching line without its line break (cut at `MAX_LINE_CHARS`)
+    pub line: String,
+    pub ranges: Vec<MatchRange>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct SearchResults {
+    pub matches: Vec<SearchMatch>,
+    pub files_searched: usize,
+    /// Stopped at `max_results` with more matches left
+    pub truncated: bool,
+}
+
+fn build_matcher(query: &str, regex: bool) -> Result<RegexMatcher, String> {
+    let pattern = if regex {
+        query.to_string()
+    } else {
+        regex::escape(query)
+    };
+    RegexMatcherBuilder::new()
+        .case_smart(true)
+        .build(&pattern)
+        .map_err(|e| format!("Invalid search pattern: {}", e))
+}
+
+/// Character range of the byte range [start, end) of `line`, or None if it
+/// starts past the part of the line that's kept
+fn char_range(line: &str, start: usize, end: usize) -> Option<MatchRange> {
+    let start = line.get(..start)?.chars().count();
+    if start >= MAX_LINE_CHARS {
+        return None;
+    }
+    let end = line.get(..end)?.chars().count();
+    Some(MatchRange {
+        start,
+        end: end.min(MAX_LINE_CHARS),
+    })
+}
+
+/// Search the files under `root` for `query`
+pub fn search(root: &Path, query: &str, options: &SearchOptions) -> Result<SearchResults, String> {
+    if query.is_empty() {
+        return Err("Empty search query".to_string());
+    }
+    if !root.is_dir() {
+        return Err(format!("Not a directory: {}", root.display()));
+    }
+    let matcher = build_matcher(query, options.regex)?;
+    let limit = options
+        .max_results
+        .unwrap_or(DEFAULT_MAX_RESULTS)
+        .clamp(1, MAX_RESULTS);
+
+    let mut overrides = OverrideBuilder::new(root);
+    for glob in &options.globs {
+        overrides
+            .add(glob)
+            .map_err(|e| format!("Invalid glob {}: {}", glob, e))?;
+    }
+    let overrides = overrides
+        .build()
+        .map_err(|e| format!("Invalid globs: {}", e))?;
+    let walker = WalkBuilder::new(root)
+        .overrides(overrides)
+        .require_git(false)
+        .max_filesize(Some(MAX_FILE_BYTES))
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .build();
+
+    let mut searcher = SearcherBuilder::new()
+        .line_number(true)
+        .binary_detection(BinaryDetection::quit(b'\x00'))
+        .build();
+    let mut results = SearchResults {
+        matches: Vec::new(),
+        files_searched: 0,
+        truncated: false,
+    };
+
+    for entry in walker.filter_map(Result::ok) {
+        if results.truncated {
+            break;
+        }
+        if !entry.file_type().is_some_and(|t| t.is_file()) {
+            continue;
+        }
+        let path = entry.path();
+        let relative_path = path
+            .strip_prefix(root)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .to_string();
+        results.files_searched += 1;
+
+        let matches = &mut results.matches;
+        let truncated = &mut results.truncated;
+        let sink = Lossy(|line_number, line| {
+            if matches.len() >= limit {
+                *truncated = true;
+                return Ok(false);
+            }
+            let line = line.trim_end_matches(['\r', '\n']);
+            let mut ranges = Vec::new();
+            matcher
+                .find_iter(line.as_bytes(), |m| {
+                    ranges.extend(char_range(line, m.start(), m.end()));
+                    true
+                })
+                .map_err(io::Error::other)?;
+            matches.push(SearchMatch {
+                path: path.to_string_lossy().to_string(),
+                relative_path: relative_path.clone(),
+                line_number,
+                line: line.chars().take(MAX_LINE_CHARS).collect(),
+                ranges,
+            });
+            Ok(true)
+        });
+        // Unreadable files are skipped, like ripgrep does
+        if let Err(e) = searcher.search_path(&matcher, path, sink) {
+            eprintln!("[Search] Skipping {}: {}", path.display(), e);
+        }
+    }
+    Ok(results)
+}
+
+/// Search a project's files (respecting .gitignore) for `query`
+#[tauri::command]
+pub async fn search_in_project(
+    project_dir: String,
+    query: String,
+    options: Option<SearchOptions>,
+) -> Result<SearchResults, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        search(
+            Path::new(&project_dir),
+            &query,
+            &options.unwrap_or_default(),
+        )
+    })
+    .await
+    .map_err(|e| format!("Search task failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_search() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::create_dir_all(root.join("dist")).unwrap();
+        fs::write(root.join(".gitignore"), "dist/\n").unwrap();
+        fs::write(
+            root.join("src/main.rs"),
+            "fn main() {\n    // héllo TODO: wire up\n    todo!()\n}\n",
+        )
+        .unwrap();
+        fs::write(root.join("src/notes.md"), "TODO list\n").unwrap();
+        fs::write(root.join("dist/bundle.js"), "// TODO generated\n").unwrap();
+        fs::write(root.join("blob.bin"), b"\0TODO\x01").unwrap();
+
+        // Smart case: lowercase matches both cases; ignored and binary
+        // files are skipped
+        let results = search(root, "todo", &SearchOptions::default()).unwrap();
+        let found: Vec<(&str, u64)> = results
+            .matches
+            .iter()
+            .map(|m| (m.relative_path.as_str(), m.line_number))
+            .collect();
+        assert_eq!(found, vec![("src/main.rs", 2), ("src/main.rs", 3), ("src/notes.md", 1)]);
+        // Character ranges, past the two-byte "é"
+        assert_eq!(results.matches[0].ranges, vec![MatchRange { start: 13, end: 17 }]);
+        assert!(!results.truncated);
+
+        let upper = search(root, "TODO", &SearchOptions::default()).unwrap();
+        assert_eq!(upper.matches.len(), 2);
+
+        let options = SearchOptions {
+            regex: true,
+            globs: vec!["*.rs".to_string()],
+            ..Default::default()
+        };
+        let results = search(root, r"todo!\(\)|fn \w+", &options).unwrap();
+        assert_eq!(results.matches.len(), 2);
+        assert_eq!(results.matches[0].line, "fn main() {");
+
+        let options = SearchOptions {
+            max_results: Some(1),
+            ..Default::default()
+        };
+        let results = search(root, "todo", &options).unwrap();
+        assert_eq!(results.matches.len(), 1);
+        assert!(results.truncated);
+
+        assert!(search(root, "", &SearchOptions::default()).is_err());
+        let options = SearchOptions {
+            regex: true,
+            ..Default::default()
+        };
+        assert!(search(root, "(", &options).is_err());
+    }
+}
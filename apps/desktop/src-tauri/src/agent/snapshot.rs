@@ -0,0 +1,674 @@
+//! Workspace snapshots
+//!
+//! Projects outside git have nothing to fall back on when an agent breaks
+//! them. A snapshot records every file in a project (skipping ignored
+//! directories, `.gitignore` patterns and `snapshots.ignore`, and files or
+//! totals past the size caps) in a content-addressed store under
+//! ~/.crafter-code/snapshots: file contents go to `objects/` by SHA-256, so
+//! unchanged files cost nothing after the first snapshot, and each snapshot
+//! is a manifest in `manifests/`. One is taken when a session starts in a
+//! non-git project and whenever `create_snapshot` is called;
+//! `diff_against_snapshot` shows what changed since and `restore_snapshot`
+//! puts it back. Only the newest `snapshots.keep` per project are kept.
+//! Objects and manifests are encrypted when `encrypt_sessions` is on.
+
+use crate::agent::tree::{is_glob_ignored, DEFAULT_IGNORES};
+use crate::orchestrator::patch::{file_patch, BaseSource, FilePatch};
+use crate::secrets::at_rest::{self, Encryption};
+use crate::settings::load_settings;
+use crate::settings::store::SnapshotSettings;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// One file in a snapshot
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SnapshotFile {
+    /// SHA-256 of the content, hex
+    pub hash: String,
+    pub size: u64,
+}
+
+/// A snapshot manifest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceSnapshot {
+    pub id: String,
+    /// Project root
+    pub root: String,
+    pub label: Option<String>,
+    /// Session that was starting, for automatic snapshots
+    pub session_id: Option<String>,
+    /// Unix timestamp (milliseconds)
+    pub created_at: i64,
+    /// Relative path -> content
+    pub files: BTreeMap<String, SnapshotFile>,
+    /// Files left out for the size caps (never deleted by a restore)
+    pub skipped: Vec<String>,
+}
+
+/// A snapshot without its file list
+#[derive(Debug, Clone, Serialize)]
+pub struct SnapshotSummary {
+    pub id: String,
+    pub root: String,
+    pub label: Option<String>,
+    pub session_id: Option<String>,
+    pub created_at: i64,
+    pub files: usize,
+    pub bytes: u64,
+    pub skipped: usize,
+}
+
+impl From<&WorkspaceSnapshot> for SnapshotSummary {
+    fn from(snapshot: &WorkspaceSnapshot) -> Self {
+        Self {
+            id: snapshot.id.clone(),
+            root: snapshot.root.clone(),
+            label: snapshot.label.clone(),
+            session_id: snapshot.session_id.clone(),
+            created_at: snapshot.created_at,
+            files: snapshot.files.len(),
+            bytes: snapshot.files.values().map(|f| f.size).sum(),
+            skipped: snapshot.skipped.len(),
+        }
+    }
+}
+
+/// Changes in a project since a snapshot
+#[derive(Debug, Clone, Serialize)]
+pub struct SnapshotDiff {
+    pub snapshot: SnapshotSummary,
+    /// Text files added, modified or deleted
+    pub files: Vec<FilePatch>,
+    /// Binary files that changed (relative paths)
+    pub binary: Vec<String>,
+    pub additions: usize,
+    pub deletions: usize,
+}
+
+/// What `restore_snapshot` changed
+#[derive(Debug, Clone, Serialize)]
+pub struct RestoreResult {
+    /// Files written back (relative paths)
+    pub restored: Vec<String>,
+    /// Files created since the snapshot that were removed
+    pub removed: Vec<String>,
+    /// Snapshot of the project taken just before restoring
+    pub backup_id: String,
+}
+
+fn hash(content: &[u8]) -> String {
+    Sha256::digest(content)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+fn as_text(content: &Option<Vec<u8>>) -> Result<Option<&str>, std::str::Utf8Error> {
+    content.as_deref().map(std::str::from_utf8).transpose()
+}
+
+/// Whether `dir` is inside a git work tree
+pub fn is_git_repo(dir: &Path) -> bool {
+    Command::new("git")
+        .current_dir(dir)
+        .args(["rev-parse", "--is-inside-work-tree"])
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+/// Patterns from the root `.gitignore` (negations aren't supported)
+fn gitignore_globs(root: &Path) -> Vec<String> {
+    fs::read_to_string(root.join(".gitignore"))
+        .unwrap_or_default()
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#') && !line.starts_with('!'))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Every file in the project that isn't ignored, by relative path
+fn project_files(root: &Path, ignore: &[String]) -> Vec<(String, PathBuf, u64)> {
+    let mut files = Vec::new();
+    let mut dirs = vec![root.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Ok(rel) = path.strip_prefix(root) else {
+                continue;
+            };
+            let rel = rel.to_string_lossy().replace('\\', "/");
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if DEFAULT_IGNORES.contains(&name.as_str()) || is_glob_ignored(ignore, &name, &rel) {
+                continue;
+            }
+            // Symlinks are left alone
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            if file_type.is_dir() {
+                dirs.push(path);
+            } else if file_type.is_file() {
+                let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+                files.push((rel, path, size));
+            }
+        }
+    }
+    files.sort_by(|a, b| a.0.cmp(&b.0));
+    files
+}
+
+fn ignore_globs(root: &Path, settings: &SnapshotSettings) -> Vec<String> {
+    let mut globs = gitignore_globs(root);
+    globs.extend(settings.ignore.iter().cloned());
+    globs
+}
+
+/// Snapshots on disk
+pub struct SnapshotStore {
+    base_path: PathBuf,
+    encryption: Encryption,
+}
+
+impl SnapshotStore {
+    /// Create a store rooted at ~/.crafter-code/snapshots
+    pub fn new() -> Result<Self, String> {
+        let base_path = dirs::home_dir()
+            .ok_or_else(|| "Could not determine home directory".to_string())?
+            .join(".crafter-code")
+            .join("snapshots");
+        Ok(Self::with_path(base_path, Encryption::Configured))
+    }
+
+    /// Create a store backed by a specific directory
+    pub fn with_path(base_path: PathBuf, encryption: Encryption) -> Self {
+        Self {
+            base_path,
+            encryption,
+        }
+    }
+
+    fn object_path(&self, hash: &str) -> PathBuf {
+        self.base_path.join("objects").join(&hash[..2]).join(hash)
+    }
+
+    fn manifest_path(&self, id: &str) -> PathBuf {
+        self.base_path
+            .join("manifests")
+            .join(format!("{}.json", id))
+    }
+
+    fn write_object(&self, content: &[u8]) -> Result<String, String> {
+        let hash = hash(content);
+        let path = self.object_path(&hash);
+        if !path.exists() {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)
+                    .map_err(|e| format!("Failed to create snapshot store: {}", e))?;
+            }
+            at_rest::write(&path, content, self.encryption)
+                .map_err(|e| format!("Failed to write snapshot object: {}", e))?;
+        }
+        Ok(hash)
+    }
+
+    fn read_object(&self, hash: &str) -> Result<Vec<u8>, String> {
+        at_rest::read(&self.object_path(hash), self.encryption)
+            .map_err(|e| format!("Snapshot object {} is missing: {}", hash, e))
+    }
+
+    /// Snapshot the project at `root`
+    pub fn create(
+        &self,
+        root: &Path,
+        label: Option<String>,
+        session_id: Option<String>,
+        settings: &SnapshotSettings,
+    ) -> Result<WorkspaceSnapshot, String> {
+        if !root.is_dir() {
+            return Err(format!("Not a directory: {}", root.display()));
+        }
+        let mut files = BTreeMap::new();
+        let mut skipped = Vec::new();
+        let mut total = 0u64;
+        for (rel, path, size) in project_files(root, &ignore_globs(root, settings)) {
+            let too_big = settings.max_file_bytes > 0 && size > settings.max_file_bytes;
+            let over_total =
+                settings.max_total_bytes > 0 && total + size > settings.max_total_bytes;
+            if too_big || over_total {
+                skipped.push(rel);
+                continue;
+            }
+            let Ok(content) = fs::read(&path) else {
+                continue;
+            };
+            total += content.len() as u64;
+            files.insert(
+                rel,
+                SnapshotFile {
+                    hash: self.write_object(&content)?,
+                    size: content.len() as u64,
+                },
+            );
+        }
+
+        let snapshot = WorkspaceSnapshot {
+            id: uuid::Uuid::new_v4().to_string(),
+            root: root.to_string_lossy().into_owned(),
+            label,
+            session_id,
+            created_at: chrono::Utc::now().timestamp_millis(),
+            files,
+            skipped,
+        };
+        let path = self.manifest_path(&snapshot.id);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create snapshot store: {}", e))?;
+        }
+        let json = serde_json::to_string(&snapshot)
+            .map_err(|e| format!("Failed to serialize snapshot: {}", e))?;
+        at_rest::write(&path, json.as_bytes(), self.encryption)
+            .map_err(|e| format!("Failed to write snapshot: {}", e))?;
+        eprintln!(
+            "[Snapshot] {} files of {} saved as {} ({} skipped)",
+            snapshot.files.len(),
+            snapshot.root,
+            snapshot.id,
+            snapshot.skipped.len()
+        );
+
+        self.prune(&snapshot.root, settings.keep as usize)?;
+        Ok(snapshot)
+    }
+
+    pub fn load(&self, id: &str) -> Result<WorkspaceSnapshot, String> {
+        if uuid::Uuid::parse_str(id).is_err() {
+            return Err(format!("Snapshot not found: {}", id));
+        }
+        let json = at_rest::read_to_string(&self.manifest_path(id), self.encryption)
+            .map_err(|_| format!("Snapshot not found: {}", id))?;
+        serde_json::from_str(&json).map_err(|e| format!("Failed to parse snapshot {}: {}", id, e))
+    }
+
+    fn all(&self) -> Vec<WorkspaceSnapshot> {
+        let Ok(entries) = fs::read_dir(self.base_path.join("manifests")) else {
+            return Vec::new();
+        };
+        entries
+            .flatten()
+            .filter_map(|entry| at_rest::read_to_string(&entry.path(), self.encryption).ok())
+            .filter_map(|json| serde_json::from_str(&json).ok())
+            .collect()
+    }
+
+    /// Snapshots of a project, newest first
+    pub fn list(&self, root: &str) -> Vec<WorkspaceSnapshot> {
+        let mut snapshots: Vec<_> = self.all().into_iter().filter(|s| s.root == root).collect();
+        snapshots.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        snapshots
+    }
+
+    /// Drop a project's snapshots past the newest `keep` (0 = keep all),
+    /// then the objects nothing refers to anymore
+    fn prune(&self, root: &str, keep: usize) -> Result<(), String> {
+        if keep == 0 {
+            return Ok(());
+        }
+        let old = self.list(root).into_iter().skip(keep).collect::<Vec<_>>();
+        if old.is_empty() {
+            return Ok(());
+        }
+        for snapshot in &old {
+            let _ = fs::remove_file(self.manifest_path(&snapshot.id));
+        }
+        for object in self.orphaned_objects(&self.all()) {
+            let _ = fs::remove_file(object);
+        }
+        Ok(())
+    }
+
+    /// Objects none of the `kept` snapshots refer to
+    fn orphaned_objects(&self, kept: &[WorkspaceSnapshot]) -> Vec<PathBuf> {
+        let referenced: HashSet<&str> = kept
+            .iter()
+            .flat_map(|s| s.files.values().map(|f| f.hash.as_str()))
+            .collect();
+        let Ok(buckets) = fs::read_dir(self.base_path.join("objects")) else {
+            return Vec::new();
+        };
+        buckets
+            .flatten()
+            .flat_map(|bucket| fs::read_dir(bucket.path()).into_iter().flatten().flatten())
+            .filter(|object| !referenced.contains(object.file_name().to_string_lossy().as_ref()))
+            .map(|object| object.path())
+            .collect()
+    }
+
+    /// Manifests of the snapshots taken before `cutoff` (unix ms), and the
+    /// objects only they refer to: what deleting those snapshots removes
+    pub fn expired_files(&self, cutoff: i64) -> Vec<PathBuf> {
+        let (expired, kept): (Vec<_>, Vec<_>) = self
+            .all()
+            .into_iter()
+            .partition(|snapshot| snapshot.created_at < cutoff);
+        if expired.is_empty() {
+            return Vec::new();
+        }
+        expired
+            .iter()
+            .map(|snapshot| self.manifest_path(&snapshot.id))
+            .chain(self.orphaned_objects(&kept))
+            .collect()
+    }
+
+    /// Directory holding manifests and objects
+    pub fn base_path(&self) -> &Path {
+        &self.base_path
+    }
+
+    /// Changes in the project since `id`
+    pub fn diff(&self, id: &str) -> Result<SnapshotDiff, String> {
+        let snapshot = self.load(id)?;
+        let root = Path::new(&snapshot.root);
+        let settings = load_settings().snapshots;
+        let current = project_files(root, &ignore_globs(root, &settings));
+
+        let mut paths: Vec<&str> = snapshot.files.keys().map(String::as_str).collect();
+        let skipped: HashSet<&str> = snapshot.skipped.iter().map(String::as_str).collect();
+        paths.extend(
+            current
+                .iter()
+                .map(|(rel, _, _)| rel.as_str())
+                .filter(|rel| !snapshot.files.contains_key(*rel) && !skipped.contains(rel)),
+        );
+        paths.sort_unstable();
+
+        let mut files = Vec::new();
+        let mut binary = Vec::new();
+        for rel in paths {
+            let path = root.join(rel);
+            let before = match snapshot.files.get(rel) {
+                Some(file) => Some(self.read_object(&file.hash)?),
+                None => None,
+            };
+            let after = fs::read(&path).ok();
+            if before == after {
+                continue;
+            }
+            match (as_text(&before), as_text(&after)) {
+                (Ok(before), Ok(after)) => {
+                    let path = path.to_string_lossy();
+                    files.extend(file_patch(&path, rel, before, BaseSource::Snapshot, after));
+                }
+                _ => binary.push(rel.to_string()),
+            }
+        }
+
+        Ok(SnapshotDiff {
+            snapshot: SnapshotSummary::from(&snapshot),
+            additions: files.iter().map(|f| f.additions).sum(),
+            deletions: files.iter().map(|f| f.deletions).sum(),
+            files,
+            binary,
+        })
+    }
+
+    /// Put the project back as it was at `id`, or only `paths` (relative).
+    /// The current state is snapshotted first so the restore can be undone.
+    pub fn restore(&self, id: &str, paths: Option<&[String]>) -> Result<RestoreResult, String> {
+        let snapshot = self.load(id)?;
+        let root = Path::new(&snapshot.root);
+        let settings = load_settings().snapshots;
+        let wanted = |rel: &str| paths.is_none_or(|paths| paths.iter().any(|p| p == rel));
+
+        let backup = self.create(
+            root,
+            Some(format!("Before restoring {}", snapshot.id)),
+            None,
+            &settings,
+        )?;
+
+        let mut restored = Vec::new();
+        for (rel, file) in snapshot.files.iter().filter(|(rel, _)| wanted(rel)) {
+            let path = root.join(rel);
+            let unchanged = fs::read(&path).is_ok_and(|content| hash(&content) == file.hash);
+            if unchanged {
+                continue;
+            }
+            let content = self.read_object(&file.hash)?;
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)
+                    .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+            }
+            fs::write(&path, content).map_err(|e| format!("Failed to restore {}: {}", rel, e))?;
+            restored.push(rel.clone());
+        }
+
+        let mut removed = Vec::new();
+        for (rel, path, _) in project_files(root, &ignore_globs(root, &settings)) {
+            if snapshot.files.contains_key(&rel) || snapshot.skipped.contains(&rel) || !wanted(&rel)
+            {
+                continue;
+            }
+            fs::remove_file(&path).map_err(|e| format!("Failed to remove {}: {}", rel, e))?;
+            removed.push(rel);
+        }
+
+        eprintln!(
+            "[Snapshot] Restored {} in {}: {} written, {} removed",
+            snapshot.id,
+            snapshot.root,
+            restored.len(),
+            removed.len()
+        );
+        Ok(RestoreResult {
+            restored,
+            removed,
+            backup_id: backup.id,
+        })
+    }
+}
+
+/// Snapshot a non-git project as a session starts, before its agent can
+/// touch any file. The copy runs on a blocking thread.
+pub async fn snapshot_before_session(cwd: &str, session_id: &str) {
+    let settings = load_settings().snapshots;
+    if !settings.on_session_start {
+        return;
+    }
+    let root = PathBuf::from(cwd);
+    let session_id = session_id.to_string();
+    let snapshot = tauri::async_runtime::spawn_blocking(move || {
+        if is_git_repo(&root) {
+            return;
+        }
+        let result = SnapshotStore::new().and_then(|store| {
+            store.create(
+                &root,
+                Some("Session start".to_string()),
+                Some(session_id),
+                &settings,
+            )
+        });
+        if let Err(e) = result {
+            eprintln!("[Snapshot] {}", e);
+        }
+    });
+    if let Err(e) = snapshot.await {
+        eprintln!("[Snapshot] {}", e);
+    }
+}
+
+/// Snapshot a project now
+#[tauri::command]
+pub async fn create_snapshot(
+    path: String,
+    label: Option<String>,
+) -> Result<SnapshotSummary, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let snapshot = SnapshotStore::new()?.create(
+            Path::new(&path),
+            label,
+            None,
+            &load_settings().snapshots,
+        )?;
+        Ok(SnapshotSummary::from(&snapshot))
+    })
+    .await
+    .map_err(|e| format!("Snapshot task failed: {}", e))?
+}
+
+/// Snapshots of a project, newest first
+#[tauri::command]
+pub fn list_snapshots(path: String) -> Result<Vec<SnapshotSummary>, String> {
+    Ok(SnapshotStore::new()?
+        .list(&path)
+        .iter()
+        .map(SnapshotSummary::from)
+        .collect())
+}
+
+/// What changed in a project since a snapshot
+#[tauri::command]
+pub async fn diff_against_snapshot(snapshot_id: String) -> Result<SnapshotDiff, String> {
+    tauri::async_runtime::spawn_blocking(move || SnapshotStore::new()?.diff(&snapshot_id))
+        .await
+        .map_err(|e| format!("Snapshot task failed: {}", e))?
+}
+
+/// Restore a project (or some of its files) from a snapshot
+#[tauri::command]
+pub async fn restore_snapshot(
+    snapshot_id: String,
+    paths: Option<Vec<String>>,
+) -> Result<RestoreResult, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        SnapshotStore::new()?.restore(&snapshot_id, paths.as_deref())
+    })
+    .await
+    .map_err(|e| format!("Snapshot task failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn settings() -> SnapshotSettings {
+        SnapshotSettings {
+            max_file_bytes: 32,
+            ..Default::default()
+        }
+    }
+
+    fn project() -> TempDir {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::create_dir_all(root.join("node_modules/pkg")).unwrap();
+        fs::create_dir_all(root.join("dist")).unwrap();
+        fs::write(root.join(".gitignore"), "# build output\ndist/\n*.log\n").unwrap();
+        fs::write(root.join("src/main.rs"), "fn main() {}\n").unwrap();
+        fs::write(root.join("src/lib.rs"), "pub fn a() {}\n").unwrap();
+        fs::write(root.join("big.bin"), vec![0u8; 64]).unwrap();
+        fs::write(root.join("node_modules/pkg/index.js"), "x").unwrap();
+        fs::write(root.join("dist/out.js"), "x").unwrap();
+        fs::write(root.join("debug.log"), "x").unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_create_skips_ignored_and_large_files() {
+        let project = project();
+        let store_dir = TempDir::new().unwrap();
+        let store = SnapshotStore::with_path(store_dir.path().to_path_buf(), Encryption::Off);
+
+        let snapshot = store
+            .create(project.path(), None, None, &settings())
+            .unwrap();
+        let files: Vec<&str> = snapshot.files.keys().map(String::as_str).collect();
+        assert_eq!(files, vec![".gitignore", "src/lib.rs", "src/main.rs"]);
+        assert_eq!(snapshot.skipped, vec!["big.bin"]);
+
+        // Identical content is stored once
+        let again = store
+            .create(project.path(), None, None, &settings())
+            .unwrap();
+        assert_eq!(again.files["src/main.rs"], snapshot.files["src/main.rs"]);
+        assert_eq!(store.list(&snapshot.root).len(), 2);
+    }
+
+    #[test]
+    fn test_restore_undoes_changes() {
+        let project = project();
+        let root = project.path();
+        let store_dir = TempDir::new().unwrap();
+        let store = SnapshotStore::with_path(store_dir.path().to_path_buf(), Encryption::Off);
+        let snapshot = store.create(root, None, None, &settings()).unwrap();
+
+        fs::write(root.join("src/main.rs"), "fn main() { panic!() }\n").unwrap();
+        fs::remove_file(root.join("src/lib.rs")).unwrap();
+        fs::write(root.join("src/new.rs"), "// new\n").unwrap();
+
+        let result = store.restore(&snapshot.id, None).unwrap();
+        assert_eq!(result.restored, vec!["src/lib.rs", "src/main.rs"]);
+        assert_eq!(result.removed, vec!["src/new.rs"]);
+        assert_eq!(
+            fs::read_to_string(root.join("src/main.rs")).unwrap(),
+            "fn main() {}\n"
+        );
+        assert!(root.join("big.bin").exists());
+        assert!(root.join("dist/out.js").exists());
+
+        // The backup holds the state before the restore
+        let backup = store.load(&result.backup_id).unwrap();
+        assert!(backup.files.contains_key("src/new.rs"));
+    }
+
+    #[test]
+    fn test_prune_keeps_newest() {
+        let project = project();
+        let store_dir = TempDir::new().unwrap();
+        let store = SnapshotStore::with_path(store_dir.path().to_path_buf(), Encryption::Off);
+        let mut settings = settings();
+        settings.keep = 1;
+
+        let first = store.create(project.path(), None, None, &settings).unwrap();
+        fs::write(project.path().join("src/main.rs"), "fn main() { 1; }\n").unwrap();
+        let second = store.create(project.path(), None, None, &settings).unwrap();
+
+        assert!(store.load(&first.id).is_err());
+        assert!(store.load(&second.id).is_ok());
+        // The old version of main.rs is gone with its snapshot
+        assert!(!store.object_path(&first.files["src/main.rs"].hash).exists());
+        assert!(store.object_path(&second.files["src/lib.rs"].hash).exists());
+    }
+
+    #[test]
+    fn test_expired_files_spare_shared_objects() {
+        let project = project();
+        let store_dir = TempDir::new().unwrap();
+        let store = SnapshotStore::with_path(store_dir.path().to_path_buf(), Encryption::Off);
+
+        let first = store.create(project.path(), None, None, &settings()).unwrap();
+        // Taken at a later millisecond than the first
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        fs::write(project.path().join("src/main.rs"), "fn main() { 1; }\n").unwrap();
+        let second = store.create(project.path(), None, None, &settings()).unwrap();
+
+        assert!(store.expired_files(first.created_at).is_empty());
+        let expired = store.expired_files(second.created_at);
+        assert!(expired.contains(&store.manifest_path(&first.id)));
+        assert!(expired.contains(&store.object_path(&first.files["src/main.rs"].hash)));
+        assert!(!expired.contains(&store.object_path(&first.files["src/lib.rs"].hash)));
+    }
+}
@@ -0,0 +1,438 @@
+//! Directory trees for the file explorer
+//!
+//! `read_directory_tree` walks a directory a few levels deep, skipping what
+//! git ignores (or common build/dependency directories outside a repo) and
+//! anything matching the caller's ignore globs. Directories below the
+//! requested depth come back without children but with an expand token,
+//! which `expand_directory_tree` turns into the next levels. Every entry
+//! carries its size, mtime and git status; directories show as modified
+//! when anything inside them changed.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Deepest a single request may walk
+pub const MAX_TREE_DEPTH: u32 = 8;
+
+/// Entries returned per request; deeper directories are left to expand
+const MAX_TREE_ENTRIES: usize = 5000;
+
+/// Skipped outside git repositories, where there's no .gitignore to ask
+pub(crate) const DEFAULT_IGNORES: &[&str] = &[".git", "node_modules", "target", ".next"];
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum GitFileStatus {
+    Modified,
+    Added,
+    Deleted,
+    Renamed,
+    Untracked,
+    Ignored,
+    Conflicted,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GitStatusEntry {
+    /// Absolute path
+    pub path: String,
+    pub status: GitFileStatus,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TreeNode {
+    pub name: String,
+    pub path: String,
+    pub is_dir: bool,
+    pub is_file: bool,
+    pub is_hidden: bool,
+    pub size: Option<u64>,
+    pub modified: Option<i64>,
+    pub git_status: Option<GitFileStatus>,
+    /// None for files and for directories that weren't expanded
+    pub children: Option<Vec<TreeNode>>,
+    /// Pass to `expand_directory_tree` to load an unexpanded directory
+    pub expand_token: Option<String>,
+}
+
+/// What an expand token stands for
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+struct ExpandRequest {
+    /// Root of the original tree (globs are relative to it)
+    root: String,
+    path: String,
+    ignore_globs: Vec<String>,
+}
+
+/// Git status of a repository, by path relative to its root
+#[derive(Debug, Default)]
+pub struct RepoStatus {
+    root: PathBuf,
+    files: HashMap<String, GitFileStatus>,
+}
+
+/// Parse `git status --porcelain=v1 -z` output into (relative path, status)
+fn parse_porcelain(output: &str) -> Vec<(String, GitFileStatus)> {
+    let mut entries = Vec::new();
+    let mut items = output.split('\0');
+    while let Some(item) = items.next() {
+        if item.len() < 4 {
+            continue;
+        }
+        let (xy, path) = item.split_at(3);
+        let mut codes = xy.chars();
+        let (x, y) = (codes.next().unwrap_or(' '), codes.next().unwrap_or(' '));
+        // Renames and copies are followed by the original path
+        if matches!(x, 'R' | 'C') {
+            items.next();
+        }
+        let status = match (x, y) {
+            ('?', '?') => GitFileStatus::Untracked,
+            ('!', '!') => GitFileStatus::Ignored,
+            ('U', _) | (_, 'U') | ('A', 'A') | ('D', 'D') => GitFileStatus::Conflicted,
+            ('R', _) | (_, 'R') => GitFileStatus::Renamed,
+            ('A', _) => GitFileStatus::Added,
+            ('D', _) | (_, 'D') => GitFileStatus::Deleted,
+            _ => GitFileStatus::Modified,
+        };
+        entries.push((path.trim_end_matches('/').to_string(), status));
+    }
+    entries
+}
+
+impl RepoStatus {
+    /// Status of the repository containing `dir`, or None outside git
+    pub fn load(dir: &Path, include_ignored: bool) -> Option<Self> {
+        let root = git(dir, &["rev-parse", "--show-toplevel"])?;
+        let mut args = vec!["status", "--porcelain=v1", "-z"];
+        if include_ignored {
+            args.push("--ignored");
+        }
+        let output = git(dir, &args)?;
+        let root = PathBuf::from(root.trim());
+        Some(Self {
+            root: fs::canonicalize(&root).unwrap_or(root),
+            files: parse_porcelain(&output).into_iter().collect(),
+        })
+    }
+
+    /// `path` relative to the repository root
+    fn relative(&self, path: &Path) -> Option<String> {
+        let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        path.strip_prefix(&self.root)
+            .ok()
+            .map(|rel| rel.to_string_lossy().to_string())
+    }
+
+    /// Status of a relative path. Files inside an untracked or ignored
+    /// directory inherit its status; a directory containing changes counts
+    /// as modified.
+    fn status_of(&self, rel: &str, is_dir: bool) -> Option<GitFileStatus> {
+        if let Some(status) = self.files.get(rel) {
+            return Some(*status);
+        }
+        let inherited = Path::new(rel)
+            .ancestors()
+            .skip(1)
+            .filter_map(|ancestor| self.files.get(ancestor.to_str()?))
+            .find(|status| matches!(status, GitFileStatus::Untracked | GitFileStatus::Ignored));
+        if inherited.is_some() {
+            return inherited.copied();
+        }
+        // The repository root contains everything
+        let prefix = if rel.is_empty() {
+            String::new()
+        } else {
+            format!("{}/", rel)
+        };
+        (is_dir
+            && self.files.iter().any(|(path, status)| {
+                path.starts_with(&prefix) && *status != GitFileStatus::Ignored
+            }))
+        .then_some(GitFileStatus::Modified)
+    }
+
+    /// Changed and untracked files as absolute paths
+    pub fn entries(&self) -> Vec<GitStatusEntry> {
+        let mut entries: Vec<GitStatusEntry> = self
+            .files
+            .iter()
+            .filter(|(_, status)| **status != GitFileStatus::Ignored)
+            .map(|(path, status)| GitStatusEntry {
+                path: self.root.join(path).to_string_lossy().to_string(),
+                status: *status,
+            })
+            .collect();
+        entries.sort_by(|a, b| a.path.cmp(&b.path));
+        entries
+    }
+}
+
+fn git(dir: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Glob match where `*` and `?` stay within a path segment and `**`
+/// crosses segments
+fn glob_match(pattern: &str, text: &str) -> bool {
+    fn matches(p: &[u8], t: &[u8]) -> bool {
+        match p {
+            [] => t.is_empty(),
+            [b'*', b'*', rest @ ..] => {
+                let rest = rest.strip_prefix(b"/").unwrap_or(rest);
+                (0..=t.len()).any(|i| matches(rest, &t[i..]))
+            }
+            [b'*', rest @ ..] => (0..=t.len())
+                .take_while(|&i| i == 0 || t[i - 1] != b'/')
+                .any(|i| matches(rest, &t[i..])),
+            [b'?', rest @ ..] => t.first().is_some_and(|c| *c != b'/') && matches(rest, &t[1..]),
+            [c, rest @ ..] => t.first() == Some(c) && matches(rest, &t[1..]),
+        }
+    }
+    matches(pattern.as_bytes(), text.as_bytes())
+}
+
+/// Whether an entry matches one of the globs. Globs without a slash match
+/// the name, like .gitignore; others match the path relative to the root.
+pub(crate) fn is_glob_ignored(globs: &[String], name: &str, rel: &str) -> bool {
+    globs.iter().any(|glob| {
+        let glob = glob.trim_end_matches('/');
+        if glob.contains('/') {
+            glob_match(glob.trim_start_matches('/'), rel)
+        } else {
+            glob_match(glob, name)
+        }
+    })
+}
+
+struct Walk<'a> {
+    root: &'a Path,
+    ignore_globs: &'a [String],
+    repo: Option<RepoStatus>,
+    entries: usize,
+}
+
+impl Walk<'_> {
+    fn node(&mut self, path: &Path, depth: u32) -> TreeNode {
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.to_string_lossy().to_string());
+        let metadata = fs::metadata(path).ok();
+        let is_dir = metadata.as_ref().is_some_and(|m| m.is_dir());
+        let git_status = self.repo.as_ref().and_then(|repo| {
+            let rel = repo.relative(path)?;
+            repo.status_of(&rel, is_dir)
+        });
+
+        let (children, expand_token) = if !is_dir {
+            (None, None)
+        } else if depth == 0 || self.entries >= MAX_TREE_ENTRIES {
+            (None, Some(self.expand_token(path)))
+        } else {
+            (Some(self.children(path, depth - 1)), None)
+        };
+
+        TreeNode {
+            is_hidden: name.starts_with('.'),
+            name,
+            path: path.to_string_lossy().to_string(),
+            is_dir,
+            is_file: metadata.as_ref().is_some_and(|m| m.is_file()),
+            size: metadata.as_ref().map(|m| m.len()),
+            modified: metadata
+                .as_ref()
+                .and_then(|m| m.modified().ok())
+                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|d| d.as_secs() as i64),
+            git_status,
+            children,
+            expand_token,
+        }
+    }
+
+    fn children(&mut self, dir: &Path, depth: u32) -> Vec<TreeNode> {
+        let Ok(read_dir) = fs::read_dir(dir) else {
+            return Vec::new();
+        };
+        let mut paths: Vec<PathBuf> = read_dir
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| !self.is_ignored(path))
+            .collect();
+        // Directories first, then by name
+        paths.sort_by_key(|path| {
+            (
+                !path.is_dir(),
+                path.file_name()
+                    .map(|n| n.to_string_lossy().to_lowercase())
+                    .unwrap_or_default(),
+            )
+        });
+        self.entries += paths.len();
+        paths.iter().map(|path| self.node(path, depth)).collect()
+    }
+
+    fn is_ignored(&self, path: &Path) -> bool {
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let rel = path
+            .strip_prefix(self.root)
+            .map(|rel| rel.to_string_lossy().to_string())
+            .unwrap_or_else(|_| name.clone());
+        if is_glob_ignored(self.ignore_globs, &name, &rel) {
+            return true;
+        }
+        match &self.repo {
+            Some(repo) => {
+                name == ".git"
+                    || repo
+                        .relative(path)
+                        .and_then(|rel| repo.status_of(&rel, false))
+                        == Some(GitFileStatus::Ignored)
+            }
+            None => DEFAULT_IGNORES.contains(&name.as_str()),
+        }
+    }
+
+    fn expand_token(&self, path: &Path) -> String {
+        serde_json::to_string(&ExpandRequest {
+            root: self.root.to_string_lossy().to_string(),
+            path: path.to_string_lossy().to_string(),
+            ignore_globs: self.ignore_globs.to_vec(),
+        })
+        .unwrap_or_default()
+    }
+}
+
+/// Walk `path` to `depth` levels (at least one, at most `MAX_TREE_DEPTH`)
+pub fn build_tree(
+    root: &Path,
+    path: &Path,
+    depth: u32,
+    ignore_globs: &[String],
+) -> Result<TreeNode, String> {
+    if !path.is_dir() {
+        return Err(format!("Path is not a directory: {}", path.display()));
+    }
+    let mut walk = Walk {
+        root,
+        ignore_globs,
+        repo: RepoStatus::load(path, true),
+        entries: 0,
+    };
+    Ok(walk.node(path, depth.clamp(1, MAX_TREE_DEPTH)))
+}
+
+/// Walk the directory an expand token points at
+pub fn expand_tree(token: &str, depth: u32) -> Result<TreeNode, String> {
+    let request: ExpandRequest =
+        serde_json::from_str(token).map_err(|_| "Invalid expand token".to_string())?;
+    build_tree(
+        Path::new(&request.root),
+        Path::new(&request.path),
+        depth,
+        &request.ignore_globs,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_porcelain() {
+        let output =
+            " M src/lib.rs\0R  new.rs\0old.rs\0?? notes/\0!! target/\0UU merge.rs\0A  added.rs\0";
+        assert_eq!(
+            parse_porcelain(output),
+            vec![
+                ("src/lib.rs".to_string(), GitFileStatus::Modified),
+                ("new.rs".to_string(), GitFileStatus::Renamed),
+                ("notes".to_string(), GitFileStatus::Untracked),
+                ("target".to_string(), GitFileStatus::Ignored),
+                ("merge.rs".to_string(), GitFileStatus::Conflicted),
+                ("added.rs".to_string(), GitFileStatus::Added),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_status_of() {
+        let repo = RepoStatus {
+            root: PathBuf::from("/repo"),
+            files: parse_porcelain(" M src/acp/client.rs\0?? notes\0!! target\0")
+                .into_iter()
+                .collect(),
+        };
+        assert_eq!(
+            repo.status_of("src/acp/client.rs", false),
+            Some(GitFileStatus::Modified)
+        );
+        assert_eq!(repo.status_of("src", true), Some(GitFileStatus::Modified));
+        assert_eq!(
+            repo.status_of("notes/todo.md", false),
+            Some(GitFileStatus::Untracked)
+        );
+        assert_eq!(
+            repo.status_of("target/debug", true),
+            Some(GitFileStatus::Ignored)
+        );
+        assert_eq!(repo.status_of("src/lib.rs", false), None);
+        assert_eq!(repo.status_of("sr", true), None);
+        assert_eq!(repo.status_of("", true), Some(GitFileStatus::Modified));
+        assert_eq!(repo.entries().len(), 2);
+    }
+
+    #[test]
+    fn test_glob_ignores() {
+        let globs = vec![
+            "*.log".to_string(),
+            "docs/**/*.png".to_string(),
+            "dist/".to_string(),
+        ];
+        assert!(is_glob_ignored(&globs, "debug.log", "logs/debug.log"));
+        assert!(is_glob_ignored(&globs, "a.png", "docs/img/a/a.png"));
+        assert!(is_glob_ignored(&globs, "a.png", "docs/a.png"));
+        assert!(!is_glob_ignored(&globs, "a.png", "src/a.png"));
+        assert!(is_glob_ignored(&globs, "dist", "dist"));
+        assert!(!glob_match("src/*.rs", "src/acp/client.rs"));
+        assert!(glob_match("src/?ib.rs", "src/lib.rs"));
+    }
+
+    #[test]
+    fn test_build_tree_outside_git() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("src/deep")).unwrap();
+        fs::create_dir_all(root.join("node_modules/pkg")).unwrap();
+        fs::write(root.join("src/deep/file.rs"), "fn main() {}").unwrap();
+        fs::write(root.join("README.md"), "hi").unwrap();
+        fs::write(root.join("debug.log"), "").unwrap();
+
+        let tree = build_tree(root, root, 1, &["*.log".to_string()]).unwrap();
+        let children = tree.children.unwrap();
+        let names: Vec<&str> = children.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["src", "README.md"]);
+        assert_eq!(children[1].size, Some(2));
+
+        let src = &children[0];
+        assert!(src.children.is_none());
+        let expanded = expand_tree(src.expand_token.as_deref().unwrap(), 2).unwrap();
+        let deep = &expanded.children.unwrap()[0];
+        assert_eq!(deep.name, "deep");
+        assert_eq!(deep.children.as_ref().unwrap()[0].name, "file.rs");
+    }
+}
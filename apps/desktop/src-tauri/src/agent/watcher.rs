@@ -0,0 +1,342 @@
+//! Workspace file watcher
+//!
+//! One recursive notify watcher per open workspace. Raw events are
+//! coalesced per path over a short quiet window, filtered like
+//! `read_directory_tree` (.git, git-ignored paths, the caller's ignore
+//! globs) and emitted as one `fs-changed` event per batch, so the explorer
+//! and diff view follow agent edits without polling.
+
+use crate::agent::tree::{is_glob_ignored, DEFAULT_IGNORES};
+use crate::events::{emit, FsChangedEvent};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
+use ts_rs::TS;
+
+/// Quiet time before a batch is emitted
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Longest a batch is held back while changes keep coming
+const MAX_BATCH_DELAY: Duration = Duration::from_secs(1);
+
+/// Watchers of open workspaces (root path -> watcher). Dropping a watcher
+/// stops its batching thread.
+static WATCHERS: Lazy<Mutex<HashMap<String, RecommendedWatcher>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum FsChangeKind {
+    Created,
+    Modified,
+    Deleted,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, TS)]
+#[ts(export)]
+pub struct FsChange {
+    pub kind: FsChangeKind,
+    pub path: String,
+    /// Always false for deleted paths, whose type can't be known
+    pub is_dir: bool,
+}
+
+/// What the raw events said about a path within one batch
+#[derive(Debug, Default, Clone, Copy)]
+struct PathEvents {
+    created: bool,
+    renamed: bool,
+}
+
+/// Collapse a batch into one change per path, judged by whether the path
+/// exists now (`stat` returns Some(is_dir) for existing paths). Files
+/// created and removed within the batch (editor temp files) are dropped.
+fn coalesce(
+    batch: &[(PathBuf, PathEvents)],
+    stat: impl Fn(&Path) -> Option<bool>,
+) -> Vec<FsChange> {
+    batch
+        .iter()
+        .filter_map(|(path, events)| {
+            let (kind, is_dir) = match stat(path) {
+                Some(is_dir) if events.created || events.renamed => (FsChangeKind::Created, is_dir),
+                Some(is_dir) => (FsChangeKind::Modified, is_dir),
+                None if events.created && !events.renamed => return None,
+                None => (FsChangeKind::Deleted, false),
+            };
+            Some(FsChange {
+                kind,
+                path: path.to_string_lossy().to_string(),
+                is_dir,
+            })
+        })
+        .collect()
+}
+
+/// Add a raw event to the batch, keeping first-seen order
+fn record(batch: &mut Vec<(PathBuf, PathEvents)>, event: &Event) {
+    let (created, renamed) = match event.kind {
+        EventKind::Create(_) => (true, false),
+        EventKind::Modify(notify::event::ModifyKind::Name(_)) => (false, true),
+        EventKind::Modify(_) | EventKind::Remove(_) => (false, false),
+        EventKind::Access(_) | EventKind::Any | EventKind::Other => return,
+    };
+    for path in &event.paths {
+        let index = match batch.iter().position(|(p, _)| p == path) {
+            Some(index) => index,
+            None => {
+                batch.push((path.clone(), PathEvents::default()));
+                batch.len() - 1
+            }
+        };
+        let events = &mut batch[index].1;
+        events.created |= created;
+        events.renamed |= renamed;
+    }
+}
+
+/// Paths git ignores, asked in one `git check-ignore` call. None outside a
+/// repository.
+fn git_ignored(root: &Path, paths: &[String]) -> Option<Vec<String>> {
+    let mut child = Command::new("git")
+        .current_dir(root)
+        .args(["check-ignore", "--stdin", "-z"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+    let input = paths.join("\0");
+    child.stdin.take()?.write_all(input.as_bytes()).ok()?;
+    let output = child.wait_with_output().ok()?;
+    // Exit code 1 means nothing is ignored; 128 means not a repository
+    match output.status.code() {
+        Some(0) | Some(1) => Some(
+            String::from_utf8_lossy(&output.stdout)
+                .split('\0')
+                .filter(|p| !p.is_empty())
+                .map(str::to_string)
+                .collect(),
+        ),
+        _ => None,
+    }
+}
+
+/// Drop changes to .git, ignored paths and paths matching `ignore_globs`
+fn filter_ignored(root: &Path, changes: Vec<FsChange>, ignore_globs: &[String]) -> Vec<FsChange> {
+    let changes: Vec<FsChange> = changes
+        .into_iter()
+        .filter(|change| {
+            let rel = Path::new(&change.path)
+                .strip_prefix(root)
+                .unwrap_or(Path::new(&change.path));
+            let name = rel
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            !rel.components().any(|c| c.as_os_str() == ".git")
+                && !is_glob_ignored(ignore_globs, &name, &rel.to_string_lossy())
+        })
+        .collect();
+    if changes.is_empty() {
+        return changes;
+    }
+
+    let paths: Vec<String> = changes.iter().map(|c| c.path.clone()).collect();
+    match git_ignored(root, &paths) {
+        Some(ignored) => changes
+            .into_iter()
+            .filter(|c| !ignored.contains(&c.path))
+            .collect(),
+        None => changes
+            .into_iter()
+            .filter(|c| {
+                let rel = Path::new(&c.path)
+                    .strip_prefix(root)
+                    .unwrap_or(Path::new(&c.path));
+                !rel.components().any(|part| {
+                    DEFAULT_IGNORES.contains(&part.as_os_str().to_string_lossy().as_ref())
+                })
+            })
+            .collect(),
+    }
+}
+
+/// Batch raw events until the watcher is dropped
+fn run_batches(
+    root: PathBuf,
+    ignore_globs: Vec<String>,
+    events: mpsc::Receiver<notify::Result<Event>>,
+    app_handle: AppHandle,
+) {
+    let mut batch: Vec<(PathBuf, PathEvents)> = Vec::new();
+    let mut batch_started = Instant::now();
+    loop {
+        let timeout = if batch.is_empty() {
+            Duration::from_secs(3600)
+        } else {
+            DEBOUNCE.min(MAX_BATCH_DELAY.saturating_sub(batch_started.elapsed()))
+        };
+        // A full-delay batch is flushed even if events are still queued
+        let received = if timeout.is_zero() {
+            Err(RecvTimeoutError::Timeout)
+        } else {
+            events.recv_timeout(timeout)
+        };
+        match received {
+            Ok(Ok(event)) => {
+                if batch.is_empty() {
+                    batch_started = Instant::now();
+                }
+                record(&mut batch, &event);
+                continue;
+            }
+            Ok(Err(e)) => {
+                eprintln!("[Watcher] Error watching {:?}: {}", root, e);
+                continue;
+            }
+            Err(RecvTimeoutError::Timeout) if batch.is_empty() => continue,
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+
+        let changes = coalesce(&batch, |path| {
+            std::fs::symlink_metadata(path).ok().map(|m| m.is_dir())
+        });
+        batch.clear();
+        let changes = filter_ignored(&root, changes, &ignore_globs);
+        if !changes.is_empty() {
+            emit(
+                &app_handle,
+                &FsChangedEvent {
+                    root: root.to_string_lossy().to_string(),
+                    changes,
+                },
+            );
+        }
+    }
+    eprintln!("[Watcher] Stopped watching {:?}", root);
+}
+
+/// Start watching a workspace for file changes (no-op if already watched)
+#[tauri::command]
+pub fn watch_workspace(
+    path: String,
+    ignore_globs: Option<Vec<String>>,
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    let mut watchers = WATCHERS.lock();
+    if watchers.contains_key(&path) {
+        return Ok(());
+    }
+    let root = PathBuf::from(&path);
+    if !root.is_dir() {
+        return Err(format!("Path is not a directory: {}", path));
+    }
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(move |event| {
+        let _ = tx.send(event);
+    })
+    .map_err(|e| format!("Failed to create watcher: {}", e))?;
+    watcher
+        .watch(&root, RecursiveMode::Recursive)
+        .map_err(|e| format!("Failed to watch {}: {}", path, e))?;
+
+    let ignore_globs = ignore_globs.unwrap_or_default();
+    thread::spawn(move || run_batches(root, ignore_globs, rx, app_handle));
+    eprintln!("[Watcher] Watching {}", path);
+    watchers.insert(path, watcher);
+    Ok(())
+}
+
+/// Stop watching a workspace. Returns false if it wasn't watched.
+#[tauri::command]
+pub fn unwatch_workspace(path: String) -> bool {
+    WATCHERS.lock().remove(&path).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{CreateKind, ModifyKind, RemoveKind, RenameMode};
+
+    fn event(kind: EventKind, path: &str) -> Event {
+        Event::new(kind).add_path(PathBuf::from(path))
+    }
+
+    #[test]
+    fn test_coalesce() {
+        let mut batch = Vec::new();
+        for e in [
+            event(EventKind::Create(CreateKind::File), "/w/new.rs"),
+            event(EventKind::Modify(ModifyKind::Any), "/w/new.rs"),
+            event(EventKind::Modify(ModifyKind::Any), "/w/lib.rs"),
+            event(EventKind::Create(CreateKind::File), "/w/.tmp123"),
+            event(EventKind::Remove(RemoveKind::File), "/w/.tmp123"),
+            event(EventKind::Remove(RemoveKind::Folder), "/w/old"),
+            event(
+                EventKind::Modify(ModifyKind::Name(RenameMode::From)),
+                "/w/a.rs",
+            ),
+            event(
+                EventKind::Modify(ModifyKind::Name(RenameMode::To)),
+                "/w/dir",
+            ),
+        ] {
+            record(&mut batch, &e);
+        }
+        let existing = ["/w/new.rs", "/w/lib.rs", "/w/dir"];
+        let changes = coalesce(&batch, |path| {
+            let path = path.to_str().unwrap();
+            existing.contains(&path).then_some(path == "/w/dir")
+        });
+        let change = |kind, path: &str, is_dir| FsChange {
+            kind,
+            path: path.to_string(),
+            is_dir,
+        };
+        assert_eq!(
+            changes,
+            vec![
+                change(FsChangeKind::Created, "/w/new.rs", false),
+                change(FsChangeKind::Modified, "/w/lib.rs", false),
+                change(FsChangeKind::Deleted, "/w/old", false),
+                change(FsChangeKind::Deleted, "/w/a.rs", false),
+                change(FsChangeKind::Created, "/w/dir", true),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_filter_ignored_outside_git() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let change = |rel: &str| FsChange {
+            kind: FsChangeKind::Modified,
+            path: root.join(rel).to_string_lossy().to_string(),
+            is_dir: false,
+        };
+        let kept = filter_ignored(
+            root,
+            vec![
+                change("src/lib.rs"),
+                change(".git/index"),
+                change("node_modules/a/index.js"),
+                change("debug.log"),
+            ],
+            &["*.log".to_string()],
+        );
+        assert_eq!(kept, vec![change("src/lib.rs")]);
+    }
+}
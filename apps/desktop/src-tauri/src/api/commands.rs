@@ -0,0 +1,24 @@
+use super::server::{self, ApiServerStatus};
+use tauri::AppHandle;
+
+/// Start the embedded API server with the saved settings
+#[tauri::command]
+pub async fn start_api_server(app_handle: AppHandle) -> Result<ApiServerStatus, String> {
+    server::start(app_handle).await
+}
+
+#[tauri::command]
+pub fn stop_api_server() -> ApiServerStatus {
+    server::stop()
+}
+
+#[tauri::command]
+pub fn get_api_server_status() -> ApiServerStatus {
+    server::status()
+}
+
+/// Generate a new API token, invalidating the old one
+#[tauri::command]
+pub fn rotate_api_token() -> Result<String, String> {
+    server::rotate_token()
+}
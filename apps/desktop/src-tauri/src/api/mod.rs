@@ -0,0 +1,12 @@
+//! Embedded HTTP/WebSocket API
+//!
+//! An optional axum server that exposes the core session commands (create
+//! session, send prompt, list sessions) over HTTP and streams worker events
+//! over a WebSocket, so editors, scripts and remote UIs can drive the
+//! orchestration engine without Tauri IPC. Every endpoint except
+//! `/api/health` requires the bearer token from `ApiServerSettings`.
+
+pub mod commands;
+mod server;
+
+pub use server::start;
@@ -0,0 +1,443 @@
+use crate::acp::commands::{self as acp_commands, AcpSessionResponse};
+use crate::acp::events::{set_bus_enabled, subscribe_since, BusEvent};
+use crate::acp::registry::AgentConfig;
+use crate::acp::session_store::PersistedSessionSummary;
+use crate::error::{CommandError, ErrorCode};
+use crate::orchestrator::commands::{self as orchestrator_commands, SessionResponse};
+use crate::orchestrator::session::OrchestratorSession;
+use crate::settings::load_settings;
+use crate::settings::store::SettingsStore;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Path, Query, Request, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use once_cell::sync::Lazy;
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tauri::{AppHandle, Manager};
+use tokio::net::TcpListener;
+use tokio::sync::{broadcast, watch};
+use uuid::Uuid;
+
+struct RunningServer {
+    address: SocketAddr,
+    token: Arc<RwLock<String>>,
+    /// Dropping the sender shuts the server down and closes event streams
+    _shutdown: watch::Sender<()>,
+}
+
+static SERVER: Lazy<Mutex<Option<RunningServer>>> = Lazy::new(|| Mutex::new(None));
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ApiServerStatus {
+    pub running: bool,
+    /// Bound address, e.g. "127.0.0.1:7420"
+    pub address: Option<String>,
+}
+
+#[derive(Clone)]
+struct ApiState {
+    app: AppHandle,
+    token: Arc<RwLock<String>>,
+    shutdown: watch::Receiver<()>,
+}
+
+struct ApiError(StatusCode, String);
+
+impl From<String> for ApiError {
+    fn from(message: String) -> Self {
+        Self(StatusCode::BAD_REQUEST, message)
+    }
+}
+
+impl From<CommandError> for ApiError {
+    fn from(e: CommandError) -> Self {
+        let status = match e.code {
+            ErrorCode::NotFound => StatusCode::NOT_FOUND,
+            ErrorCode::AuthRequired | ErrorCode::TrustRequired => StatusCode::FORBIDDEN,
+            ErrorCode::WorkerUnavailable => StatusCode::SERVICE_UNAVAILABLE,
+            ErrorCode::LimitReached => StatusCode::TOO_MANY_REQUESTS,
+            ErrorCode::BudgetExceeded => StatusCode::PAYMENT_REQUIRED,
+            ErrorCode::AgentFailed | ErrorCode::Io | ErrorCode::Internal => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+            ErrorCode::InvalidInput | ErrorCode::AgentUnavailable | ErrorCode::Cancelled => {
+                StatusCode::BAD_REQUEST
+            }
+        };
+        Self(status, e.message)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(serde_json::json!({ "error": self.1 }))).into_response()
+    }
+}
+
+type ApiResult<T> = Result<Json<T>, ApiError>;
+
+fn generate_token() -> String {
+    format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+/// Token from an `Authorization: Bearer <token>` header
+fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+        .map(str::trim)
+}
+
+/// Token from a `?token=` query parameter (browsers can't set headers on
+/// WebSocket requests)
+fn query_token(query: Option<&str>) -> Option<&str> {
+    query?
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("token="))
+}
+
+/// Compare tokens without short-circuiting on the first mismatch
+fn tokens_match(provided: &str, expected: &str) -> bool {
+    !expected.is_empty()
+        && provided.len() == expected.len()
+        && provided
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+async fn require_token(State(state): State<ApiState>, request: Request, next: Next) -> Response {
+    let provided = bearer_token(request.headers()).or_else(|| query_token(request.uri().query()));
+    let authorized = provided.is_some_and(|token| tokens_match(token, &state.token.read()));
+    if authorized {
+        next.run(request).await
+    } else {
+        ApiError(
+            StatusCode::UNAUTHORIZED,
+            "Invalid or missing API token".to_string(),
+        )
+        .into_response()
+    }
+}
+
+async fn health() -> Json<serde_json::Value> {
+    Json(serde_json::json!({ "ok": true, "version": env!("CARGO_PKG_VERSION") }))
+}
+
+async fn list_agents() -> Json<Vec<AgentConfig>> {
+    Json(acp_commands::list_available_agents())
+}
+
+async fn list_sessions(State(state): State<ApiState>) -> Json<Vec<OrchestratorSession>> {
+    Json(orchestrator_commands::list_orchestrator_sessions(
+        state.app.state(),
+    ))
+}
+
+async fn list_persisted_sessions() -> ApiResult<Vec<PersistedSessionSummary>> {
+    Ok(Json(acp_commands::list_persisted_sessions(None)?))
+}
+
+async fn get_session(
+    State(state): State<ApiState>,
+    Path(session_id): Path<String>,
+) -> ApiResult<SessionResponse> {
+    Ok(Json(orchestrator_commands::get_orchestrator_session(
+        session_id,
+        state.app.state(),
+    )?))
+}
+
+#[derive(Deserialize)]
+struct CreateSessionBody {
+    prompt: String,
+    agent_id: String,
+    model_id: Option<String>,
+    cwd: String,
+    language: Option<String>,
+}
+
+async fn create_session(
+    State(state): State<ApiState>,
+    Json(body): Json<CreateSessionBody>,
+) -> ApiResult<AcpSessionResponse> {
+    let response = acp_commands::create_acp_session(
+        body.prompt,
+        body.agent_id,
+        body.model_id,
+        body.cwd,
+        body.language,
+        state.app.clone(),
+        state.app.state(),
+    )
+    .await?;
+    Ok(Json(response))
+}
+
+#[derive(Deserialize)]
+struct PromptBody {
+    prompt: String,
+}
+
+async fn send_prompt(
+    State(state): State<ApiState>,
+    Path(session_id): Path<String>,
+    Json(body): Json<PromptBody>,
+) -> Result<StatusCode, ApiError> {
+    acp_commands::send_acp_prompt(
+        session_id,
+        body.prompt,
+        state.app.clone(),
+        state.app.state(),
+    )
+    .await?;
+    Ok(StatusCode::ACCEPTED)
+}
+
+#[derive(Deserialize)]
+struct PermissionBody {
+    option_id: String,
+}
+
+async fn respond_to_permission(
+    Path(worker_id): Path<String>,
+    Json(body): Json<PermissionBody>,
+) -> Result<StatusCode, ApiError> {
+    acp_commands::respond_to_permission(worker_id, body.option_id)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Deserialize)]
+struct EventsQuery {
+    /// Last sequence number the client saw (set when reconnecting)
+    since: Option<u64>,
+}
+
+async fn events(
+    State(state): State<ApiState>,
+    ws: WebSocketUpgrade,
+    Query(query): Query<EventsQuery>,
+) -> Response {
+    ws.on_upgrade(move |socket| stream_events(socket, query.since, state.shutdown))
+}
+
+/// Control event telling the client it missed events and must refetch state
+fn resync_event(reason: &str) -> BusEvent {
+    BusEvent {
+        seq: 0,
+        event: "resync".to_string(),
+        payload: serde_json::json!({ "reason": reason }),
+    }
+}
+
+async fn send_event(socket: &mut WebSocket, event: &BusEvent) -> bool {
+    let Ok(text) = serde_json::to_string(event) else {
+        return true;
+    };
+    socket.send(Message::Text(text.into())).await.is_ok()
+}
+
+/// Forward bus events to a WebSocket client until either side closes.
+/// Reconnecting clients first get the events they missed.
+async fn stream_events(
+    mut socket: WebSocket,
+    since: Option<u64>,
+    mut shutdown: watch::Receiver<()>,
+) {
+    let (mut rx, replay) = subscribe_since(since);
+    let mut last_seq = since.unwrap_or(0);
+    match replay {
+        Some(events) => {
+            for event in &events {
+                if !send_event(&mut socket, event).await {
+                    return;
+                }
+                last_seq = event.seq;
+            }
+        }
+        None => {
+            if !send_event(&mut socket, &resync_event("gap")).await {
+                return;
+            }
+            last_seq = 0;
+        }
+    }
+
+    loop {
+        tokio::select! {
+            event = rx.recv() => {
+                let event = match event {
+                    // Already sent as part of the replay
+                    Ok(event) if event.seq <= last_seq => continue,
+                    Ok(event) => event,
+                    Err(broadcast::error::RecvError::Lagged(_)) => resync_event("lagged"),
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                if event.seq > 0 {
+                    last_seq = event.seq;
+                }
+                if !send_event(&mut socket, &event).await {
+                    break;
+                }
+            }
+            message = socket.recv() => match message {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                _ => {}
+            },
+            _ = shutdown.changed() => {
+                let _ = socket.send(Message::Close(None)).await;
+                break;
+            }
+        }
+    }
+}
+
+fn router(state: ApiState) -> Router {
+    let authenticated = Router::new()
+        .route("/api/agents", get(list_agents))
+        .route("/api/sessions", get(list_sessions).post(create_session))
+        .route("/api/sessions/persisted", get(list_persisted_sessions))
+        .route("/api/sessions/{session_id}", get(get_session))
+        .route("/api/sessions/{session_id}/prompt", post(send_prompt))
+        .route(
+            "/api/workers/{worker_id}/permission",
+            post(respond_to_permission),
+        )
+        .route("/api/events", get(events))
+        .route_layer(middleware::from_fn_with_state(state.clone(), require_token));
+
+    Router::new()
+        .route("/api/health", get(health))
+        .merge(authenticated)
+        .with_state(state)
+}
+
+pub fn status() -> ApiServerStatus {
+    let server = SERVER.lock();
+    ApiServerStatus {
+        running: server.is_some(),
+        address: server.as_ref().map(|s| s.address.to_string()),
+    }
+}
+
+/// Start the API server using the saved settings. Generates and saves a
+/// token on first start. No-op if the server is already running.
+pub async fn start(app: AppHandle) -> Result<ApiServerStatus, String> {
+    if SERVER.lock().is_some() {
+        return Ok(status());
+    }
+
+    let mut settings = load_settings();
+    if settings.api_server.token.is_empty() {
+        settings.api_server.token = generate_token();
+        SettingsStore::new()?.save(&settings)?;
+    }
+    let config = settings.api_server;
+
+    let listener = TcpListener::bind((config.host.as_str(), config.port))
+        .await
+        .map_err(|e| format!("Failed to bind {}:{}: {}", config.host, config.port, e))?;
+    let address = listener
+        .local_addr()
+        .map_err(|e| format!("Failed to read server address: {}", e))?;
+
+    let token = Arc::new(RwLock::new(config.token));
+    let (shutdown_tx, mut shutdown_rx) = watch::channel(());
+    let app_router = router(ApiState {
+        app,
+        token: token.clone(),
+        shutdown: shutdown_rx.clone(),
+    });
+
+    tauri::async_runtime::spawn(async move {
+        let result = axum::serve(listener, app_router)
+            .with_graceful_shutdown(async move {
+                let _ = shutdown_rx.changed().await;
+            })
+            .await;
+        if let Err(e) = result {
+            eprintln!("[API] Server error: {}", e);
+            SERVER.lock().take();
+        }
+    });
+
+    set_bus_enabled(true);
+    eprintln!("[API] Listening on http://{}", address);
+    *SERVER.lock() = Some(RunningServer {
+        address,
+        token,
+        _shutdown: shutdown_tx,
+    });
+    Ok(status())
+}
+
+pub fn stop() -> ApiServerStatus {
+    if let Some(server) = SERVER.lock().take() {
+        set_bus_enabled(false);
+        eprintln!("[API] Stopped server on {}", server.address);
+    }
+    status()
+}
+
+/// Replace the API token. A running server accepts the new token immediately.
+pub fn rotate_token() -> Result<String, String> {
+    let store = SettingsStore::new()?;
+    let mut settings = store.load()?;
+    settings.api_server.token = generate_token();
+    store.save(&settings)?;
+
+    if let Some(server) = SERVER.lock().as_ref() {
+        *server.token.write() = settings.api_server.token.clone();
+    }
+    Ok(settings.api_server.token)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    #[test]
+    fn test_bearer_token() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(bearer_token(&headers), None);
+
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_static("Bearer abc123"),
+        );
+        assert_eq!(bearer_token(&headers), Some("abc123"));
+
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_static("Basic abc123"),
+        );
+        assert_eq!(bearer_token(&headers), None);
+    }
+
+    #[test]
+    fn test_query_token() {
+        assert_eq!(query_token(None), None);
+        assert_eq!(query_token(Some("token=abc")), Some("abc"));
+        assert_eq!(query_token(Some("session=1&token=abc")), Some("abc"));
+        assert_eq!(query_token(Some("session=1")), None);
+    }
+
+    #[test]
+    fn test_tokens_match() {
+        let token = generate_token();
+        assert_eq!(token.len(), 64);
+        assert!(tokens_match(&token, &token));
+        assert!(!tokens_match("wrong", &token));
+        assert!(!tokens_match("", ""));
+    }
+}
@@ -0,0 +1,97 @@
+//! `crafter` — run agents and PRDs without the desktop app
+//!
+//! Events are streamed to stdout as JSON lines; logs go to stderr.
+
+use crafter_code_lib::headless::{run_prd, run_prompt, PrdRunOptions, RunOptions};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+const USAGE: &str = "Usage:
+  crafter run --agent <id> --prompt <text> [--cwd <dir>] [--model <model>] [--yes]
+  crafter prd run <prd.json> [--agent <id>] [--cwd <dir>] [--model <model>] [--yes]
+
+Options:
+  --agent <id>      Agent to run (default: claude)
+  --cwd <dir>       Working directory (default: current directory)
+  --model <model>   Model override
+  --yes             Approve agent permission requests (default: reject)";
+
+#[derive(Default)]
+struct Flags {
+    agent: Option<String>,
+    prompt: Option<String>,
+    cwd: Option<String>,
+    model: Option<String>,
+    yes: bool,
+    positional: Vec<String>,
+}
+
+fn parse_flags(args: &[String]) -> Result<Flags, String> {
+    let mut flags = Flags::default();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let mut value = |name: &str| {
+            iter.next()
+                .cloned()
+                .ok_or_else(|| format!("Missing value for {}", name))
+        };
+        match arg.as_str() {
+            "--agent" => flags.agent = Some(value("--agent")?),
+            "--prompt" | "-p" => flags.prompt = Some(value("--prompt")?),
+            "--cwd" => flags.cwd = Some(value("--cwd")?),
+            "--model" => flags.model = Some(value("--model")?),
+            "--yes" | "-y" => flags.yes = true,
+            other if other.starts_with('-') => return Err(format!("Unknown option: {}", other)),
+            other => flags.positional.push(other.to_string()),
+        }
+    }
+    Ok(flags)
+}
+
+fn current_dir() -> Result<String, String> {
+    std::env::current_dir()
+        .map(|p| p.to_string_lossy().to_string())
+        .map_err(|e| format!("Failed to get current directory: {}", e))
+}
+
+fn run(args: &[String]) -> Result<(), String> {
+    match args.first().map(|s| s.as_str()) {
+        Some("run") => {
+            let flags = parse_flags(&args[1..])?;
+            let prompt = flags
+                .prompt
+                .or_else(|| (!flags.positional.is_empty()).then(|| flags.positional.join(" ")))
+                .ok_or("Missing --prompt")?;
+            run_prompt(RunOptions {
+                agent: flags.agent.unwrap_or_else(|| "claude".to_string()),
+                prompt,
+                cwd: flags.cwd.map_or_else(current_dir, Ok)?,
+                model: flags.model,
+                auto_approve: flags.yes,
+            })
+        }
+        Some("prd") if args.get(1).map(|s| s.as_str()) == Some("run") => {
+            let flags = parse_flags(&args[2..])?;
+            let path = flags.positional.first().ok_or("Missing PRD file")?;
+            run_prd(PrdRunOptions {
+                path: PathBuf::from(path),
+                agent: flags.agent.unwrap_or_else(|| "claude".to_string()),
+                cwd: flags.cwd.map_or_else(current_dir, Ok)?,
+                model: flags.model,
+                auto_approve: flags.yes,
+            })
+        }
+        _ => Err(USAGE.to_string()),
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -1,15 +1,29 @@
 use crate::claude::pricing::{calculate_cost, Model};
-use crate::claude::types::{
-    Message, MessageRequest, StreamEvent, Usage, WorkerEventType, WorkerStreamEvent,
-};
+use crate::claude::types::{Message, MessageRequest, StreamEvent, Usage};
+use crate::events::{emit, TokenUsage, WorkerEvent, WorkerEventType, WorkerStreamEvent};
+use crate::secrets::env_or_secret;
 use futures_util::StreamExt;
 use reqwest::Client;
-use tauri::{AppHandle, Emitter};
+use tauri::AppHandle;
 use thiserror::Error;
 
 const ANTHROPIC_API_URL: &str = "https://api.anthropic.com/v1/messages";
 const ANTHROPIC_VERSION: &str = "2023-06-01";
 
+/// Direct API calls have no turns, so their events carry no prompt id
+fn emit_stream_event(app_handle: &AppHandle, worker_id: &str, kind: WorkerEventType) {
+    emit(
+        app_handle,
+        &WorkerStreamEvent {
+            worker_id: worker_id.to_string(),
+            event: WorkerEvent {
+                prompt_id: None,
+                kind,
+            },
+        },
+    );
+}
+
 #[derive(Error, Debug)]
 #[allow(dead_code)]
 pub enum ClaudeError {
@@ -44,8 +58,7 @@ impl ClaudeClient {
     }
 
     pub fn from_env() -> Result<Self, ClaudeError> {
-        let api_key = std::env::var("ANTHROPIC_API_KEY")
-            .map_err(|_| ClaudeError::MissingApiKey)?;
+        let api_key = env_or_secret("ANTHROPIC_API_KEY").ok_or(ClaudeError::MissingApiKey)?;
         Self::new(api_key)
     }
 
@@ -102,12 +115,10 @@ impl ClaudeClient {
                             StreamEvent::ContentBlockDelta { delta, .. } => {
                                 let crate::claude::types::ContentDelta::TextDelta { text } = delta;
                                 output.push_str(&text);
-                                let _ = app_handle.emit(
-                                    &format!("worker-stream-{}", worker_id),
-                                    WorkerStreamEvent {
-                                        worker_id: worker_id.clone(),
-                                        event: WorkerEventType::Delta { text },
-                                    },
+                                emit_stream_event(
+                                    &app_handle,
+                                    &worker_id,
+                                    WorkerEventType::Delta { text },
                                 );
                             }
                             StreamEvent::MessageDelta { usage, .. } => {
@@ -117,13 +128,11 @@ impl ClaudeClient {
                                 final_usage = message.usage;
                             }
                             StreamEvent::Error { error } => {
-                                let _ = app_handle.emit(
-                                    &format!("worker-stream-{}", worker_id),
-                                    WorkerStreamEvent {
-                                        worker_id: worker_id.clone(),
-                                        event: WorkerEventType::Error {
-                                            message: error.message,
-                                        },
+                                emit_stream_event(
+                                    &app_handle,
+                                    &worker_id,
+                                    WorkerEventType::Error {
+                                        message: error.message,
                                     },
                                 );
                             }
@@ -136,14 +145,20 @@ impl ClaudeClient {
 
         let cost = calculate_cost(model, final_usage.input_tokens, final_usage.output_tokens);
 
-        let _ = app_handle.emit(
-            &format!("worker-stream-{}", worker_id),
-            WorkerStreamEvent {
-                worker_id: worker_id.clone(),
-                event: WorkerEventType::Complete {
-                    output: output.clone(),
-                    usage: final_usage.clone(),
+        emit_stream_event(
+            &app_handle,
+            &worker_id,
+            WorkerEventType::Complete {
+                output: output.clone(),
+                thinking: None,
+                usage: TokenUsage {
+                    input_tokens: final_usage.input_tokens,
+                    output_tokens: final_usage.output_tokens,
+                    estimated: false,
                 },
+                cost_usd: Some(cost),
+                metrics: None,
+                context: None,
             },
         );
 
@@ -1,11 +1,16 @@
+use crate::settings::load_settings;
+use crate::settings::store::ModelPrice;
+use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum Model {
     #[serde(rename = "claude-opus-4-5-20251101")]
     Opus,
     #[serde(rename = "claude-sonnet-4-20250514")]
+    #[default]
     Sonnet,
     #[serde(rename = "claude-3-5-haiku-20241022")]
     Haiku,
@@ -38,39 +43,115 @@ impl Model {
     }
 }
 
-impl Default for Model {
-    fn default() -> Self {
-        Model::Sonnet
-    }
+/// Per-model prices, from the bundled pricing.json plus user overrides
+#[derive(Debug, Clone)]
+pub struct PricingRegistry {
+    default: ModelPrice,
+    /// Lowercased model id prefixes, aliases and agent ids
+    prices: HashMap<String, ModelPrice>,
+}
+
+#[derive(Deserialize)]
+struct PricingFile {
+    default: ModelPrice,
+    models: HashMap<String, PricingEntry>,
 }
 
-struct Pricing {
-    input_per_million: f64,
-    output_per_million: f64,
+#[derive(Deserialize)]
+struct PricingEntry {
+    #[serde(flatten)]
+    price: ModelPrice,
+    #[serde(default)]
+    aliases: Vec<String>,
 }
 
-fn get_pricing(model: &Model) -> Pricing {
-    match model {
-        Model::Opus => Pricing {
-            input_per_million: 15.0,
-            output_per_million: 75.0,
-        },
-        Model::Sonnet => Pricing {
-            input_per_million: 3.0,
-            output_per_million: 15.0,
-        },
-        Model::Haiku => Pricing {
-            input_per_million: 0.80,
-            output_per_million: 4.0,
-        },
+static BUNDLED: Lazy<PricingRegistry> = Lazy::new(|| {
+    PricingRegistry::from_json(include_str!("pricing.json")).expect("bundled pricing.json is valid")
+});
+
+impl PricingRegistry {
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let file: PricingFile =
+            serde_json::from_str(json).map_err(|e| format!("Invalid pricing table: {}", e))?;
+        let mut prices = HashMap::new();
+        for (id, entry) in file.models {
+            for alias in entry.aliases {
+                prices.insert(alias.to_lowercase(), entry.price);
+            }
+            prices.insert(id.to_lowercase(), entry.price);
+        }
+        Ok(Self {
+            default: file.default,
+            prices,
+        })
+    }
+
+    /// The table shipped with the app
+    pub fn bundled() -> Self {
+        BUNDLED.clone()
+    }
+
+    /// Replace or add prices (keys as in pricing.json, or an agent id)
+    pub fn with_overrides(mut self, overrides: &HashMap<String, ModelPrice>) -> Self {
+        for (key, price) in overrides {
+            self.prices.insert(key.to_lowercase(), *price);
+        }
+        self
+    }
+
+    /// Price for a model: exact id or alias, then the longest id prefix
+    /// (so dated ids like "claude-sonnet-4-5-20250929" match), then the
+    /// agent's own entry, then the default.
+    pub fn price(&self, agent_id: Option<&str>, model: &str) -> ModelPrice {
+        let model = model.to_lowercase();
+        if let Some(price) = self.prices.get(&model) {
+            return *price;
+        }
+        self.prices
+            .iter()
+            .filter(|(key, _)| model.starts_with(key.as_str()))
+            .max_by_key(|(key, _)| key.len())
+            .map(|(_, price)| *price)
+            .or_else(|| agent_id.and_then(|id| self.prices.get(&id.to_lowercase()).copied()))
+            .unwrap_or(self.default)
+    }
+
+    pub fn cost(
+        &self,
+        agent_id: Option<&str>,
+        model: &str,
+        input_tokens: u64,
+        output_tokens: u64,
+    ) -> f64 {
+        let price = self.price(agent_id, model);
+        let input_cost = (input_tokens as f64 / 1_000_000.0) * price.input_per_million;
+        let output_cost = (output_tokens as f64 / 1_000_000.0) * price.output_per_million;
+        input_cost + output_cost
     }
 }
 
+/// Bundled prices with the user's `model_pricing` overrides applied
+pub fn pricing_registry() -> PricingRegistry {
+    PricingRegistry::bundled().with_overrides(&load_settings().model_pricing)
+}
+
+/// Cost of a turn for any agent's model
+pub fn model_cost(
+    agent_id: Option<&str>,
+    model: &str,
+    input_tokens: u64,
+    output_tokens: u64,
+) -> f64 {
+    pricing_registry().cost(agent_id, model, input_tokens, output_tokens)
+}
+
 pub fn calculate_cost(model: &Model, input_tokens: u64, output_tokens: u64) -> f64 {
-    let pricing = get_pricing(model);
-    let input_cost = (input_tokens as f64 / 1_000_000.0) * pricing.input_per_million;
-    let output_cost = (output_tokens as f64 / 1_000_000.0) * pricing.output_per_million;
-    input_cost + output_cost
+    model_cost(
+        Some("claude"),
+        model.model_id(),
+        input_tokens,
+        output_tokens,
+    )
 }
 
 #[cfg(test)]
@@ -79,19 +160,65 @@ mod tests {
 
     #[test]
     fn test_opus_cost() {
-        let cost = calculate_cost(&Model::Opus, 1000, 500);
-        assert!((cost - 0.0525).abs() < 0.0001);
+        let cost = PricingRegistry::bundled().cost(None, Model::Opus.model_id(), 1000, 500);
+        assert!((cost - 0.0175).abs() < 0.0001);
     }
 
     #[test]
     fn test_sonnet_cost() {
-        let cost = calculate_cost(&Model::Sonnet, 1000, 500);
+        let cost = PricingRegistry::bundled().cost(None, Model::Sonnet.model_id(), 1000, 500);
         assert!((cost - 0.0105).abs() < 0.0001);
     }
 
     #[test]
     fn test_haiku_cost() {
-        let cost = calculate_cost(&Model::Haiku, 1000, 500);
+        let cost = PricingRegistry::bundled().cost(None, Model::Haiku.model_id(), 1000, 500);
         assert!((cost - 0.0028).abs() < 0.0001);
     }
+
+    #[test]
+    fn test_lookup_order() {
+        let registry = PricingRegistry::bundled();
+        let price =
+            |agent: Option<&str>, model: &str| registry.price(agent, model).input_per_million;
+
+        assert_eq!(price(None, "gemini-2.5-flash-lite"), 0.1);
+        assert_eq!(price(None, "gemini-2.5-flash"), 0.3);
+        assert_eq!(price(None, "claude-sonnet-4-5-20250929"), 3.0);
+        assert_eq!(price(None, "codex-mini-latest"), 1.5);
+        assert_eq!(price(None, "Haiku"), 0.8);
+        // Unknown models fall back to the agent entry, then the default
+        assert_eq!(price(Some("opencode"), "default"), 3.0);
+
+        let overrides = HashMap::from([
+            (
+                "opencode".to_string(),
+                ModelPrice {
+                    input_per_million: 0.5,
+                    output_per_million: 1.0,
+                },
+            ),
+            (
+                "gemini-2.5-pro".to_string(),
+                ModelPrice {
+                    input_per_million: 2.5,
+                    output_per_million: 15.0,
+                },
+            ),
+        ]);
+        let registry = registry.with_overrides(&overrides);
+        assert_eq!(
+            registry
+                .price(Some("opencode"), "default")
+                .input_per_million,
+            0.5
+        );
+        assert_eq!(
+            registry
+                .price(Some("gemini"), "gemini-2.5-pro")
+                .input_per_million,
+            2.5
+        );
+        assert!((registry.cost(Some("opencode"), "x", 1_000_000, 1_000_000) - 1.5).abs() < 1e-9);
+    }
 }
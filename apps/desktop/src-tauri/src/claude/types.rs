@@ -147,22 +147,3 @@ pub struct StreamError {
     pub error_type: String,
     pub message: String,
 }
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct WorkerStreamEvent {
-    pub worker_id: String,
-    pub event: WorkerEventType,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(tag = "type")]
-pub enum WorkerEventType {
-    #[serde(rename = "delta")]
-    Delta { text: String },
-
-    #[serde(rename = "complete")]
-    Complete { output: String, usage: Usage },
-
-    #[serde(rename = "error")]
-    Error { message: String },
-}
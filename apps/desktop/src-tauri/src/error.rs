@@ -0,0 +1,177 @@
+//! Structured command errors
+//!
+//! The acp, orchestrator, tasks and inbox commands fail with a
+//! `CommandError` instead of a bare string, so the frontend can branch on
+//! `code` (reconnect a dead worker, ask for a trust decision, retry once an
+//! agent slot frees) instead of matching message text. It serializes as
+//! `{ code, message, details? }`.
+//!
+//! Plain `String` errors from the stores and helpers convert to `Internal`
+//! through `?`, apart from trust errors, which keep their own code.
+
+use crate::acp::client::AcpError;
+use crate::acp::trust::TRUST_REQUIRED_PREFIX;
+use crate::claude::client::ClaudeError;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    /// A session, worker, task or message doesn't exist
+    NotFound,
+    /// The arguments were rejected
+    InvalidInput,
+    /// The agent isn't installed or isn't configured
+    AgentUnavailable,
+    /// The agent needs the user to authenticate
+    AuthRequired,
+    /// The directory needs a trust decision before an agent can start in it
+    TrustRequired,
+    /// The session has no running worker; reconnect it
+    WorkerUnavailable,
+    /// The agent process failed the request
+    AgentFailed,
+    /// Too many agents are running
+    LimitReached,
+    /// The app-wide spend cap was reached
+    BudgetExceeded,
+    Cancelled,
+    Io,
+    Internal,
+}
+
+#[derive(Debug, Clone, Serialize, thiserror::Error)]
+#[error("{message}")]
+pub struct CommandError {
+    pub code: ErrorCode,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
+}
+
+impl CommandError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            details: None,
+        }
+    }
+
+    pub fn with_details(mut self, details: serde_json::Value) -> Self {
+        self.details = Some(details);
+        self
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::NotFound, message)
+    }
+
+    pub fn invalid_input(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::InvalidInput, message)
+    }
+
+    pub fn worker_unavailable(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::WorkerUnavailable, message)
+    }
+
+    pub fn agent_failed(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::AgentFailed, message)
+    }
+
+    pub fn agent_unavailable(agent_id: &str) -> Self {
+        Self::new(
+            ErrorCode::AgentUnavailable,
+            format!("Agent '{}' not found or not available", agent_id),
+        )
+        .with_details(serde_json::json!({ "agent_id": agent_id }))
+    }
+
+    pub fn session_not_found(session_id: &str) -> Self {
+        Self::not_found(format!("Session '{}' not found", session_id))
+            .with_details(serde_json::json!({ "session_id": session_id }))
+    }
+}
+
+impl From<String> for CommandError {
+    fn from(message: String) -> Self {
+        let code = if message.starts_with(TRUST_REQUIRED_PREFIX) {
+            ErrorCode::TrustRequired
+        } else {
+            ErrorCode::Internal
+        };
+        Self::new(code, message)
+    }
+}
+
+impl From<&str> for CommandError {
+    fn from(message: &str) -> Self {
+        message.to_string().into()
+    }
+}
+
+impl From<std::io::Error> for CommandError {
+    fn from(e: std::io::Error) -> Self {
+        Self::new(ErrorCode::Io, e.to_string())
+    }
+}
+
+impl From<AcpError> for CommandError {
+    fn from(e: AcpError) -> Self {
+        let code = match e {
+            AcpError::Cancelled => ErrorCode::Cancelled,
+            AcpError::IoError(_) => ErrorCode::Io,
+            _ => ErrorCode::AgentFailed,
+        };
+        Self::new(code, e.to_string())
+    }
+}
+
+impl From<ClaudeError> for CommandError {
+    fn from(e: ClaudeError) -> Self {
+        let code = match e {
+            ClaudeError::MissingApiKey => ErrorCode::AuthRequired,
+            _ => ErrorCode::AgentFailed,
+        };
+        Self::new(code, e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialized_shape() {
+        let error = CommandError::session_not_found("s1");
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            serde_json::json!({
+                "code": "not_found",
+                "message": "Session 's1' not found",
+                "details": { "session_id": "s1" }
+            })
+        );
+
+        let error = CommandError::worker_unavailable("gone");
+        assert!(serde_json::to_value(&error)
+            .unwrap()
+            .get("details")
+            .is_none());
+        assert_eq!(error.to_string(), "gone");
+    }
+
+    #[test]
+    fn test_from_string() {
+        assert_eq!(
+            CommandError::from("disk full".to_string()).code,
+            ErrorCode::Internal
+        );
+        let trust = format!("{} /tmp has not been trusted", TRUST_REQUIRED_PREFIX);
+        assert_eq!(CommandError::from(trust).code, ErrorCode::TrustRequired);
+        assert_eq!(
+            CommandError::from(AcpError::Cancelled).code,
+            ErrorCode::Cancelled
+        );
+    }
+}
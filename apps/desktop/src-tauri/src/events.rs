@@ -0,0 +1,1311 @@
+//! Event catalog
+//!
+//! The per-worker events (`worker-stream-*`, `worker-tool-*`,
+//! `worker-status-change`, ...) and `job-progress` are typed here rather
+//! than built as JSON at each emit site, so every site sends the same shape.
+//! `EventSink::send` and `emit` add the schema `version` to the payload.
+//!
+//! Each payload type exports a TypeScript binding to
+//! `src/lib/ipc/generated` when the tests run (`cargo test`). Bump
+//! `EVENT_SCHEMA_VERSION` when a field is removed or changes meaning; new
+//! fields don't need a bump.
+
+use crate::acp::auth::AgentAuthStatus;
+use crate::acp::check_loop::CheckRun;
+use crate::acp::context::ContextUsage;
+use crate::acp::criteria::CriterionResult;
+use crate::acp::events::EventSink;
+use crate::acp::hooks::HookRun;
+use crate::acp::resource_limits::{LimitLevel, LimitResource};
+use crate::acp::retry::TransientError;
+use crate::acp::stream_metrics::StreamProgress;
+use crate::agent::watcher::FsChange;
+use crate::jobs::JobStatus;
+use crate::orchestrator::build_results::BuildResult;
+use crate::orchestrator::worker::WorkerStatus;
+use crate::prd::types::CriterionStatus;
+use crate::pty::service::ServiceStatus;
+use crate::remote::client::RemoteConnectionStatus;
+use crate::scheduler::ScheduledJob;
+use crate::secrets::at_rest::ResealFailure;
+use crate::secrets::redact::Redactor;
+use crate::settings::store::{GuardAction, GuardTarget};
+use crate::shutdown::ShutdownStage;
+use crate::stats::budget::BudgetPeriod;
+use crate::tasks::query::{TaskChange, TaskColumns};
+use serde::Serialize;
+use tauri::AppHandle;
+use ts_rs::TS;
+
+pub const EVENT_SCHEMA_VERSION: u32 = 1;
+
+/// A payload in the catalog
+pub trait AppEvent: Serialize + Clone {
+    /// The Tauri event name it is emitted under
+    fn name(&self) -> String;
+}
+
+/// A payload as emitted: the event's fields plus `version`
+#[derive(Debug, Clone, Serialize)]
+pub struct Versioned<'a, E> {
+    pub version: u32,
+    #[serde(flatten)]
+    pub event: &'a E,
+}
+
+impl<'a, E> Versioned<'a, E> {
+    pub fn new(event: &'a E) -> Self {
+        Self {
+            version: EVENT_SCHEMA_VERSION,
+            event,
+        }
+    }
+}
+
+/// Emit a catalog event to the frontend (and the API event bus)
+pub fn emit<E: AppEvent>(app_handle: &AppHandle, event: &E) {
+    EventSink::App(app_handle.clone()).send(event);
+}
+
+// ============================================================================
+// worker-stream-{worker_id}
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct WorkerStreamEvent {
+    pub worker_id: String,
+    pub event: WorkerEvent,
+}
+
+impl AppEvent for WorkerStreamEvent {
+    fn name(&self) -> String {
+        format!("worker-stream-{}", self.worker_id)
+    }
+}
+
+/// One stream event, tagged with the prompt (turn) it belongs to
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct WorkerEvent {
+    pub prompt_id: Option<String>,
+    #[serde(flatten)]
+    pub kind: WorkerEventType,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[ts(export)]
+pub enum WorkerEventType {
+    Delta {
+        text: String,
+    },
+    Thinking {
+        text: String,
+    },
+    Complete {
+        output: String,
+        thinking: Option<String>,
+        usage: TokenUsage,
+        cost_usd: Option<f64>,
+        metrics: Option<StreamProgress>,
+        context: Option<ContextUsage>,
+    },
+    Error {
+        message: String,
+    },
+    Plan {
+        entries: Vec<PlanEntry>,
+    },
+    /// An action the client refused because the session is in a read-only mode
+    ReadOnlyBlocked {
+        mode: String,
+        action: String,
+        detail: String,
+    },
+    /// A terminal command the client refused because the session is
+    /// network-isolated and no sandbox could run it
+    NetworkBlocked {
+        command: String,
+        reason: String,
+    },
+    /// A file write the agent reported without a diff, diffed by the client
+    FileDiff {
+        /// Edit tool call the write belongs to
+        tool_call_id: Option<String>,
+        path: String,
+        old_text: Option<String>,
+        new_text: String,
+        unified: String,
+    },
+}
+
+impl WorkerEventType {
+    /// Redact secrets from the text the event carries
+    pub fn redact(self, redactor: &Redactor) -> Self {
+        let redact = |text: String| redactor.redact(&text).into_owned();
+        match self {
+            Self::Delta { text } => Self::Delta { text: redact(text) },
+            Self::Thinking { text } => Self::Thinking { text: redact(text) },
+            Self::Complete {
+                output,
+                thinking,
+                usage,
+                cost_usd,
+                metrics,
+                context,
+            } => Self::Complete {
+                output: redact(output),
+                thinking: thinking.map(redact),
+                usage,
+                cost_usd,
+                metrics,
+                context,
+            },
+            Self::Error { message } => Self::Error {
+                message: redact(message),
+            },
+            Self::Plan { entries } => Self::Plan {
+                entries: entries
+                    .into_iter()
+                    .map(|entry| PlanEntry {
+                        content: redact(entry.content),
+                        ..entry
+                    })
+                    .collect(),
+            },
+            Self::ReadOnlyBlocked {
+                mode,
+                action,
+                detail,
+            } => Self::ReadOnlyBlocked {
+                mode,
+                action,
+                detail: redact(detail),
+            },
+            Self::NetworkBlocked { command, reason } => Self::NetworkBlocked {
+                command: redact(command),
+                reason,
+            },
+            Self::FileDiff {
+                tool_call_id,
+                path,
+                old_text,
+                new_text,
+                unified,
+            } => Self::FileDiff {
+                tool_call_id,
+                path,
+                old_text: old_text.map(redact),
+                new_text: redact(new_text),
+                unified: redact(unified),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct TokenUsage {
+    #[ts(type = "number")]
+    pub input_tokens: u64,
+    #[ts(type = "number")]
+    pub output_tokens: u64,
+    /// Counted from characters rather than reported by the agent
+    pub estimated: bool,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct PlanEntry {
+    pub content: String,
+    #[ts(type = r#""high" | "medium" | "low""#)]
+    pub priority: String,
+    #[ts(type = r#""pending" | "in_progress" | "completed""#)]
+    pub status: String,
+}
+
+// ============================================================================
+// worker-progress-{worker_id}
+// ============================================================================
+
+/// Generation speed, sent periodically while a prompt streams
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct WorkerProgressEvent {
+    pub worker_id: String,
+    pub session_id: String,
+    pub prompt_id: Option<String>,
+    pub progress: StreamProgress,
+}
+
+impl AppEvent for WorkerProgressEvent {
+    fn name(&self) -> String {
+        format!("worker-progress-{}", self.worker_id)
+    }
+}
+
+// ============================================================================
+// worker-tool-{worker_id}
+// ============================================================================
+
+/// A tool call starting or being updated. Updates leave the fields that
+/// didn't change null (and `content` empty).
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct WorkerToolEvent {
+    pub worker_id: String,
+    pub tool_call_id: String,
+    pub title: Option<String>,
+    pub kind: Option<String>,
+    pub status: Option<String>,
+    pub content: Vec<ToolCallContent>,
+    /// Raw input for special tool calls (e.g. the plan for ExitPlanMode)
+    #[ts(type = "Record<string, unknown> | null")]
+    pub raw_input: Option<serde_json::Value>,
+}
+
+impl AppEvent for WorkerToolEvent {
+    fn name(&self) -> String {
+        format!("worker-tool-{}", self.worker_id)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[ts(export)]
+pub enum ToolCallContent {
+    Text {
+        text: String,
+    },
+    /// Non-text content, debug-printed
+    Content {
+        text: String,
+    },
+    Diff {
+        path: String,
+        old_text: Option<String>,
+        new_text: String,
+    },
+    Terminal {
+        terminal_id: String,
+    },
+    Unknown {
+        text: String,
+    },
+}
+
+// ============================================================================
+// worker-permission-{worker_id}
+// ============================================================================
+
+/// A permission request. Headless runs answer it themselves and report the
+/// answer in `selected`, with no options to choose from.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct WorkerPermissionEvent {
+    pub worker_id: String,
+    pub title: String,
+    pub tool_call_id: String,
+    pub options: Vec<PermissionOption>,
+    pub selected: Option<String>,
+}
+
+impl AppEvent for WorkerPermissionEvent {
+    fn name(&self) -> String {
+        format!("worker-permission-{}", self.worker_id)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct PermissionOption {
+    pub id: String,
+    pub name: String,
+    /// ACP `PermissionOptionKind`, e.g. "allow_once"
+    pub kind: String,
+}
+
+// ============================================================================
+// worker-commands-{worker_id}, worker-mode-{worker_id},
+// worker-user-message-{worker_id}
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct WorkerCommandsEvent {
+    pub worker_id: String,
+    /// ACP `AvailableCommand`s, as the agent sent them
+    #[ts(type = "Array<unknown>")]
+    pub commands: Vec<serde_json::Value>,
+}
+
+impl AppEvent for WorkerCommandsEvent {
+    fn name(&self) -> String {
+        format!("worker-commands-{}", self.worker_id)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct WorkerModeEvent {
+    pub worker_id: String,
+    pub mode_id: String,
+}
+
+impl AppEvent for WorkerModeEvent {
+    fn name(&self) -> String {
+        format!("worker-mode-{}", self.worker_id)
+    }
+}
+
+/// A user message chunk echoed back by the agent
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct WorkerUserMessageEvent {
+    pub worker_id: String,
+    pub text: String,
+}
+
+impl AppEvent for WorkerUserMessageEvent {
+    fn name(&self) -> String {
+        format!("worker-user-message-{}", self.worker_id)
+    }
+}
+
+// ============================================================================
+// worker-mode-change, worker-authenticated
+// ============================================================================
+
+/// A `set_acp_session_mode` the agent accepted
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct WorkerModeChangeEvent {
+    pub session_id: String,
+    pub worker_id: String,
+    pub mode_id: String,
+}
+
+impl AppEvent for WorkerModeChangeEvent {
+    fn name(&self) -> String {
+        "worker-mode-change".to_string()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct WorkerAuthenticatedEvent {
+    pub session_id: String,
+    pub worker_id: String,
+    pub method_id: String,
+}
+
+impl AppEvent for WorkerAuthenticatedEvent {
+    fn name(&self) -> String {
+        "worker-authenticated".to_string()
+    }
+}
+
+// ============================================================================
+// worker-status-change
+// ============================================================================
+
+/// A worker's status changed. The optional fields are set by the sites
+/// they apply to, e.g. `error` for failures and `stop_reason` for finished
+/// prompts.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct WorkerStatusChange {
+    pub session_id: String,
+    pub worker_id: String,
+    pub status: WorkerStatus,
+    pub error: Option<String>,
+    pub cost: Option<f64>,
+    pub stop_reason: Option<String>,
+    pub agent: Option<String>,
+    /// Fleet workers only
+    pub is_leader: Option<bool>,
+    /// Loading a saved ACP session into a new worker
+    pub resuming: bool,
+    pub resumed: bool,
+    /// Resumed from a condensed transcript because the agent can't
+    /// load_session
+    pub resumed_from_summary: bool,
+    /// Restarting the agent for a session whose worker died
+    pub reconnecting: bool,
+    pub reconnected: bool,
+}
+
+impl WorkerStatusChange {
+    pub fn new(session_id: &str, worker_id: &str, status: WorkerStatus) -> Self {
+        Self {
+            session_id: session_id.to_string(),
+            worker_id: worker_id.to_string(),
+            status,
+            error: None,
+            cost: None,
+            stop_reason: None,
+            agent: None,
+            is_leader: None,
+            resuming: false,
+            resumed: false,
+            resumed_from_summary: false,
+            reconnecting: false,
+            reconnected: false,
+        }
+    }
+}
+
+impl AppEvent for WorkerStatusChange {
+    fn name(&self) -> String {
+        "worker-status-change".to_string()
+    }
+}
+
+// ============================================================================
+// worker-resource-limit
+// ============================================================================
+
+/// An agent process or terminal went over a resource limit. At the hard
+/// level its processes have been killed and the worker failed.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct WorkerResourceLimitEvent {
+    pub session_id: String,
+    pub worker_id: String,
+    /// Set when the process is an agent-created terminal
+    pub terminal_id: Option<String>,
+    pub resource: LimitResource,
+    pub level: LimitLevel,
+    /// MB for memory, percent of one core for CPU
+    pub usage: f64,
+    pub limit: f64,
+    pub message: String,
+}
+
+impl AppEvent for WorkerResourceLimitEvent {
+    fn name(&self) -> String {
+        "worker-resource-limit".to_string()
+    }
+}
+
+// ============================================================================
+// worker-agent-log
+// ============================================================================
+
+/// A line the agent process wrote to stderr (redacted)
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct WorkerAgentLogEvent {
+    pub session_id: String,
+    pub worker_id: String,
+    pub line: String,
+}
+
+impl AppEvent for WorkerAgentLogEvent {
+    fn name(&self) -> String {
+        "worker-agent-log".to_string()
+    }
+}
+
+// ============================================================================
+// worker-retry
+// ============================================================================
+
+/// A prompt failed with a transient error and will be sent again after
+/// `delay_secs`
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct WorkerRetryEvent {
+    pub session_id: String,
+    pub worker_id: String,
+    pub kind: TransientError,
+    /// 1 for the first retry
+    pub attempt: u32,
+    pub max_attempts: u32,
+    #[ts(type = "number")]
+    pub delay_secs: u64,
+    pub error: String,
+}
+
+impl AppEvent for WorkerRetryEvent {
+    fn name(&self) -> String {
+        "worker-retry".to_string()
+    }
+}
+
+// ============================================================================
+// rate-limit-status
+// ============================================================================
+
+/// An agent and model were rate limited, or the limit has lifted
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct RateLimitStatusEvent {
+    pub agent_id: String,
+    pub model: String,
+    pub limited: bool,
+    /// Unix timestamp the limit resets, while limited
+    #[ts(type = "number | null")]
+    pub reset_at: Option<i64>,
+    /// Rate-limited responses since the limit was first hit
+    pub hits: u32,
+    pub message: Option<String>,
+}
+
+impl AppEvent for RateLimitStatusEvent {
+    fn name(&self) -> String {
+        "rate-limit-status".to_string()
+    }
+}
+
+// ============================================================================
+// task-board-changed
+// ============================================================================
+
+/// A session's tasks changed, through the task commands or a worker's
+/// `swarm task` commands
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct TaskBoardChangedEvent {
+    pub session_id: String,
+    /// The task created, updated, claimed or deleted
+    pub task_id: Option<String>,
+    pub change: TaskChange,
+    /// Counts after the change
+    pub columns: TaskColumns,
+}
+
+impl AppEvent for TaskBoardChangedEvent {
+    fn name(&self) -> String {
+        "task-board-changed".to_string()
+    }
+}
+
+// ============================================================================
+// plan-imported
+// ============================================================================
+
+/// A worker's plan was copied to the task board
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct PlanImportedEvent {
+    pub session_id: String,
+    pub worker_id: String,
+    /// Tasks created for new plan entries
+    pub created: Vec<String>,
+    /// Tasks moved along because their entry was started or completed
+    pub updated: Vec<String>,
+}
+
+impl AppEvent for PlanImportedEvent {
+    fn name(&self) -> String {
+        "plan-imported".to_string()
+    }
+}
+
+// ============================================================================
+// slash-command-result
+// ============================================================================
+
+/// A backend slash command (`/cost`, `/compact`, `/retry`, `/export`) ran
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct SlashCommandResultEvent {
+    pub session_id: String,
+    /// Command name without the `/`
+    pub command: String,
+    pub success: bool,
+    /// What the command reported, or why it failed
+    pub output: String,
+}
+
+impl AppEvent for SlashCommandResultEvent {
+    fn name(&self) -> String {
+        "slash-command-result".to_string()
+    }
+}
+
+// ============================================================================
+// slow-tool
+// ============================================================================
+
+/// A tool call has been running longer than `slow_tool_threshold_secs`.
+/// Sent once per call, while it's still running.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct SlowToolEvent {
+    pub session_id: String,
+    pub worker_id: String,
+    pub tool_call_id: String,
+    pub kind: Option<String>,
+    pub title: Option<String>,
+    #[ts(type = "number")]
+    pub elapsed_ms: i64,
+    #[ts(type = "number")]
+    pub threshold_ms: i64,
+}
+
+impl AppEvent for SlowToolEvent {
+    fn name(&self) -> String {
+        "slow-tool".to_string()
+    }
+}
+
+// ============================================================================
+// job-progress
+// ============================================================================
+
+/// A background job started, moved to a new phase, logged a line or
+/// finished
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct JobProgressEvent {
+    pub job_id: String,
+    /// e.g. "install_agent" or "export_session"
+    pub kind: String,
+    pub status: JobStatus,
+    pub phase: String,
+    /// 0-100, or None while the job can't tell how far along it is
+    pub percent: Option<f32>,
+    /// Log line added since the previous event
+    pub line: Option<String>,
+    pub error: Option<String>,
+}
+
+impl AppEvent for JobProgressEvent {
+    fn name(&self) -> String {
+        "job-progress".to_string()
+    }
+}
+
+// ============================================================================
+// service-status, service-output-{service_id}
+// ============================================================================
+
+/// A service terminal started, became ready, exited, or is waiting to be
+/// restarted
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct ServiceStatusEvent {
+    pub service_id: String,
+    pub session_id: String,
+    pub name: String,
+    pub status: ServiceStatus,
+    pub pid: Option<u32>,
+    pub restarts: u32,
+    /// Exit code of the last run, once one has ended
+    pub exit_code: Option<i32>,
+}
+
+impl AppEvent for ServiceStatusEvent {
+    fn name(&self) -> String {
+        "service-status".to_string()
+    }
+}
+
+/// A line of a service terminal's output
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct ServiceOutputEvent {
+    pub service_id: String,
+    /// Including its newline
+    pub line: String,
+}
+
+impl AppEvent for ServiceOutputEvent {
+    fn name(&self) -> String {
+        format!("service-output-{}", self.service_id)
+    }
+}
+
+// ============================================================================
+// dev-server-detected
+// ============================================================================
+
+/// A local server address showed up in an agent or service terminal's
+/// output for the first time in the session
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct DevServerDetectedEvent {
+    pub session_id: String,
+    pub terminal_id: String,
+    /// None for service terminals
+    pub worker_id: Option<String>,
+    /// Normalized to localhost, e.g. "http://localhost:5173/"
+    pub url: String,
+    pub port: u16,
+}
+
+impl AppEvent for DevServerDetectedEvent {
+    fn name(&self) -> String {
+        "dev-server-detected".to_string()
+    }
+}
+
+// ============================================================================
+// preview-request
+// ============================================================================
+
+/// A request went through a preview proxy
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct PreviewRequestEvent {
+    pub preview_id: String,
+    pub session_id: Option<String>,
+    pub method: String,
+    /// Path and query
+    pub path: String,
+    /// 502 when the dev server couldn't be reached
+    pub status: u16,
+    #[ts(type = "number")]
+    pub duration_ms: u64,
+}
+
+impl AppEvent for PreviewRequestEvent {
+    fn name(&self) -> String {
+        "preview-request".to_string()
+    }
+}
+
+// ============================================================================
+// session-encryption-changed
+// ============================================================================
+
+/// Files on disk were converted after `encrypt_sessions` was turned on or
+/// off
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct SessionEncryptionEvent {
+    pub encrypted: bool,
+    /// Files converted
+    pub converted: usize,
+    /// Files left as they were
+    pub failed: Vec<ResealFailure>,
+    /// Set when nothing could be converted (e.g. no keychain)
+    pub error: Option<String>,
+}
+
+impl AppEvent for SessionEncryptionEvent {
+    fn name(&self) -> String {
+        "session-encryption-changed".to_string()
+    }
+}
+
+// ============================================================================
+// session-queued, session-dequeued
+// ============================================================================
+
+/// A new session is waiting for an agent slot (`max_running_agents`)
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct SessionQueuedEvent {
+    pub session_id: String,
+    pub worker_id: String,
+    /// Place in the queue, 1 for next
+    pub position: usize,
+}
+
+impl AppEvent for SessionQueuedEvent {
+    fn name(&self) -> String {
+        "session-queued".to_string()
+    }
+}
+
+/// A queued session got a slot and is starting
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct SessionDequeuedEvent {
+    pub session_id: String,
+}
+
+impl AppEvent for SessionDequeuedEvent {
+    fn name(&self) -> String {
+        "session-dequeued".to_string()
+    }
+}
+
+// ============================================================================
+// global-budget-exceeded
+// ============================================================================
+
+/// A spend cap was crossed; running prompts were cancelled and PRD
+/// sessions paused
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct GlobalBudgetExceededEvent {
+    pub period: BudgetPeriod,
+    pub spent_usd: f64,
+    pub cap_usd: f64,
+}
+
+impl AppEvent for GlobalBudgetExceededEvent {
+    fn name(&self) -> String {
+        "global-budget-exceeded".to_string()
+    }
+}
+
+// ============================================================================
+// tray-open-session
+// ============================================================================
+
+/// A session was picked from the tray menu
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct TrayOpenSessionEvent {
+    pub session_id: String,
+}
+
+impl AppEvent for TrayOpenSessionEvent {
+    fn name(&self) -> String {
+        "tray-open-session".to_string()
+    }
+}
+
+// ============================================================================
+// prd-update
+// ============================================================================
+
+/// A change to a running PRD session. The Ralph loop's own updates
+/// (iterations, story status) are still sent as plain JSON.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct PrdUpdateEvent {
+    pub session_id: String,
+    #[serde(flatten)]
+    pub update: PrdUpdate,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[ts(export)]
+pub enum PrdUpdate {
+    StoryAdded { story_id: String },
+    StoryUpdated { story_id: String },
+    StoryRemoved { story_id: String },
+    StorySkipped { story_id: String },
+    StoryPaused { story_id: String },
+    StoryResumed { story_id: String },
+    /// Stories that depended on a skipped one start without it
+    DependentsUnblocked {
+        story_id: String,
+        dependents: Vec<String>,
+        warning: String,
+    },
+    /// The leader agent answered a `prd_send_message`
+    LeaderReply { message: String, reply: String },
+    /// Results of `prd_verify_story`
+    Criteria {
+        story_id: String,
+        criteria: Vec<CriterionStatus>,
+    },
+}
+
+impl AppEvent for PrdUpdateEvent {
+    fn name(&self) -> String {
+        "prd-update".to_string()
+    }
+}
+
+// ============================================================================
+// context-warning, session-compacted
+// ============================================================================
+
+/// A session is nearing its model's context window
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct ContextWarningEvent {
+    pub session_id: String,
+    pub worker_id: String,
+    #[serde(flatten)]
+    pub usage: ContextUsage,
+}
+
+impl AppEvent for ContextWarningEvent {
+    fn name(&self) -> String {
+        "context-warning".to_string()
+    }
+}
+
+/// A session was compacted into a fresh ACP session seeded with a summary
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct SessionCompactedEvent {
+    pub session_id: String,
+    pub worker_id: String,
+    pub acp_session_id: String,
+    pub summary: String,
+    pub context: ContextUsage,
+}
+
+impl AppEvent for SessionCompactedEvent {
+    fn name(&self) -> String {
+        "session-compacted".to_string()
+    }
+}
+
+// ============================================================================
+// session-forked
+// ============================================================================
+
+/// A new session branched off a saved one (`fork_session`)
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct SessionForkedEvent {
+    pub session_id: String,
+    pub parent_session_id: String,
+    /// Messages before this index were carried over
+    pub at_message_index: usize,
+}
+
+impl AppEvent for SessionForkedEvent {
+    fn name(&self) -> String {
+        "session-forked".to_string()
+    }
+}
+
+// ============================================================================
+// session-title-updated
+// ============================================================================
+
+/// A saved session got a generated title
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct SessionTitleUpdatedEvent {
+    pub session_id: String,
+    pub title: String,
+}
+
+impl AppEvent for SessionTitleUpdatedEvent {
+    fn name(&self) -> String {
+        "session-title-updated".to_string()
+    }
+}
+
+// ============================================================================
+// agent-login-finished
+// ============================================================================
+
+/// A `start_agent_login` terminal exited; the agent's auth was checked again
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct AgentLoginFinishedEvent {
+    pub agent_id: String,
+    pub terminal_id: String,
+    pub status: Option<AgentAuthStatus>,
+    /// Why the status couldn't be checked
+    pub error: Option<String>,
+}
+
+impl AppEvent for AgentLoginFinishedEvent {
+    fn name(&self) -> String {
+        "agent-login-finished".to_string()
+    }
+}
+
+// ============================================================================
+// inbox-push
+// ============================================================================
+
+/// An idle worker was prompted with its unread inbox messages
+/// (`inbox_push`)
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct InboxPushEvent {
+    pub session_id: String,
+    pub worker_id: String,
+    /// Messages in the prompt
+    pub count: usize,
+    pub prompt: String,
+}
+
+impl AppEvent for InboxPushEvent {
+    fn name(&self) -> String {
+        "inbox-push".to_string()
+    }
+}
+
+// ============================================================================
+// fs-changed
+// ============================================================================
+
+/// A debounced batch of changes in a watched workspace
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct FsChangedEvent {
+    /// The workspace passed to `watch_workspace`
+    pub root: String,
+    pub changes: Vec<FsChange>,
+}
+
+impl AppEvent for FsChangedEvent {
+    fn name(&self) -> String {
+        "fs-changed".to_string()
+    }
+}
+
+// ============================================================================
+// shutdown-progress
+// ============================================================================
+
+/// Progress of draining workers after the app was asked to quit
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct ShutdownProgressEvent {
+    pub stage: ShutdownStage,
+    pub message: String,
+    /// Agent processes still alive
+    pub running_agents: usize,
+}
+
+impl AppEvent for ShutdownProgressEvent {
+    fn name(&self) -> String {
+        "shutdown-progress".to_string()
+    }
+}
+
+// ============================================================================
+// session-history-truncated
+// ============================================================================
+
+/// `edit_and_resend` dropped the saved history from `message_index` on
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct SessionHistoryTruncatedEvent {
+    pub session_id: String,
+    pub message_index: usize,
+}
+
+impl AppEvent for SessionHistoryTruncatedEvent {
+    fn name(&self) -> String {
+        "session-history-truncated".to_string()
+    }
+}
+
+// ============================================================================
+// hook-output
+// ============================================================================
+
+/// A project hook script ran
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct HookOutputEvent {
+    pub session_id: String,
+    pub run: HookRun,
+}
+
+impl AppEvent for HookOutputEvent {
+    fn name(&self) -> String {
+        "hook-output".to_string()
+    }
+}
+
+// ============================================================================
+// guard-triggered
+// ============================================================================
+
+/// A guard rule fired on agent activity
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct GuardTriggeredEvent {
+    pub session_id: String,
+    pub worker_id: String,
+    pub rule: String,
+    pub target: GuardTarget,
+    pub action: GuardAction,
+    pub matched: String,
+    pub message: Option<String>,
+    /// True when the action is held until `respond_to_guard_review`
+    pub awaiting_review: bool,
+    #[ts(type = "number")]
+    pub timestamp: i64,
+}
+
+impl AppEvent for GuardTriggeredEvent {
+    fn name(&self) -> String {
+        "guard-triggered".to_string()
+    }
+}
+
+// ============================================================================
+// session-criteria
+// ============================================================================
+
+/// A session's acceptance criteria were checked after a turn
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct SessionCriteriaEvent {
+    pub session_id: String,
+    pub worker_id: String,
+    pub passed: bool,
+    pub results: Vec<CriterionResult>,
+    /// Prompt sent back to the agent listing failures, if any
+    pub follow_up: Option<String>,
+    pub follow_ups_sent: u32,
+    #[ts(type = "number")]
+    pub timestamp: i64,
+}
+
+impl AppEvent for SessionCriteriaEvent {
+    fn name(&self) -> String {
+        "session-criteria".to_string()
+    }
+}
+
+// ============================================================================
+// check-loop
+// ============================================================================
+
+/// A check-loop run, with the prompt it led to (if any)
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct CheckLoopEvent {
+    pub session_id: String,
+    pub command: String,
+    pub iterations: u32,
+    pub max_iterations: u32,
+    pub run: CheckRun,
+    /// Prompt sent to the agent after this run, if it failed
+    pub follow_up: Option<String>,
+    #[ts(type = "number")]
+    pub timestamp: i64,
+}
+
+impl AppEvent for CheckLoopEvent {
+    fn name(&self) -> String {
+        "check-loop".to_string()
+    }
+}
+
+// ============================================================================
+// build-result
+// ============================================================================
+
+/// Test or build output was parsed from a command the agent ran
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct BuildResultEvent {
+    pub session_id: String,
+    pub result: BuildResult,
+}
+
+impl AppEvent for BuildResultEvent {
+    fn name(&self) -> String {
+        "build-result".to_string()
+    }
+}
+
+// ============================================================================
+// scheduled-job-update
+// ============================================================================
+
+/// A scheduled job started a run or its run finished
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct ScheduledJobUpdateEvent {
+    #[serde(flatten)]
+    pub job: ScheduledJob,
+}
+
+impl AppEvent for ScheduledJobUpdateEvent {
+    fn name(&self) -> String {
+        "scheduled-job-update".to_string()
+    }
+}
+
+// ============================================================================
+// remote-host-status
+// ============================================================================
+
+/// A remote host connection changed state
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct RemoteHostStatusEvent {
+    #[serde(flatten)]
+    pub status: RemoteConnectionStatus,
+}
+
+impl AppEvent for RemoteHostStatusEvent {
+    fn name(&self) -> String {
+        "remote-host-status".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_versioned_payload() {
+        let event = WorkerStreamEvent {
+            worker_id: "w1".to_string(),
+            event: WorkerEvent {
+                prompt_id: Some("p1".to_string()),
+                kind: WorkerEventType::Delta {
+                    text: "hi".to_string(),
+                },
+            },
+        };
+        assert_eq!(event.name(), "worker-stream-w1");
+        assert_eq!(
+            serde_json::to_value(Versioned::new(&event)).unwrap(),
+            serde_json::json!({
+                "version": EVENT_SCHEMA_VERSION,
+                "worker_id": "w1",
+                "event": { "prompt_id": "p1", "type": "delta", "text": "hi" }
+            })
+        );
+
+        let status = WorkerStatusChange {
+            error: Some("boom".to_string()),
+            ..WorkerStatusChange::new("s1", "w1", WorkerStatus::Failed)
+        };
+        let value = serde_json::to_value(Versioned::new(&status)).unwrap();
+        assert_eq!(value["status"], "failed");
+        assert_eq!(value["error"], "boom");
+        assert_eq!(value["reconnecting"], false);
+    }
+
+    #[test]
+    fn test_redact() {
+        let redactor = Redactor::new(vec!["hunter2hunter2".to_string()], &[], Vec::new());
+        let event = WorkerEventType::Complete {
+            output: "key=hunter2hunter2".to_string(),
+            thinking: Some("hunter2hunter2".to_string()),
+            usage: TokenUsage {
+                input_tokens: 1,
+                output_tokens: 2,
+                estimated: true,
+            },
+            cost_usd: None,
+            metrics: None,
+            context: None,
+        };
+        let WorkerEventType::Complete {
+            output, thinking, ..
+        } = event.redact(&redactor)
+        else {
+            panic!("redact changed the event type");
+        };
+        assert!(!output.contains("hunter2hunter2"));
+        assert!(!thinking.unwrap().contains("hunter2hunter2"));
+    }
+
+    #[test]
+    fn test_tool_call_content_shape() {
+        let content = ToolCallContent::Diff {
+            path: "a.rs".to_string(),
+            old_text: None,
+            new_text: "fn a() {}".to_string(),
+        };
+        assert_eq!(
+            serde_json::to_value(&content).unwrap(),
+            serde_json::json!({
+                "type": "diff",
+                "path": "a.rs",
+                "old_text": null,
+                "new_text": "fn a() {}"
+            })
+        );
+    }
+}
@@ -0,0 +1,327 @@
+//! Headless runner
+//!
+//! Drives ACP agents and PRD loops without the desktop UI, for the `crafter`
+//! CLI binary. Worker events are written to stdout as JSON lines (see
+//! `acp::events`); diagnostics go to stderr.
+
+use crate::acp::client::AcpClient;
+use crate::acp::events::EventSink;
+use crate::acp::registry::{get_agent, AgentConfig};
+use crate::prd::manager::{build_story_prompt, story_model};
+use crate::prd::parser::validate_prd;
+use crate::prd::progress::{write_progress, PROGRESS_FILE};
+use crate::prd::types::{Prd, PrdSession, PrdSessionStatus};
+use crate::prd::verifier::{all_criteria_pass, verify_all_criteria_cached, VerifierCache};
+use agent_client_protocol::{ContentBlock, TextContent};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+/// Options for `crafter run`
+pub struct RunOptions {
+    pub agent: String,
+    pub prompt: String,
+    pub cwd: String,
+    pub model: Option<String>,
+    /// Approve agent permission requests instead of rejecting them
+    pub auto_approve: bool,
+}
+
+/// Options for `crafter prd run`
+pub struct PrdRunOptions {
+    pub path: PathBuf,
+    pub agent: String,
+    pub cwd: String,
+    /// Overrides the per-story model assignment
+    pub model: Option<String>,
+    pub auto_approve: bool,
+}
+
+/// Run a future on a single-threaded runtime (ACP connections are `!Send`)
+fn block_on<F: Future>(future: F) -> Result<F::Output, String> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| format!("Failed to create runtime: {}", e))?;
+    let local = tokio::task::LocalSet::new();
+    Ok(local.block_on(&runtime, future))
+}
+
+/// Cancellation channel fired by Ctrl+C. Must be called inside the `LocalSet`.
+fn cancel_on_ctrl_c() -> mpsc::Receiver<()> {
+    let (tx, rx) = mpsc::channel(1);
+    tokio::task::spawn_local(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            let _ = tx.send(()).await;
+        }
+    });
+    rx
+}
+
+/// Write the PRD progress file, logging failures
+fn save_progress(dir: &Path, session: &PrdSession) {
+    if let Err(e) = write_progress(dir, session) {
+        eprintln!("[PRD] Failed to write {}: {}", PROGRESS_FILE, e);
+    }
+}
+
+fn installed_agent(id: &str) -> Result<AgentConfig, String> {
+    get_agent(id).ok_or_else(|| format!("Agent '{}' is not installed", id))
+}
+
+/// Spawn an agent and open an ACP session in `cwd`
+async fn start_agent(
+    agent: &AgentConfig,
+    cwd: &str,
+    model: Option<String>,
+    events: EventSink,
+    worker_id: &str,
+    session_id: &str,
+) -> Result<AcpClient, String> {
+    let args: Vec<&str> = agent.args.iter().map(|s| s.as_str()).collect();
+    let mut client = AcpClient::spawn(
+        &agent.command,
+        &args,
+        cwd,
+        &agent.env_vars,
+        model,
+        agent.model_env_var.clone(),
+        events,
+        worker_id.to_string(),
+        session_id.to_string(),
+        None,
+        None,
+    )
+    .await
+    .map_err(|e| format!("Failed to spawn agent: {}", e))?;
+    client.set_agent_id(&agent.id);
+
+    if let Err(e) = client.initialize().await {
+        let _ = client.kill().await;
+        return Err(format!("Failed to initialize agent: {}", e));
+    }
+    if let Err(e) = client.create_acp_session(cwd).await {
+        let _ = client.kill().await;
+        return Err(format!("Failed to create session: {}", e));
+    }
+    Ok(client)
+}
+
+/// Run a single prompt to completion
+pub fn run_prompt(options: RunOptions) -> Result<(), String> {
+    let agent = installed_agent(&options.agent)?;
+    let events = EventSink::Stdout {
+        auto_approve: options.auto_approve,
+    };
+    let session_id = Uuid::new_v4().to_string();
+    let worker_id = Uuid::new_v4().to_string();
+
+    block_on(async move {
+        let mut client = start_agent(
+            &agent,
+            &options.cwd,
+            options.model.clone(),
+            events.clone(),
+            &worker_id,
+            &session_id,
+        )
+        .await?;
+
+        let mut cancel_rx = cancel_on_ctrl_c();
+        let content = vec![ContentBlock::Text(TextContent::new(&options.prompt))];
+        let result = client.run_user_prompt(content, &mut cancel_rx).await;
+        let _ = client.kill().await;
+        let stop_reason = result.map_err(|e| e.to_string())?;
+
+        events.emit(
+            "run-complete",
+            serde_json::json!({
+                "session_id": session_id,
+                "worker_id": worker_id,
+                "stop_reason": format!("{:?}", stop_reason),
+            }),
+        );
+        Ok(())
+    })?
+}
+
+fn load_prd(path: &Path) -> Result<Prd, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Invalid PRD {}: {}", path.display(), e))
+}
+
+/// Run every story of a PRD in dependency order, iterating each until its
+/// acceptance criteria pass. Stops at the first story that runs out of
+/// iterations.
+pub fn run_prd(options: PrdRunOptions) -> Result<(), String> {
+    let prd = load_prd(&options.path)?;
+    let validation = validate_prd(&prd);
+    if !validation.valid {
+        return Err(format!("Invalid PRD: {}", validation.errors.join("; ")));
+    }
+    let agent = installed_agent(&options.agent)?;
+    let events = EventSink::Stdout {
+        auto_approve: options.auto_approve,
+    };
+    let session_id = Uuid::new_v4().to_string();
+    let working_dir = PathBuf::from(&options.cwd);
+    let mut session = PrdSession::new(session_id.clone(), prd.clone());
+    session.status = PrdSessionStatus::Running;
+    let cache = VerifierCache::new();
+
+    block_on(async move {
+        let mut cancel_rx = cancel_on_ctrl_c();
+        save_progress(&working_dir, &session);
+        events.emit(
+            "prd-update",
+            serde_json::json!({
+                "session_id": session_id,
+                "type": "started",
+                "title": prd.title,
+                "stories": validation.dependency_order,
+            }),
+        );
+
+        for story_id in &validation.dependency_order {
+            let Some(story) = prd.stories.iter().find(|s| &s.id == story_id) else {
+                continue;
+            };
+            let worker_id = Uuid::new_v4().to_string();
+            let model = options
+                .model
+                .clone()
+                .unwrap_or_else(|| story_model(story).to_string());
+            let mut client = start_agent(
+                &agent,
+                &options.cwd,
+                Some(model),
+                events.clone(),
+                &worker_id,
+                &session_id,
+            )
+            .await?;
+            if let Some(progress) = session.story_progress.get_mut(story_id) {
+                progress.start(worker_id.clone());
+            }
+
+            let mut guardrails: Vec<String> = Vec::new();
+            let mut passed = false;
+            for iteration in 1..=prd.constraints.max_iterations_per_story {
+                if let Some(progress) = session.story_progress.get_mut(story_id) {
+                    progress.iteration = iteration;
+                }
+                events.emit(
+                    "prd-update",
+                    serde_json::json!({
+                        "session_id": session_id,
+                        "story_id": story_id,
+                        "worker_id": worker_id,
+                        "type": "iteration",
+                        "iteration": iteration
+                    }),
+                );
+
+                let prompt = build_story_prompt(story, iteration, &guardrails);
+                match client.prompt(&prompt, &mut cancel_rx).await {
+                    Ok(_) => {}
+                    Err(crate::acp::client::AcpError::Cancelled) => {
+                        let _ = client.kill().await;
+                        return Err("Cancelled".to_string());
+                    }
+                    Err(e) => {
+                        guardrails.push(format!("Agent error: {}", e));
+                        continue;
+                    }
+                }
+
+                let statuses = verify_all_criteria_cached(
+                    story,
+                    Some(&working_dir),
+                    &cache,
+                    prd.constraints.criteria_concurrency,
+                    false,
+                )
+                .await;
+                events.emit(
+                    "prd-update",
+                    serde_json::json!({
+                        "session_id": session_id,
+                        "story_id": story_id,
+                        "type": "criteria",
+                        "criteria": statuses,
+                    }),
+                );
+                let all_passed = all_criteria_pass(&statuses);
+                if let Some(progress) = session.story_progress.get_mut(story_id) {
+                    progress.criteria_status = statuses.clone();
+                }
+                if all_passed {
+                    passed = true;
+                    break;
+                }
+
+                for (criterion, status) in story.acceptance_criteria.iter().zip(&statuses) {
+                    if !status.passed {
+                        let description = criterion
+                            .description
+                            .as_deref()
+                            .unwrap_or("Unknown criterion");
+                        let error = status.error.as_deref().unwrap_or("Failed");
+                        guardrails.push(format!("Criterion '{}': {}", description, error));
+                    }
+                }
+                if let Some(progress) = session.story_progress.get_mut(story_id) {
+                    progress.guardrails = guardrails.clone();
+                }
+                save_progress(&working_dir, &session);
+            }
+            let _ = client.kill().await;
+
+            if !passed {
+                if let Some(progress) = session.story_progress.get_mut(story_id) {
+                    progress.fail("Max iterations reached".to_string());
+                }
+                session.status = PrdSessionStatus::Failed;
+                save_progress(&working_dir, &session);
+                events.emit(
+                    "prd-update",
+                    serde_json::json!({
+                        "session_id": session_id,
+                        "story_id": story_id,
+                        "type": "failed",
+                        "error": "Max iterations reached",
+                    }),
+                );
+                return Err(format!(
+                    "Story '{}' failed after {} iterations",
+                    story.title, prd.constraints.max_iterations_per_story
+                ));
+            }
+            if let Some(progress) = session.story_progress.get_mut(story_id) {
+                progress.complete();
+            }
+            save_progress(&working_dir, &session);
+            events.emit(
+                "prd-update",
+                serde_json::json!({
+                    "session_id": session_id,
+                    "story_id": story_id,
+                    "type": "completed"
+                }),
+            );
+        }
+
+        session.status = PrdSessionStatus::Completed;
+        save_progress(&working_dir, &session);
+        events.emit(
+            "prd-update",
+            serde_json::json!({
+                "session_id": session_id,
+                "type": "completed"
+            }),
+        );
+        Ok(())
+    })?
+}
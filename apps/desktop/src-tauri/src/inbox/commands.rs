@@ -1,4 +1,5 @@
-use super::message::{Message, MessageType};
+use super::message::{Message, MessageType, WorkerInfo};
+use crate::error::CommandError;
 use crate::AppState;
 use tauri::State;
 
@@ -6,12 +7,14 @@ use tauri::State;
 pub fn inbox_register(
     session_id: String,
     worker_id: String,
+    specialties: Option<Vec<String>>,
     state: State<'_, AppState>,
-) -> Result<(), String> {
-    let manager = state
-        .get_inbox_manager(&session_id)
-        .map_err(|e| e.to_string())?;
+) -> Result<(), CommandError> {
+    let manager = state.get_inbox_manager(&session_id)?;
     manager.register_worker(&worker_id);
+    if let Some(specialties) = specialties {
+        manager.declare_specialties(&worker_id, specialties);
+    }
     Ok(())
 }
 
@@ -22,10 +25,8 @@ pub fn inbox_write(
     to: String,
     content: String,
     state: State<'_, AppState>,
-) -> Result<Message, String> {
-    let manager = state
-        .get_inbox_manager(&session_id)
-        .map_err(|e| e.to_string())?;
+) -> Result<Message, CommandError> {
+    let manager = state.get_inbox_manager(&session_id)?;
     Ok(manager.send(&from, &to, MessageType::Text { content }))
 }
 
@@ -35,10 +36,8 @@ pub fn inbox_broadcast(
     from: String,
     content: String,
     state: State<'_, AppState>,
-) -> Result<Vec<Message>, String> {
-    let manager = state
-        .get_inbox_manager(&session_id)
-        .map_err(|e| e.to_string())?;
+) -> Result<Vec<Message>, CommandError> {
+    let manager = state.get_inbox_manager(&session_id)?;
     Ok(manager.broadcast(&from, MessageType::Text { content }))
 }
 
@@ -49,10 +48,8 @@ pub fn inbox_broadcast_to(
     content: String,
     targets: Vec<String>,
     state: State<'_, AppState>,
-) -> Result<Vec<Message>, String> {
-    let manager = state
-        .get_inbox_manager(&session_id)
-        .map_err(|e| e.to_string())?;
+) -> Result<Vec<Message>, CommandError> {
+    let manager = state.get_inbox_manager(&session_id)?;
     Ok(manager.broadcast_to(&from, MessageType::Text { content }, &targets))
 }
 
@@ -62,10 +59,8 @@ pub fn inbox_read(
     worker_id: String,
     unread_only: Option<bool>,
     state: State<'_, AppState>,
-) -> Result<Vec<Message>, String> {
-    let manager = state
-        .get_inbox_manager(&session_id)
-        .map_err(|e| e.to_string())?;
+) -> Result<Vec<Message>, CommandError> {
+    let manager = state.get_inbox_manager(&session_id)?;
     if unread_only.unwrap_or(false) {
         Ok(manager.read_unread(&worker_id))
     } else {
@@ -79,10 +74,8 @@ pub fn inbox_mark_read(
     worker_id: String,
     message_ids: Vec<String>,
     state: State<'_, AppState>,
-) -> Result<(), String> {
-    let manager = state
-        .get_inbox_manager(&session_id)
-        .map_err(|e| e.to_string())?;
+) -> Result<(), CommandError> {
+    let manager = state.get_inbox_manager(&session_id)?;
     manager.mark_read(&worker_id, &message_ids);
     Ok(())
 }
@@ -92,10 +85,8 @@ pub fn inbox_mark_all_read(
     session_id: String,
     worker_id: String,
     state: State<'_, AppState>,
-) -> Result<(), String> {
-    let manager = state
-        .get_inbox_manager(&session_id)
-        .map_err(|e| e.to_string())?;
+) -> Result<(), CommandError> {
+    let manager = state.get_inbox_manager(&session_id)?;
     manager.mark_all_read(&worker_id);
     Ok(())
 }
@@ -107,10 +98,8 @@ pub fn inbox_send_structured(
     to: String,
     message: MessageType,
     state: State<'_, AppState>,
-) -> Result<Message, String> {
-    let manager = state
-        .get_inbox_manager(&session_id)
-        .map_err(|e| e.to_string())?;
+) -> Result<Message, CommandError> {
+    let manager = state.get_inbox_manager(&session_id)?;
     Ok(manager.send(&from, &to, message))
 }
 
@@ -120,20 +109,20 @@ pub fn inbox_count(
     worker_id: String,
     unread_only: Option<bool>,
     state: State<'_, AppState>,
-) -> Result<usize, String> {
-    let manager = state
-        .get_inbox_manager(&session_id)
-        .map_err(|e| e.to_string())?;
+) -> Result<usize, CommandError> {
+    let manager = state.get_inbox_manager(&session_id)?;
     Ok(manager.count(&worker_id, unread_only.unwrap_or(false)))
 }
 
+/// Registered workers with their role, agent, model, specialties and status
 #[tauri::command]
 pub fn inbox_get_workers(
     session_id: String,
     state: State<'_, AppState>,
-) -> Result<Vec<String>, String> {
-    let manager = state
-        .get_inbox_manager(&session_id)
-        .map_err(|e| e.to_string())?;
-    Ok(manager.get_workers())
+) -> Result<Vec<WorkerInfo>, CommandError> {
+    let manager = state.get_inbox_manager(&session_id)?;
+    if let Some(session) = state.orchestrator_manager.lock().get_session(&session_id) {
+        manager.sync_status(&session.workers);
+    }
+    Ok(manager.get_worker_infos())
 }
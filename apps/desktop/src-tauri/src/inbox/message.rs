@@ -1,6 +1,8 @@
+use crate::orchestrator::worker::{WorkerSession, WorkerStatus};
 use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use tokio::sync::watch;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
@@ -56,11 +58,72 @@ pub struct Message {
     pub timestamp: i64,
 }
 
+/// What a worker registered as, so others can tell who to ask
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkerInfo {
+    pub worker_id: String,
+    /// "leader" or "worker"
+    pub role: String,
+    pub agent_id: Option<String>,
+    pub model: Option<String>,
+    /// Skills the worker declared with `swarm team declare`
+    pub specialties: Vec<String>,
+    pub status: WorkerStatus,
+}
+
+impl WorkerInfo {
+    pub fn new(worker_id: &str) -> Self {
+        Self {
+            worker_id: worker_id.to_string(),
+            role: "worker".to_string(),
+            agent_id: None,
+            model: None,
+            specialties: Vec::new(),
+            status: WorkerStatus::Pending,
+        }
+    }
+
+    /// How well the worker fits `skill`: 2 for a declared specialty, 1 for
+    /// a specialty that contains it (or the other way round), 0 otherwise
+    fn skill_score(&self, skill: &str) -> u32 {
+        let skill = skill.to_lowercase();
+        self.specialties
+            .iter()
+            .map(|s| s.to_lowercase())
+            .map(|s| {
+                if s == skill {
+                    2
+                } else if s.contains(&skill) || skill.contains(&s) {
+                    1
+                } else {
+                    0
+                }
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Whether the worker can pick up new work now
+    fn available(&self) -> bool {
+        matches!(
+            self.status,
+            WorkerStatus::Idle | WorkerStatus::Pending | WorkerStatus::Completed | WorkerStatus::Hibernated
+        )
+    }
+
+    fn stopped(&self) -> bool {
+        matches!(self.status, WorkerStatus::Failed | WorkerStatus::Cancelled)
+    }
+}
+
 pub struct InboxManager {
     /// worker_id -> messages
     inboxes: Mutex<HashMap<String, Vec<Message>>>,
-    /// Track all known workers for broadcast
-    workers: Mutex<Vec<String>>,
+    /// Track all known workers for broadcast, in the order they joined
+    workers: Mutex<Vec<WorkerInfo>>,
+    /// Bumped on every delivered message
+    version: watch::Sender<u64>,
     #[allow(dead_code)]
     session_id: String,
 }
@@ -70,22 +133,68 @@ impl InboxManager {
         Self {
             inboxes: Mutex::new(HashMap::new()),
             workers: Mutex::new(Vec::new()),
+            version: watch::channel(0).0,
             session_id,
         }
     }
 
+    /// Watch for new messages (the value changes on every delivery)
+    pub fn subscribe(&self) -> watch::Receiver<u64> {
+        self.version.subscribe()
+    }
+
     /// Register a worker (call when worker joins session)
     pub fn register_worker(&self, worker_id: &str) {
+        self.register(WorkerInfo::new(worker_id));
+    }
+
+    /// Register a worker with what it is. Re-registering replaces the
+    /// metadata but keeps declared specialties.
+    pub fn register(&self, info: WorkerInfo) {
+        let worker_id = info.worker_id.clone();
         let mut workers = self.workers.lock();
-        if !workers.contains(&worker_id.to_string()) {
-            workers.push(worker_id.to_string());
+        match workers.iter_mut().find(|w| w.worker_id == worker_id) {
+            Some(existing) => {
+                let specialties = std::mem::take(&mut existing.specialties);
+                *existing = info;
+                if existing.specialties.is_empty() {
+                    existing.specialties = specialties;
+                }
+            }
+            None => workers.push(info),
         }
+        drop(workers);
 
         // Initialize inbox
         let mut inboxes = self.inboxes.lock();
-        inboxes
-            .entry(worker_id.to_string())
-            .or_insert_with(Vec::new);
+        inboxes.entry(worker_id).or_insert_with(Vec::new);
+    }
+
+    /// Record the skills a worker says it's good at (replacing earlier ones)
+    pub fn declare_specialties(&self, worker_id: &str, specialties: Vec<String>) -> bool {
+        let mut workers = self.workers.lock();
+        let Some(worker) = workers.iter_mut().find(|w| w.worker_id == worker_id) else {
+            return false;
+        };
+        let mut declared: Vec<String> = Vec::new();
+        for specialty in specialties {
+            let specialty = specialty.trim().to_string();
+            if !specialty.is_empty() && !declared.iter().any(|d| d.eq_ignore_ascii_case(&specialty)) {
+                declared.push(specialty);
+            }
+        }
+        worker.specialties = declared;
+        true
+    }
+
+    /// Bring worker statuses up to date with the orchestrator's
+    pub fn sync_status(&self, sessions: &[WorkerSession]) {
+        let mut workers = self.workers.lock();
+        for worker in workers.iter_mut() {
+            if let Some(session) = sessions.iter().find(|s| s.id == worker.worker_id) {
+                worker.status = session.status.clone();
+            }
+        }
     }
 
     /// Send a message from one worker to another
@@ -103,6 +212,8 @@ impl InboxManager {
         };
 
         inbox.push(msg.clone());
+        drop(inboxes);
+        self.version.send_modify(|v| *v += 1);
         msg
     }
 
@@ -164,13 +275,35 @@ impl InboxManager {
 
     /// Get all registered workers
     pub fn get_workers(&self) -> Vec<String> {
+        self.workers.lock().iter().map(|w| w.worker_id.clone()).collect()
+    }
+
+    /// Registration metadata of all workers
+    pub fn get_worker_infos(&self) -> Vec<WorkerInfo> {
         self.workers.lock().clone()
     }
 
+    /// Workers suited to `skill`, best first: those declaring it, then
+    /// available before busy ones, leaving out failed or cancelled workers
+    /// and `exclude` (the one asking)
+    pub fn find_workers(&self, skill: &str, exclude: Option<&str>) -> Vec<WorkerInfo> {
+        let mut matches: Vec<(u32, WorkerInfo)> = self
+            .workers
+            .lock()
+            .iter()
+            .filter(|w| Some(w.worker_id.as_str()) != exclude && !w.stopped())
+            .map(|w| (w.skill_score(skill), w.clone()))
+            .filter(|(score, _)| *score > 0)
+            .collect();
+        // Stable, so ties keep join order
+        matches.sort_by_key(|(score, w)| (std::cmp::Reverse(*score), !w.available()));
+        matches.into_iter().map(|(_, w)| w).collect()
+    }
+
     /// Unregister a worker (call when worker leaves session)
     pub fn unregister_worker(&self, worker_id: &str) {
         let mut workers = self.workers.lock();
-        workers.retain(|w| w != worker_id);
+        workers.retain(|w| w.worker_id != worker_id);
     }
 
     /// Get message count for a worker
@@ -188,3 +321,51 @@ impl InboxManager {
             .unwrap_or(0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_workers() {
+        let inbox = InboxManager::new("team-test".to_string());
+        inbox.register(WorkerInfo {
+            role: "leader".to_string(),
+            ..WorkerInfo::new("worker-1")
+        });
+        inbox.register_worker("worker-2");
+        inbox.register_worker("worker-3");
+        inbox.register_worker("worker-4");
+        inbox.declare_specialties("worker-1", vec!["Rust".to_string()]);
+        inbox.declare_specialties("worker-2", vec!["frontend".to_string(), "React".to_string()]);
+        inbox.declare_specialties("worker-3", vec!["react".to_string(), "".to_string(), "React".to_string()]);
+        inbox.declare_specialties("worker-4", vec!["react-native".to_string()]);
+
+        let mut busy = WorkerSession::new(
+            "worker-2".to_string(),
+            "team-test".to_string(),
+            String::new(),
+            crate::claude::pricing::Model::default(),
+        );
+        busy.status = WorkerStatus::Running;
+        inbox.sync_status(&[busy]);
+
+        let found: Vec<String> = inbox
+            .find_workers("react", Some("worker-1"))
+            .into_iter()
+            .map(|w| w.worker_id)
+            .collect();
+        assert_eq!(found, vec!["worker-3", "worker-2", "worker-4"]);
+        assert!(inbox.find_workers("rust", Some("worker-1")).is_empty());
+
+        // Re-registering keeps what the worker declared
+        inbox.register(WorkerInfo {
+            model: Some("opus".to_string()),
+            ..WorkerInfo::new("worker-3")
+        });
+        let infos = inbox.get_worker_infos();
+        assert_eq!(infos[2].specialties, vec!["react"]);
+        assert_eq!(infos[2].model.as_deref(), Some("opus"));
+        assert_eq!(inbox.get_workers(), vec!["worker-1", "worker-2", "worker-3", "worker-4"]);
+    }
+}
@@ -0,0 +1,14 @@
+use super::webhook::{deliver, http_client, LifecycleEvent, WebhookDelivery, WebhookEvent};
+use crate::settings::store::WebhookConfig;
+
+/// Send a test event to a webhook (bypasses its event filter)
+#[tauri::command]
+pub async fn test_webhook(webhook: WebhookConfig) -> Result<WebhookDelivery, String> {
+    let event = WebhookEvent::new(
+        LifecycleEvent::Test,
+        "test",
+        "crafter-code webhook test",
+        &format!("Webhook \"{}\" is configured correctly.", webhook.name),
+    );
+    deliver(&http_client()?, &webhook, &event).await
+}
@@ -0,0 +1,10 @@
+//! Outbound integrations
+//!
+//! Posts session lifecycle events (session completed, story passed, worker
+//! failed, cost threshold) to user-configured webhooks so teams can follow
+//! agent fleets from chat.
+
+pub mod commands;
+pub mod webhook;
+
+pub use webhook::{dispatch, LifecycleEvent, WebhookEvent};
@@ -0,0 +1,266 @@
+use crate::settings::load_settings;
+use crate::settings::store::{WebhookConfig, WebhookFormat};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Delivery attempts per webhook before giving up
+const MAX_ATTEMPTS: u32 = 3;
+
+/// Delay before the first retry (doubled on each attempt)
+const BASE_BACKOFF: Duration = Duration::from_secs(1);
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum LifecycleEvent {
+    SessionCompleted,
+    StoryPassed,
+    WorkerFailed,
+    CostThreshold,
+    /// Sent by `test_webhook`
+    Test,
+}
+
+impl LifecycleEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::SessionCompleted => "session_completed",
+            Self::StoryPassed => "story_passed",
+            Self::WorkerFailed => "worker_failed",
+            Self::CostThreshold => "cost_threshold",
+            Self::Test => "test",
+        }
+    }
+}
+
+/// An event delivered to webhooks
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookEvent {
+    pub event: LifecycleEvent,
+    pub session_id: String,
+    pub title: String,
+    pub message: String,
+    /// Event-specific details (worker id, cost, ...)
+    pub data: serde_json::Value,
+    pub timestamp: i64,
+}
+
+impl WebhookEvent {
+    pub fn new(event: LifecycleEvent, session_id: &str, title: &str, message: &str) -> Self {
+        Self {
+            event,
+            session_id: session_id.to_string(),
+            title: title.to_string(),
+            message: message.to_string(),
+            data: serde_json::Value::Null,
+            timestamp: chrono::Utc::now().timestamp_millis(),
+        }
+    }
+
+    pub fn with_data(mut self, data: serde_json::Value) -> Self {
+        self.data = data;
+        self
+    }
+}
+
+/// Outcome of delivering one event to one webhook
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookDelivery {
+    pub status: u16,
+    pub attempts: u32,
+}
+
+/// Whether a webhook is subscribed to an event
+fn wants(config: &WebhookConfig, event: LifecycleEvent) -> bool {
+    config.enabled
+        && (config.events.is_empty() || config.events.iter().any(|e| e == event.as_str()))
+}
+
+/// Build the request body for a webhook format
+pub fn payload(format: WebhookFormat, event: &WebhookEvent) -> serde_json::Value {
+    match format {
+        WebhookFormat::Json => serde_json::json!({
+            "source": "crafter-code",
+            "event": event.event,
+            "session_id": event.session_id,
+            "title": event.title,
+            "message": event.message,
+            "data": event.data,
+            "timestamp": event.timestamp,
+        }),
+        WebhookFormat::Slack => serde_json::json!({
+            "text": format!("{}: {}", event.title, event.message),
+            "blocks": [
+                {
+                    "type": "section",
+                    "text": {
+                        "type": "mrkdwn",
+                        "text": format!("*{}*\n{}", event.title, event.message),
+                    }
+                },
+                {
+                    "type": "context",
+                    "elements": [{
+                        "type": "mrkdwn",
+                        "text": format!("`{}` · session `{}`", event.event.as_str(), event.session_id),
+                    }]
+                }
+            ]
+        }),
+    }
+}
+
+/// Delay before retry number `attempt` (1-based)
+fn backoff(attempt: u32) -> Duration {
+    BASE_BACKOFF * 2u32.pow(attempt.saturating_sub(1).min(4))
+}
+
+fn is_retryable(status: reqwest::StatusCode) -> bool {
+    status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+}
+
+/// POST an event to a webhook, retrying network errors, 429s and 5xxs
+pub async fn deliver(
+    client: &reqwest::Client,
+    config: &WebhookConfig,
+    event: &WebhookEvent,
+) -> Result<WebhookDelivery, String> {
+    let body = payload(config.format, event);
+    let mut attempt = 0;
+
+    loop {
+        attempt += 1;
+        let error = match client.post(&config.url).json(&body).send().await {
+            Ok(response) if response.status().is_success() => {
+                return Ok(WebhookDelivery {
+                    status: response.status().as_u16(),
+                    attempts: attempt,
+                });
+            }
+            Ok(response) if !is_retryable(response.status()) => {
+                return Err(format!(
+                    "Webhook {} returned {}",
+                    config.name,
+                    response.status()
+                ));
+            }
+            Ok(response) => format!("returned {}", response.status()),
+            Err(e) => e.to_string(),
+        };
+
+        if attempt >= MAX_ATTEMPTS {
+            return Err(format!(
+                "Webhook {} failed after {} attempts: {}",
+                config.name, attempt, error
+            ));
+        }
+        eprintln!(
+            "[Webhook] {} attempt {} failed ({}), retrying",
+            config.name, attempt, error
+        );
+        tokio::time::sleep(backoff(attempt)).await;
+    }
+}
+
+pub fn http_client() -> Result<reqwest::Client, String> {
+    reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))
+}
+
+/// Send an event to every subscribed webhook in the background
+pub fn dispatch(event: WebhookEvent) {
+    let webhooks: Vec<WebhookConfig> = load_settings()
+        .integrations
+        .webhooks
+        .into_iter()
+        .filter(|w| wants(w, event.event))
+        .collect();
+    if webhooks.is_empty() {
+        return;
+    }
+
+    tauri::async_runtime::spawn(async move {
+        let client = match http_client() {
+            Ok(client) => client,
+            Err(e) => {
+                eprintln!("[Webhook] {}", e);
+                return;
+            }
+        };
+        for webhook in &webhooks {
+            if let Err(e) = deliver(&client, webhook, &event).await {
+                eprintln!("[Webhook] {}", e);
+            }
+        }
+    });
+}
+
+/// Whether a cost update crossed the configured threshold
+pub fn crossed_threshold(previous: f64, current: f64, threshold: Option<f64>) -> bool {
+    threshold.is_some_and(|t| previous < t && current >= t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(events: &[&str]) -> WebhookConfig {
+        WebhookConfig {
+            name: "team".to_string(),
+            url: "https://example.com/hook".to_string(),
+            format: WebhookFormat::Json,
+            events: events.iter().map(|e| e.to_string()).collect(),
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn test_event_filter() {
+        assert!(wants(&config(&[]), LifecycleEvent::WorkerFailed));
+        assert!(wants(&config(&["worker_failed"]), LifecycleEvent::WorkerFailed));
+        assert!(!wants(&config(&["story_passed"]), LifecycleEvent::WorkerFailed));
+
+        let disabled = WebhookConfig {
+            enabled: false,
+            ..config(&[])
+        };
+        assert!(!wants(&disabled, LifecycleEvent::WorkerFailed));
+    }
+
+    #[test]
+    fn test_payload_formats() {
+        let event = WebhookEvent::new(
+            LifecycleEvent::StoryPassed,
+            "s1",
+            "Story passed",
+            "Add login page",
+        );
+
+        let json = payload(WebhookFormat::Json, &event);
+        assert_eq!(json["event"], "story_passed");
+        assert_eq!(json["session_id"], "s1");
+
+        let slack = payload(WebhookFormat::Slack, &event);
+        assert_eq!(slack["text"], "Story passed: Add login page");
+        assert_eq!(slack["blocks"][0]["text"]["text"], "*Story passed*\nAdd login page");
+    }
+
+    #[test]
+    fn test_backoff() {
+        assert_eq!(backoff(1), Duration::from_secs(1));
+        assert_eq!(backoff(2), Duration::from_secs(2));
+        assert_eq!(backoff(3), Duration::from_secs(4));
+        assert_eq!(backoff(20), Duration::from_secs(16));
+    }
+
+    #[test]
+    fn test_crossed_threshold() {
+        assert!(crossed_threshold(0.9, 1.1, Some(1.0)));
+        assert!(!crossed_threshold(1.1, 1.5, Some(1.0)));
+        assert!(!crossed_threshold(0.5, 0.9, Some(1.0)));
+        assert!(!crossed_threshold(0.0, 100.0, None));
+    }
+}
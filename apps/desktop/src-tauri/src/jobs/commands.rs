@@ -0,0 +1,17 @@
+use super::JobInfo;
+use crate::error::CommandError;
+
+/// Running and recently finished background jobs, newest first
+#[tauri::command]
+pub fn list_jobs() -> Vec<JobInfo> {
+    super::list()
+}
+
+/// Ask a job to stop. Returns false if it already finished.
+#[tauri::command]
+pub fn cancel_job(job_id: String) -> Result<bool, CommandError> {
+    super::cancel(&job_id).ok_or_else(|| {
+        CommandError::not_found(format!("Job '{}' not found", job_id))
+            .with_details(serde_json::json!({ "job_id": job_id }))
+    })
+}
@@ -0,0 +1,299 @@
+//! Background jobs
+//!
+//! Long operations (agent installs, session exports) run as jobs instead of
+//! blocking their command. The command starts the work with `spawn` and
+//! returns the job id; the work reports its phase, a percentage when it
+//! knows one, and log lines through its `Job`. Every change is emitted as
+//! `job-progress`, and `list_jobs` returns the running jobs plus the most
+//! recently finished ones so a reopened panel can catch up.
+//!
+//! `cancel_job` only raises the job's cancel flag: the work checks it
+//! between steps (`check_cancelled`) and stops, and the job then finishes
+//! as cancelled.
+
+pub mod commands;
+
+use crate::events::{emit, JobProgressEvent};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use tauri::AppHandle;
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Log lines kept per job
+const MAX_LOG_LINES: usize = 200;
+
+/// Finished jobs kept for `list_jobs`
+const MAX_FINISHED_JOBS: usize = 20;
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum JobStatus {
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct JobInfo {
+    pub id: String,
+    /// e.g. "install_agent" or "export_session"
+    pub kind: String,
+    /// What the job does, e.g. "Install Gemini CLI"
+    pub label: String,
+    pub status: JobStatus,
+    pub phase: String,
+    /// 0-100, or None while the job can't tell how far along it is
+    pub percent: Option<f32>,
+    /// The latest log lines, oldest first
+    pub log: Vec<String>,
+    pub error: Option<String>,
+    /// Unix timestamps in milliseconds
+    pub started_at: i64,
+    pub finished_at: Option<i64>,
+}
+
+struct Entry {
+    info: JobInfo,
+    cancelled: Arc<AtomicBool>,
+}
+
+static JOBS: Lazy<Mutex<HashMap<String, Entry>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Message of the error a cancelled job stops with
+const CANCELLED: &str = "Cancelled";
+
+/// A running job, as seen by the work it tracks
+pub struct Job {
+    id: String,
+    kind: String,
+    cancelled: Arc<AtomicBool>,
+    /// None in tests, where nothing is emitted
+    app_handle: Option<AppHandle>,
+}
+
+impl Job {
+    fn register(app_handle: Option<AppHandle>, kind: &str, label: String) -> Self {
+        let id = Uuid::new_v4().to_string();
+        let cancelled = Arc::new(AtomicBool::new(false));
+        JOBS.lock().insert(
+            id.clone(),
+            Entry {
+                info: JobInfo {
+                    id: id.clone(),
+                    kind: kind.to_string(),
+                    label,
+                    status: JobStatus::Running,
+                    phase: "starting".to_string(),
+                    percent: None,
+                    log: Vec::new(),
+                    error: None,
+                    started_at: chrono::Utc::now().timestamp_millis(),
+                    finished_at: None,
+                },
+                cancelled: cancelled.clone(),
+            },
+        );
+        Self {
+            id,
+            kind: kind.to_string(),
+            cancelled,
+            app_handle,
+        }
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Fails with `CANCELLED` once `cancel_job` was called, for `?` between
+    /// steps
+    pub fn check_cancelled(&self) -> Result<(), String> {
+        if self.is_cancelled() {
+            Err(CANCELLED.to_string())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Move on to `phase`; `percent` replaces the previous one
+    pub fn phase(&self, phase: &str, percent: Option<f32>) {
+        self.update(None, |info| {
+            info.phase = phase.to_string();
+            info.percent = percent.map(|p| p.clamp(0.0, 100.0));
+        });
+    }
+
+    pub fn log(&self, line: impl Into<String>) {
+        self.update(Some(line.into()), |_| {});
+    }
+
+    fn finish(&self, result: Result<(), String>) {
+        // Work that noticed the flag may fail with any error on the way out
+        let status = match result {
+            _ if self.is_cancelled() => JobStatus::Cancelled,
+            Ok(()) => JobStatus::Completed,
+            Err(_) => JobStatus::Failed,
+        };
+        self.update(None, |info| {
+            info.status = status;
+            info.finished_at = Some(chrono::Utc::now().timestamp_millis());
+            match status {
+                JobStatus::Completed => info.percent = Some(100.0),
+                JobStatus::Failed => info.error = result.err(),
+                _ => {}
+            }
+        });
+        prune_finished(&mut JOBS.lock(), MAX_FINISHED_JOBS);
+    }
+
+    /// Change the job's info (and append `line`), then emit the new state
+    fn update(&self, line: Option<String>, change: impl FnOnce(&mut JobInfo)) {
+        let event = {
+            let mut jobs = JOBS.lock();
+            let Some(entry) = jobs.get_mut(&self.id) else {
+                return;
+            };
+            let info = &mut entry.info;
+            change(info);
+            if let Some(line) = &line {
+                if info.log.len() >= MAX_LOG_LINES {
+                    info.log.remove(0);
+                }
+                info.log.push(line.clone());
+            }
+            JobProgressEvent {
+                job_id: info.id.clone(),
+                kind: info.kind.clone(),
+                status: info.status,
+                phase: info.phase.clone(),
+                percent: info.percent,
+                line,
+                error: info.error.clone(),
+            }
+        };
+        if let Some(app_handle) = &self.app_handle {
+            emit(app_handle, &event);
+        }
+    }
+}
+
+/// Run `work` as a job on its own thread and return the job id. The job
+/// finishes with the work's result (or as cancelled, if it was).
+pub fn spawn<F>(app_handle: &AppHandle, kind: &str, label: String, work: F) -> String
+where
+    F: FnOnce(&Job) -> Result<(), String> + Send + 'static,
+{
+    let job = Job::register(Some(app_handle.clone()), kind, label);
+    let id = job.id.clone();
+    // Announce the job before the work's first update
+    job.update(None, |_| {});
+    thread::spawn(move || {
+        let result = work(&job);
+        if let Err(e) = &result {
+            eprintln!("[Jobs] {} job {} ended: {}", job.kind, job.id, e);
+        }
+        job.finish(result);
+    });
+    id
+}
+
+/// Drop the oldest finished jobs beyond `keep`
+fn prune_finished(jobs: &mut HashMap<String, Entry>, keep: usize) {
+    let mut finished: Vec<(i64, String)> = jobs
+        .values()
+        .filter_map(|entry| Some((entry.info.finished_at?, entry.info.id.clone())))
+        .collect();
+    if finished.len() <= keep {
+        return;
+    }
+    finished.sort();
+    for (_, id) in &finished[..finished.len() - keep] {
+        jobs.remove(id);
+    }
+}
+
+/// Running and recently finished jobs, newest first
+pub fn list() -> Vec<JobInfo> {
+    let mut jobs: Vec<JobInfo> = JOBS.lock().values().map(|e| e.info.clone()).collect();
+    jobs.sort_by_key(|job| std::cmp::Reverse(job.started_at));
+    jobs
+}
+
+/// Ask a job to stop. None if there is no such job, false if it already
+/// finished.
+pub fn cancel(job_id: &str) -> Option<bool> {
+    let jobs = JOBS.lock();
+    let entry = jobs.get(job_id)?;
+    if entry.info.status != JobStatus::Running {
+        return Some(false);
+    }
+    entry.cancelled.store(true, Ordering::SeqCst);
+    Some(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(job: &Job) -> JobInfo {
+        JOBS.lock().get(&job.id).unwrap().info.clone()
+    }
+
+    #[test]
+    fn test_progress_and_log() {
+        let job = Job::register(None, "test", "Test".to_string());
+        job.phase("copying", Some(140.0));
+        for i in 0..MAX_LOG_LINES + 5 {
+            job.log(format!("line {}", i));
+        }
+        let current = info(&job);
+        assert_eq!(current.phase, "copying");
+        assert_eq!(current.percent, Some(100.0));
+        assert_eq!(current.log.len(), MAX_LOG_LINES);
+        assert_eq!(current.log[0], "line 5");
+
+        job.finish(Err("disk full".to_string()));
+        let finished = info(&job);
+        assert_eq!(finished.status, JobStatus::Failed);
+        assert_eq!(finished.error.as_deref(), Some("disk full"));
+        assert!(finished.finished_at.is_some());
+        assert_eq!(cancel(&job.id), Some(false));
+    }
+
+    #[test]
+    fn test_cancel() {
+        let job = Job::register(None, "test", "Test".to_string());
+        assert!(job.check_cancelled().is_ok());
+        assert_eq!(cancel(&job.id), Some(true));
+        assert_eq!(job.check_cancelled().unwrap_err(), CANCELLED);
+
+        // Whatever the work returns afterwards, the job was cancelled
+        job.finish(Ok(()));
+        assert_eq!(info(&job).status, JobStatus::Cancelled);
+        assert_eq!(cancel("no-such-job"), None);
+    }
+
+    #[test]
+    fn test_prune_finished_keeps_running_and_newest() {
+        let mut jobs = HashMap::new();
+        for (id, finished_at) in [("a", Some(1)), ("b", None), ("c", Some(3)), ("d", Some(2))] {
+            let job = Job::register(None, "test", String::new());
+            let mut entry = JOBS.lock().remove(&job.id).unwrap();
+            entry.info.id = id.to_string();
+            entry.info.finished_at = finished_at;
+            jobs.insert(id.to_string(), entry);
+        }
+        prune_finished(&mut jobs, 2);
+        let mut left: Vec<&str> = jobs.keys().map(String::as_str).collect();
+        left.sort();
+        assert_eq!(left, vec!["b", "c", "d"]);
+    }
+}
@@ -1,11 +1,33 @@
 mod acp;
 mod agent;
+mod api;
 mod claude;
+mod error;
+mod events;
+pub mod headless;
 mod inbox;
+mod integrations;
+mod jobs;
+mod migrations;
+mod notifications;
 mod orchestrator;
+mod palette;
+mod power;
 mod prd;
+mod preview;
 mod pty;
+mod remote;
+mod scheduler;
+mod secrets;
+mod settings;
+mod shutdown;
+mod stats;
+mod storage;
 mod tasks;
+mod telemetry;
+mod time;
+mod transcription;
+mod tray;
 
 use acp::commands::WorkerHandle;
 use agent::manager::AgentManager;
@@ -53,6 +75,22 @@ impl AppState {
         }
         Ok(managers.get(session_id).unwrap().clone())
     }
+
+    /// Cancel every in-flight prompt and pause running PRD sessions;
+    /// returns how many of each
+    pub fn pause_all(&self) -> (usize, usize) {
+        let cancelled = self.orchestrator_manager.lock().cancel_all_workers();
+        let paused = self
+            .prd_manager
+            .list_sessions()
+            .into_iter()
+            .filter(|session| {
+                session.status == prd::types::PrdSessionStatus::Running
+                    && self.prd_manager.pause_session(&session.id).is_ok()
+            })
+            .count();
+        (cancelled, paused)
+    }
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -67,6 +105,7 @@ pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_notification::init())
         .manage(AppState {
             agent_manager: agent_manager.clone(),
             orchestrator_manager: orchestrator_manager.clone(),
@@ -81,34 +120,128 @@ pub fn run() {
             pty::commands::write_terminal,
             pty::commands::resize_terminal,
             pty::commands::kill_terminal,
+            pty::commands::signal_terminal,
+            pty::commands::list_terminals,
+            pty::commands::attach_terminal,
+            pty::commands::set_terminal_keep_alive,
+            pty::commands::close_session_terminals,
+            pty::commands::start_service,
+            pty::commands::stop_service,
+            pty::commands::restart_service,
+            pty::commands::list_services,
+            pty::commands::get_service_output,
+            acp::dev_servers::list_detected_servers,
+            preview::commands::start_preview,
+            preview::commands::stop_preview,
+            preview::commands::list_previews,
+            preview::commands::get_preview_requests,
+            preview::commands::get_preview_routes,
+            pty::commands::start_terminal_recording,
+            pty::commands::stop_terminal_recording,
+            pty::commands::export_recording,
             // Agent commands
             agent::commands::read_directory,
-            agent::commands::read_file_content,
+            agent::commands::read_directory_tree,
+            agent::commands::expand_directory_tree,
+            agent::commands::get_git_status,
+            agent::watcher::watch_workspace,
+            agent::watcher::unwatch_workspace,
+            agent::file_read::read_file_content,
+            agent::file_read::stream_file,
+            agent::file_read::stop_file_stream,
+            agent::search::search_in_project,
             agent::commands::get_project_info,
+            agent::commands::list_project_context_files,
+            agent::dotenv::preview_project_env,
+            agent::editor::open_in_editor,
+            agent::editor::detect_editors,
+            agent::editor::set_project_editor,
+            agent::snapshot::create_snapshot,
+            agent::snapshot::list_snapshots,
+            agent::snapshot::diff_against_snapshot,
+            agent::snapshot::restore_snapshot,
+            agent::scratch::get_scratch_dir,
             // Orchestrator commands
             orchestrator::commands::create_orchestrator_session,
             orchestrator::commands::get_orchestrator_session,
             orchestrator::commands::list_orchestrator_sessions,
+            orchestrator::commands::list_all_sessions,
+            orchestrator::commands::archive_session,
+            orchestrator::commands::list_archived_sessions,
+            orchestrator::commands::get_archived_session,
+            orchestrator::commands::purge_sessions,
+            orchestrator::commands::get_session_storage,
+            storage::get_storage_usage,
+            storage::clean_storage,
+            migrations::get_data_health,
+            power::is_preventing_sleep,
             orchestrator::commands::cancel_worker,
             orchestrator::commands::retry_worker,
             orchestrator::commands::get_session_conflicts,
             orchestrator::commands::get_session_cost,
+            orchestrator::commands::get_session_tool_calls,
+            orchestrator::commands::get_session_tool_metrics,
+            orchestrator::commands::get_session_build_results,
+            acp::hooks::get_session_hook_runs,
+            acp::agent_log::get_session_agent_log,
+            acp::rate_limits::get_rate_limits,
+            acp::preprocess::set_prompt_preprocessors,
+            acp::preprocess::get_prompt_preprocessors,
+            acp::preprocess::preview_prompt,
+            telemetry::get_telemetry_status,
+            acp::criteria::set_session_criteria,
+            acp::criteria::get_session_criteria,
+            orchestrator::commands::get_session_patch,
+            orchestrator::commands::apply_session_patch,
+            orchestrator::commands::reject_session_hunk,
             // ACP commands
             acp::commands::list_available_agents,
+            acp::commands::check_agent_health,
+            acp::auth::get_agent_auth_status,
+            acp::auth::start_agent_login,
+            acp::install::install_agent,
+            acp::trust::get_directory_trust,
+            acp::trust::set_directory_trust,
+            acp::trust::list_directory_trust,
+            acp::trust::revoke_directory_trust,
+            acp::coordination_prompt::preview_coordination_prompt,
+            acp::coordination_delta::set_coordination_updates,
+            acp::coordination_delta::get_coordination_updates,
             acp::commands::create_acp_session,
             acp::commands::create_acp_fleet_session,
             acp::commands::send_acp_prompt,
             acp::commands::send_acp_prompt_with_images,
             acp::commands::respond_to_permission,
+            acp::guards::respond_to_guard_review,
             acp::commands::set_acp_session_mode,
+            acp::modes::get_session_modes,
+            acp::language::set_session_language,
+            acp::language::get_session_language,
+            acp::protocol_trace::set_protocol_trace,
+            acp::protocol_trace::get_protocol_trace,
             acp::commands::authenticate_acp_session,
+            acp::commands::compact_session,
+            acp::commands::run_until_passing,
+            acp::commands::edit_and_resend,
+            acp::fork::fork_session,
             // Session persistence commands
             acp::commands::list_persisted_sessions,
+            acp::commands::update_session_metadata,
             acp::commands::get_persisted_session,
             acp::commands::delete_persisted_session,
+            acp::drafts::save_prompt_draft,
+            acp::drafts::get_prompt_draft,
             acp::commands::resume_acp_session,
             acp::commands::save_session_to_persistence,
+            acp::commands::export_persisted_session,
+            acp::commands::export_session_to_file,
+            acp::bundle::export_session_bundle,
+            acp::bundle::import_session_bundle,
+            acp::bundle::list_imported_bundles,
+            acp::bundle::get_imported_bundle,
+            acp::bundle::delete_imported_bundle,
             acp::commands::reconnect_worker,
+            acp::worker_health::get_worker_health,
             // Task commands
             tasks::commands::task_create,
             tasks::commands::task_list,
@@ -116,6 +249,13 @@ pub fn run() {
             tasks::commands::task_update,
             tasks::commands::task_claim,
             tasks::commands::task_delete,
+            tasks::board::task_board_mount,
+            tasks::board::task_board_unmount,
+            tasks::board::task_board_status,
+            tasks::query::task_query,
+            tasks::query::task_column_counts,
+            tasks::plan::set_plan_import,
+            tasks::plan::get_plan_import,
             // Inbox commands
             inbox::commands::inbox_register,
             inbox::commands::inbox_write,
@@ -142,9 +282,20 @@ pub fn run() {
             acp::skills_commands::list_slash_commands,
             acp::skills_commands::list_commands_by_category,
             acp::skills_commands::process_slash_command,
+            acp::slash_actions::run_slash_command,
             acp::skills_commands::is_slash_command,
             acp::skills_commands::process_user_input,
             acp::skills_commands::cleanup_session_features,
+            // Staged context and attachments
+            acp::staged_context::stage_context,
+            acp::staged_context::get_staged_context,
+            acp::staged_context::unstage_context,
+            acp::attachments::ingest_attachment,
+            acp::attachments::prepare_clipboard_image,
+            // Command palette
+            palette::palette_search,
+            // Voice input
+            transcription::transcribe_audio,
             // PRD commands
             prd::commands::validate_prd,
             prd::commands::create_prd_session,
@@ -154,9 +305,55 @@ pub fn run() {
             prd::commands::resume_prd_session,
             prd::commands::cancel_prd_session,
             prd::commands::retry_prd_story,
+            prd::commands::prd_add_story,
+            prd::commands::prd_update_story,
+            prd::commands::prd_remove_story,
+            prd::commands::prd_skip_story,
+            prd::commands::prd_pause_story,
+            prd::commands::prd_resume_story,
+            prd::commands::prd_send_message,
+            prd::commands::prd_verify_story,
             prd::commands::get_story_progress,
             prd::commands::get_prd_workers,
             prd::commands::get_prd_cost_breakdown,
+            // Settings commands
+            settings::commands::get_app_settings,
+            settings::commands::update_app_settings,
+            // Notification commands
+            notifications::commands::notify_on_completion,
+            // Integration commands
+            integrations::commands::test_webhook,
+            // API server commands
+            api::commands::start_api_server,
+            api::commands::stop_api_server,
+            api::commands::get_api_server_status,
+            api::commands::rotate_api_token,
+            // Remote host commands
+            remote::commands::connect_remote_host,
+            remote::commands::disconnect_remote_host,
+            remote::commands::list_remote_connections,
+            remote::commands::list_remote_agents,
+            remote::commands::list_remote_sessions,
+            remote::commands::create_remote_session,
+            remote::commands::send_remote_prompt,
+            remote::commands::respond_to_remote_permission,
+            // Scheduler commands
+            scheduler::commands::create_scheduled_job,
+            scheduler::commands::list_scheduled_jobs,
+            scheduler::commands::set_scheduled_job_enabled,
+            scheduler::commands::delete_scheduled_job,
+            scheduler::commands::run_job_now,
+            // Background job commands
+            jobs::commands::list_jobs,
+            jobs::commands::cancel_job,
+            // Secrets commands
+            secrets::commands::set_secret,
+            secrets::commands::delete_secret,
+            secrets::commands::list_secret_names,
+            // Stats commands
+            stats::commands::get_usage_stats,
+            stats::commands::export_usage_csv,
+            stats::budget::get_global_spend,
         ])
         .setup(|app| {
             #[cfg(debug_assertions)]
@@ -164,8 +361,55 @@ pub fn run() {
                 let window = app.get_webview_window("main").unwrap();
                 window.open_devtools();
             }
+
+            // Before anything reads the app data
+            secrets::at_rest::init(settings::load_settings().encrypt_sessions);
+            migrations::run();
+
+            if settings::load_settings().api_server.enabled {
+                let app_handle = app.handle().clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = api::start(app_handle).await {
+                        eprintln!("[API] {}", e);
+                    }
+                });
+            }
+            scheduler::start(app.handle().clone());
+            orchestrator::archive::start(app.handle().clone());
+            agent::scratch::start(app.handle().clone());
+            power::start(app.handle().clone());
+            acp::worker_health::start(app.handle().clone());
+            telemetry::init();
+            orchestrator::tool_calls::start(app.handle().clone());
+            acp::resource_limits::start(app.handle().clone());
+            stats::budget::start(app.handle().clone());
+            tasks::query::start(app.handle().clone());
+            if settings::load_settings().tray_icon {
+                if let Err(e) = tray::start(app.handle()) {
+                    eprintln!("[Tray] {}", e);
+                }
+            }
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        // Keep the window open for the shutdown splash while workers drain
+        .on_window_event(|window, event| {
+            if let tauri::WindowEvent::CloseRequested { api, .. } = event {
+                if window.label() == "main" && shutdown::hold_exit(window.app_handle()) {
+                    api.prevent_close();
+                }
+            }
+        })
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            match event {
+                tauri::RunEvent::ExitRequested { api, .. } => {
+                    if shutdown::hold_exit(app) {
+                        api.prevent_exit();
+                    }
+                }
+                tauri::RunEvent::Exit => telemetry::shutdown(),
+                _ => {}
+            }
+        });
 }
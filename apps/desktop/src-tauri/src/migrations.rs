@@ -0,0 +1,342 @@
+//! App data schema version and migrations
+//!
+//! `~/.crafter-code/schema.json` records which layout the app data is in.
+//! At startup `run` applies every migration newer than that, in order,
+//! after copying the records it rewrites (the JSON files in the directories
+//! it touches) to `~/.crafter-code/backups/<time>-v<from>-to-v<to>/`, and
+//! records the new version after each one. A failing migration stops the run with the
+//! data at the last version that succeeded; data written by a newer version
+//! of the app is left alone.
+//!
+//! `get_data_health` reports what couldn't be migrated, plus every settings
+//! file, saved session and archived session that doesn't parse, instead of
+//! those silently missing from lists.
+
+use crate::acp::session_store::SessionStore;
+use crate::orchestrator::archive::ArchiveStore;
+use crate::secrets::at_rest::Encryption;
+use crate::settings::store::SettingsStore;
+use crate::time::now_ms;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The schema version this build reads and writes
+pub const CURRENT_VERSION: u32 = 1;
+
+const SCHEMA_FILE: &str = "schema.json";
+
+/// What went wrong in this launch's migration run, if anything
+static MIGRATION_ERROR: Lazy<Mutex<Option<String>>> = Lazy::new(|| Mutex::new(None));
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct SchemaInfo {
+    pub version: u32,
+    /// Unix timestamp (milliseconds) of the last migration
+    #[serde(default)]
+    pub migrated_at: i64,
+}
+
+struct Migration {
+    version: u32,
+    description: &'static str,
+    /// Directories (under the data directory) whose records it rewrites
+    touches: &'static [&'static str],
+    apply: fn(&Path) -> Result<usize, String>,
+}
+
+const MIGRATIONS: &[Migration] = &[Migration {
+    version: 1,
+    description: "Store session timestamps in milliseconds",
+    touches: &["sessions", "archive"],
+    apply: timestamps_to_ms,
+}];
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordKind {
+    Schema,
+    Settings,
+    Session,
+    ArchivedSession,
+}
+
+/// A record that can't be loaded
+#[derive(Debug, Clone, Serialize)]
+pub struct DataProblem {
+    pub kind: RecordKind,
+    pub path: String,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DataHealth {
+    /// Version of the data on disk
+    pub schema_version: u32,
+    /// Version this build expects
+    pub current_version: u32,
+    /// Records checked
+    pub checked: usize,
+    pub problems: Vec<DataProblem>,
+}
+
+/// ~/.crafter-code
+fn data_dir() -> Result<PathBuf, String> {
+    Ok(dirs::home_dir()
+        .ok_or_else(|| "Could not determine home directory".to_string())?
+        .join(".crafter-code"))
+}
+
+/// Ids of the `<id>.json` records in `dir`
+fn record_ids(dir: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|path| Some(path.file_stem()?.to_string_lossy().to_string()))
+        .collect()
+}
+
+/// Version 1: files from before millisecond timestamps are converted as
+/// they're read; rewrite them so every record on disk is in milliseconds
+fn timestamps_to_ms(base: &Path) -> Result<usize, String> {
+    let mut migrated = 0;
+    let sessions = SessionStore::at(base.join("sessions"), Encryption::Configured)?;
+    for id in record_ids(sessions.base_path()) {
+        // Records that don't parse are left for `get_data_health` to report
+        if let Ok(session) = sessions.load_session(&id) {
+            sessions.save_session(&session)?;
+            migrated += 1;
+        }
+    }
+    let archive_dir = base.join("archive");
+    let archive = ArchiveStore::at(archive_dir.clone(), Encryption::Configured)?;
+    for id in record_ids(&archive_dir) {
+        if let Ok(archived) = archive.load(&id) {
+            archive.save(&archived)?;
+            migrated += 1;
+        }
+    }
+    Ok(migrated)
+}
+
+/// The recorded schema version. Data from before versioning is version 0;
+/// a data directory with nothing in it yet is already current.
+fn read_schema(base: &Path) -> Result<SchemaInfo, String> {
+    let path = base.join(SCHEMA_FILE);
+    if path.exists() {
+        let json = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        return serde_json::from_str(&json)
+            .map_err(|e| format!("Failed to parse {}: {}", path.display(), e));
+    }
+    let has_data = fs::read_dir(base).is_ok_and(|mut entries| entries.next().is_some());
+    Ok(SchemaInfo {
+        version: if has_data { 0 } else { CURRENT_VERSION },
+        migrated_at: 0,
+    })
+}
+
+fn write_schema(base: &Path, schema: &SchemaInfo) -> Result<(), String> {
+    fs::create_dir_all(base).map_err(|e| format!("Failed to create {}: {}", base.display(), e))?;
+    let json = serde_json::to_string_pretty(schema)
+        .map_err(|e| format!("Failed to serialize schema version: {}", e))?;
+    fs::write(base.join(SCHEMA_FILE), json)
+        .map_err(|e| format!("Failed to write schema version: {}", e))
+}
+
+/// Copy the records `migration` rewrites into a fresh backup directory
+fn back_up(base: &Path, migration: &Migration, from: u32) -> Result<PathBuf, String> {
+    let stamp = chrono::Utc::now().format("%Y%m%d-%H%M%S");
+    let backup = base
+        .join("backups")
+        .join(format!("{}-v{}-to-v{}", stamp, from, migration.version));
+    for dir in migration.touches {
+        let target = backup.join(dir);
+        fs::create_dir_all(&target)
+            .map_err(|e| format!("Failed to create {}: {}", target.display(), e))?;
+        for id in record_ids(&base.join(dir)) {
+            let file = format!("{}.json", id);
+            fs::copy(base.join(dir).join(&file), target.join(&file))
+                .map_err(|e| format!("Failed to back up {}/{}: {}", dir, file, e))?;
+        }
+    }
+    Ok(backup)
+}
+
+/// Bring the data in `base` up to `CURRENT_VERSION`
+fn migrate(base: &Path) -> Result<SchemaInfo, String> {
+    let mut schema = read_schema(base)?;
+    if schema.version > CURRENT_VERSION {
+        return Err(format!(
+            "App data is at schema version {}, newer than this build's {}; it was left as is",
+            schema.version, CURRENT_VERSION
+        ));
+    }
+    let found = schema.version;
+    for migration in MIGRATIONS.iter().filter(|m| m.version > found) {
+        let backup = back_up(base, migration, schema.version)?;
+        let migrated = (migration.apply)(base).map_err(|e| {
+            format!(
+                "Migration to version {} ({}) failed: {}; backup in {}",
+                migration.version,
+                migration.description,
+                e,
+                backup.display()
+            )
+        })?;
+        eprintln!(
+            "[Migrations] v{}: {} ({} records, backup in {})",
+            migration.version,
+            migration.description,
+            migrated,
+            backup.display()
+        );
+        schema = SchemaInfo {
+            version: migration.version,
+            migrated_at: now_ms(),
+        };
+        write_schema(base, &schema)?;
+    }
+    if !base.join(SCHEMA_FILE).exists() {
+        write_schema(base, &schema)?;
+    }
+    Ok(schema)
+}
+
+/// Migrate the app data at startup, before anything loads it
+pub fn run() {
+    let result = data_dir().and_then(|base| migrate(&base));
+    if let Err(e) = &result {
+        eprintln!("[Migrations] {}", e);
+    }
+    *MIGRATION_ERROR.lock() = result.err();
+}
+
+fn check(base: &Path) -> DataHealth {
+    let mut problems = Vec::new();
+    let mut checked = 0;
+    let mut problem = |kind, path: &Path, error: String| {
+        problems.push(DataProblem {
+            kind,
+            path: path.to_string_lossy().to_string(),
+            error,
+        });
+    };
+
+    let schema_path = base.join(SCHEMA_FILE);
+    let schema_version = match read_schema(base) {
+        Ok(schema) => schema.version,
+        Err(e) => {
+            problem(RecordKind::Schema, &schema_path, e);
+            0
+        }
+    };
+    if let Some(e) = MIGRATION_ERROR.lock().clone() {
+        problem(RecordKind::Schema, &schema_path, e);
+    }
+
+    let settings_path = base.join("settings.json");
+    if settings_path.exists() {
+        checked += 1;
+        if let Err(e) = SettingsStore::with_path(settings_path.clone()).load() {
+            problem(RecordKind::Settings, &settings_path, e);
+        }
+    }
+
+    let sessions_dir = base.join("sessions");
+    if let Ok(sessions) = SessionStore::at(sessions_dir.clone(), Encryption::Configured) {
+        for id in record_ids(&sessions_dir) {
+            checked += 1;
+            if let Err(e) = sessions.load_session(&id) {
+                problem(RecordKind::Session, &sessions.session_path(&id), e);
+            }
+        }
+    }
+
+    let archive_dir = base.join("archive");
+    if let Ok(archive) = ArchiveStore::at(archive_dir.clone(), Encryption::Configured) {
+        for id in record_ids(&archive_dir) {
+            checked += 1;
+            if let Err(e) = archive.load(&id) {
+                problem(RecordKind::ArchivedSession, &archive.session_path(&id), e);
+            }
+        }
+    }
+
+    DataHealth {
+        schema_version,
+        current_version: CURRENT_VERSION,
+        checked,
+        problems,
+    }
+}
+
+/// Records that can't be loaded or migrated
+#[tauri::command]
+pub async fn get_data_health() -> Result<DataHealth, String> {
+    let base = data_dir()?;
+    tauri::async_runtime::spawn_blocking(move || check(&base))
+        .await
+        .map_err(|e| format!("Data health check failed: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OLD_SESSION: &str = r#"{"id":"s1","acp_session_id":"a","cwd":"/","agent_id":"claude",
+        "created_at":1706000000,"updated_at":1706001000,"messages":[],"mode":"default",
+        "initial_prompt":"hi"}"#;
+
+    #[test]
+    fn test_migrate_backs_up_and_rewrites() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = dir.path();
+        fs::create_dir_all(base.join("sessions")).unwrap();
+        fs::write(base.join("sessions").join("s1.json"), OLD_SESSION).unwrap();
+        fs::write(base.join("sessions").join("broken.json"), "{").unwrap();
+
+        let schema = migrate(base).unwrap();
+        assert_eq!(schema.version, CURRENT_VERSION);
+        assert_eq!(read_schema(base).unwrap(), schema);
+
+        let json = fs::read_to_string(base.join("sessions").join("s1.json")).unwrap();
+        assert!(json.contains("1706000000000"));
+        let backups: Vec<_> = fs::read_dir(base.join("backups")).unwrap().flatten().collect();
+        assert_eq!(backups.len(), 1);
+        let backed_up = backups[0].path().join("sessions").join("s1.json");
+        assert_eq!(fs::read_to_string(backed_up).unwrap(), OLD_SESSION);
+
+        // Already current: nothing more to do
+        migrate(base).unwrap();
+        assert_eq!(fs::read_dir(base.join("backups")).unwrap().count(), 1);
+
+        let health = check(base);
+        assert_eq!(health.checked, 2);
+        assert_eq!(health.problems.len(), 1);
+        assert_eq!(health.problems[0].kind, RecordKind::Session);
+        assert!(health.problems[0].path.ends_with("broken.json"));
+    }
+
+    #[test]
+    fn test_newer_and_fresh_data() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(read_schema(dir.path()).unwrap().version, CURRENT_VERSION);
+
+        write_schema(
+            dir.path(),
+            &SchemaInfo {
+                version: CURRENT_VERSION + 1,
+                migrated_at: 0,
+            },
+        )
+        .unwrap();
+        assert!(migrate(dir.path()).is_err());
+    }
+}
@@ -0,0 +1,7 @@
+use super::notifier::set_completion_override;
+
+/// Notify when a session's prompts complete, overriding the global toggle
+#[tauri::command]
+pub fn notify_on_completion(session_id: String, enabled: bool) {
+    set_completion_override(&session_id, enabled);
+}
@@ -0,0 +1,10 @@
+//! Desktop notifications
+//!
+//! Shows OS notifications for events that need attention while the user is
+//! away: finished prompts, permission requests, failed workers, exceeded
+//! budgets and completed PRD stories. Each event can be toggled in settings.
+
+pub mod commands;
+mod notifier;
+
+pub use notifier::{notify, NotificationEvent};
@@ -0,0 +1,144 @@
+use crate::settings::load_settings;
+use crate::settings::store::NotificationSettings;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+/// Per-session overrides for completion notifications (session_id -> enabled)
+static COMPLETION_OVERRIDES: Lazy<Mutex<HashMap<String, bool>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Maximum notification body length
+const MAX_BODY_CHARS: usize = 160;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NotificationEvent {
+    PromptCompleted,
+    PermissionRequired,
+    WorkerFailed,
+    BudgetExceeded,
+    PrdStoryCompleted,
+}
+
+impl NotificationEvent {
+    fn toggle(&self, settings: &NotificationSettings) -> bool {
+        match self {
+            Self::PromptCompleted => settings.prompt_completed,
+            Self::PermissionRequired => settings.permission_required,
+            Self::WorkerFailed => settings.worker_failed,
+            Self::BudgetExceeded => settings.budget_exceeded,
+            Self::PrdStoryCompleted => settings.prd_story_completed,
+        }
+    }
+}
+
+/// Enable or disable completion notifications for a single session,
+/// regardless of the global `prompt_completed` toggle
+pub fn set_completion_override(session_id: &str, enabled: bool) {
+    COMPLETION_OVERRIDES
+        .lock()
+        .insert(session_id.to_string(), enabled);
+}
+
+/// Decide whether an event should produce a notification
+fn should_notify(
+    event: NotificationEvent,
+    settings: &NotificationSettings,
+    completion_override: Option<bool>,
+    focused: bool,
+) -> bool {
+    if !settings.enabled || (focused && !settings.when_focused) {
+        return false;
+    }
+    match (event, completion_override) {
+        (NotificationEvent::PromptCompleted, Some(enabled)) => enabled,
+        _ => event.toggle(settings),
+    }
+}
+
+fn truncate(text: &str) -> String {
+    let text = text.trim();
+    if text.chars().count() <= MAX_BODY_CHARS {
+        return text.to_string();
+    }
+    let cut: String = text.chars().take(MAX_BODY_CHARS - 1).collect();
+    format!("{}…", cut.trim_end())
+}
+
+/// Show a desktop notification if the event is enabled
+pub fn notify(
+    app_handle: &AppHandle,
+    event: NotificationEvent,
+    session_id: Option<&str>,
+    title: &str,
+    body: &str,
+) {
+    let settings = load_settings().notifications;
+    let completion_override =
+        session_id.and_then(|id| COMPLETION_OVERRIDES.lock().get(id).copied());
+    let focused = app_handle
+        .get_webview_window("main")
+        .and_then(|w| w.is_focused().ok())
+        .unwrap_or(false);
+
+    if !should_notify(event, &settings, completion_override, focused) {
+        return;
+    }
+
+    if let Err(e) = app_handle
+        .notification()
+        .builder()
+        .title(title)
+        .body(truncate(body))
+        .show()
+    {
+        eprintln!("[Notifications] Failed to show {:?}: {}", event, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_should_notify_respects_toggles() {
+        let settings = NotificationSettings::default();
+        assert!(should_notify(NotificationEvent::WorkerFailed, &settings, None, false));
+        assert!(!should_notify(NotificationEvent::PromptCompleted, &settings, None, false));
+        assert!(!should_notify(NotificationEvent::WorkerFailed, &settings, None, true));
+
+        let disabled = NotificationSettings {
+            enabled: false,
+            ..Default::default()
+        };
+        assert!(!should_notify(NotificationEvent::WorkerFailed, &disabled, None, false));
+    }
+
+    #[test]
+    fn test_completion_override() {
+        let settings = NotificationSettings::default();
+        assert!(should_notify(NotificationEvent::PromptCompleted, &settings, Some(true), false));
+
+        let all_completions = NotificationSettings {
+            prompt_completed: true,
+            ..Default::default()
+        };
+        assert!(!should_notify(
+            NotificationEvent::PromptCompleted,
+            &all_completions,
+            Some(false),
+            false
+        ));
+        // Overrides only apply to completions
+        assert!(should_notify(NotificationEvent::WorkerFailed, &settings, Some(false), false));
+    }
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(truncate("  short  "), "short");
+        let long = "a".repeat(500);
+        assert_eq!(truncate(&long).chars().count(), MAX_BODY_CHARS);
+    }
+}
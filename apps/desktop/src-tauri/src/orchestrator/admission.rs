@@ -0,0 +1,177 @@
+//! Admission control for agent subprocesses
+//!
+//! Every persistent worker holds a slot while its agent process is alive.
+//! New sessions only start when a slot is free under the configured limit;
+//! otherwise they wait in a FIFO queue and are started as slots free up.
+
+use std::collections::{HashSet, VecDeque};
+use std::fmt;
+
+/// Starts a queued session's worker thread
+pub type StartFn = Box<dyn FnOnce() + Send>;
+
+struct QueuedSession {
+    session_id: String,
+    worker_id: String,
+    start: StartFn,
+}
+
+/// Outcome of asking to start a session
+pub enum Admission {
+    /// A slot was taken; run this to start the worker
+    Start(StartFn),
+    /// Waiting for a slot (1-based position in the queue)
+    Queued { position: usize },
+}
+
+#[derive(Default)]
+pub struct AgentSlots {
+    /// Worker ids whose agent process is alive
+    running: HashSet<String>,
+    queue: VecDeque<QueuedSession>,
+}
+
+impl fmt::Debug for AgentSlots {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AgentSlots")
+            .field("running", &self.running)
+            .field("queued", &self.queue.len())
+            .finish()
+    }
+}
+
+impl AgentSlots {
+    /// Whether another agent may start; a limit of 0 means unlimited
+    pub fn has_free_slot(&self, limit: usize) -> bool {
+        limit == 0 || self.running.len() < limit
+    }
+
+    /// Take a slot regardless of the limit (resumed and reconnected workers)
+    pub fn occupy(&mut self, worker_id: &str) {
+        self.running.insert(worker_id.to_string());
+    }
+
+    /// Start now if a slot is free and nobody is waiting, otherwise queue
+    pub fn admit(
+        &mut self,
+        session_id: &str,
+        worker_id: &str,
+        limit: usize,
+        start: StartFn,
+    ) -> Admission {
+        if self.queue.is_empty() && self.has_free_slot(limit) {
+            self.occupy(worker_id);
+            return Admission::Start(start);
+        }
+        self.queue.push_back(QueuedSession {
+            session_id: session_id.to_string(),
+            worker_id: worker_id.to_string(),
+            start,
+        });
+        Admission::Queued {
+            position: self.queue.len(),
+        }
+    }
+
+    /// Free a worker's slot. Returns the (session_id, start) of queued
+    /// sessions that now fit, with their slots already taken.
+    pub fn release(&mut self, worker_id: &str, limit: usize) -> Vec<(String, StartFn)> {
+        self.running.remove(worker_id);
+
+        let mut started = Vec::new();
+        while self.has_free_slot(limit) {
+            let Some(queued) = self.queue.pop_front() else {
+                break;
+            };
+            self.occupy(&queued.worker_id);
+            started.push((queued.session_id, queued.start));
+        }
+        started
+    }
+
+    /// Agent processes alive
+    pub fn running(&self) -> usize {
+        self.running.len()
+    }
+
+    /// Drop every queued session (app shutdown); returns their session ids
+    pub fn clear_queue(&mut self) -> Vec<String> {
+        self.queue.drain(..).map(|q| q.session_id).collect()
+    }
+
+    /// Drop a queued session before it started
+    pub fn cancel_queued(&mut self, worker_id: &str) -> bool {
+        let before = self.queue.len();
+        self.queue.retain(|q| q.worker_id != worker_id);
+        self.queue.len() != before
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    fn start(counter: &Arc<AtomicUsize>) -> StartFn {
+        let counter = counter.clone();
+        Box::new(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        })
+    }
+
+    fn run(admission: Admission) -> Option<usize> {
+        match admission {
+            Admission::Start(start) => {
+                start();
+                None
+            }
+            Admission::Queued { position } => Some(position),
+        }
+    }
+
+    #[test]
+    fn test_queue_until_slot_frees() {
+        let started = Arc::new(AtomicUsize::new(0));
+        let mut slots = AgentSlots::default();
+
+        assert_eq!(run(slots.admit("s1", "w1", 2, start(&started))), None);
+        assert_eq!(run(slots.admit("s2", "w2", 2, start(&started))), None);
+        assert_eq!(run(slots.admit("s3", "w3", 2, start(&started))), Some(1));
+        assert_eq!(run(slots.admit("s4", "w4", 2, start(&started))), Some(2));
+        assert_eq!(started.load(Ordering::SeqCst), 2);
+
+        // Cancelled sessions never start
+        assert!(slots.cancel_queued("w3"));
+        assert!(!slots.cancel_queued("w3"));
+
+        let next = slots.release("w1", 2);
+        assert_eq!(next.len(), 1);
+        assert_eq!(next[0].0, "s4");
+        next.into_iter().for_each(|(_, start)| start());
+        assert_eq!(started.load(Ordering::SeqCst), 3);
+        assert!(!slots.has_free_slot(2));
+
+        // Nothing queued starts after the queue is cleared
+        assert_eq!(run(slots.admit("s5", "w5", 2, start(&started))), Some(1));
+        assert_eq!(slots.clear_queue(), vec!["s5"]);
+        assert!(slots.release("w2", 2).is_empty());
+        assert_eq!(slots.running(), 1);
+    }
+
+    #[test]
+    fn test_unlimited_and_occupy() {
+        let started = Arc::new(AtomicUsize::new(0));
+        let mut slots = AgentSlots::default();
+
+        // Resumed workers count even past the limit
+        slots.occupy("resumed");
+        assert_eq!(run(slots.admit("s1", "w1", 1, start(&started))), Some(1));
+        assert_eq!(slots.release("resumed", 1).len(), 1);
+
+        for i in 0..10 {
+            let id = i.to_string();
+            assert_eq!(run(slots.admit(&id, &id, 0, start(&started))), None);
+        }
+    }
+}
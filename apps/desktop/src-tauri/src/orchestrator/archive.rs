@@ -0,0 +1,494 @@
+//! Session archive
+//!
+//! Live orchestrator sessions are kept in `OrchestratorManager` until they
+//! are archived. A finished session (completed, failed or cancelled) with no
+//! worker left is written to ~/.crafter-code/archive/{session_id}.json along
+//! with its tasks and inbox messages, then evicted from memory. That happens
+//! on request (`archive_session`) and from a background sweep once the
+//! session is older than `archive_after_hours`. `purge_sessions` deletes
+//! archived (and optionally saved ACP) sessions past an age.
+
+use crate::acp::commands::clear_session_state;
+use crate::acp::session_store::{PersistedSessionSummary, SessionFilter, SessionStore};
+use crate::inbox::message::Message;
+use crate::orchestrator::session::{OrchestratorSession, SessionStatus};
+use crate::orchestrator::worker::WorkerStatus;
+use crate::secrets::at_rest::{self, Encryption};
+use crate::settings::load_settings;
+use crate::tasks::task::Task;
+use crate::time::{normalize_ms, now_ms, secs_to_ms};
+use crate::AppState;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+/// How often the background sweep looks for sessions to archive
+const SWEEP_INTERVAL: Duration = Duration::from_secs(600);
+
+/// A session as written to the archive
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedSession {
+    pub session: OrchestratorSession,
+    #[serde(default)]
+    pub tasks: Vec<Task>,
+    #[serde(default)]
+    pub messages: Vec<Message>,
+    /// Unix timestamp (milliseconds)
+    pub archived_at: i64,
+}
+
+/// Summary of an archived session for listing
+#[derive(Debug, Clone, Serialize)]
+pub struct ArchivedSessionSummary {
+    pub id: String,
+    pub prompt: String,
+    pub status: SessionStatus,
+    pub workers: usize,
+    pub total_cost: f64,
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub archived_at: i64,
+}
+
+impl From<&ArchivedSession> for ArchivedSessionSummary {
+    fn from(archived: &ArchivedSession) -> Self {
+        let session = &archived.session;
+        Self {
+            id: session.id.clone(),
+            prompt: session.prompt.clone(),
+            status: session.status.clone(),
+            workers: session.workers.len(),
+            total_cost: session.total_cost,
+            created_at: session.created_at,
+            updated_at: session.updated_at,
+            archived_at: archived.archived_at,
+        }
+    }
+}
+
+/// Files in one session store and their total size
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct StoreUsage {
+    pub path: String,
+    pub files: usize,
+    pub bytes: u64,
+}
+
+/// Disk space taken by saved and archived sessions
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionStorage {
+    /// Saved ACP sessions (~/.crafter-code/sessions)
+    pub sessions: StoreUsage,
+    /// Archived orchestrator sessions (~/.crafter-code/archive)
+    pub archive: StoreUsage,
+    pub total_bytes: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PurgeResult {
+    /// Archived sessions deleted
+    pub archived: usize,
+    /// Saved ACP sessions deleted
+    pub saved: usize,
+    pub freed_bytes: u64,
+}
+
+/// Size of the session files (`*.json`) in `dir`
+pub fn dir_usage(dir: &Path) -> StoreUsage {
+    let mut usage = StoreUsage {
+        path: dir.to_string_lossy().to_string(),
+        ..Default::default()
+    };
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            if entry.path().extension().is_some_and(|ext| ext == "json") {
+                if let Ok(metadata) = entry.metadata() {
+                    usage.files += 1;
+                    usage.bytes += metadata.len();
+                }
+            }
+        }
+    }
+    usage
+}
+
+/// Archived sessions on disk
+pub struct ArchiveStore {
+    base_path: PathBuf,
+    encryption: Encryption,
+}
+
+impl ArchiveStore {
+    pub fn new() -> Result<Self, String> {
+        let base_path = dirs::home_dir()
+            .ok_or_else(|| "Could not determine home directory".to_string())?
+            .join(".crafter-code")
+            .join("archive");
+        Self::at(base_path, Encryption::Configured)
+    }
+
+    /// Sessions saved as they were when the app quit
+    /// (~/.crafter-code/snapshots)
+    pub fn snapshots() -> Result<Self, String> {
+        let base_path = dirs::home_dir()
+            .ok_or_else(|| "Could not determine home directory".to_string())?
+            .join(".crafter-code")
+            .join("snapshots");
+        Self::at(base_path, Encryption::Configured)
+    }
+
+    /// A store in `base_path` instead of the home directory
+    pub fn at(base_path: PathBuf, encryption: Encryption) -> Result<Self, String> {
+        fs::create_dir_all(&base_path)
+            .map_err(|e| format!("Failed to create archive directory: {}", e))?;
+        Ok(Self {
+            base_path,
+            encryption,
+        })
+    }
+
+    /// The file an archived session is kept in
+    pub fn session_path(&self, session_id: &str) -> PathBuf {
+        self.base_path.join(format!("{}.json", session_id))
+    }
+
+    pub fn save(&self, archived: &ArchivedSession) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(archived)
+            .map_err(|e| format!("Failed to serialize archived session: {}", e))?;
+        at_rest::write(&self.session_path(&archived.session.id), json.as_bytes(), self.encryption)
+            .map_err(|e| format!("Failed to write archived session: {}", e))
+    }
+
+    pub fn load(&self, session_id: &str) -> Result<ArchivedSession, String> {
+        let json = at_rest::read_to_string(&self.session_path(session_id), self.encryption)
+            .map_err(|e| format!("Archived session {} can't be read: {}", session_id, e))?;
+        parse(&json).map_err(|e| format!("Failed to parse archived session: {}", e))
+    }
+
+    fn load_all(&self) -> Vec<ArchivedSession> {
+        let Ok(entries) = fs::read_dir(&self.base_path) else {
+            return Vec::new();
+        };
+        entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|path| at_rest::read_to_string(&path, self.encryption).ok())
+            .filter_map(|json| parse(&json).ok())
+            .collect()
+    }
+
+    /// Archived sessions, most recently archived first
+    pub fn list(&self) -> Vec<ArchivedSessionSummary> {
+        let mut sessions: Vec<ArchivedSessionSummary> = self
+            .load_all()
+            .iter()
+            .map(ArchivedSessionSummary::from)
+            .collect();
+        sessions.sort_by_key(|s| std::cmp::Reverse(s.archived_at));
+        sessions
+    }
+
+    pub fn delete(&self, session_id: &str) -> Result<(), String> {
+        let path = self.session_path(session_id);
+        if path.exists() {
+            fs::remove_file(&path)
+                .map_err(|e| format!("Failed to delete archived session: {}", e))?;
+        }
+        Ok(())
+    }
+
+    /// Delete archived sessions last updated before `cutoff`; returns how
+    /// many were deleted
+    pub fn purge(&self, cutoff: i64) -> usize {
+        self.load_all()
+            .iter()
+            .filter(|archived| archived.session.updated_at < cutoff)
+            .filter(|archived| self.delete(&archived.session.id).is_ok())
+            .count()
+    }
+
+    pub fn usage(&self) -> StoreUsage {
+        dir_usage(&self.base_path)
+    }
+}
+
+/// Read an archived session, converting timestamps saved in seconds
+fn parse(json: &str) -> Result<ArchivedSession, serde_json::Error> {
+    let mut archived: ArchivedSession = serde_json::from_str(json)?;
+    archived.session.normalize_timestamps();
+    archived.archived_at = normalize_ms(archived.archived_at);
+    Ok(archived)
+}
+
+/// Whether a session is finished, has no worker that could still run, and
+/// was last updated at or before `cutoff`
+fn is_archivable(session: &OrchestratorSession, cutoff: i64) -> bool {
+    let finished = matches!(
+        session.status,
+        SessionStatus::Completed | SessionStatus::Failed | SessionStatus::Cancelled
+    );
+    let workers_done = session.workers.iter().all(|w| {
+        !matches!(
+            w.status,
+            WorkerStatus::Pending
+                | WorkerStatus::Running
+                | WorkerStatus::Idle
+                | WorkerStatus::Hibernated
+        )
+    });
+    finished && workers_done && session.updated_at <= cutoff
+}
+
+/// A live session with its tasks and inbox messages
+pub fn capture(state: &AppState, session: &OrchestratorSession) -> ArchivedSession {
+    let tasks = state
+        .task_managers
+        .lock()
+        .get(&session.id)
+        .map(|tasks| tasks.list())
+        .unwrap_or_default();
+    let messages = state
+        .inbox_managers
+        .lock()
+        .get(&session.id)
+        .map(|inbox| {
+            inbox
+                .get_workers()
+                .iter()
+                .flat_map(|worker| inbox.read(worker))
+                .collect()
+        })
+        .unwrap_or_default();
+    ArchivedSession {
+        session: session.clone(),
+        tasks,
+        messages,
+        archived_at: now_ms(),
+    }
+}
+
+/// Write a live session to the archive and evict it, along with its task
+/// and inbox managers
+pub fn archive_live_session(
+    state: &AppState,
+    store: &ArchiveStore,
+    session_id: &str,
+) -> Result<ArchivedSessionSummary, String> {
+    if state.worker_handles.lock().contains_key(session_id) {
+        return Err(format!(
+            "Session {} still has a running worker; stop it first",
+            session_id
+        ));
+    }
+    let mut mgr = state.orchestrator_manager.lock();
+    let session = mgr
+        .get_session(session_id)
+        .ok_or_else(|| format!("Session {} not found", session_id))?;
+    if !is_archivable(session, i64::MAX) {
+        return Err(format!("Session {} hasn't finished", session_id));
+    }
+
+    let archived = capture(state, session);
+    store.save(&archived)?;
+
+    mgr.remove_session(session_id);
+    drop(mgr);
+    state.task_managers.lock().remove(session_id);
+    state.inbox_managers.lock().remove(session_id);
+    clear_session_state(session_id);
+    Ok(ArchivedSessionSummary::from(&archived))
+}
+
+/// Archive every live session older than `max_age_secs`; returns how many
+/// were archived
+pub fn archive_older_than(state: &AppState, store: &ArchiveStore, max_age_secs: i64) -> usize {
+    let cutoff = now_ms().saturating_sub(secs_to_ms(max_age_secs));
+    let candidates: Vec<String> = state
+        .orchestrator_manager
+        .lock()
+        .list_sessions()
+        .into_iter()
+        .filter(|session| is_archivable(session, cutoff))
+        .map(|session| session.id.clone())
+        .collect();
+    candidates
+        .iter()
+        .filter(|id| match archive_live_session(state, store, id) {
+            Ok(_) => true,
+            Err(e) => {
+                eprintln!("[Archive] {}", e);
+                false
+            }
+        })
+        .count()
+}
+
+/// Saved ACP sessions last updated before `cutoff` that aren't pinned or
+/// loaded (or unreadable, which may just be locked)
+pub fn stale_saved_sessions(
+    state: &AppState,
+    saved: &SessionStore,
+    cutoff: i64,
+) -> Vec<PersistedSessionSummary> {
+    let filter = SessionFilter {
+        include_archived: true,
+        ..Default::default()
+    };
+    saved
+        .list_sessions(&filter)
+        .into_iter()
+        .filter(|summary| summary.updated_at < cutoff && !summary.metadata.pinned)
+        .filter(|summary| summary.error.is_none())
+        .filter(|summary| {
+            !state.worker_handles.lock().contains_key(&summary.id)
+                && state
+                    .orchestrator_manager
+                    .lock()
+                    .get_session(&summary.id)
+                    .is_none()
+        })
+        .collect()
+}
+
+/// Delete archived sessions, and saved ACP sessions if `include_saved`,
+/// last updated more than `max_age_secs` ago. Pinned and loaded saved
+/// sessions are kept.
+pub fn purge_older_than(
+    state: &AppState,
+    store: &ArchiveStore,
+    max_age_secs: i64,
+    include_saved: bool,
+) -> Result<PurgeResult, String> {
+    let cutoff = now_ms().saturating_sub(secs_to_ms(max_age_secs));
+    let before = store.usage().bytes;
+    let mut result = PurgeResult {
+        archived: store.purge(cutoff),
+        ..Default::default()
+    };
+    let mut freed = before.saturating_sub(store.usage().bytes);
+
+    if include_saved {
+        let saved = SessionStore::new()?;
+        let before = dir_usage(saved.base_path()).bytes;
+        for summary in stale_saved_sessions(state, &saved, cutoff) {
+            saved.delete_session(&summary.id)?;
+            result.saved += 1;
+        }
+        freed += before.saturating_sub(dir_usage(saved.base_path()).bytes);
+    }
+    result.freed_bytes = freed;
+    Ok(result)
+}
+
+/// Disk space used by the saved and archived session stores
+pub fn session_storage() -> Result<SessionStorage, String> {
+    let sessions = dir_usage(SessionStore::new()?.base_path());
+    let archive = ArchiveStore::new()?.usage();
+    Ok(SessionStorage {
+        total_bytes: sessions.bytes + archive.bytes,
+        sessions,
+        archive,
+    })
+}
+
+/// Start the background sweep archiving sessions older than
+/// `archive_after_hours`
+pub fn start(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(SWEEP_INTERVAL);
+        loop {
+            interval.tick().await;
+            let hours = load_settings().archive_after_hours;
+            if hours == 0 {
+                continue;
+            }
+            let store = match ArchiveStore::new() {
+                Ok(store) => store,
+                Err(e) => {
+                    eprintln!("[Archive] {}", e);
+                    continue;
+                }
+            };
+            let state = app.state::<AppState>();
+            let archived = archive_older_than(&state, &store, i64::from(hours) * 3600);
+            if archived > 0 {
+                eprintln!("[Archive] Archived {} finished session(s)", archived);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::claude::pricing::Model;
+    use crate::orchestrator::worker::WorkerSession;
+
+    fn session(id: &str, status: SessionStatus, updated_at: i64) -> OrchestratorSession {
+        let mut session =
+            OrchestratorSession::new(id.to_string(), "Fix it".to_string(), Model::Sonnet);
+        session.status = status;
+        session.updated_at = updated_at;
+        session
+    }
+
+    #[test]
+    fn test_is_archivable() {
+        assert!(is_archivable(
+            &session("a", SessionStatus::Completed, 100),
+            100
+        ));
+        assert!(is_archivable(&session("a", SessionStatus::Failed, 50), 100));
+        assert!(!is_archivable(
+            &session("a", SessionStatus::Completed, 101),
+            100
+        ));
+        assert!(!is_archivable(
+            &session("a", SessionStatus::Running, 50),
+            100
+        ));
+
+        // A persistent worker waiting for follow-ups keeps the session live
+        let mut waiting = session("a", SessionStatus::Completed, 50);
+        let mut worker = WorkerSession::new(
+            "w".to_string(),
+            "a".to_string(),
+            "task".to_string(),
+            Model::Sonnet,
+        );
+        worker.status = WorkerStatus::Idle;
+        waiting.workers.push(worker);
+        assert!(!is_archivable(&waiting, 100));
+        waiting.workers[0].status = WorkerStatus::Completed;
+        assert!(is_archivable(&waiting, 100));
+    }
+
+    #[test]
+    fn test_archive_store_purge_and_usage() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ArchiveStore::at(dir.path().join("archive"), Encryption::Off).unwrap();
+        for (id, updated_at) in [("old", 1_700_000_000_000), ("new", 1_700_000_100_000)] {
+            store
+                .save(&ArchivedSession {
+                    session: session(id, SessionStatus::Completed, updated_at),
+                    tasks: vec![],
+                    messages: vec![],
+                    archived_at: updated_at + 10,
+                })
+                .unwrap();
+        }
+        let ids: Vec<String> = store.list().into_iter().map(|s| s.id).collect();
+        assert_eq!(ids, vec!["new", "old"]);
+        assert_eq!(store.load("old").unwrap().session.prompt, "Fix it");
+        let usage = store.usage();
+        assert_eq!(usage.files, 2);
+        assert!(usage.bytes > 0);
+
+        assert_eq!(store.purge(1_700_000_050_000), 1);
+        assert!(store.load("old").is_err());
+        assert_eq!(store.usage().files, 1);
+    }
+}
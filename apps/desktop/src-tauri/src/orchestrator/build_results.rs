@@ -0,0 +1,369 @@
+//! Test/build output analysis
+//!
+//! Recognizes the output of common test runners and compilers (cargo,
+//! jest, pytest, tsc) in agent terminals and shell tool calls, and extracts
+//! pass/fail counts, failing test names and error locations so the UI can
+//! show a summary instead of raw logs.
+
+use crate::time::now_ms;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use ts_rs::TS;
+
+/// Global registry of build results (session_id -> results in order)
+static BUILD_RESULTS: Lazy<Mutex<HashMap<String, Vec<BuildResult>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Maximum number of errors/failing tests kept per result
+const MAX_ITEMS: usize = 50;
+
+static ANSI_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\x1b\[[0-9;?]*[A-Za-z]").unwrap());
+
+static CARGO_SUMMARY_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"test result: \w+\. (\d+) passed; (\d+) failed; (\d+) ignored").unwrap()
+});
+static CARGO_FAILED_TEST_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^test (\S+) \.\.\. FAILED").unwrap());
+static CARGO_ERROR_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^error(?:\[(E\d+)\])?: (.+)$").unwrap());
+static CARGO_LOCATION_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\s*--> (.+?):(\d+):(\d+)").unwrap());
+
+static JEST_SUMMARY_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^Tests:\s+(.+) total").unwrap());
+static JEST_FAILED_TEST_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\s*● (.+)$").unwrap());
+
+static PYTEST_SUMMARY_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^=+ (.+) in [\d.]+s.*=+$").unwrap());
+static PYTEST_FAILED_TEST_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(?:FAILED|ERROR) (\S+)").unwrap());
+
+static TSC_ERROR_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(.+?)(?:\((\d+),(\d+)\): | ?:(\d+):(\d+) - )error (TS\d+): (.+)$").unwrap()
+});
+
+static COUNT_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(\d+) (passed|failed|skipped|errors?|xfailed|todo)").unwrap());
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum BuildTool {
+    Cargo,
+    Jest,
+    Pytest,
+    Tsc,
+}
+
+/// A compiler/type-checker error location
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
+#[ts(export)]
+pub struct BuildError {
+    pub file: Option<String>,
+    pub line: Option<u32>,
+    pub column: Option<u32>,
+    pub code: Option<String>,
+    pub message: String,
+}
+
+/// Structured result parsed from one output
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
+#[ts(export)]
+pub struct BuildSummary {
+    pub tool: BuildTool,
+    pub success: bool,
+    pub passed: u32,
+    pub failed: u32,
+    pub skipped: u32,
+    pub failing_tests: Vec<String>,
+    pub errors: Vec<BuildError>,
+}
+
+impl BuildSummary {
+    fn new(tool: BuildTool) -> Self {
+        Self {
+            tool,
+            success: true,
+            passed: 0,
+            failed: 0,
+            skipped: 0,
+            failing_tests: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    fn finish(mut self) -> Self {
+        self.failing_tests.truncate(MAX_ITEMS);
+        self.errors.truncate(MAX_ITEMS);
+        self.success = self.failed == 0 && self.errors.is_empty();
+        self
+    }
+}
+
+/// A build result recorded for a session
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct BuildResult {
+    pub id: String,
+    pub worker_id: String,
+    /// Terminal or tool call id the output came from
+    pub source_id: String,
+    #[ts(type = "number")]
+    pub timestamp: i64,
+    #[serde(flatten)]
+    pub summary: BuildSummary,
+}
+
+fn count(text: &str, label: &str) -> u32 {
+    COUNT_RE
+        .captures_iter(text)
+        .filter(|c| c[2].starts_with(label))
+        .filter_map(|c| c[1].parse::<u32>().ok())
+        .sum()
+}
+
+fn parse_cargo(lines: &[&str]) -> Option<BuildSummary> {
+    let mut summary = BuildSummary::new(BuildTool::Cargo);
+    let mut seen = false;
+
+    for (i, line) in lines.iter().enumerate() {
+        if let Some(c) = CARGO_SUMMARY_RE.captures(line) {
+            seen = true;
+            summary.passed += c[1].parse::<u32>().unwrap_or(0);
+            summary.failed += c[2].parse::<u32>().unwrap_or(0);
+            summary.skipped += c[3].parse::<u32>().unwrap_or(0);
+        } else if let Some(c) = CARGO_FAILED_TEST_RE.captures(line) {
+            summary.failing_tests.push(c[1].to_string());
+        } else if let Some(c) = CARGO_ERROR_RE.captures(line) {
+            let message = c[2].to_string();
+            // Summary lines, not actual diagnostics
+            if message.starts_with("could not compile") || message.starts_with("aborting") {
+                seen = true;
+                continue;
+            }
+            let location = lines
+                .iter()
+                .skip(i + 1)
+                .take(3)
+                .find_map(|l| CARGO_LOCATION_RE.captures(l));
+            // Plain `error: ...` lines without a location come from many tools
+            if c.get(1).is_none() && location.is_none() {
+                continue;
+            }
+            seen = true;
+            summary.errors.push(BuildError {
+                file: location.as_ref().map(|l| l[1].to_string()),
+                line: location.as_ref().and_then(|l| l[2].parse().ok()),
+                column: location.as_ref().and_then(|l| l[3].parse().ok()),
+                code: c.get(1).map(|m| m.as_str().to_string()),
+                message,
+            });
+        }
+    }
+
+    seen.then(|| summary.finish())
+}
+
+fn parse_jest(lines: &[&str]) -> Option<BuildSummary> {
+    let totals = lines.iter().find_map(|l| JEST_SUMMARY_RE.captures(l))?;
+    let mut summary = BuildSummary::new(BuildTool::Jest);
+    summary.passed = count(&totals[1], "passed");
+    summary.failed = count(&totals[1], "failed");
+    summary.skipped = count(&totals[1], "skipped") + count(&totals[1], "todo");
+    for line in lines {
+        if let Some(c) = JEST_FAILED_TEST_RE.captures(line) {
+            let name = c[1].trim().to_string();
+            if !name.starts_with("Console") && !summary.failing_tests.contains(&name) {
+                summary.failing_tests.push(name);
+            }
+        }
+    }
+    Some(summary.finish())
+}
+
+fn parse_pytest(lines: &[&str]) -> Option<BuildSummary> {
+    let totals = lines
+        .iter()
+        .rev()
+        .find_map(|l| PYTEST_SUMMARY_RE.captures(l))
+        .filter(|c| COUNT_RE.is_match(&c[1]))?;
+    let mut summary = BuildSummary::new(BuildTool::Pytest);
+    summary.passed = count(&totals[1], "passed");
+    summary.failed = count(&totals[1], "failed") + count(&totals[1], "error");
+    summary.skipped = count(&totals[1], "skipped");
+    for line in lines {
+        if let Some(c) = PYTEST_FAILED_TEST_RE.captures(line) {
+            summary.failing_tests.push(c[1].to_string());
+        }
+    }
+    Some(summary.finish())
+}
+
+fn parse_tsc(lines: &[&str]) -> Option<BuildSummary> {
+    let mut summary = BuildSummary::new(BuildTool::Tsc);
+    for line in lines {
+        if let Some(c) = TSC_ERROR_RE.captures(line) {
+            let (line_no, column) = match (c.get(2), c.get(4)) {
+                (Some(l), _) => (l, c.get(3)),
+                (None, Some(l)) => (l, c.get(5)),
+                (None, None) => continue,
+            };
+            summary.errors.push(BuildError {
+                file: Some(c[1].trim().to_string()),
+                line: line_no.as_str().parse().ok(),
+                column: column.and_then(|m| m.as_str().parse().ok()),
+                code: Some(c[6].to_string()),
+                message: c[7].to_string(),
+            });
+        }
+    }
+    (!summary.errors.is_empty()).then(|| summary.finish())
+}
+
+/// Parse test/build output. Returns None if no known tool is recognized.
+pub fn parse_output(text: &str) -> Option<BuildSummary> {
+    let clean = ANSI_RE.replace_all(text, "");
+    let lines: Vec<&str> = clean.lines().map(|l| l.trim_end_matches('\r')).collect();
+
+    parse_cargo(&lines)
+        .or_else(|| parse_pytest(&lines))
+        .or_else(|| parse_jest(&lines))
+        .or_else(|| parse_tsc(&lines))
+}
+
+/// Store a parsed result on a session
+pub fn record_build_result(
+    session_id: &str,
+    worker_id: &str,
+    source_id: &str,
+    summary: BuildSummary,
+) -> BuildResult {
+    let result = BuildResult {
+        id: uuid::Uuid::new_v4().to_string(),
+        worker_id: worker_id.to_string(),
+        source_id: source_id.to_string(),
+        timestamp: now_ms(),
+        summary,
+    };
+    BUILD_RESULTS
+        .lock()
+        .entry(session_id.to_string())
+        .or_default()
+        .push(result.clone());
+    result
+}
+
+/// Get a session's build results, oldest first
+pub fn get_build_results(session_id: &str) -> Option<Vec<BuildResult>> {
+    BUILD_RESULTS.lock().get(session_id).cloned()
+}
+
+/// Drop a session's build results (on session cleanup)
+pub fn clear_build_results(session_id: &str) {
+    BUILD_RESULTS.lock().remove(session_id);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cargo_test_output() {
+        let output = "\
+running 3 tests
+test a::works ... ok
+test a::breaks ... FAILED
+test a::skipped ... ignored
+
+failures:
+
+test result: FAILED. 1 passed; 1 failed; 1 ignored; 0 measured; 0 filtered out
+";
+        let summary = parse_output(output).unwrap();
+        assert_eq!(summary.tool, BuildTool::Cargo);
+        assert!(!summary.success);
+        assert_eq!((summary.passed, summary.failed, summary.skipped), (1, 1, 1));
+        assert_eq!(summary.failing_tests, vec!["a::breaks".to_string()]);
+    }
+
+    #[test]
+    fn test_cargo_compile_error() {
+        let output = "\
+   Compiling app v0.1.0
+\x1b[1merror[E0308]\x1b[0m: mismatched types
+  --> src/main.rs:4:18
+   |
+error: could not compile `app` (bin \"app\") due to 1 previous error
+";
+        let summary = parse_output(output).unwrap();
+        assert_eq!(summary.tool, BuildTool::Cargo);
+        assert!(!summary.success);
+        assert_eq!(
+            summary.errors,
+            vec![BuildError {
+                file: Some("src/main.rs".to_string()),
+                line: Some(4),
+                column: Some(18),
+                code: Some("E0308".to_string()),
+                message: "mismatched types".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_jest_output() {
+        let output = "\
+FAIL src/sum.test.ts
+  ● math › adds numbers
+
+Test Suites: 1 failed, 1 passed, 2 total
+Tests:       1 failed, 1 skipped, 7 passed, 9 total
+";
+        let summary = parse_output(output).unwrap();
+        assert_eq!(summary.tool, BuildTool::Jest);
+        assert_eq!((summary.passed, summary.failed, summary.skipped), (7, 1, 1));
+        assert_eq!(summary.failing_tests, vec!["math › adds numbers".to_string()]);
+    }
+
+    #[test]
+    fn test_pytest_output() {
+        let output = "\
+tests/test_api.py ..F.
+FAILED tests/test_api.py::test_login - AssertionError: 401
+==================== 1 failed, 3 passed, 2 skipped in 0.42s ====================
+";
+        let summary = parse_output(output).unwrap();
+        assert_eq!(summary.tool, BuildTool::Pytest);
+        assert_eq!((summary.passed, summary.failed, summary.skipped), (3, 1, 2));
+        assert_eq!(
+            summary.failing_tests,
+            vec!["tests/test_api.py::test_login".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_tsc_output() {
+        let output = "\
+src/app.ts(10,5): error TS2322: Type 'string' is not assignable to type 'number'.
+src/lib/util.ts:3:1 - error TS2304: Cannot find name 'foo'.
+
+Found 2 errors.
+";
+        let summary = parse_output(output).unwrap();
+        assert_eq!(summary.tool, BuildTool::Tsc);
+        assert_eq!(summary.errors.len(), 2);
+        assert_eq!(summary.errors[0].file.as_deref(), Some("src/app.ts"));
+        assert_eq!(summary.errors[0].line, Some(10));
+        assert_eq!(summary.errors[1].file.as_deref(), Some("src/lib/util.ts"));
+        assert_eq!(summary.errors[1].column, Some(1));
+        assert_eq!(summary.errors[1].code.as_deref(), Some("TS2304"));
+    }
+
+    #[test]
+    fn test_unrecognized_output() {
+        assert_eq!(parse_output("hello world\nerror: something went wrong"), None);
+        assert_eq!(parse_output(""), None);
+    }
+}
@@ -1,10 +1,30 @@
+use crate::acp::session_store::{SessionFilter, SessionStore};
 use crate::claude::pricing::Model;
 use crate::claude::ClaudeClient;
+use crate::error::CommandError;
+use crate::orchestrator::archive::{
+    archive_live_session, purge_older_than, session_storage, ArchiveStore, ArchivedSession,
+    ArchivedSessionSummary, PurgeResult, SessionStorage,
+};
+use crate::orchestrator::build_results::{get_build_results, BuildResult};
 use crate::orchestrator::manager::{execute_worker, plan_subtasks};
+use crate::orchestrator::patch::{
+    apply_selections, reject_hunk, session_patch, FilePatch, HunkSelection, PatchApplyResult,
+    SessionPatch,
+};
 use crate::orchestrator::session::{OrchestratorSession, SessionStatus};
+use crate::orchestrator::session_list::{merge_sessions, SessionRecord};
+use crate::orchestrator::tool_calls::{
+    compute_metrics, get_tool_call_metrics, get_tool_calls, slow_threshold_ms, ToolCallFilter,
+    ToolCallMetrics, ToolCallRecord,
+};
 use crate::orchestrator::worker::{WorkerSession, WorkerStatus};
+use crate::stats::budget::require_budget;
+use crate::stats::session_costs;
+use crate::time::now_ms;
 use crate::AppState;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Arc;
 use tauri::{AppHandle, Emitter, State};
 use tokio::sync::mpsc;
@@ -32,7 +52,8 @@ pub async fn create_orchestrator_session(
     model: Option<String>,
     app_handle: AppHandle,
     state: State<'_, AppState>,
-) -> Result<SessionResponse, String> {
+) -> Result<SessionResponse, CommandError> {
+    require_budget()?;
     let model = model
         .and_then(|m| Model::from_string(&m))
         .unwrap_or(Model::Opus);
@@ -52,7 +73,7 @@ pub async fn create_orchestrator_session(
         }),
     );
 
-    let client = ClaudeClient::from_env().map_err(|e| e.to_string())?;
+    let client = ClaudeClient::from_env()?;
 
     let subtasks = plan_subtasks(&client, &prompt).await?;
 
@@ -105,7 +126,7 @@ pub async fn create_orchestrator_session(
 
     match session {
         Some(s) => Ok(SessionResponse { session: s }),
-        None => Err("Session not found after creation".to_string()),
+        None => Err(CommandError::session_not_found(&session_id)),
     }
 }
 
@@ -113,13 +134,13 @@ pub async fn create_orchestrator_session(
 pub fn get_orchestrator_session(
     session_id: String,
     state: State<'_, AppState>,
-) -> Result<SessionResponse, String> {
+) -> Result<SessionResponse, CommandError> {
     let mgr = state.orchestrator_manager.lock();
     match mgr.get_session(&session_id) {
         Some(session) => Ok(SessionResponse {
             session: session.clone(),
         }),
-        None => Err(format!("Session {} not found", session_id)),
+        None => Err(CommandError::session_not_found(&session_id)),
     }
 }
 
@@ -129,16 +150,41 @@ pub fn list_orchestrator_sessions(state: State<'_, AppState>) -> Vec<Orchestrato
     mgr.list_sessions().into_iter().cloned().collect()
 }
 
+/// Live orchestrator sessions, persisted ACP sessions and PRD sessions in
+/// one list, most recently updated first (archived sessions are left out)
+#[tauri::command]
+pub fn list_all_sessions(
+    state: State<'_, AppState>,
+) -> Result<Vec<SessionRecord>, CommandError> {
+    let live: Vec<OrchestratorSession> = state
+        .orchestrator_manager
+        .lock()
+        .list_sessions()
+        .into_iter()
+        .cloned()
+        .collect();
+    let persisted = SessionStore::new()?.list_sessions(&SessionFilter::default());
+    let prd = state.prd_manager.list_sessions();
+    let ledger_costs = session_costs().unwrap_or_else(|e| {
+        eprintln!("[Orchestrator] Session costs unavailable: {}", e);
+        HashMap::new()
+    });
+    Ok(merge_sessions(&live, &persisted, &prd, &ledger_costs))
+}
+
 #[tauri::command]
 pub fn cancel_worker(
     session_id: String,
     worker_id: String,
     state: State<'_, AppState>,
-) -> Result<(), String> {
+) -> Result<(), CommandError> {
     let mut mgr = state.orchestrator_manager.lock();
 
-    if !mgr.cancel_worker(&worker_id) {
-        return Err(format!("Worker {} not found or already completed", worker_id));
+    if !mgr.cancel_worker(&worker_id) && !mgr.cancel_queued_session(&worker_id) {
+        return Err(CommandError::not_found(format!(
+            "Worker {} not found or already completed",
+            worker_id
+        )));
     }
 
     mgr.update_worker_status(&session_id, &worker_id, WorkerStatus::Cancelled);
@@ -151,21 +197,23 @@ pub async fn retry_worker(
     worker_id: String,
     app_handle: AppHandle,
     state: State<'_, AppState>,
-) -> Result<WorkerResponse, String> {
+) -> Result<WorkerResponse, CommandError> {
     let (_, new_worker) = {
         let mut mgr = state.orchestrator_manager.lock();
         let session = mgr
             .get_session(&session_id)
-            .ok_or_else(|| format!("Session {} not found", session_id))?;
+            .ok_or_else(|| CommandError::session_not_found(&session_id))?;
 
         let old_worker = session
             .get_worker(&worker_id)
-            .ok_or_else(|| format!("Worker {} not found", worker_id))?
+            .ok_or_else(|| CommandError::not_found(format!("Worker {} not found", worker_id)))?
             .clone();
 
         if old_worker.status != WorkerStatus::Failed && old_worker.status != WorkerStatus::Cancelled
         {
-            return Err("Can only retry failed or cancelled workers".to_string());
+            return Err(CommandError::invalid_input(
+                "Can only retry failed or cancelled workers",
+            ));
         }
 
         let new_worker = WorkerSession::new(
@@ -179,7 +227,7 @@ pub async fn retry_worker(
         (old_worker, new_worker)
     };
 
-    let client = ClaudeClient::from_env().map_err(|e| e.to_string())?;
+    let client = ClaudeClient::from_env()?;
     let client = Arc::new(client);
 
     let (cancel_tx, cancel_rx) = mpsc::channel(1);
@@ -216,10 +264,139 @@ pub fn get_session_conflicts(
 }
 
 #[tauri::command]
-pub fn get_session_cost(session_id: String, state: State<'_, AppState>) -> Result<f64, String> {
+pub fn get_session_cost(
+    session_id: String,
+    state: State<'_, AppState>,
+) -> Result<f64, CommandError> {
     let mgr = state.orchestrator_manager.lock();
     match mgr.get_session(&session_id) {
         Some(session) => Ok(session.total_cost),
-        None => Err(format!("Session {} not found", session_id)),
+        None => Err(CommandError::session_not_found(&session_id)),
+    }
+}
+
+/// Get the tool calls run in a session (live log, or the persisted copy)
+#[tauri::command]
+pub fn get_session_tool_calls(
+    session_id: String,
+    filter: Option<ToolCallFilter>,
+) -> Result<Vec<ToolCallRecord>, CommandError> {
+    let filter = filter.unwrap_or_default();
+
+    if let Some(calls) = get_tool_calls(&session_id, &filter) {
+        return Ok(calls);
+    }
+
+    let store = SessionStore::new()?;
+    if !store.session_exists(&session_id) {
+        return Ok(Vec::new());
+    }
+    let persisted = store.load_session(&session_id)?;
+    Ok(persisted
+        .tool_calls
+        .into_iter()
+        .filter(|call| filter.matches(call))
+        .collect())
+}
+
+/// Get per-kind tool-call latency and concurrency for a session (live, or
+/// computed from the persisted log)
+#[tauri::command]
+pub fn get_session_tool_metrics(session_id: String) -> Result<ToolCallMetrics, CommandError> {
+    if let Some(metrics) = get_tool_call_metrics(&session_id) {
+        return Ok(metrics);
+    }
+
+    let store = SessionStore::new()?;
+    let records = if store.session_exists(&session_id) {
+        store.load_session(&session_id)?.tool_calls
+    } else {
+        Vec::new()
+    };
+    Ok(compute_metrics(
+        &records,
+        slow_threshold_ms(),
+        now_ms(),
+    ))
+}
+
+/// Get the test/build results detected in a session (live, or the persisted copy)
+#[tauri::command]
+pub fn get_session_build_results(session_id: String) -> Result<Vec<BuildResult>, CommandError> {
+    if let Some(results) = get_build_results(&session_id) {
+        return Ok(results);
+    }
+
+    let store = SessionStore::new()?;
+    if !store.session_exists(&session_id) {
+        return Ok(Vec::new());
     }
+    Ok(store.load_session(&session_id)?.build_results)
+}
+
+/// Get a unified patch of every file changed in a session
+#[tauri::command]
+pub fn get_session_patch(session_id: String) -> SessionPatch {
+    session_patch(&session_id)
+}
+
+/// Stage the accepted hunks of a session patch into the git index
+#[tauri::command]
+pub fn apply_session_patch(
+    session_id: String,
+    selections: Vec<HunkSelection>,
+) -> Result<PatchApplyResult, CommandError> {
+    Ok(apply_selections(&session_id, &selections)?)
+}
+
+/// Revert a single hunk of a session patch in the working tree
+#[tauri::command]
+pub fn reject_session_hunk(
+    session_id: String,
+    path: String,
+    hunk_index: usize,
+) -> Result<FilePatch, CommandError> {
+    Ok(reject_hunk(&session_id, &path, hunk_index)?)
+}
+
+/// Write a finished session to the archive and drop it from memory
+#[tauri::command]
+pub fn archive_session(
+    session_id: String,
+    state: State<'_, AppState>,
+) -> Result<ArchivedSessionSummary, CommandError> {
+    Ok(archive_live_session(&state, &ArchiveStore::new()?, &session_id)?)
+}
+
+#[tauri::command]
+pub fn list_archived_sessions() -> Result<Vec<ArchivedSessionSummary>, CommandError> {
+    Ok(ArchiveStore::new()?.list())
+}
+
+#[tauri::command]
+pub fn get_archived_session(session_id: String) -> Result<ArchivedSession, CommandError> {
+    Ok(ArchiveStore::new()?.load(&session_id)?)
+}
+
+/// Delete archived sessions last updated more than `older_than_hours` ago,
+/// and saved ACP sessions too when `include_saved` is set
+#[tauri::command]
+pub fn purge_sessions(
+    older_than_hours: u64,
+    include_saved: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<PurgeResult, CommandError> {
+    let max_age_secs = i64::try_from(older_than_hours.saturating_mul(3600)).unwrap_or(i64::MAX);
+    Ok(purge_older_than(
+        &state,
+        &ArchiveStore::new()?,
+        max_age_secs,
+        include_saved.unwrap_or(false),
+    )?)
+}
+
+/// Disk space used by saved and archived sessions
+#[tauri::command]
+pub fn get_session_storage() -> Result<SessionStorage, CommandError> {
+    Ok(session_storage()?)
 }
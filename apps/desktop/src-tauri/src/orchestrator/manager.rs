@@ -1,12 +1,19 @@
 use crate::claude::pricing::Model;
 use crate::claude::{ClaudeClient, Message};
+use crate::events::{emit, WorkerStatusChange};
+use crate::integrations::webhook::crossed_threshold;
+use crate::integrations::{dispatch, LifecycleEvent, WebhookEvent};
+use crate::notifications::{notify, NotificationEvent};
+use crate::orchestrator::admission::{Admission, AgentSlots, StartFn};
 use crate::orchestrator::session::{FileConflict, OrchestratorSession, SessionStatus};
 use crate::orchestrator::worker::{WorkerSession, WorkerStatus};
+use crate::settings::load_settings;
+use crate::telemetry;
 use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
-use tauri::{AppHandle, Emitter};
+use tauri::AppHandle;
 use tokio::sync::mpsc;
 use uuid::Uuid;
 
@@ -40,10 +47,44 @@ pub struct SubTask {
     pub model: String,
 }
 
+/// What's going on across sessions, for the tray
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct StatusSummary {
+    pub running_workers: usize,
+    /// Sessions with a running worker
+    pub active_sessions: usize,
+    /// Most recently finished prompts, newest first
+    pub recent_completions: Vec<RecentCompletion>,
+    /// Session with the latest worker activity
+    pub last_session_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct RecentCompletion {
+    pub session_id: String,
+    pub task: String,
+    pub completed_at: i64,
+}
+
+/// Report a session that just reached a final status to telemetry
+fn record_finish(session: &OrchestratorSession, before: &SessionStatus) {
+    let status = match session.status {
+        SessionStatus::Completed => "completed",
+        SessionStatus::Failed => "failed",
+        SessionStatus::Cancelled => "cancelled",
+        SessionStatus::Planning | SessionStatus::Running => return,
+    };
+    if &session.status != before {
+        telemetry::session_finished(status, session.created_at, session.workers.len());
+    }
+}
+
 #[derive(Debug)]
 pub struct OrchestratorManager {
     sessions: HashMap<String, OrchestratorSession>,
     active_workers: HashMap<String, tokio::sync::mpsc::Sender<()>>,
+    /// Running agent processes and sessions waiting for one to exit
+    agent_slots: AgentSlots,
 }
 
 impl OrchestratorManager {
@@ -51,12 +92,14 @@ impl OrchestratorManager {
         Self {
             sessions: HashMap::new(),
             active_workers: HashMap::new(),
+            agent_slots: AgentSlots::default(),
         }
     }
 
     pub fn create_session(&mut self, prompt: String, model: Model) -> OrchestratorSession {
         let session = OrchestratorSession::new(Uuid::new_v4().to_string(), prompt, model);
         self.sessions.insert(session.id.clone(), session.clone());
+        telemetry::session_started();
         session
     }
 
@@ -73,13 +116,20 @@ impl OrchestratorManager {
         self.sessions.get_mut(id)
     }
 
+    /// Evict a session (see `archive`)
+    pub fn remove_session(&mut self, id: &str) -> Option<OrchestratorSession> {
+        self.sessions.remove(id)
+    }
+
     pub fn list_sessions(&self) -> Vec<&OrchestratorSession> {
         self.sessions.values().collect()
     }
 
     pub fn update_session_status(&mut self, id: &str, status: SessionStatus) -> bool {
         if let Some(session) = self.sessions.get_mut(id) {
+            let before = session.status.clone();
             session.status = status;
+            record_finish(session, &before);
             return true;
         }
         false
@@ -100,7 +150,25 @@ impl OrchestratorManager {
         status: WorkerStatus,
     ) -> bool {
         if let Some(session) = self.sessions.get_mut(session_id) {
-            return session.update_worker_status(worker_id, status);
+            let before = session.status.clone();
+            let was_completed = before == SessionStatus::Completed;
+            let updated = session.update_worker_status(worker_id, status);
+            record_finish(session, &before);
+            if !was_completed && session.status == SessionStatus::Completed {
+                dispatch(
+                    WebhookEvent::new(
+                        LifecycleEvent::SessionCompleted,
+                        session_id,
+                        "Session completed",
+                        &session.prompt,
+                    )
+                    .with_data(serde_json::json!({
+                        "workers": session.workers.len(),
+                        "total_cost": session.total_cost,
+                    })),
+                );
+            }
+            return updated;
         }
         false
     }
@@ -126,7 +194,24 @@ impl OrchestratorManager {
         cost: f64,
     ) -> bool {
         if let Some(session) = self.sessions.get_mut(session_id) {
-            return session.update_worker_cost(worker_id, input_tokens, output_tokens, cost);
+            let previous = session.total_cost;
+            let updated = session.update_worker_cost(worker_id, input_tokens, output_tokens, cost);
+            let threshold = load_settings().integrations.cost_threshold_usd;
+            if crossed_threshold(previous, session.total_cost, threshold) {
+                dispatch(
+                    WebhookEvent::new(
+                        LifecycleEvent::CostThreshold,
+                        session_id,
+                        "Cost threshold reached",
+                        &format!("Session cost is now ${:.2}", session.total_cost),
+                    )
+                    .with_data(serde_json::json!({
+                        "total_cost": session.total_cost,
+                        "threshold": threshold,
+                    })),
+                );
+            }
+            return updated;
         }
         false
     }
@@ -147,6 +232,115 @@ impl OrchestratorManager {
         self.active_workers.remove(worker_id);
     }
 
+    /// Mark a worker failed. False if it already was: a failed prompt is
+    /// reported both by the worker loop and by the task waiting on it, and
+    /// only the first report should be acted on.
+    pub fn fail_worker(&mut self, session_id: &str, worker_id: &str, error: String) -> bool {
+        self.remove_worker_cancel(worker_id);
+        let Some(worker) = self
+            .sessions
+            .get_mut(session_id)
+            .and_then(|session| session.get_worker_mut(worker_id))
+        else {
+            return true;
+        };
+        if worker.status == WorkerStatus::Failed {
+            return false;
+        }
+        worker.mark_failed(error);
+        true
+    }
+
+    /// Cancel every in-flight prompt; returns how many were cancelled
+    pub fn cancel_all_workers(&mut self) -> usize {
+        let count = self.active_workers.len();
+        for (_, cancel_tx) in self.active_workers.drain() {
+            let _ = cancel_tx.try_send(());
+        }
+        count
+    }
+
+    pub fn has_free_agent_slot(&self, limit: usize) -> bool {
+        self.agent_slots.has_free_slot(limit)
+    }
+
+    /// Start a session's worker now or queue it until an agent slot frees
+    pub fn admit_session(
+        &mut self,
+        session_id: &str,
+        worker_id: &str,
+        limit: usize,
+        start: StartFn,
+    ) -> Admission {
+        self.agent_slots.admit(session_id, worker_id, limit, start)
+    }
+
+    /// Count a worker's agent process against the limit without queueing
+    pub fn occupy_agent_slot(&mut self, worker_id: &str) {
+        self.agent_slots.occupy(worker_id);
+    }
+
+    /// Free a worker's slot; returns queued sessions that should start now
+    pub fn release_agent_slot(&mut self, worker_id: &str, limit: usize) -> Vec<(String, StartFn)> {
+        self.agent_slots.release(worker_id, limit)
+    }
+
+    /// Agent processes alive
+    pub fn running_agents(&self) -> usize {
+        self.agent_slots.running()
+    }
+
+    /// Drop sessions waiting for an agent slot so none start (app shutdown)
+    pub fn clear_agent_queue(&mut self) -> Vec<String> {
+        self.agent_slots.clear_queue()
+    }
+
+    pub fn cancel_queued_session(&mut self, worker_id: &str) -> bool {
+        self.agent_slots.cancel_queued(worker_id)
+    }
+
+    /// Running workers, the last `recent` completed ones and the most
+    /// recently active session
+    pub fn status_summary(&self, recent: usize) -> StatusSummary {
+        let workers = || self.sessions.values().flat_map(|s| &s.workers);
+
+        let mut completed: Vec<&WorkerSession> = workers()
+            .filter(|w| w.status == WorkerStatus::Completed)
+            .collect();
+        completed.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+
+        StatusSummary {
+            running_workers: workers()
+                .filter(|w| w.status == WorkerStatus::Running)
+                .count(),
+            active_sessions: self
+                .sessions
+                .values()
+                .filter(|s| s.workers.iter().any(|w| w.status == WorkerStatus::Running))
+                .count(),
+            recent_completions: completed
+                .into_iter()
+                .take(recent)
+                .map(|w| RecentCompletion {
+                    session_id: w.session_id.clone(),
+                    task: w.task.clone(),
+                    completed_at: w.updated_at,
+                })
+                .collect(),
+            last_session_id: self
+                .sessions
+                .values()
+                .max_by_key(|s| {
+                    s.workers
+                        .iter()
+                        .map(|w| w.updated_at)
+                        .max()
+                        .unwrap_or(s.updated_at)
+                })
+                .map(|s| s.id.clone()),
+        }
+    }
+
     pub fn get_conflicts(&self, session_id: &str) -> Vec<FileConflict> {
         if let Some(session) = self.sessions.get(session_id) {
             return session.detect_conflicts();
@@ -207,14 +401,7 @@ pub async fn execute_worker(
         mgr.update_worker_status(&session_id, &worker_id, WorkerStatus::Running);
     }
 
-    let _ = app_handle.emit(
-        "worker-status-change",
-        serde_json::json!({
-            "session_id": session_id,
-            "worker_id": worker_id,
-            "status": "running"
-        }),
-    );
+    emit(&app_handle, &WorkerStatusChange::new(&session_id, &worker_id, WorkerStatus::Running));
 
     let messages = vec![Message::user(&task)];
     let system = Some(
@@ -230,17 +417,35 @@ pub async fn execute_worker(
                     mgr.update_worker_status(&session_id, &worker_id, WorkerStatus::Completed);
                     mgr.remove_worker_cancel(&worker_id);
 
-                    let _ = app_handle.emit(
-                        "worker-status-change",
-                        serde_json::json!({
-                            "session_id": session_id,
-                            "worker_id": worker_id,
-                            "status": "completed",
-                            "cost": cost
-                        }),
+                    emit(
+                        &app_handle,
+                        &WorkerStatusChange {
+                            cost: Some(cost),
+                            ..WorkerStatusChange::new(
+                                &session_id,
+                                &worker_id,
+                                WorkerStatus::Completed,
+                            )
+                        },
                     );
                 }
                 Err(e) => {
+                    notify(
+                        &app_handle,
+                        NotificationEvent::WorkerFailed,
+                        Some(&session_id),
+                        "Worker failed",
+                        &e.to_string(),
+                    );
+                    dispatch(
+                        WebhookEvent::new(
+                            LifecycleEvent::WorkerFailed,
+                            &session_id,
+                            "Worker failed",
+                            &e.to_string(),
+                        )
+                        .with_data(serde_json::json!({ "worker_id": worker_id })),
+                    );
                     let mut mgr = manager.lock();
                     if let Some(session) = mgr.get_session_mut(&session_id) {
                         if let Some(w) = session.get_worker_mut(&worker_id) {
@@ -249,14 +454,12 @@ pub async fn execute_worker(
                     }
                     mgr.remove_worker_cancel(&worker_id);
 
-                    let _ = app_handle.emit(
-                        "worker-status-change",
-                        serde_json::json!({
-                            "session_id": session_id,
-                            "worker_id": worker_id,
-                            "status": "failed",
-                            "error": e.to_string()
-                        }),
+                    emit(
+                        &app_handle,
+                        &WorkerStatusChange {
+                            error: Some(e.to_string()),
+                            ..WorkerStatusChange::new(&session_id, &worker_id, WorkerStatus::Failed)
+                        },
                     );
                 }
             }
@@ -266,14 +469,80 @@ pub async fn execute_worker(
             mgr.update_worker_status(&session_id, &worker_id, WorkerStatus::Cancelled);
             mgr.remove_worker_cancel(&worker_id);
 
-            let _ = app_handle.emit(
-                "worker-status-change",
-                serde_json::json!({
-                    "session_id": session_id,
-                    "worker_id": worker_id,
-                    "status": "cancelled"
-                }),
+            emit(
+                &app_handle,
+                &WorkerStatusChange::new(&session_id, &worker_id, WorkerStatus::Cancelled),
             );
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_summary() {
+        let mut mgr = OrchestratorManager::new();
+        assert_eq!(mgr.status_summary(3), StatusSummary::default());
+
+        for (session_id, statuses) in [
+            ("s1", vec![(WorkerStatus::Completed, 10), (WorkerStatus::Running, 30)]),
+            ("s2", vec![(WorkerStatus::Completed, 20), (WorkerStatus::Failed, 40)]),
+        ] {
+            let mut session =
+                OrchestratorSession::new(session_id.to_string(), "prompt".to_string(), Model::Opus);
+            for (i, (status, updated_at)) in statuses.into_iter().enumerate() {
+                let mut worker = WorkerSession::new(
+                    format!("{}-w{}", session_id, i),
+                    session_id.to_string(),
+                    format!("task {}", updated_at),
+                    Model::Opus,
+                );
+                worker.status = status;
+                worker.updated_at = updated_at;
+                session.workers.push(worker);
+            }
+            mgr.add_session(session);
+        }
+
+        let summary = mgr.status_summary(1);
+        assert_eq!(summary.running_workers, 1);
+        assert_eq!(summary.active_sessions, 1);
+        assert_eq!(
+            summary.recent_completions,
+            vec![RecentCompletion {
+                session_id: "s2".to_string(),
+                task: "task 20".to_string(),
+                completed_at: 20,
+            }]
+        );
+        assert_eq!(summary.last_session_id.as_deref(), Some("s2"));
+    }
+
+    #[test]
+    fn test_fail_worker_reports_once() {
+        let mut mgr = OrchestratorManager::new();
+        let mut session =
+            OrchestratorSession::new("s1".to_string(), "prompt".to_string(), Model::Opus);
+        session.workers.push(WorkerSession::new(
+            "w1".to_string(),
+            "s1".to_string(),
+            "task".to_string(),
+            Model::Opus,
+        ));
+        mgr.add_session(session);
+
+        // The worker loop and the prompt's waiter both report the failure;
+        // only the first one notifies and dispatches the webhook
+        assert!(mgr.fail_worker("s1", "w1", "boom".to_string()));
+        assert!(!mgr.fail_worker("s1", "w1", "boom (again)".to_string()));
+        let worker = &mgr.get_session("s1").unwrap().workers[0];
+        assert_eq!(worker.status, WorkerStatus::Failed);
+        assert_eq!(worker.error_message.as_deref(), Some("boom"));
+
+        // Running again, it can fail again
+        mgr.update_worker_status("s1", "w1", WorkerStatus::Running);
+        assert!(mgr.fail_worker("s1", "w1", "later".to_string()));
+    }
+}
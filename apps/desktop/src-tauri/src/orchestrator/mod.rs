@@ -1,6 +1,12 @@
+pub mod admission;
+pub mod archive;
+pub mod build_results;
 pub mod commands;
 pub mod manager;
+pub mod patch;
 pub mod session;
+pub mod session_list;
+pub mod tool_calls;
 pub mod worker;
 
 pub use manager::OrchestratorManager;
@@ -0,0 +1,797 @@
+//! Session patch sets
+//!
+//! Tracks every file an agent touches during a session (via `fs/write_text_file`
+//! or `ToolCallContent::Diff` payloads) and renders the combined change as a
+//! unified diff. The base for each file is the content captured right before
+//! the agent's first change (checkpoint) or, if that wasn't available, the
+//! file at git HEAD.
+//!
+//! Individual hunks can then be staged into the git index or reverted in the
+//! working tree, so the user can review an agent's work hunk by hunk. Staged
+//! hunks go on top of the file's index version, so edits the user hadn't
+//! staged before the session stay out of the index.
+
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use similar::{ChangeTag, DiffTag, TextDiff};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+/// Lines of context around each hunk
+const CONTEXT_LINES: usize = 3;
+
+/// Global registry of patch sets (session_id -> tracked files)
+static PATCH_SETS: Lazy<Mutex<HashMap<String, PatchSet>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Base content captured for a tracked file
+#[derive(Debug, Clone)]
+enum Checkpoint {
+    /// Content before the first change (`None` = file did not exist)
+    Captured(Option<String>),
+    /// Change was seen after the fact; fall back to git HEAD
+    Unknown,
+}
+
+#[derive(Debug, Default)]
+struct PatchSet {
+    cwd: Option<String>,
+    /// Absolute path -> base content
+    files: BTreeMap<String, Checkpoint>,
+}
+
+/// Where the base content of a file patch came from
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum BaseSource {
+    Checkpoint,
+    GitHead,
+    /// A workspace snapshot (see `agent::snapshot`)
+    Snapshot,
+    /// No base found; the file is treated as new
+    None,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum FileChangeKind {
+    Added,
+    Modified,
+    Deleted,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum LineTag {
+    Context,
+    Add,
+    Remove,
+}
+
+/// A single line of a hunk. `text` keeps its trailing newline (if any) so
+/// hunks can be re-applied byte-for-byte.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HunkLine {
+    pub tag: LineTag,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Hunk {
+    pub index: usize,
+    /// 0-based line offsets into the old/new file
+    pub old_start: usize,
+    pub old_lines: usize,
+    pub new_start: usize,
+    pub new_lines: usize,
+    pub lines: Vec<HunkLine>,
+}
+
+impl Hunk {
+    /// The `@@ -a,b +c,d @@` header line
+    pub fn header(&self) -> String {
+        format!(
+            "@@ -{} +{} @@",
+            range_label(self.old_start, self.old_lines),
+            range_label(self.new_start, self.new_lines)
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FilePatch {
+    /// Absolute path on disk
+    pub path: String,
+    /// Path shown in the diff (relative to the session cwd when possible)
+    pub display_path: String,
+    pub kind: FileChangeKind,
+    pub base_source: BaseSource,
+    pub additions: usize,
+    pub deletions: usize,
+    pub hunks: Vec<Hunk>,
+    pub unified: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionPatch {
+    pub session_id: String,
+    pub files: Vec<FilePatch>,
+    pub additions: usize,
+    pub deletions: usize,
+    /// All file patches concatenated into one reviewable diff
+    pub unified: String,
+}
+
+fn range_label(start: usize, len: usize) -> String {
+    // Unified diff ranges are 1-based; empty ranges point at the line before
+    let start = if len == 0 { start } else { start + 1 };
+    format!("{},{}", start, len)
+}
+
+/// Compute line hunks between two texts
+pub fn compute_hunks(old: &str, new: &str) -> Vec<Hunk> {
+    let diff = TextDiff::from_lines(old, new);
+    diff.grouped_ops(CONTEXT_LINES)
+        .iter()
+        .enumerate()
+        .filter_map(|(index, group)| {
+            let first = group.first()?;
+            let last = group.last()?;
+            let old_start = first.old_range().start;
+            let new_start = first.new_range().start;
+
+            let lines = group
+                .iter()
+                .flat_map(|op| diff.iter_changes(op))
+                .map(|change| HunkLine {
+                    tag: match change.tag() {
+                        ChangeTag::Equal => LineTag::Context,
+                        ChangeTag::Delete => LineTag::Remove,
+                        ChangeTag::Insert => LineTag::Add,
+                    },
+                    text: change.value().to_string(),
+                })
+                .collect();
+
+            Some(Hunk {
+                index,
+                old_start,
+                old_lines: last.old_range().end - old_start,
+                new_start,
+                new_lines: last.new_range().end - new_start,
+                lines,
+            })
+        })
+        .collect()
+}
+
+/// Render hunks for one file in unified diff format
+pub fn render_unified(display_path: &str, kind: FileChangeKind, hunks: &[Hunk]) -> String {
+    let old_label = match kind {
+        FileChangeKind::Added => "/dev/null".to_string(),
+        _ => format!("a/{}", display_path),
+    };
+    let new_label = match kind {
+        FileChangeKind::Deleted => "/dev/null".to_string(),
+        _ => format!("b/{}", display_path),
+    };
+
+    let mut out = format!(
+        "diff --git a/{0} b/{0}\n--- {1}\n+++ {2}\n",
+        display_path, old_label, new_label
+    );
+    for hunk in hunks {
+        out.push_str(&hunk.header());
+        out.push('\n');
+        for line in &hunk.lines {
+            out.push(match line.tag {
+                LineTag::Context => ' ',
+                LineTag::Add => '+',
+                LineTag::Remove => '-',
+            });
+            out.push_str(&line.text);
+            if !line.text.ends_with('\n') {
+                out.push_str("\n\\ No newline at end of file\n");
+            }
+        }
+    }
+    out
+}
+
+/// Build the patch for a single file (None if unchanged)
+pub fn file_patch(
+    path: &str,
+    display_path: &str,
+    base: Option<&str>,
+    base_source: BaseSource,
+    current: Option<&str>,
+) -> Option<FilePatch> {
+    let kind = match (base, current) {
+        (None, None) => return None,
+        (None, Some(_)) => FileChangeKind::Added,
+        (Some(_), None) => FileChangeKind::Deleted,
+        (Some(old), Some(new)) if old == new => return None,
+        (Some(_), Some(_)) => FileChangeKind::Modified,
+    };
+
+    let hunks = compute_hunks(base.unwrap_or(""), current.unwrap_or(""));
+    let count = |tag: LineTag| {
+        hunks
+            .iter()
+            .flat_map(|h| h.lines.iter())
+            .filter(|l| l.tag == tag)
+            .count()
+    };
+
+    Some(FilePatch {
+        path: path.to_string(),
+        display_path: display_path.to_string(),
+        kind,
+        base_source,
+        additions: count(LineTag::Add),
+        deletions: count(LineTag::Remove),
+        unified: render_unified(display_path, kind, &hunks),
+        hunks,
+    })
+}
+
+/// Path relative to the session cwd, if it lives under it
+pub fn display_path(path: &str, cwd: Option<&str>) -> String {
+    cwd.and_then(|cwd| Path::new(path).strip_prefix(cwd).ok())
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.trim_start_matches('/').to_string())
+}
+
+/// Read a file's content at git HEAD
+fn git_head_content(path: &str) -> Option<String> {
+    let path = Path::new(path);
+    let dir = path.parent()?;
+    let file_name = path.file_name()?.to_string_lossy();
+
+    let output = std::process::Command::new("git")
+        .current_dir(dir)
+        .args(["show", &format!("HEAD:./{}", file_name)])
+        .output()
+        .ok()?;
+
+    if output.status.success() {
+        String::from_utf8(output.stdout).ok()
+    } else {
+        None
+    }
+}
+
+/// Read a file's staged content (`None` when it isn't in the index)
+fn git_index_content(path: &str) -> Option<String> {
+    let path = Path::new(path);
+    let dir = path.parent()?;
+    let file_name = path.file_name()?.to_string_lossy();
+
+    let output = std::process::Command::new("git")
+        .current_dir(dir)
+        .args(["show", &format!(":./{}", file_name)])
+        .output()
+        .ok()?;
+
+    if output.status.success() {
+        String::from_utf8(output.stdout).ok()
+    } else {
+        None
+    }
+}
+
+fn read_current(path: &str) -> Option<String> {
+    std::fs::read_to_string(path).ok()
+}
+
+/// Capture a file's content before the agent writes it (first write only)
+pub fn track_before_write(session_id: &str, cwd: Option<String>, path: &str) {
+    let mut sets = PATCH_SETS.lock();
+    let set = sets.entry(session_id.to_string()).or_default();
+    if set.cwd.is_none() {
+        set.cwd = cwd;
+    }
+    set.files
+        .entry(path.to_string())
+        .or_insert_with(|| Checkpoint::Captured(read_current(path)));
+}
+
+/// Track a file from a tool-call diff. `applied` is true once the tool call
+/// has completed, in which case the file on disk no longer holds the base.
+pub fn track_diff(
+    session_id: &str,
+    cwd: Option<String>,
+    path: &str,
+    old_text: Option<&str>,
+    applied: bool,
+) {
+    let mut sets = PATCH_SETS.lock();
+    let set = sets.entry(session_id.to_string()).or_default();
+    if set.cwd.is_none() {
+        set.cwd = cwd;
+    }
+    set.files.entry(path.to_string()).or_insert_with(|| {
+        if !applied {
+            Checkpoint::Captured(read_current(path))
+        } else if old_text.is_none() {
+            // Diff without old text creates a new file
+            Checkpoint::Captured(None)
+        } else {
+            Checkpoint::Unknown
+        }
+    });
+}
+
+fn resolve_base(path: &str, checkpoint: &Checkpoint) -> (Option<String>, BaseSource) {
+    match checkpoint {
+        Checkpoint::Captured(content) => (content.clone(), BaseSource::Checkpoint),
+        Checkpoint::Unknown => match git_head_content(path) {
+            Some(content) => (Some(content), BaseSource::GitHead),
+            None => (None, BaseSource::None),
+        },
+    }
+}
+
+/// Compute the unified patch across every file touched in a session
+pub fn session_patch(session_id: &str) -> SessionPatch {
+    let (cwd, files) = {
+        let sets = PATCH_SETS.lock();
+        match sets.get(session_id) {
+            Some(set) => (set.cwd.clone(), set.files.clone()),
+            None => (None, BTreeMap::new()),
+        }
+    };
+
+    let file_patches: Vec<FilePatch> = files
+        .iter()
+        .filter_map(|(path, checkpoint)| {
+            let (base, source) = resolve_base(path, checkpoint);
+            let current = read_current(path);
+            file_patch(
+                path,
+                &display_path(path, cwd.as_deref()),
+                base.as_deref(),
+                source,
+                current.as_deref(),
+            )
+        })
+        .collect();
+
+    SessionPatch {
+        session_id: session_id.to_string(),
+        additions: file_patches.iter().map(|f| f.additions).sum(),
+        deletions: file_patches.iter().map(|f| f.deletions).sum(),
+        unified: file_patches.iter().map(|f| f.unified.as_str()).collect(),
+        files: file_patches,
+    }
+}
+
+// ============================================================================
+// Hunk selection
+// ============================================================================
+
+/// Hunks the user accepted for one file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HunkSelection {
+    /// Absolute path (as in `FilePatch::path`)
+    pub path: String,
+    /// Indices into `FilePatch::hunks`
+    pub hunks: Vec<usize>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PatchApplyResult {
+    pub staged_files: Vec<String>,
+    pub staged_hunks: usize,
+}
+
+/// Apply a subset of hunks to the base text. Fails if the hunks no longer
+/// line up with the base (e.g. the patch is stale).
+pub fn apply_hunks(base: &str, hunks: &[&Hunk]) -> Result<String, String> {
+    let base_lines: Vec<&str> = base.split_inclusive('\n').collect();
+    let mut sorted: Vec<&Hunk> = hunks.to_vec();
+    sorted.sort_by_key(|h| h.old_start);
+
+    let mut out = String::new();
+    let mut pos = 0;
+    for hunk in sorted {
+        if hunk.old_start < pos || hunk.old_start + hunk.old_lines > base_lines.len() {
+            return Err(format!("Hunk {} does not fit the base file", hunk.index));
+        }
+        for line in &base_lines[pos..hunk.old_start] {
+            out.push_str(line);
+        }
+
+        let mut old_pos = hunk.old_start;
+        for line in &hunk.lines {
+            match line.tag {
+                LineTag::Context | LineTag::Remove => {
+                    if base_lines.get(old_pos) != Some(&line.text.as_str()) {
+                        return Err(format!(
+                            "Hunk {} is out of date (line {} changed)",
+                            hunk.index,
+                            old_pos + 1
+                        ));
+                    }
+                    if line.tag == LineTag::Context {
+                        out.push_str(&line.text);
+                    }
+                    old_pos += 1;
+                }
+                LineTag::Add => out.push_str(&line.text),
+            }
+        }
+        pos = hunk.old_start + hunk.old_lines;
+    }
+    for line in &base_lines[pos..] {
+        out.push_str(line);
+    }
+    Ok(out)
+}
+
+/// Move hunks computed against `base` onto `target`, which differs from it
+/// elsewhere. Fails for a hunk whose lines `target` doesn't share with `base`.
+fn rebase_hunks(base: &str, target: &str, hunks: &[&Hunk]) -> Result<Vec<Hunk>, String> {
+    if base == target {
+        return Ok(hunks.iter().map(|hunk| (*hunk).clone()).collect());
+    }
+    let diff = TextDiff::from_lines(base, target);
+    let unchanged: Vec<_> = diff
+        .ops()
+        .iter()
+        .filter(|op| op.tag() == DiffTag::Equal)
+        .map(|op| (op.old_range(), op.new_range().start))
+        .collect();
+    hunks
+        .iter()
+        .map(|hunk| {
+            let end = hunk.old_start + hunk.old_lines;
+            unchanged
+                .iter()
+                .find(|(old, _)| old.start <= hunk.old_start && end <= old.end)
+                .map(|(old, new_start)| Hunk {
+                    old_start: new_start + hunk.old_start - old.start,
+                    ..(*hunk).clone()
+                })
+                .ok_or_else(|| {
+                    format!(
+                        "Hunk {} overlaps changes that aren't staged; stage it by hand",
+                        hunk.index
+                    )
+                })
+        })
+        .collect()
+}
+
+/// Base/current content and computed patch for one tracked file
+fn tracked_file(session_id: &str, path: &str) -> Result<(Option<String>, FilePatch), String> {
+    let (cwd, checkpoint) = {
+        let sets = PATCH_SETS.lock();
+        let set = sets
+            .get(session_id)
+            .ok_or_else(|| format!("No changes tracked for session {}", session_id))?;
+        let checkpoint = set
+            .files
+            .get(path)
+            .cloned()
+            .ok_or_else(|| format!("File {} is not part of the session patch", path))?;
+        (set.cwd.clone(), checkpoint)
+    };
+
+    let (base, source) = resolve_base(path, &checkpoint);
+    let current = read_current(path);
+    let patch = file_patch(
+        path,
+        &display_path(path, cwd.as_deref()),
+        base.as_deref(),
+        source,
+        current.as_deref(),
+    )
+    .ok_or_else(|| format!("File {} has no changes", path))?;
+    Ok((base, patch))
+}
+
+fn select_hunks<'a>(patch: &'a FilePatch, indices: &[usize]) -> Result<Vec<&'a Hunk>, String> {
+    indices
+        .iter()
+        .map(|i| {
+            patch
+                .hunks
+                .get(*i)
+                .ok_or_else(|| format!("Hunk {} not found in {}", i, patch.display_path))
+        })
+        .collect()
+}
+
+fn run_git(dir: &Path, args: &[&str], stdin: Option<&str>) -> Result<String, String> {
+    use std::io::Write;
+
+    let mut child = std::process::Command::new("git")
+        .current_dir(dir)
+        .args(args)
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run git: {}", e))?;
+
+    if let Some(mut pipe) = child.stdin.take() {
+        if let Some(input) = stdin {
+            pipe.write_all(input.as_bytes())
+                .map_err(|e| format!("Failed to write to git: {}", e))?;
+        }
+    }
+
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("Failed to run git: {}", e))?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        Err(format!(
+            "git {} failed: {}",
+            args.first().unwrap_or(&""),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// Write `content` into the git index for `path` without touching the working tree
+fn stage_content(path: &str, content: Option<&str>) -> Result<(), String> {
+    let file = Path::new(path);
+    let dir = file
+        .parent()
+        .ok_or_else(|| format!("Invalid path: {}", path))?;
+    let root = run_git(dir, &["rev-parse", "--show-toplevel"], None)?;
+    let root = Path::new(&root).canonicalize().map_err(|e| e.to_string())?;
+    let dir = dir.canonicalize().map_err(|e| e.to_string())?;
+    let file_name = file
+        .file_name()
+        .ok_or_else(|| format!("Invalid path: {}", path))?;
+    let rel_path = dir
+        .join(file_name)
+        .strip_prefix(&root)
+        .map_err(|_| format!("{} is outside the git repository", path))?
+        .to_string_lossy()
+        .to_string();
+
+    match content {
+        None => {
+            run_git(&root, &["rm", "--cached", "--quiet", "--", &rel_path], None)?;
+        }
+        Some(content) => {
+            let sha = run_git(&root, &["hash-object", "-w", "--stdin"], Some(content))?;
+            let staged = run_git(&root, &["ls-files", "--stage", "--", &rel_path], None)?;
+            let mode = staged
+                .split_whitespace()
+                .next()
+                .unwrap_or("100644")
+                .to_string();
+            let cacheinfo = format!("{},{},{}", mode, sha, rel_path);
+            run_git(&root, &["update-index", "--add", "--cacheinfo", &cacheinfo], None)?;
+        }
+    }
+    Ok(())
+}
+
+/// Stage only the accepted hunks of each selected file into the git index,
+/// on top of what's already staged for it
+pub fn apply_selections(
+    session_id: &str,
+    selections: &[HunkSelection],
+) -> Result<PatchApplyResult, String> {
+    let mut result = PatchApplyResult::default();
+
+    for selection in selections {
+        if selection.hunks.is_empty() {
+            continue;
+        }
+        let (base, patch) = tracked_file(session_id, &selection.path)?;
+        let hunks = select_hunks(&patch, &selection.hunks)?;
+
+        let staged = if patch.kind == FileChangeKind::Deleted && hunks.len() == patch.hunks.len() {
+            None
+        } else {
+            let base = base.as_deref().unwrap_or("");
+            match git_index_content(&selection.path) {
+                Some(index) => {
+                    let rebased = rebase_hunks(base, &index, &hunks)?;
+                    Some(apply_hunks(&index, &rebased.iter().collect::<Vec<_>>())?)
+                }
+                // Not in the index yet: the whole file gets added
+                None => Some(apply_hunks(base, &hunks)?),
+            }
+        };
+        stage_content(&selection.path, staged.as_deref())?;
+
+        result.staged_hunks += hunks.len();
+        result.staged_files.push(patch.display_path);
+    }
+
+    Ok(result)
+}
+
+/// Revert one hunk in the working tree, keeping the file's other changes
+pub fn reject_hunk(session_id: &str, path: &str, hunk_index: usize) -> Result<FilePatch, String> {
+    let (base, patch) = tracked_file(session_id, path)?;
+    select_hunks(&patch, &[hunk_index])?;
+
+    let remaining: Vec<&Hunk> = patch
+        .hunks
+        .iter()
+        .filter(|h| h.index != hunk_index)
+        .collect();
+
+    if patch.kind == FileChangeKind::Added && remaining.is_empty() {
+        // Rejecting the whole new file removes it
+        std::fs::remove_file(path).map_err(|e| format!("Failed to remove {}: {}", path, e))?;
+    } else {
+        let content = apply_hunks(base.as_deref().unwrap_or(""), &remaining)?;
+        std::fs::write(path, content).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    }
+
+    eprintln!(
+        "[Patch] Rejected hunk {} of {} for session {}",
+        hunk_index, patch.display_path, session_id
+    );
+    Ok(patch)
+}
+
+/// Drop a session's patch set (on session cleanup)
+pub fn clear_patch_set(session_id: &str) {
+    PATCH_SETS.lock().remove(session_id);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compute_hunks_single_change() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\n";
+        let new = "a\nb\nc\nd\nE\nf\ng\nh\n";
+        let hunks = compute_hunks(old, new);
+        assert_eq!(hunks.len(), 1);
+        let hunk = &hunks[0];
+        assert_eq!(hunk.old_start, 1);
+        assert_eq!(hunk.old_lines, 7);
+        assert_eq!(hunk.new_lines, 7);
+        assert_eq!(hunk.header(), "@@ -2,7 +2,7 @@");
+        assert!(hunk
+            .lines
+            .iter()
+            .any(|l| l.tag == LineTag::Remove && l.text == "e\n"));
+        assert!(hunk
+            .lines
+            .iter()
+            .any(|l| l.tag == LineTag::Add && l.text == "E\n"));
+    }
+
+    #[test]
+    fn test_file_patch_kinds() {
+        assert!(file_patch("/p/a", "a", Some("x\n"), BaseSource::Checkpoint, Some("x\n")).is_none());
+
+        let added = file_patch("/p/a", "a", None, BaseSource::Checkpoint, Some("x\n")).unwrap();
+        assert_eq!(added.kind, FileChangeKind::Added);
+        assert_eq!(added.additions, 1);
+        assert!(added.unified.contains("--- /dev/null"));
+        assert!(added.unified.contains("@@ -0,0 +1,1 @@"));
+
+        let deleted = file_patch("/p/a", "a", Some("x\n"), BaseSource::GitHead, None).unwrap();
+        assert_eq!(deleted.kind, FileChangeKind::Deleted);
+        assert_eq!(deleted.deletions, 1);
+        assert!(deleted.unified.contains("+++ /dev/null"));
+    }
+
+    #[test]
+    fn test_render_missing_newline() {
+        let patch = file_patch("/p/a", "a", Some("x"), BaseSource::Checkpoint, Some("y")).unwrap();
+        assert!(patch.unified.contains("-x\n\\ No newline at end of file\n"));
+        assert!(patch.unified.contains("+y\n\\ No newline at end of file\n"));
+    }
+
+    #[test]
+    fn test_apply_hunks_subset() {
+        let base = "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n11\n12\n";
+        let new = "one\n2\n3\n4\n5\n6\n7\n8\n9\n10\n11\ntwelve\n";
+        let hunks = compute_hunks(base, new);
+        assert_eq!(hunks.len(), 2);
+
+        let all: Vec<&Hunk> = hunks.iter().collect();
+        assert_eq!(apply_hunks(base, &all).unwrap(), new);
+        assert_eq!(apply_hunks(base, &[]).unwrap(), base);
+        assert_eq!(
+            apply_hunks(base, &[&hunks[1]]).unwrap(),
+            "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n11\ntwelve\n"
+        );
+    }
+
+    #[test]
+    fn test_apply_hunks_detects_stale_base() {
+        let hunks = compute_hunks("a\nb\n", "a\nc\n");
+        let err = apply_hunks("x\ny\n", &[&hunks[0]]).unwrap_err();
+        assert!(err.contains("out of date"));
+    }
+
+    #[test]
+    fn test_reject_hunk_reverts_only_that_hunk() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let file = dir.path().join("lib.rs");
+        let base = "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n11\n12\n";
+        std::fs::write(&file, base).unwrap();
+        let path = file.to_string_lossy().to_string();
+
+        let session_id = format!("reject-test-{}", uuid::Uuid::new_v4());
+        track_before_write(&session_id, None, &path);
+        std::fs::write(&file, "one\n2\n3\n4\n5\n6\n7\n8\n9\n10\n11\ntwelve\n").unwrap();
+
+        reject_hunk(&session_id, &path, 0).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&file).unwrap(),
+            "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n11\ntwelve\n"
+        );
+
+        clear_patch_set(&session_id);
+    }
+
+    #[test]
+    fn test_apply_selections_keeps_unstaged_edits_out() {
+        let dir = tempfile::TempDir::new().unwrap();
+        if run_git(dir.path(), &["init", "-q"], None).is_err() {
+            return;
+        }
+        let file = dir.path().join("lib.rs");
+        std::fs::write(&file, "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n11\n12\n").unwrap();
+        run_git(dir.path(), &["add", "lib.rs"], None).unwrap();
+        let path = file.to_string_lossy().to_string();
+
+        // The user's own edit, not staged before the session
+        std::fs::write(&file, "1\n2\n3\n4\n5\nsix\n7\n8\n9\n10\n11\n12\n").unwrap();
+        let session_id = format!("stage-test-{}", uuid::Uuid::new_v4());
+        track_before_write(&session_id, None, &path);
+        std::fs::write(&file, "one\n2\n3\n4\n5\nsix\n7\n8\n9\n10\n11\ntwelve\n").unwrap();
+
+        let selection = HunkSelection {
+            path: path.clone(),
+            hunks: vec![1],
+        };
+        let result = apply_selections(&session_id, &[selection]).unwrap();
+        assert_eq!(result.staged_hunks, 1);
+        assert_eq!(
+            git_index_content(&path).unwrap(),
+            "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n11\ntwelve\n"
+        );
+
+        clear_patch_set(&session_id);
+    }
+
+    #[test]
+    fn test_rebase_hunks_refuses_overlap() {
+        let base = "1\n2\n3\n";
+        let hunks = compute_hunks(base, "1\ntwo\n3\n");
+        let err = rebase_hunks(base, "1\nTWO\n3\n", &[&hunks[0]]).unwrap_err();
+        assert!(err.contains("aren't staged"));
+    }
+
+    #[test]
+    fn test_session_patch_uses_checkpoint() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let file = dir.path().join("main.rs");
+        std::fs::write(&file, "fn main() {}\n").unwrap();
+        let path = file.to_string_lossy().to_string();
+        let cwd = dir.path().to_string_lossy().to_string();
+
+        let session_id = format!("patch-test-{}", uuid::Uuid::new_v4());
+        track_before_write(&session_id, Some(cwd), &path);
+        std::fs::write(&file, "fn main() {\n    println!(\"hi\");\n}\n").unwrap();
+
+        let patch = session_patch(&session_id);
+        assert_eq!(patch.files.len(), 1);
+        assert_eq!(patch.files[0].display_path, "main.rs");
+        assert_eq!(patch.files[0].base_source, BaseSource::Checkpoint);
+        assert!(patch.unified.contains("+    println!(\"hi\");"));
+
+        clear_patch_set(&session_id);
+    }
+}
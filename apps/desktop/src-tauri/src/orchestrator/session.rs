@@ -1,5 +1,6 @@
 use crate::claude::pricing::Model;
 use crate::orchestrator::worker::{WorkerSession, WorkerStatus};
+use crate::time::{normalize_ms, now_ms};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -22,14 +23,16 @@ pub struct OrchestratorSession {
     pub total_input_tokens: u64,
     pub total_output_tokens: u64,
     pub total_cost: f64,
+    /// Unix timestamp (milliseconds)
     pub created_at: i64,
+    /// Unix timestamp (milliseconds)
     pub updated_at: i64,
     pub plan: Option<String>,
 }
 
 impl OrchestratorSession {
     pub fn new(id: String, prompt: String, model: Model) -> Self {
-        let now = chrono_timestamp();
+        let now = now_ms();
         Self {
             id,
             prompt,
@@ -45,16 +48,26 @@ impl OrchestratorSession {
         }
     }
 
+    /// Convert timestamps saved in seconds (older archives) to milliseconds
+    pub fn normalize_timestamps(&mut self) {
+        self.created_at = normalize_ms(self.created_at);
+        self.updated_at = normalize_ms(self.updated_at);
+        for worker in &mut self.workers {
+            worker.created_at = normalize_ms(worker.created_at);
+            worker.updated_at = normalize_ms(worker.updated_at);
+        }
+    }
+
     pub fn add_worker(&mut self, worker: WorkerSession) {
         self.workers.push(worker);
-        self.updated_at = chrono_timestamp();
+        self.updated_at = now_ms();
     }
 
     pub fn update_worker_status(&mut self, worker_id: &str, status: WorkerStatus) -> bool {
         if let Some(worker) = self.workers.iter_mut().find(|w| w.id == worker_id) {
             worker.status = status;
-            worker.updated_at = chrono_timestamp();
-            self.updated_at = chrono_timestamp();
+            worker.updated_at = now_ms();
+            self.updated_at = now_ms();
             self.recalculate_status();
             return true;
         }
@@ -64,8 +77,8 @@ impl OrchestratorSession {
     pub fn update_worker_output(&mut self, worker_id: &str, output: &str) -> bool {
         if let Some(worker) = self.workers.iter_mut().find(|w| w.id == worker_id) {
             worker.output_buffer.push_str(output);
-            worker.updated_at = chrono_timestamp();
-            self.updated_at = chrono_timestamp();
+            worker.updated_at = now_ms();
+            self.updated_at = now_ms();
             return true;
         }
         false
@@ -82,9 +95,9 @@ impl OrchestratorSession {
             worker.input_tokens = input_tokens;
             worker.output_tokens = output_tokens;
             worker.cost_usd = cost;
-            worker.updated_at = chrono_timestamp();
+            worker.updated_at = now_ms();
             self.recalculate_totals();
-            self.updated_at = chrono_timestamp();
+            self.updated_at = now_ms();
             return true;
         }
         false
@@ -94,8 +107,8 @@ impl OrchestratorSession {
         if let Some(worker) = self.workers.iter_mut().find(|w| w.id == worker_id) {
             if !worker.files_touched.contains(&file_path) {
                 worker.files_touched.push(file_path);
-                worker.updated_at = chrono_timestamp();
-                self.updated_at = chrono_timestamp();
+                worker.updated_at = now_ms();
+                self.updated_at = now_ms();
             }
             return true;
         }
@@ -172,10 +185,3 @@ pub struct FileConflict {
     pub file_path: String,
     pub worker_ids: Vec<String>,
 }
-
-fn chrono_timestamp() -> i64 {
-    std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap()
-        .as_secs() as i64
-}
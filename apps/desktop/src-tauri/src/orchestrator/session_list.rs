@@ -0,0 +1,248 @@
+//! Unified session feed
+//!
+//! Live orchestrator sessions, persisted ACP sessions and PRD sessions are
+//! tracked separately. This merges them into one list of `SessionRecord`s
+//! with a shared status model for the home screen. A live ACP session is
+//! also persisted; the two are merged into one record, with status and cost
+//! taken from the live session.
+
+use crate::acp::session_store::PersistedSessionSummary;
+use crate::orchestrator::session::{OrchestratorSession, SessionStatus};
+use crate::orchestrator::worker::WorkerStatus;
+use crate::prd::types::{PrdSessionStatus, PrdSessionSummary};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Characters of a prompt used as a title
+const MAX_TITLE_CHARS: usize = 80;
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionKind {
+    /// Planned multi-worker session driven by the Claude API
+    Orchestrator,
+    /// Chat with an ACP agent (live, persisted, or both)
+    Acp,
+    Prd,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionRecordStatus {
+    /// An agent is working
+    Running,
+    /// Waiting for the next prompt
+    Idle,
+    Paused,
+    /// Finished, or saved and not currently loaded
+    Completed,
+    Failed,
+    Cancelled,
+    /// Saved, but its file can't be read (e.g. encrypted and the key is gone)
+    Unreadable,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct SessionRecord {
+    pub kind: SessionKind,
+    pub id: String,
+    pub title: String,
+    pub status: SessionRecordStatus,
+    /// Cost in USD so far
+    pub cost: f64,
+    /// Unix timestamp (milliseconds)
+    pub updated_at: i64,
+}
+
+fn title_from_prompt(prompt: &str) -> String {
+    let line = prompt.lines().find(|l| !l.trim().is_empty()).unwrap_or("");
+    let mut title: String = line.trim().chars().take(MAX_TITLE_CHARS).collect();
+    if line.trim().chars().count() > MAX_TITLE_CHARS {
+        title.push('…');
+    }
+    title
+}
+
+fn live_status(session: &OrchestratorSession) -> SessionRecordStatus {
+    let any_worker =
+        |statuses: &[WorkerStatus]| session.workers.iter().any(|w| statuses.contains(&w.status));
+    match session.status {
+        SessionStatus::Failed => SessionRecordStatus::Failed,
+        SessionStatus::Cancelled => SessionRecordStatus::Cancelled,
+        SessionStatus::Planning => SessionRecordStatus::Running,
+        _ if any_worker(&[WorkerStatus::Running, WorkerStatus::Pending]) => {
+            SessionRecordStatus::Running
+        }
+        // Persistent ACP workers stay around for follow-up prompts
+        _ if any_worker(&[WorkerStatus::Idle, WorkerStatus::Hibernated]) => {
+            SessionRecordStatus::Idle
+        }
+        SessionStatus::Running => SessionRecordStatus::Running,
+        SessionStatus::Completed => SessionRecordStatus::Completed,
+    }
+}
+
+fn prd_status(status: &PrdSessionStatus) -> SessionRecordStatus {
+    match status {
+        PrdSessionStatus::Idle => SessionRecordStatus::Idle,
+        PrdSessionStatus::Validating | PrdSessionStatus::Running => SessionRecordStatus::Running,
+        PrdSessionStatus::Paused => SessionRecordStatus::Paused,
+        PrdSessionStatus::Completed => SessionRecordStatus::Completed,
+        PrdSessionStatus::Failed => SessionRecordStatus::Failed,
+    }
+}
+
+/// Merge the three session sources, most recently updated first.
+/// `ledger_costs` are per-session costs from the usage ledger, used for
+/// persisted sessions that aren't loaded.
+pub fn merge_sessions(
+    live: &[OrchestratorSession],
+    persisted: &[PersistedSessionSummary],
+    prd: &[PrdSessionSummary],
+    ledger_costs: &HashMap<String, f64>,
+) -> Vec<SessionRecord> {
+    let persisted_by_id: HashMap<&str, &PersistedSessionSummary> =
+        persisted.iter().map(|s| (s.id.as_str(), s)).collect();
+
+    let mut records: Vec<SessionRecord> = live
+        .iter()
+        .map(|session| {
+            let saved = persisted_by_id.get(session.id.as_str());
+            SessionRecord {
+                kind: if saved.is_some() {
+                    SessionKind::Acp
+                } else {
+                    SessionKind::Orchestrator
+                },
+                id: session.id.clone(),
+                title: saved
+                    .and_then(|s| s.metadata.title.clone())
+                    .unwrap_or_else(|| title_from_prompt(&session.prompt)),
+                status: live_status(session),
+                cost: session.total_cost,
+                updated_at: session
+                    .updated_at
+                    .max(saved.map(|s| s.updated_at).unwrap_or(0)),
+            }
+        })
+        .collect();
+
+    records.extend(
+        persisted
+            .iter()
+            .filter(|s| !live.iter().any(|l| l.id == s.id))
+            .map(|s| SessionRecord {
+                kind: SessionKind::Acp,
+                id: s.id.clone(),
+                title: s
+                    .metadata
+                    .title
+                    .clone()
+                    .unwrap_or_else(|| title_from_prompt(&s.initial_prompt)),
+                status: if s.error.is_some() {
+                    SessionRecordStatus::Unreadable
+                } else {
+                    SessionRecordStatus::Completed
+                },
+                cost: ledger_costs.get(&s.id).copied().unwrap_or(0.0),
+                updated_at: s.updated_at,
+            }),
+    );
+
+    records.extend(prd.iter().map(|s| SessionRecord {
+        kind: SessionKind::Prd,
+        id: s.id.clone(),
+        title: s.title.clone(),
+        status: prd_status(&s.status),
+        cost: s.total_cost,
+        updated_at: s.completed_at.or(s.started_at).unwrap_or(0),
+    }));
+
+    records.sort_by_key(|r| std::cmp::Reverse(r.updated_at));
+    records
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acp::session_store::SessionMetadata;
+    use crate::claude::pricing::Model;
+
+    fn persisted(id: &str, title: Option<&str>, updated_at: i64) -> PersistedSessionSummary {
+        PersistedSessionSummary {
+            id: id.to_string(),
+            acp_session_id: String::new(),
+            cwd: "/tmp".to_string(),
+            agent_id: "claude".to_string(),
+            created_at: 0,
+            updated_at,
+            message_count: 2,
+            initial_prompt: "Fix the login bug\nDetails...".to_string(),
+            parent_session_id: None,
+            metadata: SessionMetadata {
+                title: title.map(str::to_string),
+                ..Default::default()
+            },
+            error: None,
+            locked: false,
+        }
+    }
+
+    #[test]
+    fn test_merge_sessions() {
+        let mut live =
+            OrchestratorSession::new("a".to_string(), "Live prompt".to_string(), Model::Sonnet);
+        live.status = SessionStatus::Completed;
+        live.total_cost = 0.5;
+        live.updated_at = 100;
+
+        let prd = PrdSessionSummary {
+            id: "p".to_string(),
+            title: "Billing PRD".to_string(),
+            status: PrdSessionStatus::Paused,
+            stories_total: 3,
+            stories_completed: 1,
+            active_workers: 0,
+            total_cost: 2.0,
+            started_at: Some(50),
+            completed_at: None,
+        };
+        let costs = HashMap::from([("b".to_string(), 0.25)]);
+
+        let records = merge_sessions(
+            &[live],
+            &[persisted("a", Some("Named"), 90), persisted("b", None, 200)],
+            &[prd],
+            &costs,
+        );
+        assert_eq!(
+            records,
+            vec![
+                SessionRecord {
+                    kind: SessionKind::Acp,
+                    id: "b".to_string(),
+                    title: "Fix the login bug".to_string(),
+                    status: SessionRecordStatus::Completed,
+                    cost: 0.25,
+                    updated_at: 200,
+                },
+                SessionRecord {
+                    kind: SessionKind::Acp,
+                    id: "a".to_string(),
+                    title: "Named".to_string(),
+                    status: SessionRecordStatus::Completed,
+                    cost: 0.5,
+                    updated_at: 100,
+                },
+                SessionRecord {
+                    kind: SessionKind::Prd,
+                    id: "p".to_string(),
+                    title: "Billing PRD".to_string(),
+                    status: SessionRecordStatus::Paused,
+                    cost: 2.0,
+                    updated_at: 50,
+                },
+            ]
+        );
+    }
+}
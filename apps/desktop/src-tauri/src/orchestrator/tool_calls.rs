@@ -0,0 +1,492 @@
+//! Tool-call history
+//!
+//! Tool calls are streamed to the frontend as transient `worker-tool-{id}`
+//! events. This module keeps a per-session log of every call (status
+//! transitions, raw input, diffs, duration) so it can be queried later and
+//! persisted alongside the session transcript.
+//!
+//! Durations feed per-kind latency stats, and a background watch emits
+//! `slow-tool` once for each call still running past
+//! `slow_tool_threshold_secs`.
+
+use crate::events::{emit, SlowToolEvent, ToolCallContent};
+use crate::settings::load_settings;
+use crate::telemetry;
+use crate::time::now_ms;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+use tauri::AppHandle;
+
+/// How often running tool calls are checked against the slow threshold
+const SLOW_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Global registry of tool-call logs (session_id -> records in call order)
+static TOOL_CALL_LOGS: Lazy<Mutex<HashMap<String, Vec<ToolCallRecord>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Calls already reported as slow, as (session_id, tool_call_id)
+static SLOW_REPORTED: Lazy<Mutex<HashSet<(String, String)>>> =
+    Lazy::new(|| Mutex::new(HashSet::new()));
+
+/// A single status change of a tool call
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolCallTransition {
+    pub status: String,
+    pub timestamp: i64,
+}
+
+/// A file change proposed by a tool call
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ToolCallDiff {
+    pub path: String,
+    pub old_text: Option<String>,
+    pub new_text: String,
+}
+
+/// Everything known about one tool call
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolCallRecord {
+    pub id: String,
+    pub worker_id: String,
+    pub kind: Option<String>,
+    pub title: Option<String>,
+    pub status: String,
+    pub transitions: Vec<ToolCallTransition>,
+    pub raw_input: Option<serde_json::Value>,
+    pub diffs: Vec<ToolCallDiff>,
+    pub started_at: i64,
+    pub finished_at: Option<i64>,
+    pub duration_ms: Option<i64>,
+}
+
+/// Optional filters for `get_session_tool_calls`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ToolCallFilter {
+    pub worker_id: Option<String>,
+    pub kind: Option<String>,
+    pub status: Option<String>,
+    /// Only calls that produced diffs
+    pub with_diffs: bool,
+}
+
+impl ToolCallFilter {
+    pub fn matches(&self, record: &ToolCallRecord) -> bool {
+        if let Some(worker_id) = &self.worker_id {
+            if &record.worker_id != worker_id {
+                return false;
+            }
+        }
+        if let Some(kind) = &self.kind {
+            if record.kind.as_deref() != Some(kind.as_str()) {
+                return false;
+            }
+        }
+        if let Some(status) = &self.status {
+            if &record.status != status {
+                return false;
+            }
+        }
+        !(self.with_diffs && record.diffs.is_empty())
+    }
+}
+
+fn is_terminal_status(status: &str) -> bool {
+    matches!(status, "completed" | "failed")
+}
+
+/// Pull diff entries out of the tool-call content sent to the frontend
+pub fn diffs_from_content(content: &[ToolCallContent]) -> Vec<ToolCallDiff> {
+    content
+        .iter()
+        .filter_map(|c| match c {
+            ToolCallContent::Diff {
+                path,
+                old_text,
+                new_text,
+            } => Some(ToolCallDiff {
+                path: path.clone(),
+                old_text: old_text.clone(),
+                new_text: new_text.clone(),
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Update a session's log with a tool call start or update. Returns true
+/// when this update finished the call.
+#[allow(clippy::too_many_arguments)]
+fn apply(
+    records: &mut Vec<ToolCallRecord>,
+    worker_id: &str,
+    tool_call_id: &str,
+    status: Option<String>,
+    title: Option<String>,
+    kind: Option<String>,
+    raw_input: Option<serde_json::Value>,
+    diffs: Vec<ToolCallDiff>,
+    now: i64,
+) -> bool {
+    let index = match records.iter().position(|r| r.id == tool_call_id) {
+        Some(index) => index,
+        None => {
+            records.push(ToolCallRecord {
+                id: tool_call_id.to_string(),
+                worker_id: worker_id.to_string(),
+                kind: None,
+                title: None,
+                status: "pending".to_string(),
+                transitions: Vec::new(),
+                raw_input: None,
+                diffs: Vec::new(),
+                started_at: now,
+                finished_at: None,
+                duration_ms: None,
+            });
+            records.len() - 1
+        }
+    };
+    let record = &mut records[index];
+
+    if title.is_some() {
+        record.title = title;
+    }
+    if kind.is_some() {
+        record.kind = kind;
+    }
+    if raw_input.is_some() {
+        record.raw_input = raw_input;
+    }
+    for diff in diffs {
+        if !record.diffs.contains(&diff) {
+            record.diffs.push(diff);
+        }
+    }
+
+    if let Some(status) = status {
+        if record.transitions.last().map(|t| t.status.as_str()) != Some(status.as_str()) {
+            record.transitions.push(ToolCallTransition {
+                status: status.clone(),
+                timestamp: now,
+            });
+        }
+        let finished = is_terminal_status(&status) && record.finished_at.is_none();
+        if finished {
+            record.finished_at = Some(now);
+            record.duration_ms = Some(now - record.started_at);
+        }
+        record.status = status;
+        return finished;
+    }
+    false
+}
+
+/// Record a tool call notification for a session
+#[allow(clippy::too_many_arguments)]
+pub fn record_tool_call(
+    session_id: &str,
+    worker_id: &str,
+    tool_call_id: &str,
+    status: Option<String>,
+    title: Option<String>,
+    kind: Option<String>,
+    raw_input: Option<serde_json::Value>,
+    diffs: Vec<ToolCallDiff>,
+) {
+    let mut logs = TOOL_CALL_LOGS.lock();
+    let records = logs.entry(session_id.to_string()).or_default();
+    let finished = apply(
+        records,
+        worker_id,
+        tool_call_id,
+        status,
+        title,
+        kind,
+        raw_input,
+        diffs,
+        now_ms(),
+    );
+    if finished {
+        if let Some(record) = records.iter().find(|r| r.id == tool_call_id) {
+            telemetry::tool_call_finished(record.kind.as_deref(), &record.status, record.duration_ms);
+        }
+    }
+}
+
+/// Get a session's tool calls (in call order), optionally filtered
+pub fn get_tool_calls(session_id: &str, filter: &ToolCallFilter) -> Option<Vec<ToolCallRecord>> {
+    let logs = TOOL_CALL_LOGS.lock();
+    logs.get(session_id)
+        .map(|records| records.iter().filter(|r| filter.matches(r)).cloned().collect())
+}
+
+/// Drop a session's log (on session cleanup)
+pub fn clear_tool_calls(session_id: &str) {
+    TOOL_CALL_LOGS.lock().remove(session_id);
+    SLOW_REPORTED.lock().retain(|(id, _)| id != session_id);
+}
+
+/// Latency of one kind of tool call in a session
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ToolKindLatency {
+    /// "other" for calls the agent didn't give a kind
+    pub kind: String,
+    /// Finished calls
+    pub count: usize,
+    pub failed: usize,
+    pub mean_ms: i64,
+    pub p95_ms: i64,
+    pub max_ms: i64,
+}
+
+/// Tool-call timing for a session
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ToolCallMetrics {
+    /// Slowest kind first
+    pub by_kind: Vec<ToolKindLatency>,
+    /// Calls still running
+    pub in_flight: usize,
+    /// Most calls that were running at the same time
+    pub peak_concurrency: usize,
+    /// Running calls past the slow threshold
+    pub slow: usize,
+}
+
+/// Whether a call is still running past `threshold_ms`
+fn is_slow(record: &ToolCallRecord, threshold_ms: i64, now: i64) -> bool {
+    threshold_ms > 0 && record.finished_at.is_none() && now - record.started_at >= threshold_ms
+}
+
+/// Latency stats, concurrency and slow calls of a session's log
+pub fn compute_metrics(records: &[ToolCallRecord], threshold_ms: i64, now: i64) -> ToolCallMetrics {
+    let mut durations: HashMap<String, (Vec<i64>, usize)> = HashMap::new();
+    for record in records {
+        let Some(duration) = record.duration_ms else {
+            continue;
+        };
+        let kind = record.kind.clone().unwrap_or_else(|| "other".to_string());
+        let entry = durations.entry(kind).or_default();
+        entry.0.push(duration);
+        if record.status == "failed" {
+            entry.1 += 1;
+        }
+    }
+
+    let mut by_kind: Vec<ToolKindLatency> = durations
+        .into_iter()
+        .map(|(kind, (mut durations, failed))| {
+            durations.sort_unstable();
+            let count = durations.len();
+            let p95_index = ((count as f64 * 0.95).ceil() as usize).clamp(1, count) - 1;
+            ToolKindLatency {
+                kind,
+                count,
+                failed,
+                mean_ms: durations.iter().sum::<i64>() / count as i64,
+                p95_ms: durations[p95_index],
+                max_ms: durations[count - 1],
+            }
+        })
+        .collect();
+    by_kind.sort_by(|a, b| b.p95_ms.cmp(&a.p95_ms).then_with(|| a.kind.cmp(&b.kind)));
+
+    // Finishes sort before starts at the same instant, so they don't overlap
+    let mut edges: Vec<(i64, i32)> = Vec::new();
+    for record in records {
+        edges.push((record.started_at, 1));
+        if let Some(finished_at) = record.finished_at {
+            edges.push((finished_at, -1));
+        }
+    }
+    edges.sort();
+    let mut running = 0;
+    let mut peak_concurrency = 0;
+    for (_, delta) in edges {
+        running += delta;
+        peak_concurrency = peak_concurrency.max(running);
+    }
+
+    ToolCallMetrics {
+        by_kind,
+        in_flight: records.iter().filter(|r| r.finished_at.is_none()).count(),
+        peak_concurrency: peak_concurrency as usize,
+        slow: records.iter().filter(|r| is_slow(r, threshold_ms, now)).count(),
+    }
+}
+
+/// The slow-tool threshold from settings, in milliseconds (0 = off)
+pub fn slow_threshold_ms() -> i64 {
+    load_settings().slow_tool_threshold_secs as i64 * 1000
+}
+
+/// Tool-call timing for a live session
+pub fn get_tool_call_metrics(session_id: &str) -> Option<ToolCallMetrics> {
+    let logs = TOOL_CALL_LOGS.lock();
+    let records = logs.get(session_id)?;
+    Some(compute_metrics(
+        records,
+        slow_threshold_ms(),
+        now_ms(),
+    ))
+}
+
+/// Running calls of a worker past the slow threshold
+pub fn slow_call_count(session_id: &str, worker_id: &str) -> usize {
+    let threshold_ms = slow_threshold_ms();
+    let now = now_ms();
+    TOOL_CALL_LOGS
+        .lock()
+        .get(session_id)
+        .map(|records| {
+            records
+                .iter()
+                .filter(|r| r.worker_id == worker_id && is_slow(r, threshold_ms, now))
+                .count()
+        })
+        .unwrap_or(0)
+}
+
+/// Calls that went past the threshold since the last check
+fn newly_slow(threshold_ms: i64, now: i64) -> Vec<(String, ToolCallRecord)> {
+    let logs = TOOL_CALL_LOGS.lock();
+    let mut reported = SLOW_REPORTED.lock();
+    let mut slow = Vec::new();
+    for (session_id, records) in logs.iter() {
+        for record in records.iter().filter(|r| is_slow(r, threshold_ms, now)) {
+            if reported.insert((session_id.clone(), record.id.clone())) {
+                slow.push((session_id.clone(), record.clone()));
+            }
+        }
+    }
+    slow
+}
+
+/// Start the background watch for slow tool calls
+pub fn start(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(SLOW_CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            let threshold_ms = slow_threshold_ms();
+            if threshold_ms == 0 {
+                continue;
+            }
+            let now = now_ms();
+            for (session_id, record) in newly_slow(threshold_ms, now) {
+                eprintln!(
+                    "[ACP] Tool call {} ({:?}) running for {}s",
+                    record.id,
+                    record.kind,
+                    (now - record.started_at) / 1000
+                );
+                emit(
+                    &app,
+                    &SlowToolEvent {
+                        session_id,
+                        worker_id: record.worker_id,
+                        tool_call_id: record.id,
+                        kind: record.kind,
+                        title: record.title,
+                        elapsed_ms: now - record.started_at,
+                        threshold_ms,
+                    },
+                );
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_transitions_and_duration() {
+        let mut records = Vec::new();
+        apply(
+            &mut records,
+            "w1",
+            "call_1",
+            Some("pending".to_string()),
+            Some("Edit file".to_string()),
+            Some("edit".to_string()),
+            None,
+            vec![],
+            1000,
+        );
+        apply(&mut records, "w1", "call_1", Some("inprogress".to_string()), None, None, None, vec![], 1500);
+        apply(&mut records, "w1", "call_1", Some("completed".to_string()), None, None, None, vec![], 3000);
+
+        assert_eq!(records.len(), 1);
+        let record = &records[0];
+        assert_eq!(record.title.as_deref(), Some("Edit file"));
+        assert_eq!(record.transitions.len(), 3);
+        assert!(record.finished_at.is_some());
+        assert_eq!(record.duration_ms, Some(2000));
+    }
+
+    #[test]
+    fn test_diffs_from_content() {
+        let content = vec![
+            ToolCallContent::Text { text: "hi".to_string() },
+            ToolCallContent::Diff {
+                path: "a.rs".to_string(),
+                old_text: None,
+                new_text: "fn a() {}".to_string(),
+            },
+        ];
+        let diffs = diffs_from_content(&content);
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].path, "a.rs");
+        assert_eq!(diffs[0].old_text, None);
+    }
+
+    #[test]
+    fn test_filter() {
+        let mut records = Vec::new();
+        apply(&mut records, "w1", "a", Some("completed".to_string()), None, Some("edit".to_string()), None, vec![], 0);
+        apply(&mut records, "w2", "b", Some("failed".to_string()), None, Some("execute".to_string()), None, vec![], 0);
+
+        let filter = ToolCallFilter {
+            kind: Some("edit".to_string()),
+            ..Default::default()
+        };
+        let matched: Vec<_> = records.iter().filter(|r| filter.matches(r)).collect();
+        assert_eq!(matched.len(), 1);
+        assert_eq!(matched[0].id, "a");
+
+        let filter = ToolCallFilter {
+            worker_id: Some("w2".to_string()),
+            ..Default::default()
+        };
+        assert!(filter.matches(&records[1]));
+        assert!(!filter.matches(&records[0]));
+    }
+
+    #[test]
+    fn test_metrics() {
+        let mut records = Vec::new();
+        let edit = Some("edit".to_string());
+        let execute = Some("execute".to_string());
+        apply(&mut records, "w1", "a", Some("pending".to_string()), None, edit.clone(), None, vec![], 0);
+        apply(&mut records, "w1", "a", Some("completed".to_string()), None, None, None, vec![], 100);
+        apply(&mut records, "w1", "b", Some("pending".to_string()), None, edit, None, vec![], 50);
+        apply(&mut records, "w1", "b", Some("failed".to_string()), None, None, None, vec![], 350);
+        apply(&mut records, "w2", "c", Some("pending".to_string()), None, execute, None, vec![], 100);
+
+        let metrics = compute_metrics(&records, 1000, 1200);
+        assert_eq!(metrics.by_kind.len(), 1);
+        let edit = &metrics.by_kind[0];
+        assert_eq!((edit.count, edit.failed), (2, 1));
+        assert_eq!((edit.mean_ms, edit.p95_ms, edit.max_ms), (200, 300, 300));
+        assert_eq!(metrics.in_flight, 1);
+        // "a" finishes as "c" starts, so at most "b" and one of them overlap
+        assert_eq!(metrics.peak_concurrency, 2);
+        assert_eq!(metrics.slow, 1);
+        assert_eq!(compute_metrics(&records, 0, 1200).slow, 0);
+    }
+}
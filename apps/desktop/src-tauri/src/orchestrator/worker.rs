@@ -1,8 +1,11 @@
 use crate::claude::pricing::Model;
+use crate::time::now_ms;
 use serde::{Deserialize, Serialize};
+use ts_rs::TS;
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
 #[serde(rename_all = "snake_case")]
+#[ts(export)]
 pub enum WorkerStatus {
     Pending,
     Running,
@@ -11,6 +14,8 @@ pub enum WorkerStatus {
     Cancelled,
     /// Worker is idle, ready to accept new prompts (after cancel or completion)
     Idle,
+    /// Agent process stopped after sitting idle; woken by the next prompt
+    Hibernated,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -26,13 +31,15 @@ pub struct WorkerSession {
     pub output_buffer: String,
     pub files_touched: Vec<String>,
     pub error_message: Option<String>,
+    /// Unix timestamp (milliseconds)
     pub created_at: i64,
+    /// Unix timestamp (milliseconds)
     pub updated_at: i64,
 }
 
 impl WorkerSession {
     pub fn new(id: String, session_id: String, task: String, model: Model) -> Self {
-        let now = chrono_timestamp();
+        let now = now_ms();
         Self {
             id,
             session_id,
@@ -52,41 +59,41 @@ impl WorkerSession {
 
     pub fn mark_running(&mut self) {
         self.status = WorkerStatus::Running;
-        self.updated_at = chrono_timestamp();
+        self.updated_at = now_ms();
     }
 
     pub fn mark_completed(&mut self) {
         self.status = WorkerStatus::Completed;
-        self.updated_at = chrono_timestamp();
+        self.updated_at = now_ms();
     }
 
     pub fn mark_failed(&mut self, error: String) {
         self.status = WorkerStatus::Failed;
         self.error_message = Some(error);
-        self.updated_at = chrono_timestamp();
+        self.updated_at = now_ms();
     }
 
     pub fn mark_cancelled(&mut self) {
         self.status = WorkerStatus::Cancelled;
-        self.updated_at = chrono_timestamp();
+        self.updated_at = now_ms();
     }
 
     pub fn append_output(&mut self, text: &str) {
         self.output_buffer.push_str(text);
-        self.updated_at = chrono_timestamp();
+        self.updated_at = now_ms();
     }
 
     pub fn set_usage(&mut self, input_tokens: u64, output_tokens: u64, cost: f64) {
         self.input_tokens = input_tokens;
         self.output_tokens = output_tokens;
         self.cost_usd = cost;
-        self.updated_at = chrono_timestamp();
+        self.updated_at = now_ms();
     }
 
     pub fn add_file(&mut self, path: String) {
         if !self.files_touched.contains(&path) {
             self.files_touched.push(path);
-            self.updated_at = chrono_timestamp();
+            self.updated_at = now_ms();
         }
     }
 
@@ -99,10 +106,3 @@ impl WorkerSession {
         }
     }
 }
-
-fn chrono_timestamp() -> i64 {
-    std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap()
-        .as_secs() as i64
-}
@@ -0,0 +1,247 @@
+//! Command palette search
+//!
+//! `palette_search` fuzzy-matches a query against slash commands, skills,
+//! sessions, recently opened files and agents and returns one ranked list,
+//! so the Cmd-K palette needs a single call per keystroke. Titles are
+//! matched first; an item whose title doesn't match can still be found by
+//! its subtitle (a description or a path), at half the score.
+
+pub mod recent;
+
+use crate::acp::registry::list_all_agents;
+use crate::acp::skills::get_builtin_skills;
+use crate::acp::skills_commands::{list_workspace_commands, list_workspace_skills};
+use crate::orchestrator::commands::list_all_sessions;
+use crate::AppState;
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+use tauri::State;
+
+/// Results returned when no limit is given
+const DEFAULT_LIMIT: usize = 50;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PaletteScope {
+    Command,
+    Skill,
+    Session,
+    File,
+    Agent,
+}
+
+impl PaletteScope {
+    const ALL: [PaletteScope; 5] = [
+        PaletteScope::Command,
+        PaletteScope::Skill,
+        PaletteScope::Session,
+        PaletteScope::File,
+        PaletteScope::Agent,
+    ];
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PaletteItem {
+    pub scope: PaletteScope,
+    /// What to act on: command name, skill id, session id, file path or
+    /// agent id
+    pub id: String,
+    pub title: String,
+    pub subtitle: String,
+    pub score: i64,
+    /// Character positions in `title` that matched the query
+    pub positions: Vec<usize>,
+}
+
+impl PaletteItem {
+    fn new(
+        scope: PaletteScope,
+        id: impl Into<String>,
+        title: impl Into<String>,
+        subtitle: impl Into<String>,
+    ) -> Self {
+        Self {
+            scope,
+            id: id.into(),
+            title: title.into(),
+            subtitle: subtitle.into(),
+            score: 0,
+            positions: Vec::new(),
+        }
+    }
+}
+
+/// Score `items` against `query`, dropping those that don't match, best
+/// first. Equal scores keep their order (sessions and files come most
+/// recent first). An empty query keeps everything in order.
+pub fn rank(items: Vec<PaletteItem>, query: &str, limit: usize) -> Vec<PaletteItem> {
+    let query = query.trim();
+    if query.is_empty() {
+        return items.into_iter().take(limit).collect();
+    }
+    let matcher = SkimMatcherV2::default().ignore_case();
+    let mut matched: Vec<PaletteItem> = items
+        .into_iter()
+        .filter_map(|mut item| {
+            if let Some((score, positions)) = matcher.fuzzy_indices(&item.title, query) {
+                item.score = score;
+                item.positions = positions;
+            } else {
+                item.score = matcher.fuzzy_match(&item.subtitle, query)? / 2;
+            }
+            Some(item)
+        })
+        .collect();
+    matched.sort_by(|a, b| b.score.cmp(&a.score));
+    matched.truncate(limit);
+    matched
+}
+
+fn commands(project_dir: Option<String>, agent_id: Option<String>) -> Vec<PaletteItem> {
+    let commands = list_workspace_commands(project_dir, agent_id);
+    commands
+        .project_commands
+        .into_iter()
+        .chain(commands.global_commands)
+        .chain(commands.builtin_commands)
+        .map(|cmd| {
+            let title = format!("/{}", cmd.name);
+            PaletteItem::new(PaletteScope::Command, cmd.name, title, cmd.description)
+        })
+        .collect()
+}
+
+fn skills(project_dir: Option<String>, agent_id: Option<String>) -> Vec<PaletteItem> {
+    let workspace = list_workspace_skills(project_dir, agent_id);
+    let mut seen = HashSet::new();
+    workspace
+        .project_skills
+        .into_iter()
+        .chain(workspace.global_skills)
+        .map(|skill| (skill.name.clone(), skill.name, skill.description))
+        .chain(
+            get_builtin_skills()
+                .into_iter()
+                .map(|skill| (skill.id, skill.name, skill.description)),
+        )
+        // Project skills override global and built-in ones of the same name
+        .filter(|(id, _, _)| seen.insert(id.clone()))
+        .map(|(id, name, description)| PaletteItem::new(PaletteScope::Skill, id, name, description))
+        .collect()
+}
+
+fn sessions(state: State<'_, AppState>) -> Vec<PaletteItem> {
+    match list_all_sessions(state) {
+        Ok(records) => records
+            .into_iter()
+            .map(|record| {
+                PaletteItem::new(PaletteScope::Session, record.id.clone(), record.title, record.id)
+            })
+            .collect(),
+        Err(e) => {
+            eprintln!("[Palette] Sessions unavailable: {}", e.message);
+            Vec::new()
+        }
+    }
+}
+
+/// Files opened this run, then files the live sessions' workers touched
+fn files(state: &AppState) -> Vec<PaletteItem> {
+    let touched: Vec<String> = state
+        .orchestrator_manager
+        .lock()
+        .list_sessions()
+        .into_iter()
+        .flat_map(|session| session.workers.iter())
+        .flat_map(|worker| worker.files_touched.iter().cloned())
+        .collect();
+    let mut seen = HashSet::new();
+    recent::recent_files()
+        .into_iter()
+        .chain(touched)
+        .filter(|path| seen.insert(path.clone()))
+        .map(|path| {
+            let name = Path::new(&path)
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| path.clone());
+            PaletteItem::new(PaletteScope::File, path.clone(), name, path)
+        })
+        .collect()
+}
+
+fn agents() -> Vec<PaletteItem> {
+    list_all_agents()
+        .into_iter()
+        .map(|agent| {
+            PaletteItem::new(PaletteScope::Agent, agent.id, agent.name, agent.description)
+        })
+        .collect()
+}
+
+/// Fuzzy-search commands, skills, sessions, recent files and agents (or
+/// just `scopes`) in one ranked list. `project_dir` and `agent_id` pick the
+/// project and provider whose custom commands and skills are included.
+#[tauri::command]
+pub fn palette_search(
+    query: String,
+    scopes: Option<Vec<PaletteScope>>,
+    project_dir: Option<String>,
+    agent_id: Option<String>,
+    limit: Option<usize>,
+    state: State<'_, AppState>,
+) -> Vec<PaletteItem> {
+    let scopes = scopes.unwrap_or_else(|| PaletteScope::ALL.to_vec());
+    let mut items = Vec::new();
+    for scope in PaletteScope::ALL {
+        if !scopes.contains(&scope) {
+            continue;
+        }
+        items.extend(match scope {
+            PaletteScope::Command => commands(project_dir.clone(), agent_id.clone()),
+            PaletteScope::Skill => skills(project_dir.clone(), agent_id.clone()),
+            PaletteScope::Session => sessions(state.clone()),
+            PaletteScope::File => files(&state),
+            PaletteScope::Agent => agents(),
+        });
+    }
+    rank(items, &query, limit.unwrap_or(DEFAULT_LIMIT))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rank() {
+        let items = vec![
+            PaletteItem::new(
+                PaletteScope::Command,
+                "review",
+                "/review",
+                "Review recent code changes",
+            ),
+            PaletteItem::new(PaletteScope::File, "/src/main.rs", "main.rs", "/src/main.rs"),
+            PaletteItem::new(PaletteScope::Session, "s1", "Fix the review bot", "s1"),
+            PaletteItem::new(PaletteScope::Agent, "claude", "Claude Code", "Anthropic's agent"),
+        ];
+
+        let ranked = rank(items.clone(), "review", 10);
+        let ids: Vec<&str> = ranked.iter().map(|i| i.id.as_str()).collect();
+        assert_eq!(ids.len(), 2);
+        assert!(ids.contains(&"review") && ids.contains(&"s1"));
+        assert_eq!(ranked[0].positions.len(), "review".len());
+
+        // Matched on the subtitle only: found, but without positions
+        let ranked = rank(items.clone(), "src", 10);
+        assert_eq!(ranked[0].id, "/src/main.rs");
+        assert!(ranked[0].positions.is_empty());
+
+        assert!(rank(items.clone(), "zzz", 10).is_empty());
+        assert_eq!(rank(items, "  ", 2).len(), 2);
+    }
+}
@@ -0,0 +1,23 @@
+//! Files opened during this run, most recent first
+
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+
+/// Files remembered for the palette
+const MAX_RECENT_FILES: usize = 100;
+
+static RECENT_FILES: Lazy<Mutex<VecDeque<String>>> = Lazy::new(|| Mutex::new(VecDeque::new()));
+
+/// Remember that a file was opened (read in the viewer or sent to an editor)
+pub fn record(path: &str) {
+    let mut recent = RECENT_FILES.lock();
+    recent.retain(|p| p != path);
+    recent.push_front(path.to_string());
+    recent.truncate(MAX_RECENT_FILES);
+}
+
+/// Recently opened files, most recent first
+pub fn recent_files() -> Vec<String> {
+    RECENT_FILES.lock().iter().cloned().collect()
+}
@@ -0,0 +1,160 @@
+//! Keeping the computer awake while agents work
+//!
+//! With `prevent_sleep` on, the system is kept from idle-sleeping while any
+//! worker or PRD session is running, so overnight loops aren't cut short;
+//! the hold is released once everything is idle. The display may still turn
+//! off, and closing a laptop's lid still sleeps it. macOS holds it with
+//! `caffeinate -i` and Linux with `systemd-inhibit`, both tied to this
+//! process so they end with it; Windows sets the execution state from a
+//! thread of its own.
+
+use crate::orchestrator::worker::WorkerStatus;
+use crate::prd::types::PrdSessionStatus;
+use crate::settings::load_settings;
+use crate::AppState;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+/// How often worker activity is checked
+const CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Whether sleep is currently being held off
+static HOLDING: AtomicBool = AtomicBool::new(false);
+
+/// A held "don't sleep" assertion, released when dropped
+struct SleepAssertion {
+    #[cfg(unix)]
+    child: std::process::Child,
+    /// The holding thread lets go once this is dropped
+    #[cfg(windows)]
+    _release: std::sync::mpsc::Sender<()>,
+}
+
+#[cfg(unix)]
+impl Drop for SleepAssertion {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[cfg(unix)]
+fn spawn_holder(program: &str, args: &[String]) -> Result<SleepAssertion, String> {
+    std::process::Command::new(program)
+        .args(args)
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .map(|child| SleepAssertion { child })
+        .map_err(|e| format!("Failed to run {}: {}", program, e))
+}
+
+#[cfg(target_os = "macos")]
+fn acquire() -> Result<SleepAssertion, String> {
+    // -i: no idle sleep; -w: until this process exits
+    spawn_holder(
+        "caffeinate",
+        &["-i".to_string(), "-w".to_string(), std::process::id().to_string()],
+    )
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn acquire() -> Result<SleepAssertion, String> {
+    spawn_holder(
+        "systemd-inhibit",
+        &[
+            "--what=idle:sleep".to_string(),
+            "--who=Crafter Code".to_string(),
+            "--why=Agents are running".to_string(),
+            "--mode=block".to_string(),
+            // Holds the lock until this process exits
+            "tail".to_string(),
+            format!("--pid={}", std::process::id()),
+            "-f".to_string(),
+            "/dev/null".to_string(),
+        ],
+    )
+}
+
+#[cfg(windows)]
+fn acquire() -> Result<SleepAssertion, String> {
+    use windows_sys::Win32::System::Power::{
+        SetThreadExecutionState, ES_CONTINUOUS, ES_SYSTEM_REQUIRED,
+    };
+
+    let (release, released) = std::sync::mpsc::channel::<()>();
+    let (started, result) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        // SAFETY: plain FFI calls; the state belongs to this thread
+        let held = unsafe { SetThreadExecutionState(ES_CONTINUOUS | ES_SYSTEM_REQUIRED) } != 0;
+        let _ = started.send(held);
+        if held {
+            // Blocks until the sender is dropped
+            let _ = released.recv();
+            unsafe { SetThreadExecutionState(ES_CONTINUOUS) };
+        }
+    });
+    match result.recv() {
+        Ok(true) => Ok(SleepAssertion { _release: release }),
+        _ => Err(format!(
+            "Failed to prevent sleep: {}",
+            std::io::Error::last_os_error()
+        )),
+    }
+}
+
+/// Whether any worker or PRD session is running
+fn is_busy(state: &AppState) -> bool {
+    let workers_running = state
+        .orchestrator_manager
+        .lock()
+        .list_sessions()
+        .iter()
+        .flat_map(|session| &session.workers)
+        .any(|worker| worker.status == WorkerStatus::Running);
+    workers_running
+        || state
+            .prd_manager
+            .list_sessions()
+            .iter()
+            .any(|session| session.status == PrdSessionStatus::Running)
+}
+
+/// Start holding off sleep whenever agents are busy (and the setting is on)
+pub fn start(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(CHECK_INTERVAL);
+        let mut assertion: Option<SleepAssertion> = None;
+        // Report a failure once, not every few seconds
+        let mut failed = false;
+        loop {
+            interval.tick().await;
+            let wanted = load_settings().prevent_sleep && is_busy(&app.state::<AppState>());
+            if wanted && assertion.is_none() {
+                match acquire() {
+                    Ok(held) => {
+                        eprintln!("[Power] Preventing sleep while agents run");
+                        assertion = Some(held);
+                        failed = false;
+                    }
+                    Err(e) if !failed => {
+                        eprintln!("[Power] {}", e);
+                        failed = true;
+                    }
+                    Err(_) => {}
+                }
+            } else if !wanted && assertion.take().is_some() {
+                eprintln!("[Power] Allowing sleep again");
+            }
+            HOLDING.store(assertion.is_some(), Ordering::Relaxed);
+        }
+    });
+}
+
+/// Whether the app is currently keeping the computer awake
+#[tauri::command]
+pub fn is_preventing_sleep() -> bool {
+    HOLDING.load(Ordering::Relaxed)
+}
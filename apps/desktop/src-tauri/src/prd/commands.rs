@@ -1,9 +1,15 @@
 //! Tauri commands for PRD execution
 
-use super::manager::run_ralph_loop;
+use super::leader::spawn_leader;
+use super::manager::{run_ralph_loop, PRD_AGENT_ID};
 use super::types::{
-    CostBreakdown, Prd, PrdSession, PrdSessionSummary, RalphWorker, StoryProgress, ValidationResult,
+    CostBreakdown, CriterionStatus, Prd, PrdSession, PrdSessionSummary, RalphWorker, Story,
+    StoryProgress, ValidationResult,
 };
+use super::verifier::verify_all_criteria_cached;
+use crate::acp::trust::require_decision;
+use crate::events::{emit, PrdUpdate, PrdUpdateEvent};
+use crate::stats::budget::require_budget;
 use crate::AppState;
 use tauri::{AppHandle, State};
 
@@ -21,6 +27,8 @@ pub async fn create_prd_session(
     app_handle: AppHandle,
     state: State<'_, AppState>,
 ) -> Result<PrdSession, String> {
+    require_decision(PRD_AGENT_ID, &state.prd_manager.worker_cwd())?;
+    require_budget().map_err(|e| e.to_string())?;
     let session = state.prd_manager.create_session(prd)?;
     let session_id = session.id.clone();
 
@@ -63,6 +71,8 @@ pub async fn resume_prd_session(
     app_handle: AppHandle,
     state: State<'_, AppState>,
 ) -> Result<(), String> {
+    require_decision(PRD_AGENT_ID, &state.prd_manager.worker_cwd())?;
+    require_budget().map_err(|e| e.to_string())?;
     state.prd_manager.resume_session(&session_id)?;
 
     // Restart the Ralph loop
@@ -110,6 +120,228 @@ pub async fn retry_prd_story(
     Ok(())
 }
 
+/// Add a story to a running (or paused, or finished) PRD session
+#[tauri::command]
+pub async fn prd_add_story(
+    session_id: String,
+    story: Story,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<PrdSession, String> {
+    let story_id = story.id.clone();
+    let restart = state.prd_manager.add_story(&session_id, story)?;
+    story_edited(&session_id, PrdUpdate::StoryAdded { story_id }, restart, app_handle, &state)
+}
+
+/// Replace a story that no worker is currently running
+#[tauri::command]
+pub async fn prd_update_story(
+    session_id: String,
+    story: Story,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<PrdSession, String> {
+    let story_id = story.id.clone();
+    let restart = state.prd_manager.update_story(&session_id, story)?;
+    story_edited(&session_id, PrdUpdate::StoryUpdated { story_id }, restart, app_handle, &state)
+}
+
+/// Remove a story that no worker is running and no other story depends on
+#[tauri::command]
+pub async fn prd_remove_story(
+    session_id: String,
+    story_id: String,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<PrdSession, String> {
+    let restart = state.prd_manager.remove_story(&session_id, &story_id)?;
+    story_edited(&session_id, PrdUpdate::StoryRemoved { story_id }, restart, app_handle, &state)
+}
+
+/// Skip a story. Stories depending on it start without it (a warning is
+/// emitted), and a worker running it is cancelled.
+#[tauri::command]
+pub async fn prd_skip_story(
+    session_id: String,
+    story_id: String,
+    reason: Option<String>,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<PrdSession, String> {
+    let (dependents, restart) = state
+        .prd_manager
+        .skip_story(&session_id, &story_id, reason)?;
+    if !dependents.is_empty() {
+        let warning = format!(
+            "Story {} was skipped; {} will start without it",
+            story_id,
+            dependents.join(", ")
+        );
+        eprintln!("[PRD] {}", warning);
+        emit(
+            &app_handle,
+            &PrdUpdateEvent {
+                session_id: session_id.clone(),
+                update: PrdUpdate::DependentsUnblocked {
+                    story_id: story_id.clone(),
+                    dependents,
+                    warning,
+                },
+            },
+        );
+    }
+    story_edited(&session_id, PrdUpdate::StorySkipped { story_id }, restart, app_handle, &state)
+}
+
+/// Hold a story back from workers (cancelling the worker running it)
+#[tauri::command]
+pub async fn prd_pause_story(
+    session_id: String,
+    story_id: String,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<PrdSession, String> {
+    state.prd_manager.pause_story(&session_id, &story_id)?;
+    story_edited(&session_id, PrdUpdate::StoryPaused { story_id }, false, app_handle, &state)
+}
+
+/// Let a paused story be picked up again
+#[tauri::command]
+pub async fn prd_resume_story(
+    session_id: String,
+    story_id: String,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<PrdSession, String> {
+    state.prd_manager.resume_story(&session_id, &story_id)?;
+    story_edited(&session_id, PrdUpdate::StoryResumed { story_id }, false, app_handle, &state)
+}
+
+/// Re-run a story's acceptance criteria now, ignoring cached results
+#[tauri::command]
+pub async fn prd_verify_story(
+    session_id: String,
+    story_id: String,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<Vec<CriterionStatus>, String> {
+    let session = state
+        .prd_manager
+        .get_session(&session_id)
+        .ok_or_else(|| format!("Session {} not found", session_id))?;
+    let story = session
+        .prd
+        .stories
+        .iter()
+        .find(|s| s.id == story_id)
+        .ok_or_else(|| format!("Story {} not found", story_id))?;
+
+    let statuses = verify_all_criteria_cached(
+        story,
+        state.prd_manager.get_working_dir().map(|p| p.as_path()),
+        state.prd_manager.verifier_cache(),
+        session.prd.constraints.criteria_concurrency,
+        true,
+    )
+    .await;
+    state
+        .prd_manager
+        .update_criteria_status(&session_id, &story_id, statuses.clone());
+    state.prd_manager.write_progress(&session_id);
+
+    emit(
+        &app_handle,
+        &PrdUpdateEvent {
+            session_id,
+            update: PrdUpdate::Criteria {
+                story_id,
+                criteria: statuses.clone(),
+            },
+        },
+    );
+    Ok(statuses)
+}
+
+/// Ask the session's leader agent something (starting it on first use).
+/// Returns its reply.
+#[tauri::command]
+pub async fn prd_send_message(
+    session_id: String,
+    message: String,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    if !state.prd_manager.session_exists(&session_id) {
+        return Err(format!("Session {} not found", session_id));
+    }
+    require_decision(PRD_AGENT_ID, &state.prd_manager.worker_cwd())?;
+
+    let reply = match state
+        .prd_manager
+        .with_leader(&session_id, |leader| leader.send(message.clone()))
+    {
+        Some(Ok(reply)) => reply,
+        // No leader yet, or it stopped
+        _ => {
+            let leader = spawn_leader(
+                state.prd_manager.clone(),
+                session_id.clone(),
+                app_handle.clone(),
+            );
+            let reply = leader.send(message.clone())?;
+            state.prd_manager.set_leader(&session_id, leader);
+            reply
+        }
+    };
+    let reply = reply
+        .await
+        .map_err(|_| "PRD leader stopped".to_string())??;
+
+    emit(
+        &app_handle,
+        &PrdUpdateEvent {
+            session_id,
+            update: PrdUpdate::LeaderReply {
+                message,
+                reply: reply.clone(),
+            },
+        },
+    );
+    Ok(reply)
+}
+
+/// Emit a `prd-update` for a story edit, restarting the Ralph loop when the
+/// edit gave a finished session work again
+fn story_edited(
+    session_id: &str,
+    update: PrdUpdate,
+    restart: bool,
+    app_handle: AppHandle,
+    state: &AppState,
+) -> Result<PrdSession, String> {
+    emit(
+        &app_handle,
+        &PrdUpdateEvent {
+            session_id: session_id.to_string(),
+            update,
+        },
+    );
+    state.prd_manager.write_progress(session_id);
+
+    if restart {
+        let manager = state.prd_manager.clone();
+        let session_id = session_id.to_string();
+        tokio::spawn(async move {
+            run_ralph_loop(manager, session_id, app_handle).await;
+        });
+    }
+
+    state
+        .prd_manager
+        .get_session(session_id)
+        .ok_or_else(|| format!("Session {} not found", session_id))
+}
+
 /// Get progress for a specific story
 #[tauri::command]
 pub fn get_story_progress(
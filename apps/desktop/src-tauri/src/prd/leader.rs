@@ -0,0 +1,174 @@
+//! PRD leader: an optional agent that stays alive next to the Ralph loop
+//!
+//! The user chats with it about the session ("why is story 3 failing?").
+//! It starts on the first message, runs on the PRD's master model, and sees
+//! the session through `swarm prd` commands, which the client intercepts
+//! like the other swarm commands: story progress, criteria results and
+//! guardrails, plus re-prioritizing pending stories.
+
+use super::manager::{model_alias, prd_agent, PrdManager};
+use super::types::ModelId;
+use crate::acp::client::{AcpClient, AcpError};
+use std::sync::Arc;
+use std::thread;
+use tauri::AppHandle;
+use tokio::sync::{mpsc, oneshot};
+
+/// Sent with the user's first message
+const LEADER_PROMPT: &str = r#"You are the leader of a PRD session: workers implement its stories in a loop, retrying each until its acceptance criteria pass. The user will ask you about the session. Don't implement stories yourself; inspect the session with these terminal commands:
+
+- `swarm prd status` - every story with its status, iteration and error
+- `swarm prd story <id>` - one story with its criteria results and the guardrails (feedback from failed iterations) its worker gets
+- `swarm prd prioritize <id> [<id>...]` - move pending stories (and what they depend on) to the front of the queue
+
+The user's message:
+
+"#;
+
+/// A running leader: messages go in, replies come back
+pub struct LeaderHandle {
+    messages: mpsc::UnboundedSender<LeaderMessage>,
+    cancel: mpsc::Sender<()>,
+}
+
+struct LeaderMessage {
+    text: String,
+    reply: oneshot::Sender<Result<String, String>>,
+}
+
+impl LeaderHandle {
+    /// Ask the leader something; resolves with its reply
+    pub fn send(&self, text: String) -> Result<oneshot::Receiver<Result<String, String>>, String> {
+        let (reply, rx) = oneshot::channel();
+        self.messages
+            .send(LeaderMessage { text, reply })
+            .map_err(|_| "PRD leader has stopped".to_string())?;
+        Ok(rx)
+    }
+
+    /// Interrupt the current turn; dropping the handle then ends the leader
+    pub fn cancel(&self) {
+        let _ = self.cancel.try_send(());
+    }
+}
+
+/// Worker id the leader's events are reported under
+pub fn leader_worker_id(session_id: &str) -> String {
+    format!("leader-{}", session_id)
+}
+
+/// Start a leader for a session on its own thread (the ACP client needs a
+/// LocalSet, like PRD workers)
+pub fn spawn_leader(
+    manager: Arc<PrdManager>,
+    session_id: String,
+    app_handle: AppHandle,
+) -> LeaderHandle {
+    let (messages, rx) = mpsc::unbounded_channel();
+    let (cancel, cancel_rx) = mpsc::channel(1);
+
+    thread::spawn(move || {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("Failed to create tokio runtime");
+
+        let local_set = tokio::task::LocalSet::new();
+
+        local_set.block_on(&rt, async move {
+            run_leader(manager, session_id, app_handle, rx, cancel_rx).await;
+        });
+    });
+
+    LeaderHandle { messages, cancel }
+}
+
+async fn run_leader(
+    manager: Arc<PrdManager>,
+    session_id: String,
+    app_handle: AppHandle,
+    mut messages: mpsc::UnboundedReceiver<LeaderMessage>,
+    mut cancel_rx: mpsc::Receiver<()>,
+) {
+    let mut client = match start_client(&manager, &session_id, app_handle).await {
+        Ok(client) => client,
+        Err(e) => {
+            let error = format!("Failed to start PRD leader: {}", e);
+            eprintln!("[PRD] {}", error);
+            manager.remove_leader(&session_id);
+            while let Ok(message) = messages.try_recv() {
+                let _ = message.reply.send(Err(error.clone()));
+            }
+            return;
+        }
+    };
+
+    let mut first = true;
+    while let Some(message) = messages.recv().await {
+        let prompt = if first {
+            format!("{}{}", LEADER_PROMPT, message.text)
+        } else {
+            message.text
+        };
+        first = false;
+
+        match client.prompt(&prompt, &mut cancel_rx).await {
+            Ok(_) => {
+                let _ = message.reply.send(Ok(client.last_output().text.trim().to_string()));
+            }
+            Err(AcpError::Cancelled) => {
+                let _ = message.reply.send(Err("PRD leader stopped".to_string()));
+                break;
+            }
+            Err(e) => {
+                let _ = message.reply.send(Err(e.to_string()));
+            }
+        }
+    }
+
+    let _ = client.kill().await;
+}
+
+async fn start_client(
+    manager: &Arc<PrdManager>,
+    session_id: &str,
+    app_handle: AppHandle,
+) -> Result<AcpClient, AcpError> {
+    let master = manager
+        .get_session(session_id)
+        .and_then(|s| s.prd.constraints.models.and_then(|m| m.master))
+        .unwrap_or(ModelId::Opus);
+    let agent = prd_agent();
+    let cwd = manager.worker_cwd();
+
+    let args: Vec<&str> = agent.args.iter().map(|s| s.as_str()).collect();
+    let mut client = AcpClient::spawn(
+        &agent.command,
+        &args,
+        &cwd,
+        &agent.env_vars,
+        Some(model_alias(master).to_string()),
+        agent.model_env_var.clone(),
+        app_handle,
+        leader_worker_id(session_id),
+        session_id.to_string(),
+        None,
+        None,
+    )
+    .await?;
+    client.set_agent_id(&agent.id);
+    client.set_prd_access(manager.clone(), session_id);
+
+    if let Err(e) = client.initialize().await {
+        let _ = client.kill().await;
+        return Err(e);
+    }
+    if let Err(e) = client.create_acp_session(&cwd).await {
+        let _ = client.kill().await;
+        return Err(e);
+    }
+
+    // Plan mode keeps it from editing the repo where the agent supports it
+    let _ = client.set_mode("plan").await;
+    Ok(client)
+}
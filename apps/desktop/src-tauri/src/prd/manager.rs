@@ -1,27 +1,43 @@
 //! PRD session management
 
+use agent_client_protocol::{ContentBlock, TextContent};
+use super::leader::LeaderHandle;
 use super::parser::validate_prd;
+use super::progress::{self, PROGRESS_FILE};
 use super::types::{
     CostBreakdown, ModelId, Prd, PrdSession, PrdSessionStatus, PrdSessionSummary, RalphWorker,
     StoryProgress, StoryStatus, Story, TokenUsage, ValidationResult, WorkerStatus,
+    DEFAULT_CRITERIA_CONCURRENCY,
 };
-use super::verifier::{all_criteria_pass, verify_all_criteria};
+use super::verifier::{all_criteria_pass, verify_all_criteria_cached, VerifierCache};
 use crate::acp::client::AcpClient;
-use crate::acp::registry::get_agent;
+use crate::acp::registry::{get_agent, AgentConfig};
+use crate::integrations::{dispatch, LifecycleEvent, WebhookEvent};
+use crate::notifications::{notify, NotificationEvent};
+use crate::time::now_ms;
 use parking_lot::Mutex;
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::thread;
 use tauri::{AppHandle, Emitter};
 use tokio::sync::mpsc;
 use uuid::Uuid;
 
+/// Agent PRD workers run
+pub const PRD_AGENT_ID: &str = "claude";
+
 /// Manager for PRD sessions
 pub struct PrdManager {
     sessions: Mutex<HashMap<String, PrdSession>>,
     /// Cancel channels for running workers
     cancel_channels: Mutex<HashMap<String, mpsc::Sender<()>>>,
+    /// Leader agents, by session
+    leaders: Mutex<HashMap<String, LeaderHandle>>,
+    /// Serializes progress file writes from concurrent workers
+    progress_lock: Mutex<()>,
+    /// Criteria results reused until the files they depend on change
+    verifier_cache: VerifierCache,
     /// Working directory for file operations
     working_dir: Option<PathBuf>,
 }
@@ -31,6 +47,9 @@ impl PrdManager {
         Self {
             sessions: Mutex::new(HashMap::new()),
             cancel_channels: Mutex::new(HashMap::new()),
+            leaders: Mutex::new(HashMap::new()),
+            progress_lock: Mutex::new(()),
+            verifier_cache: VerifierCache::new(),
             working_dir: None,
         }
     }
@@ -108,7 +127,7 @@ impl PrdManager {
     pub fn start_session(&self, session_id: &str) -> Result<(), String> {
         self.update_session(session_id, |session| {
             session.status = PrdSessionStatus::Running;
-            session.started_at = Some(chrono_timestamp());
+            session.started_at = Some(now_ms());
         });
         Ok(())
     }
@@ -153,6 +172,7 @@ impl PrdManager {
             }
         }
         drop(cancel_channels);
+        self.stop_leader(session_id);
 
         let mut sessions = self.sessions.lock();
         let session = sessions
@@ -160,7 +180,7 @@ impl PrdManager {
             .ok_or_else(|| format!("Session {} not found", session_id))?;
 
         session.status = PrdSessionStatus::Failed;
-        session.completed_at = Some(chrono_timestamp());
+        session.completed_at = Some(now_ms());
 
         // Mark all running workers as error
         for worker in &mut session.workers {
@@ -194,6 +214,7 @@ impl PrdManager {
         progress.iteration = 0;
         progress.error = None;
         progress.completed_at = None;
+        progress.guardrails.clear();
 
         // Reset criteria status
         for criterion in &mut progress.criteria_status {
@@ -210,6 +231,283 @@ impl PrdManager {
         Ok(())
     }
 
+    /// Add a story to a live session. Returns whether the session went back
+    /// to running (its Ralph loop has to be restarted).
+    pub fn add_story(&self, session_id: &str, story: Story) -> Result<bool, String> {
+        self.edit_stories(session_id, |session| {
+            let story_id = story.id.clone();
+            let criteria_count = story.acceptance_criteria.len();
+            let mut prd = session.prd.clone();
+            prd.stories.push(story);
+            apply_edited_prd(session, prd)?;
+            session.story_progress.insert(
+                story_id,
+                StoryProgress::new(session.prd.constraints.max_iterations_per_story, criteria_count),
+            );
+
+            // A finished session has work again
+            Ok(session.status == PrdSessionStatus::Completed)
+        })
+    }
+
+    /// Replace a story that no worker is running. A completed story goes back
+    /// to pending so the change gets built; its criteria are checked anew.
+    pub fn update_story(&self, session_id: &str, story: Story) -> Result<bool, String> {
+        self.edit_stories(session_id, |session| {
+            let status = editable_status(session, &story.id)?;
+            let mut prd = session.prd.clone();
+            if let Some(existing) = prd.stories.iter_mut().find(|s| s.id == story.id) {
+                *existing = story.clone();
+            }
+            apply_edited_prd(session, prd)?;
+
+            let max_iterations = session.prd.constraints.max_iterations_per_story;
+            let progress = StoryProgress::new(max_iterations, story.acceptance_criteria.len());
+            if status == StoryStatus::Completed {
+                session.story_progress.insert(story.id.clone(), progress);
+                Ok(session.status == PrdSessionStatus::Completed)
+            } else {
+                // Keep iterations and any failure; only the criteria changed
+                if let Some(existing) = session.story_progress.get_mut(&story.id) {
+                    existing.criteria_status = progress.criteria_status;
+                }
+                Ok(false)
+            }
+        })
+    }
+
+    /// Remove a story that no worker is running and nothing depends on
+    pub fn remove_story(&self, session_id: &str, story_id: &str) -> Result<bool, String> {
+        self.edit_stories(session_id, |session| {
+            let status = editable_status(session, story_id)?;
+            if let Some(dependent) = session
+                .prd
+                .stories
+                .iter()
+                .find(|s| s.dependencies.iter().any(|dep| dep == story_id))
+            {
+                return Err(format!(
+                    "Story '{}' depends on story '{}'",
+                    dependent.id, story_id
+                ));
+            }
+
+            let mut prd = session.prd.clone();
+            prd.stories.retain(|s| s.id != story_id);
+            apply_edited_prd(session, prd)?;
+            session.story_progress.remove(story_id);
+            release_story_worker(session, story_id);
+
+            // Removing the story that failed the session lets it carry on
+            Ok(status == StoryStatus::Failed
+                && session.status == PrdSessionStatus::Failed
+                && !session.any_story_failed())
+        })
+    }
+
+    /// Skip a story: its dependents can start without it, and a worker
+    /// running it is cancelled. Returns the dependents that no longer wait
+    /// on it, and whether the session went back to running.
+    pub fn skip_story(
+        &self,
+        session_id: &str,
+        story_id: &str,
+        reason: Option<String>,
+    ) -> Result<(Vec<String>, bool), String> {
+        let mut worker_id = None;
+        let mut dependents = Vec::new();
+        let restart = self.edit_stories(session_id, |session| {
+            let progress = session
+                .story_progress
+                .get_mut(story_id)
+                .ok_or_else(|| format!("Story {} not found", story_id))?;
+            let status = progress.status.clone();
+            if status.satisfies_dependency() {
+                return Err(format!("Story {} is already done", story_id));
+            }
+            // A running worker frees itself once cancelled
+            worker_id = progress.worker_id.clone().filter(|_| status == StoryStatus::InProgress);
+            progress.skip(reason);
+
+            dependents = session
+                .prd
+                .stories
+                .iter()
+                .filter(|s| s.dependencies.iter().any(|dep| dep == story_id))
+                .map(|s| s.id.clone())
+                .collect();
+            if worker_id.is_none() {
+                release_story_worker(session, story_id);
+            }
+
+            // Skipping the story that failed the session lets it carry on
+            Ok(status == StoryStatus::Failed
+                && session.status == PrdSessionStatus::Failed
+                && !session.any_story_failed())
+        })?;
+
+        if let Some(worker_id) = worker_id {
+            self.cancel_worker(session_id, &worker_id);
+        }
+        Ok((dependents, restart))
+    }
+
+    /// Hold a pending or running story back from workers, cancelling the
+    /// worker running it
+    pub fn pause_story(&self, session_id: &str, story_id: &str) -> Result<(), String> {
+        let worker_id = {
+            let mut sessions = self.sessions.lock();
+            let session = sessions
+                .get_mut(session_id)
+                .ok_or_else(|| format!("Session {} not found", session_id))?;
+            let progress = session
+                .story_progress
+                .get_mut(story_id)
+                .ok_or_else(|| format!("Story {} not found", story_id))?;
+            if !matches!(progress.status, StoryStatus::Pending | StoryStatus::InProgress) {
+                return Err("Can only pause pending or running stories".to_string());
+            }
+            let worker_id = progress.worker_id.clone();
+            progress.pause();
+            worker_id
+        };
+
+        if let Some(worker_id) = worker_id {
+            self.cancel_worker(session_id, &worker_id);
+        }
+        Ok(())
+    }
+
+    /// Let a paused story be picked up again
+    pub fn resume_story(&self, session_id: &str, story_id: &str) -> Result<(), String> {
+        let mut sessions = self.sessions.lock();
+        let session = sessions
+            .get_mut(session_id)
+            .ok_or_else(|| format!("Session {} not found", session_id))?;
+        let progress = session
+            .story_progress
+            .get_mut(story_id)
+            .ok_or_else(|| format!("Story {} not found", story_id))?;
+        if progress.status != StoryStatus::Paused {
+            return Err("Can only resume paused stories".to_string());
+        }
+        progress.status = StoryStatus::Pending;
+        Ok(())
+    }
+
+    /// Move stories to the front of the queue, each after the stories it
+    /// depends on. Workers pick the first ready story, so this only changes
+    /// which ready story goes next. Returns the new story order.
+    pub fn prioritize_stories(
+        &self,
+        session_id: &str,
+        story_ids: &[String],
+    ) -> Result<Vec<String>, String> {
+        let mut sessions = self.sessions.lock();
+        let session = sessions
+            .get_mut(session_id)
+            .ok_or_else(|| format!("Session {} not found", session_id))?;
+
+        let mut order = Vec::new();
+        for story_id in story_ids {
+            if !session.story_progress.contains_key(story_id) {
+                return Err(format!("Story {} not found", story_id));
+            }
+            with_dependencies_first(&session.prd, story_id, &mut order);
+        }
+        for story in &session.prd.stories {
+            if !order.contains(&story.id) {
+                order.push(story.id.clone());
+            }
+        }
+
+        let mut prd = session.prd.clone();
+        prd.stories
+            .sort_by_key(|story| order.iter().position(|id| *id == story.id));
+        apply_edited_prd(session, prd)?;
+        Ok(session.prd.stories.iter().map(|s| s.id.clone()).collect())
+    }
+
+    /// Record the feedback a story's worker carries into its next iteration
+    pub fn set_guardrails(&self, session_id: &str, story_id: &str, guardrails: Vec<String>) {
+        self.update_session(session_id, |session| {
+            if let Some(progress) = session.story_progress.get_mut(story_id) {
+                progress.guardrails = guardrails;
+            }
+        });
+    }
+
+    /// The session's leader, if one is running
+    pub fn with_leader<R>(&self, session_id: &str, f: impl FnOnce(&LeaderHandle) -> R) -> Option<R> {
+        self.leaders.lock().get(session_id).map(f)
+    }
+
+    /// Register a session's leader
+    pub fn set_leader(&self, session_id: &str, leader: LeaderHandle) {
+        self.leaders.lock().insert(session_id.to_string(), leader);
+    }
+
+    /// Forget a session's leader (it exits once its queued messages are done)
+    pub fn remove_leader(&self, session_id: &str) {
+        self.leaders.lock().remove(session_id);
+    }
+
+    /// Stop a session's leader, interrupting a reply in progress
+    pub fn stop_leader(&self, session_id: &str) {
+        if let Some(leader) = self.leaders.lock().remove(session_id) {
+            leader.cancel();
+        }
+    }
+
+    /// Whether a worker should keep going on a story (it wasn't skipped,
+    /// paused or otherwise taken away)
+    pub fn story_in_progress(&self, session_id: &str, story_id: &str) -> bool {
+        self.sessions
+            .lock()
+            .get(session_id)
+            .and_then(|s| s.story_progress.get(story_id))
+            .is_some_and(|p| p.status == StoryStatus::InProgress)
+    }
+
+    /// A worker stopped early: free it if its story was taken away
+    pub fn release_worker(&self, session_id: &str, story_id: &str) {
+        self.update_session(session_id, |session| {
+            take_back_story(session, story_id);
+        });
+    }
+
+    fn cancel_worker(&self, session_id: &str, worker_id: &str) {
+        let key = format!("{}:{}", session_id, worker_id);
+        if let Some(tx) = self.cancel_channels.lock().get(&key) {
+            let _ = tx.try_send(());
+        }
+    }
+
+    /// Run a story edit under the sessions lock. When the edit says the
+    /// session can run again it's set back to running; a session whose
+    /// stories are now all complete is completed.
+    fn edit_stories<F>(&self, session_id: &str, edit: F) -> Result<bool, String>
+    where
+        F: FnOnce(&mut PrdSession) -> Result<bool, String>,
+    {
+        let mut sessions = self.sessions.lock();
+        let session = sessions
+            .get_mut(session_id)
+            .ok_or_else(|| format!("Session {} not found", session_id))?;
+
+        let restart = edit(session)?;
+        if session.all_stories_completed() {
+            session.status = PrdSessionStatus::Completed;
+            session.completed_at = Some(now_ms());
+            return Ok(false);
+        }
+        if restart {
+            session.status = PrdSessionStatus::Running;
+            session.completed_at = None;
+        }
+        Ok(restart)
+    }
+
     /// Get story progress
     pub fn get_story_progress(
         &self,
@@ -305,7 +603,7 @@ impl PrdManager {
                         session
                             .story_progress
                             .get(dep)
-                            .map(|p| p.status == StoryStatus::Completed)
+                            .map(|p| p.status.satisfies_dependency())
                             .unwrap_or(false)
                     })
             })
@@ -326,6 +624,9 @@ impl PrdManager {
     /// Complete a story
     pub fn complete_story(&self, session_id: &str, story_id: &str, worker_id: &str) {
         self.update_session(session_id, |session| {
+            if !take_back_story(session, story_id) {
+                return;
+            }
             if let Some(progress) = session.story_progress.get_mut(story_id) {
                 progress.complete();
             }
@@ -338,7 +639,7 @@ impl PrdManager {
             // Check if all stories completed
             if session.all_stories_completed() {
                 session.status = PrdSessionStatus::Completed;
-                session.completed_at = Some(chrono_timestamp());
+                session.completed_at = Some(now_ms());
             }
         });
     }
@@ -352,6 +653,9 @@ impl PrdManager {
         error: String,
     ) {
         self.update_session(session_id, |session| {
+            if !take_back_story(session, story_id) {
+                return;
+            }
             if let Some(progress) = session.story_progress.get_mut(story_id) {
                 progress.fail(error.clone());
             }
@@ -363,7 +667,7 @@ impl PrdManager {
             // Check if session should fail
             if session.any_story_failed() {
                 session.status = PrdSessionStatus::Failed;
-                session.completed_at = Some(chrono_timestamp());
+                session.completed_at = Some(now_ms());
             }
         });
     }
@@ -395,10 +699,36 @@ impl PrdManager {
         });
     }
 
+    /// Write the session's progress file into the worker directory
+    pub fn write_progress(&self, session_id: &str) {
+        let Some(session) = self.get_session(session_id) else {
+            return;
+        };
+        let _guard = self.progress_lock.lock();
+        if let Err(e) = progress::write_progress(Path::new(&self.worker_cwd()), &session) {
+            eprintln!("[PRD] Failed to write {}: {}", PROGRESS_FILE, e);
+        }
+    }
+
+    /// Cached criteria results
+    pub fn verifier_cache(&self) -> &VerifierCache {
+        &self.verifier_cache
+    }
+
     /// Get working directory
     pub fn get_working_dir(&self) -> Option<&PathBuf> {
         self.working_dir.as_ref()
     }
+
+    /// Directory workers run in: the working directory, or the process's
+    pub fn worker_cwd(&self) -> String {
+        self.working_dir
+            .as_ref()
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_else(|| std::env::current_dir()
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_else(|_| "/".to_string()))
+    }
 }
 
 impl Default for PrdManager {
@@ -407,11 +737,79 @@ impl Default for PrdManager {
     }
 }
 
-fn chrono_timestamp() -> i64 {
-    std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap()
-        .as_millis() as i64
+/// Free the worker assigned to a story (failed on it, or about to be
+/// cancelled) for other stories
+fn release_story_worker(session: &mut PrdSession, story_id: &str) {
+    for worker in &mut session.workers {
+        if worker.current_story_id.as_deref() == Some(story_id) {
+            worker.reset();
+        }
+    }
+}
+
+/// Whether a worker's story is still in progress. If it was skipped or
+/// paused in the meantime, the worker is freed instead.
+fn take_back_story(session: &mut PrdSession, story_id: &str) -> bool {
+    let in_progress = session
+        .story_progress
+        .get(story_id)
+        .is_some_and(|p| p.status == StoryStatus::InProgress);
+    if !in_progress {
+        release_story_worker(session, story_id);
+    }
+    in_progress
+}
+
+/// Add a story to `order` after the stories it (transitively) depends on
+fn with_dependencies_first(prd: &Prd, story_id: &str, order: &mut Vec<String>) {
+    if order.iter().any(|id| id == story_id) {
+        return;
+    }
+    if let Some(story) = prd.stories.iter().find(|s| s.id == story_id) {
+        for dep in &story.dependencies {
+            with_dependencies_first(prd, dep, order);
+        }
+    }
+    order.push(story_id.to_string());
+}
+
+/// Status of a story that may be edited or removed (no worker running it)
+fn editable_status(session: &PrdSession, story_id: &str) -> Result<StoryStatus, String> {
+    let status = session
+        .story_progress
+        .get(story_id)
+        .map(|p| p.status.clone())
+        .ok_or_else(|| format!("Story {} not found", story_id))?;
+    if status == StoryStatus::InProgress {
+        return Err(format!("Story {} is being worked on", story_id));
+    }
+    Ok(status)
+}
+
+/// Validate an edited PRD and make it the session's, with its stories in
+/// dependency order and models assigned to new stories
+fn apply_edited_prd(session: &mut PrdSession, mut prd: Prd) -> Result<(), String> {
+    let validation = validate_prd(&prd);
+    if !validation.valid {
+        return Err(validation.errors.join("; "));
+    }
+
+    let position: HashMap<&str, usize> = validation
+        .dependency_order
+        .iter()
+        .enumerate()
+        .map(|(i, id)| (id.as_str(), i))
+        .collect();
+    prd.stories
+        .sort_by_key(|story| position.get(story.id.as_str()).copied().unwrap_or(usize::MAX));
+    for story in &mut prd.stories {
+        if story.model.is_none() {
+            story.model = validation.model_assignments.get(&story.id).copied();
+        }
+    }
+
+    session.prd = prd;
+    Ok(())
 }
 
 /// Execute the Ralph loop for a session
@@ -429,6 +827,7 @@ pub async fn run_ralph_loop(
     if manager.start_session(&session_id).is_err() {
         return;
     }
+    manager.write_progress(&session_id);
 
     loop {
         // Check if session is still running
@@ -471,8 +870,18 @@ pub async fn run_ralph_loop(
         if session.all_stories_completed() {
             manager.update_session(&session_id, |s| {
                 s.status = PrdSessionStatus::Completed;
-                s.completed_at = Some(chrono_timestamp());
+                s.completed_at = Some(now_ms());
             });
+            dispatch(
+                WebhookEvent::new(
+                    LifecycleEvent::SessionCompleted,
+                    &session_id,
+                    "PRD completed",
+                    &session.prd.title,
+                )
+                .with_data(serde_json::json!({ "total_cost": session.total_cost })),
+            );
+            manager.stop_leader(&session_id);
             break;
         }
 
@@ -483,6 +892,7 @@ pub async fn run_ralph_loop(
         // Small delay to prevent tight loop
         tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
     }
+    manager.write_progress(&session_id);
 
     // Emit completion event
     let _ = app_handle.emit(
@@ -494,8 +904,41 @@ pub async fn run_ralph_loop(
     );
 }
 
+/// Model alias passed to the agent for a story (defaults to Sonnet)
+pub(crate) fn story_model(story: &Story) -> &'static str {
+    model_alias(story.model.unwrap_or(ModelId::Sonnet))
+}
+
+/// Model alias passed to the agent
+pub(crate) fn model_alias(model: ModelId) -> &'static str {
+    match model {
+        ModelId::Opus => "opus",
+        ModelId::Sonnet => "sonnet",
+        ModelId::Haiku => "haiku",
+    }
+}
+
+/// Agent config PRD workers and leaders run (default to Claude)
+pub(crate) fn prd_agent() -> AgentConfig {
+    get_agent(PRD_AGENT_ID).unwrap_or_else(|| AgentConfig {
+        id: PRD_AGENT_ID.to_string(),
+        name: "Claude".to_string(),
+        description: "Anthropic Claude via claude-code-acp".to_string(),
+        command: "claude-code-acp".to_string(),
+        args: vec![],
+        available: true,
+        env_vars: vec!["ANTHROPIC_API_KEY".to_string()],
+        config_dir: ".claude".to_string(),
+        models: vec![],
+        default_model: "claude-sonnet-4-5-20250929".to_string(),
+        model_env_var: Some("ANTHROPIC_MODEL".to_string()),
+        model_cli_flag: Some("--model".to_string()),
+        capabilities: None,
+    })
+}
+
 /// Build the prompt for a story iteration
-fn build_story_prompt(story: &Story, iteration: u32, guardrails: &[String]) -> String {
+pub(crate) fn build_story_prompt(story: &Story, iteration: u32, guardrails: &[String]) -> String {
     let mut prompt = format!(
         "## Story: {}\n\n{}\n\n",
         story.title,
@@ -531,6 +974,11 @@ fn build_story_prompt(story: &Story, iteration: u32, guardrails: &[String]) -> S
         prompt.push('\n');
     }
 
+    prompt.push_str(&format!(
+        "The state of every story in this PRD is tracked in `{}`.\n",
+        PROGRESS_FILE
+    ));
+
     prompt.push_str(&format!("\n**Iteration {}: Please implement the story and ensure all acceptance criteria pass.**\n", iteration));
 
     prompt
@@ -544,18 +992,18 @@ async fn run_worker_loop(
     story_id: String,
     app_handle: AppHandle,
 ) {
-    let max_iterations = manager
+    let (max_iterations, criteria_concurrency) = manager
         .get_session(&session_id)
-        .map(|s| s.prd.constraints.max_iterations_per_story)
-        .unwrap_or(15);
+        .map(|s| {
+            (
+                s.prd.constraints.max_iterations_per_story,
+                s.prd.constraints.criteria_concurrency,
+            )
+        })
+        .unwrap_or((15, DEFAULT_CRITERIA_CONCURRENCY));
 
     let working_dir = manager.get_working_dir().cloned();
-    let cwd = working_dir
-        .as_ref()
-        .map(|p| p.to_string_lossy().to_string())
-        .unwrap_or_else(|| std::env::current_dir()
-            .map(|p| p.to_string_lossy().to_string())
-            .unwrap_or_else(|_| "/".to_string()));
+    let cwd = manager.worker_cwd();
 
     // Get story for verification
     let story = manager
@@ -571,33 +1019,15 @@ async fn run_worker_loop(
     };
 
     // Get model for this story
-    let model_id = story.model.unwrap_or(ModelId::Sonnet);
-    let model_str = match model_id {
-        ModelId::Opus => "opus",
-        ModelId::Sonnet => "sonnet",
-        ModelId::Haiku => "haiku",
-    };
+    let model_str = story_model(&story);
 
-    // Get agent config (default to Claude)
-    let agent = get_agent("claude").unwrap_or_else(|| {
-        crate::acp::registry::AgentConfig {
-            id: "claude".to_string(),
-            name: "Claude".to_string(),
-            description: "Anthropic Claude via claude-code-acp".to_string(),
-            command: "claude-code-acp".to_string(),
-            args: vec![],
-            available: true,
-            env_vars: vec!["ANTHROPIC_API_KEY".to_string()],
-            config_dir: ".claude".to_string(),
-            models: vec![],
-            default_model: "claude-sonnet-4-5-20250929".to_string(),
-            model_env_var: Some("ANTHROPIC_MODEL".to_string()),
-            model_cli_flag: Some("--model".to_string()),
-        }
-    });
-
-    // Track guardrails (previous iteration failures)
-    let mut guardrails: Vec<String> = Vec::new();
+    let agent = prd_agent();
+
+    // Track guardrails (previous iteration failures), kept across a pause
+    let mut guardrails = manager
+        .get_story_progress(&session_id, &story_id)
+        .map(|p| p.guardrails)
+        .unwrap_or_default();
 
     // Create cancellation channel
     let (cancel_tx, mut cancel_rx) = mpsc::channel::<()>(1);
@@ -625,6 +1055,7 @@ async fn run_worker_loop(
                 return;
             }
         };
+        client.set_agent_id(&agent.id);
 
         // Initialize
         if let Err(e) = client.initialize().await {
@@ -659,30 +1090,69 @@ async fn run_worker_loop(
             // Build prompt with guardrails
             let prompt = build_story_prompt(&story, iteration, &guardrails);
 
-            // Run agent iteration
-            match client.prompt(&prompt, &mut cancel_rx).await {
+            // Run agent iteration, waiting out rate limits and other
+            // transient errors rather than burning iterations on them
+            let content = vec![ContentBlock::Text(TextContent::new(prompt))];
+            match client.prompt_with_retry(content, &mut cancel_rx).await {
                 Ok(_) => {
                     // Agent completed, now verify criteria
                 }
                 Err(crate::acp::client::AcpError::Cancelled) => {
                     eprintln!("[PRD] Worker {} cancelled", worker_id);
                     let _ = client.kill().await;
+                    manager.release_worker(&session_id, &story_id);
+                    manager.remove_cancel(&worker_key);
                     return;
                 }
+                Err(e) if e.transient().is_some() => {
+                    // Not the agent's doing, so nothing to tell it next time
+                    eprintln!("[PRD] Worker {} still failing after retries: {}", worker_id, e);
+                    manager.write_progress(&session_id);
+                    continue;
+                }
                 Err(e) => {
                     eprintln!("[PRD] Worker {} prompt failed: {}", worker_id, e);
                     guardrails.push(format!("Agent error: {}", e));
+                    manager.set_guardrails(&session_id, &story_id, guardrails.clone());
+                    manager.write_progress(&session_id);
                     continue;
                 }
             }
 
             // Verify acceptance criteria
-            let statuses = verify_all_criteria(&story, working_dir.as_deref()).await;
+            let statuses = verify_all_criteria_cached(
+                &story,
+                working_dir.as_deref(),
+                manager.verifier_cache(),
+                criteria_concurrency,
+                false,
+            )
+            .await;
             manager.update_criteria_status(&session_id, &story_id, statuses.clone());
 
             // Check if all criteria pass
             if all_criteria_pass(&statuses) {
                 manager.complete_story(&session_id, &story_id, &worker_id);
+                manager.write_progress(&session_id);
+                notify(
+                    &app_handle,
+                    NotificationEvent::PrdStoryCompleted,
+                    Some(&session_id),
+                    "Story completed",
+                    &story.title,
+                );
+                dispatch(
+                    WebhookEvent::new(
+                        LifecycleEvent::StoryPassed,
+                        &session_id,
+                        "Story passed",
+                        &story.title,
+                    )
+                    .with_data(serde_json::json!({
+                        "story_id": story_id,
+                        "iteration": iteration,
+                    })),
+                );
 
                 let _ = app_handle.emit(
                     "prd-update",
@@ -709,6 +1179,8 @@ async fn run_worker_loop(
                     guardrails.push(format!("Criterion '{}': {}", criterion_desc, error));
                 }
             }
+            manager.set_guardrails(&session_id, &story_id, guardrails.clone());
+            manager.write_progress(&session_id);
 
             // Check if session is still running
             let session = match manager.get_session(&session_id) {
@@ -719,8 +1191,12 @@ async fn run_worker_loop(
                 }
             };
 
-            if session.status != PrdSessionStatus::Running {
+            if session.status != PrdSessionStatus::Running
+                || !manager.story_in_progress(&session_id, &story_id)
+            {
                 let _ = client.kill().await;
+                manager.release_worker(&session_id, &story_id);
+                manager.remove_cancel(&worker_key);
                 return;
             }
         }
@@ -732,6 +1208,7 @@ async fn run_worker_loop(
             &worker_id,
             format!("Max iterations ({}) reached", max_iterations),
         );
+        manager.write_progress(&session_id);
 
         let _ = app_handle.emit(
             "prd-update",
@@ -748,3 +1225,193 @@ async fn run_worker_loop(
     // Cleanup
     manager.remove_cancel(&worker_key);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prd::types::{AcceptanceCriterion, CriterionType, PrdConstraints};
+
+    fn story(id: &str, dependencies: &[&str]) -> Story {
+        Story {
+            id: id.to_string(),
+            title: id.to_string(),
+            description: "Do something".to_string(),
+            acceptance_criteria: vec![AcceptanceCriterion {
+                criterion_type: CriterionType::FileExists,
+                command: None,
+                path: Some("/tmp/test".to_string()),
+                file: None,
+                pattern: None,
+                script: None,
+                description: None,
+            }],
+            dependencies: dependencies.iter().map(|d| d.to_string()).collect(),
+            hints: None,
+            complexity: None,
+            model: None,
+        }
+    }
+
+    fn session(manager: &PrdManager, stories: Vec<Story>) -> String {
+        let prd = Prd {
+            title: "Test PRD".to_string(),
+            description: None,
+            stories,
+            constraints: PrdConstraints::default(),
+        };
+        manager.create_session(prd).unwrap().id
+    }
+
+    fn story_ids(manager: &PrdManager, session_id: &str) -> Vec<String> {
+        let session = manager.get_session(session_id).unwrap();
+        session.prd.stories.iter().map(|s| s.id.clone()).collect()
+    }
+
+    #[test]
+    fn test_add_story() {
+        let manager = PrdManager::new();
+        let id = session(&manager, vec![story("s1", &[])]);
+
+        // Dependencies are re-validated
+        assert!(manager.add_story(&id, story("s2", &["missing"])).is_err());
+        assert!(manager.add_story(&id, story("s1", &[])).is_err());
+
+        assert!(!manager.add_story(&id, story("s2", &["s1"])).unwrap());
+        assert_eq!(story_ids(&manager, &id), vec!["s1", "s2"]);
+
+        let session = manager.get_session(&id).unwrap();
+        assert_eq!(session.story_progress["s2"].status, StoryStatus::Pending);
+        assert!(session.prd.stories.iter().all(|s| s.model.is_some()));
+    }
+
+    #[test]
+    fn test_add_story_reopens_completed_session() {
+        let manager = PrdManager::new();
+        let id = session(&manager, vec![story("s1", &[])]);
+        let (worker_id, _) = manager.assign_next_story(&id).unwrap();
+        manager.complete_story(&id, "s1", &worker_id);
+        assert_eq!(manager.get_session(&id).unwrap().status, PrdSessionStatus::Completed);
+
+        assert!(manager.add_story(&id, story("s2", &["s1"])).unwrap());
+        assert_eq!(manager.get_session(&id).unwrap().status, PrdSessionStatus::Running);
+    }
+
+    #[test]
+    fn test_prioritize_stories() {
+        let manager = PrdManager::new();
+        let id = session(
+            &manager,
+            vec![story("s1", &[]), story("s2", &[]), story("s3", &["s2"]), story("s4", &[])],
+        );
+
+        assert!(manager.prioritize_stories(&id, &["missing".to_string()]).is_err());
+
+        // Dependencies move up with the story
+        let order = manager.prioritize_stories(&id, &["s3".to_string()]).unwrap();
+        assert_eq!(order, vec!["s2", "s3", "s1", "s4"]);
+        assert_eq!(story_ids(&manager, &id), order);
+
+        let (_, story_id) = manager.assign_next_story(&id).unwrap();
+        assert_eq!(story_id, "s2");
+    }
+
+    #[test]
+    fn test_update_story() {
+        let manager = PrdManager::new();
+        let id = session(&manager, vec![story("s1", &[]), story("s2", &[])]);
+
+        let mut edited = story("s1", &["s2"]);
+        edited.acceptance_criteria.push(edited.acceptance_criteria[0].clone());
+        manager.update_story(&id, edited).unwrap();
+        assert_eq!(story_ids(&manager, &id), vec!["s2", "s1"]);
+        let session = manager.get_session(&id).unwrap();
+        assert_eq!(session.story_progress["s1"].criteria_status.len(), 2);
+
+        // Cycles are refused and leave the session as it was
+        assert!(manager.update_story(&id, story("s2", &["s1"])).is_err());
+        assert_eq!(story_ids(&manager, &id), vec!["s2", "s1"]);
+
+        // Stories being worked on can't be edited
+        manager.assign_next_story(&id).unwrap();
+        assert!(manager.update_story(&id, story("s2", &[])).is_err());
+    }
+
+    #[test]
+    fn test_remove_story() {
+        let manager = PrdManager::new();
+        let id = session(
+            &manager,
+            vec![story("s3", &[]), story("s1", &[]), story("s2", &["s1"])],
+        );
+
+        assert!(manager.remove_story(&id, "s1").unwrap_err().contains("depends on"));
+        assert!(manager.remove_story(&id, "missing").is_err());
+
+        let (worker_id, story_id) = manager.assign_next_story(&id).unwrap();
+        assert_eq!(story_id, "s3");
+        manager.fail_story(&id, "s3", &worker_id, "boom".to_string());
+        assert_eq!(manager.get_session(&id).unwrap().status, PrdSessionStatus::Failed);
+
+        // Removing the failed story lets the session run again
+        assert!(manager.remove_story(&id, "s3").unwrap());
+        let session = manager.get_session(&id).unwrap();
+        assert_eq!(session.status, PrdSessionStatus::Running);
+        assert!(!session.story_progress.contains_key("s3"));
+        assert!(session.workers.iter().all(|w| w.status == WorkerStatus::Idle));
+    }
+
+    #[test]
+    fn test_skip_story_unblocks_dependents() {
+        let manager = PrdManager::new();
+        let id = session(&manager, vec![story("s1", &[]), story("s2", &["s1"])]);
+
+        let (worker_id, story_id) = manager.assign_next_story(&id).unwrap();
+        assert_eq!(story_id, "s1");
+        let (dependents, restart) = manager
+            .skip_story(&id, "s1", Some("done upstream".to_string()))
+            .unwrap();
+        assert_eq!(dependents, vec!["s2"]);
+        assert!(!restart);
+        assert!(manager.skip_story(&id, "s1", None).is_err());
+
+        // The cancelled worker's late result doesn't override the skip
+        manager.complete_story(&id, "s1", &worker_id);
+        let session = manager.get_session(&id).unwrap();
+        assert_eq!(session.story_progress["s1"].status, StoryStatus::Skipped);
+        assert_eq!(
+            session.story_progress["s1"].skip_reason.as_deref(),
+            Some("done upstream")
+        );
+
+        let (_, story_id) = manager.assign_next_story(&id).unwrap();
+        assert_eq!(story_id, "s2");
+    }
+
+    #[test]
+    fn test_skipping_every_story_completes_session() {
+        let manager = PrdManager::new();
+        let id = session(&manager, vec![story("s1", &[])]);
+        manager.skip_story(&id, "s1", None).unwrap();
+        assert_eq!(manager.get_session(&id).unwrap().status, PrdSessionStatus::Completed);
+    }
+
+    #[test]
+    fn test_pause_story() {
+        let manager = PrdManager::new();
+        let id = session(&manager, vec![story("s1", &[])]);
+
+        let (worker_id, _) = manager.assign_next_story(&id).unwrap();
+        manager.pause_story(&id, "s1").unwrap();
+        assert!(!manager.story_in_progress(&id, "s1"));
+        manager.release_worker(&id, "s1");
+        let session = manager.get_session(&id).unwrap();
+        assert_eq!(session.story_progress["s1"].status, StoryStatus::Paused);
+        assert!(session.workers.iter().all(|w| w.status == WorkerStatus::Idle));
+        assert!(manager.assign_next_story(&id).is_none());
+
+        assert!(manager.resume_story(&id, "s1").is_ok());
+        assert!(manager.resume_story(&id, "s1").is_err());
+        let (next_worker, story_id) = manager.assign_next_story(&id).unwrap();
+        assert_eq!((next_worker, story_id), (worker_id, "s1".to_string()));
+    }
+}
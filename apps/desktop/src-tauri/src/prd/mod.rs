@@ -7,8 +7,10 @@
 //! - Progress persistence via files + git
 
 pub mod commands;
+pub mod leader;
 pub mod manager;
 pub mod parser;
+pub mod progress;
 pub mod types;
 pub mod verifier;
 
@@ -3,7 +3,8 @@
 use super::types::{
     AcceptanceCriterion, Complexity, CriterionType, ModelId, Prd, Story, ValidationResult,
 };
-use std::collections::{HashMap, HashSet};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
 
 /// Validate a PRD and return model assignments + dependency order
 pub fn validate_prd(prd: &Prd) -> ValidationResult {
@@ -189,7 +190,8 @@ fn dfs_cycle(
     None
 }
 
-/// Topological sort for dependency order
+/// Topological sort for dependency order. Stories keep their PRD order
+/// where the dependencies allow it.
 fn topological_sort(stories: &[Story]) -> Vec<String> {
     let mut in_degree: HashMap<&str, usize> = HashMap::new();
     let mut graph: HashMap<&str, Vec<&str>> = HashMap::new();
@@ -210,16 +212,23 @@ fn topological_sort(stories: &[Story]) -> Vec<String> {
         }
     }
 
-    // Kahn's algorithm
-    let mut queue: Vec<&str> = in_degree
+    // Kahn's algorithm, taking the earliest ready story in PRD order first
+    let index: HashMap<&str, usize> = stories
         .iter()
-        .filter(|(_, &deg)| deg == 0)
-        .map(|(&id, _)| id)
+        .enumerate()
+        .map(|(i, s)| (s.id.as_str(), i))
+        .collect();
+    let mut ready: BinaryHeap<Reverse<usize>> = stories
+        .iter()
+        .enumerate()
+        .filter(|(_, s)| in_degree.get(s.id.as_str()) == Some(&0))
+        .map(|(i, _)| Reverse(i))
         .collect();
 
     let mut result = Vec::new();
 
-    while let Some(node) = queue.pop() {
+    while let Some(Reverse(i)) = ready.pop() {
+        let node = stories[i].id.as_str();
         result.push(node.to_string());
 
         if let Some(neighbors) = graph.get(node) {
@@ -227,7 +236,9 @@ fn topological_sort(stories: &[Story]) -> Vec<String> {
                 if let Some(deg) = in_degree.get_mut(*neighbor) {
                     *deg -= 1;
                     if *deg == 0 {
-                        queue.push(*neighbor);
+                        if let Some(&j) = index.get(*neighbor) {
+                            ready.push(Reverse(j));
+                        }
                     }
                 }
             }
@@ -373,6 +384,30 @@ mod tests {
         assert!(result.errors.iter().any(|e| e.contains("at least one story")));
     }
 
+    #[test]
+    fn test_dependency_order() {
+        let mut prd = simple_prd();
+        let mut s0 = prd.stories[0].clone();
+        s0.id = "s0".to_string();
+        s0.dependencies = vec!["s2".to_string()];
+        let mut s2 = prd.stories[0].clone();
+        s2.id = "s2".to_string();
+        prd.stories.insert(0, s0);
+        prd.stories.push(s2);
+
+        let result = validate_prd(&prd);
+        assert!(result.valid);
+        assert_eq!(result.dependency_order, vec!["s1", "s2", "s0"]);
+
+        // A story waiting on a dependency goes ahead of later stories once
+        // the dependency is done
+        let mut s3 = prd.stories[1].clone();
+        s3.id = "s3".to_string();
+        prd.stories.push(s3);
+        let result = validate_prd(&prd);
+        assert_eq!(result.dependency_order, vec!["s1", "s2", "s0", "s3"]);
+    }
+
     #[test]
     fn test_duplicate_ids() {
         let mut prd = simple_prd();
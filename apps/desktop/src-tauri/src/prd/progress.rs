@@ -0,0 +1,193 @@
+//! PRD progress file
+//!
+//! Story statuses, criteria results and guardrails are written to
+//! `prd/PROGRESS.md` in the working directory after every iteration, so
+//! progress survives a crash, shows up in git history, and fresh workers
+//! can read what happened to the stories around theirs.
+
+use super::types::{PrdSession, StoryStatus};
+use serde::Serialize;
+use std::path::Path;
+
+/// Progress file, relative to the working directory
+pub const PROGRESS_FILE: &str = "prd/PROGRESS.md";
+
+/// Longest criterion error or guardrail kept in the file
+const MAX_LINE_CHARS: usize = 300;
+
+/// Render a session's progress as markdown
+pub fn render_progress(session: &PrdSession) -> String {
+    let completed = session
+        .story_progress
+        .values()
+        .filter(|p| p.status == StoryStatus::Completed)
+        .count();
+
+    let mut out = format!("# PRD Progress: {}\n\n", session.prd.title);
+    out.push_str(&format!(
+        "Status: {} · {}/{} stories completed · ${:.2}\n\n",
+        label(&session.status),
+        completed,
+        session.prd.stories.len(),
+        session.total_cost
+    ));
+
+    out.push_str("| Story | Status | Iteration | Criteria |\n");
+    out.push_str("|---|---|---|---|\n");
+    for story in &session.prd.stories {
+        let Some(progress) = session.story_progress.get(&story.id) else {
+            continue;
+        };
+        out.push_str(&format!(
+            "| {}: {} | {} | {}/{} | {}/{} |\n",
+            story.id,
+            one_line(&story.title).replace('|', "\\|"),
+            label(&progress.status),
+            progress.iteration,
+            progress.max_iterations,
+            progress.criteria_status.iter().filter(|c| c.passed).count(),
+            progress.criteria_status.len()
+        ));
+    }
+
+    for story in &session.prd.stories {
+        let Some(progress) = session.story_progress.get(&story.id) else {
+            continue;
+        };
+        out.push_str(&format!("\n## {}: {}\n\n", story.id, one_line(&story.title)));
+        out.push_str(&format!("Status: {}", label(&progress.status)));
+        if let Some(worker_id) = &progress.worker_id {
+            out.push_str(&format!(" ({})", worker_id));
+        }
+        out.push('\n');
+        if !story.dependencies.is_empty() {
+            out.push_str(&format!("Depends on: {}\n", story.dependencies.join(", ")));
+        }
+        if let Some(reason) = &progress.skip_reason {
+            out.push_str(&format!("Skipped: {}\n", one_line(reason)));
+        }
+        if let Some(error) = &progress.error {
+            out.push_str(&format!("Error: {}\n", one_line(error)));
+        }
+
+        out.push_str("\n### Criteria\n\n");
+        for (criterion, status) in story.acceptance_criteria.iter().zip(&progress.criteria_status) {
+            let description = criterion.description.as_deref().unwrap_or("No description");
+            let check = if status.passed { "x" } else { " " };
+            out.push_str(&format!("- [{}] {}", check, one_line(description)));
+            if let Some(error) = status.error.as_deref().filter(|_| !status.passed) {
+                out.push_str(&format!(": {}", one_line(error)));
+            }
+            out.push('\n');
+        }
+
+        if !progress.guardrails.is_empty() {
+            out.push_str("\n### Guardrails\n\n");
+            for guardrail in &progress.guardrails {
+                out.push_str(&format!("- {}\n", one_line(guardrail)));
+            }
+        }
+    }
+
+    out
+}
+
+/// Write a session's progress file into `dir`
+pub fn write_progress(dir: &Path, session: &PrdSession) -> std::io::Result<()> {
+    let path = dir.join(PROGRESS_FILE);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    // Replace the file in one step so a crash never leaves half of it
+    let tmp = path.with_extension(format!("md.{}.tmp", session.id));
+    std::fs::write(&tmp, render_progress(session))?;
+    std::fs::rename(&tmp, &path)
+}
+
+/// snake_case name of a status enum
+fn label<T: Serialize>(status: &T) -> String {
+    serde_json::to_value(status)
+        .ok()
+        .and_then(|v| v.as_str().map(String::from))
+        .unwrap_or_default()
+}
+
+/// First line of a message, shortened
+fn one_line(text: &str) -> String {
+    let line = text.lines().find(|l| !l.trim().is_empty()).unwrap_or("").trim();
+    if line.chars().count() > MAX_LINE_CHARS {
+        let short: String = line.chars().take(MAX_LINE_CHARS).collect();
+        format!("{}…", short)
+    } else {
+        line.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prd::types::{
+        AcceptanceCriterion, CriterionStatus, CriterionType, Prd, PrdConstraints, Story,
+    };
+    use tempfile::TempDir;
+
+    fn session() -> PrdSession {
+        let criterion = |description: &str| AcceptanceCriterion {
+            criterion_type: CriterionType::Test,
+            command: Some("cargo test".to_string()),
+            path: None,
+            file: None,
+            pattern: None,
+            script: None,
+            description: Some(description.to_string()),
+        };
+        let prd = Prd {
+            title: "Auth".to_string(),
+            description: None,
+            stories: vec![Story {
+                id: "s1".to_string(),
+                title: "Login | logout".to_string(),
+                description: "Add login".to_string(),
+                acceptance_criteria: vec![criterion("Tests pass"), criterion("Lints pass")],
+                dependencies: vec![],
+                hints: None,
+                complexity: None,
+                model: None,
+            }],
+            constraints: PrdConstraints::default(),
+        };
+        PrdSession::new("session-1".to_string(), prd)
+    }
+
+    #[test]
+    fn test_render_progress() {
+        let mut session = session();
+        let progress = session.story_progress.get_mut("s1").unwrap();
+        progress.start("worker-0".to_string());
+        progress.iteration = 2;
+        progress.criteria_status = vec![
+            CriterionStatus::passed(),
+            CriterionStatus::failed("error: unused variable\nmore output".to_string()),
+        ];
+        progress.guardrails = vec!["Criterion 'Lints pass': error: unused variable".to_string()];
+
+        let markdown = render_progress(&session);
+        assert!(markdown.contains("| s1: Login \\| logout | in_progress | 2/15 | 1/2 |"));
+        assert!(markdown.contains("Status: in_progress (worker-0)"));
+        assert!(markdown.contains("- [x] Tests pass\n"));
+        assert!(markdown.contains("- [ ] Lints pass: error: unused variable\n"));
+        assert!(!markdown.contains("more output"));
+        assert!(markdown.contains("### Guardrails\n\n- Criterion 'Lints pass'"));
+    }
+
+    #[test]
+    fn test_write_progress() {
+        let dir = TempDir::new().unwrap();
+        let session = session();
+        write_progress(dir.path(), &session).unwrap();
+
+        let written = std::fs::read_to_string(dir.path().join(PROGRESS_FILE)).unwrap();
+        assert_eq!(written, render_progress(&session));
+        assert_eq!(std::fs::read_dir(dir.path().join("prd")).unwrap().count(), 1);
+    }
+}
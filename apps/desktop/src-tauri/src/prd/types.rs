@@ -1,9 +1,12 @@
+use crate::time::now_ms;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use ts_rs::TS;
 
 /// Acceptance criterion types for story verification
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
 #[serde(rename_all = "snake_case")]
+#[ts(export)]
 pub enum CriterionType {
     Test,
     FileExists,
@@ -12,7 +15,8 @@ pub enum CriterionType {
 }
 
 /// Acceptance criterion for a story
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
 pub struct AcceptanceCriterion {
     #[serde(rename = "type")]
     pub criterion_type: CriterionType,
@@ -31,11 +35,13 @@ pub struct AcceptanceCriterion {
 }
 
 /// Status of a criterion check
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[serde(rename_all = "camelCase")]
+#[ts(export)]
 pub struct CriterionStatus {
     pub passed: bool,
     pub error: Option<String>,
+    #[ts(type = "number | null")]
     pub last_checked: Option<i64>,
 }
 
@@ -44,7 +50,7 @@ impl CriterionStatus {
         Self {
             passed: true,
             error: None,
-            last_checked: Some(chrono_timestamp()),
+            last_checked: Some(now_ms()),
         }
     }
 
@@ -52,7 +58,7 @@ impl CriterionStatus {
         Self {
             passed: false,
             error: Some(error),
-            last_checked: Some(chrono_timestamp()),
+            last_checked: Some(now_ms()),
         }
     }
 }
@@ -66,6 +72,17 @@ pub enum StoryStatus {
     Completed,
     Failed,
     Blocked,
+    /// Left out on purpose; counts as done for its dependents
+    Skipped,
+    /// Held back from workers until resumed
+    Paused,
+}
+
+impl StoryStatus {
+    /// Whether stories depending on this one may start
+    pub fn satisfies_dependency(&self) -> bool {
+        matches!(self, StoryStatus::Completed | StoryStatus::Skipped)
+    }
 }
 
 /// Model identifier for Claude models
@@ -78,29 +95,23 @@ pub enum ModelId {
 }
 
 impl ModelId {
-    /// Cost per million input tokens
-    pub fn input_cost_per_million(&self) -> f64 {
-        match self {
-            ModelId::Opus => 15.0,
-            ModelId::Sonnet => 3.0,
-            ModelId::Haiku => 0.25,
-        }
-    }
-
-    /// Cost per million output tokens
-    pub fn output_cost_per_million(&self) -> f64 {
+    /// Alias in the pricing registry
+    pub fn pricing_key(&self) -> &'static str {
         match self {
-            ModelId::Opus => 75.0,
-            ModelId::Sonnet => 15.0,
-            ModelId::Haiku => 1.25,
+            ModelId::Opus => "opus",
+            ModelId::Sonnet => "sonnet",
+            ModelId::Haiku => "haiku",
         }
     }
 
     /// Calculate cost for given token counts
     pub fn calculate_cost(&self, input_tokens: u64, output_tokens: u64) -> f64 {
-        let input_cost = (input_tokens as f64 / 1_000_000.0) * self.input_cost_per_million();
-        let output_cost = (output_tokens as f64 / 1_000_000.0) * self.output_cost_per_million();
-        input_cost + output_cost
+        crate::claude::pricing::model_cost(
+            Some("claude"),
+            self.pricing_key(),
+            input_tokens,
+            output_tokens,
+        )
     }
 }
 
@@ -151,6 +162,12 @@ pub struct StoryProgress {
     pub started_at: Option<i64>,
     pub completed_at: Option<i64>,
     pub error: Option<String>,
+    /// Why the story was skipped
+    #[serde(default)]
+    pub skip_reason: Option<String>,
+    /// Feedback from failed iterations, fed into the next prompt
+    #[serde(default)]
+    pub guardrails: Vec<String>,
 }
 
 impl StoryProgress {
@@ -171,24 +188,39 @@ impl StoryProgress {
             started_at: None,
             completed_at: None,
             error: None,
+            skip_reason: None,
+            guardrails: Vec::new(),
         }
     }
 
     pub fn start(&mut self, worker_id: String) {
         self.status = StoryStatus::InProgress;
         self.worker_id = Some(worker_id);
-        self.started_at = Some(chrono_timestamp());
+        self.started_at = Some(now_ms());
     }
 
     pub fn complete(&mut self) {
         self.status = StoryStatus::Completed;
-        self.completed_at = Some(chrono_timestamp());
+        self.completed_at = Some(now_ms());
     }
 
     pub fn fail(&mut self, error: String) {
         self.status = StoryStatus::Failed;
         self.error = Some(error);
-        self.completed_at = Some(chrono_timestamp());
+        self.completed_at = Some(now_ms());
+    }
+
+    pub fn skip(&mut self, reason: Option<String>) {
+        self.status = StoryStatus::Skipped;
+        self.worker_id = None;
+        self.skip_reason = reason;
+        self.completed_at = Some(now_ms());
+    }
+
+    /// Hold the story back; it restarts from its current iteration
+    pub fn pause(&mut self) {
+        self.status = StoryStatus::Paused;
+        self.worker_id = None;
     }
 
     pub fn all_criteria_passed(&self) -> bool {
@@ -196,6 +228,13 @@ impl StoryProgress {
     }
 }
 
+/// Acceptance criteria verified at once, unless the PRD says otherwise
+pub const DEFAULT_CRITERIA_CONCURRENCY: u32 = 4;
+
+fn default_criteria_concurrency() -> u32 {
+    DEFAULT_CRITERIA_CONCURRENCY
+}
+
 /// PRD constraints
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -204,6 +243,10 @@ pub struct PrdConstraints {
     pub max_iterations_per_story: u32,
     pub total_timeout_minutes: Option<u32>,
     pub models: Option<ModelConstraints>,
+    /// Acceptance criteria of a story verified at once (0 verifies them one
+    /// by one)
+    #[serde(default = "default_criteria_concurrency")]
+    pub criteria_concurrency: u32,
 }
 
 impl Default for PrdConstraints {
@@ -213,6 +256,7 @@ impl Default for PrdConstraints {
             max_iterations_per_story: 15,
             total_timeout_minutes: Some(120),
             models: Some(ModelConstraints::default()),
+            criteria_concurrency: DEFAULT_CRITERIA_CONCURRENCY,
         }
     }
 }
@@ -283,24 +327,24 @@ impl RalphWorker {
         self.status = WorkerStatus::Working;
         self.current_story_id = Some(story_id);
         self.iteration = 1;
-        self.started_at = Some(chrono_timestamp());
-        self.last_activity_at = Some(chrono_timestamp());
+        self.started_at = Some(now_ms());
+        self.last_activity_at = Some(now_ms());
     }
 
     pub fn next_iteration(&mut self) {
         self.iteration += 1;
-        self.last_activity_at = Some(chrono_timestamp());
+        self.last_activity_at = Some(now_ms());
     }
 
     pub fn complete(&mut self) {
         self.status = WorkerStatus::Completed;
-        self.last_activity_at = Some(chrono_timestamp());
+        self.last_activity_at = Some(now_ms());
     }
 
     pub fn fail(&mut self, error: String) {
         self.status = WorkerStatus::Error;
         self.error = Some(error);
-        self.last_activity_at = Some(chrono_timestamp());
+        self.last_activity_at = Some(now_ms());
     }
 
     pub fn reset(&mut self) {
@@ -396,11 +440,11 @@ impl PrdSession {
                     return false;
                 }
 
-                // All dependencies must be completed
+                // All dependencies must be completed (or skipped)
                 story.dependencies.iter().all(|dep_id| {
                     self.story_progress
                         .get(dep_id)
-                        .map(|p| p.status == StoryStatus::Completed)
+                        .map(|p| p.status.satisfies_dependency())
                         .unwrap_or(false)
                 })
             })
@@ -414,10 +458,11 @@ impl PrdSession {
             .collect()
     }
 
+    /// Whether every story is completed or skipped
     pub fn all_stories_completed(&self) -> bool {
         self.story_progress
             .values()
-            .all(|p| p.status == StoryStatus::Completed)
+            .all(|p| p.status.satisfies_dependency())
     }
 
     pub fn any_story_failed(&self) -> bool {
@@ -500,6 +545,7 @@ pub struct PrdSessionSummary {
     pub active_workers: usize,
     pub total_cost: f64,
     pub started_at: Option<i64>,
+    pub completed_at: Option<i64>,
 }
 
 impl From<&PrdSession> for PrdSessionSummary {
@@ -525,13 +571,7 @@ impl From<&PrdSession> for PrdSessionSummary {
             active_workers,
             total_cost: session.total_cost,
             started_at: session.started_at,
+            completed_at: session.completed_at,
         }
     }
 }
-
-fn chrono_timestamp() -> i64 {
-    std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap()
-        .as_millis() as i64
-}
@@ -1,35 +1,169 @@
 //! Acceptance criteria verification
+//!
+//! Results can be cached across iterations: `file_exists` and `pattern`
+//! criteria are keyed by the content of the path they look at, `test` and
+//! `custom` criteria by a fingerprint of the git working tree, so they only
+//! rerun after files change (outside a git repository they always rerun).
+//! Criteria that do need to run are verified concurrently, up to the
+//! session's `criteria_concurrency` at a time.
 
-use super::types::{AcceptanceCriterion, CriterionStatus, CriterionType, Story};
-use std::path::Path;
-use std::process::Command;
+use super::progress::PROGRESS_FILE;
+use super::types::{
+    AcceptanceCriterion, CriterionStatus, CriterionType, Story, DEFAULT_CRITERIA_CONCURRENCY,
+};
+use crate::telemetry;
+use parking_lot::Mutex;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::time::{Instant, SystemTime};
+use tokio::task::JoinSet;
+
+/// Criterion results from earlier verifications
+#[derive(Default)]
+pub struct VerifierCache {
+    /// Criterion key -> (fingerprint of what it looked at, result)
+    entries: Mutex<HashMap<String, (u64, CriterionStatus)>>,
+}
+
+impl VerifierCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
 
 /// Verify a single acceptance criterion
 pub async fn verify_criterion(
     criterion: &AcceptanceCriterion,
     working_dir: Option<&Path>,
 ) -> CriterionStatus {
-    match criterion.criterion_type {
-        CriterionType::Test => verify_test(criterion, working_dir).await,
-        CriterionType::FileExists => verify_file_exists(criterion, working_dir),
-        CriterionType::Pattern => verify_pattern(criterion, working_dir),
-        CriterionType::Custom => verify_custom(criterion, working_dir).await,
+    let started = SystemTime::now();
+    let timer = Instant::now();
+    let (kind, status) = match criterion.criterion_type {
+        CriterionType::Test => ("test", verify_test(criterion, working_dir).await),
+        CriterionType::FileExists => ("file_exists", verify_file_exists(criterion, working_dir)),
+        CriterionType::Pattern => ("pattern", verify_pattern(criterion, working_dir)),
+        CriterionType::Custom => ("custom", verify_custom(criterion, working_dir).await),
+    };
+    telemetry::verifier_run(kind, status.passed, started, timer.elapsed());
+    status
+}
+
+/// Verify a list of acceptance criteria, results in their order
+pub async fn verify_criteria(
+    criteria: &[AcceptanceCriterion],
+    working_dir: Option<&Path>,
+) -> Vec<CriterionStatus> {
+    verify_concurrently(criteria.to_vec(), working_dir, DEFAULT_CRITERIA_CONCURRENCY).await
+}
+
+/// Verify criteria with up to `concurrency` of them running at once (0
+/// runs them one by one), returning results in the criteria's order
+async fn verify_concurrently(
+    criteria: Vec<AcceptanceCriterion>,
+    working_dir: Option<&Path>,
+    concurrency: u32,
+) -> Vec<CriterionStatus> {
+    let limit = concurrency.max(1) as usize;
+    let working_dir = working_dir.map(Path::to_path_buf);
+    let mut results = vec![None; criteria.len()];
+    let mut pending = criteria.into_iter().enumerate();
+    let mut running = JoinSet::new();
+
+    loop {
+        while running.len() < limit {
+            let Some((i, criterion)) = pending.next() else {
+                break;
+            };
+            let dir = working_dir.clone();
+            running.spawn(async move { (i, verify_criterion(&criterion, dir.as_deref()).await) });
+        }
+        let Some(joined) = running.join_next().await else {
+            break;
+        };
+        match joined {
+            Ok((i, status)) => results[i] = Some(status),
+            Err(e) => eprintln!("[PRD] Criterion verification task failed: {}", e),
+        }
     }
+
+    results
+        .into_iter()
+        .map(|status| {
+            status.unwrap_or_else(|| CriterionStatus::failed("Verification task failed".to_string()))
+        })
+        .collect()
 }
 
-/// Verify all acceptance criteria for a story
-pub async fn verify_all_criteria(
+/// Verify a story's criteria, reusing cached results for criteria whose
+/// inputs haven't changed and running the rest `concurrency` at a time.
+/// `force` reruns everything (and refreshes the cache).
+pub async fn verify_all_criteria_cached(
     story: &Story,
     working_dir: Option<&Path>,
+    cache: &VerifierCache,
+    concurrency: u32,
+    force: bool,
 ) -> Vec<CriterionStatus> {
-    let mut results = Vec::new();
+    let dir = working_dir.unwrap_or(Path::new("."));
+    // Computed at most once, and only if a test or custom criterion needs it
+    let mut tree: Option<Option<u64>> = None;
+    let mut results = vec![None; story.acceptance_criteria.len()];
+    // Criteria to run: index, and cache key + fingerprint when cacheable
+    let mut to_run = Vec::new();
+
+    for (i, criterion) in story.acceptance_criteria.iter().enumerate() {
+        let fingerprint = match criterion.criterion_type {
+            CriterionType::FileExists | CriterionType::Pattern => {
+                Some(path_fingerprint(criterion, working_dir))
+            }
+            CriterionType::Test | CriterionType::Custom => {
+                *tree.get_or_insert_with(|| tree_fingerprint(dir))
+            }
+        };
+        let Some(fingerprint) = fingerprint else {
+            to_run.push((i, None));
+            continue;
+        };
 
-    for criterion in &story.acceptance_criteria {
-        let status = verify_criterion(criterion, working_dir).await;
-        results.push(status);
+        let key = format!(
+            "{}\0{}",
+            dir.display(),
+            serde_json::to_string(criterion).unwrap_or_default()
+        );
+        if !force {
+            let cached = cache
+                .entries
+                .lock()
+                .get(&key)
+                .filter(|(f, _)| *f == fingerprint)
+                .map(|(_, status)| status.clone());
+            if cached.is_some() {
+                results[i] = cached;
+                continue;
+            }
+        }
+        to_run.push((i, Some((key, fingerprint))));
     }
 
-    results
+    let criteria = to_run
+        .iter()
+        .map(|(i, _)| story.acceptance_criteria[*i].clone())
+        .collect();
+    let statuses = verify_concurrently(criteria, working_dir, concurrency).await;
+    for ((i, cache_key), status) in to_run.into_iter().zip(statuses) {
+        if let Some((key, fingerprint)) = cache_key {
+            cache
+                .entries
+                .lock()
+                .insert(key, (fingerprint, status.clone()));
+        }
+        results[i] = Some(status);
+    }
+
+    results.into_iter().flatten().collect()
 }
 
 /// Check if all criteria pass
@@ -37,6 +171,83 @@ pub fn all_criteria_pass(statuses: &[CriterionStatus]) -> bool {
     statuses.iter().all(|s| s.passed)
 }
 
+/// File a `file_exists` or `pattern` criterion looks at
+fn criterion_path(criterion: &AcceptanceCriterion, working_dir: Option<&Path>) -> Option<PathBuf> {
+    let path = match criterion.criterion_type {
+        CriterionType::FileExists => criterion.path.as_ref()?,
+        CriterionType::Pattern => criterion.file.as_ref()?,
+        _ => return None,
+    };
+    Some(match working_dir {
+        Some(dir) if !Path::new(path).is_absolute() => dir.join(path),
+        _ => PathBuf::from(path),
+    })
+}
+
+/// Hash of whether a criterion's file exists and what it contains
+fn path_fingerprint(criterion: &AcceptanceCriterion, working_dir: Option<&Path>) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    if let Some(path) = criterion_path(criterion, working_dir) {
+        path.exists().hash(&mut hasher);
+        if criterion.criterion_type == CriterionType::Pattern {
+            std::fs::read(&path).ok().hash(&mut hasher);
+        }
+    }
+    hasher.finish()
+}
+
+/// Hash of the working tree's state: HEAD, the index, and the size and
+/// modification time of every changed or untracked file. None outside a
+/// git repository.
+fn tree_fingerprint(dir: &Path) -> Option<u64> {
+    let git = |args: &[&str]| {
+        Command::new("git")
+            .current_dir(dir)
+            .args(args)
+            .output()
+            .ok()
+            .filter(|out| out.status.success())
+            .map(|out| out.stdout)
+    };
+
+    let changed = git(&["ls-files", "-z", "--modified", "--others", "--exclude-standard"])?;
+    let mut hasher = DefaultHasher::new();
+    git(&["rev-parse", "HEAD"]).hash(&mut hasher);
+    git(&["diff", "--cached", "--raw", "-z"]).hash(&mut hasher);
+
+    for file in changed.split(|b| *b == 0).filter(|f| !f.is_empty()) {
+        let file = String::from_utf8_lossy(file);
+        // Rewritten after every iteration; not something criteria test
+        if file == PROGRESS_FILE {
+            continue;
+        }
+        file.hash(&mut hasher);
+        if let Ok(meta) = std::fs::metadata(dir.join(file.as_ref())) {
+            meta.len().hash(&mut hasher);
+            meta.modified().ok().hash(&mut hasher);
+        }
+    }
+    Some(hasher.finish())
+}
+
+/// Run a command through the platform shell. The process is killed if
+/// verification is abandoned.
+async fn run_shell(command: &str, working_dir: Option<&Path>) -> std::io::Result<Output> {
+    let mut cmd = if cfg!(target_os = "windows") {
+        let mut cmd = tokio::process::Command::new("cmd");
+        cmd.args(["/C", command]);
+        cmd
+    } else {
+        let mut cmd = tokio::process::Command::new("sh");
+        cmd.args(["-c", command]);
+        cmd
+    };
+    if let Some(dir) = working_dir {
+        cmd.current_dir(dir);
+    }
+    cmd.kill_on_drop(true).output().await
+}
+
 /// Verify a test criterion (run command, check exit code)
 async fn verify_test(
     criterion: &AcceptanceCriterion,
@@ -47,22 +258,7 @@ async fn verify_test(
         None => return CriterionStatus::failed("No command specified".to_string()),
     };
 
-    // Parse command - handle shell commands
-    let output = if cfg!(target_os = "windows") {
-        let mut cmd = Command::new("cmd");
-        cmd.args(["/C", command]);
-        if let Some(dir) = working_dir {
-            cmd.current_dir(dir);
-        }
-        cmd.output()
-    } else {
-        let mut cmd = Command::new("sh");
-        cmd.args(["-c", command]);
-        if let Some(dir) = working_dir {
-            cmd.current_dir(dir);
-        }
-        cmd.output()
-    };
+    let output = run_shell(command, working_dir).await;
 
     match output {
         Ok(out) => {
@@ -168,21 +364,7 @@ async fn verify_custom(
     };
 
     // Execute script through shell
-    let output = if cfg!(target_os = "windows") {
-        let mut cmd = Command::new("cmd");
-        cmd.args(["/C", script]);
-        if let Some(dir) = working_dir {
-            cmd.current_dir(dir);
-        }
-        cmd.output()
-    } else {
-        let mut cmd = Command::new("sh");
-        cmd.args(["-c", script]);
-        if let Some(dir) = working_dir {
-            cmd.current_dir(dir);
-        }
-        cmd.output()
-    };
+    let output = run_shell(script, working_dir).await;
 
     match output {
         Ok(out) => {
@@ -204,6 +386,132 @@ async fn verify_custom(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use tempfile::TempDir;
+
+    fn story(criteria: Vec<AcceptanceCriterion>) -> Story {
+        Story {
+            id: "s1".to_string(),
+            title: "Story".to_string(),
+            description: "Do something".to_string(),
+            acceptance_criteria: criteria,
+            dependencies: vec![],
+            hints: None,
+            complexity: None,
+            model: None,
+        }
+    }
+
+    fn pattern(file: &str, pattern: &str) -> AcceptanceCriterion {
+        AcceptanceCriterion {
+            criterion_type: CriterionType::Pattern,
+            command: None,
+            path: None,
+            file: Some(file.to_string()),
+            pattern: Some(pattern.to_string()),
+            script: None,
+            description: None,
+        }
+    }
+
+    /// A test criterion that counts its runs in `counter`
+    fn counting_test(counter: &Path) -> AcceptanceCriterion {
+        AcceptanceCriterion {
+            criterion_type: CriterionType::Test,
+            command: Some(format!("echo run >> '{}'", counter.display())),
+            path: None,
+            file: None,
+            pattern: None,
+            script: None,
+            description: None,
+        }
+    }
+
+    fn runs(counter: &Path) -> usize {
+        std::fs::read_to_string(counter).map(|s| s.lines().count()).unwrap_or(0)
+    }
+
+    #[tokio::test]
+    async fn test_verify_concurrently_keeps_order() {
+        let test = |command: &str| AcceptanceCriterion {
+            criterion_type: CriterionType::Test,
+            command: Some(command.to_string()),
+            path: None,
+            file: None,
+            pattern: None,
+            script: None,
+            description: None,
+        };
+        let criteria = vec![
+            test("sleep 0.5; false"),
+            test("sleep 0.5; true"),
+            test("sleep 0.5; exit 3"),
+        ];
+
+        let started = std::time::Instant::now();
+        let statuses = verify_concurrently(criteria, None, 3).await;
+        assert!(started.elapsed() < std::time::Duration::from_millis(1200));
+        assert_eq!(
+            statuses.iter().map(|s| s.passed).collect::<Vec<_>>(),
+            vec![false, true, false]
+        );
+        assert!(statuses[2].error.as_deref().unwrap().contains("Some(3)"));
+    }
+
+    #[tokio::test]
+    async fn test_cached_pattern_follows_file_content() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("lib.rs"), "fn old() {}").unwrap();
+        let story = story(vec![pattern("lib.rs", "fn new")]);
+        let cache = VerifierCache::new();
+
+        let first = verify_all_criteria_cached(&story, Some(dir.path()), &cache, 1, false).await;
+        assert!(!first[0].passed);
+        let again = verify_all_criteria_cached(&story, Some(dir.path()), &cache, 1, false).await;
+        assert_eq!(again[0].last_checked, first[0].last_checked);
+
+        std::fs::write(dir.path().join("lib.rs"), "fn new() {}").unwrap();
+        let changed = verify_all_criteria_cached(&story, Some(dir.path()), &cache, 1, false).await;
+        assert!(changed[0].passed);
+    }
+
+    #[tokio::test]
+    async fn test_cached_test_reruns_after_changes() {
+        let dir = TempDir::new().unwrap();
+        let counter_dir = TempDir::new().unwrap();
+        let counter = counter_dir.path().join("runs");
+        let story = story(vec![counting_test(&counter)]);
+        let cache = VerifierCache::new();
+
+        // Outside git there's nothing to key on: always rerun
+        verify_all_criteria_cached(&story, Some(dir.path()), &cache, 1, false).await;
+        verify_all_criteria_cached(&story, Some(dir.path()), &cache, 1, false).await;
+        assert_eq!(runs(&counter), 2);
+
+        let initialized = Command::new("git")
+            .current_dir(dir.path())
+            .args(["init", "-q"])
+            .status()
+            .is_ok_and(|s| s.success());
+        if !initialized {
+            return;
+        }
+        verify_all_criteria_cached(&story, Some(dir.path()), &cache, 1, false).await;
+        verify_all_criteria_cached(&story, Some(dir.path()), &cache, 1, false).await;
+        assert_eq!(runs(&counter), 3);
+
+        // The progress file doesn't count as a change
+        std::fs::create_dir(dir.path().join("prd")).unwrap();
+        std::fs::write(dir.path().join(PROGRESS_FILE), "# PRD Progress").unwrap();
+        verify_all_criteria_cached(&story, Some(dir.path()), &cache, 1, false).await;
+        assert_eq!(runs(&counter), 3);
+
+        std::fs::write(dir.path().join("main.rs"), "fn main() {}").unwrap();
+        verify_all_criteria_cached(&story, Some(dir.path()), &cache, 1, false).await;
+        assert_eq!(runs(&counter), 4);
+
+        verify_all_criteria_cached(&story, Some(dir.path()), &cache, 1, true).await;
+        assert_eq!(runs(&counter), 5);
+    }
 
     #[tokio::test]
     async fn test_verify_file_exists_pass() {
@@ -0,0 +1,36 @@
+use super::proxy::{self, PreviewInfo, ProxyRequest, RouteSummary};
+use tauri::AppHandle;
+
+/// Start a proxy in front of a local dev server (e.g. one from
+/// `list_detected_servers`) for the preview panel
+#[tauri::command]
+pub async fn start_preview(
+    app_handle: AppHandle,
+    target: String,
+    session_id: Option<String>,
+) -> Result<PreviewInfo, String> {
+    proxy::start(app_handle, &target, session_id).await
+}
+
+#[tauri::command]
+pub fn stop_preview(id: String) -> bool {
+    proxy::stop(&id)
+}
+
+#[tauri::command]
+pub fn list_previews(session_id: Option<String>) -> Vec<PreviewInfo> {
+    proxy::list(session_id.as_deref())
+}
+
+/// Requests made through a preview, oldest first
+#[tauri::command]
+pub fn get_preview_requests(id: String) -> Result<Vec<ProxyRequest>, String> {
+    proxy::requests(&id).ok_or_else(|| format!("Preview not found: {}", id))
+}
+
+/// The routes requested through a preview, most requested first
+#[tauri::command]
+pub fn get_preview_routes(id: String) -> Result<Vec<RouteSummary>, String> {
+    let requests = proxy::requests(&id).ok_or_else(|| format!("Preview not found: {}", id))?;
+    Ok(proxy::summarize_routes(&requests))
+}
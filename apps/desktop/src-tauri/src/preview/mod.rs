@@ -0,0 +1,14 @@
+//! Preview proxy
+//!
+//! `start_preview` fronts a dev server found in terminal output (see
+//! `acp::dev_servers`) with a reverse proxy on a port of its own, bound to
+//! 127.0.0.1. The preview panel loads the proxy rather than the server, and
+//! every request through it is logged and reported as a "preview-request"
+//! event, so a session keeps a record of the routes exercised while its
+//! work was checked. WebSocket upgrades (hot reload) are passed through.
+//! Only servers on this machine can be proxied.
+
+pub mod commands;
+mod proxy;
+
+pub use proxy::stop_session;
@@ -0,0 +1,490 @@
+use crate::events::{emit, PreviewRequestEvent};
+use crate::time::now_ms;
+use axum::body::Body;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{FromRequestParts, Request, State};
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Router;
+use futures::{SinkExt, StreamExt};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::Instant;
+use tauri::AppHandle;
+use tokio::net::TcpListener;
+use tokio::sync::watch;
+use tokio_tungstenite::tungstenite;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use uuid::Uuid;
+
+/// Requests kept per preview
+const LOG_LIMIT: usize = 1000;
+
+/// Hosts a preview may point at
+const LOCAL_HOSTS: &[&str] = &["localhost", "127.0.0.1", "[::1]"];
+
+/// Headers about one connection, which aren't forwarded
+const HOP_BY_HOP: &[&str] = &[
+    "connection",
+    "host",
+    "keep-alive",
+    "proxy-authenticate",
+    "proxy-authorization",
+    "te",
+    "trailer",
+    "transfer-encoding",
+    "upgrade",
+];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PreviewInfo {
+    pub id: String,
+    pub session_id: Option<String>,
+    /// The dev server, e.g. "http://localhost:5173"
+    pub target: String,
+    /// What the preview panel loads, e.g. "http://127.0.0.1:51234"
+    pub url: String,
+    pub started_at: i64,
+}
+
+/// One request through a preview
+#[derive(Debug, Clone, Serialize)]
+pub struct ProxyRequest {
+    pub method: String,
+    /// Path and query
+    pub path: String,
+    /// 502 when the dev server couldn't be reached
+    pub status: u16,
+    pub duration_ms: u64,
+    pub at: i64,
+    pub websocket: bool,
+}
+
+/// Requests to one route (method and path, without the query)
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct RouteSummary {
+    pub method: String,
+    pub path: String,
+    pub count: usize,
+    pub last_status: u16,
+    pub last_at: i64,
+}
+
+struct RunningPreview {
+    info: PreviewInfo,
+    log: Arc<Mutex<VecDeque<ProxyRequest>>>,
+    /// Dropping the sender shuts the proxy down
+    _shutdown: watch::Sender<()>,
+}
+
+static PREVIEWS: Lazy<Mutex<HashMap<String, RunningPreview>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Clone)]
+struct ProxyState {
+    app: AppHandle,
+    info: PreviewInfo,
+    client: reqwest::Client,
+    log: Arc<Mutex<VecDeque<ProxyRequest>>>,
+}
+
+/// The origin of a local http(s) URL, e.g. "http://localhost:5173"
+fn local_origin(target: &str) -> Result<String, String> {
+    let url = reqwest::Url::parse(target).map_err(|e| format!("Invalid URL {}: {}", target, e))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(format!("Can't preview {} URLs", url.scheme()));
+    }
+    let host = url.host_str().unwrap_or_default();
+    if !LOCAL_HOSTS.contains(&host) {
+        return Err(format!("Only local servers can be previewed, not {}", host));
+    }
+    Ok(url.origin().ascii_serialization())
+}
+
+/// Request or response headers minus the hop-by-hop ones
+fn forward_headers(headers: &HeaderMap) -> HeaderMap {
+    let mut forwarded = headers.clone();
+    for name in HOP_BY_HOP {
+        forwarded.remove(*name);
+    }
+    forwarded
+}
+
+/// Point redirects to the dev server back at the proxy
+fn rewrite_location(headers: &mut HeaderMap, target: &str, url: &str) {
+    let Some(location) = headers
+        .get(header::LOCATION)
+        .and_then(|value| value.to_str().ok())
+    else {
+        return;
+    };
+    if let Some(rest) = location.strip_prefix(target) {
+        if let Ok(value) = HeaderValue::from_str(&format!("{}{}", url, rest)) {
+            headers.insert(header::LOCATION, value);
+        }
+    }
+}
+
+fn record(state: &ProxyState, request: ProxyRequest) {
+    {
+        let mut log = state.log.lock();
+        if log.len() == LOG_LIMIT {
+            log.pop_front();
+        }
+        log.push_back(request.clone());
+    }
+    emit(
+        &state.app,
+        &PreviewRequestEvent {
+            preview_id: state.info.id.clone(),
+            session_id: state.info.session_id.clone(),
+            method: request.method,
+            path: request.path,
+            status: request.status,
+            duration_ms: request.duration_ms,
+        },
+    );
+}
+
+async fn forward(state: &ProxyState, request: Request, path: &str) -> Result<Response, String> {
+    let (parts, body) = request.into_parts();
+    let mut headers = forward_headers(&parts.headers);
+    // Dev servers check Origin on some requests; make it theirs
+    if headers.contains_key(header::ORIGIN) {
+        if let Ok(origin) = HeaderValue::from_str(&state.info.target) {
+            headers.insert(header::ORIGIN, origin);
+        }
+    }
+
+    let response = state
+        .client
+        .request(parts.method, format!("{}{}", state.info.target, path))
+        .headers(headers)
+        .body(reqwest::Body::wrap_stream(body.into_data_stream()))
+        .send()
+        .await
+        .map_err(|e| format!("{} is not reachable: {}", state.info.target, e))?;
+
+    let status = response.status();
+    let mut headers = forward_headers(response.headers());
+    rewrite_location(&mut headers, &state.info.target, &state.info.url);
+    let mut proxied = Response::new(Body::from_stream(response.bytes_stream()));
+    *proxied.status_mut() = status;
+    *proxied.headers_mut() = headers;
+    Ok(proxied)
+}
+
+fn to_server(message: Message) -> tungstenite::Message {
+    match message {
+        Message::Text(text) => tungstenite::Message::Text(text.as_str().to_string().into()),
+        Message::Binary(data) => tungstenite::Message::Binary(data),
+        Message::Ping(data) => tungstenite::Message::Ping(data),
+        Message::Pong(data) => tungstenite::Message::Pong(data),
+        Message::Close(_) => tungstenite::Message::Close(None),
+    }
+}
+
+fn to_client(message: tungstenite::Message) -> Option<Message> {
+    Some(match message {
+        tungstenite::Message::Text(text) => Message::Text(text.as_str().to_string().into()),
+        tungstenite::Message::Binary(data) => Message::Binary(data),
+        tungstenite::Message::Ping(data) => Message::Ping(data),
+        tungstenite::Message::Pong(data) => Message::Pong(data),
+        tungstenite::Message::Close(_) => Message::Close(None),
+        tungstenite::Message::Frame(_) => return None,
+    })
+}
+
+/// Relay a WebSocket between the preview and the dev server until either
+/// side closes
+async fn pipe_websocket(
+    client: WebSocket,
+    target: String,
+    protocols: Vec<String>,
+) -> Result<(), String> {
+    let mut request = target
+        .as_str()
+        .into_client_request()
+        .map_err(|e| e.to_string())?;
+    if !protocols.is_empty() {
+        let value = HeaderValue::from_str(&protocols.join(", ")).map_err(|e| e.to_string())?;
+        request
+            .headers_mut()
+            .insert(header::SEC_WEBSOCKET_PROTOCOL, value);
+    }
+    let (server, _) = tokio_tungstenite::connect_async(request)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let (mut server_tx, mut server_rx) = server.split();
+    let (mut client_tx, mut client_rx) = client.split();
+    let upstream = async {
+        while let Some(Ok(message)) = client_rx.next().await {
+            if server_tx.send(to_server(message)).await.is_err() {
+                break;
+            }
+        }
+    };
+    let downstream = async {
+        while let Some(Ok(message)) = server_rx.next().await {
+            let Some(message) = to_client(message) else {
+                continue;
+            };
+            if client_tx.send(message).await.is_err() {
+                break;
+            }
+        }
+    };
+    tokio::select! {
+        _ = upstream => {}
+        _ = downstream => {}
+    }
+    Ok(())
+}
+
+fn websocket(
+    upgrade: WebSocketUpgrade,
+    state: &ProxyState,
+    path: &str,
+    headers: &HeaderMap,
+) -> Response {
+    let protocols: Vec<String> = headers
+        .get(header::SEC_WEBSOCKET_PROTOCOL)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| {
+            value
+                .split(',')
+                .map(|p| p.trim().to_string())
+                .filter(|p| !p.is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+    let target = format!("ws{}{}", state.info.target.trim_start_matches("http"), path);
+    upgrade
+        .protocols(protocols.clone())
+        .on_upgrade(move |socket| async move {
+            if let Err(e) = pipe_websocket(socket, target.clone(), protocols).await {
+                eprintln!("[Preview] WebSocket {}: {}", target, e);
+            }
+        })
+}
+
+async fn proxy(State(state): State<ProxyState>, request: Request) -> Response {
+    let started = Instant::now();
+    let method = request.method().to_string();
+    let path = request
+        .uri()
+        .path_and_query()
+        .map(|p| p.as_str().to_string())
+        .unwrap_or_else(|| "/".to_string());
+    let is_websocket = request
+        .headers()
+        .get(header::UPGRADE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.eq_ignore_ascii_case("websocket"));
+
+    let response = if is_websocket {
+        let (mut parts, _) = request.into_parts();
+        match WebSocketUpgrade::from_request_parts(&mut parts, &state).await {
+            Ok(upgrade) => websocket(upgrade, &state, &path, &parts.headers),
+            Err(rejection) => rejection.into_response(),
+        }
+    } else {
+        forward(&state, request, &path)
+            .await
+            .unwrap_or_else(|e| (StatusCode::BAD_GATEWAY, e).into_response())
+    };
+
+    record(
+        &state,
+        ProxyRequest {
+            method,
+            path,
+            status: response.status().as_u16(),
+            duration_ms: started.elapsed().as_millis() as u64,
+            at: now_ms(),
+            websocket: is_websocket,
+        },
+    );
+    response
+}
+
+/// Start a proxy in front of a local dev server. A preview already running
+/// for the same server and session is returned as is.
+pub async fn start(
+    app: AppHandle,
+    target: &str,
+    session_id: Option<String>,
+) -> Result<PreviewInfo, String> {
+    let target = local_origin(target)?;
+    if let Some(existing) = PREVIEWS
+        .lock()
+        .values()
+        .find(|p| p.info.target == target && p.info.session_id == session_id)
+    {
+        return Ok(existing.info.clone());
+    }
+
+    let listener = TcpListener::bind(("127.0.0.1", 0))
+        .await
+        .map_err(|e| format!("Failed to bind a preview port: {}", e))?;
+    let address = listener
+        .local_addr()
+        .map_err(|e| format!("Failed to read preview address: {}", e))?;
+    let client = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .map_err(|e| format!("Failed to create preview client: {}", e))?;
+
+    let info = PreviewInfo {
+        id: Uuid::new_v4().to_string(),
+        session_id,
+        target,
+        url: format!("http://{}", address),
+        started_at: now_ms(),
+    };
+    let log = Arc::new(Mutex::new(VecDeque::new()));
+    let router = Router::new().fallback(proxy).with_state(ProxyState {
+        app,
+        info: info.clone(),
+        client,
+        log: log.clone(),
+    });
+
+    let (shutdown_tx, mut shutdown_rx) = watch::channel(());
+    let id = info.id.clone();
+    tauri::async_runtime::spawn(async move {
+        let result = axum::serve(listener, router)
+            .with_graceful_shutdown(async move {
+                let _ = shutdown_rx.changed().await;
+            })
+            .await;
+        if let Err(e) = result {
+            eprintln!("[Preview] Proxy error: {}", e);
+            PREVIEWS.lock().remove(&id);
+        }
+    });
+
+    eprintln!("[Preview] {} -> {}", info.url, info.target);
+    PREVIEWS.lock().insert(
+        info.id.clone(),
+        RunningPreview {
+            info: info.clone(),
+            log,
+            _shutdown: shutdown_tx,
+        },
+    );
+    Ok(info)
+}
+
+/// Stop a preview. Returns false if it wasn't running.
+pub fn stop(id: &str) -> bool {
+    PREVIEWS.lock().remove(id).is_some()
+}
+
+/// Stop a session's previews
+pub fn stop_session(session_id: &str) {
+    PREVIEWS
+        .lock()
+        .retain(|_, p| p.info.session_id.as_deref() != Some(session_id));
+}
+
+/// Running previews, optionally only a session's
+pub fn list(session_id: Option<&str>) -> Vec<PreviewInfo> {
+    let mut previews: Vec<PreviewInfo> = PREVIEWS
+        .lock()
+        .values()
+        .filter(|p| session_id.map_or(true, |id| p.info.session_id.as_deref() == Some(id)))
+        .map(|p| p.info.clone())
+        .collect();
+    previews.sort_by_key(|p| p.started_at);
+    previews
+}
+
+pub fn requests(id: &str) -> Option<Vec<ProxyRequest>> {
+    let previews = PREVIEWS.lock();
+    let log = previews.get(id)?.log.lock();
+    Some(log.iter().cloned().collect())
+}
+
+/// Group requests by method and path, most requested first
+pub fn summarize_routes(requests: &[ProxyRequest]) -> Vec<RouteSummary> {
+    let mut routes: BTreeMap<(String, String), RouteSummary> = BTreeMap::new();
+    for request in requests {
+        let path = request.path.split('?').next().unwrap_or_default().to_string();
+        let route = routes
+            .entry((request.method.clone(), path.clone()))
+            .or_insert_with(|| RouteSummary {
+                method: request.method.clone(),
+                path,
+                count: 0,
+                last_status: request.status,
+                last_at: request.at,
+            });
+        route.count += 1;
+        if request.at >= route.last_at {
+            route.last_status = request.status;
+            route.last_at = request.at;
+        }
+    }
+    let mut routes: Vec<RouteSummary> = routes.into_values().collect();
+    routes.sort_by(|a, b| b.count.cmp(&a.count));
+    routes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_local_origin() {
+        assert_eq!(
+            local_origin("http://localhost:5173/app?x=1").unwrap(),
+            "http://localhost:5173"
+        );
+        assert_eq!(
+            local_origin("http://127.0.0.1:8000").unwrap(),
+            "http://127.0.0.1:8000"
+        );
+        assert!(local_origin("http://example.com:3000").is_err());
+        assert!(local_origin("file:///etc/passwd").is_err());
+    }
+
+    #[test]
+    fn test_rewrite_location() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::LOCATION,
+            HeaderValue::from_static("http://localhost:3000/login?next=/"),
+        );
+        rewrite_location(&mut headers, "http://localhost:3000", "http://127.0.0.1:4100");
+        assert_eq!(
+            headers[header::LOCATION],
+            "http://127.0.0.1:4100/login?next=/"
+        );
+    }
+
+    #[test]
+    fn test_summarize_routes() {
+        let request = |path: &str, status: u16, at: i64| ProxyRequest {
+            method: "GET".to_string(),
+            path: path.to_string(),
+            status,
+            duration_ms: 1,
+            at,
+            websocket: false,
+        };
+        let routes = summarize_routes(&[
+            request("/", 200, 1),
+            request("/api/items?page=1", 500, 2),
+            request("/api/items?page=2", 200, 3),
+        ]);
+        assert_eq!(routes[0].path, "/api/items");
+        assert_eq!(routes[0].count, 2);
+        assert_eq!(routes[0].last_status, 200);
+        assert_eq!(routes[1].path, "/");
+    }
+}
@@ -1,4 +1,8 @@
-use super::terminal::TERMINAL_MANAGER;
+use super::recording::{self, RecordingInfo};
+use super::service::{self, ServiceInfo, ServiceOutput};
+use super::signal::{signal_tree, TerminalSignal};
+use super::terminal::{TerminalAttachment, TerminalInfo, TerminalOptions, TERMINAL_MANAGER};
+use crate::acp::resource_limits;
 use tauri::AppHandle;
 
 #[tauri::command]
@@ -7,9 +11,10 @@ pub fn spawn_terminal(
     cols: u16,
     rows: u16,
     cwd: Option<String>,
+    options: Option<TerminalOptions>,
 ) -> Result<String, String> {
     let mut manager = TERMINAL_MANAGER.lock();
-    manager.create(app_handle, cols, rows, cwd)
+    manager.create(app_handle, cols, rows, cwd, options.unwrap_or_default())
 }
 
 #[tauri::command]
@@ -20,7 +25,7 @@ pub fn write_terminal(id: String, data: String) -> Result<(), String> {
 
 #[tauri::command]
 pub fn resize_terminal(id: String, cols: u16, rows: u16) -> Result<(), String> {
-    let manager = TERMINAL_MANAGER.lock();
+    let mut manager = TERMINAL_MANAGER.lock();
     manager.resize(&id, cols, rows)
 }
 
@@ -29,3 +34,104 @@ pub fn kill_terminal(id: String) -> Result<(), String> {
     let mut manager = TERMINAL_MANAGER.lock();
     manager.kill(&id)
 }
+
+/// Send SIGINT or SIGTERM to a terminal, whether opened with
+/// `spawn_terminal` or created by an agent
+#[tauri::command]
+pub fn signal_terminal(terminal_id: String, signal: TerminalSignal) -> Result<(), String> {
+    {
+        let manager = TERMINAL_MANAGER.lock();
+        if manager.contains(&terminal_id) {
+            return manager.signal(&terminal_id, signal);
+        }
+    }
+    let pid = resource_limits::terminal_pid(&terminal_id)
+        .ok_or_else(|| format!("Terminal not found: {}", terminal_id))?;
+    signal_tree(pid, signal).map(|_| ())
+}
+
+/// List running terminals, optionally only those owned by a session
+#[tauri::command]
+pub fn list_terminals(session_id: Option<String>) -> Vec<TerminalInfo> {
+    let manager = TERMINAL_MANAGER.lock();
+    manager.list(session_id.as_deref())
+}
+
+/// Get a terminal's metadata and scrollback so another view can reattach
+#[tauri::command]
+pub fn attach_terminal(id: String) -> Result<TerminalAttachment, String> {
+    let manager = TERMINAL_MANAGER.lock();
+    manager.attach(&id)
+}
+
+#[tauri::command]
+pub fn set_terminal_keep_alive(id: String, keep_alive: bool) -> Result<(), String> {
+    let mut manager = TERMINAL_MANAGER.lock();
+    manager.set_keep_alive(&id, keep_alive)
+}
+
+/// Kill a session's terminals (keep-alive terminals are left running)
+#[tauri::command]
+pub fn close_session_terminals(session_id: String) -> Vec<String> {
+    let mut manager = TERMINAL_MANAGER.lock();
+    manager.close_session(&session_id)
+}
+
+/// Start a service terminal for a session. Without `command` the service
+/// must be declared in the project's terminal config; with one it replaces
+/// the declared command.
+#[tauri::command]
+pub fn start_service(
+    app_handle: AppHandle,
+    session_id: String,
+    name: String,
+    project_dir: String,
+    command: Option<String>,
+) -> Result<ServiceInfo, String> {
+    let spec = service::resolve_spec(&project_dir, &name, command)?;
+    service::start(&app_handle, &session_id, &name, spec, &project_dir)
+}
+
+#[tauri::command]
+pub fn stop_service(id: String) -> Result<ServiceInfo, String> {
+    service::stop(&id)
+}
+
+#[tauri::command]
+pub fn restart_service(app_handle: AppHandle, id: String) -> Result<ServiceInfo, String> {
+    service::restart(&app_handle, &id)
+}
+
+/// List service terminals, optionally only a session's
+#[tauri::command]
+pub fn list_services(session_id: Option<String>) -> Vec<ServiceInfo> {
+    service::list(session_id.as_deref())
+}
+
+#[tauri::command]
+pub fn get_service_output(id: String) -> Result<ServiceOutput, String> {
+    service::output(&id).ok_or_else(|| format!("Service not found: {}", id))
+}
+
+/// Start recording a terminal to an asciicast file. Ids not owned by the
+/// terminal manager (agent-created terminals) are recorded at 80x24.
+#[tauri::command]
+pub fn start_terminal_recording(id: String) -> Result<RecordingInfo, String> {
+    let manager = TERMINAL_MANAGER.lock();
+    if manager.list(None).iter().any(|t| t.id == id) {
+        manager.start_recording(&id)
+    } else {
+        recording::start(&id, None, 80, 24, None)
+    }
+}
+
+#[tauri::command]
+pub fn stop_terminal_recording(id: String) -> Result<RecordingInfo, String> {
+    recording::stop(&id).ok_or_else(|| format!("Terminal {} is not being recorded", id))
+}
+
+/// Copy a recording to a user-chosen file or directory
+#[tauri::command]
+pub fn export_recording(path: String, destination: String) -> Result<String, String> {
+    recording::export(&path, &destination)
+}
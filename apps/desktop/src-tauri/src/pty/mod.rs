@@ -1,2 +1,6 @@
 pub mod commands;
-mod terminal;
+pub(crate) mod profile;
+pub mod recording;
+pub mod service;
+pub mod signal;
+pub(crate) mod terminal;
@@ -0,0 +1,186 @@
+//! Terminal shell/environment profiles
+//!
+//! A project can define terminal defaults and named profiles in
+//! `{project}/.crafter-code/terminal.json`:
+//!
+//! ```json
+//! {
+//!   "default": { "shell": "fish", "env": { "RUST_LOG": "debug" } },
+//!   "profiles": {
+//!     "tests": { "startup_commands": ["cargo watch -x test"] }
+//!   },
+//!   "services": {
+//!     "web": { "command": "pnpm dev", "ready_pattern": "Local:", "autostart": true }
+//!   }
+//! }
+//! ```
+//!
+//! Values passed to `spawn_terminal` override the named profile, which
+//! overrides the project default. Without a shell anywhere, the user's login
+//! shell is used. `services` declares service terminals (see `service`).
+
+use super::service::ServiceSpec;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Project terminal config file, relative to the project root
+pub const PROJECT_CONFIG_PATH: &str = ".crafter-code/terminal.json";
+
+/// Shell, env vars and startup commands for a terminal
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct TerminalProfile {
+    /// Shell name (`zsh`, `bash`, `fish`, `pwsh`) or absolute path
+    pub shell: Option<String>,
+    pub env: HashMap<String, String>,
+    /// Lines typed into the shell right after it starts
+    pub startup_commands: Vec<String>,
+}
+
+impl TerminalProfile {
+    /// Layer `other` on top of `self`: shell and startup commands are
+    /// replaced when set, env vars are merged.
+    pub fn merged(mut self, other: &TerminalProfile) -> TerminalProfile {
+        if other.shell.is_some() {
+            self.shell = other.shell.clone();
+        }
+        self.env
+            .extend(other.env.iter().map(|(k, v)| (k.clone(), v.clone())));
+        if !other.startup_commands.is_empty() {
+            self.startup_commands = other.startup_commands.clone();
+        }
+        self
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProjectTerminalConfig {
+    pub default: TerminalProfile,
+    pub profiles: HashMap<String, TerminalProfile>,
+    pub services: HashMap<String, ServiceSpec>,
+}
+
+/// Load the project terminal config, if the project has one
+pub fn load_project_config(cwd: &Path) -> Option<ProjectTerminalConfig> {
+    let path = cwd.join(PROJECT_CONFIG_PATH);
+    let content = std::fs::read_to_string(&path).ok()?;
+    match serde_json::from_str(&content) {
+        Ok(config) => Some(config),
+        Err(e) => {
+            eprintln!("[PTY] Invalid terminal config {:?}: {}", path, e);
+            None
+        }
+    }
+}
+
+/// Resolve the effective profile for a new terminal
+pub fn resolve_profile(
+    project: Option<&ProjectTerminalConfig>,
+    profile_name: Option<&str>,
+    overrides: &TerminalProfile,
+) -> TerminalProfile {
+    let mut profile = TerminalProfile::default();
+    if let Some(project) = project {
+        profile = profile.merged(&project.default);
+        if let Some(named) = profile_name.and_then(|name| project.profiles.get(name)) {
+            profile = profile.merged(named);
+        }
+    }
+    profile.merged(overrides)
+}
+
+/// The user's login shell
+pub fn default_shell() -> String {
+    if let Ok(shell) = std::env::var("SHELL") {
+        if !shell.is_empty() {
+            return shell;
+        }
+    }
+    if cfg!(windows) {
+        return "powershell.exe".to_string();
+    }
+    // Apps launched from the desktop may not inherit $SHELL
+    if let Some(shell) = std::env::var("USER")
+        .ok()
+        .and_then(|user| passwd_shell(&std::fs::read_to_string("/etc/passwd").ok()?, &user))
+    {
+        return shell;
+    }
+    if cfg!(target_os = "macos") {
+        "/bin/zsh".to_string()
+    } else {
+        "/bin/bash".to_string()
+    }
+}
+
+fn passwd_shell(passwd: &str, user: &str) -> Option<String> {
+    passwd
+        .lines()
+        .map(|line| line.split(':').collect::<Vec<_>>())
+        .find(|fields| fields.len() >= 7 && fields[0] == user)
+        .map(|fields| fields[6].to_string())
+        .filter(|shell| !shell.is_empty())
+}
+
+/// Arguments to start `shell` as a login shell
+pub fn login_args(shell: &str) -> Vec<&'static str> {
+    let name = Path::new(shell)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    match name.as_str() {
+        "pwsh" | "powershell" => vec!["-NoLogo", "-Login"],
+        "cmd" => vec![],
+        _ => vec!["-l"],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_profile_layers() {
+        let config: ProjectTerminalConfig = serde_json::from_str(
+            r#"{
+                "default": { "shell": "fish", "env": { "A": "1", "B": "1" } },
+                "profiles": { "tests": { "env": { "B": "2" }, "startup_commands": ["make test"] } }
+            }"#,
+        )
+        .unwrap();
+
+        let overrides = TerminalProfile {
+            shell: Some("bash".to_string()),
+            ..Default::default()
+        };
+        let profile = resolve_profile(Some(&config), Some("tests"), &overrides);
+        assert_eq!(profile.shell.as_deref(), Some("bash"));
+        assert_eq!(profile.env.get("A").map(String::as_str), Some("1"));
+        assert_eq!(profile.env.get("B").map(String::as_str), Some("2"));
+        assert_eq!(profile.startup_commands, vec!["make test".to_string()]);
+
+        let profile = resolve_profile(Some(&config), None, &TerminalProfile::default());
+        assert_eq!(profile.shell.as_deref(), Some("fish"));
+        assert!(profile.startup_commands.is_empty());
+    }
+
+    #[test]
+    fn test_login_args() {
+        assert_eq!(login_args("/usr/local/bin/fish"), vec!["-l"]);
+        assert_eq!(login_args("zsh"), vec!["-l"]);
+        assert_eq!(login_args("pwsh"), vec!["-NoLogo", "-Login"]);
+        assert_eq!(login_args("cmd.exe"), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn test_passwd_shell() {
+        let passwd = "root:x:0:0:root:/root:/bin/bash\nalex:x:501:20::/home/alex:/usr/bin/fish\n";
+        assert_eq!(
+            passwd_shell(passwd, "alex").as_deref(),
+            Some("/usr/bin/fish")
+        );
+        assert_eq!(passwd_shell(passwd, "nobody"), None);
+    }
+}
@@ -0,0 +1,240 @@
+//! Terminal recording (asciicast v2)
+//!
+//! Records terminal output to `.cast` files that can be replayed with
+//! asciinema. Recordings live next to the session they belong to:
+//! `~/.crafter-code/sessions/{session_id}/recordings/{terminal_id}-{ts}.cast`
+//! (or `~/.crafter-code/recordings/` for terminals without a session).
+//!
+//! Both PTY terminals and agent-created (`terminal/create`) terminals feed
+//! their output through `record_output`, which is a no-op unless a recording
+//! was started for that terminal.
+
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// Global registry of active recordings (terminal_id -> recorder)
+static RECORDINGS: Lazy<Mutex<HashMap<String, Recorder>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Metadata about a recording
+#[derive(Debug, Clone, Serialize)]
+pub struct RecordingInfo {
+    pub terminal_id: String,
+    pub session_id: Option<String>,
+    pub path: String,
+    pub started_at: i64,
+    pub event_count: u64,
+}
+
+/// Writes asciicast v2 events to a file
+pub struct Recorder {
+    info: RecordingInfo,
+    writer: BufWriter<File>,
+    start: Instant,
+}
+
+impl Recorder {
+    /// Create the cast file and write the header line
+    pub fn create(
+        path: &Path,
+        terminal_id: &str,
+        session_id: Option<String>,
+        cols: u16,
+        rows: u16,
+        title: Option<&str>,
+    ) -> Result<Self, String> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create recordings directory: {}", e))?;
+        }
+        let file =
+            File::create(path).map_err(|e| format!("Failed to create recording file: {}", e))?;
+        let started_at = chrono::Utc::now();
+
+        let mut header = serde_json::json!({
+            "version": 2,
+            "width": cols,
+            "height": rows,
+            "timestamp": started_at.timestamp(),
+            "env": {
+                "TERM": "xterm-256color",
+                "SHELL": std::env::var("SHELL").unwrap_or_default(),
+            },
+        });
+        if let Some(title) = title {
+            header["title"] = serde_json::json!(title);
+        }
+
+        let mut recorder = Self {
+            info: RecordingInfo {
+                terminal_id: terminal_id.to_string(),
+                session_id,
+                path: path.to_string_lossy().to_string(),
+                started_at: started_at.timestamp_millis(),
+                event_count: 0,
+            },
+            writer: BufWriter::new(file),
+            start: Instant::now(),
+        };
+        recorder.write_line(&header)?;
+        Ok(recorder)
+    }
+
+    /// Append an output event
+    pub fn output(&mut self, data: &str) -> Result<(), String> {
+        self.event("o", data)
+    }
+
+    /// Append a terminal resize event
+    pub fn resize(&mut self, cols: u16, rows: u16) -> Result<(), String> {
+        self.event("r", &format!("{}x{}", cols, rows))
+    }
+
+    fn event(&mut self, code: &str, data: &str) -> Result<(), String> {
+        let elapsed = self.start.elapsed().as_secs_f64();
+        self.info.event_count += 1;
+        self.write_line(&serde_json::json!([elapsed, code, data]))
+    }
+
+    fn write_line(&mut self, value: &serde_json::Value) -> Result<(), String> {
+        writeln!(self.writer, "{}", value)
+            .and_then(|_| self.writer.flush())
+            .map_err(|e| format!("Failed to write recording: {}", e))
+    }
+
+    pub fn info(&self) -> RecordingInfo {
+        self.info.clone()
+    }
+}
+
+/// Directory for a session's recordings
+pub fn recordings_dir(session_id: Option<&str>) -> Result<PathBuf, String> {
+    let base = dirs::home_dir()
+        .ok_or_else(|| "Could not determine home directory".to_string())?
+        .join(".crafter-code");
+    Ok(match session_id {
+        Some(session_id) => base.join("sessions").join(session_id).join("recordings"),
+        None => base.join("recordings"),
+    })
+}
+
+/// Start recording a terminal (errors if it is already being recorded)
+pub fn start(
+    terminal_id: &str,
+    session_id: Option<String>,
+    cols: u16,
+    rows: u16,
+    title: Option<&str>,
+) -> Result<RecordingInfo, String> {
+    let mut recordings = RECORDINGS.lock();
+    if recordings.contains_key(terminal_id) {
+        return Err(format!(
+            "Terminal {} is already being recorded",
+            terminal_id
+        ));
+    }
+
+    let file_name = format!(
+        "{}-{}.cast",
+        terminal_id,
+        chrono::Utc::now().format("%Y%m%d-%H%M%S")
+    );
+    let path = recordings_dir(session_id.as_deref())?.join(file_name);
+    let recorder = Recorder::create(&path, terminal_id, session_id, cols, rows, title)?;
+    let info = recorder.info();
+    recordings.insert(terminal_id.to_string(), recorder);
+
+    eprintln!("[Recording] Started {} -> {}", terminal_id, info.path);
+    Ok(info)
+}
+
+/// Stop recording a terminal. Returns None if it wasn't being recorded.
+pub fn stop(terminal_id: &str) -> Option<RecordingInfo> {
+    let recorder = RECORDINGS.lock().remove(terminal_id)?;
+    eprintln!("[Recording] Stopped {}", terminal_id);
+    Some(recorder.info())
+}
+
+/// Feed terminal output into its recording, if one is active
+pub fn record_output(terminal_id: &str, data: &str) {
+    let mut recordings = RECORDINGS.lock();
+    if let Some(recorder) = recordings.get_mut(terminal_id) {
+        if let Err(e) = recorder.output(data) {
+            eprintln!("[Recording] {}", e);
+            recordings.remove(terminal_id);
+        }
+    }
+}
+
+/// Record a resize, if the terminal is being recorded
+pub fn record_resize(terminal_id: &str, cols: u16, rows: u16) {
+    if let Some(recorder) = RECORDINGS.lock().get_mut(terminal_id) {
+        let _ = recorder.resize(cols, rows);
+    }
+}
+
+/// Copy a recording to `destination`
+pub fn export(path: &str, destination: &str) -> Result<String, String> {
+    let source = Path::new(path);
+    if source.extension().and_then(|e| e.to_str()) != Some("cast") {
+        return Err(format!("Not a recording: {}", path));
+    }
+    let base = recordings_dir(None)?
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_default();
+    if !source.starts_with(&base) {
+        return Err(format!("Recording is outside {}", base.display()));
+    }
+
+    let mut target = PathBuf::from(destination);
+    if target.is_dir() {
+        if let Some(name) = source.file_name() {
+            target = target.join(name);
+        }
+    }
+    std::fs::copy(source, &target).map_err(|e| format!("Failed to export recording: {}", e))?;
+    Ok(target.to_string_lossy().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_asciicast_format() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("nested").join("t1.cast");
+
+        let mut recorder = Recorder::create(&path, "t1", None, 120, 40, Some("tests")).unwrap();
+        recorder.output("hello\r\n").unwrap();
+        recorder.resize(100, 30).unwrap();
+        assert_eq!(recorder.info().event_count, 2);
+        drop(recorder);
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<serde_json::Value> = content
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0]["version"], 2);
+        assert_eq!(lines[0]["width"], 120);
+        assert_eq!(lines[0]["title"], "tests");
+        assert_eq!(lines[1][1], "o");
+        assert_eq!(lines[1][2], "hello\r\n");
+        assert_eq!(lines[2][1], "r");
+        assert_eq!(lines[2][2], "100x30");
+    }
+
+    #[test]
+    fn test_export_rejects_non_recordings() {
+        assert!(export("/etc/passwd", "/tmp").is_err());
+    }
+}
@@ -0,0 +1,654 @@
+//! Service terminals
+//!
+//! A service is a long-running command, usually a dev server, that crafter
+//! keeps running for a session. It is declared under `services` in the
+//! project's terminal config or started by an agent with
+//! `swarm service start <name> "<command>"`, and runs under `sh -c` in the
+//! project directory. When the command exits it is restarted according to
+//! its restart policy after 1s, 2s, 4s... (at most 30s); a run that lasted
+//! `STABLE_AFTER` resets the backoff, and after `MAX_FAILURES` quick crashes
+//! in a row the service is left failed.
+//!
+//! Output is kept like a terminal's scrollback. Service ids work as terminal
+//! ids for the agent's `terminal/output`, and `swarm service logs` prints
+//! the tail, so an agent can check on its dev server in later prompts.
+//! Services stop with their session and at shutdown.
+
+use super::profile::load_project_config;
+use super::signal::{signal_tree, TerminalSignal};
+use super::terminal::Scrollback;
+use crate::acp::dev_servers;
+use crate::agent::dotenv::project_env;
+use crate::events::{emit, ServiceOutputEvent, ServiceStatusEvent};
+use crate::time::now_ms;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::{Child, Command};
+use tokio::sync::watch;
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Service ids, which double as terminal ids, start with this
+pub const SERVICE_ID_PREFIX: &str = "svc_";
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// A run this long counts as healthy and resets the backoff
+const STABLE_AFTER: Duration = Duration::from_secs(60);
+
+/// Quick crashes in a row before the service is given up on
+const MAX_FAILURES: u32 = 5;
+
+/// How long a stopping service gets to exit after SIGTERM
+const STOP_GRACE: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RestartPolicy {
+    /// Restart after a crash, not after a clean exit
+    #[default]
+    OnFailure,
+    Always,
+    Never,
+}
+
+/// A service as declared in the project config or by an agent
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ServiceSpec {
+    pub command: String,
+    /// Working directory, relative to the project root
+    pub cwd: Option<String>,
+    pub env: HashMap<String, String>,
+    pub restart: RestartPolicy,
+    /// Regex matched against each output line; the first match marks the
+    /// service ready (e.g. "Local:|listening on")
+    pub ready_pattern: Option<String>,
+    /// Start with every session opened on the project
+    pub autostart: bool,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum ServiceStatus {
+    Starting,
+    /// Running; with a ready pattern, one that hasn't matched yet
+    Running,
+    /// The ready pattern matched
+    Ready,
+    /// Exited and waiting to be restarted
+    Backoff,
+    Stopped,
+    /// Crashed too often, or failed with a policy that doesn't restart
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ServiceInfo {
+    pub id: String,
+    pub session_id: String,
+    pub name: String,
+    pub command: String,
+    pub cwd: String,
+    pub status: ServiceStatus,
+    pub pid: Option<u32>,
+    /// Times the command has been started again
+    pub restarts: u32,
+    pub last_exit_code: Option<i32>,
+    /// When the current or last run started
+    pub started_at: Option<i64>,
+}
+
+/// A service's info with its retained output
+#[derive(Debug, Clone, Serialize)]
+pub struct ServiceOutput {
+    pub info: ServiceInfo,
+    pub output: String,
+    /// Whether older output has been dropped
+    pub truncated: bool,
+}
+
+/// What a supervisor is asked to do
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Control {
+    Run,
+    Restart,
+    Stop,
+}
+
+/// Everything a supervisor needs to (re)start the command
+#[derive(Clone)]
+struct Launch {
+    spec: ServiceSpec,
+    cwd: PathBuf,
+    ready: Option<Regex>,
+}
+
+struct Service {
+    info: ServiceInfo,
+    launch: Launch,
+    output: Arc<Mutex<Scrollback>>,
+    control: watch::Sender<Control>,
+}
+
+/// Services of every session (service_id -> service)
+static SERVICES: Lazy<Mutex<HashMap<String, Service>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+pub fn is_service(id: &str) -> bool {
+    id.starts_with(SERVICE_ID_PREFIX)
+}
+
+/// Delay before the restart after `failures` crashes in a row
+fn backoff(failures: u32) -> Duration {
+    INITIAL_BACKOFF
+        .saturating_mul(1 << failures.saturating_sub(1).min(5))
+        .min(MAX_BACKOFF)
+}
+
+fn should_restart(policy: RestartPolicy, success: bool) -> bool {
+    match policy {
+        RestartPolicy::Always => true,
+        RestartPolicy::OnFailure => !success,
+        RestartPolicy::Never => false,
+    }
+}
+
+/// The service `name` as declared in the project config, with `command`
+/// replacing the declared command. Without a declaration `command` is
+/// required.
+pub fn resolve_spec(
+    project_dir: &str,
+    name: &str,
+    command: Option<String>,
+) -> Result<ServiceSpec, String> {
+    let declared = load_project_config(Path::new(project_dir))
+        .and_then(|config| config.services.get(name).cloned());
+    match (declared, command) {
+        (Some(spec), None) => Ok(spec),
+        (declared, Some(command)) => Ok(ServiceSpec {
+            command,
+            ..declared.unwrap_or_default()
+        }),
+        (None, None) => Err(format!(
+            "Service {} isn't declared in {}; give a command to run",
+            name,
+            super::profile::PROJECT_CONFIG_PATH
+        )),
+    }
+}
+
+/// Change a service's status (and whatever `change` sets) and tell the
+/// frontend
+fn set_status(
+    app: &AppHandle,
+    id: &str,
+    status: ServiceStatus,
+    change: impl FnOnce(&mut ServiceInfo),
+) {
+    let event = {
+        let mut services = SERVICES.lock();
+        let Some(service) = services.get_mut(id) else {
+            return;
+        };
+        service.info.status = status;
+        change(&mut service.info);
+        let info = &service.info;
+        ServiceStatusEvent {
+            service_id: info.id.clone(),
+            session_id: info.session_id.clone(),
+            name: info.name.clone(),
+            status,
+            pid: info.pid,
+            restarts: info.restarts,
+            exit_code: info.last_exit_code,
+        }
+    };
+    emit(app, &event);
+}
+
+fn status(id: &str) -> Option<ServiceStatus> {
+    SERVICES.lock().get(id).map(|service| service.info.status)
+}
+
+/// Start a service for a session. A service of the same name that is still
+/// up is returned as is; a stopped or failed one is replaced.
+pub fn start(
+    app: &AppHandle,
+    session_id: &str,
+    name: &str,
+    spec: ServiceSpec,
+    project_dir: &str,
+) -> Result<ServiceInfo, String> {
+    if spec.command.trim().is_empty() {
+        return Err(format!("Service {} has no command", name));
+    }
+    let ready = spec
+        .ready_pattern
+        .as_deref()
+        .map(Regex::new)
+        .transpose()
+        .map_err(|e| format!("Invalid ready_pattern for {}: {}", name, e))?;
+    let cwd = match &spec.cwd {
+        Some(dir) => Path::new(project_dir).join(dir),
+        None => PathBuf::from(project_dir),
+    };
+    if !cwd.is_dir() {
+        return Err(format!("Service directory not found: {}", cwd.display()));
+    }
+
+    let mut services = SERVICES.lock();
+    let same =
+        |service: &Service| service.info.session_id == session_id && service.info.name == name;
+    if let Some(existing) = services.values().find(|service| same(service)) {
+        if !matches!(
+            existing.info.status,
+            ServiceStatus::Stopped | ServiceStatus::Failed
+        ) {
+            return Ok(existing.info.clone());
+        }
+    }
+    services.retain(|_, service| !same(service));
+
+    let id = format!("{}{}", SERVICE_ID_PREFIX, Uuid::new_v4().simple());
+    let info = ServiceInfo {
+        id: id.clone(),
+        session_id: session_id.to_string(),
+        name: name.to_string(),
+        command: spec.command.clone(),
+        cwd: cwd.to_string_lossy().into_owned(),
+        status: ServiceStatus::Starting,
+        pid: None,
+        restarts: 0,
+        last_exit_code: None,
+        started_at: None,
+    };
+    let launch = Launch { spec, cwd, ready };
+    let output = Arc::new(Mutex::new(Scrollback::default()));
+    let (control, control_rx) = watch::channel(Control::Run);
+    services.insert(
+        id.clone(),
+        Service {
+            info: info.clone(),
+            launch: launch.clone(),
+            output: output.clone(),
+            control,
+        },
+    );
+    drop(services);
+
+    eprintln!(
+        "[Service] Starting {} ({}) for session={}",
+        name, id, session_id
+    );
+    tauri::async_runtime::spawn(supervise(app.clone(), id, launch, output, control_rx));
+    Ok(info)
+}
+
+/// Start the project's `autostart` services for a new session
+pub fn autostart(app: &AppHandle, session_id: &str, project_dir: &str) {
+    let Some(config) = load_project_config(Path::new(project_dir)) else {
+        return;
+    };
+    for (name, spec) in config.services {
+        if spec.autostart {
+            if let Err(e) = start(app, session_id, &name, spec, project_dir) {
+                eprintln!("[Service] {}", e);
+            }
+        }
+    }
+}
+
+/// Stop a service; it stays listed (as stopped) until its session ends
+pub fn stop(id: &str) -> Result<ServiceInfo, String> {
+    let services = SERVICES.lock();
+    let service = services
+        .get(id)
+        .ok_or_else(|| format!("Service not found: {}", id))?;
+    service.control.send_replace(Control::Stop);
+    Ok(service.info.clone())
+}
+
+/// Restart a service now, resetting its backoff. A stopped or failed
+/// service is started again.
+pub fn restart(app: &AppHandle, id: &str) -> Result<ServiceInfo, String> {
+    let mut services = SERVICES.lock();
+    let service = services
+        .get_mut(id)
+        .ok_or_else(|| format!("Service not found: {}", id))?;
+    if matches!(
+        service.info.status,
+        ServiceStatus::Stopped | ServiceStatus::Failed
+    ) {
+        let (control, control_rx) = watch::channel(Control::Run);
+        service.control = control;
+        service.info.status = ServiceStatus::Starting;
+        service.info.restarts += 1;
+        tauri::async_runtime::spawn(supervise(
+            app.clone(),
+            id.to_string(),
+            service.launch.clone(),
+            service.output.clone(),
+            control_rx,
+        ));
+    } else {
+        service.control.send_replace(Control::Restart);
+    }
+    Ok(service.info.clone())
+}
+
+/// List services, optionally only a session's
+pub fn list(session_id: Option<&str>) -> Vec<ServiceInfo> {
+    let mut services: Vec<ServiceInfo> = SERVICES
+        .lock()
+        .values()
+        .filter(|service| session_id.map_or(true, |id| service.info.session_id == id))
+        .map(|service| service.info.clone())
+        .collect();
+    services.sort_by(|a, b| a.name.cmp(&b.name));
+    services
+}
+
+/// A session's service by name
+pub fn find(session_id: &str, name: &str) -> Option<ServiceInfo> {
+    SERVICES
+        .lock()
+        .values()
+        .find(|service| service.info.session_id == session_id && service.info.name == name)
+        .map(|service| service.info.clone())
+}
+
+pub fn output(id: &str) -> Option<ServiceOutput> {
+    let services = SERVICES.lock();
+    let service = services.get(id)?;
+    let output = service.output.lock();
+    Some(ServiceOutput {
+        info: service.info.clone(),
+        output: output.contents().to_string(),
+        truncated: output.truncated(),
+    })
+}
+
+/// Stop and forget a session's services; returns their ids
+pub fn stop_session(session_id: &str) -> Vec<String> {
+    let mut services = SERVICES.lock();
+    let ids: Vec<String> = services
+        .values()
+        .filter(|service| service.info.session_id == session_id)
+        .map(|service| service.info.id.clone())
+        .collect();
+    for id in &ids {
+        if let Some(service) = services.remove(id) {
+            service.control.send_replace(Control::Stop);
+        }
+    }
+    ids
+}
+
+/// SIGTERM every running service at shutdown, without waiting for the
+/// supervisors, which may not get to run again
+pub fn stop_all() -> usize {
+    let services = SERVICES.lock();
+    let mut signalled = 0;
+    for service in services.values() {
+        service.control.send_replace(Control::Stop);
+        if let Some(pid) = service.info.pid {
+            if signal_tree(pid, TerminalSignal::Terminate).is_ok() {
+                signalled += 1;
+            }
+        }
+    }
+    signalled
+}
+
+/// Wait for a stop or restart request; a dropped sender means stop
+async fn next_request(control: &mut watch::Receiver<Control>) -> Control {
+    match control.changed().await {
+        Ok(()) => *control.borrow_and_update(),
+        Err(_) => Control::Stop,
+    }
+}
+
+/// SIGTERM the command's process tree, then kill it if it doesn't exit
+async fn terminate(child: &mut Child) {
+    if let Some(pid) = child.id() {
+        let _ = signal_tree(pid, TerminalSignal::Terminate);
+    }
+    if tokio::time::timeout(STOP_GRACE, child.wait()).await.is_err() {
+        let _ = child.kill().await;
+    }
+}
+
+/// Copy one of the command's streams into the scrollback, watching for the
+/// ready pattern and server addresses
+async fn pipe_output(
+    app: AppHandle,
+    id: String,
+    stream: impl AsyncRead + Unpin,
+    output: Arc<Mutex<Scrollback>>,
+    ready: Option<Regex>,
+) {
+    let session_id = SERVICES
+        .lock()
+        .get(&id)
+        .map(|service| service.info.session_id.clone())
+        .unwrap_or_default();
+    let mut reader = BufReader::new(stream);
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        match reader.read_until(b'\n', &mut buf).await {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {}
+        }
+        let line = String::from_utf8_lossy(&buf);
+        output.lock().push(&line);
+        emit(
+            &app,
+            &ServiceOutputEvent {
+                service_id: id.clone(),
+                line: line.to_string(),
+            },
+        );
+        for event in dev_servers::record(&session_id, &id, None, &line) {
+            emit(&app, &event);
+        }
+        if ready.as_ref().is_some_and(|re| re.is_match(&line))
+            && status(&id) == Some(ServiceStatus::Running)
+        {
+            set_status(&app, &id, ServiceStatus::Ready, |_| {});
+        }
+    }
+}
+
+/// How one run of the command ended
+enum RunEnd {
+    Exited { code: Option<i32>, success: bool },
+    Requested(Control),
+}
+
+async fn run_once(
+    app: &AppHandle,
+    id: &str,
+    launch: &Launch,
+    output: &Arc<Mutex<Scrollback>>,
+    control: &mut watch::Receiver<Control>,
+) -> RunEnd {
+    let mut cmd = Command::new("/bin/sh");
+    cmd.arg("-c")
+        .arg(&launch.spec.command)
+        .current_dir(&launch.cwd)
+        .envs(project_env(&launch.cwd.to_string_lossy()))
+        .envs(&launch.spec.env)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+
+    let mut child = match cmd.spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            output
+                .lock()
+                .push(&format!("[crafter] Failed to start: {}\n", e));
+            return RunEnd::Exited {
+                code: None,
+                success: false,
+            };
+        }
+    };
+    let pid = child.id();
+    set_status(app, id, ServiceStatus::Running, |info| {
+        info.pid = pid;
+        info.started_at = Some(now_ms());
+    });
+    if let Some(stdout) = child.stdout.take() {
+        tauri::async_runtime::spawn(pipe_output(
+            app.clone(),
+            id.to_string(),
+            stdout,
+            output.clone(),
+            launch.ready.clone(),
+        ));
+    }
+    if let Some(stderr) = child.stderr.take() {
+        tauri::async_runtime::spawn(pipe_output(
+            app.clone(),
+            id.to_string(),
+            stderr,
+            output.clone(),
+            launch.ready.clone(),
+        ));
+    }
+
+    tokio::select! {
+        status = child.wait() => RunEnd::Exited {
+            code: status.as_ref().ok().and_then(|s| s.code()),
+            success: status.is_ok_and(|s| s.success()),
+        },
+        request = next_request(control) => {
+            terminate(&mut child).await;
+            RunEnd::Requested(request)
+        }
+    }
+}
+
+/// Keep a service's command running until it is stopped or given up on
+async fn supervise(
+    app: AppHandle,
+    id: String,
+    launch: Launch,
+    output: Arc<Mutex<Scrollback>>,
+    mut control: watch::Receiver<Control>,
+) {
+    let mut failures = 0;
+    let mut first = true;
+    loop {
+        if !first {
+            set_status(&app, &id, ServiceStatus::Starting, |info| info.restarts += 1);
+        }
+        first = false;
+
+        let started = Instant::now();
+        let (code, success) = match run_once(&app, &id, &launch, &output, &mut control).await {
+            RunEnd::Requested(Control::Stop) => {
+                set_status(&app, &id, ServiceStatus::Stopped, |info| info.pid = None);
+                return;
+            }
+            RunEnd::Requested(_) => {
+                failures = 0;
+                continue;
+            }
+            RunEnd::Exited { code, success } => (code, success),
+        };
+        output.lock().push(&match code {
+            Some(code) => format!("[crafter] Exited with code {}\n", code),
+            None => "[crafter] Exited\n".to_string(),
+        });
+
+        if !should_restart(launch.spec.restart, success) {
+            let status = if success {
+                ServiceStatus::Stopped
+            } else {
+                ServiceStatus::Failed
+            };
+            set_status(&app, &id, status, |info| {
+                info.pid = None;
+                info.last_exit_code = code;
+            });
+            return;
+        }
+        if started.elapsed() >= STABLE_AFTER {
+            failures = 0;
+        }
+        failures += 1;
+        if failures > MAX_FAILURES {
+            output.lock().push(&format!(
+                "[crafter] Gave up after {} crashes in a row\n",
+                MAX_FAILURES
+            ));
+            set_status(&app, &id, ServiceStatus::Failed, |info| {
+                info.pid = None;
+                info.last_exit_code = code;
+            });
+            return;
+        }
+
+        set_status(&app, &id, ServiceStatus::Backoff, |info| {
+            info.pid = None;
+            info.last_exit_code = code;
+        });
+        tokio::select! {
+            _ = tokio::time::sleep(backoff(failures)) => {}
+            request = next_request(&mut control) => {
+                if request == Control::Stop {
+                    set_status(&app, &id, ServiceStatus::Stopped, |_| {});
+                    return;
+                }
+                failures = 0;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff() {
+        assert_eq!(backoff(1), Duration::from_secs(1));
+        assert_eq!(backoff(2), Duration::from_secs(2));
+        assert_eq!(backoff(4), Duration::from_secs(8));
+        assert_eq!(backoff(6), MAX_BACKOFF);
+        assert_eq!(backoff(40), MAX_BACKOFF);
+    }
+
+    #[test]
+    fn test_should_restart() {
+        assert!(should_restart(RestartPolicy::OnFailure, false));
+        assert!(!should_restart(RestartPolicy::OnFailure, true));
+        assert!(should_restart(RestartPolicy::Always, true));
+        assert!(!should_restart(RestartPolicy::Never, false));
+    }
+
+    #[test]
+    fn test_spec_defaults() {
+        let spec: ServiceSpec = serde_json::from_str(r#"{ "command": "pnpm dev" }"#).unwrap();
+        assert_eq!(spec.restart, RestartPolicy::OnFailure);
+        assert!(!spec.autostart);
+        let spec: ServiceSpec =
+            serde_json::from_str(r#"{ "command": "x", "restart": "always" }"#).unwrap();
+        assert_eq!(spec.restart, RestartPolicy::Always);
+    }
+}
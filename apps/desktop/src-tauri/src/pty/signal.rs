@@ -0,0 +1,79 @@
+//! Interrupting terminals
+//!
+//! `signal_terminal` lets a long-running command (a dev server an agent
+//! started, say) shut down cleanly instead of being killed. Agent-created
+//! terminals run their command under `sh -c`, so the signal goes to the
+//! whole process tree, children first. Windows has no signals: there both
+//! become a CTRL_BREAK, which reaches console programs in the process's
+//! group.
+
+#[cfg(unix)]
+use crate::acp::resource_limits::{children_by_parent, process_tree};
+use serde::{Deserialize, Serialize};
+#[cfg(unix)]
+use sysinfo::{Pid, ProcessesToUpdate, System};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum TerminalSignal {
+    /// What Ctrl+C sends
+    #[serde(rename = "SIGINT")]
+    Interrupt,
+    #[serde(rename = "SIGTERM")]
+    Terminate,
+}
+
+/// Signal a process and its descendants, returning how many were signalled
+#[cfg(unix)]
+pub fn signal_tree(pid: u32, signal: TerminalSignal) -> Result<usize, String> {
+    let mut system = System::new();
+    system.refresh_processes(ProcessesToUpdate::All, true);
+    let children = children_by_parent(&system);
+    let signal = match signal {
+        TerminalSignal::Interrupt => sysinfo::Signal::Interrupt,
+        TerminalSignal::Terminate => sysinfo::Signal::Term,
+    };
+    let signalled = process_tree(Pid::from_u32(pid), &children)
+        .into_iter()
+        .rev()
+        .filter_map(|pid| system.process(pid))
+        .filter(|process| process.kill_with(signal) == Some(true))
+        .count();
+    if signalled == 0 {
+        return Err(format!("Process {} is not running", pid));
+    }
+    Ok(signalled)
+}
+
+/// Send CTRL_BREAK to a process's group
+#[cfg(windows)]
+pub fn signal_tree(pid: u32, _signal: TerminalSignal) -> Result<usize, String> {
+    use windows_sys::Win32::System::Console::{GenerateConsoleCtrlEvent, CTRL_BREAK_EVENT};
+
+    // SAFETY: plain FFI call; an unknown pid makes it fail, not misbehave
+    if unsafe { GenerateConsoleCtrlEvent(CTRL_BREAK_EVENT, pid) } == 0 {
+        return Err(format!(
+            "Failed to interrupt process {}: {}",
+            pid,
+            std::io::Error::last_os_error()
+        ));
+    }
+    Ok(1)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    #[test]
+    fn test_signal_tree() {
+        let mut child = Command::new("/bin/sh")
+            .args(["-c", "sleep 30; echo done"])
+            .spawn()
+            .unwrap();
+        // Give the shell a moment to start its sleep
+        std::thread::sleep(std::time::Duration::from_millis(200));
+        assert!(signal_tree(child.id(), TerminalSignal::Terminate).unwrap() >= 1);
+        assert!(!child.wait().unwrap().success());
+    }
+}
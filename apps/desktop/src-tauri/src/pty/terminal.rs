@@ -1,17 +1,155 @@
+use super::profile::{
+    default_shell, load_project_config, login_args, resolve_profile, TerminalProfile,
+};
+use super::recording::{self, RecordingInfo};
+use super::signal::{signal_tree, TerminalSignal};
+use crate::secrets::redact::{redactor_for, RedactingStream};
 use parking_lot::Mutex;
 use portable_pty::{native_pty_system, Child, CommandBuilder, MasterPty, PtySize};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::io::{Read, Write};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 use std::thread;
+use std::time::Duration;
 use tauri::{AppHandle, Emitter};
 use uuid::Uuid;
 
+/// Output kept per terminal so a new view can reattach with history
+const SCROLLBACK_LIMIT: usize = 256 * 1024;
+
+/// Output held back for redaction is sent anyway once the terminal has been
+/// quiet this long, so an interactive shell's echo doesn't lag
+const REDACT_FLUSH_DELAY: Duration = Duration::from_millis(30);
+
+/// Optional metadata for a spawned terminal
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct TerminalOptions {
+    /// Orchestrator session the terminal belongs to
+    pub session_id: Option<String>,
+    /// Human-readable name ("dev server", "tests")
+    pub name: Option<String>,
+    /// Keep running when the owning session is closed
+    pub keep_alive: bool,
+    /// Named profile from the project's terminal config
+    pub profile: Option<String>,
+    /// Shell, env vars and startup commands (override the project config)
+    #[serde(flatten)]
+    pub overrides: TerminalProfile,
+}
+
+/// Terminal metadata returned by `list_terminals`
+#[derive(Debug, Clone, Serialize)]
+pub struct TerminalInfo {
+    pub id: String,
+    pub session_id: Option<String>,
+    pub name: Option<String>,
+    pub cwd: Option<String>,
+    pub shell: String,
+    pub keep_alive: bool,
+    pub cols: u16,
+    pub rows: u16,
+    pub created_at: i64,
+    pub exited: bool,
+}
+
+/// Everything a view needs to reattach to a running terminal
+#[derive(Debug, Clone, Serialize)]
+pub struct TerminalAttachment {
+    pub info: TerminalInfo,
+    pub scrollback: String,
+}
+
+/// Bounded output history; drops the oldest output first
+#[derive(Debug, Default)]
+pub(super) struct Scrollback {
+    buffer: String,
+    /// Whether output has been dropped
+    truncated: bool,
+}
+
+impl Scrollback {
+    pub(super) fn push(&mut self, data: &str) {
+        self.buffer.push_str(data);
+        if self.buffer.len() > SCROLLBACK_LIMIT {
+            self.truncated = true;
+            let mut cut = self.buffer.len() - SCROLLBACK_LIMIT;
+            while !self.buffer.is_char_boundary(cut) {
+                cut += 1;
+            }
+            self.buffer.drain(..cut);
+        }
+    }
+
+    pub(super) fn contents(&self) -> &str {
+        &self.buffer
+    }
+
+    pub(super) fn truncated(&self) -> bool {
+        self.truncated
+    }
+}
+
+/// Redacts a terminal's output on its way to the scrollback, the recording
+/// and `pty-output-*` listeners
+struct PtyOutput {
+    id: String,
+    app_handle: AppHandle,
+    /// Directory whose secrets are redacted
+    project: Option<String>,
+    scrollback: Arc<Mutex<Scrollback>>,
+    stream: Mutex<RedactingStream>,
+    /// Reads that left output held back; a scheduled flush only sends it if
+    /// no read came after it
+    held_reads: AtomicU64,
+}
+
+impl PtyOutput {
+    fn send(&self, data: &str) {
+        if data.is_empty() {
+            return;
+        }
+        self.scrollback.lock().push(data);
+        recording::record_output(&self.id, data);
+        let _ = self
+            .app_handle
+            .emit(&format!("pty-output-{}", self.id), data);
+    }
+
+    fn push(self: &Arc<Self>, data: &str) {
+        let redactor = redactor_for(self.project.as_deref());
+        let mut stream = self.stream.lock();
+        self.send(&stream.push(&redactor, data));
+        if stream.is_empty() {
+            return;
+        }
+        let read = self.held_reads.fetch_add(1, Ordering::SeqCst) + 1;
+        let output = self.clone();
+        tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(REDACT_FLUSH_DELAY).await;
+            if output.held_reads.load(Ordering::SeqCst) == read {
+                output.flush();
+            }
+        });
+    }
+
+    fn flush(&self) {
+        let redactor = redactor_for(self.project.as_deref());
+        let mut stream = self.stream.lock();
+        self.send(&stream.flush(&redactor));
+    }
+}
+
 pub struct PtyTerminal {
     pub id: String,
+    info: TerminalInfo,
     master: Box<dyn MasterPty + Send>,
     writer: Arc<Mutex<Box<dyn Write + Send>>>,
     child: Box<dyn Child + Send + Sync>,
+    scrollback: Arc<Mutex<Scrollback>>,
+    exited: Arc<AtomicBool>,
     #[allow(dead_code)]
     reader_handle: Option<thread::JoinHandle<()>>,
 }
@@ -22,6 +160,7 @@ impl PtyTerminal {
         cols: u16,
         rows: u16,
         cwd: Option<String>,
+        options: TerminalOptions,
     ) -> Result<Self, String> {
         let pty_system = native_pty_system();
 
@@ -34,13 +173,21 @@ impl PtyTerminal {
             })
             .map_err(|e| e.to_string())?;
 
-        let shell = std::env::var("SHELL").unwrap_or_else(|_| "/bin/zsh".to_string());
+        let project = cwd
+            .as_deref()
+            .and_then(|cwd| load_project_config(std::path::Path::new(cwd)));
+        let profile = resolve_profile(
+            project.as_ref(),
+            options.profile.as_deref(),
+            &options.overrides,
+        );
+        let shell = profile.shell.clone().unwrap_or_else(default_shell);
 
         let mut cmd = CommandBuilder::new(&shell);
-        cmd.arg("-l"); // Login shell
+        cmd.args(login_args(&shell));
 
         // Set working directory
-        if let Some(cwd) = cwd {
+        if let Some(cwd) = &cwd {
             cmd.cwd(cwd);
         } else if let Some(home) = dirs::home_dir() {
             cmd.cwd(home);
@@ -49,6 +196,9 @@ impl PtyTerminal {
         // Set environment variables
         cmd.env("TERM", "xterm-256color");
         cmd.env("COLORTERM", "truecolor");
+        for (key, value) in &profile.env {
+            cmd.env(key, value);
+        }
 
         let child = pair.slave.spawn_command(cmd).map_err(|e| e.to_string())?;
 
@@ -59,41 +209,96 @@ impl PtyTerminal {
         let writer = master.take_writer().map_err(|e| e.to_string())?;
         let writer = Arc::new(Mutex::new(writer));
 
+        // Type startup commands; the shell reads them once it is ready
+        if !profile.startup_commands.is_empty() {
+            let mut writer = writer.lock();
+            for command in &profile.startup_commands {
+                writer
+                    .write_all(format!("{}\r", command).as_bytes())
+                    .map_err(|e| e.to_string())?;
+            }
+            writer.flush().map_err(|e| e.to_string())?;
+        }
+
         // Create reader for streaming output
         let mut reader = master.try_clone_reader().map_err(|e| e.to_string())?;
         let id_clone = id.clone();
+        let scrollback = Arc::new(Mutex::new(Scrollback::default()));
+        let output = Arc::new(PtyOutput {
+            id: id.clone(),
+            app_handle: app_handle.clone(),
+            project: cwd.clone(),
+            scrollback: scrollback.clone(),
+            stream: Mutex::new(RedactingStream::default()),
+            held_reads: AtomicU64::new(0),
+        });
+        let exited = Arc::new(AtomicBool::new(false));
+        let exited_clone = exited.clone();
 
         let reader_handle = thread::spawn(move || {
             let mut buffer = [0u8; 4096];
             loop {
                 match reader.read(&mut buffer) {
                     Ok(0) => break, // EOF
-                    Ok(n) => {
-                        let data = String::from_utf8_lossy(&buffer[..n]).to_string();
-                        let _ = app_handle.emit(&format!("pty-output-{}", id_clone), data);
-                    }
+                    Ok(n) => output.push(&String::from_utf8_lossy(&buffer[..n])),
                     Err(_) => break,
                 }
             }
+            output.flush();
+            exited_clone.store(true, Ordering::SeqCst);
+            recording::stop(&id_clone);
+            let _ = app_handle.emit(&format!("pty-exit-{}", id_clone), &id_clone);
         });
 
+        let info = TerminalInfo {
+            id: id.clone(),
+            session_id: options.session_id,
+            name: options.name,
+            cwd,
+            shell,
+            keep_alive: options.keep_alive,
+            cols,
+            rows,
+            created_at: chrono::Utc::now().timestamp_millis(),
+            exited: false,
+        };
+
         Ok(Self {
             id,
+            info,
             master,
             writer,
             child,
+            scrollback,
+            exited,
             reader_handle: Some(reader_handle),
         })
     }
 
+    pub fn info(&self) -> TerminalInfo {
+        TerminalInfo {
+            exited: self.exited.load(Ordering::SeqCst),
+            ..self.info.clone()
+        }
+    }
+
+    pub fn attachment(&self) -> TerminalAttachment {
+        TerminalAttachment {
+            info: self.info(),
+            scrollback: self.scrollback.lock().buffer.clone(),
+        }
+    }
+
     pub fn write(&self, data: &str) -> Result<(), String> {
         let mut writer = self.writer.lock();
-        writer.write_all(data.as_bytes()).map_err(|e| e.to_string())?;
+        writer
+            .write_all(data.as_bytes())
+            .map_err(|e| e.to_string())?;
         writer.flush().map_err(|e| e.to_string())?;
         Ok(())
     }
 
-    pub fn resize(&self, cols: u16, rows: u16) -> Result<(), String> {
+    pub fn resize(&mut self, cols: u16, rows: u16) -> Result<(), String> {
         self.master
             .resize(PtySize {
                 rows,
@@ -101,12 +306,31 @@ impl PtyTerminal {
                 pixel_width: 0,
                 pixel_height: 0,
             })
-            .map_err(|e| e.to_string())
+            .map_err(|e| e.to_string())?;
+        self.info.cols = cols;
+        self.info.rows = rows;
+        recording::record_resize(&self.id, cols, rows);
+        Ok(())
     }
 
     pub fn kill(&mut self) -> Result<(), String> {
         self.child.kill().map_err(|e| e.to_string())
     }
+
+    /// Interrupt the way Ctrl+C does (the pty delivers it to whatever runs
+    /// in the foreground), or terminate the shell and everything under it
+    pub fn signal(&self, signal: TerminalSignal) -> Result<(), String> {
+        match signal {
+            TerminalSignal::Interrupt => self.write("\x03"),
+            TerminalSignal::Terminate => {
+                let pid = self
+                    .child
+                    .process_id()
+                    .ok_or_else(|| format!("Terminal {} has exited", self.id))?;
+                signal_tree(pid, signal).map(|_| ())
+            }
+        }
+    }
 }
 
 pub struct TerminalManager {
@@ -126,8 +350,9 @@ impl TerminalManager {
         cols: u16,
         rows: u16,
         cwd: Option<String>,
+        options: TerminalOptions,
     ) -> Result<String, String> {
-        let terminal = PtyTerminal::new(app_handle, cols, rows, cwd)?;
+        let terminal = PtyTerminal::new(app_handle, cols, rows, cwd, options)?;
         let id = terminal.id.clone();
         self.terminals.insert(id.clone(), terminal);
         Ok(id)
@@ -141,14 +366,26 @@ impl TerminalManager {
         terminal.write(data)
     }
 
-    pub fn resize(&self, id: &str, cols: u16, rows: u16) -> Result<(), String> {
+    pub fn resize(&mut self, id: &str, cols: u16, rows: u16) -> Result<(), String> {
         let terminal = self
             .terminals
-            .get(id)
+            .get_mut(id)
             .ok_or_else(|| format!("Terminal not found: {}", id))?;
         terminal.resize(cols, rows)
     }
 
+    pub fn contains(&self, id: &str) -> bool {
+        self.terminals.contains_key(id)
+    }
+
+    pub fn signal(&self, id: &str, signal: TerminalSignal) -> Result<(), String> {
+        let terminal = self
+            .terminals
+            .get(id)
+            .ok_or_else(|| format!("Terminal not found: {}", id))?;
+        terminal.signal(signal)
+    }
+
     pub fn kill(&mut self, id: &str) -> Result<(), String> {
         if let Some(mut terminal) = self.terminals.remove(id) {
             terminal.kill()
@@ -156,6 +393,68 @@ impl TerminalManager {
             Err(format!("Terminal not found: {}", id))
         }
     }
+
+    /// List terminals, optionally only those owned by a session
+    pub fn list(&self, session_id: Option<&str>) -> Vec<TerminalInfo> {
+        let mut terminals: Vec<TerminalInfo> = self
+            .terminals
+            .values()
+            .map(|t| t.info())
+            .filter(|info| session_id.is_none() || info.session_id.as_deref() == session_id)
+            .collect();
+        terminals.sort_by_key(|info| info.created_at);
+        terminals
+    }
+
+    pub fn attach(&self, id: &str) -> Result<TerminalAttachment, String> {
+        self.terminals
+            .get(id)
+            .map(|t| t.attachment())
+            .ok_or_else(|| format!("Terminal not found: {}", id))
+    }
+
+    /// Start an asciicast recording of a terminal
+    pub fn start_recording(&self, id: &str) -> Result<RecordingInfo, String> {
+        let terminal = self
+            .terminals
+            .get(id)
+            .ok_or_else(|| format!("Terminal not found: {}", id))?;
+        let info = &terminal.info;
+        recording::start(
+            id,
+            info.session_id.clone(),
+            info.cols,
+            info.rows,
+            info.name.as_deref(),
+        )
+    }
+
+    pub fn set_keep_alive(&mut self, id: &str, keep_alive: bool) -> Result<(), String> {
+        let terminal = self
+            .terminals
+            .get_mut(id)
+            .ok_or_else(|| format!("Terminal not found: {}", id))?;
+        terminal.info.keep_alive = keep_alive;
+        Ok(())
+    }
+
+    /// Kill a closed session's terminals, except keep-alive ones.
+    /// Returns the ids of the killed terminals.
+    pub fn close_session(&mut self, session_id: &str) -> Vec<String> {
+        let ids: Vec<String> = self
+            .terminals
+            .values()
+            .filter(|t| t.info.session_id.as_deref() == Some(session_id) && !t.info.keep_alive)
+            .map(|t| t.id.clone())
+            .collect();
+
+        for id in &ids {
+            if let Err(e) = self.kill(id) {
+                eprintln!("[PTY] Failed to kill terminal {}: {}", id, e);
+            }
+        }
+        ids
+    }
 }
 
 impl Default for TerminalManager {
@@ -168,3 +467,27 @@ impl Default for TerminalManager {
 lazy_static::lazy_static! {
     pub static ref TERMINAL_MANAGER: Arc<Mutex<TerminalManager>> = Arc::new(Mutex::new(TerminalManager::new()));
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scrollback_drops_oldest_output() {
+        let mut scrollback = Scrollback::default();
+        scrollback.push("first\n");
+        scrollback.push(&"x".repeat(SCROLLBACK_LIMIT));
+        assert_eq!(scrollback.buffer.len(), SCROLLBACK_LIMIT);
+        assert!(!scrollback.buffer.contains("first"));
+        assert!(scrollback.truncated());
+    }
+
+    #[test]
+    fn test_scrollback_cuts_on_char_boundary() {
+        let mut scrollback = Scrollback::default();
+        scrollback.push("é");
+        scrollback.push(&"x".repeat(SCROLLBACK_LIMIT - 1));
+        assert!(scrollback.buffer.len() <= SCROLLBACK_LIMIT);
+        assert!(scrollback.buffer.chars().all(|c| c == 'x'));
+    }
+}
@@ -0,0 +1,294 @@
+use crate::acp::events::BusEvent;
+use crate::events::{emit, RemoteHostStatusEvent};
+use crate::settings::store::RemoteHost;
+use futures::StreamExt;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::watch;
+use tokio_tungstenite::tungstenite::Message;
+use ts_rs::TS;
+
+/// Delay before the first reconnect (doubled on each attempt)
+const BASE_BACKOFF: Duration = Duration::from_secs(1);
+
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum ConnectionState {
+    Connecting,
+    Connected,
+    Reconnecting,
+    Disconnected,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct RemoteConnectionStatus {
+    pub host_id: String,
+    pub state: ConnectionState,
+    /// Consecutive failed connection attempts
+    pub attempt: u32,
+    /// Last event sequence number received from the host
+    #[ts(type = "number | null")]
+    pub last_seq: Option<u64>,
+    pub error: Option<String>,
+}
+
+struct RemoteConnection {
+    status: RemoteConnectionStatus,
+    /// Dropping the sender stops the connection task
+    _stop: watch::Sender<()>,
+}
+
+static CONNECTIONS: Lazy<Mutex<HashMap<String, RemoteConnection>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// `{base}{path}` without doubled slashes
+pub fn api_url(base: &str, path: &str) -> String {
+    format!("{}{}", base.trim_end_matches('/'), path)
+}
+
+/// WebSocket URL for a host's event stream
+fn events_url(base: &str, token: &str, since: Option<u64>) -> Result<String, String> {
+    let base = base.trim_end_matches('/');
+    let ws_base = if let Some(rest) = base.strip_prefix("https://") {
+        format!("wss://{}", rest)
+    } else if let Some(rest) = base.strip_prefix("http://") {
+        format!("ws://{}", rest)
+    } else if base.starts_with("ws://") || base.starts_with("wss://") {
+        base.to_string()
+    } else {
+        return Err(format!("Unsupported remote host URL: {}", base));
+    };
+
+    let mut url = format!("{}/api/events?token={}", ws_base, token);
+    if let Some(since) = since {
+        url.push_str(&format!("&since={}", since));
+    }
+    Ok(url)
+}
+
+/// Delay before reconnect attempt `attempt` (1-based)
+fn backoff(attempt: u32) -> Duration {
+    (BASE_BACKOFF * 2u32.pow(attempt.saturating_sub(1).min(5))).min(MAX_BACKOFF)
+}
+
+/// Update a connection's status and tell the frontend
+fn update_status(app: &AppHandle, host_id: &str, f: impl FnOnce(&mut RemoteConnectionStatus)) {
+    let status = {
+        let mut connections = CONNECTIONS.lock();
+        let Some(connection) = connections.get_mut(host_id) else {
+            return;
+        };
+        f(&mut connection.status);
+        connection.status.clone()
+    };
+    emit(app, &RemoteHostStatusEvent { status });
+}
+
+/// Tag an event's payload with the host it came from. Object payloads get a
+/// `host_id` field, so listeners for the local event still understand them.
+fn tag_payload(payload: serde_json::Value, host_id: &str) -> serde_json::Value {
+    match payload {
+        serde_json::Value::Object(mut fields) => {
+            fields.insert("host_id".to_string(), host_id.into());
+            serde_json::Value::Object(fields)
+        }
+        payload => serde_json::json!({ "host_id": host_id, "payload": payload }),
+    }
+}
+
+/// Re-emit one event from the host. Returns the new `since` value.
+fn handle_message(app: &AppHandle, host_id: &str, text: &str, since: Option<u64>) -> Option<u64> {
+    let Ok(event) = serde_json::from_str::<BusEvent>(text) else {
+        return since;
+    };
+
+    // Control events carry no sequence number
+    if event.seq == 0 {
+        if event.event == "resync" {
+            eprintln!("[Remote] {} requested resync: {}", host_id, event.payload);
+            let _ = app.emit(
+                "remote-resync",
+                serde_json::json!({ "host_id": host_id, "reason": event.payload["reason"] }),
+            );
+        }
+        return since;
+    }
+
+    let _ = app.emit(&event.event, tag_payload(event.payload, host_id));
+    if let Some(connection) = CONNECTIONS.lock().get_mut(host_id) {
+        connection.status.last_seq = Some(event.seq);
+    }
+    Some(event.seq)
+}
+
+/// Stream events from a host until stopped, reconnecting on failure
+async fn run_connection(app: AppHandle, host: RemoteHost, mut stop: watch::Receiver<()>) {
+    let mut since: Option<u64> = None;
+    let mut attempt: u32 = 0;
+
+    loop {
+        let url = match events_url(&host.url, &host.token, since) {
+            Ok(url) => url,
+            Err(e) => {
+                update_status(&app, &host.id, |s| {
+                    s.state = ConnectionState::Disconnected;
+                    s.error = Some(e);
+                });
+                // Nothing is left running, so a later connect starts afresh
+                CONNECTIONS.lock().remove(&host.id);
+                return;
+            }
+        };
+
+        let connected = tokio::select! {
+            result = tokio_tungstenite::connect_async(url.as_str()) => result,
+            _ = stop.changed() => return,
+        };
+
+        match connected {
+            Ok((mut socket, _)) => {
+                attempt = 0;
+                eprintln!("[Remote] Connected to {}", host.name);
+                update_status(&app, &host.id, |s| {
+                    s.state = ConnectionState::Connected;
+                    s.attempt = 0;
+                    s.error = None;
+                });
+
+                loop {
+                    tokio::select! {
+                        message = socket.next() => match message {
+                            Some(Ok(Message::Text(text))) => {
+                                since = handle_message(&app, &host.id, &text, since);
+                            }
+                            Some(Ok(Message::Close(_))) | None => break,
+                            Some(Err(e)) => {
+                                update_status(&app, &host.id, |s| s.error = Some(e.to_string()));
+                                break;
+                            }
+                            Some(Ok(_)) => {}
+                        },
+                        _ = stop.changed() => {
+                            let _ = socket.close(None).await;
+                            return;
+                        }
+                    }
+                }
+                eprintln!("[Remote] Lost connection to {}", host.name);
+            }
+            Err(e) => {
+                eprintln!("[Remote] Failed to connect to {}: {}", host.name, e);
+                update_status(&app, &host.id, |s| s.error = Some(e.to_string()));
+            }
+        }
+
+        attempt += 1;
+        update_status(&app, &host.id, |s| {
+            s.state = ConnectionState::Reconnecting;
+            s.attempt = attempt;
+        });
+        tokio::select! {
+            _ = tokio::time::sleep(backoff(attempt)) => {}
+            _ = stop.changed() => return,
+        }
+    }
+}
+
+/// Attach to a host's event stream (no-op if already attached)
+pub fn connect(app: AppHandle, host: RemoteHost) -> RemoteConnectionStatus {
+    let (stop_tx, stop_rx) = watch::channel(());
+    let status = {
+        let mut connections = CONNECTIONS.lock();
+        if let Some(existing) = connections.get(&host.id) {
+            return existing.status.clone();
+        }
+        let status = RemoteConnectionStatus {
+            host_id: host.id.clone(),
+            state: ConnectionState::Connecting,
+            attempt: 0,
+            last_seq: None,
+            error: None,
+        };
+        connections.insert(
+            host.id.clone(),
+            RemoteConnection {
+                status: status.clone(),
+                _stop: stop_tx,
+            },
+        );
+        status
+    };
+
+    tauri::async_runtime::spawn(run_connection(app, host, stop_rx));
+    status
+}
+
+/// Detach from a host. Returns false if it wasn't attached.
+pub fn disconnect(app: &AppHandle, host_id: &str) -> bool {
+    let Some(connection) = CONNECTIONS.lock().remove(host_id) else {
+        return false;
+    };
+    emit(
+        app,
+        &RemoteHostStatusEvent {
+            status: RemoteConnectionStatus {
+                state: ConnectionState::Disconnected,
+                ..connection.status
+            },
+        },
+    );
+    true
+}
+
+pub fn statuses() -> Vec<RemoteConnectionStatus> {
+    CONNECTIONS
+        .lock()
+        .values()
+        .map(|c| c.status.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_events_url() {
+        assert_eq!(
+            events_url("http://box:7420/", "t", None).unwrap(),
+            "ws://box:7420/api/events?token=t"
+        );
+        assert_eq!(
+            events_url("https://box.example.com", "t", Some(42)).unwrap(),
+            "wss://box.example.com/api/events?token=t&since=42"
+        );
+        assert!(events_url("box:7420", "t", None).is_err());
+    }
+
+    #[test]
+    fn test_tag_payload() {
+        assert_eq!(
+            tag_payload(serde_json::json!({ "worker_id": "w1" }), "box"),
+            serde_json::json!({ "worker_id": "w1", "host_id": "box" })
+        );
+        assert_eq!(
+            tag_payload(serde_json::json!("done"), "box"),
+            serde_json::json!({ "host_id": "box", "payload": "done" })
+        );
+    }
+
+    #[test]
+    fn test_backoff() {
+        assert_eq!(backoff(1), Duration::from_secs(1));
+        assert_eq!(backoff(3), Duration::from_secs(4));
+        assert_eq!(backoff(10), MAX_BACKOFF);
+    }
+}
@@ -0,0 +1,142 @@
+use super::client::{self, api_url, RemoteConnectionStatus};
+use crate::acp::commands::AcpSessionResponse;
+use crate::acp::registry::AgentConfig;
+use crate::orchestrator::session::OrchestratorSession;
+use crate::settings::load_settings;
+use crate::settings::store::RemoteHost;
+use serde::de::DeserializeOwned;
+use std::time::Duration;
+use tauri::AppHandle;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+fn remote_host(host_id: &str) -> Result<RemoteHost, String> {
+    load_settings()
+        .remote_hosts
+        .into_iter()
+        .find(|h| h.id == host_id)
+        .ok_or_else(|| format!("Remote host not found: {}", host_id))
+}
+
+/// Send an authenticated request to a host's API and check the status
+async fn send(
+    host: &RemoteHost,
+    method: reqwest::Method,
+    path: &str,
+    body: Option<serde_json::Value>,
+) -> Result<reqwest::Response, String> {
+    let client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    let mut request = client
+        .request(method, api_url(&host.url, path))
+        .bearer_auth(&host.token);
+    if let Some(body) = body {
+        request = request.json(&body);
+    }
+
+    let response = request
+        .send()
+        .await
+        .map_err(|e| format!("{} is unreachable: {}", host.name, e))?;
+    if response.status().is_success() {
+        return Ok(response);
+    }
+
+    let status = response.status();
+    let error = response
+        .json::<serde_json::Value>()
+        .await
+        .ok()
+        .and_then(|v| v["error"].as_str().map(str::to_string))
+        .unwrap_or_else(|| status.to_string());
+    Err(format!("{}: {}", host.name, error))
+}
+
+async fn get<T: DeserializeOwned>(host: &RemoteHost, path: &str) -> Result<T, String> {
+    send(host, reqwest::Method::GET, path, None)
+        .await?
+        .json()
+        .await
+        .map_err(|e| format!("Invalid response from {}: {}", host.name, e))
+}
+
+/// Attach to a remote host's event stream
+#[tauri::command]
+pub fn connect_remote_host(
+    host_id: String,
+    app_handle: AppHandle,
+) -> Result<RemoteConnectionStatus, String> {
+    let host = remote_host(&host_id)?;
+    Ok(client::connect(app_handle, host))
+}
+
+#[tauri::command]
+pub fn disconnect_remote_host(host_id: String, app_handle: AppHandle) -> bool {
+    client::disconnect(&app_handle, &host_id)
+}
+
+#[tauri::command]
+pub fn list_remote_connections() -> Vec<RemoteConnectionStatus> {
+    client::statuses()
+}
+
+#[tauri::command]
+pub async fn list_remote_agents(host_id: String) -> Result<Vec<AgentConfig>, String> {
+    get(&remote_host(&host_id)?, "/api/agents").await
+}
+
+#[tauri::command]
+pub async fn list_remote_sessions(host_id: String) -> Result<Vec<OrchestratorSession>, String> {
+    get(&remote_host(&host_id)?, "/api/sessions").await
+}
+
+/// Start a session on a remote host (cwd is a path on that host)
+#[tauri::command]
+pub async fn create_remote_session(
+    host_id: String,
+    prompt: String,
+    agent_id: String,
+    model_id: Option<String>,
+    cwd: String,
+) -> Result<AcpSessionResponse, String> {
+    let host = remote_host(&host_id)?;
+    let body = serde_json::json!({
+        "prompt": prompt,
+        "agent_id": agent_id,
+        "model_id": model_id,
+        "cwd": cwd,
+    });
+    send(&host, reqwest::Method::POST, "/api/sessions", Some(body))
+        .await?
+        .json()
+        .await
+        .map_err(|e| format!("Invalid response from {}: {}", host.name, e))
+}
+
+#[tauri::command]
+pub async fn send_remote_prompt(
+    host_id: String,
+    session_id: String,
+    prompt: String,
+) -> Result<(), String> {
+    let host = remote_host(&host_id)?;
+    let path = format!("/api/sessions/{}/prompt", session_id);
+    let body = serde_json::json!({ "prompt": prompt });
+    send(&host, reqwest::Method::POST, &path, Some(body)).await?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn respond_to_remote_permission(
+    host_id: String,
+    worker_id: String,
+    option_id: String,
+) -> Result<(), String> {
+    let host = remote_host(&host_id)?;
+    let path = format!("/api/workers/{}/permission", worker_id);
+    let body = serde_json::json!({ "option_id": option_id });
+    send(&host, reqwest::Method::POST, &path, Some(body)).await?;
+    Ok(())
+}
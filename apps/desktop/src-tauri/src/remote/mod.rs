@@ -0,0 +1,11 @@
+//! Remote agent hosts
+//!
+//! Attaches this app to sessions running on another machine's API server
+//! (see `api`). Worker events streamed from the host are re-emitted locally
+//! under their original names, so the usual worker views render remote
+//! sessions unchanged. Dropped connections reconnect with backoff and
+//! replay the events missed in between; if too much was missed a
+//! `remote-resync` event asks the UI to refetch the host's sessions.
+
+pub mod client;
+pub mod commands;
@@ -0,0 +1,78 @@
+use super::runner::{next_run, run_job};
+use super::store::{JobStore, ScheduledJob};
+use crate::acp::registry::get_agent_config;
+use chrono::Local;
+use tauri::AppHandle;
+use uuid::Uuid;
+
+/// Create a recurring job, e.g. schedule "0 3 * * *" runs nightly at 3:00
+#[tauri::command]
+pub fn create_scheduled_job(
+    name: String,
+    schedule: String,
+    prompt: String,
+    agent_id: String,
+    model_id: Option<String>,
+    cwd: String,
+) -> Result<ScheduledJob, String> {
+    get_agent_config(&agent_id).ok_or_else(|| format!("Unknown agent: {}", agent_id))?;
+    let next_run_at = next_run(&schedule, Local::now())?;
+
+    let job = ScheduledJob {
+        id: Uuid::new_v4().to_string(),
+        name,
+        schedule,
+        prompt,
+        agent_id,
+        model_id: model_id.filter(|m| !m.is_empty()),
+        cwd,
+        enabled: true,
+        created_at: chrono::Utc::now().timestamp(),
+        next_run_at,
+        last_run_at: None,
+        last_session_id: None,
+        last_status: None,
+        last_error: None,
+    };
+    JobStore::new()?.modify(|jobs| {
+        jobs.push(job.clone());
+        Ok(())
+    })?;
+    Ok(job)
+}
+
+#[tauri::command]
+pub fn list_scheduled_jobs() -> Result<Vec<ScheduledJob>, String> {
+    JobStore::new()?.load()
+}
+
+/// Pause or resume a job's schedule
+#[tauri::command]
+pub fn set_scheduled_job_enabled(job_id: String, enabled: bool) -> Result<ScheduledJob, String> {
+    JobStore::new()?.update(&job_id, |job| {
+        job.enabled = enabled;
+        job.next_run_at = if enabled {
+            next_run(&job.schedule, Local::now()).ok().flatten()
+        } else {
+            None
+        };
+    })
+}
+
+#[tauri::command]
+pub fn delete_scheduled_job(job_id: String) -> Result<(), String> {
+    JobStore::new()?.modify(|jobs| {
+        let before = jobs.len();
+        jobs.retain(|j| j.id != job_id);
+        if jobs.len() == before {
+            return Err(format!("Scheduled job not found: {}", job_id));
+        }
+        Ok(())
+    })
+}
+
+/// Run a job immediately (its schedule is unchanged)
+#[tauri::command]
+pub async fn run_job_now(job_id: String, app_handle: AppHandle) -> Result<ScheduledJob, String> {
+    run_job(&app_handle, &job_id).await
+}
@@ -0,0 +1,202 @@
+//! Minimal cron expressions
+//!
+//! Standard five fields (`minute hour day-of-month month day-of-week`) with
+//! `*`, numbers, ranges (`1-5`), steps (`*/15`, `0-30/10`) and lists
+//! (`1,15`), plus the `@hourly`, `@daily`, `@weekly` and `@monthly`
+//! shortcuts. Times are evaluated in local time.
+
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, TimeZone, Timelike};
+
+/// How far ahead `next_after` searches before giving up
+const MAX_LOOKAHEAD_DAYS: i64 = 366 * 4;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Schedule {
+    minutes: Vec<bool>,
+    hours: Vec<bool>,
+    days: Vec<bool>,
+    months: Vec<bool>,
+    weekdays: Vec<bool>,
+    /// Both day fields restricted: a day matches if either matches (cron semantics)
+    day_or_weekday: bool,
+}
+
+/// Parse one field into a membership table indexed by value
+fn parse_field(field: &str, min: u32, max: u32, name: &str) -> Result<Vec<bool>, String> {
+    let mut allowed = vec![false; max as usize + 1];
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step
+                    .parse()
+                    .map_err(|_| format!("Invalid step '{}' in {} field", step, name))?;
+                if step == 0 {
+                    return Err(format!("Step must be positive in {} field", name));
+                }
+                (range, step)
+            }
+            None => (part, 1),
+        };
+
+        let parse = |value: &str| -> Result<u32, String> {
+            value
+                .parse()
+                .ok()
+                .filter(|v| (min..=max).contains(v))
+                .ok_or_else(|| format!("Invalid {} '{}' (expected {}-{})", name, value, min, max))
+        };
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (parse(start)?, parse(end)?)
+        } else if step > 1 {
+            // "5/15" means every 15 starting at 5
+            (parse(range)?, max)
+        } else {
+            let value = parse(range)?;
+            (value, value)
+        };
+        if start > end {
+            return Err(format!("Invalid range '{}' in {} field", range, name));
+        }
+
+        for value in (start..=end).step_by(step as usize) {
+            allowed[value as usize] = true;
+        }
+    }
+    Ok(allowed)
+}
+
+impl Schedule {
+    pub fn parse(expression: &str) -> Result<Self, String> {
+        let expression = match expression.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            other => other,
+        };
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(format!(
+                "Expected 5 cron fields (minute hour day month weekday), got {}",
+                fields.len()
+            ));
+        };
+
+        let mut weekdays = parse_field(weekday, 0, 7, "weekday")?;
+        // 7 is an alias for Sunday
+        if weekdays[7] {
+            weekdays[0] = true;
+        }
+        weekdays.truncate(7);
+
+        Ok(Self {
+            minutes: parse_field(minute, 0, 59, "minute")?,
+            hours: parse_field(hour, 0, 23, "hour")?,
+            days: parse_field(day, 1, 31, "day")?,
+            months: parse_field(month, 1, 12, "month")?,
+            weekdays,
+            day_or_weekday: day != "*" && weekday != "*",
+        })
+    }
+
+    fn matches_day(&self, date: NaiveDate) -> bool {
+        let day = self.days[date.day() as usize];
+        let weekday = self.weekdays[date.weekday().num_days_from_sunday() as usize];
+        if self.day_or_weekday {
+            day || weekday
+        } else {
+            day && weekday
+        }
+    }
+
+    /// First matching local time strictly after `after`
+    pub fn next_after(&self, after: DateTime<Local>) -> Option<DateTime<Local>> {
+        let start = after.naive_local().with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        let limit = start + Duration::days(MAX_LOOKAHEAD_DAYS);
+        let mut t: NaiveDateTime = start;
+
+        while t < limit {
+            let date = t.date();
+            if !self.months[date.month() as usize] || !self.matches_day(date) {
+                t = date.succ_opt()?.and_hms_opt(0, 0, 0)?;
+                continue;
+            }
+            if !self.hours[t.hour() as usize] {
+                t = date.and_hms_opt(t.hour(), 0, 0)? + Duration::hours(1);
+                continue;
+            }
+            if !self.minutes[t.minute() as usize] {
+                t += Duration::minutes(1);
+                continue;
+            }
+            // Skip times that don't exist locally (DST gaps)
+            if let Some(local) = Local.from_local_datetime(&t).earliest() {
+                return Some(local);
+            }
+            t += Duration::minutes(1);
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn local(y: i32, mo: u32, d: u32, h: u32, mi: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(y, mo, d, h, mi, 0).unwrap()
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Schedule::parse("* * * *").is_err());
+        assert!(Schedule::parse("60 * * * *").is_err());
+        assert!(Schedule::parse("*/0 * * * *").is_err());
+        assert!(Schedule::parse("5-1 * * * *").is_err());
+        assert!(Schedule::parse("@daily").is_ok());
+    }
+
+    #[test]
+    fn test_next_after() {
+        let nightly = Schedule::parse("30 2 * * *").unwrap();
+        assert_eq!(
+            nightly.next_after(local(2025, 3, 10, 1, 0)),
+            Some(local(2025, 3, 10, 2, 30))
+        );
+        assert_eq!(
+            nightly.next_after(local(2025, 3, 10, 2, 30)),
+            Some(local(2025, 3, 11, 2, 30))
+        );
+
+        let quarter = Schedule::parse("*/15 * * * *").unwrap();
+        assert_eq!(
+            quarter.next_after(local(2025, 3, 10, 9, 7)),
+            Some(local(2025, 3, 10, 9, 15))
+        );
+
+        // 2025-03-10 is a Monday; weekdays only at 9:00
+        let weekdays = Schedule::parse("0 9 * * 1-5").unwrap();
+        assert_eq!(
+            weekdays.next_after(local(2025, 3, 14, 10, 0)),
+            Some(local(2025, 3, 17, 9, 0))
+        );
+
+        let monthly = Schedule::parse("@monthly").unwrap();
+        assert_eq!(
+            monthly.next_after(local(2025, 12, 15, 0, 0)),
+            Some(local(2026, 1, 1, 0, 0))
+        );
+    }
+
+    #[test]
+    fn test_day_or_weekday() {
+        // The 1st of the month or any Sunday
+        let schedule = Schedule::parse("0 0 1 * 0").unwrap();
+        assert_eq!(
+            schedule.next_after(local(2025, 3, 10, 0, 0)),
+            Some(local(2025, 3, 16, 0, 0))
+        );
+    }
+}
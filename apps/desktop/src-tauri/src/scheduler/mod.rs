@@ -0,0 +1,15 @@
+//! Scheduled agent jobs
+//!
+//! Recurring jobs ("every night run /update-deps in project X with agent
+//! Y") defined by cron expressions and persisted in
+//! ~/.crafter-code/scheduled-jobs.json. Each run starts a regular ACP
+//! session through `start_acp_session`, so it shows up and is saved like
+//! any other session.
+
+pub mod commands;
+mod cron;
+mod runner;
+mod store;
+
+pub use runner::{is_job_session, record_session_result, register_job_session, start};
+pub use store::ScheduledJob;
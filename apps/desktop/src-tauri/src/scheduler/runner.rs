@@ -0,0 +1,240 @@
+use super::cron::Schedule;
+use super::store::{JobRunStatus, JobStore, ScheduledJob};
+use crate::acp::commands::start_acp_session;
+use crate::events::{emit, ScheduledJobUpdateEvent};
+use crate::AppState;
+use chrono::{DateTime, Local};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+/// How often due jobs are checked
+const TICK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Sessions started by a job run -> job id
+static JOB_SESSIONS: Lazy<Mutex<HashMap<String, String>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Unix timestamp of the next run after `after`
+pub fn next_run(schedule: &str, after: DateTime<Local>) -> Result<Option<i64>, String> {
+    Ok(Schedule::parse(schedule)?
+        .next_after(after)
+        .map(|t| t.timestamp()))
+}
+
+fn emit_update(app: &AppHandle, job: &ScheduledJob) {
+    emit(app, &ScheduledJobUpdateEvent { job: job.clone() });
+}
+
+/// Record a session as a run of a job. Called before the session's worker
+/// starts, so its result can't arrive first.
+pub fn register_job_session(session_id: &str, job_id: &str) {
+    JOB_SESSIONS
+        .lock()
+        .insert(session_id.to_string(), job_id.to_string());
+}
+
+/// Whether a session was started by a scheduled job
+pub fn is_job_session(session_id: &str) -> bool {
+    JOB_SESSIONS.lock().contains_key(session_id)
+}
+
+/// Record how a job's session ended. No-op for other sessions.
+pub fn record_session_result(app: &AppHandle, session_id: &str, error: Option<&str>) {
+    let Some(job_id) = JOB_SESSIONS.lock().remove(session_id) else {
+        return;
+    };
+    let result = JobStore::new().and_then(|store| {
+        store.update(&job_id, |job| {
+            job.last_status = Some(if error.is_some() {
+                JobRunStatus::Failed
+            } else {
+                JobRunStatus::Succeeded
+            });
+            job.last_error = error.map(str::to_string);
+        })
+    });
+    match result {
+        Ok(job) => emit_update(app, &job),
+        Err(e) => eprintln!("[Scheduler] Failed to record result for {}: {}", job_id, e),
+    }
+}
+
+/// Start a job's session now, independent of its schedule
+pub async fn run_job(app: &AppHandle, job_id: &str) -> Result<ScheduledJob, String> {
+    let store = JobStore::new()?;
+    let job = store
+        .load()?
+        .into_iter()
+        .find(|j| j.id == job_id)
+        .ok_or_else(|| format!("Scheduled job not found: {}", job_id))?;
+
+    eprintln!("[Scheduler] Running job {} ({})", job.name, job.id);
+    // Marked running first: a short session can end before it's returned
+    store.update(job_id, |j| {
+        j.last_run_at = Some(chrono::Utc::now().timestamp());
+        j.last_status = Some(JobRunStatus::Running);
+        j.last_error = None;
+    })?;
+    let response = start_acp_session(
+        job.prompt.clone(),
+        job.agent_id.clone(),
+        job.model_id.clone(),
+        job.cwd.clone(),
+        None,
+        Some(job.id.clone()),
+        app.clone(),
+        app.state::<AppState>(),
+    )
+    .await
+    .map_err(|e| e.to_string());
+
+    let (job, result) = match response {
+        Ok(response) => {
+            let job = store.update(job_id, |j| {
+                j.last_session_id = Some(response.session.id);
+            })?;
+            (job.clone(), Ok(job))
+        }
+        Err(e) => {
+            let job = store.update(job_id, |j| {
+                j.last_status = Some(JobRunStatus::Failed);
+                j.last_error = Some(e.clone());
+            })?;
+            (job, Err(e))
+        }
+    };
+    emit_update(app, &job);
+    result
+}
+
+/// Advance schedules and return the ids of jobs that are due
+fn take_due_jobs(jobs: &mut [ScheduledJob], now: DateTime<Local>) -> Vec<String> {
+    let mut due = Vec::new();
+    for job in jobs.iter_mut().filter(|j| j.enabled) {
+        let is_due = job.next_run_at.is_some_and(|next| next <= now.timestamp());
+        if is_due {
+            // Don't stack runs of a slow job
+            if job.last_status == Some(JobRunStatus::Running) {
+                eprintln!(
+                    "[Scheduler] Skipping {}: previous run still active",
+                    job.name
+                );
+            } else {
+                due.push(job.id.clone());
+            }
+        }
+        if is_due || job.next_run_at.is_none() {
+            job.next_run_at = next_run(&job.schedule, now).ok().flatten();
+        }
+    }
+    due
+}
+
+async fn run_due_jobs(app: &AppHandle) {
+    let now = Local::now();
+    let due = JobStore::new().and_then(|store| {
+        let jobs = store.load()?;
+        let needs_update = jobs
+            .iter()
+            .any(|j| j.enabled && j.next_run_at.is_none_or(|next| next <= now.timestamp()));
+        if !needs_update {
+            return Ok(Vec::new());
+        }
+        store.modify(|jobs| Ok(take_due_jobs(jobs, now)))
+    });
+
+    match due {
+        Ok(due) => {
+            for job_id in due {
+                if let Err(e) = run_job(app, &job_id).await {
+                    eprintln!("[Scheduler] Job {} failed to start: {}", job_id, e);
+                }
+            }
+        }
+        Err(e) => eprintln!("[Scheduler] {}", e),
+    }
+}
+
+/// Start the background scheduler. Runs that were interrupted by the app
+/// quitting are marked failed; runs missed while it was closed fire once.
+pub fn start(app: AppHandle) {
+    let interrupted = JobStore::new().and_then(|store| {
+        store.modify(|jobs| {
+            for job in jobs
+                .iter_mut()
+                .filter(|j| j.last_status == Some(JobRunStatus::Running))
+            {
+                job.last_status = Some(JobRunStatus::Failed);
+                job.last_error = Some("Interrupted: the app was closed".to_string());
+            }
+            Ok(())
+        })
+    });
+    if let Err(e) = interrupted {
+        eprintln!("[Scheduler] {}", e);
+    }
+
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(TICK_INTERVAL);
+        loop {
+            interval.tick().await;
+            run_due_jobs(&app).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn job(id: &str, next_run_at: Option<i64>) -> ScheduledJob {
+        ScheduledJob {
+            id: id.to_string(),
+            name: id.to_string(),
+            schedule: "0 3 * * *".to_string(),
+            prompt: "/update-deps".to_string(),
+            agent_id: "claude".to_string(),
+            model_id: None,
+            cwd: "/tmp".to_string(),
+            enabled: true,
+            created_at: 0,
+            next_run_at,
+            last_run_at: None,
+            last_session_id: None,
+            last_status: None,
+            last_error: None,
+        }
+    }
+
+    #[test]
+    fn test_take_due_jobs() {
+        let now = Local.with_ymd_and_hms(2025, 3, 10, 12, 0, 0).unwrap();
+        let tomorrow_3am = Local
+            .with_ymd_and_hms(2025, 3, 11, 3, 0, 0)
+            .unwrap()
+            .timestamp();
+
+        let mut running = job("running", Some(now.timestamp() - 60));
+        running.last_status = Some(JobRunStatus::Running);
+        let mut disabled = job("disabled", Some(now.timestamp() - 60));
+        disabled.enabled = false;
+        let mut jobs = vec![
+            job("due", Some(now.timestamp() - 60)),
+            job("later", Some(now.timestamp() + 60)),
+            job("new", None),
+            running,
+            disabled,
+        ];
+
+        assert_eq!(take_due_jobs(&mut jobs, now), vec!["due".to_string()]);
+        assert_eq!(jobs[0].next_run_at, Some(tomorrow_3am));
+        assert_eq!(jobs[1].next_run_at, Some(now.timestamp() + 60));
+        assert_eq!(jobs[2].next_run_at, Some(tomorrow_3am));
+        assert_eq!(jobs[3].next_run_at, Some(tomorrow_3am));
+        assert_eq!(jobs[4].next_run_at, Some(now.timestamp() - 60));
+    }
+}
@@ -0,0 +1,160 @@
+use crate::secrets::at_rest::{self, Encryption};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use ts_rs::TS;
+
+/// Serializes read-modify-write cycles on the jobs file
+static WRITE_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum JobRunStatus {
+    Running,
+    Succeeded,
+    Failed,
+}
+
+/// A recurring agent job
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ScheduledJob {
+    pub id: String,
+    pub name: String,
+    /// Cron expression, e.g. "0 3 * * *" or "@daily"
+    pub schedule: String,
+    pub prompt: String,
+    pub agent_id: String,
+    #[serde(default)]
+    pub model_id: Option<String>,
+    pub cwd: String,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    #[ts(type = "number")]
+    pub created_at: i64,
+    /// Unix timestamp of the next scheduled run
+    #[serde(default)]
+    #[ts(type = "number | null")]
+    pub next_run_at: Option<i64>,
+    #[serde(default)]
+    #[ts(type = "number | null")]
+    pub last_run_at: Option<i64>,
+    /// Session created by the last run
+    #[serde(default)]
+    pub last_session_id: Option<String>,
+    #[serde(default)]
+    pub last_status: Option<JobRunStatus>,
+    #[serde(default)]
+    pub last_error: Option<String>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Loads and saves job definitions in ~/.crafter-code/scheduled-jobs.json
+/// (encrypted when `encrypt_sessions` is on)
+pub struct JobStore {
+    path: PathBuf,
+    encryption: Encryption,
+}
+
+impl JobStore {
+    pub fn new() -> Result<Self, String> {
+        let base_path = dirs::home_dir()
+            .ok_or_else(|| "Could not determine home directory".to_string())?
+            .join(".crafter-code");
+
+        fs::create_dir_all(&base_path)
+            .map_err(|e| format!("Failed to create jobs directory: {}", e))?;
+
+        Ok(Self::with_path(
+            base_path.join("scheduled-jobs.json"),
+            Encryption::Configured,
+        ))
+    }
+
+    pub fn with_path(path: PathBuf, encryption: Encryption) -> Self {
+        Self { path, encryption }
+    }
+
+    pub fn load(&self) -> Result<Vec<ScheduledJob>, String> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        let json = at_rest::read_to_string(&self.path, self.encryption)
+            .map_err(|e| format!("Failed to read jobs file: {}", e))?;
+        serde_json::from_str(&json).map_err(|e| format!("Failed to parse jobs file: {}", e))
+    }
+
+    pub fn save(&self, jobs: &[ScheduledJob]) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(jobs)
+            .map_err(|e| format!("Failed to serialize jobs: {}", e))?;
+        at_rest::write(&self.path, json.as_bytes(), self.encryption)
+            .map_err(|e| format!("Failed to write jobs file: {}", e))
+    }
+
+    /// Load, modify and save the job list atomically
+    pub fn modify<T, F>(&self, f: F) -> Result<T, String>
+    where
+        F: FnOnce(&mut Vec<ScheduledJob>) -> Result<T, String>,
+    {
+        let _guard = WRITE_LOCK.lock();
+        let mut jobs = self.load()?;
+        let result = f(&mut jobs)?;
+        self.save(&jobs)?;
+        Ok(result)
+    }
+
+    /// Modify one job and save. Returns the updated job.
+    pub fn update<F>(&self, job_id: &str, f: F) -> Result<ScheduledJob, String>
+    where
+        F: FnOnce(&mut ScheduledJob),
+    {
+        self.modify(|jobs| {
+            let job = jobs
+                .iter_mut()
+                .find(|j| j.id == job_id)
+                .ok_or_else(|| format!("Scheduled job not found: {}", job_id))?;
+            f(job);
+            Ok(job.clone())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_update_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let store = JobStore::with_path(dir.path().join("jobs.json"), Encryption::Off);
+        assert!(store.load().unwrap().is_empty());
+
+        // Older files without run fields still load
+        fs::write(
+            dir.path().join("jobs.json"),
+            r#"[{"id":"j1","name":"Deps","schedule":"@daily","prompt":"/update-deps",
+                "agent_id":"claude","cwd":"/tmp","created_at":1}]"#,
+        )
+        .unwrap();
+        let jobs = store.load().unwrap();
+        assert!(jobs[0].enabled);
+        assert_eq!(jobs[0].last_status, None);
+
+        let job = store
+            .update("j1", |j| j.last_status = Some(JobRunStatus::Succeeded))
+            .unwrap();
+        assert_eq!(job.last_status, Some(JobRunStatus::Succeeded));
+        assert_eq!(
+            store.load().unwrap()[0].last_status,
+            Some(JobRunStatus::Succeeded)
+        );
+        assert!(store.update("missing", |_| {}).is_err());
+    }
+}
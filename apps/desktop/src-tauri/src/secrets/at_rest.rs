@@ -0,0 +1,319 @@
+//! Encrypting app data at rest
+//!
+//! With `encrypt_sessions` on, the stores that hold session content are
+//! written encrypted with XChaCha20-Poly1305, under a key kept in the OS
+//! keychain and created the first time it's needed: saved and archived
+//! sessions (with their tasks and inbox messages), prompt drafts, scheduled
+//! jobs, trust decisions, workspace snapshots and project task boards.
+//! Reading decrypts transparently, and plain files keep loading either way,
+//! so flipping the setting never strands data; `reseal` converts what's
+//! already on disk. Without the keychain entry, encrypted files can't be
+//! read.
+//!
+//! Stores are given an `Encryption` rather than looking at the settings, and
+//! write through `write`, which holds `STORE_IO` shared; `reseal` holds it
+//! exclusively, so no save lands halfway through a conversion.
+
+use super::entry;
+use crate::storage::files_under;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use once_cell::sync::Lazy;
+use parking_lot::{Mutex, RwLock};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use ts_rs::TS;
+
+/// Keychain entry (under the secrets' service) holding the key
+const KEY_ENTRY: &str = "session-store-key";
+
+/// Starts an encrypted file; base64 of the nonce and ciphertext follows
+const MAGIC: &str = "crafter-encrypted:v1:";
+
+const NONCE_LEN: usize = 24;
+
+/// The key, once read from the keychain (which may prompt on every read)
+static KEY: Lazy<Mutex<Option<Key>>> = Lazy::new(|| Mutex::new(None));
+
+/// What `Encryption::Configured` stores do; set from `encrypt_sessions` at
+/// startup and by `reseal`
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Shared by writes, exclusive while `reseal` converts the stores
+static STORE_IO: Lazy<RwLock<()>> = Lazy::new(|| RwLock::new(()));
+
+/// How a store encrypts what it writes
+#[derive(Clone, Copy, Default)]
+pub enum Encryption {
+    /// As `encrypt_sessions` is when the file is written, with the
+    /// keychain's key
+    #[default]
+    Configured,
+    /// Always plain
+    Off,
+    /// Always encrypted, with this key
+    With(Key),
+}
+
+impl Encryption {
+    /// `Off` or `With` the key to write with; call with `STORE_IO` held
+    fn resolve(self) -> Result<Encryption, String> {
+        match self {
+            Encryption::Configured if ENABLED.load(Ordering::Relaxed) => {
+                key(true).map(Encryption::With)
+            }
+            Encryption::Configured => Ok(Encryption::Off),
+            resolved => Ok(resolved),
+        }
+    }
+}
+
+/// A file `reseal` couldn't convert
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct ResealFailure {
+    pub path: String,
+    pub error: String,
+}
+
+/// The session key, generated and stored on first use if `create`
+fn key(create: bool) -> Result<Key, String> {
+    let mut cached = KEY.lock();
+    if let Some(key) = cached.as_ref() {
+        return Ok(*key);
+    }
+    let entry = entry(KEY_ENTRY)?;
+    let key = match entry.get_password() {
+        Ok(encoded) => {
+            let bytes = STANDARD
+                .decode(encoded.trim())
+                .map_err(|e| format!("Session encryption key is corrupt: {}", e))?;
+            if bytes.len() != 32 {
+                return Err("Session encryption key is corrupt: wrong length".to_string());
+            }
+            *Key::from_slice(&bytes)
+        }
+        Err(keyring::Error::NoEntry) if create => {
+            let key = XChaCha20Poly1305::generate_key(&mut OsRng);
+            entry
+                .set_password(&STANDARD.encode(key))
+                .map_err(|e| format!("Failed to store session encryption key: {}", e))?;
+            key
+        }
+        Err(keyring::Error::NoEntry) => {
+            return Err("No session encryption key in the keychain".to_string())
+        }
+        Err(e) => return Err(format!("Failed to read session encryption key: {}", e)),
+    };
+    *cached = Some(key);
+    Ok(key)
+}
+
+fn encrypt(plain: &[u8], key: &Key) -> Result<Vec<u8>, String> {
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = XChaCha20Poly1305::new(key)
+        .encrypt(&nonce, plain)
+        .map_err(|_| "Failed to encrypt".to_string())?;
+    let mut sealed = nonce.to_vec();
+    sealed.extend_from_slice(&ciphertext);
+    Ok(format!("{}{}", MAGIC, STANDARD.encode(sealed)).into_bytes())
+}
+
+fn decrypt(encoded: &[u8], key: &Key) -> Result<Vec<u8>, String> {
+    let sealed = STANDARD
+        .decode(encoded.trim_ascii())
+        .map_err(|e| format!("Encrypted file is corrupt: {}", e))?;
+    if sealed.len() < NONCE_LEN {
+        return Err("Encrypted file is corrupt: too short".to_string());
+    }
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+    XChaCha20Poly1305::new(key)
+        .decrypt(XNonce::from_slice(nonce), ciphertext)
+        .map_err(|_| "Failed to decrypt: wrong key or corrupt file".to_string())
+}
+
+/// Set what `Encryption::Configured` stores do, at startup
+pub fn init(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether `contents` were written encrypted
+pub fn is_sealed(contents: &[u8]) -> bool {
+    contents.starts_with(MAGIC.as_bytes())
+}
+
+/// Write then rename, so a reader never sees half a file
+fn replace(path: &Path, contents: &[u8]) -> Result<(), String> {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let tmp = path.with_file_name(format!(".{}.tmp", name));
+    fs::write(&tmp, contents).map_err(|e| e.to_string())?;
+    fs::rename(&tmp, path).map_err(|e| e.to_string())
+}
+
+/// Write `contents` to `path`, encrypted if `encryption` says so
+pub fn write(path: &Path, contents: &[u8], encryption: Encryption) -> Result<(), String> {
+    let _io = STORE_IO.read();
+    match encryption.resolve()? {
+        Encryption::With(key) => replace(path, &encrypt(contents, &key)?),
+        _ => replace(path, contents),
+    }
+}
+
+/// The plain contents of a file written by `write`
+pub fn open(contents: Vec<u8>, encryption: Encryption) -> Result<Vec<u8>, String> {
+    let Some(encoded) = contents.strip_prefix(MAGIC.as_bytes()) else {
+        return Ok(contents);
+    };
+    let key = match encryption {
+        Encryption::With(key) => key,
+        Encryption::Configured | Encryption::Off => key(false)?,
+    };
+    decrypt(encoded, &key)
+}
+
+/// Read a file written by `write`
+pub fn read(path: &Path, encryption: Encryption) -> Result<Vec<u8>, String> {
+    open(fs::read(path).map_err(|e| e.to_string())?, encryption)
+}
+
+/// Read a text file written by `write`
+pub fn read_to_string(path: &Path, encryption: Encryption) -> Result<String, String> {
+    String::from_utf8(read(path, encryption)?).map_err(|e| format!("File isn't text: {}", e))
+}
+
+/// Temporary files `replace` hasn't renamed yet
+fn is_hidden(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|name| name.to_string_lossy().starts_with('.'))
+}
+
+/// `<id>.json` files directly in `dir`
+fn json_files(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "json"))
+        .filter(|path| !is_hidden(path))
+        .collect()
+}
+
+/// Every file the stores encrypt, under the data directory `base` and in
+/// the task boards `boards`
+fn store_files(base: &Path, boards: &[PathBuf]) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    for dir in ["sessions", "sessions/drafts", "archive", "snapshots", "snapshots/manifests"] {
+        files.extend(json_files(&base.join(dir)));
+    }
+    files.extend(
+        files_under(&base.join("snapshots").join("objects"))
+            .into_iter()
+            .map(|file| file.path)
+            .filter(|path| !is_hidden(path)),
+    );
+    for file in ["scheduled-jobs.json", "trusted-directories.json"] {
+        let path = base.join(file);
+        if path.is_file() {
+            files.push(path);
+        }
+    }
+    for board in boards {
+        files.extend(json_files(board));
+    }
+    files
+}
+
+/// Convert one file to what `to` (`Off` or `With` a key) writes, decrypting
+/// it as `from` reads; returns whether it needed converting
+fn reseal_file(path: &Path, to: Encryption, from: Encryption) -> Result<bool, String> {
+    let contents = fs::read(path).map_err(|e| e.to_string())?;
+    let converted = match to {
+        Encryption::With(_) if is_sealed(&contents) => return Ok(false),
+        Encryption::With(key) => encrypt(&contents, &key)?,
+        _ if !is_sealed(&contents) => return Ok(false),
+        _ => open(contents, from)?,
+    };
+    replace(path, &converted)?;
+    Ok(true)
+}
+
+/// Make `Encryption::Configured` stores encrypt (or not) from now on, and
+/// convert their files already on disk under `base` and in the task boards
+/// `boards`. Saves wait until it's done. A file that can't be converted is
+/// left as it is and reported; returns how many were converted.
+pub fn reseal(
+    enabled: bool,
+    base: &Path,
+    boards: &[PathBuf],
+) -> Result<(usize, Vec<ResealFailure>), String> {
+    let _io = STORE_IO.write();
+    ENABLED.store(enabled, Ordering::Relaxed);
+    let to = Encryption::Configured.resolve()?;
+    let mut converted = 0;
+    let mut failed = Vec::new();
+    for path in store_files(base, boards) {
+        match reseal_file(&path, to, Encryption::Configured) {
+            Ok(true) => converted += 1,
+            Ok(false) => {}
+            Err(error) => failed.push(ResealFailure {
+                path: path.to_string_lossy().to_string(),
+                error,
+            }),
+        }
+    }
+    Ok((converted, failed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypt_round_trip() {
+        let key = XChaCha20Poly1305::generate_key(&mut OsRng);
+        let json = r#"{"id":"s1","messages":[]}"#;
+        let sealed = encrypt(json.as_bytes(), &key).unwrap();
+        assert!(is_sealed(&sealed));
+        assert!(!String::from_utf8_lossy(&sealed).contains("messages"));
+        assert_eq!(open(sealed.clone(), Encryption::With(key)).unwrap(), json.as_bytes());
+
+        let other = XChaCha20Poly1305::generate_key(&mut OsRng);
+        assert!(open(sealed, Encryption::With(other)).is_err());
+        // Plain files are read as they are
+        assert_eq!(open(json.into(), Encryption::Off).unwrap(), json.as_bytes());
+    }
+
+    #[test]
+    fn test_write_and_reseal_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let key = XChaCha20Poly1305::generate_key(&mut OsRng);
+        let other = XChaCha20Poly1305::generate_key(&mut OsRng);
+        let jobs = dir.path().join("scheduled-jobs.json");
+        write(&jobs, b"[]", Encryption::With(key)).unwrap();
+        assert!(is_sealed(&fs::read(&jobs).unwrap()));
+        assert_eq!(read_to_string(&jobs, Encryption::With(key)).unwrap(), "[]");
+
+        fs::create_dir_all(dir.path().join("sessions")).unwrap();
+        let session = dir.path().join("sessions").join("s1.json");
+        fs::write(&session, "{}").unwrap();
+        assert_eq!(store_files(dir.path(), &[]).len(), 2);
+
+        // Already as wanted
+        assert!(!reseal_file(&session, Encryption::Off, Encryption::Off).unwrap());
+        assert!(!reseal_file(&jobs, Encryption::With(key), Encryption::Off).unwrap());
+        // The wrong key fails and leaves the file alone
+        assert!(reseal_file(&jobs, Encryption::Off, Encryption::With(other)).is_err());
+        assert!(is_sealed(&fs::read(&jobs).unwrap()));
+
+        assert!(reseal_file(&jobs, Encryption::Off, Encryption::With(key)).unwrap());
+        assert_eq!(fs::read_to_string(&jobs).unwrap(), "[]");
+        assert!(reseal_file(&session, Encryption::With(key), Encryption::Off).unwrap());
+        assert_eq!(read_to_string(&session, Encryption::With(key)).unwrap(), "{}");
+    }
+}
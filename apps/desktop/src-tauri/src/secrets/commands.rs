@@ -0,0 +1,18 @@
+use super::{remove_secret, secret_names, store_secret};
+
+/// Store an API key (or any secret) in the OS keychain
+#[tauri::command]
+pub fn set_secret(name: String, value: String) -> Result<(), String> {
+    store_secret(&name, &value)
+}
+
+#[tauri::command]
+pub fn delete_secret(name: String) -> Result<(), String> {
+    remove_secret(&name)
+}
+
+/// Names of the stored secrets; values never leave the backend
+#[tauri::command]
+pub fn list_secret_names() -> Result<Vec<String>, String> {
+    secret_names()
+}
@@ -0,0 +1,164 @@
+//! Secrets manager
+//!
+//! API keys are stored in the OS keychain (via the keyring crate) under the
+//! "crafter-code" service. The keychain can't enumerate entries, so the names
+//! of stored secrets are kept in ~/.crafter-code/secrets.json; values never
+//! touch disk. Agents get the secrets named in their registry `env_vars` when
+//! the variable isn't already set in the app's environment.
+
+pub mod at_rest;
+pub mod commands;
+pub mod redact;
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+const KEYRING_SERVICE: &str = "crafter-code";
+
+/// Names of the secrets stored in the keychain
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SecretIndex {
+    names: Vec<String>,
+}
+
+struct SecretIndexStore {
+    path: PathBuf,
+}
+
+impl SecretIndexStore {
+    fn new() -> Result<Self, String> {
+        let dir = dirs::home_dir()
+            .ok_or_else(|| "Could not determine home directory".to_string())?
+            .join(".crafter-code");
+        fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create config directory: {}", e))?;
+        Ok(Self {
+            path: dir.join("secrets.json"),
+        })
+    }
+
+    #[allow(dead_code)]
+    fn with_path(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    fn load(&self) -> SecretIndex {
+        fs::read_to_string(&self.path)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self, index: &SecretIndex) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(index)
+            .map_err(|e| format!("Failed to serialize secret index: {}", e))?;
+        fs::write(&self.path, json).map_err(|e| format!("Failed to write secret index: {}", e))
+    }
+
+    fn add(&self, name: &str) -> Result<(), String> {
+        let mut index = self.load();
+        if !index.names.iter().any(|n| n == name) {
+            index.names.push(name.to_string());
+            index.names.sort();
+            self.save(&index)?;
+        }
+        Ok(())
+    }
+
+    fn remove(&self, name: &str) -> Result<(), String> {
+        let mut index = self.load();
+        index.names.retain(|n| n != name);
+        self.save(&index)
+    }
+}
+
+/// Secret names double as environment variable names
+fn validate_name(name: &str) -> Result<(), String> {
+    let mut chars = name.chars();
+    let valid = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "Invalid secret name '{}': use letters, digits and underscores",
+            name
+        ))
+    }
+}
+
+fn entry(name: &str) -> Result<keyring::Entry, String> {
+    keyring::Entry::new(KEYRING_SERVICE, name).map_err(|e| format!("Keychain error: {}", e))
+}
+
+/// Store a secret in the keychain
+pub fn store_secret(name: &str, value: &str) -> Result<(), String> {
+    validate_name(name)?;
+    if value.is_empty() {
+        return Err("Secret value is empty".to_string());
+    }
+    entry(name)?
+        .set_password(value)
+        .map_err(|e| format!("Failed to store secret {}: {}", name, e))?;
+    redact::invalidate();
+    SecretIndexStore::new()?.add(name)
+}
+
+/// Read a secret from the keychain
+pub fn load_secret(name: &str) -> Option<String> {
+    entry(name).ok()?.get_password().ok()
+}
+
+/// Remove a secret from the keychain
+pub fn remove_secret(name: &str) -> Result<(), String> {
+    match entry(name)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => {}
+        Err(e) => return Err(format!("Failed to delete secret {}: {}", name, e)),
+    }
+    redact::invalidate();
+    SecretIndexStore::new()?.remove(name)
+}
+
+/// Names of the stored secrets (values are never returned)
+pub fn secret_names() -> Result<Vec<String>, String> {
+    Ok(SecretIndexStore::new()?.load().names)
+}
+
+/// An environment variable, falling back to the stored secret of that name
+pub fn env_or_secret(name: &str) -> Option<String> {
+    std::env::var(name).ok().or_else(|| load_secret(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_name() {
+        assert!(validate_name("ANTHROPIC_API_KEY").is_ok());
+        assert!(validate_name("_token2").is_ok());
+        assert!(validate_name("").is_err());
+        assert!(validate_name("2FA").is_err());
+        assert!(validate_name("MY-KEY").is_err());
+    }
+
+    #[test]
+    fn test_index_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = SecretIndexStore::with_path(dir.path().join("secrets.json"));
+
+        store.add("OPENAI_API_KEY").unwrap();
+        store.add("ANTHROPIC_API_KEY").unwrap();
+        store.add("OPENAI_API_KEY").unwrap();
+        assert_eq!(
+            store.load().names,
+            vec!["ANTHROPIC_API_KEY", "OPENAI_API_KEY"]
+        );
+
+        store.remove("OPENAI_API_KEY").unwrap();
+        assert_eq!(store.load().names, vec!["ANTHROPIC_API_KEY"]);
+    }
+}
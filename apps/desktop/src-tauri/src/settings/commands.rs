@@ -0,0 +1,70 @@
+use super::store::{AppSettings, SettingsStore};
+use super::load_settings;
+use crate::events::{emit, SessionEncryptionEvent};
+use crate::secrets::at_rest;
+use crate::AppState;
+use tauri::{AppHandle, Manager};
+
+/// Get the current application settings
+#[tauri::command]
+pub fn get_app_settings() -> Result<AppSettings, String> {
+    SettingsStore::new()?.load()
+}
+
+/// Replace the application settings
+#[tauri::command]
+pub fn update_app_settings(settings: AppSettings, app: AppHandle) -> Result<AppSettings, String> {
+    let store = SettingsStore::new()?;
+    let was_encrypting = store.load().is_ok_and(|old| old.encrypt_sessions);
+    store.save(&settings)?;
+    crate::secrets::redact::invalidate();
+    if settings.encrypt_sessions != was_encrypting {
+        std::thread::spawn(move || reseal_stores(&app));
+    }
+    Ok(settings)
+}
+
+/// Convert the files already on disk to what `encrypt_sessions` now says,
+/// and report how it went
+fn reseal_stores(app: &AppHandle) {
+    // Read again, in case it changed while an earlier conversion ran
+    let encrypted = load_settings().encrypt_sessions;
+    let boards: Vec<_> = app
+        .state::<AppState>()
+        .task_managers
+        .lock()
+        .values()
+        .filter_map(|manager| manager.board())
+        .collect();
+    let result = dirs::home_dir()
+        .ok_or_else(|| "Could not determine home directory".to_string())
+        .and_then(|home| at_rest::reseal(encrypted, &home.join(".crafter-code"), &boards));
+    let event = match result {
+        Ok((converted, failed)) => {
+            eprintln!(
+                "[Settings] Converted {} file(s), {} failed",
+                converted,
+                failed.len()
+            );
+            for failure in &failed {
+                eprintln!("[Settings] {}: {}", failure.path, failure.error);
+            }
+            SessionEncryptionEvent {
+                encrypted,
+                converted,
+                failed,
+                error: None,
+            }
+        }
+        Err(e) => {
+            eprintln!("[Settings] Failed to convert saved data: {}", e);
+            SessionEncryptionEvent {
+                encrypted,
+                converted: 0,
+                failed: Vec::new(),
+                error: Some(e),
+            }
+        }
+    };
+    emit(app, &event);
+}
@@ -0,0 +1,8 @@
+//! Application settings
+//!
+//! Global user preferences stored in ~/.crafter-code/settings.json
+
+pub mod commands;
+pub mod store;
+
+pub use store::load_settings;
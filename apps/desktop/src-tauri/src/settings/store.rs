@@ -0,0 +1,722 @@
+//! Settings persistence
+//!
+//! Settings live in a single JSON file. Every field has a serde default so
+//! older files keep loading as new settings are added.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use ts_rs::TS;
+
+/// Global application settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppSettings {
+    /// Store agent thought/reasoning chunks in persisted sessions
+    pub persist_thinking: bool,
+    /// Include thought chunks when exporting sessions
+    pub export_thinking: bool,
+    /// Record agent-created terminals to asciicast files
+    pub record_agent_terminals: bool,
+    /// Desktop notification toggles
+    pub notifications: NotificationSettings,
+    /// Outbound webhooks
+    pub integrations: IntegrationSettings,
+    /// Embedded HTTP/WebSocket API server
+    pub api_server: ApiServerSettings,
+    /// Other machines running the API server whose sessions can be attached
+    pub remote_hosts: Vec<RemoteHost>,
+    /// Stop idle agent processes after this many minutes (0 = never)
+    pub worker_idle_timeout_minutes: u32,
+    /// Archive finished sessions after this many hours (0 = never)
+    pub archive_after_hours: u32,
+    /// Most agent processes alive at once (0 = unlimited)
+    pub max_running_agents: u32,
+    /// Queue new sessions past the limit instead of rejecting them
+    pub queue_sessions_when_full: bool,
+    /// Prices overriding the bundled pricing table, keyed by model id,
+    /// model alias, or agent id (for agents whose models aren't listed)
+    pub model_pricing: HashMap<String, ModelPrice>,
+    /// Session modes in which the client itself refuses file writes, edit
+    /// permissions and every command but a few read-only tools
+    pub read_only_modes: Vec<String>,
+    /// Project .env files
+    pub dotenv: DotenvSettings,
+    /// Masking secrets in worker output and persisted transcripts
+    pub redaction: RedactionSettings,
+    /// Rules that flag or block agent output, tool calls and commands
+    /// (projects add their own in `.crafter-code/guards.json`)
+    pub guard_rules: Vec<GuardRule>,
+    /// Kill project hook scripts (`.crafter/hooks/`) after this many seconds
+    pub hook_timeout_secs: u64,
+    /// Run Claude Code hooks (PreToolUse, PostToolUse, Stop) for every agent
+    pub claude_hooks: bool,
+    /// Prompting idle workers when inbox messages arrive
+    pub inbox_push: InboxPushSettings,
+    /// On quit, wait this long for agent processes to exit
+    pub shutdown_timeout_secs: u64,
+    /// CPU and memory limits for agent processes and their terminals
+    pub resource_limits: ResourceLimitSettings,
+    /// Cutting agent-created terminals off from the network (projects can
+    /// opt in, but not out, in `.crafter-code/network.json`)
+    pub network_isolation: NetworkIsolationSettings,
+    /// Project instruction files (AGENTS.md, CLAUDE.md, ...) for agents
+    /// that don't read them themselves
+    pub context_files: ContextFileSettings,
+    /// Show the tray icon with running workers and quick actions (applies
+    /// on restart)
+    pub tray_icon: bool,
+    /// App-wide spend caps across all sessions
+    pub budget: BudgetSettings,
+    /// Which editor files open in
+    pub editor: EditorSettings,
+    /// Workspace snapshots of projects outside git
+    pub snapshots: SnapshotSettings,
+    /// Language agents answer in (sessions can choose their own)
+    pub language: LanguageSettings,
+    /// Rotate a worker's protocol trace file past this many MB (0 = never)
+    pub protocol_trace_max_mb: u64,
+    /// Resending prompts that fail with rate limit, overload or network errors
+    pub prompt_retry: PromptRetrySettings,
+    /// Warn about tool calls running longer than this many seconds (0 = never)
+    pub slow_tool_threshold_secs: u64,
+    /// Streamed agent text is sent to the window this many times a second
+    /// (0 = every chunk as it arrives)
+    pub delta_flush_hz: u32,
+    /// Preprocessors follow-up prompts go through, in order (sessions can
+    /// choose their own)
+    pub prompt_preprocessors: Vec<PromptPreprocessor>,
+    /// Values for `{{name}}` placeholders in prompts
+    pub prompt_templates: HashMap<String, String>,
+    /// OpenTelemetry export (builds with the `otel` feature only)
+    pub telemetry: TelemetrySettings,
+    /// Keep every session's tasks on its project's board in
+    /// `<project>/.crafter/tasks/`, so later sessions continue the backlog
+    pub project_task_board: bool,
+    /// Turn the plans agents publish into tasks on the session's board
+    pub import_agent_plans: bool,
+    /// Turning recorded speech into prompt text
+    pub transcription: TranscriptionSettings,
+    /// Per-session directories agents keep temporary files in
+    pub scratch: ScratchSettings,
+    /// Keep the computer from idle-sleeping while workers or PRD sessions
+    /// run
+    pub prevent_sleep: bool,
+    /// Encrypt saved sessions and the other stores holding session content
+    /// with a key kept in the OS keychain (see `secrets::at_rest`)
+    pub encrypt_sessions: bool,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            persist_thinking: true,
+            export_thinking: false,
+            record_agent_terminals: false,
+            notifications: NotificationSettings::default(),
+            integrations: IntegrationSettings::default(),
+            api_server: ApiServerSettings::default(),
+            remote_hosts: Vec::new(),
+            worker_idle_timeout_minutes: 30,
+            archive_after_hours: 24,
+            max_running_agents: 0,
+            queue_sessions_when_full: true,
+            model_pricing: HashMap::new(),
+            read_only_modes: vec!["plan".to_string()],
+            dotenv: DotenvSettings::default(),
+            redaction: RedactionSettings::default(),
+            guard_rules: Vec::new(),
+            hook_timeout_secs: 120,
+            claude_hooks: true,
+            inbox_push: InboxPushSettings::default(),
+            shutdown_timeout_secs: 10,
+            resource_limits: ResourceLimitSettings::default(),
+            network_isolation: NetworkIsolationSettings::default(),
+            context_files: ContextFileSettings::default(),
+            tray_icon: true,
+            budget: BudgetSettings::default(),
+            editor: EditorSettings::default(),
+            snapshots: SnapshotSettings::default(),
+            language: LanguageSettings::default(),
+            protocol_trace_max_mb: 20,
+            prompt_retry: PromptRetrySettings::default(),
+            slow_tool_threshold_secs: 120,
+            delta_flush_hz: 30,
+            prompt_preprocessors: vec![
+                PromptPreprocessor::SlashCommands,
+                PromptPreprocessor::Templates,
+                PromptPreprocessor::Mentions,
+                PromptPreprocessor::Skills,
+                PromptPreprocessor::Redaction,
+            ],
+            prompt_templates: HashMap::new(),
+            telemetry: TelemetrySettings::default(),
+            project_task_board: false,
+            import_agent_plans: false,
+            transcription: TranscriptionSettings::default(),
+            scratch: ScratchSettings::default(),
+            prevent_sleep: false,
+            encrypt_sessions: false,
+        }
+    }
+}
+
+/// Per-event desktop notification toggles
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationSettings {
+    /// Master switch
+    pub enabled: bool,
+    /// Every finished prompt (sessions can opt in via `notify_on_completion`)
+    pub prompt_completed: bool,
+    pub permission_required: bool,
+    pub worker_failed: bool,
+    pub budget_exceeded: bool,
+    pub prd_story_completed: bool,
+    /// Also notify while the app window has focus
+    pub when_focused: bool,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            prompt_completed: false,
+            permission_required: true,
+            worker_failed: true,
+            budget_exceeded: true,
+            prd_story_completed: true,
+            when_focused: false,
+        }
+    }
+}
+
+/// Outbound integrations (webhooks)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IntegrationSettings {
+    pub webhooks: Vec<WebhookConfig>,
+    /// Send a `cost_threshold` event when a session's cost crosses this amount
+    pub cost_threshold_usd: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookFormat {
+    /// Generic JSON payload
+    #[default]
+    Json,
+    /// Slack incoming-webhook message
+    Slack,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    pub name: String,
+    pub url: String,
+    #[serde(default)]
+    pub format: WebhookFormat,
+    /// Event names to send (e.g. "worker_failed"); empty sends all events
+    #[serde(default)]
+    pub events: Vec<String>,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Embedded API server for external tools and remote UIs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ApiServerSettings {
+    /// Start the server when the app launches
+    pub enabled: bool,
+    pub host: String,
+    pub port: u16,
+    /// Bearer token required by every endpoint (generated on first start)
+    pub token: String,
+}
+
+impl Default for ApiServerSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            host: "127.0.0.1".to_string(),
+            port: 7420,
+            token: String::new(),
+        }
+    }
+}
+
+/// Prompting idle workers with new inbox messages, so agents react to
+/// coordination traffic without polling `swarm inbox read`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InboxPushSettings {
+    pub enabled: bool,
+    /// Least time between two pushes to the same worker
+    pub min_interval_secs: u64,
+    /// Most pushes to the same worker per hour, so workers messaging each
+    /// other can't loop forever
+    pub max_per_hour: u32,
+}
+
+impl Default for InboxPushSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_interval_secs: 30,
+            max_per_hour: 10,
+        }
+    }
+}
+
+/// Resending a prompt that failed with a transient error. The delay doubles
+/// with each retry; the worker fails once `max_attempts` retries have failed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PromptRetrySettings {
+    /// Retries per prompt (0 = fail on the first error)
+    pub max_attempts: u32,
+    pub base_delay_secs: u64,
+    pub max_delay_secs: u64,
+}
+
+impl Default for PromptRetrySettings {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay_secs: 2,
+            max_delay_secs: 60,
+        }
+    }
+}
+
+/// CPU and memory limits for agent processes and the terminals they create,
+/// each measured with its child processes. Soft limits emit a warning;
+/// hard limits kill the processes and fail the worker. A limit of 0 is off.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResourceLimitSettings {
+    pub enabled: bool,
+    /// Resident memory, in MB
+    pub memory_soft_mb: u64,
+    pub memory_hard_mb: u64,
+    /// CPU usage, in percent of one core (200 = two cores busy)
+    pub cpu_soft_percent: u32,
+    pub cpu_hard_percent: u32,
+    /// CPU usage must stay over a limit this long before it counts, so
+    /// short bursts like a build don't trip it
+    pub cpu_window_secs: u64,
+}
+
+impl Default for ResourceLimitSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            memory_soft_mb: 4096,
+            memory_hard_mb: 8192,
+            cpu_soft_percent: 400,
+            cpu_hard_percent: 0,
+            cpu_window_secs: 60,
+        }
+    }
+}
+
+/// Running agent-created terminal commands without network access: in a
+/// network namespace on Linux, under a sandbox-exec profile on macOS, and
+/// elsewhere by refusing known network commands
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NetworkIsolationSettings {
+    /// Isolate terminals in every session
+    pub enabled: bool,
+    /// Session modes whose terminals are always isolated (e.g. "plan" for
+    /// offline reviews)
+    pub modes: Vec<String>,
+    /// Refuse every command when no sandbox is available, instead of only
+    /// known network commands
+    pub require_sandbox: bool,
+}
+
+/// Passing project context files (AGENTS.md, CLAUDE.md, GEMINI.md,
+/// .cursorrules) to agents that don't load them natively
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ContextFileSettings {
+    /// Add the files to the coordination prompt
+    pub inject: bool,
+    /// Bytes of each file included (0 = all)
+    pub max_bytes: usize,
+}
+
+impl Default for ContextFileSettings {
+    fn default() -> Self {
+        Self {
+            inject: false,
+            max_bytes: 32 * 1024,
+        }
+    }
+}
+
+/// App-wide spend caps, checked against the usage ledger. Crossing one
+/// pauses every running worker and blocks new sessions until the period
+/// rolls over or the cap is raised.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BudgetSettings {
+    /// Most USD spent per local calendar day (0 = no cap)
+    pub daily_usd: f64,
+    /// Most USD spent per local calendar month (0 = no cap)
+    pub monthly_usd: f64,
+}
+
+/// The editor `open_in_editor` uses. Ids are "vscode", "zed", "jetbrains"
+/// and "env" (`$VISUAL` / `$EDITOR`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EditorSettings {
+    /// Editor for every project ("" = the first one installed)
+    pub preferred: String,
+    /// Editor per project root, overriding `preferred`
+    pub projects: HashMap<String, String>,
+}
+
+/// Workspace snapshots (content-addressed copies of a project's files)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SnapshotSettings {
+    /// Snapshot non-git projects when a session starts
+    pub on_session_start: bool,
+    /// Larger files are left out (0 = no limit)
+    pub max_file_bytes: u64,
+    /// Files past this total are left out (0 = no limit)
+    pub max_total_bytes: u64,
+    /// Snapshots kept per project (0 = all)
+    pub keep: u32,
+    /// Globs skipped besides `.gitignore` patterns and dependency directories
+    pub ignore: Vec<String>,
+}
+
+impl Default for SnapshotSettings {
+    fn default() -> Self {
+        Self {
+            on_session_start: true,
+            max_file_bytes: 1024 * 1024,
+            max_total_bytes: 256 * 1024 * 1024,
+            keep: 20,
+            ignore: Vec::new(),
+        }
+    }
+}
+
+/// Exporting metrics and spans to an OpenTelemetry collector over OTLP/HTTP
+/// (applies on restart)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TelemetrySettings {
+    pub enabled: bool,
+    /// Collector base URL; `/v1/metrics` and `/v1/traces` are appended
+    pub endpoint: String,
+    /// Sent with every export, e.g. an API key for a hosted collector
+    pub headers: HashMap<String, String>,
+    pub export_interval_secs: u64,
+    /// `service.name` resource attribute, to tell machines apart
+    pub service_name: String,
+}
+
+impl Default for TelemetrySettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            endpoint: "http://localhost:4318".to_string(),
+            headers: HashMap::new(),
+            export_interval_secs: 30,
+            service_name: "crafter-code".to_string(),
+        }
+    }
+}
+
+/// Where recorded speech is transcribed
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TranscriptionBackend {
+    /// An OpenAI-compatible `/audio/transcriptions` endpoint
+    Api,
+    /// whisper.cpp on this machine (builds with the `whisper` feature only)
+    Local,
+}
+
+/// Turning recorded speech into prompt text
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TranscriptionSettings {
+    pub backend: TranscriptionBackend,
+    pub api_url: String,
+    /// Environment variable or stored secret holding the API key
+    pub api_key_name: String,
+    pub model: String,
+    /// Language spoken, as an ISO-639-1 code ("" = detect)
+    pub language: String,
+    /// ggml model file for the local backend
+    pub local_model_path: String,
+}
+
+impl Default for TranscriptionSettings {
+    fn default() -> Self {
+        Self {
+            backend: TranscriptionBackend::Api,
+            api_url: "https://api.openai.com/v1/audio/transcriptions".to_string(),
+            api_key_name: "OPENAI_API_KEY".to_string(),
+            model: "whisper-1".to_string(),
+            language: String::new(),
+            local_model_path: String::new(),
+        }
+    }
+}
+
+/// Scratch directories (`~/.crafter-code/scratch/<session>/`) offered to
+/// agents for temporary artifacts, deleted with their session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScratchSettings {
+    pub enabled: bool,
+    /// Oldest files are removed once a session's directory grows past
+    /// this (0 = no limit)
+    pub max_mb: u64,
+}
+
+impl Default for ScratchSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_mb: 1024,
+        }
+    }
+}
+
+/// A step follow-up prompts go through before reaching the agent
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PromptPreprocessor {
+    /// Expand `/command args`
+    SlashCommands,
+    /// Fill `{{name}}` from `prompt_templates`
+    Templates,
+    /// Append the contents of `@path` files in the project
+    Mentions,
+    /// Put the session's active skill prompts first
+    Skills,
+    /// Mask secrets
+    Redaction,
+}
+
+/// The language agents are asked to answer in
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LanguageSettings {
+    /// A code ("es", "pt-BR") or a name ("" = the agent's default)
+    pub respond_in: String,
+    /// Also translate the client's own text in coordination prompts and
+    /// `swarm help`
+    pub localize_prompts: bool,
+}
+
+/// Loading project .env files into agents and their terminals
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DotenvSettings {
+    /// Inject project .env variables into agent processes and terminals
+    pub inject: bool,
+    /// Files read from the project root, later files overriding earlier ones
+    pub files: Vec<String>,
+    /// Variables whose values are masked, as `*` wildcard patterns
+    /// (case-insensitive)
+    pub mask: Vec<String>,
+    /// Variables never masked, even when matching `mask`
+    pub unmask: Vec<String>,
+}
+
+impl Default for DotenvSettings {
+    fn default() -> Self {
+        Self {
+            inject: false,
+            files: vec![".env".to_string(), ".env.local".to_string()],
+            mask: ["*KEY*", "*SECRET*", "*TOKEN*", "*PASSWORD*", "*PASS", "*DATABASE_URL"]
+                .iter()
+                .map(|p| p.to_string())
+                .collect(),
+            unmask: Vec::new(),
+        }
+    }
+}
+
+/// Secret redaction (projects can add patterns or opt out in
+/// `.crafter-code/redaction.json`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RedactionSettings {
+    pub enabled: bool,
+    /// Extra regex patterns to redact, on top of the built-in token formats
+    pub patterns: Vec<String>,
+}
+
+impl Default for RedactionSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            patterns: Vec::new(),
+        }
+    }
+}
+
+/// What a guard rule inspects
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum GuardTarget {
+    /// The agent's completed response text
+    Output,
+    /// Tool calls, as "kind title raw-input-json"
+    ToolCall,
+    /// Shell commands the agent runs through the client
+    Command,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum GuardAction {
+    /// Emit `guard-triggered` and carry on
+    #[default]
+    Flag,
+    /// Refuse the tool call or command
+    Block,
+    /// Hold the tool call or command until the user approves it
+    Pause,
+}
+
+/// A regex or script check on agent activity. Scripts get the text on
+/// stdin and trigger the rule by exiting non-zero.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GuardRule {
+    pub name: String,
+    pub target: GuardTarget,
+    #[serde(default)]
+    pub pattern: Option<String>,
+    #[serde(default)]
+    pub script: Option<String>,
+    #[serde(default)]
+    pub action: GuardAction,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+/// A machine running crafter-code's API server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteHost {
+    pub id: String,
+    pub name: String,
+    /// Base URL, e.g. "http://buildbox.local:7420"
+    pub url: String,
+    /// The host's API token
+    pub token: String,
+}
+
+/// USD per million tokens
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct ModelPrice {
+    pub input_per_million: f64,
+    pub output_per_million: f64,
+}
+
+/// Loads and saves `AppSettings` on disk
+pub struct SettingsStore {
+    path: PathBuf,
+}
+
+impl SettingsStore {
+    /// Create a settings store rooted at ~/.crafter-code
+    pub fn new() -> Result<Self, String> {
+        let base_path = dirs::home_dir()
+            .ok_or_else(|| "Could not determine home directory".to_string())?
+            .join(".crafter-code");
+
+        fs::create_dir_all(&base_path)
+            .map_err(|e| format!("Failed to create settings directory: {}", e))?;
+
+        Ok(Self {
+            path: base_path.join("settings.json"),
+        })
+    }
+
+    /// Create a settings store backed by a specific file
+    pub fn with_path(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Load settings, falling back to defaults if the file is missing
+    pub fn load(&self) -> Result<AppSettings, String> {
+        if !self.path.exists() {
+            return Ok(AppSettings::default());
+        }
+        let json = fs::read_to_string(&self.path)
+            .map_err(|e| format!("Failed to read settings file: {}", e))?;
+        serde_json::from_str(&json).map_err(|e| format!("Failed to parse settings file: {}", e))
+    }
+
+    /// Save settings to disk
+    pub fn save(&self, settings: &AppSettings) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(settings)
+            .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+        fs::write(&self.path, json).map_err(|e| format!("Failed to write settings file: {}", e))
+    }
+}
+
+/// Load the current settings, using defaults if they can't be read
+pub fn load_settings() -> AppSettings {
+    SettingsStore::new()
+        .and_then(|store| store.load())
+        .unwrap_or_else(|e| {
+            eprintln!("[Settings] Using defaults: {}", e);
+            AppSettings::default()
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_missing_file_uses_defaults() {
+        let dir = TempDir::new().unwrap();
+        let store = SettingsStore::with_path(dir.path().join("settings.json"));
+        let settings = store.load().unwrap();
+        assert!(settings.persist_thinking);
+        assert!(!settings.export_thinking);
+    }
+
+    #[test]
+    fn test_round_trip_and_partial_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("settings.json");
+        let store = SettingsStore::with_path(path.clone());
+
+        let settings = AppSettings {
+            persist_thinking: false,
+            ..Default::default()
+        };
+        store.save(&settings).unwrap();
+        assert!(!store.load().unwrap().persist_thinking);
+
+        // Unknown/missing fields fall back to defaults
+        fs::write(&path, r#"{"export_thinking": true}"#).unwrap();
+        let loaded = store.load().unwrap();
+        assert!(loaded.persist_thinking);
+        assert!(loaded.export_thinking);
+    }
+}
@@ -0,0 +1,173 @@
+//! Graceful shutdown
+//!
+//! Closing the main window or quitting holds the exit while `drain` winds
+//! the workers down: in-flight prompts are cancelled, every live session is
+//! snapshotted with its tasks and inbox (~/.crafter-code/snapshots), and each
+//! worker gets `Stop`, which saves its ACP session and kills the agent. The
+//! app exits once no agent process is left or `shutdown_timeout_secs` has
+//! passed. Each step is reported as a "shutdown-progress" event for the
+//! shutdown splash.
+
+use crate::acp::commands::WorkerCommand;
+use crate::events::{emit, ShutdownProgressEvent};
+use crate::orchestrator::archive::{capture, ArchiveStore};
+use crate::pty::service;
+use crate::settings::load_settings;
+use crate::AppState;
+use serde::Serialize;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tokio::time::Instant;
+use ts_rs::TS;
+
+const RUNNING: u8 = 0;
+const DRAINING: u8 = 1;
+const DRAINED: u8 = 2;
+
+static STATE: AtomicU8 = AtomicU8::new(RUNNING);
+
+/// How often the agent count is checked while waiting
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum ShutdownStage {
+    Cancelling,
+    Saving,
+    Stopping,
+    Waiting,
+    Done,
+}
+
+fn report(app: &AppHandle, stage: ShutdownStage, message: String, running_agents: usize) {
+    eprintln!("[Shutdown] {}", message);
+    emit(
+        app,
+        &ShutdownProgressEvent {
+            stage,
+            message,
+            running_agents,
+        },
+    );
+}
+
+/// Called when the main window is closed or the app asked to exit. Starts
+/// draining the workers the first time; returns whether the exit has to
+/// wait for it (false once the drain finished).
+pub fn hold_exit(app: &AppHandle) -> bool {
+    match STATE.compare_exchange(RUNNING, DRAINING, Ordering::SeqCst, Ordering::SeqCst) {
+        Ok(_) => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                drain(&app).await;
+                STATE.store(DRAINED, Ordering::SeqCst);
+                app.exit(0);
+            });
+            true
+        }
+        Err(state) => state == DRAINING,
+    }
+}
+
+/// Wind down every worker, waiting at most `shutdown_timeout_secs` for
+/// agent processes to exit
+async fn drain(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    let deadline = Instant::now() + Duration::from_secs(load_settings().shutdown_timeout_secs);
+
+    let (cancelled, running) = {
+        let mut mgr = state.orchestrator_manager.lock();
+        mgr.clear_agent_queue();
+        (mgr.cancel_all_workers(), mgr.running_agents())
+    };
+    report(
+        app,
+        ShutdownStage::Cancelling,
+        format!("Cancelled {} running prompt(s)", cancelled),
+        running,
+    );
+
+    let sessions: Vec<_> = {
+        let mgr = state.orchestrator_manager.lock();
+        mgr.list_sessions().into_iter().cloned().collect()
+    };
+    let saved = match ArchiveStore::snapshots() {
+        Ok(store) => sessions
+            .iter()
+            .filter(|session| match store.save(&capture(&state, session)) {
+                Ok(()) => true,
+                Err(e) => {
+                    eprintln!("[Shutdown] Session {}: {}", session.id, e);
+                    false
+                }
+            })
+            .count(),
+        Err(e) => {
+            eprintln!("[Shutdown] {}", e);
+            0
+        }
+    };
+    report(
+        app,
+        ShutdownStage::Saving,
+        format!("Saved {} of {} session(s)", saved, sessions.len()),
+        running,
+    );
+
+    // Dropping the handles closes each channel right after its Stop
+    let handles: Vec<_> = state.worker_handles.lock().drain().collect();
+    for (session_id, handle) in &handles {
+        if handle.command_tx.try_send(WorkerCommand::Stop).is_err() {
+            eprintln!(
+                "[Shutdown] Couldn't stop the worker for session={}",
+                session_id
+            );
+        }
+    }
+    report(
+        app,
+        ShutdownStage::Stopping,
+        format!("Stopping {} worker(s)", handles.len()),
+        running,
+    );
+    drop(handles);
+    let services = service::stop_all();
+    if services > 0 {
+        eprintln!("[Shutdown] Stopped {} service(s)", services);
+    }
+
+    let mut last = None;
+    loop {
+        let running = state.orchestrator_manager.lock().running_agents();
+        if running == 0 {
+            break;
+        }
+        if Instant::now() >= deadline {
+            report(
+                app,
+                ShutdownStage::Done,
+                format!("Gave up waiting for {} agent(s)", running),
+                running,
+            );
+            return;
+        }
+        if last != Some(running) {
+            report(
+                app,
+                ShutdownStage::Waiting,
+                format!("Waiting for {} agent(s) to exit", running),
+                running,
+            );
+            last = Some(running);
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+    report(
+        app,
+        ShutdownStage::Done,
+        "All workers stopped".to_string(),
+        0,
+    );
+}
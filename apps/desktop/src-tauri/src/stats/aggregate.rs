@@ -0,0 +1,238 @@
+use super::ledger::UsageRecord;
+use chrono::{DateTime, Datelike, Duration, Local, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// How far back to look
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum StatsRange {
+    /// Last 24 hours
+    Day,
+    /// Last 7 days
+    Week,
+    /// Last 30 days
+    Month,
+    All,
+}
+
+impl StatsRange {
+    fn start(self, now: DateTime<Local>) -> Option<i64> {
+        let days = match self {
+            Self::Day => 1,
+            Self::Week => 7,
+            Self::Month => 30,
+            Self::All => return None,
+        };
+        Some((now - Duration::days(days)).timestamp())
+    }
+}
+
+/// How to bucket records
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum StatsGroupBy {
+    Day,
+    Week,
+    Month,
+    Agent,
+    Model,
+}
+
+impl StatsGroupBy {
+    fn key(self, record: &UsageRecord) -> String {
+        let time = || Local.timestamp_opt(record.timestamp, 0).earliest();
+        match self {
+            Self::Day => time().map(|t| t.format("%Y-%m-%d").to_string()),
+            Self::Week => time().map(|t| {
+                let week = t.iso_week();
+                format!("{}-W{:02}", week.year(), week.week())
+            }),
+            Self::Month => time().map(|t| t.format("%Y-%m").to_string()),
+            Self::Agent => Some(record.agent_id.clone()),
+            Self::Model => Some(record.model.clone()),
+        }
+        .unwrap_or_default()
+    }
+
+    fn is_time(self) -> bool {
+        matches!(self, Self::Day | Self::Week | Self::Month)
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct UsageTotals {
+    pub cost_usd: f64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    /// Distinct sessions with usage in the bucket
+    pub sessions: usize,
+    /// Completed prompts
+    pub prompts: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct UsageGroup {
+    /// Date ("2025-03-10", "2025-W11", "2025-03"), agent id or model
+    pub key: String,
+    #[serde(flatten)]
+    pub totals: UsageTotals,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageStats {
+    pub range: StatsRange,
+    pub group_by: StatsGroupBy,
+    pub totals: UsageTotals,
+    pub groups: Vec<UsageGroup>,
+}
+
+#[derive(Default)]
+struct Accumulator {
+    totals: UsageTotals,
+    sessions: HashSet<String>,
+}
+
+impl Accumulator {
+    fn add(&mut self, record: &UsageRecord) {
+        self.totals.cost_usd += record.cost_usd;
+        self.totals.input_tokens += record.input_tokens;
+        self.totals.output_tokens += record.output_tokens;
+        self.totals.prompts += 1;
+        self.sessions.insert(record.session_id.clone());
+    }
+
+    fn finish(mut self) -> UsageTotals {
+        self.totals.sessions = self.sessions.len();
+        self.totals
+    }
+}
+
+/// Aggregate usage within `range`. Time buckets are in chronological order,
+/// agent and model buckets by descending cost.
+pub fn aggregate(
+    records: &[UsageRecord],
+    now: DateTime<Local>,
+    range: StatsRange,
+    group_by: StatsGroupBy,
+) -> UsageStats {
+    let start = range.start(now);
+    let mut overall = Accumulator::default();
+    let mut buckets: HashMap<String, Accumulator> = HashMap::new();
+
+    for record in records
+        .iter()
+        .filter(|r| start.is_none_or(|start| r.timestamp >= start))
+    {
+        overall.add(record);
+        buckets.entry(group_by.key(record)).or_default().add(record);
+    }
+
+    let mut groups: Vec<UsageGroup> = buckets
+        .into_iter()
+        .map(|(key, acc)| UsageGroup {
+            key,
+            totals: acc.finish(),
+        })
+        .collect();
+    if group_by.is_time() {
+        groups.sort_by(|a, b| a.key.cmp(&b.key));
+    } else {
+        groups.sort_by(|a, b| b.totals.cost_usd.total_cmp(&a.totals.cost_usd));
+    }
+
+    UsageStats {
+        range,
+        group_by,
+        totals: overall.finish(),
+        groups,
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// One row per group, for spreadsheets
+pub fn to_csv(stats: &UsageStats) -> String {
+    let mut csv = String::from("group,sessions,prompts,input_tokens,output_tokens,cost_usd\n");
+    for group in &stats.groups {
+        let t = &group.totals;
+        csv.push_str(&format!(
+            "{},{},{},{},{},{:.4}\n",
+            csv_field(&group.key),
+            t.sessions,
+            t.prompts,
+            t.input_tokens,
+            t.output_tokens,
+            t.cost_usd
+        ));
+    }
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(timestamp: i64, session: &str, agent: &str, cost: f64) -> UsageRecord {
+        UsageRecord {
+            timestamp,
+            session_id: session.to_string(),
+            agent_id: agent.to_string(),
+            model: format!("{}-model", agent),
+            input_tokens: 100,
+            output_tokens: 50,
+            cost_usd: cost,
+            estimated: true,
+        }
+    }
+
+    #[test]
+    fn test_aggregate_by_day_within_range() {
+        let now = Local.with_ymd_and_hms(2025, 3, 10, 12, 0, 0).unwrap();
+        let day = 86_400;
+        let t = now.timestamp();
+        let records = vec![
+            record(t - 40 * day, "old", "claude", 9.0),
+            record(t - day - 60, "s1", "claude", 1.0),
+            record(t - day - 30, "s1", "claude", 2.0),
+            record(t - 60, "s2", "gemini", 0.5),
+        ];
+
+        let stats = aggregate(&records, now, StatsRange::Week, StatsGroupBy::Day);
+        assert_eq!(stats.totals.prompts, 3);
+        assert_eq!(stats.totals.sessions, 2);
+        assert_eq!(stats.totals.cost_usd, 3.5);
+        let keys: Vec<&str> = stats.groups.iter().map(|g| g.key.as_str()).collect();
+        assert_eq!(keys, vec!["2025-03-09", "2025-03-10"]);
+        assert_eq!(stats.groups[0].totals.sessions, 1);
+        assert_eq!(stats.groups[0].totals.input_tokens, 200);
+
+        let all = aggregate(&records, now, StatsRange::All, StatsGroupBy::Agent);
+        assert_eq!(all.groups[0].key, "claude");
+        assert_eq!(all.groups[0].totals.cost_usd, 12.0);
+        assert_eq!(all.groups[1].key, "gemini");
+
+        let month = aggregate(&records, now, StatsRange::Month, StatsGroupBy::Week);
+        assert_eq!(month.groups.len(), 2);
+        assert_eq!(month.groups[0].key, "2025-W10");
+    }
+
+    #[test]
+    fn test_to_csv() {
+        let now = Local.with_ymd_and_hms(2025, 3, 10, 12, 0, 0).unwrap();
+        let mut r = record(now.timestamp(), "s1", "claude", 0.25);
+        r.model = "custom, \"large\"".to_string();
+        let stats = aggregate(&[r], now, StatsRange::All, StatsGroupBy::Model);
+        assert_eq!(
+            to_csv(&stats),
+            "group,sessions,prompts,input_tokens,output_tokens,cost_usd\n\
+             \"custom, \"\"large\"\"\",1,1,100,50,0.2500\n"
+        );
+    }
+}
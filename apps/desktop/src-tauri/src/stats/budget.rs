@@ -0,0 +1,337 @@
+//! App-wide spend caps
+//!
+//! Spend for the current local day and month is totalled from the usage
+//! ledger once, then kept up to date as prompts are recorded. Past
+//! `budget.daily_usd` or `budget.monthly_usd`, every running worker is
+//! paused (in-flight prompts cancelled, PRD sessions paused),
+//! `global-budget-exceeded` is emitted once per period, and
+//! `require_budget` refuses new sessions until the period rolls over or
+//! the cap is raised.
+
+use super::ledger::{UsageLedger, UsageRecord};
+use crate::error::{CommandError, ErrorCode};
+use crate::events::{emit, GlobalBudgetExceededEvent};
+use crate::notifications::{notify, NotificationEvent};
+use crate::settings::load_settings;
+use crate::settings::store::BudgetSettings;
+use crate::AppState;
+use chrono::{DateTime, Local, TimeZone};
+use once_cell::sync::{Lazy, OnceCell};
+use parking_lot::Mutex;
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+use ts_rs::TS;
+
+/// Spend so far in the current periods (loaded on first use)
+static SPEND: Lazy<Mutex<Option<PeriodSpend>>> = Lazy::new(|| Mutex::new(None));
+
+/// Set by `start`; breaches before then only block new sessions
+static APP: OnceCell<AppHandle> = OnceCell::new();
+
+/// The last breach reported ("day:2025-03-10"), so each is emitted once
+static REPORTED: Lazy<Mutex<Option<String>>> = Lazy::new(|| Mutex::new(None));
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum BudgetPeriod {
+    Day,
+    Month,
+}
+
+/// Spend in the current local day and month
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct PeriodSpend {
+    /// "2025-03-10"
+    pub day: String,
+    pub day_usd: f64,
+    /// "2025-03"
+    pub month: String,
+    pub month_usd: f64,
+}
+
+/// A cap that was reached
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct BudgetBreach {
+    pub period: BudgetPeriod,
+    pub spent_usd: f64,
+    pub cap_usd: f64,
+}
+
+/// Payload of `get_global_spend`
+#[derive(Debug, Clone, Serialize)]
+pub struct GlobalSpend {
+    #[serde(flatten)]
+    pub spend: PeriodSpend,
+    pub daily_cap_usd: f64,
+    pub monthly_cap_usd: f64,
+    pub exceeded: Option<BudgetBreach>,
+}
+
+impl BudgetBreach {
+    pub fn message(&self) -> String {
+        let (period, until) = match self.period {
+            BudgetPeriod::Day => ("Daily", "tomorrow"),
+            BudgetPeriod::Month => ("Monthly", "next month"),
+        };
+        format!(
+            "{} spend cap of ${:.2} reached (${:.2} spent). New sessions are blocked until {} or the cap is raised in settings.",
+            period, self.cap_usd, self.spent_usd, until
+        )
+    }
+}
+
+fn day_key(time: DateTime<Local>) -> String {
+    time.format("%Y-%m-%d").to_string()
+}
+
+fn month_key(time: DateTime<Local>) -> String {
+    time.format("%Y-%m").to_string()
+}
+
+impl PeriodSpend {
+    fn empty(now: DateTime<Local>) -> Self {
+        Self {
+            day: day_key(now),
+            month: month_key(now),
+            ..Default::default()
+        }
+    }
+
+    fn from_records(records: &[UsageRecord], now: DateTime<Local>) -> Self {
+        let mut spend = Self::empty(now);
+        for record in records {
+            spend.add(record.timestamp, record.cost_usd);
+        }
+        spend
+    }
+
+    /// Count a cost in the periods it falls in
+    fn add(&mut self, timestamp: i64, cost_usd: f64) {
+        let Some(time) = Local.timestamp_opt(timestamp, 0).earliest() else {
+            return;
+        };
+        if day_key(time) == self.day {
+            self.day_usd += cost_usd;
+        }
+        if month_key(time) == self.month {
+            self.month_usd += cost_usd;
+        }
+    }
+
+    /// Start over when a new day or month begins
+    fn roll(&mut self, now: DateTime<Local>) {
+        if day_key(now) != self.day {
+            self.day = day_key(now);
+            self.day_usd = 0.0;
+        }
+        if month_key(now) != self.month {
+            self.month = month_key(now);
+            self.month_usd = 0.0;
+        }
+    }
+
+    /// The first cap reached, the daily one before the monthly one
+    pub fn breach(&self, settings: &BudgetSettings) -> Option<BudgetBreach> {
+        if settings.daily_usd > 0.0 && self.day_usd >= settings.daily_usd {
+            return Some(BudgetBreach {
+                period: BudgetPeriod::Day,
+                spent_usd: self.day_usd,
+                cap_usd: settings.daily_usd,
+            });
+        }
+        if settings.monthly_usd > 0.0 && self.month_usd >= settings.monthly_usd {
+            return Some(BudgetBreach {
+                period: BudgetPeriod::Month,
+                spent_usd: self.month_usd,
+                cap_usd: settings.monthly_usd,
+            });
+        }
+        None
+    }
+
+    /// Identifies a breach of `period` within the current period
+    fn breach_key(&self, period: BudgetPeriod) -> String {
+        match period {
+            BudgetPeriod::Day => format!("day:{}", self.day),
+            BudgetPeriod::Month => format!("month:{}", self.month),
+        }
+    }
+}
+
+fn has_caps(settings: &BudgetSettings) -> bool {
+    settings.daily_usd > 0.0 || settings.monthly_usd > 0.0
+}
+
+fn load(now: DateTime<Local>) -> PeriodSpend {
+    let records = UsageLedger::new()
+        .and_then(|ledger| ledger.load())
+        .unwrap_or_else(|e| {
+            eprintln!("[Budget] {}", e);
+            Vec::new()
+        });
+    PeriodSpend::from_records(&records, now)
+}
+
+/// Spend in the current day and month
+pub fn current_spend() -> PeriodSpend {
+    let now = Local::now();
+    let mut spend = SPEND.lock();
+    let spend = spend.get_or_insert_with(|| load(now));
+    spend.roll(now);
+    spend.clone()
+}
+
+/// Count a prompt that was just appended to the ledger, pausing everything
+/// if it crossed a cap
+pub fn add_spend(record: &UsageRecord) {
+    {
+        let now = Local::now();
+        let mut spend = SPEND.lock();
+        match spend.as_mut() {
+            Some(spend) => {
+                spend.roll(now);
+                spend.add(record.timestamp, record.cost_usd);
+            }
+            // The ledger already has the record
+            None => *spend = Some(load(now)),
+        }
+    }
+
+    let settings = load_settings().budget;
+    if !has_caps(&settings) {
+        return;
+    }
+    let spend = current_spend();
+    let Some(breach) = spend.breach(&settings) else {
+        return;
+    };
+    let Some(app) = APP.get() else {
+        return;
+    };
+
+    // Whatever is still running keeps getting stopped while over the cap
+    let (cancelled, paused) = app.state::<AppState>().pause_all();
+    eprintln!(
+        "[Budget] {} Cancelled {} prompt(s), paused {} PRD session(s)",
+        breach.message(),
+        cancelled,
+        paused
+    );
+
+    let key = spend.breach_key(breach.period);
+    if REPORTED.lock().replace(key.clone()).as_deref() == Some(key.as_str()) {
+        return;
+    }
+    emit(
+        app,
+        &GlobalBudgetExceededEvent {
+            period: breach.period,
+            spent_usd: breach.spent_usd,
+            cap_usd: breach.cap_usd,
+        },
+    );
+    notify(
+        app,
+        NotificationEvent::BudgetExceeded,
+        None,
+        "Spend cap reached",
+        &breach.message(),
+    );
+}
+
+/// Refuse to start or prompt an agent while a cap is exceeded
+pub fn require_budget() -> Result<(), CommandError> {
+    let settings = load_settings().budget;
+    if !has_caps(&settings) {
+        return Ok(());
+    }
+    match current_spend().breach(&settings) {
+        None => Ok(()),
+        Some(breach) => Err(CommandError::new(ErrorCode::BudgetExceeded, breach.message())
+            .with_details(serde_json::json!(breach))),
+    }
+}
+
+/// Let breaches pause workers and notify
+pub fn start(app: AppHandle) {
+    let _ = APP.set(app);
+}
+
+/// Spend so far today and this month, against the caps
+#[tauri::command]
+pub fn get_global_spend() -> GlobalSpend {
+    let settings = load_settings().budget;
+    let spend = current_spend();
+    GlobalSpend {
+        exceeded: spend.breach(&settings),
+        spend,
+        daily_cap_usd: settings.daily_usd,
+        monthly_cap_usd: settings.monthly_usd,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(time: DateTime<Local>, cost_usd: f64) -> UsageRecord {
+        UsageRecord {
+            timestamp: time.timestamp(),
+            session_id: "s1".to_string(),
+            agent_id: "claude".to_string(),
+            model: "sonnet".to_string(),
+            input_tokens: 0,
+            output_tokens: 0,
+            cost_usd,
+            estimated: true,
+        }
+    }
+
+    #[test]
+    fn test_period_spend() {
+        let now = Local.with_ymd_and_hms(2025, 3, 10, 12, 0, 0).unwrap();
+        let records = vec![
+            record(Local.with_ymd_and_hms(2025, 2, 28, 12, 0, 0).unwrap(), 100.0),
+            record(Local.with_ymd_and_hms(2025, 3, 2, 12, 0, 0).unwrap(), 4.0),
+            record(Local.with_ymd_and_hms(2025, 3, 10, 9, 0, 0).unwrap(), 1.5),
+        ];
+        let mut spend = PeriodSpend::from_records(&records, now);
+        assert_eq!(spend.day, "2025-03-10");
+        assert_eq!(spend.day_usd, 1.5);
+        assert_eq!(spend.month_usd, 5.5);
+
+        spend.roll(Local.with_ymd_and_hms(2025, 3, 11, 0, 5, 0).unwrap());
+        assert_eq!(spend.day_usd, 0.0);
+        assert_eq!(spend.month_usd, 5.5);
+        spend.roll(Local.with_ymd_and_hms(2025, 4, 1, 0, 5, 0).unwrap());
+        assert_eq!(spend.month, "2025-04");
+        assert_eq!(spend.month_usd, 0.0);
+    }
+
+    #[test]
+    fn test_breach() {
+        let spend = PeriodSpend {
+            day: "2025-03-10".to_string(),
+            day_usd: 12.0,
+            month: "2025-03".to_string(),
+            month_usd: 80.0,
+        };
+        let mut settings = BudgetSettings::default();
+        assert_eq!(spend.breach(&settings), None);
+
+        settings.monthly_usd = 50.0;
+        let breach = spend.breach(&settings).unwrap();
+        assert_eq!(breach.period, BudgetPeriod::Month);
+        assert_eq!(spend.breach_key(breach.period), "month:2025-03");
+
+        settings.daily_usd = 10.0;
+        let breach = spend.breach(&settings).unwrap();
+        assert_eq!(breach.period, BudgetPeriod::Day);
+        assert!(breach.message().starts_with("Daily spend cap of $10.00 reached ($12.00 spent)"));
+
+        settings.daily_usd = 20.0;
+        settings.monthly_usd = 100.0;
+        assert_eq!(spend.breach(&settings), None);
+    }
+}
@@ -0,0 +1,16 @@
+use super::aggregate::{aggregate, to_csv, StatsGroupBy, StatsRange, UsageStats};
+use super::ledger::UsageLedger;
+use chrono::Local;
+
+/// Spend and token usage across all sessions, e.g. range "week" grouped by "day"
+#[tauri::command]
+pub fn get_usage_stats(range: StatsRange, group_by: StatsGroupBy) -> Result<UsageStats, String> {
+    let records = UsageLedger::new()?.load()?;
+    Ok(aggregate(&records, Local::now(), range, group_by))
+}
+
+/// The same aggregation as CSV text
+#[tauri::command]
+pub fn export_usage_csv(range: StatsRange, group_by: StatsGroupBy) -> Result<String, String> {
+    get_usage_stats(range, group_by).map(|stats| to_csv(&stats))
+}
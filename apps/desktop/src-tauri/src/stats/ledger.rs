@@ -0,0 +1,121 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+/// Token usage and cost of one completed prompt
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageRecord {
+    /// Unix timestamp when the turn completed
+    pub timestamp: i64,
+    pub session_id: String,
+    pub agent_id: String,
+    pub model: String,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cost_usd: f64,
+    /// Tokens were estimated from character counts
+    #[serde(default)]
+    pub estimated: bool,
+}
+
+/// Append-only usage log in ~/.crafter-code/usage.jsonl (one record per line)
+pub struct UsageLedger {
+    path: PathBuf,
+}
+
+impl UsageLedger {
+    pub fn new() -> Result<Self, String> {
+        let base_path = dirs::home_dir()
+            .ok_or_else(|| "Could not determine home directory".to_string())?
+            .join(".crafter-code");
+
+        fs::create_dir_all(&base_path)
+            .map_err(|e| format!("Failed to create usage directory: {}", e))?;
+
+        Ok(Self {
+            path: base_path.join("usage.jsonl"),
+        })
+    }
+
+    #[allow(dead_code)]
+    pub fn with_path(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    pub fn append(&self, record: &UsageRecord) -> Result<(), String> {
+        let line = serde_json::to_string(record)
+            .map_err(|e| format!("Failed to serialize usage record: {}", e))?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| format!("Failed to open usage log: {}", e))?;
+        writeln!(file, "{}", line).map_err(|e| format!("Failed to write usage log: {}", e))
+    }
+
+    /// All records; lines that fail to parse (e.g. a torn write) are skipped
+    pub fn load(&self) -> Result<Vec<UsageRecord>, String> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        let contents = fs::read_to_string(&self.path)
+            .map_err(|e| format!("Failed to read usage log: {}", e))?;
+        Ok(contents
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect())
+    }
+}
+
+/// Total recorded cost of each session
+pub fn session_costs() -> Result<HashMap<String, f64>, String> {
+    let mut costs = HashMap::new();
+    for record in UsageLedger::new()?.load()? {
+        *costs.entry(record.session_id).or_insert(0.0) += record.cost_usd;
+    }
+    Ok(costs)
+}
+
+/// Append a record to the usage log, logging failures, and count it
+/// against the spend caps
+pub fn record_usage(record: UsageRecord) {
+    if let Err(e) = UsageLedger::new().and_then(|ledger| ledger.append(&record)) {
+        eprintln!("[Stats] {}", e);
+    }
+    super::budget::add_spend(&record);
+    crate::telemetry::usage(&record);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_append_and_load_skips_bad_lines() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("usage.jsonl");
+        let ledger = UsageLedger::with_path(path.clone());
+        assert!(ledger.load().unwrap().is_empty());
+
+        let record = UsageRecord {
+            timestamp: 1,
+            session_id: "s1".to_string(),
+            agent_id: "claude".to_string(),
+            model: "sonnet".to_string(),
+            input_tokens: 10,
+            output_tokens: 20,
+            cost_usd: 0.5,
+            estimated: true,
+        };
+        ledger.append(&record).unwrap();
+        fs::write(&path, fs::read_to_string(&path).unwrap() + "{\"timest\n").unwrap();
+        ledger.append(&record).unwrap();
+
+        let records = ledger.load().unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1].output_tokens, 20);
+    }
+}
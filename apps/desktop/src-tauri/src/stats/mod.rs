@@ -0,0 +1,13 @@
+//! Usage statistics
+//!
+//! Every completed prompt appends its token usage and cost to a ledger in
+//! ~/.crafter-code/usage.jsonl. The ledger is aggregated on demand into
+//! per-day/week/month, per-agent or per-model totals for the spend dashboard,
+//! and checked against the app-wide spend caps (see `budget`).
+
+mod aggregate;
+pub mod budget;
+pub mod commands;
+mod ledger;
+
+pub use ledger::{record_usage, session_costs, UsageRecord};
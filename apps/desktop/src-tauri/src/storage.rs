@@ -0,0 +1,359 @@
+//! Disk space used by the app's data
+//!
+//! Everything the app keeps lives under `~/.crafter-code`.
+//! `get_storage_usage` sums it up by category, and `clean_storage` deletes
+//! whatever is older than a given age (or, as a dry run, reports what it
+//! would delete):
+//!
+//! - sessions: saved ACP sessions and archived orchestrator sessions;
+//!   pinned and loaded sessions are kept, and a deleted session's state
+//!   goes with it
+//! - logs: protocol traces, except the ones being written
+//! - snapshots: workspace snapshots, with the file contents only they
+//!   refer to
+//! - recordings: terminal recordings
+//! - scratch: files in the scratch directories (see `agent::scratch`) of
+//!   sessions that aren't loaded
+
+use crate::acp::commands::clear_session_state;
+use crate::acp::protocol_trace::is_tracing;
+use crate::acp::session_store::SessionStore;
+use crate::agent::snapshot::SnapshotStore;
+use crate::orchestrator::archive::{dir_usage, stale_saved_sessions, ArchiveStore};
+use crate::time::{now_ms, secs_to_ms};
+use crate::AppState;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Manager};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageCategory {
+    Sessions,
+    Logs,
+    Snapshots,
+    Recordings,
+    Scratch,
+}
+
+impl StorageCategory {
+    pub const ALL: [StorageCategory; 5] = [
+        StorageCategory::Sessions,
+        StorageCategory::Logs,
+        StorageCategory::Snapshots,
+        StorageCategory::Recordings,
+        StorageCategory::Scratch,
+    ];
+}
+
+/// Files in one category and their total size
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct CategoryUsage {
+    pub category: StorageCategory,
+    pub files: usize,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StorageUsage {
+    /// ~/.crafter-code
+    pub path: String,
+    pub categories: Vec<CategoryUsage>,
+    pub total_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CleanResult {
+    /// Nothing was deleted; `categories` is what would have been
+    pub dry_run: bool,
+    /// What was deleted from each category
+    pub categories: Vec<CategoryUsage>,
+    pub freed_bytes: u64,
+}
+
+/// A file somewhere under the data directory
+#[derive(Debug, Clone)]
+pub struct StoredFile {
+    pub path: PathBuf,
+    pub bytes: u64,
+    pub modified: SystemTime,
+}
+
+/// ~/.crafter-code
+fn data_dir() -> Result<PathBuf, String> {
+    Ok(dirs::home_dir()
+        .ok_or_else(|| "Could not determine home directory".to_string())?
+        .join(".crafter-code"))
+}
+
+/// Files under `dir`, recursively; symlinks are listed, never followed
+pub fn files_under(dir: &Path) -> Vec<StoredFile> {
+    let mut found = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let Ok(metadata) = fs::symlink_metadata(entry.path()) else {
+                continue;
+            };
+            if metadata.is_dir() {
+                pending.push(entry.path());
+            } else {
+                found.push(StoredFile {
+                    path: entry.path(),
+                    bytes: metadata.len(),
+                    modified: metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+                });
+            }
+        }
+    }
+    found
+}
+
+/// `sessions/<id>/<name>` directories, with their session ids
+fn session_dirs(base: &Path, name: &str) -> Vec<(String, PathBuf)> {
+    let Ok(entries) = fs::read_dir(base.join("sessions")) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .map(|entry| {
+            let session_id = entry.file_name().to_string_lossy().to_string();
+            (session_id, entry.path().join(name))
+        })
+        .filter(|(_, dir)| dir.is_dir())
+        .collect()
+}
+
+/// Files of every category but sessions, which share their directory with
+/// logs and recordings
+fn category_files(base: &Path, category: StorageCategory) -> Vec<StoredFile> {
+    let dirs = match category {
+        StorageCategory::Sessions => Vec::new(),
+        StorageCategory::Logs => session_dirs(base, "traces")
+            .into_iter()
+            .map(|(_, dir)| dir)
+            .collect(),
+        StorageCategory::Snapshots => vec![
+            base.join("snapshots").join("manifests"),
+            base.join("snapshots").join("objects"),
+        ],
+        StorageCategory::Recordings => session_dirs(base, "recordings")
+            .into_iter()
+            .map(|(_, dir)| dir)
+            .chain([base.join("recordings")])
+            .collect(),
+        StorageCategory::Scratch => vec![base.join("scratch")],
+    };
+    dirs.iter().flat_map(|dir| files_under(dir)).collect()
+}
+
+fn category_usage(base: &Path, category: StorageCategory) -> CategoryUsage {
+    let (files, bytes) = match category {
+        StorageCategory::Sessions => [base.join("sessions"), base.join("archive")]
+            .iter()
+            .map(|dir| dir_usage(dir))
+            .fold((0, 0), |(files, bytes), usage| {
+                (files + usage.files, bytes + usage.bytes)
+            }),
+        _ => {
+            let files = category_files(base, category);
+            (files.len(), files.iter().map(|file| file.bytes).sum())
+        }
+    };
+    CategoryUsage {
+        category,
+        files,
+        bytes,
+    }
+}
+
+/// Disk space used by each category
+pub fn storage_usage() -> Result<StorageUsage, String> {
+    let base = data_dir()?;
+    let categories: Vec<CategoryUsage> = StorageCategory::ALL
+        .iter()
+        .map(|category| category_usage(&base, *category))
+        .collect();
+    Ok(StorageUsage {
+        path: base.to_string_lossy().to_string(),
+        total_bytes: categories.iter().map(|usage| usage.bytes).sum(),
+        categories,
+    })
+}
+
+/// Whether a session is loaded in the app
+fn is_live(state: &AppState, session_id: &str) -> bool {
+    state.worker_handles.lock().contains_key(session_id)
+        || state
+            .orchestrator_manager
+            .lock()
+            .get_session(session_id)
+            .is_some()
+}
+
+/// Paths that exist, with their sizes
+fn sized(paths: impl IntoIterator<Item = PathBuf>) -> Vec<(PathBuf, u64)> {
+    paths
+        .into_iter()
+        .filter_map(|path| fs::metadata(&path).ok().map(|metadata| (path, metadata.len())))
+        .collect()
+}
+
+/// Files in `category` last changed more than `older_than` ago that can go
+fn stale_files(
+    state: &AppState,
+    base: &Path,
+    category: StorageCategory,
+    older_than: Duration,
+) -> Result<Vec<(PathBuf, u64)>, String> {
+    let older_than_secs = i64::try_from(older_than.as_secs()).unwrap_or(i64::MAX);
+    let cutoff_ms = now_ms().saturating_sub(secs_to_ms(older_than_secs));
+    let cutoff = SystemTime::now()
+        .checked_sub(older_than)
+        .unwrap_or(SystemTime::UNIX_EPOCH);
+    let old = |files: Vec<StoredFile>| {
+        files
+            .into_iter()
+            .filter(|file| file.modified < cutoff)
+            .map(|file| (file.path, file.bytes))
+            .collect::<Vec<_>>()
+    };
+    Ok(match category {
+        StorageCategory::Sessions => {
+            let archive = ArchiveStore::new()?;
+            let saved = SessionStore::new()?;
+            let archived = archive
+                .list()
+                .into_iter()
+                .filter(|summary| summary.updated_at < cutoff_ms)
+                .map(|summary| archive.session_path(&summary.id));
+            let saved_paths = stale_saved_sessions(state, &saved, cutoff_ms)
+                .into_iter()
+                .map(|summary| saved.session_path(&summary.id));
+            sized(archived.chain(saved_paths))
+        }
+        StorageCategory::Logs => session_dirs(base, "traces")
+            .into_iter()
+            .filter(|(session_id, _)| !is_tracing(session_id))
+            .flat_map(|(_, dir)| old(files_under(&dir)))
+            .collect(),
+        StorageCategory::Snapshots => sized(SnapshotStore::new()?.expired_files(cutoff_ms)),
+        StorageCategory::Recordings => old(category_files(base, category)),
+        StorageCategory::Scratch => fs::read_dir(base.join("scratch"))
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter(|entry| !is_live(state, &entry.file_name().to_string_lossy()))
+            .flat_map(|entry| old(files_under(&entry.path())))
+            .collect(),
+    })
+}
+
+/// Delete what's older than `older_than` in `categories`; with `dry_run`
+/// only report it
+pub fn clean(
+    state: &AppState,
+    older_than: Duration,
+    categories: &[StorageCategory],
+    dry_run: bool,
+) -> Result<CleanResult, String> {
+    let base = data_dir()?;
+    let mut cleaned = Vec::new();
+    for category in categories {
+        let mut files = stale_files(state, &base, *category, older_than)?;
+        if !dry_run {
+            files.retain(|(path, _)| fs::remove_file(path).is_ok());
+            if *category == StorageCategory::Sessions {
+                // Saved and archived sessions are `<id>.json`
+                for (path, _) in &files {
+                    if let Some(session_id) = path.file_stem().and_then(|stem| stem.to_str()) {
+                        clear_session_state(session_id);
+                    }
+                }
+            }
+        }
+        cleaned.push(CategoryUsage {
+            category: *category,
+            files: files.len(),
+            bytes: files.iter().map(|(_, bytes)| bytes).sum(),
+        });
+    }
+    let freed_bytes = cleaned.iter().map(|usage| usage.bytes).sum();
+    if !dry_run && freed_bytes > 0 {
+        eprintln!("[Storage] Freed {} bytes", freed_bytes);
+    }
+    Ok(CleanResult {
+        dry_run,
+        categories: cleaned,
+        freed_bytes,
+    })
+}
+
+/// Disk space used by sessions, logs, snapshots, recordings and scratch
+/// directories
+#[tauri::command]
+pub async fn get_storage_usage() -> Result<StorageUsage, String> {
+    tauri::async_runtime::spawn_blocking(storage_usage)
+        .await
+        .map_err(|e| format!("Storage usage task failed: {}", e))?
+}
+
+/// Delete data last changed more than `older_than_hours` ago in
+/// `categories` (all of them when omitted). A `dry_run` reports what would
+/// be deleted without deleting anything.
+#[tauri::command]
+pub async fn clean_storage(
+    older_than_hours: u64,
+    categories: Option<Vec<StorageCategory>>,
+    dry_run: Option<bool>,
+    app: AppHandle,
+) -> Result<CleanResult, String> {
+    let older_than = Duration::from_secs(older_than_hours.saturating_mul(3600));
+    let categories = categories.unwrap_or_else(|| StorageCategory::ALL.to_vec());
+    tauri::async_runtime::spawn_blocking(move || {
+        let state = app.state::<AppState>();
+        clean(&state, older_than, &categories, dry_run.unwrap_or(false))
+    })
+    .await
+    .map_err(|e| format!("Storage cleanup task failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_category_usage() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = dir.path();
+        let session = base.join("sessions").join("s1");
+        fs::create_dir_all(session.join("traces")).unwrap();
+        fs::create_dir_all(session.join("recordings")).unwrap();
+        fs::create_dir_all(base.join("recordings")).unwrap();
+        fs::create_dir_all(base.join("scratch").join("s1").join("out")).unwrap();
+        fs::write(base.join("sessions").join("s1.json"), vec![b' '; 10]).unwrap();
+        fs::write(session.join("traces").join("claude.jsonl"), vec![0u8; 100]).unwrap();
+        fs::write(session.join("recordings").join("term_1.cast"), vec![0u8; 20]).unwrap();
+        fs::write(base.join("recordings").join("term_2.cast"), vec![0u8; 30]).unwrap();
+        fs::write(base.join("scratch/s1/out/result.txt"), vec![0u8; 5]).unwrap();
+
+        let usage = |category| category_usage(base, category);
+        assert_eq!(usage(StorageCategory::Sessions).bytes, 10);
+        assert_eq!(usage(StorageCategory::Logs).bytes, 100);
+        assert_eq!(
+            usage(StorageCategory::Recordings),
+            CategoryUsage {
+                category: StorageCategory::Recordings,
+                files: 2,
+                bytes: 50,
+            }
+        );
+        assert_eq!(usage(StorageCategory::Snapshots).files, 0);
+        assert_eq!(usage(StorageCategory::Scratch).bytes, 5);
+    }
+}
@@ -0,0 +1,181 @@
+//! Project task boards
+//!
+//! A session's tasks normally live and die with its `TaskManager`. A project
+//! board keeps them in `<project>/.crafter/tasks/` instead, one
+//! `<id>.json` per task, so the next session in the same repository picks up
+//! the same backlog. A board is mounted into a session's `TaskManager`
+//! (with `task_board_mount`, or for every session when `project_task_board`
+//! is on); from then on the manager reloads the board before each operation
+//! and writes back what it changed, so sessions sharing a board stay in sync.
+//! Task files are encrypted when `encrypt_sessions` is on.
+
+use super::task::Task;
+use crate::secrets::at_rest::{self, Encryption};
+use crate::settings::load_settings;
+use crate::tasks::TaskManager;
+use crate::AppState;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::State;
+
+/// Held while a manager reloads, changes and writes back a board, so two
+/// sessions on the same board don't hand out the same id
+pub(super) static BOARD_IO: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+/// Where a project's board lives
+pub fn board_dir(project: &Path) -> PathBuf {
+    project.join(".crafter").join("tasks")
+}
+
+/// Every task on a board (deleted ones included). Unreadable files are
+/// skipped.
+pub fn load(dir: &Path) -> HashMap<String, Task> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return HashMap::new();
+    };
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|path| {
+            let json = at_rest::read_to_string(&path, Encryption::Configured)
+                .map_err(|e| eprintln!("[Tasks] Skipping {}: {}", path.display(), e))
+                .ok()?;
+            let task: Task = serde_json::from_str(&json)
+                .map_err(|e| eprintln!("[Tasks] Skipping {}: {}", path.display(), e))
+                .ok()?;
+            Some((task.id.clone(), task))
+        })
+        .collect()
+}
+
+/// Write one task to a board
+pub fn save(dir: &Path, task: &Task) -> Result<(), String> {
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let json = serde_json::to_string_pretty(task)
+        .map_err(|e| format!("Failed to serialize task {}: {}", task.id, e))?;
+    // Written then renamed, so a session reloading mid-write never sees half a task
+    let path = dir.join(format!("{}.json", task.id));
+    at_rest::write(&path, json.as_bytes(), Encryption::Configured)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// The id after the highest numeric id on a board
+pub fn next_id(tasks: &HashMap<String, Task>) -> u64 {
+    tasks
+        .keys()
+        .filter_map(|id| id.parse::<u64>().ok())
+        .max()
+        .map_or(1, |max| max + 1)
+}
+
+/// Mount the project's board into a session when `project_task_board` is on
+pub fn auto_mount(task_manager: &TaskManager, cwd: &str) {
+    if !load_settings().project_task_board || task_manager.board().is_some() {
+        return;
+    }
+    if let Err(e) = task_manager.mount_board(&board_dir(Path::new(cwd))) {
+        eprintln!("[Tasks] {}", e);
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskBoardStatus {
+    /// Directory of the mounted board, if any
+    pub board: Option<String>,
+    pub tasks: usize,
+}
+
+fn status(task_manager: &TaskManager) -> TaskBoardStatus {
+    TaskBoardStatus {
+        board: task_manager
+            .board()
+            .map(|dir| dir.to_string_lossy().to_string()),
+        tasks: task_manager.list().len(),
+    }
+}
+
+/// Mount `<project_path>/.crafter/tasks/` into a session's task manager.
+/// Tasks the session already had are added to the board.
+#[tauri::command]
+pub fn task_board_mount(
+    session_id: String,
+    project_path: String,
+    state: State<'_, AppState>,
+) -> Result<TaskBoardStatus, String> {
+    let manager = state.get_task_manager(&session_id)?;
+    manager.mount_board(&board_dir(Path::new(&project_path)))?;
+    Ok(status(&manager))
+}
+
+/// Stop sharing a session's tasks with its project board; the session keeps
+/// a copy of the tasks as they are now
+#[tauri::command]
+pub fn task_board_unmount(
+    session_id: String,
+    state: State<'_, AppState>,
+) -> Result<TaskBoardStatus, String> {
+    let manager = state.get_task_manager(&session_id)?;
+    manager.unmount_board();
+    Ok(status(&manager))
+}
+
+/// Which board (if any) a session's tasks live on
+#[tauri::command]
+pub fn task_board_status(
+    session_id: String,
+    state: State<'_, AppState>,
+) -> Result<TaskBoardStatus, String> {
+    let manager = state.get_task_manager(&session_id)?;
+    Ok(status(&manager))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tasks::task::{TaskStatus, TaskUpdate};
+
+    #[test]
+    fn test_consecutive_sessions_share_backlog() {
+        let project = tempfile::tempdir().unwrap();
+        let dir = board_dir(project.path());
+
+        let first = TaskManager::new("board-first".to_string());
+        let setup = first.create("Setup".to_string(), "".to_string(), None);
+        first.mount_board(&dir).unwrap();
+        let docs = first.create("Docs".to_string(), "".to_string(), None);
+        first.update(
+            &docs.id,
+            TaskUpdate {
+                add_blocked_by: Some(vec![setup.id.clone()]),
+                ..Default::default()
+            },
+        );
+        assert_eq!(load(&dir).len(), 2);
+
+        // A later session continues where the first one stopped
+        let second = TaskManager::new("board-second".to_string());
+        let local = second.create("Local".to_string(), "".to_string(), None);
+        second.mount_board(&dir).unwrap();
+        let tasks = second.list();
+        assert_eq!(tasks.len(), 3);
+        assert_eq!(second.get(&docs.id).unwrap().blocked_by, vec![setup.id.clone()]);
+        // The session's own task got the next free id on the board
+        assert_eq!(local.id, "1");
+        assert!(tasks.iter().any(|t| t.subject == "Local" && t.id == "3"));
+
+        // Both see each other's changes
+        let claimed = second.claim_available("worker-1").unwrap();
+        assert_eq!(first.get(&claimed.id).unwrap().status, TaskStatus::InProgress);
+        assert_eq!(first.create("Ship".to_string(), "".to_string(), None).id, "4");
+
+        second.unmount_board();
+        second.create("Scratch".to_string(), "".to_string(), None);
+        assert_eq!(load(&dir).len(), 4);
+    }
+}
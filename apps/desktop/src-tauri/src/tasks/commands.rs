@@ -1,4 +1,5 @@
 use super::task::{Task, TaskUpdate};
+use crate::error::CommandError;
 use crate::AppState;
 use tauri::State;
 
@@ -9,18 +10,17 @@ pub fn task_create(
     description: String,
     active_form: Option<String>,
     state: State<'_, AppState>,
-) -> Result<Task, String> {
-    let manager = state
-        .get_task_manager(&session_id)
-        .map_err(|e| e.to_string())?;
+) -> Result<Task, CommandError> {
+    let manager = state.get_task_manager(&session_id)?;
     Ok(manager.create(subject, description, active_form))
 }
 
 #[tauri::command]
-pub fn task_list(session_id: String, state: State<'_, AppState>) -> Result<Vec<Task>, String> {
-    let manager = state
-        .get_task_manager(&session_id)
-        .map_err(|e| e.to_string())?;
+pub fn task_list(
+    session_id: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<Task>, CommandError> {
+    let manager = state.get_task_manager(&session_id)?;
     Ok(manager.list())
 }
 
@@ -29,13 +29,11 @@ pub fn task_get(
     session_id: String,
     task_id: String,
     state: State<'_, AppState>,
-) -> Result<Task, String> {
-    let manager = state
-        .get_task_manager(&session_id)
-        .map_err(|e| e.to_string())?;
+) -> Result<Task, CommandError> {
+    let manager = state.get_task_manager(&session_id)?;
     manager
         .get(&task_id)
-        .ok_or_else(|| format!("Task {} not found", task_id))
+        .ok_or_else(|| CommandError::not_found(format!("Task {} not found", task_id)))
 }
 
 #[tauri::command]
@@ -44,13 +42,11 @@ pub fn task_update(
     task_id: String,
     updates: TaskUpdate,
     state: State<'_, AppState>,
-) -> Result<Task, String> {
-    let manager = state
-        .get_task_manager(&session_id)
-        .map_err(|e| e.to_string())?;
+) -> Result<Task, CommandError> {
+    let manager = state.get_task_manager(&session_id)?;
     manager
         .update(&task_id, updates)
-        .ok_or_else(|| format!("Task {} not found", task_id))
+        .ok_or_else(|| CommandError::not_found(format!("Task {} not found", task_id)))
 }
 
 #[tauri::command]
@@ -58,10 +54,8 @@ pub fn task_claim(
     session_id: String,
     worker_id: String,
     state: State<'_, AppState>,
-) -> Result<Option<Task>, String> {
-    let manager = state
-        .get_task_manager(&session_id)
-        .map_err(|e| e.to_string())?;
+) -> Result<Option<Task>, CommandError> {
+    let manager = state.get_task_manager(&session_id)?;
     Ok(manager.claim_available(&worker_id))
 }
 
@@ -70,11 +64,9 @@ pub fn task_delete(
     session_id: String,
     task_id: String,
     state: State<'_, AppState>,
-) -> Result<Task, String> {
-    let manager = state
-        .get_task_manager(&session_id)
-        .map_err(|e| e.to_string())?;
+) -> Result<Task, CommandError> {
+    let manager = state.get_task_manager(&session_id)?;
     manager
         .delete(&task_id)
-        .ok_or_else(|| format!("Task {} not found", task_id))
+        .ok_or_else(|| CommandError::not_found(format!("Task {} not found", task_id)))
 }
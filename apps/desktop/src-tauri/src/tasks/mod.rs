@@ -1,4 +1,7 @@
+pub mod board;
 pub mod commands;
+pub mod plan;
+pub mod query;
 pub mod task;
 
 pub use task::TaskManager;
@@ -0,0 +1,240 @@
+//! Importing agent plans into the task board
+//!
+//! Agents that publish a plan (`SessionUpdate::Plan`) keep their own todo
+//! list, which the swarm board never sees. With `import_agent_plans` on (or
+//! turned on for the session), each plan entry becomes a task: entries that
+//! match an existing task by subject reuse it, priorities go in the task's
+//! `priority` metadata, and later plan updates move the tasks along as the
+//! agent marks entries in progress or completed. Tasks never move back, so
+//! a stale plan can't undo progress made on the board.
+
+use super::task::{Task, TaskStatus, TaskUpdate};
+use super::TaskManager;
+use crate::events::PlanEntry;
+use crate::settings::load_settings;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use std::collections::{HashMap, HashSet};
+
+/// Longest task subject taken from a plan entry; the full entry goes in the
+/// description
+const MAX_SUBJECT_CHARS: usize = 120;
+
+/// Per-session switch (session_id -> enabled); others follow the setting
+static PLAN_IMPORT: Lazy<Mutex<HashMap<String, bool>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Entries already imported per session (normalized subjects), so a task
+/// deleted from the board isn't created again by the next plan update
+static IMPORTED: Lazy<Mutex<HashMap<String, HashSet<String>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Task ids a plan update created or moved
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PlanImport {
+    pub created: Vec<String>,
+    pub updated: Vec<String>,
+}
+
+impl PlanImport {
+    pub fn is_empty(&self) -> bool {
+        self.created.is_empty() && self.updated.is_empty()
+    }
+}
+
+/// Whether plans from the session's agents become tasks
+pub fn import_enabled(session_id: &str) -> bool {
+    PLAN_IMPORT
+        .lock()
+        .get(session_id)
+        .copied()
+        .unwrap_or_else(|| load_settings().import_agent_plans)
+}
+
+fn normalize(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+fn subject(content: &str) -> String {
+    let line = content.lines().map(str::trim).find(|l| !l.is_empty()).unwrap_or_default();
+    if line.chars().count() <= MAX_SUBJECT_CHARS {
+        return line.to_string();
+    }
+    let cut: String = line.chars().take(MAX_SUBJECT_CHARS - 1).collect();
+    format!("{}…", cut.trim_end())
+}
+
+fn plan_status(status: &str) -> TaskStatus {
+    match status {
+        "in_progress" => TaskStatus::InProgress,
+        "completed" => TaskStatus::Completed,
+        _ => TaskStatus::Pending,
+    }
+}
+
+fn rank(status: &TaskStatus) -> u8 {
+    match status {
+        TaskStatus::Pending => 0,
+        TaskStatus::InProgress => 1,
+        TaskStatus::Completed | TaskStatus::Deleted => 2,
+    }
+}
+
+/// Bring the board in line with a plan `worker_id` published
+pub fn import_plan(
+    task_manager: &TaskManager,
+    session_id: &str,
+    worker_id: &str,
+    entries: &[PlanEntry],
+) -> PlanImport {
+    let mut result = PlanImport::default();
+    let mut by_subject: HashMap<String, Task> = task_manager
+        .list()
+        .into_iter()
+        .map(|task| (normalize(&task.subject), task))
+        .collect();
+
+    for entry in entries {
+        let subject = subject(&entry.content);
+        let key = normalize(&subject);
+        if key.is_empty() {
+            continue;
+        }
+        let status = plan_status(&entry.status);
+
+        let task = match by_subject.get(&key) {
+            Some(task) => task.clone(),
+            None => {
+                let first_import = IMPORTED
+                    .lock()
+                    .entry(session_id.to_string())
+                    .or_default()
+                    .insert(key.clone());
+                if !first_import {
+                    continue;
+                }
+                let description = if entry.content.trim() == subject {
+                    String::new()
+                } else {
+                    entry.content.trim().to_string()
+                };
+                let task = task_manager.create(subject, description, None);
+                let metadata = HashMap::from([
+                    ("source".to_string(), serde_json::json!("plan")),
+                    ("priority".to_string(), serde_json::json!(entry.priority)),
+                    ("plan_worker".to_string(), serde_json::json!(worker_id)),
+                ]);
+                let task = task_manager
+                    .update(
+                        &task.id,
+                        TaskUpdate {
+                            metadata: Some(metadata),
+                            ..Default::default()
+                        },
+                    )
+                    .unwrap_or(task);
+                result.created.push(task.id.clone());
+                by_subject.insert(key.clone(), task.clone());
+                task
+            }
+        };
+
+        if rank(&status) > rank(&task.status) {
+            let owner = task.owner.is_none().then(|| worker_id.to_string());
+            if let Some(moved) = task_manager.update(
+                &task.id,
+                TaskUpdate {
+                    status: Some(status),
+                    owner,
+                    ..Default::default()
+                },
+            ) {
+                if !result.created.contains(&moved.id) && !result.updated.contains(&moved.id) {
+                    result.updated.push(moved.id.clone());
+                }
+                by_subject.insert(key, moved);
+            }
+        }
+    }
+    result
+}
+
+/// Forget a session's imports and switch
+pub fn clear_session(session_id: &str) {
+    PLAN_IMPORT.lock().remove(session_id);
+    IMPORTED.lock().remove(session_id);
+}
+
+/// Turn plan import on or off for a session (overrides `import_agent_plans`)
+#[tauri::command]
+pub fn set_plan_import(session_id: String, enabled: bool) {
+    PLAN_IMPORT.lock().insert(session_id, enabled);
+}
+
+/// Whether plans from the session's agents become tasks
+#[tauri::command]
+pub fn get_plan_import(session_id: String) -> bool {
+    import_enabled(&session_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(content: &str, priority: &str, status: &str) -> PlanEntry {
+        PlanEntry {
+            content: content.to_string(),
+            priority: priority.to_string(),
+            status: status.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_import_and_sync() {
+        let session = "plan-import-test";
+        let tasks = TaskManager::new(session.to_string());
+        let existing = tasks.create("Write  the docs".to_string(), "".to_string(), None);
+
+        let plan = vec![
+            entry("Add the parser", "high", "in_progress"),
+            entry("write the docs", "low", "pending"),
+            entry("Ship it", "medium", "pending"),
+        ];
+        let result = import_plan(&tasks, session, "worker-1", &plan);
+        assert_eq!(result.created.len(), 2);
+        assert!(result.updated.is_empty());
+        assert_eq!(tasks.list().len(), 3);
+
+        let parser = tasks.get(&result.created[0]).unwrap();
+        assert_eq!(parser.subject, "Add the parser");
+        assert_eq!(parser.status, TaskStatus::InProgress);
+        assert_eq!(parser.owner.as_deref(), Some("worker-1"));
+        assert_eq!(parser.metadata["priority"], "high");
+
+        // The agent finishes the docs; the parser entry going back to
+        // pending doesn't undo progress
+        let plan = vec![
+            entry("Add the parser", "high", "pending"),
+            entry("write the docs", "low", "completed"),
+            entry("Ship it", "medium", "pending"),
+        ];
+        let result = import_plan(&tasks, session, "worker-1", &plan);
+        assert_eq!(result, PlanImport { created: vec![], updated: vec![existing.id.clone()] });
+        assert_eq!(tasks.get(&existing.id).unwrap().status, TaskStatus::Completed);
+        assert_eq!(tasks.get(&parser.id).unwrap().status, TaskStatus::InProgress);
+
+        // Deleted tasks stay deleted
+        tasks.delete(&parser.id);
+        assert!(import_plan(&tasks, session, "worker-1", &plan).is_empty());
+        clear_session(session);
+    }
+
+    #[test]
+    fn test_subject() {
+        assert_eq!(subject("\n  Fix the build\nthen rerun"), "Fix the build");
+        let long = "x".repeat(200);
+        assert_eq!(subject(&long).chars().count(), MAX_SUBJECT_CHARS);
+    }
+}
@@ -0,0 +1,269 @@
+//! Task board queries and change events
+//!
+//! `task_query` filters, sorts and pages a session's tasks for the Kanban
+//! view and counts them per column. Every change to a session's tasks, made
+//! through the task commands or a worker's `swarm task` commands, is
+//! announced as `task-board-changed` with the new counts, so the view can
+//! refresh without polling `task_list`.
+
+use super::task::{Task, TaskStatus};
+use crate::events::{emit, TaskBoardChangedEvent};
+use crate::AppState;
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, State};
+use ts_rs::TS;
+
+/// Set by `start`; changes before then aren't announced
+static APP: OnceCell<AppHandle> = OnceCell::new();
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskSort {
+    #[default]
+    Created,
+    Updated,
+    Subject,
+    /// Pending, in progress, completed
+    Status,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TaskQuery {
+    /// Only these statuses (deleted tasks are never returned)
+    pub status: Option<Vec<TaskStatus>>,
+    /// Only tasks owned by this worker; "" for unassigned tasks
+    pub assignee: Option<String>,
+    /// Case-insensitive match on id, subject or description
+    pub text: Option<String>,
+    pub sort: TaskSort,
+    pub descending: bool,
+    pub limit: Option<usize>,
+}
+
+/// Task counts per Kanban column
+#[derive(Debug, Clone, Default, Serialize, PartialEq, TS)]
+#[ts(export)]
+pub struct TaskColumns {
+    pub pending: usize,
+    pub in_progress: usize,
+    pub completed: usize,
+    /// Pending tasks still waiting on others (counted in `pending` too)
+    pub blocked: usize,
+}
+
+impl TaskColumns {
+    pub fn count<'a>(tasks: impl IntoIterator<Item = &'a Task>) -> Self {
+        let mut columns = Self::default();
+        for task in tasks {
+            match task.status {
+                TaskStatus::Pending => {
+                    columns.pending += 1;
+                    if !task.blocked_by.is_empty() {
+                        columns.blocked += 1;
+                    }
+                }
+                TaskStatus::InProgress => columns.in_progress += 1,
+                TaskStatus::Completed => columns.completed += 1,
+                TaskStatus::Deleted => {}
+            }
+        }
+        columns
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskQueryResult {
+    pub tasks: Vec<Task>,
+    /// Matches before `limit`
+    pub total: usize,
+    /// Counts of the tasks matching `assignee` and `text`, whatever their
+    /// status
+    pub columns: TaskColumns,
+}
+
+/// What happened to the board
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum TaskChange {
+    Created,
+    Updated,
+    Claimed,
+    Deleted,
+    /// A project board was mounted, replacing the session's tasks
+    Mounted,
+}
+
+fn status_rank(status: &TaskStatus) -> u8 {
+    match status {
+        TaskStatus::Pending => 0,
+        TaskStatus::InProgress => 1,
+        TaskStatus::Completed => 2,
+        TaskStatus::Deleted => 3,
+    }
+}
+
+/// Run a query over a session's (non-deleted) tasks
+pub fn query(tasks: Vec<Task>, query: &TaskQuery) -> TaskQueryResult {
+    let text = query
+        .text
+        .as_deref()
+        .map(|t| t.trim().to_lowercase())
+        .filter(|t| !t.is_empty());
+    let matching: Vec<Task> = tasks
+        .into_iter()
+        .filter(|task| match query.assignee.as_deref() {
+            None => true,
+            Some("") => task.owner.is_none(),
+            Some(assignee) => task.owner.as_deref() == Some(assignee),
+        })
+        .filter(|task| {
+            text.as_ref().map_or(true, |text| {
+                task.id == text.trim_start_matches('#')
+                    || task.subject.to_lowercase().contains(text)
+                    || task.description.to_lowercase().contains(text)
+            })
+        })
+        .collect();
+    let columns = TaskColumns::count(&matching);
+
+    let mut tasks: Vec<Task> = matching
+        .into_iter()
+        .filter(|task| {
+            query
+                .status
+                .as_ref()
+                .map_or(true, |statuses| statuses.contains(&task.status))
+        })
+        .collect();
+    match query.sort {
+        TaskSort::Created => tasks.sort_by_key(|t| t.created_at),
+        TaskSort::Updated => tasks.sort_by_key(|t| t.updated_at),
+        TaskSort::Subject => tasks.sort_by_key(|t| t.subject.to_lowercase()),
+        TaskSort::Status => tasks.sort_by_key(|t| (status_rank(&t.status), t.created_at)),
+    }
+    if query.descending {
+        tasks.reverse();
+    }
+    let total = tasks.len();
+    if let Some(limit) = query.limit {
+        tasks.truncate(limit);
+    }
+
+    TaskQueryResult {
+        tasks,
+        total,
+        columns,
+    }
+}
+
+/// Announce a change to a session's tasks
+pub(super) fn notify(
+    session_id: &str,
+    task_id: Option<&str>,
+    change: TaskChange,
+    columns: TaskColumns,
+) {
+    let Some(app) = APP.get() else {
+        return;
+    };
+    emit(
+        app,
+        &TaskBoardChangedEvent {
+            session_id: session_id.to_string(),
+            task_id: task_id.map(str::to_string),
+            change,
+            columns,
+        },
+    );
+}
+
+/// Start announcing task changes
+pub fn start(app: AppHandle) {
+    let _ = APP.set(app);
+}
+
+/// Filter, sort and page a session's tasks
+#[tauri::command]
+pub fn task_query(
+    session_id: String,
+    query: Option<TaskQuery>,
+    state: State<'_, AppState>,
+) -> Result<TaskQueryResult, String> {
+    let manager = state.get_task_manager(&session_id)?;
+    Ok(self::query(manager.list(), &query.unwrap_or_default()))
+}
+
+/// How many tasks are in each column
+#[tauri::command]
+pub fn task_column_counts(
+    session_id: String,
+    state: State<'_, AppState>,
+) -> Result<TaskColumns, String> {
+    let manager = state.get_task_manager(&session_id)?;
+    Ok(TaskColumns::count(&manager.list()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tasks::task::TaskUpdate;
+    use crate::tasks::TaskManager;
+
+    #[test]
+    fn test_query() {
+        let manager = TaskManager::new("query-test".to_string());
+        let setup = manager.create("Set up CI".to_string(), "GitHub actions".to_string(), None);
+        let docs = manager.create("Write docs".to_string(), "".to_string(), None);
+        manager.create("Add tests".to_string(), "cover the CI scripts".to_string(), None);
+        manager.update(
+            &docs.id,
+            TaskUpdate {
+                add_blocked_by: Some(vec![setup.id.clone()]),
+                ..Default::default()
+            },
+        );
+        manager.claim_available("worker-1");
+
+        let all = query(manager.list(), &TaskQuery::default());
+        assert_eq!(all.total, 3);
+        assert_eq!(all.columns.pending + all.columns.in_progress, 3);
+        assert_eq!(all.columns.blocked, 1);
+
+        let ci = query(
+            manager.list(),
+            &TaskQuery {
+                text: Some("ci".to_string()),
+                sort: TaskSort::Subject,
+                ..Default::default()
+            },
+        );
+        let subjects: Vec<&str> = ci.tasks.iter().map(|t| t.subject.as_str()).collect();
+        assert_eq!(subjects, vec!["Add tests", "Set up CI"]);
+
+        let unassigned = query(
+            manager.list(),
+            &TaskQuery {
+                assignee: Some(String::new()),
+                status: Some(vec![TaskStatus::Pending]),
+                limit: Some(1),
+                ..Default::default()
+            },
+        );
+        assert_eq!(unassigned.total, 2);
+        assert_eq!(unassigned.tasks.len(), 1);
+        assert_eq!(unassigned.columns.in_progress, 0);
+
+        let by_id = query(
+            manager.list(),
+            &TaskQuery {
+                text: Some(format!("#{}", docs.id)),
+                ..Default::default()
+            },
+        );
+        assert_eq!(by_id.tasks[0].id, docs.id);
+    }
+}
@@ -1,6 +1,10 @@
-use parking_lot::Mutex;
+use super::board::{self, BOARD_IO};
+use super::query::{self, TaskChange, TaskColumns};
+use crate::time::now_ms;
+use parking_lot::{Mutex, MutexGuard};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
@@ -11,7 +15,7 @@ pub enum TaskStatus {
     Deleted,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Task {
     pub id: String,
@@ -30,7 +34,8 @@ pub struct Task {
 pub struct TaskManager {
     tasks: Mutex<HashMap<String, Task>>,
     next_id: Mutex<u64>,
-    #[allow(dead_code)]
+    /// Project board the tasks are kept on, if one is mounted (see `board`)
+    board: Mutex<Option<PathBuf>>,
     session_id: String,
 }
 
@@ -39,15 +44,108 @@ impl TaskManager {
         Self {
             tasks: Mutex::new(HashMap::new()),
             next_id: Mutex::new(1),
+            board: Mutex::new(None),
             session_id,
         }
     }
 
+    /// The mounted project board, if any
+    pub fn board(&self) -> Option<PathBuf> {
+        self.board.lock().clone()
+    }
+
+    /// Keep tasks on the board in `dir` from now on. Tasks already in the
+    /// session are added to the board under new ids (dependencies follow).
+    pub fn mount_board(&self, dir: &Path) -> Result<(), String> {
+        let _io = BOARD_IO.lock();
+        let mut on_board = board::load(dir);
+        let mut next_id = board::next_id(&on_board);
+
+        let mut local: Vec<Task> = self.tasks.lock().values().cloned().collect();
+        local.sort_by_key(|t| t.created_at);
+        let new_ids: HashMap<String, String> = local
+            .iter()
+            .map(|t| {
+                let id = next_id.to_string();
+                next_id += 1;
+                (t.id.clone(), id)
+            })
+            .collect();
+        let renumber = |ids: &[String]| -> Vec<String> {
+            ids.iter()
+                .map(|id| new_ids.get(id).cloned().unwrap_or_else(|| id.clone()))
+                .collect()
+        };
+        for mut task in local {
+            task.id = new_ids[&task.id].clone();
+            task.blocked_by = renumber(&task.blocked_by);
+            task.blocks = renumber(&task.blocks);
+            board::save(dir, &task)?;
+            on_board.insert(task.id.clone(), task);
+        }
+
+        *self.tasks.lock() = on_board;
+        *self.next_id.lock() = next_id;
+        *self.board.lock() = Some(dir.to_path_buf());
+        self.changed(None, TaskChange::Mounted);
+        Ok(())
+    }
+
+    /// Stop keeping tasks on the board; the session keeps its current copy
+    pub fn unmount_board(&self) {
+        let _io = BOARD_IO.lock();
+        self.reload();
+        *self.board.lock() = None;
+    }
+
+    /// Pick up changes other sessions made to the board. Returns the guard
+    /// to hold until this session's own changes are written back.
+    fn sync(&self) -> Option<MutexGuard<'static, ()>> {
+        self.board.lock().as_ref()?;
+        let io = BOARD_IO.lock();
+        self.reload();
+        Some(io)
+    }
+
+    fn reload(&self) {
+        let Some(dir) = self.board() else {
+            return;
+        };
+        let tasks = board::load(&dir);
+        *self.next_id.lock() = board::next_id(&tasks);
+        *self.tasks.lock() = tasks;
+    }
+
+    /// Write tasks back to the board (when one is mounted)
+    fn persist<'a>(&self, tasks: impl IntoIterator<Item = &'a Task>) {
+        let Some(dir) = self.board() else {
+            return;
+        };
+        for task in tasks {
+            if let Err(e) = board::save(&dir, task) {
+                eprintln!("[Tasks] {}", e);
+            }
+        }
+    }
+
     pub fn create(
         &self,
         subject: String,
         description: String,
         active_form: Option<String>,
+    ) -> Task {
+        let _io = self.sync();
+        let task = self.create_task(subject, description, active_form);
+        self.persist([&task]);
+        self.changed(Some(&task.id), TaskChange::Created);
+        task
+    }
+
+    fn create_task(
+        &self,
+        subject: String,
+        description: String,
+        active_form: Option<String>,
     ) -> Task {
         let mut tasks = self.tasks.lock();
         let mut next_id = self.next_id.lock();
@@ -55,7 +153,7 @@ impl TaskManager {
         let id = next_id.to_string();
         *next_id += 1;
 
-        let now = chrono::Utc::now().timestamp_millis();
+        let now = now_ms();
         let task = Task {
             id: id.clone(),
             subject,
@@ -75,6 +173,26 @@ impl TaskManager {
     }
 
     pub fn update(&self, id: &str, updates: TaskUpdate) -> Option<Task> {
+        let io = self.sync();
+        let before = io.as_ref().map(|_| self.tasks.lock().clone());
+        let change = if updates.status == Some(TaskStatus::Deleted) {
+            TaskChange::Deleted
+        } else {
+            TaskChange::Updated
+        };
+        let updated = self.update_task(id, updates);
+        // The update can touch the tasks it blocks or is blocked by too
+        if let Some(before) = before {
+            let tasks = self.tasks.lock();
+            self.persist(tasks.values().filter(|t| before.get(&t.id) != Some(*t)));
+        }
+        if updated.is_some() {
+            self.changed(Some(id), change);
+        }
+        updated
+    }
+
+    fn update_task(&self, id: &str, updates: TaskUpdate) -> Option<Task> {
         let mut tasks = self.tasks.lock();
         let task_id = id.to_string();
 
@@ -153,13 +271,13 @@ impl TaskManager {
                 }
             }
 
-            task.updated_at = chrono::Utc::now().timestamp_millis();
+            task.updated_at = now_ms();
         }
 
         // Apply deferred updates to other tasks
 
         // Unblock tasks (remove this task from their blocked_by)
-        let now = chrono::Utc::now().timestamp_millis();
+        let now = now_ms();
         for blocked_id in unblock_tasks {
             if let Some(blocked_task) = tasks.get_mut(&blocked_id) {
                 blocked_task.blocked_by.retain(|b| b != &task_id);
@@ -191,6 +309,7 @@ impl TaskManager {
     }
 
     pub fn list(&self) -> Vec<Task> {
+        let _io = self.sync();
         let tasks = self.tasks.lock();
         let mut result: Vec<Task> = tasks
             .values()
@@ -204,10 +323,27 @@ impl TaskManager {
     }
 
     pub fn get(&self, id: &str) -> Option<Task> {
+        let _io = self.sync();
         self.tasks.lock().get(id).cloned()
     }
 
     pub fn claim_available(&self, worker_id: &str) -> Option<Task> {
+        let _io = self.sync();
+        let claimed = self.claim_task(worker_id);
+        self.persist(&claimed);
+        if let Some(task) = &claimed {
+            self.changed(Some(&task.id), TaskChange::Claimed);
+        }
+        claimed
+    }
+
+    /// Announce a change with the new column counts
+    fn changed(&self, task_id: Option<&str>, change: TaskChange) {
+        let columns = TaskColumns::count(self.tasks.lock().values());
+        query::notify(&self.session_id, task_id, change, columns);
+    }
+
+    fn claim_task(&self, worker_id: &str) -> Option<Task> {
         let mut tasks = self.tasks.lock();
 
         // Find first available task (pending, no owner, not blocked)
@@ -224,7 +360,7 @@ impl TaskManager {
             if let Some(task) = tasks.get_mut(&id) {
                 task.owner = Some(worker_id.to_string());
                 task.status = TaskStatus::InProgress;
-                task.updated_at = chrono::Utc::now().timestamp_millis();
+                task.updated_at = now_ms();
                 return Some(task.clone());
             }
         }
@@ -0,0 +1,94 @@
+//! OpenTelemetry export
+//!
+//! With the `otel` feature and `telemetry.enabled` set, session lifecycle,
+//! prompt latency, tool calls, verifier runs and spend are exported over
+//! OTLP/HTTP to `telemetry.endpoint` (metrics every
+//! `export_interval_secs`, spans in batches). Without the feature every
+//! function here does nothing, so call sites don't need `cfg`s.
+//!
+//! Metric names are prefixed `crafter.`; attributes are kept to agent,
+//! model, kind and outcome so cardinality stays low (no session ids).
+
+#[cfg(feature = "otel")]
+mod otlp;
+
+use serde::Serialize;
+use std::time::{Duration, SystemTime};
+
+/// Start exporting, if built with `otel` and enabled in settings (applies
+/// on restart)
+pub fn init() {
+    #[cfg(feature = "otel")]
+    otlp::init(&crate::settings::load_settings().telemetry);
+}
+
+/// Flush and stop the exporters
+pub fn shutdown() {
+    #[cfg(feature = "otel")]
+    otlp::shutdown();
+}
+
+pub fn session_started() {
+    #[cfg(feature = "otel")]
+    otlp::session_started();
+}
+
+/// A session reached a final status; `started_at` is a unix timestamp in
+/// milliseconds
+#[cfg_attr(not(feature = "otel"), allow(unused_variables))]
+pub fn session_finished(status: &str, started_at: i64, workers: usize) {
+    #[cfg(feature = "otel")]
+    otlp::session_finished(status, started_at, workers);
+}
+
+#[cfg_attr(not(feature = "otel"), allow(unused_variables))]
+pub fn prompt_finished(
+    agent_id: &str,
+    model: &str,
+    outcome: &str,
+    started: SystemTime,
+    duration: Duration,
+) {
+    #[cfg(feature = "otel")]
+    otlp::prompt_finished(agent_id, model, outcome, started, duration);
+}
+
+#[cfg_attr(not(feature = "otel"), allow(unused_variables))]
+pub fn tool_call_finished(kind: Option<&str>, status: &str, duration_ms: Option<i64>) {
+    #[cfg(feature = "otel")]
+    otlp::tool_call_finished(kind, status, duration_ms);
+}
+
+#[cfg_attr(not(feature = "otel"), allow(unused_variables))]
+pub fn verifier_run(criterion_type: &str, passed: bool, started: SystemTime, duration: Duration) {
+    #[cfg(feature = "otel")]
+    otlp::verifier_run(criterion_type, passed, started, duration);
+}
+
+#[cfg_attr(not(feature = "otel"), allow(unused_variables))]
+pub fn usage(record: &crate::stats::UsageRecord) {
+    #[cfg(feature = "otel")]
+    otlp::usage(record);
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TelemetryStatus {
+    /// Built with the `otel` feature
+    pub available: bool,
+    /// Exporting right now
+    pub active: bool,
+    pub endpoint: String,
+}
+
+/// Whether OTLP export is built in and running
+#[tauri::command]
+pub fn get_telemetry_status() -> TelemetryStatus {
+    TelemetryStatus {
+        available: cfg!(feature = "otel"),
+        #[cfg(feature = "otel")]
+        active: otlp::is_active(),
+        #[cfg(not(feature = "otel"))]
+        active: false,
+        endpoint: crate::settings::load_settings().telemetry.endpoint,
+    }
+}
@@ -0,0 +1,283 @@
+//! OTLP/HTTP exporters and the instruments recorded into them
+
+use crate::settings::store::TelemetrySettings;
+use crate::stats::UsageRecord;
+use once_cell::sync::Lazy;
+use opentelemetry::global::{self, BoxedTracer};
+use opentelemetry::metrics::{Counter, Histogram, Meter};
+use opentelemetry::trace::{Span, Tracer, TracerProvider as _};
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::{MetricExporter, SpanExporter, WithExportConfig, WithHttpConfig};
+use opentelemetry_sdk::metrics::{PeriodicReader, SdkMeterProvider};
+use opentelemetry_sdk::trace::TracerProvider;
+use opentelemetry_sdk::{runtime, Resource};
+use parking_lot::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const SCOPE: &str = "crafter-code";
+
+struct Instruments {
+    sessions_started: Counter<u64>,
+    sessions_finished: Counter<u64>,
+    session_duration: Histogram<f64>,
+    prompts: Counter<u64>,
+    prompt_duration: Histogram<f64>,
+    tool_calls: Counter<u64>,
+    tool_call_duration: Histogram<f64>,
+    verifier_runs: Counter<u64>,
+    verifier_duration: Histogram<f64>,
+    cost: Counter<f64>,
+    tokens: Counter<u64>,
+}
+
+impl Instruments {
+    fn new(meter: &Meter) -> Self {
+        Self {
+            sessions_started: meter
+                .u64_counter("crafter.sessions.started")
+                .with_description("Sessions created")
+                .build(),
+            sessions_finished: meter
+                .u64_counter("crafter.sessions.finished")
+                .with_description("Sessions that completed, failed or were cancelled")
+                .build(),
+            session_duration: meter
+                .f64_histogram("crafter.session.duration")
+                .with_unit("s")
+                .build(),
+            prompts: meter
+                .u64_counter("crafter.prompts")
+                .with_description("Prompts sent to agents")
+                .build(),
+            prompt_duration: meter
+                .f64_histogram("crafter.prompt.duration")
+                .with_unit("s")
+                .build(),
+            tool_calls: meter
+                .u64_counter("crafter.tool_calls")
+                .with_description("Finished tool calls")
+                .build(),
+            tool_call_duration: meter
+                .f64_histogram("crafter.tool_call.duration")
+                .with_unit("s")
+                .build(),
+            verifier_runs: meter
+                .u64_counter("crafter.verifier.runs")
+                .with_description("Acceptance criteria checked")
+                .build(),
+            verifier_duration: meter
+                .f64_histogram("crafter.verifier.duration")
+                .with_unit("s")
+                .build(),
+            cost: meter
+                .f64_counter("crafter.cost")
+                .with_unit("USD")
+                .build(),
+            tokens: meter
+                .u64_counter("crafter.tokens")
+                .with_unit("{token}")
+                .build(),
+        }
+    }
+}
+
+struct Telemetry {
+    meter_provider: SdkMeterProvider,
+    tracer_provider: TracerProvider,
+    tracer: BoxedTracer,
+    instruments: Instruments,
+}
+
+static TELEMETRY: Lazy<Mutex<Option<Telemetry>>> = Lazy::new(|| Mutex::new(None));
+
+fn build(settings: &TelemetrySettings) -> Result<Telemetry, String> {
+    let endpoint = settings.endpoint.trim_end_matches('/');
+    let resource = Resource::new(vec![
+        KeyValue::new("service.name", settings.service_name.clone()),
+        KeyValue::new("service.version", env!("CARGO_PKG_VERSION")),
+    ]);
+
+    let metric_exporter = MetricExporter::builder()
+        .with_http()
+        .with_endpoint(format!("{}/v1/metrics", endpoint))
+        .with_headers(settings.headers.clone())
+        .build()
+        .map_err(|e| format!("Failed to create metric exporter: {}", e))?;
+    let reader = PeriodicReader::builder(metric_exporter, runtime::Tokio)
+        .with_interval(Duration::from_secs(settings.export_interval_secs.max(1)))
+        .build();
+    let meter_provider = SdkMeterProvider::builder()
+        .with_reader(reader)
+        .with_resource(resource.clone())
+        .build();
+
+    let span_exporter = SpanExporter::builder()
+        .with_http()
+        .with_endpoint(format!("{}/v1/traces", endpoint))
+        .with_headers(settings.headers.clone())
+        .build()
+        .map_err(|e| format!("Failed to create span exporter: {}", e))?;
+    let tracer_provider = TracerProvider::builder()
+        .with_batch_exporter(span_exporter, runtime::Tokio)
+        .with_resource(resource)
+        .build();
+
+    global::set_meter_provider(meter_provider.clone());
+    global::set_tracer_provider(tracer_provider.clone());
+
+    Ok(Telemetry {
+        instruments: Instruments::new(&global::meter(SCOPE)),
+        tracer: global::tracer(SCOPE),
+        meter_provider,
+        tracer_provider,
+    })
+}
+
+pub fn init(settings: &TelemetrySettings) {
+    if !settings.enabled || settings.endpoint.trim().is_empty() {
+        return;
+    }
+    // The exporters spawn their tasks on the current Tokio runtime
+    let _runtime = tauri::async_runtime::handle().inner().enter();
+    match build(settings) {
+        Ok(telemetry) => {
+            eprintln!("[Telemetry] Exporting to {}", settings.endpoint);
+            *TELEMETRY.lock() = Some(telemetry);
+        }
+        Err(e) => eprintln!("[Telemetry] {}", e),
+    }
+}
+
+pub fn shutdown() {
+    let Some(telemetry) = TELEMETRY.lock().take() else {
+        return;
+    };
+    if let Err(e) = telemetry.meter_provider.shutdown() {
+        eprintln!("[Telemetry] Failed to flush metrics: {}", e);
+    }
+    if let Err(e) = telemetry.tracer_provider.shutdown() {
+        eprintln!("[Telemetry] Failed to flush spans: {}", e);
+    }
+}
+
+pub fn is_active() -> bool {
+    TELEMETRY.lock().is_some()
+}
+
+fn with<F: FnOnce(&Telemetry)>(f: F) {
+    if let Some(telemetry) = TELEMETRY.lock().as_ref() {
+        f(telemetry);
+    }
+}
+
+/// Record a span that already finished
+fn span(
+    telemetry: &Telemetry,
+    name: &'static str,
+    started: SystemTime,
+    duration: Duration,
+    attributes: Vec<KeyValue>,
+) {
+    let mut span = telemetry
+        .tracer
+        .span_builder(name)
+        .with_start_time(started)
+        .with_attributes(attributes)
+        .start(&telemetry.tracer);
+    span.end_with_timestamp(started + duration);
+}
+
+pub fn session_started() {
+    with(|t| t.instruments.sessions_started.add(1, &[]));
+}
+
+pub fn session_finished(status: &str, started_at: i64, workers: usize) {
+    let started = UNIX_EPOCH + Duration::from_millis(started_at.max(0) as u64);
+    let duration = SystemTime::now().duration_since(started).unwrap_or_default();
+    with(|t| {
+        let attributes = [KeyValue::new("status", status.to_string())];
+        t.instruments.sessions_finished.add(1, &attributes);
+        t.instruments
+            .session_duration
+            .record(duration.as_secs_f64(), &attributes);
+        span(
+            t,
+            "session",
+            started,
+            duration,
+            vec![
+                KeyValue::new("status", status.to_string()),
+                KeyValue::new("workers", workers as i64),
+            ],
+        );
+    });
+}
+
+pub fn prompt_finished(
+    agent_id: &str,
+    model: &str,
+    outcome: &str,
+    started: SystemTime,
+    duration: Duration,
+) {
+    with(|t| {
+        let attributes = vec![
+            KeyValue::new("agent", agent_id.to_string()),
+            KeyValue::new("model", model.to_string()),
+            KeyValue::new("outcome", outcome.to_string()),
+        ];
+        t.instruments.prompts.add(1, &attributes);
+        t.instruments
+            .prompt_duration
+            .record(duration.as_secs_f64(), &attributes);
+        span(t, "prompt", started, duration, attributes);
+    });
+}
+
+pub fn tool_call_finished(kind: Option<&str>, status: &str, duration_ms: Option<i64>) {
+    with(|t| {
+        let attributes = [
+            KeyValue::new("kind", kind.unwrap_or("other").to_string()),
+            KeyValue::new("status", status.to_string()),
+        ];
+        t.instruments.tool_calls.add(1, &attributes);
+        if let Some(duration_ms) = duration_ms {
+            t.instruments
+                .tool_call_duration
+                .record(duration_ms.max(0) as f64 / 1000.0, &attributes);
+        }
+    });
+}
+
+pub fn verifier_run(criterion_type: &str, passed: bool, started: SystemTime, duration: Duration) {
+    with(|t| {
+        let attributes = vec![
+            KeyValue::new("type", criterion_type.to_string()),
+            KeyValue::new("passed", passed),
+        ];
+        t.instruments.verifier_runs.add(1, &attributes);
+        t.instruments
+            .verifier_duration
+            .record(duration.as_secs_f64(), &attributes);
+        span(t, "verify_criterion", started, duration, attributes);
+    });
+}
+
+pub fn usage(record: &UsageRecord) {
+    with(|t| {
+        let attributes = [
+            KeyValue::new("agent", record.agent_id.clone()),
+            KeyValue::new("model", record.model.clone()),
+        ];
+        t.instruments.cost.add(record.cost_usd, &attributes);
+        let tokens = |direction: &'static str| {
+            [
+                attributes[0].clone(),
+                attributes[1].clone(),
+                KeyValue::new("direction", direction),
+            ]
+        };
+        t.instruments.tokens.add(record.input_tokens, &tokens("input"));
+        t.instruments.tokens.add(record.output_tokens, &tokens("output"));
+    });
+}
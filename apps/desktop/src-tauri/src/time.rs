@@ -0,0 +1,123 @@
+//! Timestamps
+//!
+//! Every timestamp the backend stores or sends is milliseconds since the
+//! Unix epoch, UTC (`now_ms`). Session files written before that saved
+//! seconds, so anything loaded from disk goes through `normalize_ms`.
+//! Timestamps made by another clock (the webview, a remote client) go
+//! through `clamp_skew`, so a clock running ahead can't date a message in
+//! the future. Local time only appears in exports, rendered in a
+//! `LocalZone` that is exported alongside it.
+
+use chrono::{DateTime, FixedOffset, Local, Offset, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Smaller positive timestamps are taken to be seconds: as milliseconds
+/// they'd be before March 1973, as seconds they reach the year 5138
+const SECONDS_BELOW: i64 = 100_000_000_000;
+
+/// How far ahead of our clock another clock may be before its timestamps
+/// are clamped to now
+pub const MAX_CLOCK_SKEW_MS: i64 = 60_000;
+
+/// Milliseconds since the Unix epoch
+pub fn now_ms() -> i64 {
+    Utc::now().timestamp_millis()
+}
+
+pub fn secs_to_ms(secs: i64) -> i64 {
+    secs.saturating_mul(1000)
+}
+
+/// A stored timestamp in milliseconds, whether it was saved in seconds or
+/// milliseconds
+pub fn normalize_ms(timestamp: i64) -> i64 {
+    if timestamp > 0 && timestamp < SECONDS_BELOW {
+        timestamp * 1000
+    } else {
+        timestamp
+    }
+}
+
+/// A timestamp from another clock, pulled back to now if that clock is
+/// more than `MAX_CLOCK_SKEW_MS` ahead of ours
+pub fn clamp_skew(timestamp: i64) -> i64 {
+    let now = now_ms();
+    if timestamp > now + MAX_CLOCK_SKEW_MS {
+        now
+    } else {
+        timestamp
+    }
+}
+
+/// A time zone local times were (or will be) shown in
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct LocalZone {
+    /// IANA name (e.g. "Europe/Madrid"), when the system says
+    pub name: Option<String>,
+    /// Offset from UTC in minutes, positive east of Greenwich
+    pub utc_offset_minutes: i32,
+}
+
+impl LocalZone {
+    /// The zone this machine is in now
+    pub fn current() -> Self {
+        Self {
+            name: zone_name(),
+            utc_offset_minutes: Local::now().offset().fix().local_minus_utc() / 60,
+        }
+    }
+
+    /// RFC 3339 local time (to the second) of a timestamp in milliseconds
+    pub fn format(&self, timestamp_ms: i64) -> String {
+        let offset =
+            FixedOffset::east_opt(self.utc_offset_minutes * 60).unwrap_or_else(|| Utc.fix());
+        DateTime::from_timestamp_millis(timestamp_ms)
+            .unwrap_or_default()
+            .with_timezone(&offset)
+            .to_rfc3339_opts(SecondsFormat::Secs, false)
+    }
+}
+
+/// `$TZ`, else the zone `/etc/localtime` links to
+fn zone_name() -> Option<String> {
+    if let Ok(tz) = std::env::var("TZ") {
+        let tz = tz.trim_start_matches(':');
+        if !tz.is_empty() {
+            return Some(tz.to_string());
+        }
+    }
+    let target = std::fs::read_link("/etc/localtime").ok()?;
+    let target = target.to_string_lossy();
+    target
+        .split_once("zoneinfo/")
+        .map(|(_, name)| name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_and_clamp() {
+        assert_eq!(normalize_ms(1_706_000_000), 1_706_000_000_000);
+        assert_eq!(normalize_ms(1_706_000_000_000), 1_706_000_000_000);
+        assert_eq!(normalize_ms(0), 0);
+
+        let now = now_ms();
+        assert_eq!(clamp_skew(now - 5_000), now - 5_000);
+        assert!(clamp_skew(now + 3_600_000) <= now_ms());
+    }
+
+    #[test]
+    fn test_format() {
+        let madrid = LocalZone {
+            name: Some("Europe/Madrid".to_string()),
+            utc_offset_minutes: 120,
+        };
+        assert_eq!(madrid.format(1_706_000_000_000), "2024-01-23T10:53:20+02:00");
+        assert_eq!(
+            LocalZone::default().format(1_706_000_000_000),
+            "2024-01-23T08:53:20+00:00"
+        );
+    }
+}
@@ -0,0 +1,148 @@
+//! Voice input
+//!
+//! `transcribe_audio` turns a recording from the prompt box's microphone
+//! button into text the frontend inserts into the prompt; the audio itself
+//! never reaches the agent. By default it goes to an OpenAI-compatible
+//! `/audio/transcriptions` endpoint (`transcription` in settings, with the
+//! key read from the environment or the keychain). Builds with the
+//! `whisper` feature can transcribe locally with whisper.cpp instead; that
+//! backend takes WAV recordings only.
+
+#[cfg(feature = "whisper")]
+mod whisper;
+
+use crate::error::CommandError;
+use crate::secrets::env_or_secret;
+use crate::settings::load_settings;
+use crate::settings::store::{TranscriptionBackend, TranscriptionSettings};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use reqwest::multipart::{Form, Part};
+use serde::Deserialize;
+use std::time::Duration;
+
+/// Largest recording accepted (the OpenAI API's own limit)
+const MAX_AUDIO_BYTES: usize = 25 * 1024 * 1024;
+
+const API_TIMEOUT: Duration = Duration::from_secs(120);
+
+#[derive(Deserialize)]
+struct ApiTranscription {
+    text: String,
+}
+
+/// File extension the API infers the format from
+fn extension(mime_type: &str) -> &'static str {
+    let essence = mime_type.split(';').next().unwrap_or_default().trim();
+    match essence {
+        "audio/wav" | "audio/x-wav" | "audio/wave" => "wav",
+        "audio/mpeg" | "audio/mp3" => "mp3",
+        "audio/mp4" | "audio/m4a" | "audio/x-m4a" => "m4a",
+        "audio/ogg" => "ogg",
+        "audio/flac" => "flac",
+        _ => "webm",
+    }
+}
+
+async fn transcribe_api(
+    settings: &TranscriptionSettings,
+    audio: Vec<u8>,
+    mime_type: &str,
+) -> Result<String, String> {
+    let key = env_or_secret(&settings.api_key_name).ok_or_else(|| {
+        format!(
+            "No API key for transcription: set {} or store it as a secret",
+            settings.api_key_name
+        )
+    })?;
+    let part = Part::bytes(audio)
+        .file_name(format!("recording.{}", extension(mime_type)))
+        .mime_str(mime_type)
+        .map_err(|e| format!("Invalid MIME type {}: {}", mime_type, e))?;
+    let mut form = Form::new()
+        .text("model", settings.model.clone())
+        .part("file", part);
+    if !settings.language.is_empty() {
+        form = form.text("language", settings.language.clone());
+    }
+
+    let response = reqwest::Client::new()
+        .post(&settings.api_url)
+        .bearer_auth(key)
+        .multipart(form)
+        .timeout(API_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| format!("Transcription request failed: {}", e))?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(format!("Transcription failed ({}): {}", status, body.trim()));
+    }
+    response
+        .json::<ApiTranscription>()
+        .await
+        .map(|t| t.text)
+        .map_err(|e| format!("Unexpected transcription response: {}", e))
+}
+
+#[cfg(feature = "whisper")]
+async fn transcribe_local(
+    settings: &TranscriptionSettings,
+    audio: Vec<u8>,
+    mime_type: &str,
+) -> Result<String, String> {
+    let settings = settings.clone();
+    let mime_type = mime_type.to_string();
+    tauri::async_runtime::spawn_blocking(move || {
+        whisper::transcribe(&settings, &audio, &mime_type)
+    })
+    .await
+    .map_err(|e| format!("Transcription task failed: {}", e))?
+}
+
+#[cfg(not(feature = "whisper"))]
+async fn transcribe_local(
+    _settings: &TranscriptionSettings,
+    _audio: Vec<u8>,
+    _mime_type: &str,
+) -> Result<String, String> {
+    Err("This build can't transcribe locally (it lacks the `whisper` feature)".to_string())
+}
+
+/// Transcribe a base64-encoded recording with the backend chosen in
+/// settings, returning the spoken text
+#[tauri::command]
+pub async fn transcribe_audio(data: String, mime_type: String) -> Result<String, CommandError> {
+    let audio = BASE64
+        .decode(data.trim())
+        .map_err(|e| CommandError::invalid_input(format!("Invalid audio data: {}", e)))?;
+    if audio.is_empty() {
+        return Err(CommandError::invalid_input("Empty recording"));
+    }
+    if audio.len() > MAX_AUDIO_BYTES {
+        return Err(CommandError::invalid_input(format!(
+            "Recording too long ({} MB max)",
+            MAX_AUDIO_BYTES / (1024 * 1024)
+        )));
+    }
+
+    let settings = load_settings().transcription;
+    let text = match settings.backend {
+        TranscriptionBackend::Api => transcribe_api(&settings, audio, &mime_type).await?,
+        TranscriptionBackend::Local => transcribe_local(&settings, audio, &mime_type).await?,
+    };
+    Ok(text.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extension() {
+        assert_eq!(extension("audio/webm;codecs=opus"), "webm");
+        assert_eq!(extension("audio/x-wav"), "wav");
+        assert_eq!(extension("audio/mp4"), "m4a");
+    }
+}
@@ -0,0 +1,134 @@
+//! Local transcription with whisper.cpp
+//!
+//! The model named by `local_model_path` is loaded on first use and kept
+//! for later recordings. whisper.cpp wants 16 kHz mono samples, so WAV
+//! recordings are mixed down and resampled first.
+
+use crate::settings::store::TranscriptionSettings;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use std::io::Cursor;
+use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters};
+
+const WHISPER_SAMPLE_RATE: u32 = 16_000;
+
+/// The loaded model and the path it came from
+static MODEL: Lazy<Mutex<Option<(String, WhisperContext)>>> = Lazy::new(|| Mutex::new(None));
+
+/// Mono samples in [-1, 1] from a WAV file, with its sample rate
+fn decode_wav(audio: &[u8]) -> Result<(Vec<f32>, u32), String> {
+    let reader =
+        hound::WavReader::new(Cursor::new(audio)).map_err(|e| format!("Not a WAV file: {}", e))?;
+    let spec = reader.spec();
+    let samples: Vec<f32> = match spec.sample_format {
+        hound::SampleFormat::Float => reader
+            .into_samples::<f32>()
+            .collect::<Result<_, _>>()
+            .map_err(|e| format!("Failed to read WAV: {}", e))?,
+        hound::SampleFormat::Int => {
+            let scale = (1i64 << (spec.bits_per_sample - 1)) as f32;
+            reader
+                .into_samples::<i32>()
+                .map(|s| s.map(|s| s as f32 / scale))
+                .collect::<Result<_, _>>()
+                .map_err(|e| format!("Failed to read WAV: {}", e))?
+        }
+    };
+    let channels = spec.channels.max(1) as usize;
+    let mono = samples
+        .chunks(channels)
+        .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32)
+        .collect();
+    Ok((mono, spec.sample_rate))
+}
+
+/// Linear resampling, plenty for speech
+fn resample(samples: &[f32], from_rate: u32, to_rate: u32) -> Vec<f32> {
+    if from_rate == to_rate || samples.is_empty() {
+        return samples.to_vec();
+    }
+    let ratio = from_rate as f64 / to_rate as f64;
+    let len = (samples.len() as f64 / ratio) as usize;
+    (0..len)
+        .map(|i| {
+            let pos = i as f64 * ratio;
+            let index = pos as usize;
+            let frac = (pos - index as f64) as f32;
+            let a = samples[index];
+            let b = samples.get(index + 1).copied().unwrap_or(a);
+            a + (b - a) * frac
+        })
+        .collect()
+}
+
+pub fn transcribe(
+    settings: &TranscriptionSettings,
+    audio: &[u8],
+    mime_type: &str,
+) -> Result<String, String> {
+    if !mime_type.contains("wav") {
+        return Err(format!(
+            "Local transcription takes WAV recordings, not {}",
+            mime_type
+        ));
+    }
+    if settings.local_model_path.is_empty() {
+        return Err("Set transcription.local_model_path to a whisper.cpp model".to_string());
+    }
+    let (samples, rate) = decode_wav(audio)?;
+    let samples = resample(&samples, rate, WHISPER_SAMPLE_RATE);
+
+    let mut model = MODEL.lock();
+    if model
+        .as_ref()
+        .map_or(true, |(path, _)| *path != settings.local_model_path)
+    {
+        let context = WhisperContext::new_with_params(
+            &settings.local_model_path,
+            WhisperContextParameters::default(),
+        )
+        .map_err(|e| format!("Failed to load {}: {}", settings.local_model_path, e))?;
+        *model = Some((settings.local_model_path.clone(), context));
+    }
+    let Some((_, context)) = model.as_ref() else {
+        return Err("Whisper model not loaded".to_string());
+    };
+
+    let mut state = context
+        .create_state()
+        .map_err(|e| format!("Failed to start whisper: {}", e))?;
+    let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
+    params.set_language((!settings.language.is_empty()).then_some(settings.language.as_str()));
+    params.set_print_progress(false);
+    params.set_print_realtime(false);
+    params.set_print_timestamps(false);
+    state
+        .full(params, &samples)
+        .map_err(|e| format!("Transcription failed: {}", e))?;
+
+    let segments = state
+        .full_n_segments()
+        .map_err(|e| format!("Transcription failed: {}", e))?;
+    let mut text = String::new();
+    for i in 0..segments {
+        let segment = state
+            .full_get_segment_text(i)
+            .map_err(|e| format!("Transcription failed: {}", e))?;
+        text.push_str(&segment);
+    }
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resample() {
+        let samples: Vec<f32> = (0..48).map(|i| i as f32).collect();
+        let down = resample(&samples, 48_000, 16_000);
+        assert_eq!(down.len(), 16);
+        assert_eq!(down[1], 3.0);
+        assert_eq!(resample(&samples, 16_000, 16_000), samples);
+    }
+}
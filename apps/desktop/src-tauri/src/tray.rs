@@ -0,0 +1,212 @@
+//! System tray
+//!
+//! The tray icon shows how many workers are running, and its menu lists the
+//! last few completed prompts (from `OrchestratorManager::status_summary`),
+//! refreshed every couple of seconds. Menu actions:
+//!
+//! - Pause all: cancels in-flight prompts and pauses running PRD sessions
+//! - Open last session (or a recent completion): focuses the main window and
+//!   emits "tray-open-session" for the frontend to switch to it
+//! - Quit: drains the workers first, like closing the window (see `shutdown`)
+
+use crate::events::{emit, TrayOpenSessionEvent};
+use crate::orchestrator::manager::StatusSummary;
+use crate::prd::types::PrdSessionStatus;
+use crate::shutdown;
+use crate::AppState;
+use std::time::Duration;
+use tauri::menu::{Menu, MenuItem, PredefinedMenuItem};
+use tauri::tray::{TrayIcon, TrayIconBuilder};
+use tauri::{AppHandle, Manager, Wry};
+
+const TRAY_ID: &str = "main";
+
+/// How often the tray is brought up to date
+const REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Completed prompts listed in the menu
+const RECENT_COMPLETIONS: usize = 5;
+
+/// Longest task shown for a completion
+const MAX_TASK_CHARS: usize = 40;
+
+const PAUSE_ALL: &str = "pause_all";
+const OPEN_LAST: &str = "open_last";
+const QUIT: &str = "quit";
+/// Prefix of the menu ids of recent completions
+const OPEN_PREFIX: &str = "open:";
+
+/// Add the tray icon and keep it up to date
+pub fn start(app: &AppHandle) -> tauri::Result<()> {
+    let summary = summary(app);
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .tooltip(tooltip(&summary))
+        .menu(&build_menu(app, &summary)?)
+        .on_menu_event(|app, event| handle_menu_event(app, event.id.as_ref()));
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+    let tray = builder.build(app)?;
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut last = summary;
+        let mut interval = tokio::time::interval(REFRESH_INTERVAL);
+        loop {
+            interval.tick().await;
+            let summary = summary(&app);
+            if summary == last {
+                continue;
+            }
+            if let Err(e) = refresh(&app, &tray, &summary) {
+                eprintln!("[Tray] {}", e);
+            }
+            last = summary;
+        }
+    });
+    Ok(())
+}
+
+fn summary(app: &AppHandle) -> StatusSummary {
+    app.state::<AppState>()
+        .orchestrator_manager
+        .lock()
+        .status_summary(RECENT_COMPLETIONS)
+}
+
+fn refresh(app: &AppHandle, tray: &TrayIcon, summary: &StatusSummary) -> tauri::Result<()> {
+    tray.set_menu(Some(build_menu(app, summary)?))?;
+    tray.set_tooltip(Some(tooltip(summary)))
+}
+
+fn tooltip(summary: &StatusSummary) -> String {
+    match summary.running_workers {
+        0 => "Crafter Code: idle".to_string(),
+        1 => "Crafter Code: 1 worker running".to_string(),
+        n => format!(
+            "Crafter Code: {} workers running in {} session(s)",
+            n, summary.active_sessions
+        ),
+    }
+}
+
+fn build_menu(app: &AppHandle, summary: &StatusSummary) -> tauri::Result<Menu<Wry>> {
+    let menu = Menu::new(app)?;
+
+    let status = match summary.running_workers {
+        0 => "No workers running".to_string(),
+        1 => "1 worker running".to_string(),
+        n => format!("{} workers running", n),
+    };
+    menu.append(&MenuItem::with_id(app, "status", status, false, None::<&str>)?)?;
+
+    if !summary.recent_completions.is_empty() {
+        menu.append(&PredefinedMenuItem::separator(app)?)?;
+        menu.append(&MenuItem::with_id(
+            app,
+            "recent",
+            "Recently completed",
+            false,
+            None::<&str>,
+        )?)?;
+        for (i, completion) in summary.recent_completions.iter().enumerate() {
+            menu.append(&MenuItem::with_id(
+                app,
+                // Indexed so repeats of a session keep distinct ids
+                format!("{}{}:{}", OPEN_PREFIX, i, completion.session_id),
+                format!("✓ {}", shorten(&completion.task)),
+                true,
+                None::<&str>,
+            )?)?;
+        }
+    }
+
+    menu.append(&PredefinedMenuItem::separator(app)?)?;
+    menu.append(&MenuItem::with_id(
+        app,
+        PAUSE_ALL,
+        "Pause All",
+        summary.running_workers > 0 || has_running_prd(app),
+        None::<&str>,
+    )?)?;
+    menu.append(&MenuItem::with_id(
+        app,
+        OPEN_LAST,
+        "Open Last Session",
+        summary.last_session_id.is_some(),
+        None::<&str>,
+    )?)?;
+    menu.append(&PredefinedMenuItem::separator(app)?)?;
+    menu.append(&MenuItem::with_id(app, QUIT, "Quit", true, None::<&str>)?)?;
+    Ok(menu)
+}
+
+fn handle_menu_event(app: &AppHandle, id: &str) {
+    match id {
+        PAUSE_ALL => pause_all(app),
+        OPEN_LAST => {
+            if let Some(session_id) = summary(app).last_session_id {
+                open_session(app, &session_id);
+            }
+        }
+        QUIT => {
+            // The splash in the main window shows the drain
+            show_main_window(app);
+            if !shutdown::hold_exit(app) {
+                app.exit(0);
+            }
+        }
+        _ => {
+            if let Some((_, session_id)) = id
+                .strip_prefix(OPEN_PREFIX)
+                .and_then(|rest| rest.split_once(':'))
+            {
+                open_session(app, session_id);
+            }
+        }
+    }
+}
+
+fn pause_all(app: &AppHandle) {
+    let (cancelled, paused) = app.state::<AppState>().pause_all();
+    eprintln!(
+        "[Tray] Cancelled {} prompt(s), paused {} PRD session(s)",
+        cancelled, paused
+    );
+}
+
+fn open_session(app: &AppHandle, session_id: &str) {
+    show_main_window(app);
+    emit(
+        app,
+        &TrayOpenSessionEvent {
+            session_id: session_id.to_string(),
+        },
+    );
+}
+
+fn show_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+}
+
+fn has_running_prd(app: &AppHandle) -> bool {
+    app.state::<AppState>()
+        .prd_manager
+        .list_sessions()
+        .iter()
+        .any(|s| s.status == PrdSessionStatus::Running)
+}
+
+fn shorten(task: &str) -> String {
+    let line = task.lines().next().unwrap_or("").trim();
+    if line.chars().count() > MAX_TASK_CHARS {
+        let short: String = line.chars().take(MAX_TASK_CHARS).collect();
+        format!("{}…", short.trim_end())
+    } else {
+        line.to_string()
+    }
+}
@@ -16,6 +16,7 @@ import {
   DollarSign,
   FileText,
   Loader2,
+  Moon,
   RefreshCw,
   Square,
 } from "lucide-react";
@@ -189,6 +190,8 @@ function StatusIcon({ status }: { status: WorkerStatus }) {
       return <AlertCircle className="size-4 text-destructive" />;
     case "cancelled":
       return <Square className="size-4 text-muted-foreground" />;
+    case "hibernated":
+      return <Moon className="size-4 text-muted-foreground" />;
     default:
       return null;
   }
@@ -60,12 +60,13 @@ export function InboxPanel({ sessionId, className }: InboxPanelProps) {
   // Fetch messages and workers
   const fetchData = useCallback(async () => {
     try {
-      const [workerList, count] = await Promise.all([
+      const [workerInfos, count] = await Promise.all([
         inboxGetWorkers(sessionId),
         // Get total unread across all workers (use first worker or "system")
         inboxCount(sessionId, "system", true).catch(() => 0),
       ]);
 
+      const workerList = workerInfos.map((worker) => worker.workerId);
       setWorkers(workerList);
       setUnreadCount(count);
 
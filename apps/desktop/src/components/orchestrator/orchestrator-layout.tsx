@@ -76,7 +76,7 @@ export function OrchestratorLayout({ className }: OrchestratorLayoutProps) {
           | "failed"
           | "cancelled",
         costUsd: event.cost ?? 0,
-        errorMessage: event.error,
+        errorMessage: event.error ?? undefined,
       });
 
       // Only add error messages - completion is shown via status indicator
@@ -25,6 +25,7 @@ import {
 } from "lucide-react";
 import { Streamdown } from "streamdown";
 
+import { needsReconnect } from "@/lib/ipc/errors";
 import {
   cancelWorker,
   type ImageAttachment,
@@ -379,11 +380,7 @@ export function SessionCard({
               const errorStr = String(error);
               console.error("Failed to set mode:", errorStr);
 
-              const isDeadSession =
-                errorStr.includes("No active worker for session") ||
-                errorStr.includes("not found");
-
-              if (isDeadSession) {
+              if (needsReconnect(error)) {
                 console.log("[Frontend] Session dead, reconnecting before mode change...");
                 const agentId = session.agentType || "claude";
                 const cwd = session.cwd || "/";
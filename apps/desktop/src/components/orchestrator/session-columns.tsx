@@ -13,6 +13,7 @@ import {
 import { ArrowUp, ChevronDown, FileText, ImagePlus, PanelLeft, X } from "lucide-react";
 import { homeDir } from "@tauri-apps/api/path";
 
+import { needsReconnect } from "@/lib/ipc/errors";
 import {
   type ImageAttachment,
   type AgentConfig,
@@ -99,11 +100,7 @@ export function SessionColumns({ className, showSidebar, onToggleSidebar }: Sess
         const errorStr = String(error);
         console.error("Follow-up failed:", errorStr);
 
-        const isDeadSession =
-          errorStr.includes("No active worker for session") ||
-          errorStr.includes("not found");
-
-        if (isDeadSession) {
+        if (needsReconnect(error)) {
           console.log("[Frontend] Session/worker dead, attempting to reconnect...");
 
           const agentId = originalSession.agentType || "claude";
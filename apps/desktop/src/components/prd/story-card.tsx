@@ -14,6 +14,8 @@ import {
   Cpu,
   Gauge,
   RefreshCw,
+  SkipForward,
+  PauseCircle,
 } from "lucide-react";
 
 import { cn } from "@/lib/utils";
@@ -68,6 +70,18 @@ const statusConfig: Record<
     bgColor: "bg-amber-500/10",
     label: "Blocked",
   },
+  skipped: {
+    icon: SkipForward,
+    color: "text-muted-foreground",
+    bgColor: "bg-muted",
+    label: "Skipped",
+  },
+  paused: {
+    icon: PauseCircle,
+    color: "text-amber-500",
+    bgColor: "bg-amber-500/10",
+    label: "Paused",
+  },
 };
 
 const modelConfig: Record<ModelId, { color: string; label: string }> = {
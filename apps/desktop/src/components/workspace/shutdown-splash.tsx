@@ -0,0 +1,40 @@
+"use client";
+
+import { useEffect, useState } from "react";
+
+import { Loader2 } from "lucide-react";
+
+import {
+  onShutdownProgress,
+  type ShutdownProgressEvent,
+} from "@/lib/ipc/orchestrator";
+
+// Covers the window while workers are stopped after the app was asked to quit
+export function ShutdownSplash() {
+  const [progress, setProgress] = useState<ShutdownProgressEvent | null>(null);
+
+  useEffect(() => {
+    const unsubscribe = onShutdownProgress(setProgress);
+    return () => {
+      unsubscribe.then((fn) => fn());
+    };
+  }, []);
+
+  if (!progress) return null;
+
+  return (
+    <div className="fixed inset-0 z-50 flex items-center justify-center bg-background/95">
+      <div className="flex flex-col items-center gap-3 text-center">
+        <Loader2 className="size-6 animate-spin text-muted-foreground" />
+        <p className="font-medium">Shutting down</p>
+        <p className="text-sm text-muted-foreground">{progress.message}</p>
+        {progress.running_agents > 0 && (
+          <p className="text-xs text-muted-foreground">
+            {progress.running_agents} agent
+            {progress.running_agents === 1 ? "" : "s"} still running
+          </p>
+        )}
+      </div>
+    </div>
+  );
+}
@@ -3,9 +3,16 @@
 import { OrchestratorLayout } from "@/components/orchestrator/orchestrator-layout";
 import { useTerminalEvents } from "@/hooks/use-terminal-events";
 
+import { ShutdownSplash } from "./shutdown-splash";
+
 export function Workspace() {
   // Hook to track ACP terminal events globally
   useTerminalEvents();
 
-  return <OrchestratorLayout />;
+  return (
+    <>
+      <OrchestratorLayout />
+      <ShutdownSplash />
+    </>
+  );
 }
@@ -0,0 +1,37 @@
+import { invoke } from "@tauri-apps/api/core";
+
+// ============================================================================
+// API Server Types
+// ============================================================================
+
+export interface ApiServerStatus {
+  running: boolean;
+  /** Bound address, e.g. "127.0.0.1:7420" */
+  address: string | null;
+}
+
+// ============================================================================
+// API Server Commands
+// ============================================================================
+
+/**
+ * Start the embedded API server using the saved settings
+ */
+export async function startApiServer(): Promise<ApiServerStatus> {
+  return invoke<ApiServerStatus>("start_api_server");
+}
+
+export async function stopApiServer(): Promise<ApiServerStatus> {
+  return invoke<ApiServerStatus>("stop_api_server");
+}
+
+export async function getApiServerStatus(): Promise<ApiServerStatus> {
+  return invoke<ApiServerStatus>("get_api_server_status");
+}
+
+/**
+ * Generate a new API token, invalidating the old one
+ */
+export async function rotateApiToken(): Promise<string> {
+  return invoke<string>("rotate_api_token");
+}
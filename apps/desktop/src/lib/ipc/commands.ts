@@ -1,6 +1,17 @@
-import { invoke } from "@tauri-apps/api/core";
+import { Channel, invoke } from "@tauri-apps/api/core";
 import { listen, type UnlistenFn } from "@tauri-apps/api/event";
 
+import {
+  type DevServerDetectedEvent,
+  type FsChange,
+  type FsChangedEvent,
+  listenVersioned,
+  type ServiceOutputEvent,
+  type ServiceStatus,
+  type ServiceStatusEvent,
+} from "./events";
+import type { FilePatch } from "./orchestrator";
+
 // File system types
 export interface FileEntry {
   name: string;
@@ -12,11 +23,107 @@ export interface FileEntry {
   modified?: number;
 }
 
+export interface Toolchain {
+  language: string;
+  // "cargo", "npm", "pnpm", "yarn", "bun", "uv", "poetry", "pip" or "go"
+  tool: string;
+  manifest: string;
+}
+
+export interface ProjectScript {
+  name: string;
+  // Shell command that runs it
+  command: string;
+  source: string;
+}
+
+export interface GitRemote {
+  name: string;
+  url: string;
+}
+
+export interface GitMetadata {
+  branch?: string;
+  head?: string;
+  upstream?: string;
+  ahead: number;
+  behind: number;
+  dirty: boolean;
+  changed_files: number;
+  remotes: GitRemote[];
+}
+
+// An agent instructions file in a project root
+export interface ProjectContextFile {
+  name: string;
+  size: number;
+  // Agents that read the file themselves
+  native_agents: string[];
+}
+
 export interface ProjectInfo {
   name: string;
   path: string;
   git_branch?: string;
   git_status?: string;
+  // Project .env files found in the root (see settings.dotenv.files)
+  env_files: string[];
+  // AGENTS.md, CLAUDE.md and friends found in the root
+  context_files: ProjectContextFile[];
+  language?: string;
+  toolchains: Toolchain[];
+  frameworks: string[];
+  scripts: ProjectScript[];
+  build_command?: string;
+  test_command?: string;
+  // Workspace member globs of a monorepo
+  workspaces: string[];
+  git?: GitMetadata;
+}
+
+// A variable a project .env file would contribute
+export interface EnvVarPreview {
+  name: string;
+  // The value, or a mask for secrets
+  value: string;
+  // File the value comes from (the last file defining it)
+  file: string;
+  masked: boolean;
+  // Already set in the app's environment, so it won't be injected
+  overridden: boolean;
+}
+
+export interface TerminalProfile {
+  // Shell name ("zsh", "bash", "fish", "pwsh") or absolute path
+  shell?: string;
+  env?: Record<string, string>;
+  startup_commands?: string[];
+}
+
+export interface TerminalOptions extends TerminalProfile {
+  session_id?: string;
+  name?: string;
+  keep_alive?: boolean;
+  // Named profile from {project}/.crafter-code/terminal.json
+  profile?: string;
+}
+
+export interface TerminalInfo {
+  id: string;
+  session_id: string | null;
+  name: string | null;
+  cwd: string | null;
+  shell: string;
+  keep_alive: boolean;
+  cols: number;
+  rows: number;
+  created_at: number;
+  exited: boolean;
+}
+
+export interface TerminalAttachment {
+  info: TerminalInfo;
+  scrollback: string;
 }
 
 // Terminal commands
@@ -24,8 +131,34 @@ export async function spawnTerminal(
   cols: number,
   rows: number,
   cwd?: string,
+  options?: TerminalOptions,
 ): Promise<string> {
-  return invoke<string>("spawn_terminal", { cols, rows, cwd });
+  return invoke<string>("spawn_terminal", { cols, rows, cwd, options });
+}
+
+export async function listTerminals(
+  sessionId?: string,
+): Promise<TerminalInfo[]> {
+  return invoke<TerminalInfo[]>("list_terminals", { sessionId });
+}
+
+// Get scrollback and metadata to reattach a terminal in another view
+export async function attachTerminal(id: string): Promise<TerminalAttachment> {
+  return invoke<TerminalAttachment>("attach_terminal", { id });
+}
+
+export async function setTerminalKeepAlive(
+  id: string,
+  keepAlive: boolean,
+): Promise<void> {
+  return invoke<void>("set_terminal_keep_alive", { id, keepAlive });
+}
+
+// Kill a session's terminals (keep-alive terminals survive)
+export async function closeSessionTerminals(
+  sessionId: string,
+): Promise<string[]> {
+  return invoke<string[]>("close_session_terminals", { sessionId });
 }
 
 export async function writeTerminal(id: string, data: string): Promise<void> {
@@ -44,6 +177,18 @@ export async function killTerminal(id: string): Promise<void> {
   return invoke<void>("kill_terminal", { id });
 }
 
+export type TerminalSignal = "SIGINT" | "SIGTERM";
+
+// Interrupt (like Ctrl+C) or terminate a terminal without killing it
+// outright; works for agent-created terminals too. On Windows both send
+// CTRL_BREAK.
+export async function signalTerminal(
+  terminalId: string,
+  signal: TerminalSignal,
+): Promise<void> {
+  return invoke<void>("signal_terminal", { terminalId, signal });
+}
+
 // Terminal output listener
 export function onTerminalOutput(
   terminalId: string,
@@ -54,15 +199,436 @@ export function onTerminalOutput(
   });
 }
 
+// Terminal exit listener
+export function onTerminalExit(
+  terminalId: string,
+  callback: () => void,
+): Promise<UnlistenFn> {
+  return listen<string>(`pty-exit-${terminalId}`, () => {
+    callback();
+  });
+}
+
+// Service terminals: long-running commands (dev servers, watchers) kept up
+// for a session and restarted with backoff when they crash. Declared under
+// "services" in .crafter-code/terminal.json or started by agents with
+// `swarm service start`.
+export interface ServiceInfo {
+  id: string;
+  session_id: string;
+  name: string;
+  command: string;
+  cwd: string;
+  status: ServiceStatus;
+  pid: number | null;
+  restarts: number;
+  last_exit_code: number | null;
+  started_at: number | null;
+}
+
+export interface ServiceOutput {
+  info: ServiceInfo;
+  output: string;
+  truncated: boolean;
+}
+
+// Without a command the service must be declared in the project config
+export async function startService(
+  sessionId: string,
+  name: string,
+  projectDir: string,
+  command?: string,
+): Promise<ServiceInfo> {
+  return invoke<ServiceInfo>("start_service", {
+    sessionId,
+    name,
+    projectDir,
+    command,
+  });
+}
+
+export async function stopService(id: string): Promise<ServiceInfo> {
+  return invoke<ServiceInfo>("stop_service", { id });
+}
+
+export async function restartService(id: string): Promise<ServiceInfo> {
+  return invoke<ServiceInfo>("restart_service", { id });
+}
+
+export async function listServices(
+  sessionId?: string,
+): Promise<ServiceInfo[]> {
+  return invoke<ServiceInfo[]>("list_services", { sessionId });
+}
+
+export async function getServiceOutput(id: string): Promise<ServiceOutput> {
+  return invoke<ServiceOutput>("get_service_output", { id });
+}
+
+export function onServiceStatus(
+  callback: (event: ServiceStatusEvent) => void,
+): Promise<UnlistenFn> {
+  return listenVersioned<ServiceStatusEvent>("service-status", callback);
+}
+
+export function onServiceOutput(
+  serviceId: string,
+  callback: (line: string) => void,
+): Promise<UnlistenFn> {
+  return listenVersioned<ServiceOutputEvent>(
+    `service-output-${serviceId}`,
+    (payload) => {
+      callback(payload.line);
+    },
+  );
+}
+
+// Local servers found in agent and service terminal output
+export interface DetectedServer {
+  url: string;
+  port: number;
+  terminal_id: string;
+  worker_id: string | null;
+  detected_at: number;
+  // Whether the port accepted a connection when listed
+  alive: boolean;
+}
+
+export async function listDetectedServers(
+  sessionId: string,
+): Promise<DetectedServer[]> {
+  return invoke<DetectedServer[]>("list_detected_servers", { sessionId });
+}
+
+export function onDevServerDetected(
+  callback: (event: DevServerDetectedEvent) => void,
+): Promise<UnlistenFn> {
+  return listenVersioned<DevServerDetectedEvent>(
+    "dev-server-detected",
+    callback,
+  );
+}
+
+// Terminal recording (asciicast v2)
+export interface RecordingInfo {
+  terminal_id: string;
+  session_id: string | null;
+  path: string;
+  started_at: number;
+  event_count: number;
+}
+
+export async function startTerminalRecording(
+  id: string,
+): Promise<RecordingInfo> {
+  return invoke<RecordingInfo>("start_terminal_recording", { id });
+}
+
+export async function stopTerminalRecording(
+  id: string,
+): Promise<RecordingInfo> {
+  return invoke<RecordingInfo>("stop_terminal_recording", { id });
+}
+
+// Copy a .cast file to a file or directory; returns the written path
+export async function exportRecording(
+  path: string,
+  destination: string,
+): Promise<string> {
+  return invoke<string>("export_recording", { path, destination });
+}
+
 // File system commands
 export async function readDirectory(path: string): Promise<FileEntry[]> {
   return invoke<FileEntry[]>("read_directory", { path });
 }
 
+export type GitFileStatus =
+  | "modified"
+  | "added"
+  | "deleted"
+  | "renamed"
+  | "untracked"
+  | "ignored"
+  | "conflicted";
+
+export interface TreeNode {
+  name: string;
+  path: string;
+  is_dir: boolean;
+  is_file: boolean;
+  is_hidden: boolean;
+  size: number | null;
+  modified: number | null;
+  git_status: GitFileStatus | null;
+  // null for files and unexpanded directories
+  children: TreeNode[] | null;
+  // Pass to expandDirectoryTree to load an unexpanded directory
+  expand_token: string | null;
+}
+
+export interface GitStatusEntry {
+  path: string;
+  status: GitFileStatus;
+}
+
+// Directory tree (default depth 2), skipping git-ignored entries and ignoreGlobs
+export async function readDirectoryTree(
+  path: string,
+  depth?: number,
+  ignoreGlobs?: string[],
+): Promise<TreeNode> {
+  return invoke<TreeNode>("read_directory_tree", { path, depth, ignoreGlobs });
+}
+
+export async function expandDirectoryTree(
+  token: string,
+  depth?: number,
+): Promise<TreeNode> {
+  return invoke<TreeNode>("expand_directory_tree", { token, depth });
+}
+
+// Changed and untracked files in the repository containing path
+export async function getGitStatus(path: string): Promise<GitStatusEntry[]> {
+  return invoke<GitStatusEntry[]>("get_git_status", { path });
+}
+
+export type { FsChange, FsChangedEvent };
+
+// Watch a workspace; batches of changes arrive as fs-changed events
+export async function watchWorkspace(
+  path: string,
+  ignoreGlobs?: string[],
+): Promise<void> {
+  return invoke("watch_workspace", { path, ignoreGlobs });
+}
+
+export async function unwatchWorkspace(path: string): Promise<boolean> {
+  return invoke<boolean>("unwatch_workspace", { path });
+}
+
+export function onFsChanged(
+  callback: (event: FsChangedEvent) => void,
+): Promise<UnlistenFn> {
+  return listenVersioned<FsChangedEvent>("fs-changed", callback);
+}
+
 export async function getProjectInfo(path: string): Promise<ProjectInfo> {
   return invoke<ProjectInfo>("get_project_info", { path });
 }
 
-export async function readFileContent(path: string): Promise<string> {
-  return invoke<string>("read_file_content", { path });
+export async function listProjectContextFiles(
+  path: string,
+): Promise<ProjectContextFile[]> {
+  return invoke<ProjectContextFile[]>("list_project_context_files", { path });
+}
+
+export type Editor = "vscode" | "zed" | "jetbrains" | "env";
+
+export interface EditorInfo {
+  editor: Editor;
+  name: string;
+  // The program that would be run (null when not installed)
+  program: string | null;
+}
+
+export interface OpenedEditor {
+  editor: Editor;
+  command: string;
+  // Integrated terminal running a terminal editor from $EDITOR
+  terminal_id: string | null;
+}
+
+// Open a file at a 1-based line and column. Without an editor, the
+// project's preference, the global one, or the first installed is used.
+export async function openInEditor(
+  path: string,
+  line?: number,
+  column?: number,
+  editor?: Editor,
+): Promise<OpenedEditor> {
+  return invoke<OpenedEditor>("open_in_editor", { path, line, column, editor });
+}
+
+export async function detectEditors(): Promise<EditorInfo[]> {
+  return invoke<EditorInfo[]>("detect_editors");
+}
+
+// Set (or with null, clear) the editor used for files in a project
+export async function setProjectEditor(
+  project: string,
+  editor: Editor | null,
+): Promise<void> {
+  return invoke("set_project_editor", { project, editor });
+}
+
+// A content-addressed copy of a project's files (for projects outside git)
+export interface SnapshotSummary {
+  id: string;
+  root: string;
+  label: string | null;
+  session_id: string | null;
+  created_at: number;
+  files: number;
+  bytes: number;
+  // Files left out for the size caps
+  skipped: number;
+}
+
+export interface SnapshotDiff {
+  snapshot: SnapshotSummary;
+  files: FilePatch[];
+  // Binary files that changed (relative paths)
+  binary: string[];
+  additions: number;
+  deletions: number;
+}
+
+export interface RestoreResult {
+  restored: string[];
+  removed: string[];
+  // Snapshot taken just before restoring
+  backup_id: string;
+}
+
+export async function createSnapshot(
+  path: string,
+  label?: string,
+): Promise<SnapshotSummary> {
+  return invoke<SnapshotSummary>("create_snapshot", { path, label });
+}
+
+export async function listSnapshots(path: string): Promise<SnapshotSummary[]> {
+  return invoke<SnapshotSummary[]>("list_snapshots", { path });
+}
+
+export async function diffAgainstSnapshot(
+  snapshotId: string,
+): Promise<SnapshotDiff> {
+  return invoke<SnapshotDiff>("diff_against_snapshot", { snapshotId });
+}
+
+// Restore a project, or only some of its files (relative paths)
+export async function restoreSnapshot(
+  snapshotId: string,
+  paths?: string[],
+): Promise<RestoreResult> {
+  return invoke<RestoreResult>("restore_snapshot", { snapshotId, paths });
+}
+
+// A session's directory for temporary files (~/.crafter-code/scratch)
+export interface ScratchDir {
+  path: string;
+  bytes: number;
+  /** 0 = no limit */
+  max_bytes: number;
+}
+
+export async function getScratchDir(sessionId: string): Promise<ScratchDir> {
+  return invoke<ScratchDir>("get_scratch_dir", { sessionId });
+}
+
+// Variables the project's .env files would contribute (secrets masked)
+export async function previewProjectEnv(
+  path: string,
+): Promise<EnvVarPreview[]> {
+  return invoke<EnvVarPreview[]>("preview_project_env", { path });
+}
+
+// Ranged file reads. Line ranges take precedence over byte ranges.
+export interface ReadRange {
+  offset?: number;
+  length?: number;
+  start_line?: number;
+  line_count?: number;
+}
+
+export type FileEncoding =
+  | "utf-8"
+  | "utf-8-lossy"
+  | "utf-16le"
+  | "utf-16be"
+  | "binary";
+
+export interface FileContent {
+  content: string;
+  encoding: FileEncoding;
+  total_size: number;
+  offset: number;
+  end_offset: number;
+  truncated: boolean;
+}
+
+export interface FileChunk {
+  offset: number;
+  data: string;
+  eof: boolean;
+}
+
+export async function readFileContent(
+  path: string,
+  range?: ReadRange,
+): Promise<FileContent> {
+  return invoke<FileContent>("read_file_content", { path, range });
+}
+
+// Stream a file in chunks; with follow, keep tailing until stopFileStream
+export async function streamFile(
+  path: string,
+  onChunk: (chunk: FileChunk) => void,
+  options?: { offset?: number; follow?: boolean },
+): Promise<string> {
+  const channel = new Channel<FileChunk>();
+  channel.onmessage = onChunk;
+  return invoke<string>("stream_file", {
+    path,
+    offset: options?.offset,
+    follow: options?.follow,
+    onChunk: channel,
+  });
+}
+
+export async function stopFileStream(streamId: string): Promise<boolean> {
+  return invoke<boolean>("stop_file_stream", { streamId });
+}
+
+// Project search. Files git ignores, hidden files and binary files are
+// skipped; queries match case-insensitively unless they have an uppercase
+// letter.
+export interface SearchOptions {
+  /** Treat the query as a regular expression rather than literal text */
+  regex?: boolean;
+  /** Only files matching these globs, relative to the project (`!` excludes) */
+  globs?: string[];
+  /** Stop after this many matching lines (500 by default) */
+  max_results?: number;
+}
+
+export interface SearchMatch {
+  path: string;
+  relative_path: string;
+  /** 1-based */
+  line_number: number;
+  line: string;
+  /** Character ranges [start, end) of the matches in `line` */
+  ranges: { start: number; end: number }[];
+}
+
+export interface SearchResults {
+  matches: SearchMatch[];
+  files_searched: number;
+  /** Stopped at max_results with more matches left */
+  truncated: boolean;
+}
+
+export async function searchInProject(
+  projectDir: string,
+  query: string,
+  options?: SearchOptions,
+): Promise<SearchResults> {
+  return invoke<SearchResults>("search_in_project", {
+    projectDir,
+    query,
+    options,
+  });
 }
@@ -0,0 +1,90 @@
+import { type InvokeArgs, invoke as tauriInvoke } from "@tauri-apps/api/core";
+
+// ============================================================================
+// Command Errors
+// ============================================================================
+
+export type ErrorCode =
+  | "not_found"
+  | "invalid_input"
+  | "agent_unavailable"
+  | "auth_required"
+  | "trust_required"
+  | "worker_unavailable"
+  | "agent_failed"
+  | "limit_reached"
+  | "budget_exceeded"
+  | "cancelled"
+  | "io"
+  | "internal";
+
+interface RawCommandError {
+  code: ErrorCode;
+  message: string;
+  details?: Record<string, unknown>;
+}
+
+// Prefix of the plain-string trust errors from commands that don't return a
+// structured error
+const TRUST_REQUIRED_PREFIX = "trust-required:";
+
+/** Error thrown by `invoke` for a failed backend command */
+export class CommandError extends Error {
+  readonly code: ErrorCode;
+  readonly details?: Record<string, unknown>;
+
+  constructor(code: ErrorCode, message: string, details?: Record<string, unknown>) {
+    super(message);
+    this.name = "CommandError";
+    this.code = code;
+    this.details = details;
+  }
+
+  // Keep `String(error)` the bare message, as it was for string errors
+  override toString(): string {
+    return this.message;
+  }
+}
+
+function isRawCommandError(error: unknown): error is RawCommandError {
+  return (
+    typeof error === "object" &&
+    error !== null &&
+    typeof (error as RawCommandError).code === "string" &&
+    typeof (error as RawCommandError).message === "string"
+  );
+}
+
+/** Normalize anything a command rejected with into a `CommandError` */
+export function toCommandError(error: unknown): CommandError {
+  if (error instanceof CommandError) return error;
+  if (isRawCommandError(error)) {
+    return new CommandError(error.code, error.message, error.details);
+  }
+  const message = error instanceof Error ? error.message : String(error);
+  const code = message.startsWith(TRUST_REQUIRED_PREFIX) ? "trust_required" : "internal";
+  return new CommandError(code, message);
+}
+
+/** `invoke` that rejects with a `CommandError` */
+export async function invoke<T>(cmd: string, args?: InvokeArgs): Promise<T> {
+  try {
+    return await tauriInvoke<T>(cmd, args);
+  } catch (error) {
+    throw toCommandError(error);
+  }
+}
+
+export function hasErrorCode(error: unknown, ...codes: ErrorCode[]): boolean {
+  return codes.includes(toCommandError(error).code);
+}
+
+/** The session has no live worker (or isn't loaded); reconnect, then retry */
+export function needsReconnect(error: unknown): boolean {
+  return hasErrorCode(error, "worker_unavailable", "not_found");
+}
+
+/** The same call may succeed after a reconnect or a short wait */
+export function isRetryable(error: unknown): boolean {
+  return hasErrorCode(error, "worker_unavailable", "limit_reached", "io");
+}
@@ -0,0 +1,144 @@
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
+
+// Payload types for the worker and job events, generated from the catalog in
+// src-tauri/src/events.rs (run `cargo test` in src-tauri to regenerate)
+export type { AcceptanceCriterion } from "./generated/AcceptanceCriterion";
+export type { AgentAuthStatus } from "./generated/AgentAuthStatus";
+export type {
+  AgentLoginFinishedEvent,
+} from "./generated/AgentLoginFinishedEvent";
+export type { AuthMethodInfo } from "./generated/AuthMethodInfo";
+export type { BudgetPeriod } from "./generated/BudgetPeriod";
+export type { BuildError } from "./generated/BuildError";
+export type { BuildResult } from "./generated/BuildResult";
+export type { BuildResultEvent } from "./generated/BuildResultEvent";
+export type { BuildSummary } from "./generated/BuildSummary";
+export type { BuildTool } from "./generated/BuildTool";
+export type { CheckLoopEvent } from "./generated/CheckLoopEvent";
+export type { CheckRun } from "./generated/CheckRun";
+export type { ConnectionState } from "./generated/ConnectionState";
+export type { ContextLevel } from "./generated/ContextLevel";
+export type { ContextUsage } from "./generated/ContextUsage";
+export type { ContextWarningEvent } from "./generated/ContextWarningEvent";
+export type { CriterionResult } from "./generated/CriterionResult";
+export type { CriterionStatus } from "./generated/CriterionStatus";
+export type { CriterionType } from "./generated/CriterionType";
+export type {
+  DevServerDetectedEvent,
+} from "./generated/DevServerDetectedEvent";
+export type { FsChange } from "./generated/FsChange";
+export type { FsChangeKind } from "./generated/FsChangeKind";
+export type { FsChangedEvent } from "./generated/FsChangedEvent";
+export type {
+  GlobalBudgetExceededEvent,
+} from "./generated/GlobalBudgetExceededEvent";
+export type { GuardAction } from "./generated/GuardAction";
+export type { GuardTarget } from "./generated/GuardTarget";
+export type { GuardTriggeredEvent } from "./generated/GuardTriggeredEvent";
+export type { HookOutputEvent } from "./generated/HookOutputEvent";
+export type { HookRun } from "./generated/HookRun";
+export type { InboxPushEvent } from "./generated/InboxPushEvent";
+export type { JobProgressEvent } from "./generated/JobProgressEvent";
+export type { JobRunStatus } from "./generated/JobRunStatus";
+export type { JobStatus } from "./generated/JobStatus";
+export type { LimitLevel } from "./generated/LimitLevel";
+export type { LimitResource } from "./generated/LimitResource";
+export type { PermissionOption } from "./generated/PermissionOption";
+export type { PlanEntry } from "./generated/PlanEntry";
+export type { PlanImportedEvent } from "./generated/PlanImportedEvent";
+export type { PrdUpdate } from "./generated/PrdUpdate";
+export type { PrdUpdateEvent } from "./generated/PrdUpdateEvent";
+export type { PreviewRequestEvent } from "./generated/PreviewRequestEvent";
+export type {
+  RateLimitStatusEvent,
+} from "./generated/RateLimitStatusEvent";
+export type {
+  RemoteConnectionStatus,
+} from "./generated/RemoteConnectionStatus";
+export type { RemoteHostStatusEvent } from "./generated/RemoteHostStatusEvent";
+export type { ResealFailure } from "./generated/ResealFailure";
+export type { ScheduledJob } from "./generated/ScheduledJob";
+export type {
+  ScheduledJobUpdateEvent,
+} from "./generated/ScheduledJobUpdateEvent";
+export type { ServiceOutputEvent } from "./generated/ServiceOutputEvent";
+export type { ServiceStatus } from "./generated/ServiceStatus";
+export type { ServiceStatusEvent } from "./generated/ServiceStatusEvent";
+export type { SessionCompactedEvent } from "./generated/SessionCompactedEvent";
+export type { SessionCriteriaEvent } from "./generated/SessionCriteriaEvent";
+export type { SessionDequeuedEvent } from "./generated/SessionDequeuedEvent";
+export type {
+  SessionEncryptionEvent,
+} from "./generated/SessionEncryptionEvent";
+export type { SessionForkedEvent } from "./generated/SessionForkedEvent";
+export type {
+  SessionHistoryTruncatedEvent,
+} from "./generated/SessionHistoryTruncatedEvent";
+export type { SessionQueuedEvent } from "./generated/SessionQueuedEvent";
+export type {
+  SessionTitleUpdatedEvent,
+} from "./generated/SessionTitleUpdatedEvent";
+export type { ShutdownProgressEvent } from "./generated/ShutdownProgressEvent";
+export type { ShutdownStage } from "./generated/ShutdownStage";
+export type {
+  SlashCommandResultEvent,
+} from "./generated/SlashCommandResultEvent";
+export type { SlowToolEvent } from "./generated/SlowToolEvent";
+export type { StreamProgress } from "./generated/StreamProgress";
+export type {
+  TaskBoardChangedEvent,
+} from "./generated/TaskBoardChangedEvent";
+export type { TaskChange } from "./generated/TaskChange";
+export type { TaskColumns } from "./generated/TaskColumns";
+export type { TokenUsage } from "./generated/TokenUsage";
+export type { ToolCallContent } from "./generated/ToolCallContent";
+export type { TransientError } from "./generated/TransientError";
+export type { TrayOpenSessionEvent } from "./generated/TrayOpenSessionEvent";
+export type { WorkerAgentLogEvent } from "./generated/WorkerAgentLogEvent";
+export type {
+  WorkerAuthenticatedEvent,
+} from "./generated/WorkerAuthenticatedEvent";
+export type { WorkerCommandsEvent } from "./generated/WorkerCommandsEvent";
+export type { WorkerEvent } from "./generated/WorkerEvent";
+export type {
+  WorkerModeChangeEvent,
+} from "./generated/WorkerModeChangeEvent";
+export type { WorkerModeEvent } from "./generated/WorkerModeEvent";
+export type { WorkerPermissionEvent } from "./generated/WorkerPermissionEvent";
+export type { WorkerProgressEvent } from "./generated/WorkerProgressEvent";
+export type {
+  WorkerResourceLimitEvent,
+} from "./generated/WorkerResourceLimitEvent";
+export type { WorkerRetryEvent } from "./generated/WorkerRetryEvent";
+export type { WorkerStatus } from "./generated/WorkerStatus";
+export type { WorkerStatusChange } from "./generated/WorkerStatusChange";
+export type { WorkerStreamEvent } from "./generated/WorkerStreamEvent";
+export type { WorkerToolEvent } from "./generated/WorkerToolEvent";
+export type {
+  WorkerUserMessageEvent,
+} from "./generated/WorkerUserMessageEvent";
+
+// Must match EVENT_SCHEMA_VERSION in src-tauri/src/events.rs
+export const EVENT_SCHEMA_VERSION = 1;
+
+// Catalog payloads carry the schema version they were emitted with
+export type Versioned<T> = T & { version: number };
+
+// Newest unknown schema version already warned about
+let warnedVersion = EVENT_SCHEMA_VERSION;
+
+/** `listen` for a catalog event; warns once if the backend's schema is newer */
+export function listenVersioned<T>(
+  event: string,
+  handler: (payload: Versioned<T>) => void,
+): Promise<UnlistenFn> {
+  return listen<Versioned<T>>(event, ({ payload }) => {
+    if (payload.version > warnedVersion) {
+      warnedVersion = payload.version;
+      console.warn(
+        `[Events] ${event} has schema version ${payload.version}, expected ${EVENT_SCHEMA_VERSION}`,
+      );
+    }
+    handler(payload);
+  });
+}
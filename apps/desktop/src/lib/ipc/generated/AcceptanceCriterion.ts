@@ -0,0 +1,31 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CriterionType } from "./CriterionType";
+
+/**
+ * Acceptance criterion for a story
+ */
+export type AcceptanceCriterion = { type: CriterionType, 
+/**
+ * For type: "test" - command to run
+ */
+command: string | null, 
+/**
+ * For type: "file_exists" - path to check
+ */
+path: string | null, 
+/**
+ * For type: "pattern" - file to search in
+ */
+file: string | null, 
+/**
+ * For type: "pattern" - regex pattern to match
+ */
+pattern: string | null, 
+/**
+ * For type: "custom" - script to execute
+ */
+script: string | null, 
+/**
+ * Human-readable description
+ */
+description: string | null, };
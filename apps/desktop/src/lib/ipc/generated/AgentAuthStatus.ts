@@ -0,0 +1,25 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AuthMethodInfo } from "./AuthMethodInfo";
+
+export type AgentAuthStatus = { agent_id: string, 
+/**
+ * The handshake succeeded and either no auth is advertised or
+ * credentials were found
+ */
+authenticated: boolean, 
+/**
+ * Where the credentials were found
+ */
+credential_source: string | null, 
+/**
+ * Auth methods from the handshake
+ */
+auth_methods: Array<AuthMethodInfo>, 
+/**
+ * Command `start_agent_login` runs, if the agent has one
+ */
+login_command: string | null, 
+/**
+ * Why the handshake failed
+ */
+error: string | null, };
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AgentAuthStatus } from "./AgentAuthStatus";
+
+/**
+ * A `start_agent_login` terminal exited; the agent's auth was checked again
+ */
+export type AgentLoginFinishedEvent = { agent_id: string, terminal_id: string, status: AgentAuthStatus | null, 
+/**
+ * Why the status couldn't be checked
+ */
+error: string | null, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A compiler/type-checker error location
+ */
+export type BuildError = { file: string | null, line: number | null, column: number | null, code: string | null, message: string, };
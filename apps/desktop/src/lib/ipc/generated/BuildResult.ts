@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BuildError } from "./BuildError";
+import type { BuildTool } from "./BuildTool";
+
+/**
+ * A build result recorded for a session
+ */
+export type BuildResult = { id: string, worker_id: string, 
+/**
+ * Terminal or tool call id the output came from
+ */
+source_id: string, timestamp: number, tool: BuildTool, success: boolean, passed: number, failed: number, skipped: number, failing_tests: Array<string>, errors: Array<BuildError>, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BuildResult } from "./BuildResult";
+
+/**
+ * Test or build output was parsed from a command the agent ran
+ */
+export type BuildResultEvent = { session_id: string, result: BuildResult, };
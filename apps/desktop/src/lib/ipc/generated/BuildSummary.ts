@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BuildError } from "./BuildError";
+import type { BuildTool } from "./BuildTool";
+
+/**
+ * Structured result parsed from one output
+ */
+export type BuildSummary = { tool: BuildTool, success: boolean, passed: number, failed: number, skipped: number, failing_tests: Array<string>, errors: Array<BuildError>, };
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CheckRun } from "./CheckRun";
+
+/**
+ * A check-loop run, with the prompt it led to (if any)
+ */
+export type CheckLoopEvent = { session_id: string, command: string, iterations: number, max_iterations: number, run: CheckRun, 
+/**
+ * Prompt sent to the agent after this run, if it failed
+ */
+follow_up: string | null, timestamp: number, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * One run of the check command
+ */
+export type CheckRun = { passed: boolean, exit_code: number | null, 
+/**
+ * Combined stdout and stderr
+ */
+output: string, duration_ms: number, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ContextLevel } from "./ContextLevel";
+
+/**
+ * A session is nearing its model's context window
+ */
+export type ContextWarningEvent = { session_id: string, worker_id: string, tokens: number, limit: number, level: ContextLevel, };
@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AcceptanceCriterion } from "./AcceptanceCriterion";
+import type { CriterionStatus } from "./CriterionStatus";
+
+export type CriterionResult = { criterion: AcceptanceCriterion, status: CriterionStatus, };
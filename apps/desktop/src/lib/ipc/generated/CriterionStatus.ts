@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Status of a criterion check
+ */
+export type CriterionStatus = { passed: boolean, error: string | null, lastChecked: number | null, };
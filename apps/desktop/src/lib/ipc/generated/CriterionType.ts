@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Acceptance criterion types for story verification
+ */
+export type CriterionType = "test" | "file_exists" | "pattern" | "custom";
@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A local server address showed up in an agent or service terminal's
+ * output for the first time in the session
+ */
+export type DevServerDetectedEvent = { session_id: string, terminal_id: string, 
+/**
+ * None for service terminals
+ */
+worker_id: string | null, 
+/**
+ * Normalized to localhost, e.g. "http://localhost:5173/"
+ */
+url: string, port: number, };
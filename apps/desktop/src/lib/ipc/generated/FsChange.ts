@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FsChangeKind } from "./FsChangeKind";
+
+export type FsChange = { kind: FsChangeKind, path: string, 
+/**
+ * Always false for deleted paths, whose type can't be known
+ */
+is_dir: boolean, };
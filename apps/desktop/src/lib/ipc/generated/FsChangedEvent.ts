@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FsChange } from "./FsChange";
+
+/**
+ * A debounced batch of changes in a watched workspace
+ */
+export type FsChangedEvent = { 
+/**
+ * The workspace passed to `watch_workspace`
+ */
+root: string, changes: Array<FsChange>, };
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BudgetPeriod } from "./BudgetPeriod";
+
+/**
+ * A spend cap was crossed; running prompts were cancelled and PRD
+ * sessions paused
+ */
+export type GlobalBudgetExceededEvent = { period: BudgetPeriod, spent_usd: number, cap_usd: number, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * What a guard rule inspects
+ */
+export type GuardTarget = "output" | "tool_call" | "command";
@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { GuardAction } from "./GuardAction";
+import type { GuardTarget } from "./GuardTarget";
+
+/**
+ * A guard rule fired on agent activity
+ */
+export type GuardTriggeredEvent = { session_id: string, worker_id: string, rule: string, target: GuardTarget, action: GuardAction, matched: string, message: string | null, 
+/**
+ * True when the action is held until `respond_to_guard_review`
+ */
+awaiting_review: boolean, timestamp: number, };
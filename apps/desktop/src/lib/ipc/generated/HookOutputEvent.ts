@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { HookRun } from "./HookRun";
+
+/**
+ * A project hook script ran
+ */
+export type HookOutputEvent = { session_id: string, run: HookRun, };
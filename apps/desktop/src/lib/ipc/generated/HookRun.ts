@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * One execution of a hook script
+ */
+export type HookRun = { 
+/**
+ * Script name, e.g. "post-tool-edit"
+ */
+hook: string, worker_id: string, 
+/**
+ * None if the script was killed or couldn't start
+ */
+exit_code: number | null, 
+/**
+ * Combined stdout and stderr
+ */
+output: string, duration_ms: number, timed_out: boolean, timestamp: number, };
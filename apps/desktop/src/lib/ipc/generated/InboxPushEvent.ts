@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * An idle worker was prompted with its unread inbox messages
+ * (`inbox_push`)
+ */
+export type InboxPushEvent = { session_id: string, worker_id: string, 
+/**
+ * Messages in the prompt
+ */
+count: number, prompt: string, };
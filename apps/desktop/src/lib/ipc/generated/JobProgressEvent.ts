@@ -0,0 +1,20 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { JobStatus } from "./JobStatus";
+
+/**
+ * A background job started, moved to a new phase, logged a line or
+ * finished
+ */
+export type JobProgressEvent = { job_id: string, 
+/**
+ * e.g. "install_agent" or "export_session"
+ */
+kind: string, status: JobStatus, phase: string, 
+/**
+ * 0-100, or None while the job can't tell how far along it is
+ */
+percent: number | null, 
+/**
+ * Log line added since the previous event
+ */
+line: string | null, error: string | null, };
@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A worker's plan was copied to the task board
+ */
+export type PlanImportedEvent = { session_id: string, worker_id: string, 
+/**
+ * Tasks created for new plan entries
+ */
+created: Array<string>, 
+/**
+ * Tasks moved along because their entry was started or completed
+ */
+updated: Array<string>, };
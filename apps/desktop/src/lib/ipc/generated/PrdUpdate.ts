@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CriterionStatus } from "./CriterionStatus";
+
+export type PrdUpdate = { "type": "story_added", story_id: string, } | { "type": "story_updated", story_id: string, } | { "type": "story_removed", story_id: string, } | { "type": "story_skipped", story_id: string, } | { "type": "story_paused", story_id: string, } | { "type": "story_resumed", story_id: string, } | { "type": "dependents_unblocked", story_id: string, dependents: Array<string>, warning: string, } | { "type": "leader_reply", message: string, reply: string, } | { "type": "criteria", story_id: string, criteria: Array<CriterionStatus>, };
@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A request went through a preview proxy
+ */
+export type PreviewRequestEvent = { preview_id: string, session_id: string | null, method: string, 
+/**
+ * Path and query
+ */
+path: string, 
+/**
+ * 502 when the dev server couldn't be reached
+ */
+status: number, duration_ms: number, };
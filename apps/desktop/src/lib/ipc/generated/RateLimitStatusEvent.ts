@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * An agent and model were rate limited, or the limit has lifted
+ */
+export type RateLimitStatusEvent = { agent_id: string, model: string, limited: boolean, 
+/**
+ * Unix timestamp the limit resets, while limited
+ */
+reset_at: number | null, 
+/**
+ * Rate-limited responses since the limit was first hit
+ */
+hits: number, message: string | null, };
@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ConnectionState } from "./ConnectionState";
+
+export type RemoteConnectionStatus = { host_id: string, state: ConnectionState, 
+/**
+ * Consecutive failed connection attempts
+ */
+attempt: number, 
+/**
+ * Last event sequence number received from the host
+ */
+last_seq: number | null, error: string | null, };
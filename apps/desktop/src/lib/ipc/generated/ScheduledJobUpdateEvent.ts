@@ -0,0 +1,19 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { JobRunStatus } from "./JobRunStatus";
+
+/**
+ * A scheduled job started a run or its run finished
+ */
+export type ScheduledJobUpdateEvent = { id: string, name: string, 
+/**
+ * Cron expression, e.g. "0 3 * * *" or "@daily"
+ */
+schedule: string, prompt: string, agent_id: string, model_id: string | null, cwd: string, enabled: boolean, created_at: number, 
+/**
+ * Unix timestamp of the next scheduled run
+ */
+next_run_at: number | null, last_run_at: number | null, 
+/**
+ * Session created by the last run
+ */
+last_session_id: string | null, last_status: JobRunStatus | null, last_error: string | null, };
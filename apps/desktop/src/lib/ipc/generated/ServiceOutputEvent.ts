@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A line of a service terminal's output
+ */
+export type ServiceOutputEvent = { service_id: string, 
+/**
+ * Including its newline
+ */
+line: string, };
@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ServiceStatus } from "./ServiceStatus";
+
+/**
+ * A service terminal started, became ready, exited, or is waiting to be
+ * restarted
+ */
+export type ServiceStatusEvent = { service_id: string, session_id: string, name: string, status: ServiceStatus, pid: number | null, restarts: number, 
+/**
+ * Exit code of the last run, once one has ended
+ */
+exit_code: number | null, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ContextUsage } from "./ContextUsage";
+
+/**
+ * A session was compacted into a fresh ACP session seeded with a summary
+ */
+export type SessionCompactedEvent = { session_id: string, worker_id: string, acp_session_id: string, summary: string, context: ContextUsage, };
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CriterionResult } from "./CriterionResult";
+
+/**
+ * A session's acceptance criteria were checked after a turn
+ */
+export type SessionCriteriaEvent = { session_id: string, worker_id: string, passed: boolean, results: Array<CriterionResult>, 
+/**
+ * Prompt sent back to the agent listing failures, if any
+ */
+follow_up: string | null, follow_ups_sent: number, timestamp: number, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A queued session got a slot and is starting
+ */
+export type SessionDequeuedEvent = { session_id: string, };
@@ -0,0 +1,20 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ResealFailure } from "./ResealFailure";
+
+/**
+ * Files on disk were converted after `encrypt_sessions` was turned on or
+ * off
+ */
+export type SessionEncryptionEvent = { encrypted: boolean, 
+/**
+ * Files converted
+ */
+converted: number, 
+/**
+ * Files left as they were
+ */
+failed: Array<ResealFailure>, 
+/**
+ * Set when nothing could be converted (e.g. no keychain)
+ */
+error: string | null, };
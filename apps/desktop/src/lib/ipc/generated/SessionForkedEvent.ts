@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A new session branched off a saved one (`fork_session`)
+ */
+export type SessionForkedEvent = { session_id: string, parent_session_id: string, 
+/**
+ * Messages before this index were carried over
+ */
+at_message_index: number, };
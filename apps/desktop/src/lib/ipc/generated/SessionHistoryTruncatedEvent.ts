@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * `edit_and_resend` dropped the saved history from `message_index` on
+ */
+export type SessionHistoryTruncatedEvent = { session_id: string, message_index: number, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A new session is waiting for an agent slot (`max_running_agents`)
+ */
+export type SessionQueuedEvent = { session_id: string, worker_id: string, 
+/**
+ * Place in the queue, 1 for next
+ */
+position: number, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A saved session got a generated title
+ */
+export type SessionTitleUpdatedEvent = { session_id: string, title: string, };
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ShutdownStage } from "./ShutdownStage";
+
+/**
+ * Progress of draining workers after the app was asked to quit
+ */
+export type ShutdownProgressEvent = { stage: ShutdownStage, message: string, 
+/**
+ * Agent processes still alive
+ */
+running_agents: number, };
@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A backend slash command (`/cost`, `/compact`, `/retry`, `/export`) ran
+ */
+export type SlashCommandResultEvent = { session_id: string, 
+/**
+ * Command name without the `/`
+ */
+command: string, success: boolean, 
+/**
+ * What the command reported, or why it failed
+ */
+output: string, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A tool call has been running longer than `slow_tool_threshold_secs`.
+ * Sent once per call, while it's still running.
+ */
+export type SlowToolEvent = { session_id: string, worker_id: string, tool_call_id: string, kind: string | null, title: string | null, elapsed_ms: number, threshold_ms: number, };
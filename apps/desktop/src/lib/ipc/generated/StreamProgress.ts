@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Snapshot of streaming progress sent to the frontend
+ */
+export type StreamProgress = { elapsed_ms: number, chunk_count: number, total_chars: number, 
+/**
+ * Throughput over the last few seconds
+ */
+chars_per_sec: number, 
+/**
+ * Throughput since the first delta
+ */
+avg_chars_per_sec: number, 
+/**
+ * Time since the most recent delta (useful to detect stalls)
+ */
+idle_ms: number, };
@@ -0,0 +1,17 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { TaskChange } from "./TaskChange";
+import type { TaskColumns } from "./TaskColumns";
+
+/**
+ * A session's tasks changed, through the task commands or a worker's
+ * `swarm task` commands
+ */
+export type TaskBoardChangedEvent = { session_id: string, 
+/**
+ * The task created, updated, claimed or deleted
+ */
+task_id: string | null, change: TaskChange, 
+/**
+ * Counts after the change
+ */
+columns: TaskColumns, };
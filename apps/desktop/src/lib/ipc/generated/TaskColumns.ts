@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Task counts per Kanban column
+ */
+export type TaskColumns = { pending: number, in_progress: number, completed: number, 
+/**
+ * Pending tasks still waiting on others (counted in `pending` too)
+ */
+blocked: number, };
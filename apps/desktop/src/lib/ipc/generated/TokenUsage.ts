@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type TokenUsage = { input_tokens: number, output_tokens: number, 
+/**
+ * Counted from characters rather than reported by the agent
+ */
+estimated: boolean, };
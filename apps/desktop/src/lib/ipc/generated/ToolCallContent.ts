@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type ToolCallContent = { "type": "text", text: string, } | { "type": "content", text: string, } | { "type": "diff", path: string, old_text: string | null, new_text: string, } | { "type": "terminal", terminal_id: string, } | { "type": "unknown", text: string, };
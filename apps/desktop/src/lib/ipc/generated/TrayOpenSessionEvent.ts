@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A session was picked from the tray menu
+ */
+export type TrayOpenSessionEvent = { session_id: string, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A line the agent process wrote to stderr (redacted)
+ */
+export type WorkerAgentLogEvent = { session_id: string, worker_id: string, line: string, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type WorkerCommandsEvent = { worker_id: string, 
+/**
+ * ACP `AvailableCommand`s, as the agent sent them
+ */
+commands: Array<unknown>, };
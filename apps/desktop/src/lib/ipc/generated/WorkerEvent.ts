@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ContextUsage } from "./ContextUsage";
+import type { PlanEntry } from "./PlanEntry";
+import type { StreamProgress } from "./StreamProgress";
+import type { TokenUsage } from "./TokenUsage";
+
+/**
+ * One stream event, tagged with the prompt (turn) it belongs to
+ */
+export type WorkerEvent = { prompt_id: string | null, } & ({ "type": "delta", text: string, } | { "type": "thinking", text: string, } | { "type": "complete", output: string, thinking: string | null, usage: TokenUsage, cost_usd: number | null, metrics: StreamProgress | null, context: ContextUsage | null, } | { "type": "error", message: string, } | { "type": "plan", entries: Array<PlanEntry>, } | { "type": "read_only_blocked", mode: string, action: string, detail: string, } | { "type": "network_blocked", command: string, reason: string, } | { "type": "file_diff", tool_call_id: string | null, path: string, old_text: string | null, new_text: string, unified: string, });
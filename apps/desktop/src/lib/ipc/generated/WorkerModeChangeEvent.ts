@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A `set_acp_session_mode` the agent accepted
+ */
+export type WorkerModeChangeEvent = { session_id: string, worker_id: string, mode_id: string, };
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PermissionOption } from "./PermissionOption";
+
+/**
+ * A permission request. Headless runs answer it themselves and report the
+ * answer in `selected`, with no options to choose from.
+ */
+export type WorkerPermissionEvent = { worker_id: string, title: string, tool_call_id: string, options: Array<PermissionOption>, selected: string | null, };
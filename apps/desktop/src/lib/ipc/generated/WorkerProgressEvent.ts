@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { StreamProgress } from "./StreamProgress";
+
+/**
+ * Generation speed, sent periodically while a prompt streams
+ */
+export type WorkerProgressEvent = { worker_id: string, session_id: string, prompt_id: string | null, progress: StreamProgress, };
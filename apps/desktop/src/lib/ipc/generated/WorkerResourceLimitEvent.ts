@@ -0,0 +1,17 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { LimitLevel } from "./LimitLevel";
+import type { LimitResource } from "./LimitResource";
+
+/**
+ * An agent process or terminal went over a resource limit. At the hard
+ * level its processes have been killed and the worker failed.
+ */
+export type WorkerResourceLimitEvent = { session_id: string, worker_id: string, 
+/**
+ * Set when the process is an agent-created terminal
+ */
+terminal_id: string | null, resource: LimitResource, level: LimitLevel, 
+/**
+ * MB for memory, percent of one core for CPU
+ */
+usage: number, limit: number, message: string, };
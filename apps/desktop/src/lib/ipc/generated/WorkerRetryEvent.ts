@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { TransientError } from "./TransientError";
+
+/**
+ * A prompt failed with a transient error and will be sent again after
+ * `delay_secs`
+ */
+export type WorkerRetryEvent = { session_id: string, worker_id: string, kind: TransientError, 
+/**
+ * 1 for the first retry
+ */
+attempt: number, max_attempts: number, delay_secs: number, error: string, };
@@ -0,0 +1,26 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { WorkerStatus } from "./WorkerStatus";
+
+/**
+ * A worker's status changed. The optional fields are set by the sites
+ * they apply to, e.g. `error` for failures and `stop_reason` for finished
+ * prompts.
+ */
+export type WorkerStatusChange = { session_id: string, worker_id: string, status: WorkerStatus, error: string | null, cost: number | null, stop_reason: string | null, agent: string | null, 
+/**
+ * Fleet workers only
+ */
+is_leader: boolean | null, 
+/**
+ * Loading a saved ACP session into a new worker
+ */
+resuming: boolean, resumed: boolean, 
+/**
+ * Resumed from a condensed transcript because the agent can't
+ * load_session
+ */
+resumed_from_summary: boolean, 
+/**
+ * Restarting the agent for a session whose worker died
+ */
+reconnecting: boolean, reconnected: boolean, };
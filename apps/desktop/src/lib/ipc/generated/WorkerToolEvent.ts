@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ToolCallContent } from "./ToolCallContent";
+
+/**
+ * A tool call starting or being updated. Updates leave the fields that
+ * didn't change null (and `content` empty).
+ */
+export type WorkerToolEvent = { worker_id: string, tool_call_id: string, title: string | null, kind: string | null, status: string | null, content: Array<ToolCallContent>, 
+/**
+ * Raw input for special tool calls (e.g. the plan for ExitPlanMode)
+ */
+raw_input: Record<string, unknown> | null, };
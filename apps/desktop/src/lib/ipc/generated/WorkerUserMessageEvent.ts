@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A user message chunk echoed back by the agent
+ */
+export type WorkerUserMessageEvent = { worker_id: string, text: string, };
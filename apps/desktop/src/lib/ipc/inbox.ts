@@ -1,4 +1,5 @@
-import { invoke } from "@tauri-apps/api/core";
+import { invoke } from "./errors";
+import type { WorkerStatus } from "./events";
 
 // ============================================================================
 // Inbox System Types
@@ -25,6 +26,18 @@ export interface Message {
   timestamp: number;
 }
 
+/** What a worker registered as */
+export interface WorkerInfo {
+  workerId: string;
+  /** "leader" or "worker" */
+  role: string;
+  agentId: string | null;
+  model: string | null;
+  /** Skills the worker declared with `swarm team declare` */
+  specialties: string[];
+  status: WorkerStatus;
+}
+
 // ============================================================================
 // Inbox Commands
 // ============================================================================
@@ -35,8 +48,9 @@ export interface Message {
 export async function inboxRegister(
   sessionId: string,
   workerId: string,
+  specialties?: string[],
 ): Promise<void> {
-  return invoke<void>("inbox_register", { sessionId, workerId });
+  return invoke<void>("inbox_register", { sessionId, workerId, specialties });
 }
 
 /**
@@ -140,10 +154,13 @@ export async function inboxCount(
 }
 
 /**
- * Get all registered workers in the session
+ * Get all registered workers in the session, with their role, agent,
+ * model, specialties and status
  */
-export async function inboxGetWorkers(sessionId: string): Promise<string[]> {
-  return invoke<string[]>("inbox_get_workers", { sessionId });
+export async function inboxGetWorkers(
+  sessionId: string,
+): Promise<WorkerInfo[]> {
+  return invoke<WorkerInfo[]>("inbox_get_workers", { sessionId });
 }
 
 // ============================================================================
@@ -0,0 +1,31 @@
+import { invoke } from "@tauri-apps/api/core";
+import type { WebhookConfig } from "./settings";
+
+// ============================================================================
+// Integration Types
+// ============================================================================
+
+export type LifecycleEvent =
+  | "session_completed"
+  | "story_passed"
+  | "worker_failed"
+  | "cost_threshold"
+  | "test";
+
+export interface WebhookDelivery {
+  status: number;
+  attempts: number;
+}
+
+// ============================================================================
+// Integration Commands
+// ============================================================================
+
+/**
+ * Send a test event to a webhook (ignores its event filter)
+ */
+export async function testWebhook(
+  webhook: WebhookConfig,
+): Promise<WebhookDelivery> {
+  return invoke<WebhookDelivery>("test_webhook", { webhook });
+}
@@ -0,0 +1,49 @@
+import type { UnlistenFn } from "@tauri-apps/api/event";
+
+import { invoke } from "./errors";
+import { type JobProgressEvent, type JobStatus, listenVersioned } from "./events";
+
+// ============================================================================
+// Job Types
+// ============================================================================
+
+export type { JobProgressEvent, JobStatus };
+
+export interface JobInfo {
+  id: string;
+  /** "install_agent" or "export_session" */
+  kind: string;
+  /** e.g. "Install Gemini CLI" */
+  label: string;
+  status: JobStatus;
+  phase: string;
+  /** 0-100, or null while the job can't tell how far along it is */
+  percent: number | null;
+  /** The latest log lines, oldest first */
+  log: string[];
+  error: string | null;
+  /** Unix timestamps in milliseconds */
+  started_at: number;
+  finished_at: number | null;
+}
+
+// ============================================================================
+// Job Commands
+// ============================================================================
+
+/** Running and recently finished background jobs, newest first */
+export async function listJobs(): Promise<JobInfo[]> {
+  return invoke<JobInfo[]>("list_jobs");
+}
+
+/** Ask a job to stop; resolves false if it already finished */
+export async function cancelJob(jobId: string): Promise<boolean> {
+  return invoke<boolean>("cancel_job", { jobId });
+}
+
+/** Every phase change, log line and finish of every job */
+export function onJobProgress(
+  callback: (event: JobProgressEvent) => void,
+): Promise<UnlistenFn> {
+  return listenVersioned<JobProgressEvent>("job-progress", callback);
+}
@@ -0,0 +1,16 @@
+import { invoke } from "@tauri-apps/api/core";
+
+// ============================================================================
+// Notification Commands
+// ============================================================================
+
+/**
+ * Notify when a session's prompts complete, overriding the global
+ * prompt_completed toggle for that session
+ */
+export async function notifyOnCompletion(
+  sessionId: string,
+  enabled: boolean,
+): Promise<void> {
+  return invoke<void>("notify_on_completion", { sessionId, enabled });
+}
@@ -1,16 +1,64 @@
 import type { PermissionOptionKind } from "@agentclientprotocol/sdk";
-import { invoke } from "@tauri-apps/api/core";
 import { listen, type UnlistenFn } from "@tauri-apps/api/event";
 
+import type { AcceptanceCriterion } from "@/lib/types/prd";
 import type {
   AgentType,
   FileConflict,
   OrchestratorSession,
   ToolCall,
+  ToolCallContent as ToolCallContentView,
   ToolCallKind,
   ToolCallStatus,
   WorkerSession,
 } from "@/stores/orchestrator-store";
+import { hasErrorCode, invoke } from "./errors";
+import {
+  type AgentLoginFinishedEvent,
+  type BuildError,
+  type BuildResult,
+  type BuildResultEvent,
+  type BuildTool,
+  type CheckLoopEvent,
+  type CheckRun,
+  type ContextUsage,
+  type ContextWarningEvent,
+  type CriterionResult,
+  type GuardTriggeredEvent,
+  type HookOutputEvent,
+  type HookRun,
+  type InboxPushEvent,
+  listenVersioned,
+  type RateLimitStatusEvent,
+  type SessionCompactedEvent,
+  type SessionCriteriaEvent,
+  type SessionDequeuedEvent,
+  type SessionForkedEvent,
+  type SessionHistoryTruncatedEvent,
+  type SessionQueuedEvent,
+  type SessionTitleUpdatedEvent,
+  type ShutdownProgressEvent,
+  type ShutdownStage,
+  type SlowToolEvent,
+  type StreamProgress,
+  type ToolCallContent,
+  type TrayOpenSessionEvent,
+  type WorkerAgentLogEvent,
+  type WorkerAuthenticatedEvent,
+  type WorkerCommandsEvent,
+  type WorkerEvent,
+  type WorkerModeEvent,
+  type WorkerPermissionEvent,
+  type WorkerProgressEvent,
+  type WorkerResourceLimitEvent,
+  type WorkerRetryEvent,
+  type WorkerStatusChange,
+  type WorkerStreamEvent,
+  type WorkerToolEvent,
+} from "./events";
+import type { EnvVarPreview } from "./commands";
+import type { Message } from "./inbox";
+import type { Task } from "./tasks";
 
 // Raw types from backend (snake_case)
 interface RawWorkerSession {
@@ -29,7 +77,7 @@ interface RawWorkerSession {
   updated_at: number;
 }
 
-interface RawOrchestratorSession {
+export interface RawOrchestratorSession {
   id: string;
   prompt: string;
   status: string;
@@ -51,42 +99,25 @@ interface WorkerResponse {
   worker: RawWorkerSession;
 }
 
-interface WorkerStreamEvent {
-  worker_id: string;
-  event: WorkerEventType;
-}
-
-type WorkerEventType =
-  | { type: "delta"; text: string }
-  | { type: "thinking"; text: string }
-  | {
-      type: "complete";
-      output: string;
-      usage: {
-        input_tokens: number;
-        output_tokens: number;
-        estimated?: boolean;
-      };
-      cost_usd?: number;
-    }
-  | { type: "error"; message: string }
-  | {
-      type: "plan";
-      entries: Array<{
-        content: string;
-        priority: "high" | "medium" | "low";
-        status: "pending" | "in_progress" | "completed";
-      }>;
-    };
-
-interface WorkerStatusChangeEvent {
-  session_id: string;
-  worker_id: string;
-  status: string;
-  cost?: number;
-  error?: string;
-  reconnecting?: boolean;
-}
+export type {
+  AgentLoginFinishedEvent,
+  BuildError,
+  BuildResult,
+  BuildResultEvent,
+  BuildTool,
+  CheckLoopEvent,
+  CheckRun,
+  ContextUsage,
+  ContextWarningEvent,
+  CriterionResult,
+  GuardTriggeredEvent,
+  HookOutputEvent,
+  HookRun,
+  SessionCriteriaEvent,
+  ShutdownProgressEvent,
+  ShutdownStage,
+  StreamProgress,
+};
 
 interface RawFileConflict {
   file_path: string;
@@ -100,6 +131,35 @@ export interface AgentModel {
   description: string;
 }
 
+export interface AgentAuthMethod {
+  id: string;
+  name: string;
+  description: string | null;
+}
+
+export interface AgentSessionMode {
+  id: string;
+  name: string;
+}
+
+// The modes a session offers (empty if the agent has none) and the one
+// it's in
+export interface SessionModes {
+  current: string | null;
+  available: AgentSessionMode[];
+}
+
+// Cached from the agent's last initialize handshake
+export interface AgentCapabilityInfo {
+  load_session: boolean;
+  image: boolean;
+  audio: boolean;
+  embedded_context: boolean;
+  modes: AgentSessionMode[];
+  auth_methods: AgentAuthMethod[];
+  checked_at: number;
+}
+
 // ACP Agent types
 export interface AgentConfig {
   id: string;
@@ -114,31 +174,14 @@ export interface AgentConfig {
   default_model: string;
   model_env_var: string | null;
   model_cli_flag: string | null;
+  capabilities: AgentCapabilityInfo | null;
 }
 
-interface WorkerToolCallEvent {
-  worker_id: string;
-  tool_call_id: string;
-  title: string;
-  kind: string;
-  status: string;
-  content?: Array<{
-    type: string;
-    text?: string;
-    code?: string;
-    language?: string;
-    message?: string;
-    // Diff-specific fields
-    path?: string;
-    old_text?: string;
-    new_text?: string;
-    // Terminal-specific fields
-    terminal_id?: string;
-    output?: string;
-    exit_code?: number;
-  }>;
-  // Raw input for special tool calls (e.g., plan for ExitPlanMode)
-  raw_input?: Record<string, unknown>;
+export interface AgentHealth {
+  agent_id: string;
+  ok: boolean;
+  error: string | null;
+  capabilities: AgentCapabilityInfo | null;
 }
 
 // Permission request types (aligned with ACP SDK)
@@ -148,13 +191,6 @@ export interface PermissionOption {
   kind: PermissionOptionKind;
 }
 
-interface WorkerPermissionEvent {
-  worker_id: string;
-  title: string;
-  tool_call_id: string;
-  options: PermissionOption[];
-}
-
 // Create a new orchestrator session
 export async function createOrchestratorSession(
   prompt: string,
@@ -190,6 +226,182 @@ export async function listOrchestratorSessions(): Promise<
   return sessions.map((s) => transformSession(s));
 }
 
+export type SessionKind = "orchestrator" | "acp" | "prd";
+
+export type SessionRecordStatus =
+  | "running"
+  | "idle"
+  | "paused"
+  | "completed"
+  | "failed"
+  | "cancelled"
+  // Saved, but its file can't be read (e.g. encrypted and the key is gone)
+  | "unreadable";
+
+// One entry of the unified session feed
+export interface SessionRecord {
+  kind: SessionKind;
+  id: string;
+  title: string;
+  status: SessionRecordStatus;
+  // Cost in USD so far
+  cost: number;
+  // Unix timestamp (milliseconds)
+  updated_at: number;
+}
+
+// Live, persisted and PRD sessions in one list, most recent first
+export async function listAllSessions(): Promise<SessionRecord[]> {
+  return invoke<SessionRecord[]>("list_all_sessions");
+}
+
+// A finished session written to ~/.crafter-code/archive
+export interface ArchivedSession {
+  session: RawOrchestratorSession;
+  tasks: Task[];
+  messages: Message[];
+  // Unix timestamp (milliseconds)
+  archived_at: number;
+}
+
+export interface ArchivedSessionSummary {
+  id: string;
+  prompt: string;
+  status: string;
+  workers: number;
+  total_cost: number;
+  created_at: number;
+  updated_at: number;
+  archived_at: number;
+}
+
+export interface StoreUsage {
+  path: string;
+  files: number;
+  bytes: number;
+}
+
+export interface SessionStorage {
+  // Saved ACP sessions
+  sessions: StoreUsage;
+  // Archived orchestrator sessions
+  archive: StoreUsage;
+  total_bytes: number;
+}
+
+export interface PurgeResult {
+  archived: number;
+  saved: number;
+  freed_bytes: number;
+}
+
+// Write a finished session to the archive and drop it from memory
+export async function archiveSession(
+  sessionId: string,
+): Promise<ArchivedSessionSummary> {
+  return invoke<ArchivedSessionSummary>("archive_session", { sessionId });
+}
+
+// Archived sessions, most recently archived first
+export async function listArchivedSessions(): Promise<
+  ArchivedSessionSummary[]
+> {
+  return invoke<ArchivedSessionSummary[]>("list_archived_sessions");
+}
+
+export async function getArchivedSession(
+  sessionId: string,
+): Promise<ArchivedSession> {
+  return invoke<ArchivedSession>("get_archived_session", { sessionId });
+}
+
+// Delete archived sessions (and saved ones with includeSaved) older than
+// the given age; pinned and loaded saved sessions are kept
+export async function purgeSessions(
+  olderThanHours: number,
+  includeSaved = false,
+): Promise<PurgeResult> {
+  return invoke<PurgeResult>("purge_sessions", {
+    olderThanHours,
+    includeSaved,
+  });
+}
+
+// Disk space used by saved and archived sessions
+export async function getSessionStorage(): Promise<SessionStorage> {
+  return invoke<SessionStorage>("get_session_storage");
+}
+
+// Kinds of data kept in ~/.crafter-code
+export type StorageCategory =
+  | "sessions"
+  | "logs"
+  | "snapshots"
+  | "recordings"
+  | "scratch";
+
+export interface CategoryUsage {
+  category: StorageCategory;
+  files: number;
+  bytes: number;
+}
+
+export interface StorageUsage {
+  path: string;
+  categories: CategoryUsage[];
+  total_bytes: number;
+}
+
+export interface CleanResult {
+  // Nothing was deleted; categories is what would have been
+  dry_run: boolean;
+  categories: CategoryUsage[];
+  freed_bytes: number;
+}
+
+// Disk space used by sessions, logs, snapshots, recordings and scratch dirs
+export async function getStorageUsage(): Promise<StorageUsage> {
+  return invoke<StorageUsage>("get_storage_usage");
+}
+
+// Delete data older than the given age in some categories (all by
+// default); dryRun only reports what would be deleted
+export async function cleanStorage(options: {
+  olderThanHours: number;
+  categories?: StorageCategory[];
+  dryRun?: boolean;
+}): Promise<CleanResult> {
+  return invoke<CleanResult>("clean_storage", options);
+}
+
+export type RecordKind = "schema" | "settings" | "session" | "archived_session";
+
+// A record in ~/.crafter-code that can't be loaded or migrated
+export interface DataProblem {
+  kind: RecordKind;
+  path: string;
+  error: string;
+}
+
+export interface DataHealth {
+  // Version of the data on disk, and the one this build expects
+  schema_version: number;
+  current_version: number;
+  checked: number;
+  problems: DataProblem[];
+}
+
+// Corrupt or unmigratable app data, instead of sessions silently missing
+export async function getDataHealth(): Promise<DataHealth> {
+  return invoke<DataHealth>("get_data_health");
+}
+
+// Whether the app is keeping the computer awake for running agents (see
+// the prevent_sleep setting)
+export async function isPreventingSleep(): Promise<boolean> {
+  return invoke<boolean>("is_preventing_sleep");
+}
+
 // Cancel a specific worker
 export async function cancelWorker(
   sessionId: string,
@@ -228,6 +440,232 @@ export async function getSessionCost(sessionId: string): Promise<number> {
   return invoke<number>("get_session_cost", { sessionId });
 }
 
+// Recorded tool call (status transitions, raw input, diffs, duration)
+export interface ToolCallRecord {
+  id: string;
+  worker_id: string;
+  kind: string | null;
+  title: string | null;
+  status: string;
+  transitions: Array<{ status: string; timestamp: number }>;
+  raw_input: unknown;
+  diffs: Array<{ path: string; old_text: string | null; new_text: string }>;
+  started_at: number;
+  finished_at: number | null;
+  duration_ms: number | null;
+}
+
+export interface ToolCallFilter {
+  worker_id?: string;
+  kind?: string;
+  status?: string;
+  with_diffs?: boolean;
+}
+
+// Unified patch of everything an agent changed in a session
+export interface PatchHunkLine {
+  tag: "context" | "add" | "remove";
+  text: string;
+}
+
+export interface PatchHunk {
+  index: number;
+  old_start: number;
+  old_lines: number;
+  new_start: number;
+  new_lines: number;
+  lines: PatchHunkLine[];
+}
+
+export interface FilePatch {
+  path: string;
+  display_path: string;
+  kind: "added" | "modified" | "deleted";
+  base_source: "checkpoint" | "git_head" | "snapshot" | "none";
+  additions: number;
+  deletions: number;
+  hunks: PatchHunk[];
+  unified: string;
+}
+
+export interface SessionPatch {
+  session_id: string;
+  files: FilePatch[];
+  additions: number;
+  deletions: number;
+  unified: string;
+}
+
+// Get a unified patch across all files touched in a session
+export async function getSessionPatch(
+  sessionId: string,
+): Promise<SessionPatch> {
+  return invoke<SessionPatch>("get_session_patch", { sessionId });
+}
+
+export interface HunkSelection {
+  path: string;
+  hunks: number[];
+}
+
+export interface PatchApplyResult {
+  staged_files: string[];
+  staged_hunks: number;
+}
+
+// Stage only the accepted hunks into the git index
+export async function applySessionPatch(
+  sessionId: string,
+  selections: HunkSelection[],
+): Promise<PatchApplyResult> {
+  return invoke<PatchApplyResult>("apply_session_patch", {
+    sessionId,
+    selections,
+  });
+}
+
+// Revert one hunk in the working tree; returns the file patch it belonged to
+export async function rejectSessionHunk(
+  sessionId: string,
+  path: string,
+  hunkIndex: number,
+): Promise<FilePatch> {
+  return invoke<FilePatch>("reject_session_hunk", {
+    sessionId,
+    path,
+    hunkIndex,
+  });
+}
+
+// Get the tool calls run in a session
+export async function getSessionToolCalls(
+  sessionId: string,
+  filter?: ToolCallFilter,
+): Promise<ToolCallRecord[]> {
+  return invoke<ToolCallRecord[]>("get_session_tool_calls", {
+    sessionId,
+    filter,
+  });
+}
+
+// Latency of one kind of tool call in a session
+export interface ToolKindLatency {
+  // "other" for calls the agent didn't give a kind
+  kind: string;
+  // Finished calls
+  count: number;
+  failed: number;
+  mean_ms: number;
+  p95_ms: number;
+  max_ms: number;
+}
+
+// Tool-call timing for a session
+export interface ToolCallMetrics {
+  // Slowest kind first
+  by_kind: ToolKindLatency[];
+  // Calls still running
+  in_flight: number;
+  // Most calls that were running at the same time
+  peak_concurrency: number;
+  // Running calls past the slow threshold
+  slow: number;
+}
+
+// Get per-kind tool-call latency and concurrency for a session
+export async function getSessionToolMetrics(
+  sessionId: string,
+): Promise<ToolCallMetrics> {
+  return invoke<ToolCallMetrics>("get_session_tool_metrics", { sessionId });
+}
+
+// Listen for tool calls running past the slow-tool threshold
+export function onSlowTool(
+  callback: (event: SlowToolEvent) => void,
+): Promise<UnlistenFn> {
+  return listenVersioned<SlowToolEvent>("slow-tool", callback);
+}
+
+// Get test/build results detected in a session's command output
+export async function getSessionBuildResults(
+  sessionId: string,
+): Promise<BuildResult[]> {
+  return invoke<BuildResult[]>("get_session_build_results", { sessionId });
+}
+
+// Get the hook scripts run during a session
+export async function getSessionHookRuns(
+  sessionId: string,
+): Promise<HookRun[]> {
+  return invoke<HookRun[]>("get_session_hook_runs", { sessionId });
+}
+
+export function onHookOutput(
+  callback: (event: HookOutputEvent) => void,
+): Promise<UnlistenFn> {
+  return listenVersioned<HookOutputEvent>("hook-output", callback);
+}
+
+// A line an agent wrote to stderr
+export interface AgentLogLine {
+  worker_id: string;
+  line: string;
+  timestamp: number;
+}
+
+// Get the agents' stderr for a session (live, or the persisted copy)
+export async function getSessionAgentLog(
+  sessionId: string,
+): Promise<AgentLogLine[]> {
+  return invoke<AgentLogLine[]>("get_session_agent_log", { sessionId });
+}
+
+// Listen for stderr lines from agent processes
+export function onWorkerAgentLog(
+  callback: (event: WorkerAgentLogEvent) => void,
+): Promise<UnlistenFn> {
+  return listenVersioned<WorkerAgentLogEvent>("worker-agent-log", callback);
+}
+
+// A session's "definition of done", checked after each completed prompt
+export interface SessionCriteria {
+  criteria: AcceptanceCriterion[];
+  // Send failures back to the agent automatically
+  auto_follow_up: boolean;
+  // Most automatic follow-ups per user prompt
+  max_follow_ups: number;
+  last_results: CriterionResult[];
+}
+
+// Attach acceptance criteria to a session (an empty list removes them)
+export async function setSessionCriteria(
+  sessionId: string,
+  criteria: AcceptanceCriterion[],
+  autoFollowUp?: boolean,
+  maxFollowUps?: number,
+): Promise<SessionCriteria | null> {
+  return invoke<SessionCriteria | null>("set_session_criteria", {
+    sessionId,
+    criteria,
+    autoFollowUp,
+    maxFollowUps,
+  });
+}
+
+export async function getSessionCriteria(
+  sessionId: string,
+): Promise<SessionCriteria | null> {
+  return invoke<SessionCriteria | null>("get_session_criteria", {
+    sessionId,
+  });
+}
+
+export function onSessionCriteria(
+  callback: (event: SessionCriteriaEvent) => void,
+): Promise<UnlistenFn> {
+  return listenVersioned<SessionCriteriaEvent>("session-criteria", callback);
+}
+
 // ============================================================================
 // ACP Commands
 // ============================================================================
@@ -237,34 +675,222 @@ export async function listAvailableAgents(): Promise<AgentConfig[]> {
   return invoke<AgentConfig[]>("list_available_agents");
 }
 
+// Run an agent's initialize handshake to check it works and refresh its capabilities
+export async function checkAgentHealth(agentId: string): Promise<AgentHealth> {
+  return invoke<AgentHealth>("check_agent_health", { agentId });
+}
+
+export interface AgentAuthStatus {
+  agent_id: string;
+  authenticated: boolean;
+  credential_source: string | null;
+  auth_methods: AgentAuthMethod[];
+  login_command: string | null;
+  error: string | null;
+}
+
+// Check whether an agent is signed in (runs its handshake)
+export async function getAgentAuthStatus(
+  agentId: string,
+): Promise<AgentAuthStatus> {
+  return invoke<AgentAuthStatus>("get_agent_auth_status", { agentId });
+}
+
+// Run an agent's interactive login in a terminal; returns the terminal id
+export async function startAgentLogin(
+  agentId: string,
+  cols?: number,
+  rows?: number,
+): Promise<string> {
+  return invoke<string>("start_agent_login", { agentId, cols, rows });
+}
+
+// Install an agent with its package manager; returns the job id (progress
+// arrives as job-progress events)
+export async function installAgent(agentId: string): Promise<string> {
+  return invoke<string>("install_agent", { agentId });
+}
+
+export function onAgentLoginFinished(
+  callback: (event: AgentLoginFinishedEvent) => void,
+): Promise<UnlistenFn> {
+  return listenVersioned<AgentLoginFinishedEvent>(
+    "agent-login-finished",
+    callback,
+  );
+}
+
+export type TrustLevel = "trusted" | "untrusted" | "unknown";
+
+export interface TrustDecision {
+  path: string;
+  trusted: boolean;
+  decided_at: number;
+}
+
+// Session starts in a directory without a trust decision fail with this
+export function isTrustRequiredError(error: unknown): boolean {
+  return hasErrorCode(error, "trust_required");
+}
+
+export async function getDirectoryTrust(
+  agentId: string,
+  cwd: string,
+): Promise<TrustLevel> {
+  return invoke<TrustLevel>("get_directory_trust", { agentId, cwd });
+}
+
+// Trust (or distrust) a directory and its subdirectories for an agent.
+// Sessions in untrusted directories are read-only.
+export async function setDirectoryTrust(
+  agentId: string,
+  cwd: string,
+  trusted: boolean,
+): Promise<TrustDecision> {
+  return invoke<TrustDecision>("set_directory_trust", { agentId, cwd, trusted });
+}
+
+export async function listDirectoryTrust(): Promise<
+  Record<string, TrustDecision[]>
+> {
+  return invoke<Record<string, TrustDecision[]>>("list_directory_trust");
+}
+
+export async function revokeDirectoryTrust(
+  agentId: string,
+  cwd: string,
+): Promise<boolean> {
+  return invoke<boolean>("revoke_directory_trust", { agentId, cwd });
+}
+
+// Coordination prompt templates (overridable at ~/.crafter/prompts/ and
+// <project>/.crafter/prompts/coordination.md)
+export interface CoordinationPromptPreview {
+  prompt: string;
+  template: string;
+  // Path of the override used, "builtin" or "draft"
+  source: string;
+}
+
+// Render the coordination prompt a worker in cwd would get. Pass template
+// to preview unsaved edits, agentId to include the project context files
+// that agent doesn't load itself, language to preview a response language
+// other than the session's.
+export async function previewCoordinationPrompt(
+  cwd: string,
+  options?: {
+    sessionId?: string;
+    workerId?: string;
+    isLeader?: boolean;
+    template?: string;
+    agentId?: string;
+    language?: string;
+  },
+): Promise<CoordinationPromptPreview> {
+  return invoke<CoordinationPromptPreview>("preview_coordination_prompt", {
+    cwd,
+    sessionId: options?.sessionId,
+    workerId: options?.workerId,
+    isLeader: options?.isLeader,
+    template: options?.template,
+    agentId: options?.agentId,
+    language: options?.language,
+  });
+}
+
+// Prepend task/roster changes to follow-up prompts (on by default)
+export async function setCoordinationUpdates(
+  sessionId: string,
+  enabled: boolean,
+): Promise<void> {
+  return invoke("set_coordination_updates", { sessionId, enabled });
+}
+
+export async function getCoordinationUpdates(
+  sessionId: string,
+): Promise<boolean> {
+  return invoke<boolean>("get_coordination_updates", { sessionId });
+}
+
+// An idle worker was prompted with its unread inbox messages
+// (settings.inbox_push)
+export function onInboxPush(
+  callback: (event: InboxPushEvent) => void,
+): Promise<UnlistenFn> {
+  return listenVersioned<InboxPushEvent>("inbox-push", callback);
+}
+
+// Progress of draining workers after the app was asked to quit
+export function onShutdownProgress(
+  callback: (event: ShutdownProgressEvent) => void,
+): Promise<UnlistenFn> {
+  return listenVersioned<ShutdownProgressEvent>("shutdown-progress", callback);
+}
+
 // Create a new ACP-based session (uses CLI agent instead of direct API)
 export async function createAcpSession(
   prompt: string,
   agentId: string,
   cwd: string,
   modelId?: string,
+  language?: string,
 ): Promise<OrchestratorSession> {
   const response = await invoke<SessionResponse>("create_acp_session", {
     prompt,
     agentId,
     modelId,
     cwd,
+    language,
+  });
+  return transformSession(response.session, agentId as AgentType, cwd);
+}
+
+// Branch a persisted session, keeping the messages before atMessageIndex.
+// The original session is left untouched.
+export async function forkSession(
+  sessionId: string,
+  atMessageIndex: number,
+  agentId: string,
+  cwd: string,
+): Promise<OrchestratorSession> {
+  const response = await invoke<SessionResponse>("fork_session", {
+    sessionId,
+    atMessageIndex,
   });
   return transformSession(response.session, agentId as AgentType, cwd);
 }
 
+// Listen for generated session titles (after the first completed prompt)
+export function onSessionTitleUpdated(
+  callback: (event: SessionTitleUpdatedEvent) => void,
+): Promise<UnlistenFn> {
+  return listenVersioned<SessionTitleUpdatedEvent>(
+    "session-title-updated",
+    callback,
+  );
+}
+
+// Listen for new forks of a session
+export function onSessionForked(
+  callback: (event: SessionForkedEvent) => void,
+): Promise<UnlistenFn> {
+  return listenVersioned<SessionForkedEvent>("session-forked", callback);
+}
+
 // Create a new ACP fleet session with multiple workers
 export async function createAcpFleetSession(
   prompt: string,
   agentId: string,
   cwd: string,
   workerCount: number,
+  language?: string,
 ): Promise<OrchestratorSession> {
   const response = await invoke<SessionResponse>("create_acp_fleet_session", {
     prompt,
     agentId,
     cwd,
     workerCount,
+    language,
   });
   return transformSession(response.session, agentId as AgentType, cwd);
 }
@@ -301,23 +927,153 @@ export async function sendAcpPromptWithImages(
   });
 }
 
+// A file, or some of its lines, staged for a session's next prompt
+export interface ContextItem {
+  /** Absolute, or relative to the session's project */
+  path: string;
+  /** 1-based, inclusive; the whole file when neither is set */
+  start_line?: number | null;
+  end_line?: number | null;
+  /** Sent instead of the file (text extracted from a document) */
+  text?: string | null;
+}
+
+// Stage files or search hits to go out with the next prompt (as embedded
+// resources, or inlined for agents without embedded context). Returns
+// everything staged so far; the next prompt clears it.
+export async function stageContext(
+  sessionId: string,
+  items: ContextItem[],
+): Promise<ContextItem[]> {
+  return invoke<ContextItem[]>("stage_context", { sessionId, items });
+}
+
+export async function getStagedContext(
+  sessionId: string,
+): Promise<ContextItem[]> {
+  return invoke<ContextItem[]>("get_staged_context", { sessionId });
+}
+
+// Unstage some items (all when omitted), returning what's left
+export async function unstageContext(
+  sessionId: string,
+  items?: ContextItem[],
+): Promise<ContextItem[]> {
+  return invoke<ContextItem[]>("unstage_context", { sessionId, items });
+}
+
+export type AttachmentKind = "image" | "document" | "table" | "code";
+
+// A dropped file, ready for the next prompt
+export interface Attachment {
+  kind: AttachmentKind;
+  name: string;
+  path: string;
+  /** Images: send with sendAcpPromptWithImages */
+  image: ImageAttachment | null;
+  /** Everything else: staged for the next prompt (unstage to drop it) */
+  context: ContextItem | null;
+  /** Only part of the file was kept (a long document, a large table) */
+  truncated: boolean;
+}
+
+// Ingest a dropped file: images are scaled down, PDFs and Word documents
+// have their text extracted, tables are cut to a preview, and text files
+// are staged as they are
+export async function ingestAttachment(
+  sessionId: string,
+  path: string,
+): Promise<Attachment> {
+  return invoke<Attachment>("ingest_attachment", { sessionId, path });
+}
+
+// Check a pasted image (base64) and make it ready to send: the format is
+// sniffed from the bytes, formats agents don't take (TIFF, BMP) become PNG
+// and large images are scaled down
+export async function prepareClipboardImage(
+  data: string,
+): Promise<ImageAttachment> {
+  return invoke<ImageAttachment>("prepare_clipboard_image", { data });
+}
+
 // Listen for worker stream events (deltas, complete, error)
 export function onWorkerStream(
   workerId: string,
-  callback: (event: WorkerEventType) => void,
+  callback: (event: WorkerEvent) => void,
 ): Promise<UnlistenFn> {
-  return listen<WorkerStreamEvent>(`worker-stream-${workerId}`, (event) => {
-    callback(event.payload.event);
-  });
+  return listenVersioned<WorkerStreamEvent>(
+    `worker-stream-${workerId}`,
+    (payload) => {
+      callback(payload.event);
+    },
+  );
+}
+
+// Listen for streaming throughput updates
+export function onWorkerProgress(
+  workerId: string,
+  callback: (progress: StreamProgress) => void,
+): Promise<UnlistenFn> {
+  return listenVersioned<WorkerProgressEvent>(
+    `worker-progress-${workerId}`,
+    (payload) => {
+      callback(payload.progress);
+    },
+  );
+}
+
+// Listen for test/build results parsed from agent output
+export function onBuildResult(
+  callback: (event: BuildResultEvent) => void,
+): Promise<UnlistenFn> {
+  return listenVersioned<BuildResultEvent>("build-result", callback);
 }
 
 // Listen for worker status changes
 export function onWorkerStatusChange(
-  callback: (event: WorkerStatusChangeEvent) => void,
+  callback: (event: WorkerStatusChange) => void,
 ): Promise<UnlistenFn> {
-  return listen<WorkerStatusChangeEvent>("worker-status-change", (event) => {
-    callback(event.payload);
-  });
+  return listenVersioned<WorkerStatusChange>("worker-status-change", callback);
+}
+
+// Listen for agent processes and terminals going over a resource limit
+// (settings.resource_limits); hard limits also fail the worker
+export function onWorkerResourceLimit(
+  callback: (event: WorkerResourceLimitEvent) => void,
+): Promise<UnlistenFn> {
+  return listenVersioned<WorkerResourceLimitEvent>(
+    "worker-resource-limit",
+    callback,
+  );
+}
+
+// Listen for prompts being retried after rate limit, overload or network errors
+export function onWorkerRetry(
+  callback: (event: WorkerRetryEvent) => void,
+): Promise<UnlistenFn> {
+  return listenVersioned<WorkerRetryEvent>("worker-retry", callback);
+}
+
+// An agent and model under a rate limit; prompts to it wait for the reset
+export interface RateLimitWindow {
+  agent_id: string;
+  model: string;
+  hits: number;
+  limited_at: number;
+  reset_at: number;
+  message: string;
+}
+
+// Get the agents and models currently rate limited
+export async function getRateLimits(): Promise<RateLimitWindow[]> {
+  return invoke<RateLimitWindow[]>("get_rate_limits");
+}
+
+// Listen for agents being rate limited and the limits lifting
+export function onRateLimitStatus(
+  callback: (event: RateLimitStatusEvent) => void,
+): Promise<UnlistenFn> {
+  return listenVersioned<RateLimitStatusEvent>("rate-limit-status", callback);
 }
 
 // Listen for session creation events
@@ -336,44 +1092,58 @@ export function onSessionCreated(
   );
 }
 
+// Listen for sessions waiting for an agent slot (see max_running_agents)
+export function onSessionQueued(
+  callback: (event: SessionQueuedEvent) => void,
+): Promise<UnlistenFn> {
+  return listenVersioned<SessionQueuedEvent>("session-queued", callback);
+}
+
+// Listen for queued sessions starting once a slot frees up
+export function onSessionDequeued(
+  callback: (event: SessionDequeuedEvent) => void,
+): Promise<UnlistenFn> {
+  return listenVersioned<SessionDequeuedEvent>("session-dequeued", callback);
+}
+
 // Listen for worker tool call events (ACP-specific)
 export function onWorkerToolCall(
   workerId: string,
   callback: (toolCall: ToolCall) => void,
 ): Promise<UnlistenFn> {
-  return listen<WorkerToolCallEvent>(`worker-tool-${workerId}`, (event) => {
-    const { tool_call_id, title, kind, status, content, raw_input } =
-      event.payload;
-    callback({
-      id: tool_call_id,
-      title,
-      kind: kind as ToolCallKind,
-      status: status as ToolCallStatus,
-      content: content?.map((c) => ({
-        type: c.type as
-          | "text"
-          | "code"
-          | "error"
-          | "content"
-          | "diff"
-          | "terminal",
-        text: c.text,
-        code: c.code,
-        language: c.language,
-        message: c.message,
-        // Diff-specific fields
-        path: c.path,
-        old_text: c.old_text,
-        new_text: c.new_text,
-        // Terminal-specific fields
-        terminal_id: c.terminal_id,
-        output: c.output,
-        exit_code: c.exit_code,
-      })),
-      rawInput: raw_input,
-      timestamp: Date.now(),
-    });
-  });
+  return listenVersioned<WorkerToolEvent>(
+    `worker-tool-${workerId}`,
+    (payload) => {
+      const { tool_call_id, title, kind, status, content, raw_input } =
+        payload;
+      callback({
+        id: tool_call_id,
+        // Updates leave unchanged fields null; the store keeps the old values
+        title: title ?? "",
+        kind: kind as ToolCallKind,
+        status: status as ToolCallStatus,
+        content: content.map(toToolCallContent),
+        rawInput: raw_input ?? undefined,
+        timestamp: Date.now(),
+      });
+    },
+  );
+}
+
+function toToolCallContent(content: ToolCallContent): ToolCallContentView {
+  switch (content.type) {
+    case "diff":
+      return {
+        type: "diff",
+        path: content.path,
+        old_text: content.old_text ?? undefined,
+        new_text: content.new_text,
+      };
+    case "terminal":
+      return { type: "terminal", terminal_id: content.terminal_id };
+    default:
+      return { type: content.type, text: content.text };
+  }
 }
 
 // Listen for permission request events
@@ -385,13 +1155,16 @@ export function onWorkerPermission(
     options: PermissionOption[];
   }) => void,
 ): Promise<UnlistenFn> {
-  return listen<WorkerPermissionEvent>(
+  return listenVersioned<WorkerPermissionEvent>(
     `worker-permission-${workerId}`,
-    (event) => {
+    (payload) => {
       callback({
-        title: event.payload.title,
-        toolCallId: event.payload.tool_call_id,
-        options: event.payload.options,
+        title: payload.title,
+        toolCallId: payload.tool_call_id,
+        options: payload.options.map((option) => ({
+          ...option,
+          kind: option.kind as PermissionOptionKind,
+        })),
       });
     },
   );
@@ -408,8 +1181,86 @@ export async function respondToPermission(
   });
 }
 
+// Approve or reject a tool call or command a guard rule paused
+export async function respondToGuardReview(
+  workerId: string,
+  approve: boolean,
+): Promise<void> {
+  return invoke<void>("respond_to_guard_review", { workerId, approve });
+}
+
+// A guard rule fired on agent output, a tool call or a command
+export function onGuardTriggered(
+  callback: (event: GuardTriggeredEvent) => void,
+): Promise<UnlistenFn> {
+  return listenVersioned<GuardTriggeredEvent>("guard-triggered", callback);
+}
+
+// A session was picked from the tray menu
+export function onTrayOpenSession(
+  callback: (event: TrayOpenSessionEvent) => void,
+): Promise<UnlistenFn> {
+  return listenVersioned<TrayOpenSessionEvent>("tray-open-session", callback);
+}
+
+// Modes of a live session, or of a persisted one as last seen
+export async function getSessionModes(sessionId: string): Promise<SessionModes> {
+  return invoke<SessionModes>("get_session_modes", { sessionId });
+}
+
+// The language chosen for a session and the one its agents are told to use
+export interface SessionLanguage {
+  session: string | null;
+  effective: string | null;
+}
+
+// Choose the language a session's agents answer in (null falls back to the
+// global setting). Applies to workers started afterwards.
+export async function setSessionLanguage(
+  sessionId: string,
+  language: string | null,
+): Promise<SessionLanguage> {
+  return invoke<SessionLanguage>("set_session_language", { sessionId, language });
+}
+
+export async function getSessionLanguage(
+  sessionId: string,
+): Promise<SessionLanguage> {
+  return invoke<SessionLanguage>("get_session_language", { sessionId });
+}
+
+export interface TraceFileInfo {
+  path: string;
+  bytes: number;
+}
+
+// JSON-RPC traffic of a session's agents, recorded per worker to
+// ~/.crafter-code/sessions/{id}/traces as redacted JSON lines
+export interface ProtocolTrace {
+  session_id: string;
+  enabled: boolean;
+  dir: string;
+  /** Trace files written so far, including rotated ones */
+  files: TraceFileInfo[];
+}
+
+// Start or stop recording a session's ACP traffic
+export async function setProtocolTrace(
+  sessionId: string,
+  enabled: boolean,
+): Promise<ProtocolTrace> {
+  return invoke<ProtocolTrace>("set_protocol_trace", { sessionId, enabled });
+}
+
+export async function getProtocolTrace(
+  sessionId: string,
+): Promise<ProtocolTrace> {
+  return invoke<ProtocolTrace>("get_protocol_trace", { sessionId });
+}
+
 // Set the session mode (e.g., "default", "acceptEdits", "plan", "dontAsk", "bypassPermissions")
-// Uses the official ACP session/set_mode protocol method
+// Uses the official ACP session/set_mode protocol method; modes the agent
+// didn't offer are rejected
 export async function setAcpSessionMode(
   sessionId: string,
   modeId: string,
@@ -431,6 +1282,79 @@ export async function authenticateAcpSession(
   });
 }
 
+// Summarize a session's history and continue it in a fresh ACP session
+// seeded with the summary. Resolves to the summary.
+export async function compactSession(sessionId: string): Promise<string> {
+  return invoke<string>("compact_session", { sessionId });
+}
+
+export interface CheckLoopResult {
+  passed: boolean;
+  // Prompts sent to the agent
+  iterations: number;
+  // Stopped because the agent's turn was cancelled
+  cancelled: boolean;
+  last_run: CheckRun;
+}
+
+// Run a shell check (e.g. "cargo test") and feed failures back to the agent
+// until it passes or maxIterations prompts have been sent
+export async function runUntilPassing(
+  sessionId: string,
+  command: string,
+  maxIterations: number,
+): Promise<CheckLoopResult> {
+  return invoke<CheckLoopResult>("run_until_passing", {
+    sessionId,
+    command,
+    maxIterations,
+  });
+}
+
+export function onCheckLoop(
+  callback: (event: CheckLoopEvent) => void,
+): Promise<UnlistenFn> {
+  return listenVersioned<CheckLoopEvent>("check-loop", callback);
+}
+
+// Replace a user message and resend it. Later messages are dropped from the
+// persisted history and the agent restarts from what remains.
+export async function editAndResend(
+  sessionId: string,
+  messageIndex: number,
+  newText: string,
+): Promise<void> {
+  return invoke<void>("edit_and_resend", {
+    sessionId,
+    messageIndex,
+    newText,
+  });
+}
+
+// Listen for history dropped by editAndResend
+export function onSessionHistoryTruncated(
+  callback: (event: SessionHistoryTruncatedEvent) => void,
+): Promise<UnlistenFn> {
+  return listenVersioned<SessionHistoryTruncatedEvent>(
+    "session-history-truncated",
+    callback,
+  );
+}
+
+// Listen for sessions nearing their model's context window
+export function onContextWarning(
+  callback: (event: ContextWarningEvent) => void,
+): Promise<UnlistenFn> {
+  return listenVersioned<ContextWarningEvent>("context-warning", callback);
+}
+
+// Listen for sessions that finished compacting
+export function onSessionCompacted(
+  callback: (event: SessionCompactedEvent) => void,
+): Promise<UnlistenFn> {
+  return listenVersioned<SessionCompactedEvent>("session-compacted", callback);
+}
+
 // ============================================================================
 // Authentication Types and Events
 // ============================================================================
@@ -441,22 +1365,19 @@ export interface AuthMethod {
   description?: string;
 }
 
-interface WorkerAuthenticatedEvent {
-  worker_id: string;
-  session_id: string;
-  method_id: string;
-}
-
 // Listen for worker authenticated events
 export function onWorkerAuthenticated(
   workerId: string,
   callback: (methodId: string) => void,
 ): Promise<UnlistenFn> {
-  return listen<WorkerAuthenticatedEvent>(`worker-authenticated`, (event) => {
-    if (event.payload.worker_id === workerId) {
-      callback(event.payload.method_id);
-    }
-  });
+  return listenVersioned<WorkerAuthenticatedEvent>(
+    "worker-authenticated",
+    (payload) => {
+      if (payload.worker_id === workerId) {
+        callback(payload.method_id);
+      }
+    },
+  );
 }
 
 // ============================================================================
@@ -464,7 +1385,7 @@ export function onWorkerAuthenticated(
 // ============================================================================
 
 export interface PersistedMessage {
-  role: string;
+  role: "user" | "assistant" | "thinking" | (string & {});
   content: string;
   timestamp: number;
 }
@@ -479,9 +1400,58 @@ export interface PersistedSession {
   messages: PersistedMessage[];
   mode: string;
   initial_prompt: string;
+  tool_calls?: ToolCallRecord[];
+  build_results?: BuildResult[];
+  /** Summary the session was last compacted to */
+  summary?: string | null;
+  /** Session this one was forked from */
+  parent_session_id?: string | null;
+  /** Number of the parent's messages copied into the fork */
+  forked_at_message?: number | null;
+  title?: string | null;
+  tags?: string[];
+  pinned?: boolean;
+  archived?: boolean;
+  language?: string | null;
+}
+
+// User-managed labels for a persisted session
+export interface SessionMetadata {
+  /** Shown instead of the initial prompt when set */
+  title: string | null;
+  tags: string[];
+  pinned: boolean;
+  archived: boolean;
+  /** Language the session's agents answer in, over the global setting */
+  language: string | null;
+  /** Zone the session was started in; exports show local times in it */
+  timezone: LocalZone | null;
 }
 
-export interface PersistedSessionSummary {
+// A time zone as a name (when known) and an offset from UTC
+export interface LocalZone {
+  name: string | null;
+  /** Minutes east of UTC */
+  utc_offset_minutes: number;
+}
+
+// Partial metadata change; omitted fields are left as they are.
+// An empty title clears it.
+export interface SessionMetadataUpdate {
+  title?: string;
+  tags?: string[];
+  pinned?: boolean;
+  archived?: boolean;
+}
+
+export interface SessionFilter {
+  /** Sessions must carry all of these tags */
+  tags?: string[];
+  pinned_only?: boolean;
+  include_archived?: boolean;
+}
+
+export interface PersistedSessionSummary extends SessionMetadata {
   id: string;
   acp_session_id: string;
   cwd: string;
@@ -490,13 +1460,32 @@ export interface PersistedSessionSummary {
   updated_at: number;
   message_count: number;
   initial_prompt: string;
+  parent_session_id: string | null;
+  // Why the file can't be loaded; only id and updated_at are known then
+  error?: string;
+  // Encrypted, and couldn't be decrypted
+  locked?: boolean;
 }
 
-// List all persisted sessions
-export async function listPersistedSessions(): Promise<
-  PersistedSessionSummary[]
-> {
-  return invoke<PersistedSessionSummary[]>("list_persisted_sessions");
+// List persisted sessions (pinned first). Archived sessions are left out
+// unless the filter includes them.
+export async function listPersistedSessions(
+  filter?: SessionFilter,
+): Promise<PersistedSessionSummary[]> {
+  return invoke<PersistedSessionSummary[]>("list_persisted_sessions", {
+    filter: filter ?? null,
+  });
+}
+
+// Set a persisted session's title, tags, pinned or archived state
+export async function updateSessionMetadata(
+  sessionId: string,
+  update: SessionMetadataUpdate,
+): Promise<PersistedSessionSummary> {
+  return invoke<PersistedSessionSummary>("update_session_metadata", {
+    sessionId,
+    update,
+  });
 }
 
 // Get a specific persisted session
@@ -511,6 +1500,29 @@ export async function deletePersistedSession(sessionId: string): Promise<void> {
   return invoke<void>("delete_persisted_session", { sessionId });
 }
 
+// Unsent prompt kept for a session across restarts
+export interface PromptDraft {
+  text: string;
+  attachments: ImageAttachment[];
+  updated_at: number;
+}
+
+// Save the unsent prompt for a session (an empty draft clears it)
+export async function savePromptDraft(
+  sessionId: string,
+  text: string,
+  attachments?: ImageAttachment[],
+): Promise<void> {
+  return invoke<void>("save_prompt_draft", { sessionId, text, attachments });
+}
+
+// Get the unsent prompt saved for a session
+export async function getPromptDraft(
+  sessionId: string,
+): Promise<PromptDraft | null> {
+  return invoke<PromptDraft | null>("get_prompt_draft", { sessionId });
+}
+
 // Resume a persisted ACP session
 export async function resumeAcpSession(
   persistedSessionId: string,
@@ -542,6 +1554,78 @@ export async function saveSessionToPersistence(
   });
 }
 
+// Export a persisted session as JSON (thinking included per settings)
+export async function exportPersistedSession(
+  sessionId: string,
+): Promise<string> {
+  return invoke<string>("export_persisted_session", { sessionId });
+}
+
+// Export a persisted session to a file in the background; returns the job id
+export async function exportSessionToFile(
+  sessionId: string,
+  path: string,
+): Promise<string> {
+  return invoke<string>("export_session_to_file", { sessionId, path });
+}
+
+// A persisted session packed for review on another machine: transcript,
+// tool calls, metadata, patch set and the project's .env with secrets
+// masked, all redacted
+export interface SessionBundle {
+  format: string;
+  version: number;
+  exported_at: number;
+  app_version: string;
+  session: PersistedSession;
+  patch: SessionPatch;
+  env: EnvVarPreview[];
+}
+
+// An imported bundle; read-only, it can't be resumed or prompted
+export interface ImportedBundle extends SessionBundle {
+  imported_at: number;
+}
+
+export interface ImportedBundleSummary extends PersistedSessionSummary {
+  exported_at: number;
+  imported_at: number;
+  files_changed: number;
+  additions: number;
+  deletions: number;
+}
+
+// Write a session sharing bundle (gzipped JSON) to path in the background;
+// returns the job id
+export async function exportSessionBundle(
+  sessionId: string,
+  path: string,
+): Promise<string> {
+  return invoke<string>("export_session_bundle", { sessionId, path });
+}
+
+// Import a bundle exported on another machine for read-only review
+export async function importSessionBundle(
+  path: string,
+): Promise<ImportedBundleSummary> {
+  return invoke<ImportedBundleSummary>("import_session_bundle", { path });
+}
+
+// Imported bundles, most recently imported first
+export async function listImportedBundles(): Promise<ImportedBundleSummary[]> {
+  return invoke<ImportedBundleSummary[]>("list_imported_bundles");
+}
+
+export async function getImportedBundle(
+  sessionId: string,
+): Promise<ImportedBundle> {
+  return invoke<ImportedBundle>("get_imported_bundle", { sessionId });
+}
+
+export async function deleteImportedBundle(sessionId: string): Promise<void> {
+  return invoke<void>("delete_imported_bundle", { sessionId });
+}
+
 // Reconnect a dead worker (when send_acp_prompt fails with "No active worker")
 export async function reconnectWorker(
   sessionId: string,
@@ -555,10 +1639,33 @@ export async function reconnectWorker(
   });
 }
 
-// Mode change event
-interface WorkerModeChangeEvent {
-  worker_id: string;
-  mode_id: string;
+// "dead" and "missing" need reconnectWorker before sending;
+// a hibernated worker is woken by the next command
+export type WorkerLiveness = "alive" | "hibernated" | "dead" | "missing";
+
+export interface WorkerHealth {
+  session_id: string;
+  worker_id: string | null;
+  liveness: WorkerLiveness;
+  // Running a prompt or other command
+  busy: boolean;
+  // Tool calls running past the slow-tool threshold; a busy worker with
+  // none of these and no output is stalled rather than waiting on a tool
+  slow_tools: number;
+  // Commands waiting in the worker's channel
+  queued_commands: number;
+  // Unix timestamps (seconds)
+  started_at: number | null;
+  last_heartbeat: number | null;
+  exited_at: number | null;
+  panicked: boolean;
+}
+
+// Check whether a session's worker can take commands
+export async function getWorkerHealth(
+  sessionId: string,
+): Promise<WorkerHealth> {
+  return invoke<WorkerHealth>("get_worker_health", { sessionId });
 }
 
 // Listen for mode change events
@@ -566,9 +1673,12 @@ export function onWorkerModeChange(
   workerId: string,
   callback: (modeId: string) => void,
 ): Promise<UnlistenFn> {
-  return listen<WorkerModeChangeEvent>(`worker-mode-${workerId}`, (event) => {
-    callback(event.payload.mode_id);
-  });
+  return listenVersioned<WorkerModeEvent>(
+    `worker-mode-${workerId}`,
+    (payload) => {
+      callback(payload.mode_id);
+    },
+  );
 }
 
 // Available command from ACP agent
@@ -583,19 +1693,17 @@ export interface AvailableCommand {
   meta?: Record<string, unknown>;
 }
 
-interface WorkerCommandsEvent {
-  worker_id: string;
-  commands: AvailableCommand[];
-}
-
 // Listen for available commands/skills from agent
 export function onWorkerCommands(
   workerId: string,
   callback: (commands: AvailableCommand[]) => void,
 ): Promise<UnlistenFn> {
-  return listen<WorkerCommandsEvent>(`worker-commands-${workerId}`, (event) => {
-    callback(event.payload.commands);
-  });
+  return listenVersioned<WorkerCommandsEvent>(
+    `worker-commands-${workerId}`,
+    (payload) => {
+      callback(payload.commands as AvailableCommand[]);
+    },
+  );
 }
 
 // ============================================================================
@@ -636,7 +1744,7 @@ export function onSessionSwarmActivity(
 }
 
 // Transform snake_case from backend to camelCase for frontend
-function transformSession(
+export function transformSession(
   session: RawOrchestratorSession,
   agentType: AgentType = "claude",
   cwd?: string,
@@ -0,0 +1,40 @@
+import { invoke } from "@tauri-apps/api/core";
+
+// ============================================================================
+// Command Palette
+// ============================================================================
+
+export type PaletteScope = "command" | "skill" | "session" | "file" | "agent";
+
+export interface PaletteItem {
+  scope: PaletteScope;
+  /** What to act on: command name, skill id, session id, file path or agent id */
+  id: string;
+  title: string;
+  subtitle: string;
+  score: number;
+  /** Character positions in `title` that matched the query */
+  positions: number[];
+}
+
+export interface PaletteSearchOptions {
+  /** Only these kinds of items (all by default) */
+  scopes?: PaletteScope[];
+  /** Project whose custom commands and skills are included */
+  projectDir?: string;
+  /** Provider whose config directory commands and skills come from */
+  agentId?: string;
+  /** At most this many results (50 by default) */
+  limit?: number;
+}
+
+/**
+ * Fuzzy-search commands, skills, sessions, recently opened files and agents
+ * in one ranked list, best match first
+ */
+export async function paletteSearch(
+  query: string,
+  options: PaletteSearchOptions = {},
+): Promise<PaletteItem[]> {
+  return invoke<PaletteItem[]>("palette_search", { query, ...options });
+}
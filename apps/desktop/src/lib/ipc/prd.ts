@@ -7,6 +7,8 @@ import type {
   StoryProgress,
   RalphWorker,
   CostBreakdown,
+  CriterionStatus,
+  Story,
 } from "@/lib/types/prd";
 
 // ============================================================================
@@ -22,6 +24,7 @@ export interface PrdSessionSummary {
   activeWorkers: number;
   totalCost: number;
   startedAt?: number;
+  completedAt?: number;
 }
 
 // ============================================================================
@@ -88,6 +91,88 @@ export async function retryStory(
   return invoke("retry_prd_story", { sessionId, storyId });
 }
 
+/**
+ * Add a story to a live PRD session (dependencies are re-validated)
+ */
+export async function addStory(
+  sessionId: string,
+  story: Story
+): Promise<PrdSession> {
+  return invoke<PrdSession>("prd_add_story", { sessionId, story });
+}
+
+/**
+ * Replace a story that no worker is currently running
+ */
+export async function updateStory(
+  sessionId: string,
+  story: Story
+): Promise<PrdSession> {
+  return invoke<PrdSession>("prd_update_story", { sessionId, story });
+}
+
+/**
+ * Remove a story that no worker is running and no other story depends on
+ */
+export async function removeStory(
+  sessionId: string,
+  storyId: string
+): Promise<PrdSession> {
+  return invoke<PrdSession>("prd_remove_story", { sessionId, storyId });
+}
+
+/**
+ * Skip a story; stories depending on it start without it
+ */
+export async function skipStory(
+  sessionId: string,
+  storyId: string,
+  reason?: string
+): Promise<PrdSession> {
+  return invoke<PrdSession>("prd_skip_story", { sessionId, storyId, reason });
+}
+
+/**
+ * Hold a story back from workers (cancelling the worker running it)
+ */
+export async function pauseStory(
+  sessionId: string,
+  storyId: string
+): Promise<PrdSession> {
+  return invoke<PrdSession>("prd_pause_story", { sessionId, storyId });
+}
+
+/**
+ * Let a paused story be picked up again
+ */
+export async function resumeStory(
+  sessionId: string,
+  storyId: string
+): Promise<PrdSession> {
+  return invoke<PrdSession>("prd_resume_story", { sessionId, storyId });
+}
+
+/**
+ * Re-run a story's acceptance criteria now, ignoring cached results
+ */
+export async function verifyStory(
+  sessionId: string,
+  storyId: string
+): Promise<CriterionStatus[]> {
+  return invoke<CriterionStatus[]>("prd_verify_story", { sessionId, storyId });
+}
+
+/**
+ * Ask the session's leader agent something (it starts on first use).
+ * Resolves with its reply.
+ */
+export async function sendPrdMessage(
+  sessionId: string,
+  message: string
+): Promise<string> {
+  return invoke<string>("prd_send_message", { sessionId, message });
+}
+
 /**
  * Get progress for a specific story
  */
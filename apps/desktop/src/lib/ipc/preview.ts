@@ -0,0 +1,82 @@
+import { invoke } from "@tauri-apps/api/core";
+import type { UnlistenFn } from "@tauri-apps/api/event";
+
+import { listenVersioned, type PreviewRequestEvent } from "./events";
+
+// ============================================================================
+// Preview Types
+// ============================================================================
+
+export interface PreviewInfo {
+  id: string;
+  session_id: string | null;
+  /** The dev server, e.g. "http://localhost:5173" */
+  target: string;
+  /** What the preview panel loads, e.g. "http://127.0.0.1:51234" */
+  url: string;
+  started_at: number;
+}
+
+export interface ProxyRequest {
+  method: string;
+  /** Path and query */
+  path: string;
+  /** 502 when the dev server couldn't be reached */
+  status: number;
+  duration_ms: number;
+  at: number;
+  websocket: boolean;
+}
+
+export interface RouteSummary {
+  method: string;
+  path: string;
+  count: number;
+  last_status: number;
+  last_at: number;
+}
+
+// ============================================================================
+// Preview Commands
+// ============================================================================
+
+/**
+ * Front a local dev server (e.g. one from `listDetectedServers`) with a
+ * logging proxy and return the URL the preview panel should load
+ */
+export async function startPreview(
+  target: string,
+  sessionId?: string,
+): Promise<PreviewInfo> {
+  return invoke<PreviewInfo>("start_preview", { target, sessionId });
+}
+
+export async function stopPreview(id: string): Promise<boolean> {
+  return invoke<boolean>("stop_preview", { id });
+}
+
+export async function listPreviews(
+  sessionId?: string,
+): Promise<PreviewInfo[]> {
+  return invoke<PreviewInfo[]>("list_previews", { sessionId });
+}
+
+/**
+ * Requests made through a preview, oldest first
+ */
+export async function getPreviewRequests(id: string): Promise<ProxyRequest[]> {
+  return invoke<ProxyRequest[]>("get_preview_requests", { id });
+}
+
+/**
+ * The routes exercised through a preview, most requested first
+ */
+export async function getPreviewRoutes(id: string): Promise<RouteSummary[]> {
+  return invoke<RouteSummary[]>("get_preview_routes", { id });
+}
+
+export function onPreviewRequest(
+  callback: (event: PreviewRequestEvent) => void,
+): Promise<UnlistenFn> {
+  return listenVersioned<PreviewRequestEvent>("preview-request", callback);
+}
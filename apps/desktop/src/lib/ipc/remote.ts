@@ -0,0 +1,129 @@
+import { invoke } from "@tauri-apps/api/core";
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
+
+import type {
+  AgentType,
+  OrchestratorSession,
+} from "@/stores/orchestrator-store";
+import {
+  type ConnectionState,
+  listenVersioned,
+  type RemoteConnectionStatus,
+  type RemoteHostStatusEvent,
+} from "./events";
+import {
+  type AgentConfig,
+  type RawOrchestratorSession,
+  transformSession,
+} from "./orchestrator";
+
+export type { RemoteConnectionStatus, RemoteHostStatusEvent };
+
+// ============================================================================
+// Remote Host Types
+// ============================================================================
+
+export type RemoteConnectionState = ConnectionState;
+
+export interface RemoteResyncEvent {
+  host_id: string;
+  /** "gap" (events were dropped while disconnected) or "lagged" */
+  reason: string;
+}
+
+// ============================================================================
+// Remote Host Commands
+// ============================================================================
+
+/**
+ * Attach to a remote host's event stream. Worker events from the host are
+ * re-emitted under their usual names, so existing worker listeners work,
+ * with the host's id added as `host_id`.
+ */
+export async function connectRemoteHost(
+  hostId: string,
+): Promise<RemoteConnectionStatus> {
+  return invoke<RemoteConnectionStatus>("connect_remote_host", { hostId });
+}
+
+export async function disconnectRemoteHost(hostId: string): Promise<boolean> {
+  return invoke<boolean>("disconnect_remote_host", { hostId });
+}
+
+export async function listRemoteConnections(): Promise<
+  RemoteConnectionStatus[]
+> {
+  return invoke<RemoteConnectionStatus[]>("list_remote_connections");
+}
+
+export async function listRemoteAgents(hostId: string): Promise<AgentConfig[]> {
+  return invoke<AgentConfig[]>("list_remote_agents", { hostId });
+}
+
+export async function listRemoteSessions(
+  hostId: string,
+): Promise<OrchestratorSession[]> {
+  const sessions = await invoke<RawOrchestratorSession[]>(
+    "list_remote_sessions",
+    { hostId },
+  );
+  return sessions.map((s) => transformSession(s));
+}
+
+/**
+ * Start a session on a remote host (cwd is a path on that host)
+ */
+export async function createRemoteSession(
+  hostId: string,
+  prompt: string,
+  agentId: string,
+  cwd: string,
+  modelId?: string,
+): Promise<OrchestratorSession> {
+  const response = await invoke<{ session: RawOrchestratorSession }>(
+    "create_remote_session",
+    { hostId, prompt, agentId, modelId, cwd },
+  );
+  return transformSession(response.session, agentId as AgentType, cwd);
+}
+
+export async function sendRemotePrompt(
+  hostId: string,
+  sessionId: string,
+  prompt: string,
+): Promise<void> {
+  return invoke<void>("send_remote_prompt", { hostId, sessionId, prompt });
+}
+
+export async function respondToRemotePermission(
+  hostId: string,
+  workerId: string,
+  optionId: string,
+): Promise<void> {
+  return invoke<void>("respond_to_remote_permission", {
+    hostId,
+    workerId,
+    optionId,
+  });
+}
+
+// ============================================================================
+// Remote Host Events
+// ============================================================================
+
+export async function onRemoteHostStatus(
+  callback: (status: RemoteHostStatusEvent) => void,
+): Promise<UnlistenFn> {
+  return listenVersioned<RemoteHostStatusEvent>("remote-host-status", callback);
+}
+
+/**
+ * Fired when events from a host were lost; refetch its sessions
+ */
+export async function onRemoteResync(
+  callback: (event: RemoteResyncEvent) => void,
+): Promise<UnlistenFn> {
+  return listen<RemoteResyncEvent>("remote-resync", (event) => {
+    callback(event.payload);
+  });
+}
@@ -0,0 +1,82 @@
+import { invoke } from "@tauri-apps/api/core";
+import type { UnlistenFn } from "@tauri-apps/api/event";
+
+import {
+  type JobRunStatus,
+  listenVersioned,
+  type ScheduledJob,
+  type ScheduledJobUpdateEvent,
+} from "./events";
+
+export type { JobRunStatus, ScheduledJob, ScheduledJobUpdateEvent };
+
+// ============================================================================
+// Scheduler Types
+// ============================================================================
+
+export interface CreateScheduledJobOptions {
+  name: string;
+  schedule: string;
+  prompt: string;
+  agentId: string;
+  modelId?: string;
+  cwd: string;
+}
+
+// ============================================================================
+// Scheduler Commands
+// ============================================================================
+
+export async function createScheduledJob(
+  options: CreateScheduledJobOptions,
+): Promise<ScheduledJob> {
+  return invoke<ScheduledJob>("create_scheduled_job", {
+    name: options.name,
+    schedule: options.schedule,
+    prompt: options.prompt,
+    agentId: options.agentId,
+    modelId: options.modelId ?? null,
+    cwd: options.cwd,
+  });
+}
+
+export async function listScheduledJobs(): Promise<ScheduledJob[]> {
+  return invoke<ScheduledJob[]>("list_scheduled_jobs");
+}
+
+export async function setScheduledJobEnabled(
+  jobId: string,
+  enabled: boolean,
+): Promise<ScheduledJob> {
+  return invoke<ScheduledJob>("set_scheduled_job_enabled", {
+    jobId,
+    enabled,
+  });
+}
+
+export async function deleteScheduledJob(jobId: string): Promise<void> {
+  return invoke("delete_scheduled_job", { jobId });
+}
+
+/**
+ * Run a job immediately; its session is returned in last_session_id
+ */
+export async function runJobNow(jobId: string): Promise<ScheduledJob> {
+  return invoke<ScheduledJob>("run_job_now", { jobId });
+}
+
+// ============================================================================
+// Scheduler Events
+// ============================================================================
+
+/**
+ * Fired when a job starts a run or its run finishes
+ */
+export async function onScheduledJobUpdate(
+  callback: (job: ScheduledJobUpdateEvent) => void,
+): Promise<UnlistenFn> {
+  return listenVersioned<ScheduledJobUpdateEvent>(
+    "scheduled-job-update",
+    callback,
+  );
+}
@@ -0,0 +1,22 @@
+import { invoke } from "@tauri-apps/api/core";
+
+// ============================================================================
+// Secrets Commands
+// ============================================================================
+
+/**
+ * Store a secret (e.g. ANTHROPIC_API_KEY) in the OS keychain. Agents that
+ * need a variable of the same name receive it when spawned.
+ */
+export async function setSecret(name: string, value: string): Promise<void> {
+  return invoke<void>("set_secret", { name, value });
+}
+
+export async function deleteSecret(name: string): Promise<void> {
+  return invoke<void>("delete_secret", { name });
+}
+
+/** Names of the stored secrets; values are never sent to the frontend */
+export async function listSecretNames(): Promise<string[]> {
+  return invoke<string[]>("list_secret_names");
+}
@@ -0,0 +1,416 @@
+import { invoke } from "@tauri-apps/api/core";
+
+// ============================================================================
+// Settings Types
+// ============================================================================
+
+export interface AppSettings {
+  /** Store agent thought/reasoning chunks in persisted sessions */
+  persist_thinking: boolean;
+  /** Include thought chunks when exporting sessions */
+  export_thinking: boolean;
+  /** Record agent-created terminals to asciicast files */
+  record_agent_terminals: boolean;
+  /** Desktop notification toggles */
+  notifications: NotificationSettings;
+  /** Outbound webhooks */
+  integrations: IntegrationSettings;
+  /** Embedded HTTP/WebSocket API server */
+  api_server: ApiServerSettings;
+  /** Other machines running the API server whose sessions can be attached */
+  remote_hosts: RemoteHost[];
+  /** Stop idle agent processes after this many minutes (0 = never) */
+  worker_idle_timeout_minutes: number;
+  /** Archive finished sessions after this many hours (0 = never) */
+  archive_after_hours: number;
+  /** Most agent processes alive at once (0 = unlimited) */
+  max_running_agents: number;
+  /** Queue new sessions past the limit instead of rejecting them */
+  queue_sessions_when_full: boolean;
+  /**
+   * Prices overriding the bundled pricing table, keyed by model id,
+   * model alias, or agent id
+   */
+  model_pricing: Record<string, ModelPrice>;
+  /**
+   * Session modes in which the client itself refuses file writes, edit
+   * permissions and every command but a few read-only tools
+   */
+  read_only_modes: string[];
+  /** Project .env files */
+  dotenv: DotenvSettings;
+  /** Masking secrets in worker output and persisted transcripts */
+  redaction: RedactionSettings;
+  /**
+   * Rules that flag or block agent output, tool calls and commands
+   * (projects add their own in `.crafter-code/guards.json`)
+   */
+  guard_rules: GuardRule[];
+  /** Kill project hook scripts (`.crafter/hooks/`) after this many seconds */
+  hook_timeout_secs: number;
+  /** Run Claude Code hooks (PreToolUse, PostToolUse, Stop) for every agent */
+  claude_hooks: boolean;
+  /** Prompting idle workers when inbox messages arrive */
+  inbox_push: InboxPushSettings;
+  /** On quit, wait this long for agent processes to exit */
+  shutdown_timeout_secs: number;
+  /** CPU and memory limits for agent processes and their terminals */
+  resource_limits: ResourceLimitSettings;
+  /**
+   * Cutting agent-created terminals off from the network (projects can
+   * opt in, but not out, in `.crafter-code/network.json`)
+   */
+  network_isolation: NetworkIsolationSettings;
+  /**
+   * Project instruction files (AGENTS.md, CLAUDE.md, ...) for agents that
+   * don't read them themselves
+   */
+  context_files: ContextFileSettings;
+  /**
+   * Show the tray icon with running workers and quick actions (applies on
+   * restart)
+   */
+  tray_icon: boolean;
+  /** App-wide spend caps across all sessions */
+  budget: BudgetSettings;
+  /** Which editor files open in */
+  editor: EditorSettings;
+  /** Workspace snapshots of projects outside git */
+  snapshots: SnapshotSettings;
+  /** Language agents answer in (sessions can choose their own) */
+  language: LanguageSettings;
+  /** Rotate a worker's protocol trace file past this many MB (0 = never) */
+  protocol_trace_max_mb: number;
+  /** Resending prompts that fail with rate limit, overload or network errors */
+  prompt_retry: PromptRetrySettings;
+  /** Warn about tool calls running longer than this many seconds (0 = never) */
+  slow_tool_threshold_secs: number;
+  /**
+   * Streamed agent text is sent to the window this many times a second
+   * (0 = every chunk as it arrives)
+   */
+  delta_flush_hz: number;
+  /**
+   * Preprocessors follow-up prompts go through, in order (sessions can
+   * choose their own)
+   */
+  prompt_preprocessors: PromptPreprocessor[];
+  /** Values for `{{name}}` placeholders in prompts */
+  prompt_templates: Record<string, string>;
+  /** OpenTelemetry export (builds with the `otel` feature only) */
+  telemetry: TelemetrySettings;
+  /**
+   * Keep every session's tasks on its project's board in
+   * `<project>/.crafter/tasks/`, so later sessions continue the backlog
+   */
+  project_task_board: boolean;
+  /** Turn the plans agents publish into tasks on the session's board */
+  import_agent_plans: boolean;
+  /** Turning recorded speech into prompt text */
+  transcription: TranscriptionSettings;
+  /** Per-session directories agents keep temporary files in */
+  scratch: ScratchSettings;
+  /** Keep the computer from idle-sleeping while workers or PRD sessions run */
+  prevent_sleep: boolean;
+  /**
+   * Encrypt saved and archived sessions, drafts, scheduled jobs, trust
+   * decisions, snapshots and task boards with a key kept in the OS keychain
+   * (files already on disk are converted when this changes, reported by
+   * `session-encryption-changed`)
+   */
+  encrypt_sessions: boolean;
+}
+
+/**
+ * Exporting metrics and spans to an OpenTelemetry collector over OTLP/HTTP
+ * (applies on restart)
+ */
+export interface TelemetrySettings {
+  enabled: boolean;
+  /** Collector base URL; `/v1/metrics` and `/v1/traces` are appended */
+  endpoint: string;
+  /** Sent with every export, e.g. an API key for a hosted collector */
+  headers: Record<string, string>;
+  export_interval_secs: number;
+  /** `service.name` resource attribute, to tell machines apart */
+  service_name: string;
+}
+
+/**
+ * Where recorded speech is transcribed: an OpenAI-compatible
+ * `/audio/transcriptions` endpoint, or whisper.cpp on this machine (builds
+ * with the `whisper` feature only)
+ */
+export type TranscriptionBackend = "api" | "local";
+
+export interface TranscriptionSettings {
+  backend: TranscriptionBackend;
+  api_url: string;
+  /** Environment variable or stored secret holding the API key */
+  api_key_name: string;
+  model: string;
+  /** Language spoken, as an ISO-639-1 code ("" = detect) */
+  language: string;
+  /** ggml model file for the local backend */
+  local_model_path: string;
+}
+
+/**
+ * Scratch directories (`~/.crafter-code/scratch/<session>/`) offered to
+ * agents for temporary artifacts, deleted with their session
+ */
+export interface ScratchSettings {
+  enabled: boolean;
+  /**
+   * Oldest files are removed once a session's directory grows past this
+   * (0 = no limit)
+   */
+  max_mb: number;
+}
+
+/**
+ * A step follow-up prompts go through before reaching the agent:
+ * `/command` expansion, `{{name}}` templates, `@path` file contents, active
+ * skill prompts, secret masking
+ */
+export type PromptPreprocessor =
+  | "slash_commands"
+  | "templates"
+  | "mentions"
+  | "skills"
+  | "redaction";
+
+/**
+ * Resending a prompt that failed with a transient error. The delay doubles
+ * with each retry; the worker fails once `max_attempts` retries have failed.
+ */
+export interface PromptRetrySettings {
+  /** Retries per prompt (0 = fail on the first error) */
+  max_attempts: number;
+  base_delay_secs: number;
+  max_delay_secs: number;
+}
+
+/**
+ * Running agent-created terminal commands without network access: in a
+ * network namespace on Linux, under a sandbox-exec profile on macOS, and
+ * elsewhere by refusing known network commands
+ */
+export interface NetworkIsolationSettings {
+  /** Isolate terminals in every session */
+  enabled: boolean;
+  /**
+   * Session modes whose terminals are always isolated (e.g. "plan" for
+   * offline reviews)
+   */
+  modes: string[];
+  /**
+   * Refuse every command when no sandbox is available, instead of only
+   * known network commands
+   */
+  require_sandbox: boolean;
+}
+
+/**
+ * CPU and memory limits for agent processes and the terminals they create,
+ * each measured with its child processes. Soft limits emit a warning;
+ * hard limits kill the processes and fail the worker. A limit of 0 is off.
+ */
+export interface ResourceLimitSettings {
+  enabled: boolean;
+  /** Resident memory, in MB */
+  memory_soft_mb: number;
+  memory_hard_mb: number;
+  /** CPU usage, in percent of one core (200 = two cores busy) */
+  cpu_soft_percent: number;
+  cpu_hard_percent: number;
+  /**
+   * CPU usage must stay over a limit this long before it counts, so short
+   * bursts like a build don't trip it
+   */
+  cpu_window_secs: number;
+}
+
+/**
+ * Prompting idle workers with new inbox messages, so agents react to
+ * coordination traffic without polling `swarm inbox read`
+ */
+export interface InboxPushSettings {
+  enabled: boolean;
+  /** Least time between two pushes to the same worker */
+  min_interval_secs: number;
+  /**
+   * Most pushes to the same worker per hour, so workers messaging each
+   * other can't loop forever
+   */
+  max_per_hour: number;
+}
+
+/**
+ * A regex or script check on agent activity. Scripts get the text on stdin
+ * and trigger the rule by exiting non-zero.
+ */
+export interface GuardRule {
+  name: string;
+  target: "output" | "tool_call" | "command";
+  pattern?: string | null;
+  script?: string | null;
+  /** flag: notify only; block: refuse; pause: wait for user approval */
+  action?: "flag" | "block" | "pause";
+  enabled?: boolean;
+}
+
+/**
+ * Secret redaction (projects can add patterns or opt out in
+ * `.crafter-code/redaction.json`)
+ */
+export interface RedactionSettings {
+  enabled: boolean;
+  /** Extra regex patterns to redact, on top of the built-in token formats */
+  patterns: string[];
+}
+
+/**
+ * App-wide spend caps, checked against the usage ledger. Crossing one
+ * pauses every running worker and blocks new sessions until the period
+ * rolls over or the cap is raised.
+ */
+export interface BudgetSettings {
+  /** Most USD spent per local calendar day (0 = no cap) */
+  daily_usd: number;
+  /** Most USD spent per local calendar month (0 = no cap) */
+  monthly_usd: number;
+}
+
+/**
+ * The editor `openInEditor` uses. Ids are "vscode", "zed", "jetbrains" and
+ * "env" ($VISUAL / $EDITOR).
+ */
+export interface EditorSettings {
+  /** Editor for every project ("" = the first one installed) */
+  preferred: string;
+  /** Editor per project root, overriding `preferred` */
+  projects: Record<string, string>;
+}
+
+/** Workspace snapshots (content-addressed copies of a project's files) */
+export interface SnapshotSettings {
+  /** Snapshot non-git projects when a session starts */
+  on_session_start: boolean;
+  /** Larger files are left out (0 = no limit) */
+  max_file_bytes: number;
+  /** Files past this total are left out (0 = no limit) */
+  max_total_bytes: number;
+  /** Snapshots kept per project (0 = all) */
+  keep: number;
+  /** Globs skipped besides .gitignore patterns and dependency directories */
+  ignore: string[];
+}
+
+/** The language agents are asked to answer in */
+export interface LanguageSettings {
+  /** A code ("es", "pt-BR") or a name ("" = the agent's default) */
+  respond_in: string;
+  /**
+   * Also translate the client's own text in coordination prompts and
+   * `swarm help`
+   */
+  localize_prompts: boolean;
+}
+
+/**
+ * Passing project context files (AGENTS.md, CLAUDE.md, GEMINI.md,
+ * .cursorrules) to agents that don't load them natively
+ */
+export interface ContextFileSettings {
+  /** Add the files to the coordination prompt */
+  inject: boolean;
+  /** Bytes of each file included (0 = all) */
+  max_bytes: number;
+}
+
+/** Loading project .env files into agents and their terminals */
+export interface DotenvSettings {
+  /** Inject project .env variables into agent processes and terminals */
+  inject: boolean;
+  /** Files read from the project root, later files overriding earlier ones */
+  files: string[];
+  /** Variables whose values are masked, as `*` wildcard patterns */
+  mask: string[];
+  /** Variables never masked, even when matching `mask` */
+  unmask: string[];
+}
+
+/** USD per million tokens */
+export interface ModelPrice {
+  input_per_million: number;
+  output_per_million: number;
+}
+
+export interface NotificationSettings {
+  /** Master switch */
+  enabled: boolean;
+  /** Every finished prompt (sessions can opt in via notifyOnCompletion) */
+  prompt_completed: boolean;
+  permission_required: boolean;
+  worker_failed: boolean;
+  budget_exceeded: boolean;
+  prd_story_completed: boolean;
+  /** Also notify while the app window has focus */
+  when_focused: boolean;
+}
+
+export type WebhookFormat = "json" | "slack";
+
+export interface WebhookConfig {
+  name: string;
+  url: string;
+  format?: WebhookFormat;
+  /** Event names to send (e.g. "worker_failed"); empty sends all events */
+  events?: string[];
+  enabled?: boolean;
+}
+
+export interface IntegrationSettings {
+  webhooks: WebhookConfig[];
+  /** Send a cost_threshold event when a session's cost crosses this amount */
+  cost_threshold_usd: number | null;
+}
+
+export interface ApiServerSettings {
+  /** Start the server when the app launches */
+  enabled: boolean;
+  host: string;
+  port: number;
+  /** Bearer token required by every endpoint (generated on first start) */
+  token: string;
+}
+
+export interface RemoteHost {
+  id: string;
+  name: string;
+  /** Base URL, e.g. "http://buildbox.local:7420" */
+  url: string;
+  /** The host's API token */
+  token: string;
+}
+
+// ============================================================================
+// Settings Commands
+// ============================================================================
+
+/**
+ * Get the current application settings
+ */
+export async function getAppSettings(): Promise<AppSettings> {
+  return invoke<AppSettings>("get_app_settings");
+}
+
+/**
+ * Replace the application settings
+ */
+export async function updateAppSettings(
+  settings: AppSettings,
+): Promise<AppSettings> {
+  return invoke<AppSettings>("update_app_settings", { settings });
+}
@@ -1,4 +1,7 @@
 import { invoke } from "@tauri-apps/api/core";
+import type { UnlistenFn } from "@tauri-apps/api/event";
+import { listenVersioned, type SlashCommandResultEvent } from "./events";
+import type { PromptPreprocessor } from "./settings";
 
 // ==================== SKILLS ====================
 
@@ -210,6 +213,8 @@ export interface CommandInfo {
   description: string;
   inputHint: string | null;
   category: "swarm" | "code" | "git" | "analysis" | "utility";
+  /** Runs in the client (`/cost`, `/compact`, `/retry`, `/export`) instead of prompting the agent */
+  backend: boolean;
 }
 
 /**
@@ -244,6 +249,29 @@ export async function processSlashCommand(
   return invoke<string | null>("process_slash_command", { sessionId, input });
 }
 
+/**
+ * Run a backend slash command and get its output; `null` when the input
+ * isn't one and should be sent as a prompt. `sendAcpPrompt` runs them too.
+ */
+export async function runSlashCommand(
+  sessionId: string,
+  input: string,
+): Promise<string | null> {
+  return invoke<string | null>("run_slash_command", { sessionId, input });
+}
+
+/**
+ * Listen for the output of backend slash commands
+ */
+export function onSlashCommandResult(
+  callback: (event: SlashCommandResultEvent) => void,
+): Promise<UnlistenFn> {
+  return listenVersioned<SlashCommandResultEvent>(
+    "slash-command-result",
+    callback,
+  );
+}
+
 /**
  * Check if input is a slash command
  */
@@ -275,6 +303,49 @@ export async function processUserInput(
   return invoke<ProcessedInput>("process_user_input", { sessionId, input });
 }
 
+/**
+ * The preprocessors a session's follow-up prompts go through, in order
+ */
+export interface SessionPipeline {
+  steps: PromptPreprocessor[];
+  /** Whether the session overrides the `prompt_preprocessors` setting */
+  custom: boolean;
+}
+
+/**
+ * Choose (and order) a session's prompt preprocessors; `null` goes back to
+ * the `prompt_preprocessors` setting
+ */
+export async function setPromptPreprocessors(
+  sessionId: string,
+  steps: PromptPreprocessor[] | null,
+): Promise<SessionPipeline> {
+  return invoke<SessionPipeline>("set_prompt_preprocessors", {
+    sessionId,
+    steps,
+  });
+}
+
+/**
+ * Get the preprocessors a session's prompts go through
+ */
+export async function getPromptPreprocessors(
+  sessionId: string,
+): Promise<SessionPipeline> {
+  return invoke<SessionPipeline>("get_prompt_preprocessors", { sessionId });
+}
+
+/**
+ * Show what a prompt becomes after the session's preprocessors
+ */
+export async function previewPrompt(
+  sessionId: string,
+  cwd: string,
+  prompt: string,
+): Promise<string> {
+  return invoke<string>("preview_prompt", { sessionId, cwd, prompt });
+}
+
 /**
  * Cleanup session-specific skill and command data
  */
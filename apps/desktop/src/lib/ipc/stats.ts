@@ -0,0 +1,109 @@
+import { invoke } from "@tauri-apps/api/core";
+import type { UnlistenFn } from "@tauri-apps/api/event";
+
+import {
+  type BudgetPeriod,
+  type GlobalBudgetExceededEvent,
+  listenVersioned,
+} from "./events";
+
+// ============================================================================
+// Stats Types
+// ============================================================================
+
+/** Last 24 hours, 7 days, 30 days, or everything */
+export type StatsRange = "day" | "week" | "month" | "all";
+
+export type StatsGroupBy = "day" | "week" | "month" | "agent" | "model";
+
+export interface UsageTotals {
+  cost_usd: number;
+  input_tokens: number;
+  output_tokens: number;
+  /** Distinct sessions with usage */
+  sessions: number;
+  /** Completed prompts */
+  prompts: number;
+}
+
+export interface UsageGroup extends UsageTotals {
+  /** "2025-03-10", "2025-W11", "2025-03", agent id or model */
+  key: string;
+}
+
+export interface UsageStats {
+  range: StatsRange;
+  group_by: StatsGroupBy;
+  totals: UsageTotals;
+  groups: UsageGroup[];
+}
+
+/** A spend cap that was reached */
+export interface BudgetBreach {
+  period: BudgetPeriod;
+  spent_usd: number;
+  cap_usd: number;
+}
+
+/** Spend in the current local day and month against the caps (0 = none) */
+export interface GlobalSpend {
+  /** "2025-03-10" */
+  day: string;
+  day_usd: number;
+  /** "2025-03" */
+  month: string;
+  month_usd: number;
+  daily_cap_usd: number;
+  monthly_cap_usd: number;
+  /** Set while new sessions are blocked */
+  exceeded: BudgetBreach | null;
+}
+
+// ============================================================================
+// Stats Commands
+// ============================================================================
+
+export async function getUsageStats(
+  range: StatsRange,
+  groupBy: StatsGroupBy,
+): Promise<UsageStats> {
+  return invoke<UsageStats>("get_usage_stats", { range, groupBy });
+}
+
+/** CSV text with one row per group */
+export async function exportUsageCsv(
+  range: StatsRange,
+  groupBy: StatsGroupBy,
+): Promise<string> {
+  return invoke<string>("export_usage_csv", { range, groupBy });
+}
+
+export async function getGlobalSpend(): Promise<GlobalSpend> {
+  return invoke<GlobalSpend>("get_global_spend");
+}
+
+/** A spend cap was crossed; running workers have been paused */
+export function onGlobalBudgetExceeded(
+  callback: (event: GlobalBudgetExceededEvent) => void,
+): Promise<UnlistenFn> {
+  return listenVersioned<GlobalBudgetExceededEvent>(
+    "global-budget-exceeded",
+    callback,
+  );
+}
+
+// ============================================================================
+// Telemetry
+// ============================================================================
+
+export interface TelemetryStatus {
+  /** Built with the `otel` feature */
+  available: boolean;
+  /** Exporting right now */
+  active: boolean;
+  endpoint: string;
+}
+
+export async function getTelemetryStatus(): Promise<TelemetryStatus> {
+  return invoke<TelemetryStatus>("get_telemetry_status");
+}
@@ -1,4 +1,11 @@
-import { invoke } from "@tauri-apps/api/core";
+import type { UnlistenFn } from "@tauri-apps/api/event";
+import { invoke } from "./errors";
+import {
+  listenVersioned,
+  type PlanImportedEvent,
+  type TaskBoardChangedEvent,
+  type TaskColumns,
+} from "./events";
 
 // ============================================================================
 // Task System Types
@@ -31,6 +38,34 @@ export interface TaskUpdate {
   metadata?: Record<string, unknown>;
 }
 
+export type TaskSort = "created" | "updated" | "subject" | "status";
+
+export interface TaskQuery {
+  /** Only these statuses (deleted tasks are never returned) */
+  status?: TaskStatus[];
+  /** Only tasks owned by this worker; "" for unassigned tasks */
+  assignee?: string;
+  /** Case-insensitive match on id, subject or description */
+  text?: string;
+  sort?: TaskSort;
+  descending?: boolean;
+  limit?: number;
+}
+
+export interface TaskQueryResult {
+  tasks: Task[];
+  /** Matches before `limit` */
+  total: number;
+  /** Counts of the tasks matching `assignee` and `text`, whatever their status */
+  columns: TaskColumns;
+}
+
+export interface TaskBoardStatus {
+  /** Directory of the mounted project board, if any */
+  board: string | null;
+  tasks: number;
+}
+
 // ============================================================================
 // Task Commands
 // ============================================================================
@@ -95,6 +130,80 @@ export async function taskDelete(sessionId: string, taskId: string): Promise<Tas
   return invoke<Task>("task_delete", { sessionId, taskId });
 }
 
+/**
+ * Filter, sort and page the session's tasks
+ */
+export async function taskQuery(
+  sessionId: string,
+  query?: TaskQuery,
+): Promise<TaskQueryResult> {
+  return invoke<TaskQueryResult>("task_query", { sessionId, query });
+}
+
+/**
+ * How many tasks are in each Kanban column
+ */
+export async function taskColumnCounts(sessionId: string): Promise<TaskColumns> {
+  return invoke<TaskColumns>("task_column_counts", { sessionId });
+}
+
+/**
+ * Listen for changes to any session's tasks (from the UI or from workers)
+ */
+export function onTaskBoardChanged(
+  callback: (event: TaskBoardChangedEvent) => void,
+): Promise<UnlistenFn> {
+  return listenVersioned<TaskBoardChangedEvent>("task-board-changed", callback);
+}
+
+/**
+ * Listen for agent plans copied to the task board
+ */
+export function onPlanImported(
+  callback: (event: PlanImportedEvent) => void,
+): Promise<UnlistenFn> {
+  return listenVersioned<PlanImportedEvent>("plan-imported", callback);
+}
+
+/**
+ * Turn the session's agent plans into tasks (overrides `import_agent_plans`)
+ */
+export async function setPlanImport(sessionId: string, enabled: boolean): Promise<void> {
+  return invoke<void>("set_plan_import", { sessionId, enabled });
+}
+
+/**
+ * Whether the session's agent plans become tasks
+ */
+export async function getPlanImport(sessionId: string): Promise<boolean> {
+  return invoke<boolean>("get_plan_import", { sessionId });
+}
+
+/**
+ * Keep the session's tasks on the project board in
+ * `<projectPath>/.crafter/tasks/`; tasks it already has are added to it
+ */
+export async function taskBoardMount(
+  sessionId: string,
+  projectPath: string,
+): Promise<TaskBoardStatus> {
+  return invoke<TaskBoardStatus>("task_board_mount", { sessionId, projectPath });
+}
+
+/**
+ * Stop sharing the session's tasks with its project board
+ */
+export async function taskBoardUnmount(sessionId: string): Promise<TaskBoardStatus> {
+  return invoke<TaskBoardStatus>("task_board_unmount", { sessionId });
+}
+
+/**
+ * Which project board (if any) the session's tasks live on
+ */
+export async function taskBoardStatus(sessionId: string): Promise<TaskBoardStatus> {
+  return invoke<TaskBoardStatus>("task_board_status", { sessionId });
+}
+
 // ============================================================================
 // Helper Functions
 // ============================================================================
@@ -0,0 +1,17 @@
+import { invoke } from "@tauri-apps/api/core";
+
+// ============================================================================
+// Voice Input
+// ============================================================================
+
+/**
+ * Transcribe a recording (base64, e.g. from MediaRecorder) with the backend
+ * chosen in settings and return the spoken text for the prompt box. The
+ * local whisper backend takes `audio/wav` only.
+ */
+export async function transcribeAudio(
+  data: string,
+  mimeType: string,
+): Promise<string> {
+  return invoke<string>("transcribe_audio", { data, mimeType });
+}
@@ -38,7 +38,11 @@ export type StoryStatus =
   | "in_progress"
   | "completed"
   | "failed"
-  | "blocked";
+  | "blocked"
+  /** Left out on purpose; counts as done for its dependents */
+  | "skipped"
+  /** Held back from workers until resumed */
+  | "paused";
 
 export type ModelId = "opus" | "sonnet" | "haiku";
 
@@ -66,6 +70,10 @@ export interface StoryProgress {
   startedAt?: number;
   completedAt?: number;
   error?: string;
+  /** Why the story was skipped */
+  skipReason?: string;
+  /** Feedback from failed iterations, fed into the next prompt */
+  guardrails: string[];
 }
 
 // PRD types
@@ -77,6 +85,8 @@ export interface PrdConstraints {
     master?: ModelId;
     default?: ModelId;
   };
+  /** Acceptance criteria of a story verified at once (0 = one by one) */
+  criteria_concurrency?: number;
 }
 
 export interface Prd {
@@ -166,6 +176,7 @@ export const DEFAULT_CONSTRAINTS: PrdConstraints = {
     master: "opus",
     default: "sonnet",
   },
+  criteria_concurrency: 4,
 };
 
 // Model costs per 1M tokens (approximate)
@@ -34,7 +34,8 @@ export type SessionStatus =
   | "running"
   | "completed"
   | "failed"
-  | "cancelled";
+  | "cancelled"
+  | "hibernated";
 
 // Session modes from claude-code-acp
 export type SessionMode = "default" | "acceptEdits" | "plan" | "dontAsk" | "bypassPermissions";